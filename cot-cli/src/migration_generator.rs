@@ -607,9 +607,15 @@ impl MigrationGenerator {
         model_source
             .attrs
             .push(syn::parse_quote! {#[derive(::core::fmt::Debug)]});
-        model_source
-            .attrs
-            .push(syn::parse_quote! {#[::cot::db::model(model_type = "migration")]});
+        if model.model.soft_delete {
+            model_source.attrs.push(
+                syn::parse_quote! {#[::cot::db::model(model_type = "migration", soft_delete)]},
+            );
+        } else {
+            model_source
+                .attrs
+                .push(syn::parse_quote! {#[::cot::db::model(model_type = "migration")]});
+        }
         quote! {
             #model_source
         }
@@ -1028,6 +1034,9 @@ impl ModelInSource {
             .map_err(|e| anyhow::anyhow!("cannot parse model: {e}"))?;
         let mut model = opts.as_model(args, symbol_resolver)?;
         model.table_name = format!("{}__{}", app_name.to_snake_case(), model.table_name);
+        if let Some(field) = model.soft_delete_field() {
+            model.fields.push(field);
+        }
 
         Ok(Self {
             model_item: item,
@@ -1889,6 +1898,8 @@ mod tests {
                     unique: false,
                     foreign_key: None,
                 }],
+                soft_delete: false,
+                version_field: None,
             },
         }
     }
@@ -1938,6 +1949,8 @@ mod tests {
                         foreign_key: None,
                     },
                 ],
+                soft_delete: false,
+                version_field: None,
             },
         }
     }
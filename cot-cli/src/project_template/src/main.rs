@@ -4,7 +4,7 @@ use cot::auth::db::DatabaseUserApp;
 use cot::cli::CliMetadata;
 use cot::db::migrations::SyncDynMigration;
 use cot::html::Html;
-use cot::middleware::{AuthMiddleware, LiveReloadMiddleware, SessionMiddleware};
+use cot::middleware::{AuthMiddleware, CsrfMiddleware, LiveReloadMiddleware, SessionMiddleware};
 use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler, RootHandlerBuilder};
 use cot::request::extractors::StaticFiles;
 use cot::router::{Route, Router};
@@ -66,6 +66,7 @@ impl Project for {{ project_struct_name }} {
         handler
             .middleware(StaticFilesMiddleware::from_context(context))
             .middleware(AuthMiddleware::new())
+            .middleware(CsrfMiddleware::new())
             .middleware(SessionMiddleware::from_context(context))
             .middleware(LiveReloadMiddleware::from_context(context))
             .build()
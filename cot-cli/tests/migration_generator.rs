@@ -223,6 +223,44 @@ fn create_model_keywords_source() {
     );
 }
 
+#[test]
+fn create_model_soft_delete() {
+    let generator = test_generator();
+    let src = include_str!("migration_generator/soft_delete.rs");
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+
+    let migration = generator
+        .generate_migrations_as_source_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    assert!(
+        migration
+            .content
+            .contains(r#"::cot::db::Identifier::new("deleted_at")"#)
+    );
+    assert!(
+        migration
+            .content
+            .contains(r#"#[::cot::db::model(model_type = "migration", soft_delete)]"#)
+    );
+
+    let source_files = vec![SourceFile::parse(PathBuf::from("main.rs"), src).unwrap()];
+    let migration = generator
+        .generate_migrations_as_generated_from_files(source_files)
+        .unwrap()
+        .unwrap();
+
+    let (table_name, fields) = unwrap_create_model(&migration.operations[0]);
+    assert_eq!(table_name, "cot__customer");
+    assert_eq!(fields.len(), 3);
+
+    let field = &fields[2];
+    assert_eq!(field.column_name, "deleted_at");
+    assert!(!field.primary_key);
+    assert!(!field.auto_value);
+}
+
 /// Test that the migration generator can generate a "create model" migration
 /// for a given model which compiles successfully.
 #[test]
@@ -0,0 +1,11 @@
+use cot::db::{model, Auto};
+
+#[derive(Debug)]
+#[model(soft_delete)]
+struct Customer {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+}
+
+fn main() {}
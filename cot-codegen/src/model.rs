@@ -11,8 +11,13 @@ pub struct ModelArgs {
     #[darling(default)]
     pub model_type: ModelType,
     pub table_name: Option<String>,
+    pub soft_delete: darling::util::Flag,
 }
 
+/// The name of the column added to the models annotated with
+/// `#[model(soft_delete)]`.
+pub const SOFT_DELETE_COLUMN_NAME: &str = "deleted_at";
+
 #[expect(clippy::module_name_repetitions)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default, FromMeta)]
 pub enum ModelType {
@@ -98,6 +103,22 @@ impl ModelOpts {
 
         let primary_key_field = self.get_primary_key_field(&fields)?;
 
+        let version_field = self.get_version_field(&fields)?;
+
+        let soft_delete = args.soft_delete.is_present();
+        if soft_delete
+            && let Some(field) = fields
+                .iter()
+                .find(|field| field.column_name == SOFT_DELETE_COLUMN_NAME)
+        {
+            return Err(syn::Error::new(
+                field.name.span(),
+                format!(
+                    "soft-deletable models cannot have a field named `{SOFT_DELETE_COLUMN_NAME}`"
+                ),
+            ));
+        }
+
         let ty = {
             let mut ty = syn::Type::Path(syn::TypePath {
                 qself: None,
@@ -116,9 +137,39 @@ impl ModelOpts {
             table_name,
             pk_field: primary_key_field.clone(),
             fields,
+            soft_delete,
+            version_field,
         })
     }
 
+    fn get_version_field(&self, fields: &[Field]) -> Result<Option<Field>, syn::Error> {
+        let version_fields: Vec<_> = self
+            .fields()
+            .into_iter()
+            .zip(fields)
+            .filter(|(field_opts, _)| field_opts.version.is_present())
+            .map(|(_, field)| field)
+            .collect();
+        if version_fields.len() > 1 {
+            return Err(syn::Error::new(
+                version_fields[1].name.span(),
+                "only one field can be annotated with the `#[model(version)]` attribute",
+            ));
+        }
+
+        let Some(version_field) = version_fields.first() else {
+            return Ok(None);
+        };
+        if version_field.primary_key || version_field.auto_value {
+            return Err(syn::Error::new(
+                version_field.name.span(),
+                "the version field cannot be a primary key or an auto field",
+            ));
+        }
+
+        Ok(Some((*version_field).clone()))
+    }
+
     fn get_primary_key_field<'a>(&self, fields: &'a [Field]) -> Result<&'a Field, syn::Error> {
         let pks: Vec<_> = fields.iter().filter(|field| field.primary_key).collect();
         if pks.is_empty() {
@@ -146,6 +197,7 @@ pub struct FieldOpts {
     pub ty: syn::Type,
     pub primary_key: darling::util::Flag,
     pub unique: darling::util::Flag,
+    pub version: darling::util::Flag,
 }
 
 impl FieldOpts {
@@ -244,6 +296,11 @@ pub struct Model {
     pub table_name: String,
     pub pk_field: Field,
     pub fields: Vec<Field>,
+    /// Whether the model has been annotated with `#[model(soft_delete)]`.
+    pub soft_delete: bool,
+    /// The field annotated with `#[model(version)]`, used for optimistic
+    /// locking.
+    pub version_field: Option<Field>,
 }
 
 impl Model {
@@ -251,6 +308,28 @@ impl Model {
     pub fn field_count(&self) -> usize {
         self.fields.len()
     }
+
+    /// Returns the column added to the database table of a soft-deletable
+    /// model, or [`None`] if the model is not soft-deletable.
+    ///
+    /// The column does not correspond to any field in the struct; it stores
+    /// the time at which the row has been deleted.
+    #[must_use]
+    pub fn soft_delete_field(&self) -> Option<Field> {
+        self.soft_delete.then(|| Field {
+            name: syn::Ident::new(SOFT_DELETE_COLUMN_NAME, proc_macro2::Span::call_site()),
+            column_name: SOFT_DELETE_COLUMN_NAME.to_string(),
+            ty: syn::parse_quote!(
+                ::core::option::Option<
+                    ::cot::__private::chrono::DateTime<::cot::__private::chrono::FixedOffset>,
+                >
+            ),
+            auto_value: false,
+            primary_key: false,
+            foreign_key: None,
+            unique: false,
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -326,6 +405,7 @@ mod tests {
         let args: ModelArgs = ModelArgs::default();
         assert_eq!(args.model_type, ModelType::Application);
         assert!(args.table_name.is_none());
+        assert!(!args.soft_delete.is_present());
     }
 
     #[test]
@@ -404,6 +484,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn model_opts_as_model_soft_delete() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(soft_delete)]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                name: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let model = opts.as_model(&args, &SymbolResolver::new(vec![])).unwrap();
+        assert!(model.soft_delete);
+        assert_eq!(model.fields.len(), 2);
+
+        let field = model.soft_delete_field().unwrap();
+        assert_eq!(field.name.to_string(), "deleted_at");
+        assert_eq!(field.column_name, "deleted_at");
+        assert!(!field.primary_key);
+    }
+
+    #[test]
+    fn model_opts_as_model_soft_delete_conflicting_field() {
+        let input: syn::DeriveInput = parse_quote! {
+            #[model(soft_delete)]
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                deleted_at: String,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let args = ModelArgs::from_meta(&input.attrs.first().unwrap().meta).unwrap();
+        let err = opts
+            .as_model(&args, &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "soft-deletable models cannot have a field named `deleted_at`"
+        );
+    }
+
+    #[test]
+    fn model_opts_as_model_version() {
+        let input: syn::DeriveInput = parse_quote! {
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                #[model(version)]
+                version: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let model = opts
+            .as_model(&ModelArgs::default(), &SymbolResolver::new(vec![]))
+            .unwrap();
+        assert_eq!(model.fields.len(), 2);
+        assert_eq!(model.version_field.unwrap().column_name, "version");
+    }
+
+    #[test]
+    fn model_opts_as_model_multiple_versions() {
+        let input: syn::DeriveInput = parse_quote! {
+            struct TestModel {
+                #[model(primary_key)]
+                id: i32,
+                #[model(version)]
+                version: i64,
+                #[model(version)]
+                version_2: i64,
+            }
+        };
+        let opts = ModelOpts::new_from_derive_input(&input).unwrap();
+        let err = opts
+            .as_model(&ModelArgs::default(), &SymbolResolver::new(vec![]))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "only one field can be annotated with the `#[model(version)]` attribute"
+        );
+    }
+
     #[test]
     fn model_opts_as_model_pk_attr() {
         let input: syn::DeriveInput = parse_quote! {
@@ -516,6 +679,7 @@ mod tests {
             ty: parse_quote! { MyContainer<std::string::String> },
             primary_key: darling::util::Flag::default(),
             unique: darling::util::Flag::default(),
+            version: darling::util::Flag::default(),
         };
 
         assert!(opts.find_type("my_crate::MyContainer", &resolver).is_some());
@@ -1,8 +1,8 @@
 use cot_codegen::model::FieldOpts;
-use darling::FromDeriveInput;
+use darling::{FromDeriveInput, FromMeta};
 use heck::ToSnakeCase;
 use proc_macro2::TokenStream;
-use quote::{ToTokens, quote};
+use quote::{ToTokens, format_ident, quote};
 
 use crate::cot_ident;
 
@@ -18,15 +18,56 @@ pub(super) fn impl_admin_model_for_struct(ast: &syn::DeriveInput) -> TokenStream
     for field in opts.fields() {
         builder.push_field(field);
     }
+    if let Err(err) = builder.set_field_lists(&opts) {
+        return err.write_errors();
+    }
 
     quote!(#builder)
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(forward_attrs(allow, doc, cfg), supports(struct_named))]
+#[darling(
+    attributes(admin),
+    forward_attrs(allow, doc, cfg),
+    supports(struct_named)
+)]
 struct AdminModelOpts {
     ident: syn::Ident,
     data: darling::ast::Data<darling::util::Ignored, FieldOpts>,
+    #[darling(default)]
+    list_display: FieldList,
+    #[darling(default)]
+    search_fields: FieldList,
+    #[darling(default)]
+    list_filter: FieldList,
+    #[darling(default)]
+    readonly: FieldList,
+    #[darling(default)]
+    exclude: FieldList,
+}
+
+/// A list of field names, such as `[name, created_at]`.
+#[derive(Debug, Clone, Default)]
+struct FieldList(Vec<syn::Ident>);
+
+impl FromMeta for FieldList {
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Array(array) => array
+                .elems
+                .iter()
+                .map(|elem| match elem {
+                    syn::Expr::Path(path) => path.path.get_ident().cloned().ok_or_else(|| {
+                        darling::Error::custom("expected a field name").with_span(elem)
+                    }),
+                    _ => Err(darling::Error::custom("expected a field name").with_span(elem)),
+                })
+                .collect::<darling::Result<_>>()
+                .map(Self),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
 }
 
 impl AdminModelOpts {
@@ -42,6 +83,12 @@ impl AdminModelOpts {
         AdminModelDeriveBuilder {
             name: self.ident.clone(),
             primary_key: None,
+            fields: Vec::new(),
+            list_display: Vec::new(),
+            search_fields: Vec::new(),
+            list_filter: Vec::new(),
+            readonly: Vec::new(),
+            exclude: Vec::new(),
         }
     }
 }
@@ -50,6 +97,12 @@ impl AdminModelOpts {
 struct AdminModelDeriveBuilder {
     name: syn::Ident,
     primary_key: Option<FieldOpts>,
+    fields: Vec<(syn::Ident, syn::Type)>,
+    list_display: Vec<syn::Ident>,
+    search_fields: Vec<syn::Ident>,
+    list_filter: Vec<syn::Ident>,
+    readonly: Vec<syn::Ident>,
+    exclude: Vec<syn::Ident>,
 }
 
 impl ToTokens for AdminModelDeriveBuilder {
@@ -71,6 +124,330 @@ impl AdminModelDeriveBuilder {
         if field.primary_key.is_present() {
             self.primary_key = Some(field.clone());
         }
+        if let Some(ident) = &field.ident {
+            self.fields.push((ident.clone(), field.ty.clone()));
+        }
+    }
+
+    fn set_field_lists(&mut self, opts: &AdminModelOpts) -> darling::Result<()> {
+        let mut errors = darling::Error::accumulator();
+        let list_display = errors.handle(self.check_fields(&opts.list_display));
+        let search_fields = errors.handle(self.check_fields(&opts.search_fields));
+        let list_filter = errors.handle(self.check_fields(&opts.list_filter));
+        let readonly = errors.handle(self.check_fields(&opts.readonly));
+        let exclude = errors.handle(self.check_fields(&opts.exclude));
+        errors.finish()?;
+
+        self.list_display = list_display.unwrap_or_default();
+        self.search_fields = search_fields.unwrap_or_default();
+        self.list_filter = list_filter.unwrap_or_default();
+        self.readonly = readonly.unwrap_or_default();
+        self.exclude = exclude.unwrap_or_default();
+        Ok(())
+    }
+
+    fn field_type(&self, ident: &syn::Ident) -> &syn::Type {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == ident)
+            .map(|(_, ty)| ty)
+            .expect("field existence should have been checked")
+    }
+
+    /// Checks that all the fields in the list exist in the struct.
+    fn check_fields(&self, field_list: &FieldList) -> darling::Result<Vec<syn::Ident>> {
+        let mut errors = darling::Error::accumulator();
+        for ident in &field_list.0 {
+            if !self.fields.iter().any(|(field, _)| field == ident) {
+                errors.push(
+                    darling::Error::custom(format!("no field named `{ident}` in `{}`", self.name))
+                        .with_span(ident),
+                );
+            }
+        }
+        errors.finish_with(field_list.0.clone())
+    }
+
+    fn build_list_display(&self) -> (TokenStream, TokenStream) {
+        let crate_ident = cot_ident();
+
+        let columns = self.list_display.iter().map(|ident| {
+            let name = ident.to_string();
+            let label = field_label(&name);
+            quote!(#crate_ident::admin::ListColumn::new(#name, #label).sortable())
+        });
+        let values = self
+            .list_display
+            .iter()
+            .map(|ident| quote!(#crate_ident::admin::ToListValue::to_list_value(&self.#ident)));
+
+        let list_columns = quote! {
+            fn list_columns() -> ::std::vec::Vec<#crate_ident::admin::ListColumn> {
+                ::std::vec![#(#columns),*]
+            }
+        };
+        let list_values = quote! {
+            fn list_values(&self) -> ::std::vec::Vec<#crate_ident::admin::ListValue> {
+                ::std::vec![#(#values),*]
+            }
+        };
+
+        (list_columns, list_values)
+    }
+
+    fn build_list_filters(&self) -> (TokenStream, TokenStream) {
+        let crate_ident = cot_ident();
+
+        let search_fields = self.search_fields.iter().map(|ident| {
+            let name = ident.to_string();
+            quote!(::std::borrow::Cow::Borrowed(#name))
+        });
+        let filters = self.list_filter.iter().map(|ident| {
+            let name = ident.to_string();
+            let label = field_label(&name);
+            let ty = self.field_type(ident);
+            quote! {
+                #crate_ident::admin::ListFilter::new(
+                    #name,
+                    #label,
+                    <#ty as #crate_ident::admin::ListFilterField>::list_filter_kind(),
+                )
+            }
+        });
+
+        let search_fields = quote! {
+            fn search_fields() -> ::std::vec::Vec<::std::borrow::Cow<'static, str>> {
+                ::std::vec![#(#search_fields),*]
+            }
+        };
+        let list_filters = quote! {
+            fn list_filters() -> ::std::vec::Vec<#crate_ident::admin::ListFilter> {
+                ::std::vec![#(#filters),*]
+            }
+        };
+
+        (search_fields, list_filters)
+    }
+
+    fn build_form_fields(&self) -> (TokenStream, TokenStream) {
+        let field_names = |fields: &[syn::Ident]| {
+            let names = fields.iter().map(|ident| {
+                let name = ident.to_string();
+                quote!(::std::borrow::Cow::Borrowed(#name))
+            });
+            quote!(::std::vec![#(#names),*])
+        };
+        let readonly = field_names(&self.readonly);
+        let exclude = field_names(&self.exclude);
+
+        let readonly_fields = quote! {
+            fn readonly_fields() -> ::std::vec::Vec<::std::borrow::Cow<'static, str>> {
+                #readonly
+            }
+        };
+        let excluded_fields = quote! {
+            fn excluded_fields() -> ::std::vec::Vec<::std::borrow::Cow<'static, str>> {
+                #exclude
+            }
+        };
+
+        (readonly_fields, excluded_fields)
+    }
+
+    /// Builds the function creating the object from the form submitted in
+    /// the request, with the values of the non-editable fields taken from the
+    /// existing object.
+    ///
+    /// The values are moved from the object loaded from the database instead
+    /// of being passed through the form, since not every field type can be
+    /// converted to a form value and back (for instance, a password hash is
+    /// never shown in a form).
+    fn build_object_from_request_fn(&self, pk_name: &syn::Ident) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+
+        let idents: Vec<_> = self.fields.iter().map(|(ident, _)| ident).collect();
+        let previous_idents: Vec<_> = idents
+            .iter()
+            .map(|ident| format_ident!("previous_{}", ident))
+            .collect();
+        let value_idents: Vec<_> = idents
+            .iter()
+            .map(|ident| format_ident!("value_{}", ident))
+            .collect();
+        let nones = self
+            .fields
+            .iter()
+            .map(|(_, ty)| quote!(::core::option::Option::<#ty>::None));
+        let values = self.fields.iter().zip(&previous_idents).zip(&value_idents).map(
+            |(((ident, ty), previous_ident), value_ident)| {
+                quote! {
+                    let #value_ident = match #previous_ident {
+                        ::core::option::Option::Some(value) if non_editable_fields.contains(&stringify!(#ident)) => {
+                            // the value is only set in the context to be shown if the form is invalid
+                            #crate_ident::form::FormField::set_value(
+                                &mut context.#ident,
+                                #crate_ident::form::FormFieldValue::new_text(
+                                    #crate_ident::form::AsFormField::to_field_value(&value),
+                                ),
+                            )
+                            .await
+                            .expect("Setting value from text should never fail");
+                            ::std::result::Result::Ok(value)
+                        }
+                        _ => <#ty as #crate_ident::form::AsFormField>::clean_value(&context.#ident).map_err(|error| {
+                            context.add_error(#crate_ident::form::FormErrorTarget::Field(stringify!(#ident)), error);
+                        }),
+                    };
+                }
+            },
+        );
+
+        quote! {
+            async fn object_from_request(
+                request: &mut #crate_ident::request::Request,
+                object_id: ::core::option::Option<&str>,
+                non_editable_fields: &[&str],
+            ) -> #crate_ident::Result<#crate_ident::form::FormResult<#name>> {
+                use #crate_ident::form::{Form, FormContext};
+                use #crate_ident::request::RequestExt;
+
+                let previous = match object_id {
+                    ::core::option::Option::Some(object_id) if !non_editable_fields.is_empty() => {
+                        let id = parse_id::<#name>(object_id)?;
+                        let previous = #crate_ident::db::query!(#name, $#pk_name == id)
+                            .get(request.context().database())
+                            .await?
+                            .ok_or_else(|| {
+                                #crate_ident::error::NotFound::with_message(::std::format!(
+                                    "Object with ID `{object_id}` not found in model `{model_name}`",
+                                    model_name = stringify!(#name)
+                                ))
+                            })?;
+                        ::core::option::Option::Some(previous)
+                    }
+                    _ => ::core::option::Option::None,
+                };
+                let (#(#previous_idents,)*) = match previous {
+                    ::core::option::Option::Some(previous) => {
+                        (#(::core::option::Option::Some(previous.#idents),)*)
+                    }
+                    ::core::option::Option::None => (#(#nones,)*),
+                };
+
+                let mut context =
+                    <#name as #crate_ident::form::Form>::build_context_ignoring(request, non_editable_fields).await?;
+                #(#values)*
+
+                if context.has_errors() {
+                    ::std::result::Result::Ok(#crate_ident::form::FormResult::ValidationError(context))
+                } else {
+                    ::std::result::Result::Ok(#crate_ident::form::FormResult::Ok(#name {
+                        #(#idents: #value_idents.expect("Errors should have been returned by now"),)*
+                    }))
+                }
+            }
+        }
+    }
+
+    /// Builds the function converting an admin
+    /// [`ListQuery`](cot::admin::ListQuery) to a database query on the model.
+    fn build_list_query_fn(&self, pk_name: &syn::Ident) -> TokenStream {
+        let crate_ident = cot_ident();
+        let name = &self.name;
+        let field = |ident: &syn::Ident| quote!(<#name as #crate_ident::db::Model>::Fields::#ident.as_expr());
+
+        let search = if self.search_fields.is_empty() {
+            quote!()
+        } else {
+            let search_exprs = self.search_fields.iter().map(|ident| {
+                let field = field(ident);
+                quote!(#crate_ident::db::query::Expr::icontains(#field, search))
+            });
+            quote! {
+                if let ::core::option::Option::Some(search) = list_query.search() {
+                    filters.extend(
+                        [#(#search_exprs),*]
+                            .into_iter()
+                            .reduce(#crate_ident::db::query::Expr::or),
+                    );
+                }
+            }
+        };
+
+        let list_filter = if self.list_filter.is_empty() {
+            quote!()
+        } else {
+            let arms = self.list_filter.iter().map(|ident| {
+                let name = ident.to_string();
+                let ty = self.field_type(ident);
+                let field = field(ident);
+                quote! {
+                    #name => <#ty as #crate_ident::admin::ListFilterField>::list_filter_expr(#field, value),
+                }
+            });
+            quote! {
+                for (name, value) in list_query.filters() {
+                    filters.extend(match name {
+                        #(#arms)*
+                        _ => ::core::option::Option::None,
+                    });
+                }
+            }
+        };
+
+        let ordering = if self.list_display.is_empty() {
+            quote!()
+        } else {
+            let arms = self.list_display.iter().map(|ident| {
+                let name = ident.to_string();
+                let field = field(ident);
+                quote!(#name => ::core::option::Option::Some(#field),)
+            });
+            quote! {
+                if let ::core::option::Option::Some(ordering) = list_query.ordering() {
+                    let order = if ordering.is_descending() {
+                        #crate_ident::db::query::Order::Desc
+                    } else {
+                        #crate_ident::db::query::Order::Asc
+                    };
+                    let expr = match ordering.field() {
+                        #(#arms)*
+                        _ => ::core::option::Option::None,
+                    };
+                    if let ::core::option::Option::Some(expr) = expr {
+                        query.order_by(expr, order);
+                    }
+                }
+            }
+        };
+        let pk_field = field(pk_name);
+        let filters_mut = if self.search_fields.is_empty() && self.list_filter.is_empty() {
+            quote!()
+        } else {
+            quote!(mut)
+        };
+
+        quote! {
+            fn build_list_query(
+                list_query: &#crate_ident::admin::ListQuery,
+            ) -> #crate_ident::db::query::Query<#name> {
+                let #filters_mut filters: ::std::vec::Vec<#crate_ident::db::query::Expr> = ::std::vec::Vec::new();
+                #search
+                #list_filter
+
+                let mut query = <#name as #crate_ident::db::Model>::objects();
+                if let ::core::option::Option::Some(filter) =
+                    filters.into_iter().reduce(#crate_ident::db::query::Expr::and)
+                {
+                    query.filter(filter);
+                }
+                #ordering
+                // sort by the primary key last, so that the pagination is stable
+                query.order_by(#pk_field, #crate_ident::db::query::Order::Asc);
+                query
+            }
+        }
     }
 
     #[expect(clippy::too_many_lines)] // it's mainly the AdminModel impl
@@ -93,27 +470,32 @@ impl AdminModelDeriveBuilder {
             )
             .into_compile_error();
         };
+        let (list_columns, list_values) = self.build_list_display();
+        let (search_fields, list_filters) = self.build_list_filters();
+        let list_query_fn = self.build_list_query_fn(&pk_name);
+        let (readonly_fields, excluded_fields) = self.build_form_fields();
+        let object_from_request_fn = self.build_object_from_request_fn(&pk_name);
 
         quote! {
             #[#crate_ident::__private::async_trait]
             impl #crate_ident::admin::AdminModel for #name {
                 async fn get_total_object_counts(
                     request: &#crate_ident::request::Request,
+                    query: &#crate_ident::admin::ListQuery,
                 ) -> #crate_ident::Result<u64> {
-                    use #crate_ident::db::Model;
                     use #crate_ident::request::RequestExt;
 
-                    Ok(Self::objects().count(request.context().database()).await?)
+                    Ok(build_list_query(query).count(request.context().database()).await?)
                 }
 
                 async fn get_objects(
                     request: &#crate_ident::request::Request,
+                    query: &#crate_ident::admin::ListQuery,
                     pagination: #crate_ident::admin::Pagination,
                 ) -> #crate_ident::Result<::std::vec::Vec<Self>> {
-                    use #crate_ident::db::Model;
                     use #crate_ident::request::RequestExt;
 
-                    Ok(Self::objects().limit(pagination.limit()).offset(pagination.offset()).all(request.context().database()).await?)
+                    Ok(build_list_query(query).limit(pagination.limit()).offset(pagination.offset()).all(request.context().database()).await?)
                 }
 
                 async fn get_object_by_id(
@@ -130,6 +512,32 @@ impl AdminModelDeriveBuilder {
                     Ok(#crate_ident::db::query!(Self, $#pk_name == id).get(request.context().database()).await?)
                 }
 
+                async fn get_objects_by_ids(
+                    request: &#crate_ident::request::Request,
+                    ids: &[::std::string::String],
+                ) -> #crate_ident::Result<::std::vec::Vec<Self>>
+                where
+                    Self: Sized,
+                {
+                    use #crate_ident::request::RequestExt;
+
+                    let ids = ids
+                        .iter()
+                        .map(|id| parse_id::<Self>(id).map(#crate_ident::db::query::Expr::value))
+                        .collect::<#crate_ident::Result<::std::vec::Vec<_>>>()?;
+                    if ids.is_empty() {
+                        return Ok(::std::vec::Vec::new());
+                    }
+
+                    Ok(<Self as #crate_ident::db::Model>::objects()
+                        .filter(#crate_ident::db::query::Expr::is_in(
+                            <Self as #crate_ident::db::Model>::Fields::#pk_name.as_expr(),
+                            ids,
+                        ))
+                        .all(request.context().database())
+                        .await?)
+                }
+
                 fn name() -> &'static str {
                     stringify!(#name)
                 }
@@ -138,6 +546,10 @@ impl AdminModelDeriveBuilder {
                     #name_slug
                 }
 
+                fn table_name() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some(<Self as #crate_ident::db::Model>::TABLE_NAME.as_str())
+                }
+
                 fn id(&self) -> ::std::string::String {
                     use ::std::string::ToString;
 
@@ -148,6 +560,18 @@ impl AdminModelDeriveBuilder {
                     ::std::format!("{self}")
                 }
 
+                #list_columns
+
+                #list_values
+
+                #search_fields
+
+                #list_filters
+
+                #readonly_fields
+
+                #excluded_fields
+
                 fn form_context() -> ::std::boxed::Box<dyn #crate_ident::form::FormContext>
                 where
                     Self: Sized,
@@ -159,10 +583,29 @@ impl AdminModelDeriveBuilder {
                     ::std::boxed::Box::new(<Self as #crate_ident::form::Form>::to_context(self).await)
                 }
 
+                async fn form_context_from_request(
+                    request: &mut #crate_ident::request::Request,
+                    object_id: ::core::option::Option<&str>,
+                    non_editable_fields: &[&str],
+                ) -> #crate_ident::Result<::std::boxed::Box<dyn #crate_ident::form::FormContext + ::core::marker::Send>>
+                where
+                    Self: Sized,
+                {
+                    match object_from_request(request, object_id, non_editable_fields).await? {
+                        #crate_ident::form::FormResult::Ok(object) => ::std::result::Result::Ok(
+                            ::std::boxed::Box::new(<Self as #crate_ident::form::Form>::to_context(&object).await),
+                        ),
+                        #crate_ident::form::FormResult::ValidationError(context) => {
+                            ::std::result::Result::Ok(::std::boxed::Box::new(context))
+                        }
+                    }
+                }
+
                 async fn save_from_request(
                     request: &mut #crate_ident::request::Request,
                     object_id: ::core::option::Option<&str>,
-                ) -> #crate_ident::Result<::core::option::Option<::std::boxed::Box<dyn #crate_ident::form::FormContext>>>
+                    non_editable_fields: &[&str],
+                ) -> #crate_ident::Result<#crate_ident::admin::SaveResult>
                 where
                     Self: Sized,
                 {
@@ -170,21 +613,40 @@ impl AdminModelDeriveBuilder {
                     use #crate_ident::request::RequestExt;
                     use #crate_ident::db::Model;
 
-                    let form_result = <Self as #crate_ident::form::Form>::from_request(request).await?;
-                    match form_result {
+                    match object_from_request(request, object_id, non_editable_fields).await? {
                         #crate_ident::form::FormResult::Ok(mut object_from_form) => {
                             if let Some(object_id) = object_id {
                                 let id = parse_id::<Self>(object_id)?;
 
                                 object_from_form.set_primary_key(id);
-                                object_from_form.update(request.context().database()).await?;
+                                match object_from_form.update(request.context().database()).await {
+                                    ::std::result::Result::Ok(()) => {}
+                                    ::std::result::Result::Err(#crate_ident::db::DatabaseError::StaleObject { .. }) => {
+                                        use #crate_ident::form::FormContext;
+
+                                        let mut context = object_from_form.to_context().await;
+                                        context.add_error(
+                                            #crate_ident::form::FormErrorTarget::Form,
+                                            #crate_ident::form::FormFieldValidationError::from_static(
+                                                "This object has been modified by someone else since you opened it. \
+                                                Reload the page to see the latest version.",
+                                            ),
+                                        );
+                                        return ::std::result::Result::Ok(
+                                            #crate_ident::admin::SaveResult::Invalid(::std::boxed::Box::new(context)),
+                                        );
+                                    }
+                                    ::std::result::Result::Err(error) => return ::std::result::Result::Err(error.into()),
+                                }
                             } else {
                                 object_from_form.insert(request.context().database()).await?;
                             }
-                            ::std::result::Result::Ok(None)
+                            ::std::result::Result::Ok(
+                                #crate_ident::admin::SaveResult::Saved(::std::boxed::Box::new(object_from_form)),
+                            )
                         }
                         #crate_ident::form::FormResult::ValidationError(context) => ::std::result::Result::Ok(
-                            ::core::option::Option::Some(::std::boxed::Box::new(context)),
+                            #crate_ident::admin::SaveResult::Invalid(::std::boxed::Box::new(context)),
                         ),
                     }
                 }
@@ -204,8 +666,106 @@ impl AdminModelDeriveBuilder {
 
                     Ok(())
                 }
+
+                async fn remove_by_ids(
+                    request: &mut #crate_ident::request::Request,
+                    object_ids: &[::std::string::String],
+                ) -> #crate_ident::Result<()>
+                where
+                    Self: Sized,
+                {
+                    use #crate_ident::request::RequestExt;
+
+                    let ids = object_ids
+                        .iter()
+                        .map(|object_id| {
+                            parse_id::<Self>(object_id).map(#crate_ident::db::query::Expr::value)
+                        })
+                        .collect::<#crate_ident::Result<::std::vec::Vec<_>>>()?;
+                    if ids.is_empty() {
+                        return Ok(());
+                    }
+
+                    <Self as #crate_ident::db::Model>::objects()
+                        .filter(#crate_ident::db::query::Expr::is_in(
+                            <Self as #crate_ident::db::Model>::Fields::#pk_name.as_expr(),
+                            ids,
+                        ))
+                        .delete(request.context().database())
+                        .await?;
+
+                    Ok(())
+                }
+
+                fn is_restorable() -> bool {
+                    <Self as #crate_ident::db::Model>::SOFT_DELETE_COLUMN.is_some()
+                }
+
+                fn version_field() -> ::core::option::Option<&'static str> {
+                    <Self as #crate_ident::db::Model>::VERSION_COLUMN.map(|column| column.as_str())
+                }
+
+                async fn get_removed_objects(
+                    request: &#crate_ident::request::Request,
+                    pagination: #crate_ident::admin::Pagination,
+                ) -> #crate_ident::Result<::std::vec::Vec<Self>> {
+                    use #crate_ident::db::Model;
+                    use #crate_ident::request::RequestExt;
+
+                    if !<Self as #crate_ident::admin::AdminModel>::is_restorable() {
+                        return Ok(::std::vec::Vec::new());
+                    }
+
+                    Ok(Self::objects().only_deleted().limit(pagination.limit()).offset(pagination.offset()).all(request.context().database()).await?)
+                }
+
+                async fn get_total_removed_object_counts(
+                    request: &#crate_ident::request::Request,
+                ) -> #crate_ident::Result<u64> {
+                    use #crate_ident::db::Model;
+                    use #crate_ident::request::RequestExt;
+
+                    if !<Self as #crate_ident::admin::AdminModel>::is_restorable() {
+                        return Ok(0);
+                    }
+
+                    Ok(Self::objects().only_deleted().count(request.context().database()).await?)
+                }
+
+                async fn restore_by_id(
+                    request: &mut #crate_ident::request::Request,
+                    object_id: &str,
+                ) -> #crate_ident::Result<()>
+                where
+                    Self: Sized,
+                {
+                    use #crate_ident::request::RequestExt;
+
+                    let id = parse_id::<Self>(object_id)?;
+
+                    let restored = <Self as #crate_ident::admin::AdminModel>::is_restorable()
+                        && #crate_ident::db::query!(Self, $#pk_name == id)
+                            .restore(request.context().database())
+                            .await?
+                            .rows_affected()
+                            .0
+                            > 0;
+                    if restored {
+                        Ok(())
+                    } else {
+                        Err(#crate_ident::error::NotFound::with_message(::std::format!(
+                            "Removed object with ID `{object_id}` not found in model `{model_name}`",
+                            model_name = stringify!(#name)
+                        ))
+                        .into())
+                    }
+                }
             }
 
+            #list_query_fn
+
+            #object_from_request_fn
+
             fn parse_id<T>(id: &str) -> #crate_ident::Result<<T as #crate_ident::db::Model>::PrimaryKey>
             where
                 T: #crate_ident::db::Model,
@@ -224,3 +784,14 @@ impl AdminModelDeriveBuilder {
         }
     }
 }
+
+/// Converts a field name to a label shown in the admin panel, such as
+/// `created_at` to `Created at`.
+fn field_label(field_name: &str) -> String {
+    let label = field_name.trim_start_matches("r#").replace('_', " ");
+    let mut chars = label.trim().chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -136,7 +136,7 @@ impl FormDeriveBuilder {
             };
             quote!(#field_ident: {
                 let options = #crate_ident::form::FormFieldOptions {
-                    id: stringify!(#field_ident).to_owned(),
+                    id: ::std::format!("{prefix}{}", stringify!(#field_ident)),
                     name: #name.to_owned(),
                     required: true,
                 };
@@ -204,28 +204,41 @@ impl FormDeriveBuilder {
                 async fn from_request(
                     request: &mut #crate_ident::request::Request
                 ) -> ::core::result::Result<#crate_ident::form::FormResult<Self>, #crate_ident::form::FormError> {
-                    let mut context = <Self as #crate_ident::form::Form>::build_context(request).await?;
+                    let context = <Self as #crate_ident::form::Form>::build_context(request).await?;
 
+                    Ok(<Self as #crate_ident::form::Form>::from_context(context))
+                }
+
+                fn from_context(
+                    mut context: Self::Context
+                ) -> #crate_ident::form::FormResult<Self> {
                     use #crate_ident::form::FormContext;
                     #( #fields_as_from_context_vars; )*
 
                     if context.has_errors() {
-                        Ok(#crate_ident::form::FormResult::ValidationError(context))
+                        #crate_ident::form::FormResult::ValidationError(context)
                     } else {
-                        Ok(#crate_ident::form::FormResult::Ok(Self {
+                        #crate_ident::form::FormResult::Ok(Self {
                             #( #fields_as_from_context, )*
-                        }))
+                        })
                     }
                 }
 
                 async fn to_context(
                     &self
+                ) -> Self::Context {
+                    <Self as #crate_ident::form::Form>::to_context_with_prefix(self, "").await
+                }
+
+                async fn to_context_with_prefix(
+                    &self,
+                    prefix: &str,
                 ) -> Self::Context {
                     use #crate_ident::form::FormContext;
                     use #crate_ident::form::AsFormField;
                     use #crate_ident::form::FormField;
 
-                    let mut context = <Self as #crate_ident::form::Form>::Context::new();
+                    let mut context = <Self as #crate_ident::form::Form>::Context::with_prefix(prefix);
                     #( #fields_as_to_context; )*
                     context
                 }
@@ -271,6 +284,7 @@ impl FormDeriveBuilder {
         quote! {
             #[derive(::core::fmt::Debug)]
             pub struct #context_struct_name {
+                __prefix: ::std::string::String,
                 __errors: #context_struct_errors_name,
                 #( #fields_as_struct_fields, )*
             }
@@ -278,8 +292,9 @@ impl FormDeriveBuilder {
             #[#crate_ident::__private::async_trait]
             #[automatically_derived]
             impl #crate_ident::form::FormContext for #context_struct_name {
-                fn new() -> Self {
+                fn with_prefix(prefix: &str) -> Self {
                     Self {
+                        __prefix: prefix.to_owned(),
                         __errors: ::core::default::Default::default(),
                         #( #fields_as_struct_fields_new, )*
                     }
@@ -298,6 +313,7 @@ impl FormDeriveBuilder {
                     field_id: &str,
                     value: #crate_ident::form::FormFieldValue<'_>,
                 ) -> ::core::result::Result<(), #crate_ident::form::FormFieldValidationError> {
+                    let field_id = field_id.strip_prefix(self.__prefix.as_str()).unwrap_or(field_id);
                     match field_id {
                         #( #fields_as_context_from_request, )*
                         _ => {}
@@ -311,6 +327,7 @@ impl FormDeriveBuilder {
                 ) -> &[#crate_ident::form::FormFieldValidationError] {
                     match target {
                         #crate_ident::form::FormErrorTarget::Field(field_id) => {
+                            let field_id = field_id.strip_prefix(self.__prefix.as_str()).unwrap_or(field_id);
                             match field_id {
                                 #( #fields_as_errors_for, )*
                                 _ => {
@@ -330,6 +347,7 @@ impl FormDeriveBuilder {
                 ) -> &mut Vec<#crate_ident::form::FormFieldValidationError> {
                     match target {
                         #crate_ident::form::FormErrorTarget::Field(field_id) => {
+                            let field_id = field_id.strip_prefix(self.__prefix.as_str()).unwrap_or(field_id);
                             match field_id {
                                 #( #fields_as_errors_for_mut, )*
                                 _ => {
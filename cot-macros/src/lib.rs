@@ -37,7 +37,7 @@ pub fn derive_form(input: TokenStream) -> TokenStream {
     token_stream.into()
 }
 
-#[proc_macro_derive(AdminModel)]
+#[proc_macro_derive(AdminModel, attributes(admin))]
 pub fn derive_admin_model(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let token_stream = impl_admin_model_for_struct(&ast);
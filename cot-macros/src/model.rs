@@ -1,4 +1,4 @@
-use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType};
+use cot_codegen::model::{Field, Model, ModelArgs, ModelOpts, ModelType, SOFT_DELETE_COLUMN_NAME};
 use cot_codegen::symbol_resolver::{SymbolResolver, VisibleSymbol, VisibleSymbolKind};
 use darling::FromMeta;
 use darling::ast::NestedMeta;
@@ -76,6 +76,8 @@ struct ModelBuilder {
     vis: syn::Visibility,
     table_name: String,
     pk_field: Field,
+    soft_delete: bool,
+    version_field: Option<Field>,
     fields_struct_name: Ident,
     fields_as_columns: Vec<TokenStream>,
     fields_as_from_db: Vec<TokenStream>,
@@ -110,6 +112,8 @@ impl ModelBuilder {
             vis: model.vis,
             table_name,
             pk_field: model.pk_field.clone(),
+            soft_delete: model.soft_delete,
+            version_field: model.version_field.clone(),
             fields_struct_name: format_ident!("{}Fields", model.name),
             fields_as_columns: Vec::with_capacity(field_count),
             fields_as_from_db: Vec::with_capacity(field_count),
@@ -174,6 +178,28 @@ impl ModelBuilder {
         let fields_as_from_db = &self.fields_as_from_db;
         let fields_as_update_from_db = &self.fields_as_update_from_db;
         let fields_as_get_values = &self.fields_as_get_values;
+        let soft_delete_column = if self.soft_delete {
+            quote! {
+                const SOFT_DELETE_COLUMN: ::core::option::Option<#orm_ident::Identifier> =
+                    ::core::option::Option::Some(#orm_ident::Identifier::new(#SOFT_DELETE_COLUMN_NAME));
+            }
+        } else {
+            quote! {}
+        };
+        let version = if let Some(version_field) = &self.version_field {
+            let version_field_name = &version_field.name;
+            let version_column_name = &version_field.column_name;
+            quote! {
+                const VERSION_COLUMN: ::core::option::Option<#orm_ident::Identifier> =
+                    ::core::option::Option::Some(#orm_ident::Identifier::new(#version_column_name));
+
+                fn increment_version(&mut self) {
+                    self.#version_field_name += 1;
+                }
+            }
+        } else {
+            quote! {}
+        };
 
         quote! {
             #[#crate_ident::__private::async_trait]
@@ -188,6 +214,8 @@ impl ModelBuilder {
                 const APP_NAME: &'static str = #app_name;
                 const TABLE_NAME: #orm_ident::Identifier = #orm_ident::Identifier::new(#table_name);
                 const PRIMARY_KEY_NAME: #orm_ident::Identifier = #orm_ident::Identifier::new(#pk_column_name);
+                #soft_delete_column
+                #version
 
                 fn primary_key(&self) -> &Self::PrimaryKey {
                     &self.#pk_field_name
@@ -45,6 +45,12 @@ fn derive_admin_model() {
     let t = trybuild::TestCases::new();
     t.pass("tests/ui/derive_admin_model.rs");
     t.pass("tests/ui/derive_admin_model_derive_first.rs");
+    t.pass("tests/ui/derive_admin_model_list_display.rs");
+    t.compile_fail("tests/ui/derive_admin_model_list_display_unknown_field.rs");
+    t.pass("tests/ui/derive_admin_model_list_filter.rs");
+    t.compile_fail("tests/ui/derive_admin_model_list_filter_unsupported_type.rs");
+    t.pass("tests/ui/derive_admin_model_readonly.rs");
+    t.compile_fail("tests/ui/derive_admin_model_readonly_unknown_field.rs");
 }
 
 #[rustversion::attr(
@@ -0,0 +1,49 @@
+use std::fmt::Display;
+
+use cot::admin::{AdminModel, ListValue};
+use cot::db::{Auto, ForeignKey, model};
+use cot::form::Form;
+
+#[model]
+#[derive(Debug, Form, AdminModel)]
+#[admin(list_display = [name, is_active])]
+struct Category {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+    is_active: bool,
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[model]
+#[derive(Debug, Form, AdminModel)]
+#[admin(list_display = [id, title, category])]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: String,
+    category: ForeignKey<Category>,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+fn main() {
+    let columns = Article::list_columns();
+    assert_eq!(columns[2].label(), "Category");
+
+    let article = Article {
+        id: Auto::fixed(1),
+        title: "Hello".to_owned(),
+        category: ForeignKey::PrimaryKey(Auto::fixed(2)),
+    };
+    assert_eq!(article.list_values()[0], ListValue::Text("1".to_owned()));
+}
@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use cot::admin::{AdminModel, ListFilterKind};
+use cot::db::{Auto, model};
+use cot::form::Form;
+
+#[model]
+#[derive(Debug, Form, AdminModel)]
+#[admin(
+    list_display = [title, published_on],
+    search_fields = [title, body],
+    list_filter = [is_published, published_on],
+)]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: String,
+    body: String,
+    is_published: bool,
+    published_on: Option<chrono::NaiveDate>,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+fn main() {
+    assert_eq!(Article::search_fields(), ["title", "body"]);
+
+    let filters = Article::list_filters();
+    assert_eq!(filters[0].name(), "is_published");
+    assert_eq!(filters[0].kind(), &ListFilterKind::Bool);
+    assert_eq!(filters[1].label(), "Published on");
+    assert_eq!(filters[1].kind(), &ListFilterKind::DateRange);
+
+    assert!(Article::list_columns()[1].is_sortable());
+}
@@ -0,0 +1,22 @@
+use std::fmt::Display;
+
+use cot::admin::AdminModel;
+use cot::db::{Auto, model};
+use cot::form::Form;
+
+#[model]
+#[derive(Debug, Form, AdminModel)]
+#[admin(list_filter = [title])]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: String,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+fn main() {}
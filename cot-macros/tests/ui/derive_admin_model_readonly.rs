@@ -0,0 +1,27 @@
+use std::fmt::Display;
+
+use cot::admin::AdminModel;
+use cot::db::{Auto, model};
+use cot::form::Form;
+
+#[model]
+#[derive(Debug, Form, AdminModel)]
+#[admin(readonly = [created_at], exclude = [internal_notes])]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    title: String,
+    created_at: chrono::DateTime<chrono::FixedOffset>,
+    internal_notes: Option<String>,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+fn main() {
+    assert_eq!(Article::readonly_fields(), ["created_at"]);
+    assert_eq!(Article::excluded_fields(), ["internal_notes"]);
+}
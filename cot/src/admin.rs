@@ -4,10 +4,18 @@
 //! registered in the application, straight from the web interface.
 
 use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use cot_core::error::impl_into_cot_error;
 /// Implements the [`AdminModel`] trait for a struct.
 ///
 /// This is a simple method for adding a database model to the admin panel.
@@ -19,9 +27,10 @@ pub use cot_macros::AdminModel;
 use derive_more::Debug;
 use serde::Deserialize;
 
-use crate::auth::Auth;
+use crate::auth::{Auth, AuthError, PermissionDenied, User};
 use crate::common_types::Password;
-use crate::error::NotFound;
+use crate::csrf::CsrfToken;
+use crate::error::{MethodNotAllowed, NotFound};
 use crate::form::{
     Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, FormResult,
 };
@@ -31,7 +40,17 @@ use crate::request::{Request, RequestExt, RequestHead};
 use crate::response::{IntoResponse, Response};
 use crate::router::{Router, Urls};
 use crate::static_files::StaticFile;
-use crate::{App, Error, Method, RequestHandler, Template, reverse_redirect};
+use crate::{App, Body, Error, Method, RequestHandler, Template, reverse_redirect};
+
+#[cfg(feature = "json")]
+mod autocomplete;
+mod import_export;
+mod inline;
+pub mod log;
+
+pub use self::inline::AdminInline;
+use self::inline::{InlineChanges, InlineFormSet};
+use self::log::LogEntryAction;
 
 struct AdminAuthenticated<T, H: Send + Sync>(H, PhantomData<fn() -> T>);
 
@@ -45,23 +64,195 @@ impl<T, H: RequestHandler<T> + Send + Sync> AdminAuthenticated<T, H> {
 impl<T, H: RequestHandler<T> + Send + Sync> RequestHandler<T> for AdminAuthenticated<T, H> {
     async fn handle(&self, mut request: Request) -> crate::Result<Response> {
         let auth: Auth = request.extract_from_head().await?;
-        if !auth.user().is_authenticated() {
+        if !can_access_admin(&*auth.user()) {
             return Ok(reverse_redirect!(request, "login")?);
         }
+        // the admin views check the permissions of the user on the models
+        auth.load_permissions().await?;
 
         self.0.handle(request).await
     }
 }
 
+/// Returns whether the user is allowed to log in to the admin panel.
+fn can_access_admin(user: &dyn User) -> bool {
+    user.is_authenticated() && user.is_active() && (user.is_staff() || user.is_superuser())
+}
+
 #[derive(Debug, FromRequestHead)]
 struct BaseContext {
     urls: Urls,
     static_files: StaticFiles,
+    csrf_token: CsrfToken,
+    auth: Auth,
+}
+
+impl BaseContext {
+    fn permissions(&self, manager: &dyn AdminModelManager) -> ModelPermissions {
+        ModelPermissions::new(&*self.auth.user(), manager)
+    }
+
+    fn inline_permissions(&self, inline: &AdminInline) -> ModelPermissions {
+        ModelPermissions::from_codenames(&*self.auth.user(), |permission| {
+            permission.codename_for(inline.url_name())
+        })
+    }
+
+    /// Returns the fields of the edit form of an object of the model the user
+    /// cannot edit.
+    fn non_editable_fields(
+        &self,
+        manager: &dyn AdminModelManager,
+        object_id: Option<&str>,
+    ) -> NonEditableFields {
+        NonEditableFields::new(manager, &*self.auth.user(), object_id)
+    }
+
+    /// Returns the inlines of the model the user can edit, along with the
+    /// user's permissions for them.
+    fn editable_inlines(
+        &self,
+        manager: &dyn AdminModelManager,
+    ) -> Vec<(AdminInline, ModelPermissions)> {
+        manager
+            .inlines()
+            .into_iter()
+            .map(|inline| {
+                let permissions = self.inline_permissions(&inline);
+                (inline, permissions)
+            })
+            .filter(|(_, permissions)| permissions.change)
+            .collect()
+    }
+
+    fn check_permission(
+        &self,
+        manager: &dyn AdminModelManager,
+        permission: AdminPermission,
+    ) -> cot::Result<()> {
+        if self
+            .auth
+            .user()
+            .has_perm(&manager.permission_codename(permission))
+        {
+            Ok(())
+        } else {
+            Err(PermissionDenied::new().into())
+        }
+    }
+
+    fn check_view_permission(&self, manager: &dyn AdminModelManager) -> cot::Result<()> {
+        if self.permissions(manager).view {
+            Ok(())
+        } else {
+            Err(PermissionDenied::new().into())
+        }
+    }
+}
+
+/// The admin permissions the current user has for a specific model.
+#[derive(Debug, Copy, Clone)]
+#[expect(clippy::struct_excessive_bools)]
+struct ModelPermissions {
+    view: bool,
+    add: bool,
+    change: bool,
+    delete: bool,
+}
+
+impl ModelPermissions {
+    fn new(user: &dyn User, manager: &dyn AdminModelManager) -> Self {
+        Self::from_codenames(user, |permission| manager.permission_codename(permission))
+    }
+
+    fn from_codenames<F>(user: &dyn User, codename: F) -> Self
+    where
+        F: Fn(AdminPermission) -> String,
+    {
+        let has_perm = |permission: AdminPermission| user.has_perm(&codename(permission));
+        let change = has_perm(AdminPermission::Change);
+
+        Self {
+            // users that can change objects can also view them
+            view: change || has_perm(AdminPermission::View),
+            add: has_perm(AdminPermission::Add),
+            change,
+            delete: has_perm(AdminPermission::Delete),
+        }
+    }
+
+    fn has(self, permission: AdminPermission) -> bool {
+        match permission {
+            AdminPermission::View => self.view,
+            AdminPermission::Add => self.add,
+            AdminPermission::Change => self.change,
+            AdminPermission::Delete => self.delete,
+        }
+    }
+}
+
+/// The fields of the edit form of an object the current user cannot edit.
+#[derive(Debug)]
+struct NonEditableFields {
+    /// The fields shown, but not editable.
+    readonly: Vec<Cow<'static, str>>,
+    /// The fields not shown at all.
+    excluded: Vec<Cow<'static, str>>,
+}
+
+impl NonEditableFields {
+    /// Returns the fields of the edit form of the object with the given ID
+    /// (or of a new object if `object_id` is `None`) the user cannot edit.
+    ///
+    /// The read-only fields can be filled in when creating a new object, so
+    /// they are only returned for existing objects.
+    fn new(manager: &dyn AdminModelManager, user: &dyn User, object_id: Option<&str>) -> Self {
+        Self {
+            readonly: if object_id.is_some() {
+                manager.readonly_fields(user)
+            } else {
+                Vec::new()
+            },
+            excluded: manager.excluded_fields(user),
+        }
+    }
+
+    fn ids(&self) -> Vec<&str> {
+        self.readonly
+            .iter()
+            .chain(&self.excluded)
+            .map(AsRef::as_ref)
+            .collect()
+    }
+
+    fn is_readonly(&self, field_id: &str) -> bool {
+        self.readonly.iter().any(|field| field == field_id)
+    }
+
+    fn is_excluded(&self, field_id: &str) -> bool {
+        self.excluded.iter().any(|field| field == field_id)
+    }
+
+    /// Returns the validation errors of the excluded fields, which otherwise
+    /// wouldn't be shown to the user.
+    fn excluded_field_errors(&self, form_context: &dyn FormContext) -> Vec<String> {
+        form_context
+            .fields()
+            .filter(|field| self.is_excluded(field.dyn_id()))
+            .flat_map(|field| {
+                form_context
+                    .errors_for(FormErrorTarget::Field(field.dyn_id()))
+                    .iter()
+                    .map(|error| format!("{}: {error}", field.dyn_options().name))
+            })
+            .collect()
+    }
 }
 
 async fn index(
     base_context: BaseContext,
     AdminModelManagers(managers): AdminModelManagers,
+    request: Request,
 ) -> crate::Result<Html> {
     #[derive(Debug, Template)]
     #[template(path = "admin/model_list.html")]
@@ -69,26 +260,70 @@ async fn index(
         ctx: &'a BaseContext,
         #[debug("..")]
         model_managers: Vec<Box<dyn AdminModelManager>>,
+        recent_actions: Option<Vec<LogEntryView>>,
     }
 
+    let model_managers: Vec<_> = managers
+        .into_iter()
+        .filter(|manager| base_context.permissions(&**manager).view)
+        .collect();
+    let recent_actions = recent_actions(&request, &model_managers).await?;
     let template = ModelListTemplate {
         ctx: &base_context,
-        model_managers: managers,
+        model_managers,
+        recent_actions,
     };
     Ok(Html::new(template.render()?))
 }
 
+/// Returns the most recent actions performed on the objects of the given
+/// models, or [`None`] if the changes are not [logged](log::LogEntry).
+#[cfg(feature = "db")]
+async fn recent_actions(
+    request: &Request,
+    managers: &[Box<dyn AdminModelManager>],
+) -> cot::Result<Option<Vec<LogEntryView>>> {
+    const RECENT_ACTIONS_LIMIT: u64 = 10;
+
+    let Some(database) = request.context().try_database() else {
+        return Ok(None);
+    };
+    let time_zone = request.project_config().admin.time_zone;
+    let model_names: Vec<_> = managers.iter().map(|manager| manager.url_name()).collect();
+
+    let entries = log::LogEntry::recent(database, &model_names, RECENT_ACTIONS_LIMIT).await?;
+    let recent_actions = entries
+        .iter()
+        .map(|entry| {
+            let model_name = managers
+                .iter()
+                .find(|manager| manager.url_name() == entry.model_name())
+                .map_or_else(
+                    || entry.model_name().to_owned(),
+                    |manager| manager.name().to_owned(),
+                );
+            LogEntryView::new(entry, model_name, time_zone)
+        })
+        .collect();
+    Ok(Some(recent_actions))
+}
+
+#[cfg(not(feature = "db"))]
+#[expect(clippy::unused_async)] // to keep the same signature as with the `db` feature
+async fn recent_actions(
+    _request: &Request,
+    _managers: &[Box<dyn AdminModelManager>],
+) -> cot::Result<Option<Vec<LogEntryView>>> {
+    Ok(None)
+}
+
 #[derive(Debug, Form)]
 struct LoginForm {
     username: String,
     password: Password,
 }
 
-async fn login(
-    base_context: BaseContext,
-    auth: Auth,
-    mut request: Request,
-) -> crate::Result<Response> {
+async fn login(base_context: BaseContext, mut request: Request) -> crate::Result<Response> {
     #[derive(Debug, Template)]
     #[template(path = "admin/login.html")]
     struct LoginTemplate<'a> {
@@ -102,15 +337,35 @@ async fn login(
         let login_form = LoginForm::from_request(&mut request).await?;
         match login_form {
             FormResult::Ok(login_form) => {
-                if authenticate(&auth, login_form).await? {
-                    return Ok(reverse_redirect!(base_context.urls, "index")?);
-                }
+                let auth = &base_context.auth;
+                let error = match authenticate(auth, login_form).await {
+                    Ok(Some(user)) if can_access_admin(&*user) => {
+                        auth.login(user).await?;
+                        #[cfg(feature = "db")]
+                        if auth.pending_second_factor_user().await?.is_some() {
+                            return Ok(reverse_redirect!(base_context.urls, "login_two_factor")?);
+                        }
+                        return Ok(reverse_redirect!(base_context.urls, "index")?);
+                    }
+                    Ok(Some(_)) => FormFieldValidationError::from_static(
+                        "This account is not allowed to access the admin panel",
+                    ),
+                    Ok(None) => {
+                        FormFieldValidationError::from_static("Invalid username or password")
+                    }
+                    Err(AuthError::TooManyAttempts { retry_after }) => {
+                        FormFieldValidationError::from_string(too_many_attempts_message(
+                            retry_after,
+                        ))
+                    }
+                    Err(AuthError::EmailNotVerified) => FormFieldValidationError::from_static(
+                        "Please verify your email address before logging in",
+                    ),
+                    Err(error) => return Err(error.into()),
+                };
 
                 let mut context = LoginForm::build_context(&mut request).await?;
-                context.add_error(
-                    FormErrorTarget::Form,
-                    FormFieldValidationError::from_static("Invalid username or password"),
-                );
+                context.add_error(FormErrorTarget::Form, error);
                 context
             }
             FormResult::ValidationError(context) => context,
@@ -126,7 +381,18 @@ async fn login(
     Html::new(template.render()?).into_response()
 }
 
-async fn authenticate(auth: &Auth, login_form: LoginForm) -> crate::Result<bool> {
+/// Returns the message shown when the login has been throttled, with the
+/// remaining lockout time rounded up to whole minutes.
+fn too_many_attempts_message(retry_after: Duration) -> String {
+    let minutes = retry_after.as_secs().div_ceil(60).max(1);
+    let unit = if minutes == 1 { "minute" } else { "minutes" };
+    format!("Too many failed login attempts. Please try again in {minutes} {unit}.")
+}
+
+async fn authenticate(
+    auth: &Auth,
+    login_form: LoginForm,
+) -> crate::auth::Result<Option<Box<dyn User + Send + Sync>>> {
     #[cfg(feature = "db")]
     let user = auth
         .authenticate(&crate::auth::db::DatabaseUserCredentials::new(
@@ -136,14 +402,182 @@ async fn authenticate(auth: &Auth, login_form: LoginForm) -> crate::Result<bool>
         .await?;
 
     #[cfg(not(feature = "db"))]
-    let user: Option<Box<dyn crate::auth::User + Send + Sync>> = None;
+    let user: Option<Box<dyn User + Send + Sync>> = {
+        let _ = (auth, login_form);
+        None
+    };
 
-    if let Some(user) = user {
-        auth.login(user).await?;
-        Ok(true)
-    } else {
-        Ok(false)
+    Ok(user)
+}
+
+#[cfg(feature = "db")]
+#[derive(Debug, Form)]
+struct TwoFactorForm {
+    code: String,
+}
+
+/// The throttling of the attempts to verify the second authentication factor
+/// of a user, active if the login throttling is enabled.
+#[cfg(feature = "db")]
+struct SecondFactorThrottle<'a> {
+    throttle: Option<crate::auth::throttle::LoginThrottle>,
+    username: &'a str,
+    ip: Option<std::net::IpAddr>,
+}
+
+#[cfg(feature = "db")]
+impl<'a> SecondFactorThrottle<'a> {
+    fn new(request: &Request, username: &'a str) -> Self {
+        use crate::auth::throttle::LoginThrottle;
+
+        Self {
+            throttle: LoginThrottle::from_request(request).map(LoginThrottle::second_factor),
+            username,
+            ip: LoginThrottle::client_ip(request),
+        }
+    }
+
+    /// Returns the remaining lockout time if the user has failed to verify the
+    /// second factor too many times.
+    async fn retry_after(&self) -> crate::Result<Option<Duration>> {
+        let Some(throttle) = &self.throttle else {
+            return Ok(None);
+        };
+        match throttle.check(Some(self.username), self.ip).await {
+            Ok(()) => Ok(None),
+            Err(AuthError::TooManyAttempts { retry_after }) => Ok(Some(retry_after)),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Records the result of an attempt to verify the second factor.
+    async fn record(&self, verified: bool) -> crate::Result<()> {
+        if let Some(throttle) = &self.throttle {
+            if verified {
+                throttle.reset(self.username).await?;
+            } else {
+                throttle
+                    .record_failure(Some(self.username), self.ip)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verifies the second authentication factor of a user who has already
+/// verified their password, enrolling them in the two-factor authentication
+/// first if it's required, but not set up yet.
+#[cfg(feature = "db")]
+async fn login_two_factor(
+    base_context: BaseContext,
+    mut request: Request,
+) -> crate::Result<Response> {
+    use crate::auth::db::DatabaseUser;
+    use crate::auth::totp::{DEFAULT_RECOVERY_CODE_COUNT, TotpDevice, TotpRecoveryCode};
+
+    #[derive(Debug)]
+    struct Enrollment {
+        secret: String,
+        provisioning_uri: String,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(path = "admin/login_two_factor.html")]
+    struct LoginTwoFactorTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <TwoFactorForm as Form>::Context,
+        enrollment: Option<Enrollment>,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(path = "admin/recovery_codes.html")]
+    struct RecoveryCodesTemplate<'a> {
+        ctx: &'a BaseContext,
+        recovery_codes: Vec<String>,
     }
+
+    let auth = &base_context.auth;
+    let Some(user_id) = auth
+        .pending_second_factor_user()
+        .await?
+        .and_then(|user| user.id())
+        .and_then(|id| id.as_int())
+    else {
+        return Ok(reverse_redirect!(base_context.urls, "login")?);
+    };
+    let database = request.context().database().clone();
+    let Some(user) = DatabaseUser::get_by_id(&database, user_id).await? else {
+        return Ok(reverse_redirect!(base_context.urls, "login")?);
+    };
+    let two_factor_config = &request.project_config().two_factor;
+    let (issuer, drift) = (two_factor_config.issuer.clone(), two_factor_config.drift);
+
+    let mut device = match TotpDevice::get_for_user(&database, &user).await? {
+        Some(device) => device,
+        None => TotpDevice::enroll(&database, &user).await?,
+    };
+
+    let form_context = if request.method() == Method::GET {
+        TwoFactorForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match TwoFactorForm::from_request(&mut request).await? {
+            FormResult::Ok(form) => {
+                let throttle = SecondFactorThrottle::new(&request, user.username());
+                let error = if let Some(retry_after) = throttle.retry_after().await? {
+                    FormFieldValidationError::from_string(too_many_attempts_message(retry_after))
+                } else if device.is_confirmed() {
+                    let verified = device
+                        .verify_with_drift(&database, &form.code, drift)
+                        .await?
+                        || TotpRecoveryCode::verify(&database, &user, &form.code).await?;
+                    throttle.record(verified).await?;
+                    if verified {
+                        auth.complete_second_factor().await?;
+                        return Ok(reverse_redirect!(base_context.urls, "index")?);
+                    }
+                    FormFieldValidationError::from_static("Invalid verification code")
+                } else {
+                    let confirmed = device.confirm(&database, &form.code).await?;
+                    throttle.record(confirmed).await?;
+                    if confirmed {
+                        let recovery_codes = TotpRecoveryCode::generate(
+                            &database,
+                            &user,
+                            DEFAULT_RECOVERY_CODE_COUNT,
+                        )
+                        .await?;
+                        auth.complete_second_factor().await?;
+
+                        let template = RecoveryCodesTemplate {
+                            ctx: &base_context,
+                            recovery_codes,
+                        };
+                        return Html::new(template.render()?).into_response();
+                    }
+                    FormFieldValidationError::from_static("Invalid verification code")
+                };
+
+                let mut context = TwoFactorForm::build_context(&mut request).await?;
+                context.add_error(FormErrorTarget::Form, error);
+                context
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        panic!("Unexpected request method");
+    };
+
+    let enrollment = (!device.is_confirmed()).then(|| Enrollment {
+        secret: device.secret().to_owned(),
+        provisioning_uri: device.provisioning_uri(&issuer, user.username()),
+    });
+    let template = LoginTwoFactorTemplate {
+        ctx: &base_context,
+        form: form_context,
+        enrollment,
+    };
+    Html::new(template.render()?).into_response()
 }
 
 /// Struct representing the pagination of objects.
@@ -195,47 +629,353 @@ async fn view_model(
         ctx: &'a BaseContext,
         #[debug("..")]
         model: &'a dyn AdminModelManager,
+        permissions: ModelPermissions,
         #[debug("..")]
         objects: Vec<Box<dyn AdminModel>>,
+        columns: Vec<ListColumn>,
+        is_searchable: bool,
+        filters: Vec<ListFilter>,
+        actions: Vec<AdminAction>,
+        params: ListParams,
+        time_zone: Tz,
         page: u64,
         page_size: &'a u64,
         total_object_counts: u64,
         total_pages: u64,
     }
 
-    const DEFAULT_PAGE_SIZE: u64 = 10;
+    impl ModelTemplate<'_> {
+        fn format_datetime(&self, datetime: &DateTime<FixedOffset>) -> String {
+            format_datetime(datetime, self.time_zone)
+        }
+    }
 
     let manager = get_manager(managers, &model_name)?;
+    base_context.check_view_permission(&*manager)?;
+
+    let permissions = base_context.permissions(&*manager);
+    let columns = manager.list_columns();
+    let filters = manager.list_filters();
+    let actions = manager
+        .actions()
+        .into_iter()
+        .filter(|action| permissions.has(action.permission()))
+        .collect();
+    let time_zone = request.project_config().admin.time_zone;
+    let params = ListParams::from_request(&request);
+    let list_query = params.to_list_query(&columns, &filters, time_zone);
+
+    let total_object_counts = manager
+        .get_total_object_counts(&request, &list_query)
+        .await?;
+    let page = PageInfo::new(&pagination_params, total_object_counts)?;
+    let objects = manager
+        .get_objects(&request, &list_query, page.pagination())
+        .await?;
+
+    let template = ModelTemplate {
+        ctx: &base_context,
+        model: &*manager,
+        permissions,
+        objects,
+        columns,
+        is_searchable: !manager.search_fields().is_empty(),
+        filters,
+        actions,
+        params,
+        time_zone,
+        page: page.page,
+        page_size: &page.page_size,
+        total_object_counts,
+        total_pages: page.total_pages,
+    };
+
+    Html::new(template.render()?).into_response()
+}
+
+/// Formats the date and time for the admin panel, in the given time zone.
+fn format_datetime(datetime: &DateTime<FixedOffset>, time_zone: Tz) -> String {
+    datetime
+        .with_timezone(&time_zone)
+        .format("%Y-%m-%d %H:%M:%S %Z")
+        .to_string()
+}
+
+/// The URL query parameters of the model list page, used to build the
+/// [`ListQuery`] and the links that change it.
+#[derive(Debug, Clone, Default)]
+struct ListParams(Vec<(String, String)>);
+
+impl ListParams {
+    const SEARCH: &str = "q";
+    const ORDERING: &str = "o";
+    const PAGE: &str = "page";
+    const FILTER_PREFIX: &str = "f.";
 
-    let page = pagination_params.page.unwrap_or(1);
-    let page_size = pagination_params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    fn from_request(request: &Request) -> Self {
+        Self::from_query(request.uri().query().unwrap_or_default())
+    }
 
-    let total_object_counts = manager.get_total_object_counts(&request).await?;
-    let total_pages = total_object_counts.div_ceil(page_size);
+    fn from_query(query: &str) -> Self {
+        Self(
+            form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+        )
+    }
 
-    if (page == 0 || page > total_pages) && total_pages > 0 {
-        return Err(Error::from(NotFound::with_message(format!(
-            "page {page} not found"
-        ))));
+    fn get(&self, key: &str) -> &str {
+        self.0
+            .iter()
+            .find(|(param_key, _)| param_key == key)
+            .map_or("", |(_, value)| value.as_str())
     }
 
-    let pagination = Pagination::new(page_size, page);
+    fn filter_key(filter: &ListFilter) -> String {
+        format!("{}{}", Self::FILTER_PREFIX, filter.name())
+    }
 
-    let objects = manager.get_objects(&request, pagination).await?;
+    fn to_list_query(
+        &self,
+        columns: &[ListColumn],
+        filters: &[ListFilter],
+        time_zone: Tz,
+    ) -> ListQuery {
+        let mut query = ListQuery::new();
+
+        let search = self.get(Self::SEARCH).trim();
+        if !search.is_empty() {
+            query = query.with_search(search);
+        }
 
-    let template = ModelTemplate {
+        let ordering = self.get(Self::ORDERING);
+        let ordering = match ordering.strip_prefix('-') {
+            Some(field) => ListOrdering::descending(field),
+            None => ListOrdering::ascending(ordering),
+        };
+        if columns
+            .iter()
+            .any(|column| column.is_sortable() && column.name() == ordering.field())
+        {
+            query = query.with_ordering(ordering);
+        }
+
+        for filter in filters {
+            let key = Self::filter_key(filter);
+            let value = match filter.kind() {
+                ListFilterKind::Bool => self.get(&key).parse().ok().map(ListFilterValue::Bool),
+                ListFilterKind::Choice(choices) => {
+                    let value = self.get(&key);
+                    choices
+                        .iter()
+                        .any(|(id, _)| id == value)
+                        .then(|| ListFilterValue::Choice(value.to_owned()))
+                }
+                ListFilterKind::DateRange => {
+                    let start = self.get_day_start(&format!("{key}.from"), time_zone, 0);
+                    let end = self.get_day_start(&format!("{key}.to"), time_zone, 1);
+                    (start.is_some() || end.is_some())
+                        .then_some(ListFilterValue::DateRange { start, end })
+                }
+            };
+            if let Some(value) = value {
+                query = query.with_filter(filter.name(), value);
+            }
+        }
+
+        query
+    }
+
+    /// Returns the midnight starting the day `days_after` the date in the
+    /// parameter with the given key, in the given time zone.
+    fn get_day_start(
+        &self,
+        key: &str,
+        time_zone: Tz,
+        days_after: u64,
+    ) -> Option<DateTime<FixedOffset>> {
+        let date = NaiveDate::parse_from_str(self.get(key), "%Y-%m-%d")
+            .ok()?
+            .checked_add_days(chrono::Days::new(days_after))?;
+        time_zone
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|datetime| datetime.fixed_offset())
+    }
+
+    /// Returns the URL of the list with the given parameter set to `value`,
+    /// going back to the first page unless the page itself is set.
+    fn url_with(&self, key: &str, value: &str) -> String {
+        let mut serializer = self.serializer_without(key);
+        serializer.append_pair(key, value);
+        format!("?{}", serializer.finish())
+    }
+
+    /// Returns the URL of the list without the given parameter, going back to
+    /// the first page.
+    fn url_without(&self, key: &str) -> String {
+        format!("?{}", self.serializer_without(key).finish())
+    }
+
+    fn serializer_without(&self, key: &str) -> form_urlencoded::Serializer<'static, String> {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        serializer.extend_pairs(self.hidden_params(key));
+        serializer
+    }
+
+    /// Returns the parameters except the page and the ones starting with
+    /// `key`, to be sent along with a form changing them.
+    fn hidden_params(&self, key: &str) -> Vec<(&str, &str)> {
+        self.0
+            .iter()
+            .filter(|(param_key, _)| {
+                param_key != Self::PAGE
+                    && param_key != key
+                    && !param_key
+                        .strip_prefix(key)
+                        .is_some_and(|suffix| suffix.starts_with('.'))
+            })
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    fn page_url(&self, page: u64) -> String {
+        self.url_with(Self::PAGE, &page.to_string())
+    }
+
+    fn sort_url(&self, column: &ListColumn) -> String {
+        if self.get(Self::ORDERING) == column.name() {
+            self.url_with(Self::ORDERING, &format!("-{}", column.name()))
+        } else {
+            self.url_with(Self::ORDERING, column.name())
+        }
+    }
+
+    /// Returns `"asc"` or `"desc"` if the list is sorted by the column, or an
+    /// empty string otherwise.
+    fn sort_direction(&self, column: &ListColumn) -> &'static str {
+        let ordering = self.get(Self::ORDERING);
+        if ordering == column.name() {
+            "asc"
+        } else if ordering.strip_prefix('-') == Some(column.name()) {
+            "desc"
+        } else {
+            ""
+        }
+    }
+}
+
+async fn view_removed_model_instances(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path(model_name): Path<String>,
+    UrlQuery(pagination_params): UrlQuery<PaginationParams>,
+    request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "admin/model_removed.html")]
+    struct ModelRemovedTemplate<'a> {
+        ctx: &'a BaseContext,
+        #[debug("..")]
+        model: &'a dyn AdminModelManager,
+        permissions: ModelPermissions,
+        #[debug("..")]
+        objects: Vec<Box<dyn AdminModel>>,
+        page: u64,
+        total_object_counts: u64,
+        total_pages: u64,
+    }
+
+    let manager = get_restorable_manager(managers, &model_name)?;
+    base_context.check_view_permission(&*manager)?;
+
+    let total_object_counts = manager.get_total_removed_object_counts(&request).await?;
+    let page = PageInfo::new(&pagination_params, total_object_counts)?;
+    let objects = manager
+        .get_removed_objects(&request, page.pagination())
+        .await?;
+
+    let template = ModelRemovedTemplate {
         ctx: &base_context,
         model: &*manager,
+        permissions: base_context.permissions(&*manager),
         objects,
-        page,
-        page_size: &page_size,
+        page: page.page,
         total_object_counts,
-        total_pages,
+        total_pages: page.total_pages,
     };
 
     Html::new(template.render()?).into_response()
 }
 
+async fn restore_model_instance(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path((model_name, object_id)): Path<(String, String)>,
+    mut request: Request,
+) -> cot::Result<Response> {
+    let manager = get_restorable_manager(managers, &model_name)?;
+    base_context.check_permission(&*manager, AdminPermission::Change)?;
+
+    if request.method() != Method::POST {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    }
+    manager.restore_by_id(&mut request, &object_id).await?;
+    let object_repr = manager
+        .get_object_by_id(&request, &object_id)
+        .await?
+        .map_or_else(|| object_id.clone(), |object| object.display());
+    log_action(
+        &base_context.auth,
+        &request,
+        manager.url_name(),
+        (&object_id, &object_repr),
+        LogEntryAction::Restore,
+        Vec::new(),
+    )
+    .await?;
+
+    Ok(reverse_redirect!(
+        base_context.urls,
+        "view_removed_model_instances",
+        model_name = manager.url_name()
+    )?)
+}
+
+/// The page of a paginated object list that was requested.
+#[derive(Debug, Copy, Clone)]
+struct PageInfo {
+    page: u64,
+    page_size: u64,
+    total_pages: u64,
+}
+
+impl PageInfo {
+    const DEFAULT_PAGE_SIZE: u64 = 10;
+
+    fn new(params: &PaginationParams, total_object_counts: u64) -> cot::Result<Self> {
+        let page = params.page.unwrap_or(1);
+        let page_size = params.page_size.unwrap_or(Self::DEFAULT_PAGE_SIZE);
+        let total_pages = total_object_counts.div_ceil(page_size);
+
+        if (page == 0 || page > total_pages) && total_pages > 0 {
+            return Err(Error::from(NotFound::with_message(format!(
+                "page {page} not found"
+            ))));
+        }
+
+        Ok(Self {
+            page,
+            page_size,
+            total_pages,
+        })
+    }
+
+    fn pagination(&self) -> Pagination {
+        Pagination::new(self.page_size, self.page)
+    }
+}
+
 async fn create_model_instance(
     base_context: BaseContext,
     managers: AdminModelManagers,
@@ -275,47 +1015,255 @@ async fn edit_model_instance_impl(
         #[debug("..")]
         model: &'a dyn AdminModelManager,
         form_context: Box<dyn FormContext>,
-        is_edit: bool,
+        fields: NonEditableFields,
+        inlines: Vec<InlineFormSet>,
+        object_id: Option<&'a str>,
+        has_history: bool,
     }
 
     let manager = get_manager(managers, model_name)?;
+    let permission = if object_id.is_some() {
+        AdminPermission::Change
+    } else {
+        AdminPermission::Add
+    };
+    base_context.check_permission(&*manager, permission)?;
+    let fields = base_context.non_editable_fields(&*manager, object_id);
 
-    let form_context = if request.method() == Method::POST {
-        let form_context = manager.save_from_request(&mut request, object_id).await?;
+    // the related objects can only be edited once the object exists
+    let inlines = if object_id.is_some() {
+        base_context.editable_inlines(&*manager)
+    } else {
+        Vec::new()
+    };
+
+    let (form_context, inline_formsets) = 'form_context: {
+        if request.method() == Method::POST {
+            let previous_values = current_form_values(&mut request, &*manager, object_id).await?;
+
+            let (body, inline_changes) =
+                validate_inlines(&mut request, &inlines, object_id).await?;
+
+            // the object is not saved at all if any of the inlines is invalid
+            let save_result = if inline_changes.iter().any(Result::is_err) {
+                None
+            } else {
+                Some(
+                    manager
+                        .save_from_request(&mut request, object_id, &fields.ids())
+                        .await?,
+                )
+            };
+            let object = match save_result {
+                Some(SaveResult::Saved(object)) => object,
+                invalid => {
+                    let inline_formsets = inline_formsets_from_request(
+                        &mut request,
+                        &inlines,
+                        object_id,
+                        &body,
+                        inline_changes,
+                    )
+                    .await?;
+                    let form_context: Box<dyn FormContext> =
+                        if let Some(SaveResult::Invalid(form_context)) = invalid {
+                            form_context
+                        } else {
+                            *request.body_mut() = Body::fixed(body);
+                            manager
+                                .form_context_from_request(&mut request, object_id, &fields.ids())
+                                .await?
+                        };
+                    break 'form_context (form_context, inline_formsets);
+                }
+            };
+            log_saved_object(
+                &base_context.auth,
+                &request,
+                &*manager,
+                object_id.is_none(),
+                object,
+                &previous_values,
+            )
+            .await?;
+            save_inlines(&base_context, &mut request, &inlines, inline_changes).await?;
 
-        if let Some(form_context) = form_context {
-            form_context
-        } else {
             return Ok(reverse_redirect!(
                 base_context.urls,
                 "view_model",
                 model_name = manager.url_name()
             )?);
+        } else if let Some(object_id) = object_id {
+            let object = get_object(&mut request, &*manager, object_id).await?;
+
+            let inline_formsets = inline_formsets(&request, &inlines, object_id).await?;
+            (
+                manager.form_context_from_object(object).await,
+                inline_formsets,
+            )
+        } else {
+            (manager.form_context(), Vec::new())
         }
-    } else if let Some(object_id) = object_id {
-        let object = get_object(&mut request, &*manager, object_id).await?;
-
-        manager.form_context_from_object(object).await
-    } else {
-        manager.form_context()
     };
 
     let template = ModelEditTemplate {
         ctx: &base_context,
         model: &*manager,
         form_context,
-        is_edit: object_id.is_some(),
+        fields,
+        inlines: inline_formsets,
+        object_id,
+        has_history: is_log_enabled(&request),
     };
 
     Html::new(template.render()?).into_response()
 }
 
-async fn remove_model_instance(
-    base_context: BaseContext,
-    managers: AdminModelManagers,
-    Path((model_name, object_id)): Path<(String, String)>,
-    mut request: Request,
-) -> cot::Result<Response> {
+/// Returns the values of the fields of the edit form of the object with the
+/// given ID, or of an empty form if `object_id` is `None`.
+async fn current_form_values(
+    request: &mut Request,
+    manager: &dyn AdminModelManager,
+    object_id: Option<&str>,
+) -> cot::Result<FormValues> {
+    Ok(if let Some(object_id) = object_id {
+        let object = get_object(request, manager, object_id).await?;
+        FormValues::new(&*manager.form_context_from_object(object).await)
+    } else {
+        FormValues::new(&*manager.form_context())
+    })
+}
+
+/// Records the object saved using the edit form in the admin log.
+///
+/// `previous_values` are the values of the form fields before the object has
+/// been saved.
+async fn log_saved_object(
+    auth: &Auth,
+    request: &Request,
+    manager: &dyn AdminModelManager,
+    created: bool,
+    object: Box<dyn AdminModel>,
+    previous_values: &FormValues,
+) -> cot::Result<()> {
+    let (object_id, object_repr) = (object.id(), object.display());
+    let values = FormValues::new(&*manager.form_context_from_object(object).await);
+    let action = if created {
+        LogEntryAction::Create
+    } else {
+        LogEntryAction::Change
+    };
+    log_action(
+        auth,
+        request,
+        manager.url_name(),
+        (&object_id, &object_repr),
+        action,
+        values.changed_since(previous_values),
+    )
+    .await
+}
+
+/// The result of validating the forms of an inline.
+type InlineValidationResult = Result<Box<dyn InlineChanges>, InlineFormSet>;
+
+/// Validates the forms of all the inlines submitted in the request.
+///
+/// As the form data is read once for the object and once for each inline, the
+/// body of the request is buffered; it's returned along with the results, and
+/// left in the request to be read again.
+async fn validate_inlines(
+    request: &mut Request,
+    inlines: &[(AdminInline, ModelPermissions)],
+    object_id: Option<&str>,
+) -> cot::Result<(Bytes, Vec<InlineValidationResult>)> {
+    if inlines.is_empty() {
+        return Ok((Bytes::new(), Vec::new()));
+    }
+
+    let body = std::mem::take(request.body_mut()).into_bytes().await?;
+    let mut results = Vec::with_capacity(inlines.len());
+    for (inline, permissions) in inlines {
+        let parent_id = object_id.expect("inlines are only used for existing objects");
+        *request.body_mut() = Body::fixed(body.clone());
+        results.push(inline.validate(request, parent_id, *permissions).await?);
+    }
+    *request.body_mut() = Body::fixed(body.clone());
+
+    Ok((body, results))
+}
+
+/// Returns the forms of all the inlines, filled with the related objects of
+/// the object with the given ID.
+async fn inline_formsets(
+    request: &Request,
+    inlines: &[(AdminInline, ModelPermissions)],
+    object_id: &str,
+) -> cot::Result<Vec<InlineFormSet>> {
+    let mut formsets = Vec::with_capacity(inlines.len());
+    for (inline, permissions) in inlines {
+        formsets.push(inline.formset(request, object_id, *permissions).await?);
+    }
+    Ok(formsets)
+}
+
+/// Returns the forms of all the inlines submitted in the request, so that
+/// they can be shown to the user again.
+async fn inline_formsets_from_request(
+    request: &mut Request,
+    inlines: &[(AdminInline, ModelPermissions)],
+    object_id: Option<&str>,
+    body: &Bytes,
+    results: Vec<InlineValidationResult>,
+) -> cot::Result<Vec<InlineFormSet>> {
+    let mut formsets = Vec::with_capacity(inlines.len());
+    for ((inline, permissions), result) in inlines.iter().zip(results) {
+        let formset = match result {
+            Ok(_) => {
+                let parent_id = object_id.expect("inlines are only used for existing objects");
+                *request.body_mut() = Body::fixed(body.clone());
+                inline
+                    .formset_from_request(request, parent_id, *permissions)
+                    .await?
+            }
+            Err(formset) => formset,
+        };
+        formsets.push(formset);
+    }
+    Ok(formsets)
+}
+
+/// Saves the validated changes of all the inlines and records them in the
+/// admin log.
+async fn save_inlines(
+    base_context: &BaseContext,
+    request: &mut Request,
+    inlines: &[(AdminInline, ModelPermissions)],
+    results: Vec<InlineValidationResult>,
+) -> cot::Result<()> {
+    for ((inline, _), changes) in inlines.iter().zip(results) {
+        let changes = changes.expect("inline errors should have been handled by now");
+        for change in changes.save(request).await? {
+            log_action(
+                &base_context.auth,
+                request,
+                inline.url_name(),
+                (&change.object_id, &change.object_repr),
+                change.action,
+                change.changed_fields,
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn remove_model_instance(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path((model_name, object_id)): Path<(String, String)>,
+    mut request: Request,
+) -> cot::Result<Response> {
     #[derive(Debug, Template)]
     #[template(path = "admin/model_remove.html")]
     struct ModelRemoveTemplate<'a> {
@@ -327,10 +1275,20 @@ async fn remove_model_instance(
     }
 
     let manager = get_manager(managers, &model_name)?;
+    base_context.check_permission(&*manager, AdminPermission::Delete)?;
     let object = get_object(&mut request, &*manager, &object_id).await?;
 
     if request.method() == Method::POST {
         manager.remove_by_id(&mut request, &object_id).await?;
+        log_action(
+            &base_context.auth,
+            &request,
+            manager.url_name(),
+            (&object_id, &object.display()),
+            LogEntryAction::Delete,
+            Vec::new(),
+        )
+        .await?;
 
         Ok(reverse_redirect!(
             base_context.urls,
@@ -348,6 +1306,342 @@ async fn remove_model_instance(
     }
 }
 
+/// Shows the [log](log::LogEntry) of the actions performed on an object in the
+/// admin panel, including the ones performed before it was removed.
+#[cfg(feature = "db")]
+async fn view_model_instance_history(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path((model_name, object_id)): Path<(String, String)>,
+    request: Request,
+) -> cot::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "admin/model_history.html")]
+    struct ModelHistoryTemplate<'a> {
+        ctx: &'a BaseContext,
+        #[debug("..")]
+        model: &'a dyn AdminModelManager,
+        object_id: &'a str,
+        object_repr: String,
+        exists: bool,
+        entries: Vec<LogEntryView>,
+    }
+
+    let manager = get_manager(managers, &model_name)?;
+    base_context.check_view_permission(&*manager)?;
+
+    let Some(database) = request.context().try_database() else {
+        return Err(Error::from(NotFound::with_message(
+            "The admin log requires the database to be enabled",
+        )));
+    };
+    let time_zone = request.project_config().admin.time_zone;
+    let entries = log::LogEntry::for_object(database, manager.url_name(), &object_id).await?;
+    let object = manager.get_object_by_id(&request, &object_id).await?;
+
+    let object_repr = match (&object, entries.first()) {
+        (Some(object), _) => object.display(),
+        (None, Some(entry)) => entry.object_repr().to_owned(),
+        (None, None) => {
+            return Err(Error::from(NotFound::with_message(format!(
+                "Object with ID `{}` not found in model `{}`",
+                object_id,
+                manager.name()
+            ))));
+        }
+    };
+    let entries = entries
+        .iter()
+        .map(|entry| LogEntryView::new(entry, manager.name().to_owned(), time_zone))
+        .collect();
+
+    let template = ModelHistoryTemplate {
+        ctx: &base_context,
+        model: &*manager,
+        object_id: &object_id,
+        object_repr,
+        exists: object.is_some(),
+        entries,
+    };
+
+    Html::new(template.render()?).into_response()
+}
+
+/// The name of the built-in action removing the selected objects.
+const DELETE_SELECTED_ACTION: &str = "delete_selected";
+/// The maximum size of the form submitted to perform an action.
+const MAX_ACTION_BODY_SIZE: usize = 256 * 1024;
+/// The maximum number of objects an action can be performed on at once.
+const MAX_ACTION_OBJECTS: usize = 1000;
+
+/// More objects were selected than an action can be performed on at once.
+#[derive(Debug, thiserror::Error)]
+#[error("too many objects selected: {0} (at most {MAX_ACTION_OBJECTS} are allowed)")]
+struct TooManySelectedObjects(usize);
+impl_into_cot_error!(TooManySelectedObjects, BAD_REQUEST);
+
+/// The form submitted from the list of objects to perform an action on the
+/// selected objects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ActionForm {
+    action: String,
+    ids: Vec<String>,
+    confirmed: bool,
+}
+
+impl ActionForm {
+    /// Parses the submitted form, skipping the repeated IDs.
+    fn parse(body: &[u8]) -> Self {
+        let mut form = Self::default();
+        let mut seen_ids = HashSet::new();
+        for (key, value) in form_urlencoded::parse(body) {
+            match &*key {
+                "action" => form.action = value.into_owned(),
+                "ids" if seen_ids.insert(value.clone()) => form.ids.push(value.into_owned()),
+                "confirm" => form.confirmed = true,
+                _ => {}
+            }
+        }
+        form
+    }
+}
+
+async fn run_model_action(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path(model_name): Path<String>,
+    mut request: Request,
+) -> cot::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "admin/model_remove_selected.html")]
+    struct ModelRemoveSelectedTemplate<'a> {
+        ctx: &'a BaseContext,
+        #[debug("..")]
+        model: &'a dyn AdminModelManager,
+        #[debug("..")]
+        objects: Vec<Box<dyn AdminModel>>,
+        action: &'a str,
+    }
+
+    let manager = get_manager(managers, &model_name)?;
+    base_context.check_view_permission(&*manager)?;
+
+    if request.method() != Method::POST {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    }
+    let body = std::mem::take(request.body_mut())
+        .into_bytes_limited(MAX_ACTION_BODY_SIZE)
+        .await?;
+    let form = ActionForm::parse(&body);
+
+    if form.ids.len() > MAX_ACTION_OBJECTS {
+        return Err(TooManySelectedObjects(form.ids.len()).into());
+    }
+    if form.ids.is_empty() {
+        return Ok(reverse_redirect!(
+            base_context.urls,
+            "view_model",
+            model_name = manager.url_name()
+        )?);
+    }
+
+    if form.action == DELETE_SELECTED_ACTION {
+        base_context.check_permission(&*manager, AdminPermission::Delete)?;
+
+        // all the objects are fetched first, so that nothing is removed if any
+        // of them doesn't exist
+        let objects = manager.get_objects_by_ids(&request, &form.ids).await?;
+        if objects.len() != form.ids.len() {
+            return Err(Error::from(NotFound::with_message(format!(
+                "Some of the selected objects were not found in model `{}`",
+                manager.name()
+            ))));
+        }
+
+        if !form.confirmed {
+            let template = ModelRemoveSelectedTemplate {
+                ctx: &base_context,
+                model: &*manager,
+                objects,
+                action: DELETE_SELECTED_ACTION,
+            };
+            return Html::new(template.render()?).into_response();
+        }
+
+        // the objects might be returned in a different order than requested
+        // and with differently formatted IDs, so only their own IDs are used
+        let ids: Vec<_> = objects.iter().map(|object| object.id()).collect();
+        manager.remove_by_ids(&mut request, &ids).await?;
+        for (id, object) in ids.iter().zip(objects) {
+            log_action(
+                &base_context.auth,
+                &request,
+                manager.url_name(),
+                (id, &object.display()),
+                LogEntryAction::Delete,
+                Vec::new(),
+            )
+            .await?;
+        }
+    } else {
+        let action = manager
+            .actions()
+            .into_iter()
+            .find(|action| action.name() == form.action)
+            .ok_or_else(|| {
+                Error::from(NotFound::with_message(format!(
+                    "Action `{}` not found in model `{}`",
+                    form.action,
+                    manager.name()
+                )))
+            })?;
+        if !base_context.permissions(&*manager).has(action.permission()) {
+            return Err(PermissionDenied::new().into());
+        }
+
+        if let Some(response) = action.run(request, form.ids).await? {
+            return Ok(response);
+        }
+    }
+
+    Ok(reverse_redirect!(
+        base_context.urls,
+        "view_model",
+        model_name = manager.url_name()
+    )?)
+}
+
+/// A [`LogEntry`](log::LogEntry) prepared to be shown in the admin panel.
+#[derive(Debug, Clone)]
+struct LogEntryView {
+    model_url_name: String,
+    model_name: String,
+    object_id: String,
+    object_repr: String,
+    action: LogEntryAction,
+    user: String,
+    // only shown on the history page, which requires the database
+    #[cfg(feature = "db")]
+    changed_fields: Vec<String>,
+    timestamp: String,
+}
+
+#[cfg(feature = "db")]
+impl LogEntryView {
+    fn new(entry: &log::LogEntry, model_name: String, time_zone: Tz) -> Self {
+        Self {
+            model_url_name: entry.model_name().to_owned(),
+            model_name,
+            object_id: entry.object_id().to_owned(),
+            object_repr: entry.object_repr().to_owned(),
+            action: entry.action(),
+            user: entry
+                .username()
+                .or(entry.user_id())
+                .unwrap_or("unknown user")
+                .to_owned(),
+            changed_fields: entry.changed_fields().map(ToOwned::to_owned).collect(),
+            timestamp: format_datetime(&entry.timestamp(), time_zone),
+        }
+    }
+}
+
+/// Returns whether the changes made in the admin panel are recorded in the
+/// [log](log::LogEntry), which requires the database to be enabled.
+fn is_log_enabled(request: &Request) -> bool {
+    #[cfg(feature = "db")]
+    {
+        request.context().try_database().is_some()
+    }
+    #[cfg(not(feature = "db"))]
+    {
+        let _ = request;
+        false
+    }
+}
+
+/// Records the action performed on the object with the given ID in the
+/// [log](log::LogEntry), if it's enabled.
+///
+/// This has to be called after the action has been performed; the entry is not
+/// written in the same transaction as the change it records.
+async fn log_action(
+    auth: &Auth,
+    request: &Request,
+    model_url_name: &str,
+    (object_id, object_repr): (&str, &str),
+    action: LogEntryAction,
+    changed_fields: Vec<String>,
+) -> cot::Result<()> {
+    #[cfg(feature = "db")]
+    if let Some(database) = request.context().try_database() {
+        log::LogEntry::record(
+            database,
+            &*auth.user(),
+            model_url_name,
+            object_id,
+            object_repr,
+            action,
+            changed_fields,
+        )
+        .await?;
+    }
+    #[cfg(not(feature = "db"))]
+    let _ = (
+        auth,
+        request,
+        model_url_name,
+        object_id,
+        object_repr,
+        action,
+        changed_fields,
+    );
+
+    Ok(())
+}
+
+/// The values of the fields of a form, used to find out which fields have
+/// been changed when saving an object.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FormValues(Vec<(String, String)>);
+
+impl FormValues {
+    fn new(form_context: &dyn FormContext) -> Self {
+        Self(
+            form_context
+                .fields()
+                .map(|field| {
+                    (
+                        field.dyn_id().to_owned(),
+                        field.dyn_value().unwrap_or_default().to_owned(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    fn ids(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(id, _)| id.as_str())
+    }
+
+    fn get(&self, field_id: &str) -> &str {
+        self.0
+            .iter()
+            .find(|(id, _)| id == field_id)
+            .map_or("", |(_, value)| value.as_str())
+    }
+
+    /// Returns the IDs of the fields whose values differ from the `previous`
+    /// ones.
+    fn changed_since(&self, previous: &Self) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(id, value)| previous.get(id) != value)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
 async fn get_object(
     request: &mut Request,
     manager: &dyn AdminModelManager,
@@ -379,91 +1673,1408 @@ fn get_manager(
         })
 }
 
-#[repr(transparent)]
-struct AdminModelManagers(Vec<Box<dyn AdminModelManager>>);
-
-impl FromRequestHead for AdminModelManagers {
-    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
-        let managers = head
-            .context()
-            .apps()
-            .iter()
-            .flat_map(|app| app.admin_model_managers())
-            .collect();
-        Ok(Self(managers))
+fn get_restorable_manager(
+    managers: AdminModelManagers,
+    model_name: &str,
+) -> cot::Result<Box<dyn AdminModelManager>> {
+    let manager = get_manager(managers, model_name)?;
+    if manager.is_restorable() {
+        Ok(manager)
+    } else {
+        Err(Error::from(NotFound::with_message(format!(
+            "Model `{model_name}` does not support restoring removed objects"
+        ))))
     }
 }
 
-/// A trait for adding admin models to the app.
+/// An action that can be performed on the objects of a model in the admin
+/// panel, each guarded by its own permission.
 ///
-/// This exposes an API over [`AdminModel`] that is dyn-compatible and
-/// hence can be dynamically added to the project.
+/// The permissions are created automatically for every model registered in
+/// the admin panel when the project starts, provided that the
+/// [`DatabaseUserApp`](crate::auth::db::DatabaseUserApp) is registered. Their
+/// codenames have the form `<model URL name>.<action>`, for instance
+/// `"database-user.change"`. A user can perform an action in the admin panel
+/// if [`User::has_perm`] returns `true` for the corresponding codename.
 ///
-/// See [`DefaultAdminModelManager`] for an automatic implementation of this
-/// trait.
-#[async_trait]
-pub trait AdminModelManager: Send + Sync {
-    /// Returns the display name of the model.
-    fn name(&self) -> &str;
+/// # Examples
+///
+/// ```
+/// use cot::admin::AdminPermission;
+///
+/// assert_eq!(
+///     AdminPermission::Change.codename_for("database-user"),
+///     "database-user.change"
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AdminPermission {
+    /// Viewing the list of objects.
+    View,
+    /// Creating new objects.
+    Add,
+    /// Editing existing objects (and restoring removed ones).
+    Change,
+    /// Removing objects.
+    Delete,
+}
 
-    /// Returns the URL slug for the model.
-    fn url_name(&self) -> &str;
+impl AdminPermission {
+    /// All the admin permissions.
+    pub const ALL: [Self; 4] = [Self::View, Self::Add, Self::Change, Self::Delete];
 
-    /// Returns the list of objects of this model.
-    async fn get_objects(
-        &self,
-        request: &Request,
-        pagination: Pagination,
-    ) -> cot::Result<Vec<Box<dyn AdminModel>>>;
+    /// Returns the name of the action guarded by the permission.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminPermission;
+    ///
+    /// assert_eq!(AdminPermission::Delete.as_str(), "delete");
+    /// ```
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Add => "add",
+            Self::Change => "change",
+            Self::Delete => "delete",
+        }
+    }
+
+    /// Returns the codename of the permission for the model with given URL
+    /// name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminPermission;
+    ///
+    /// assert_eq!(
+    ///     AdminPermission::View.codename_for("article"),
+    ///     "article.view"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn codename_for(self, model_url_name: &str) -> String {
+        format!("{model_url_name}.{}", self.as_str())
+    }
 
-    /// Returns the total count of objects of this model.
-    async fn get_total_object_counts(&self, request: &Request) -> cot::Result<u64>;
+    /// Returns the human-readable description of the permission for the model
+    /// with given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminPermission;
+    ///
+    /// assert_eq!(
+    ///     AdminPermission::Add.description_for("Article"),
+    ///     "Can add Article"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn description_for(self, model_name: &str) -> String {
+        format!("Can {} {model_name}", self.as_str())
+    }
+}
 
-    /// Returns the object with the given ID.
-    async fn get_object_by_id(
+/// A custom action that can be performed on the objects selected in the list
+/// of objects of a model in the admin panel.
+///
+/// The actions are registered on an [`AdminModelManager`], for instance using
+/// [`DefaultAdminModelManager::with_action`]. They are shown in the list next
+/// to the built-in action removing the selected objects, but only to the
+/// users having the [permission](Self::with_permission) required to perform
+/// them.
+///
+/// The handler of the action is called with the request and the IDs of the
+/// selected objects. It can return a response to send to the user (for
+/// instance, a file with the exported objects), or [`None`] to go back to the
+/// list of objects. Note that the body of the request has already been read
+/// when the handler is called.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{AdminAction, AdminPermission};
+/// use cot::request::Request;
+/// use cot::response::Response;
+///
+/// async fn mark_as_shipped(request: Request, ids: Vec<String>) -> cot::Result<Option<Response>> {
+///     // update the orders with given IDs here
+///     Ok(None)
+/// }
+///
+/// let action = AdminAction::new("mark_as_shipped", "Mark as shipped", mark_as_shipped)
+///     .with_permission(AdminPermission::Change);
+/// assert_eq!(action.name(), "mark_as_shipped");
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdminAction {
+    name: Cow<'static, str>,
+    label: Cow<'static, str>,
+    permission: AdminPermission,
+    #[debug("..")]
+    handler: Arc<dyn BoxAdminActionHandler>,
+}
+
+trait BoxAdminActionHandler: Send + Sync {
+    fn handle(
         &self,
-        request: &Request,
-        id: &str,
-    ) -> cot::Result<Option<Box<dyn AdminModel>>>;
+        request: Request,
+        ids: Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = cot::Result<Option<Response>>> + Send + '_>>;
+}
 
-    /// Returns an empty form context for this model.
-    fn form_context(&self) -> Box<dyn FormContext>;
+impl<F, Fut> BoxAdminActionHandler for F
+where
+    F: Fn(Request, Vec<String>) -> Fut + Send + Sync,
+    Fut: Future<Output = cot::Result<Option<Response>>> + Send + 'static,
+{
+    fn handle(
+        &self,
+        request: Request,
+        ids: Vec<String>,
+    ) -> Pin<Box<dyn Future<Output = cot::Result<Option<Response>>> + Send + '_>> {
+        Box::pin(self(request, ids))
+    }
+}
 
-    /// Returns a form context pre-filled with the data from given object.
+impl AdminAction {
+    /// Creates a new action with the given name (used to identify the action
+    /// in the submitted form), the label shown in the admin panel, and the
+    /// handler performing the action.
     ///
-    /// It is guaranteed that `object` parameter is an object returned by either
-    /// [`Self::get_objects`] or [`Self::get_object_by_id`] methods. This means
-    /// that if you always return the same object type from these methods,
-    /// you can safely downcast the object to the same type in this method
-    /// as well.
-    async fn form_context_from_object(&self, object: Box<dyn AdminModel>) -> Box<dyn FormContext>;
+    /// By default, performing the action requires the
+    /// [`AdminPermission::Change`] permission.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::AdminAction;
+    /// use cot::request::Request;
+    ///
+    /// let action = AdminAction::new(
+    ///     "mark_as_shipped",
+    ///     "Mark as shipped",
+    ///     |_request: Request, _ids: Vec<String>| async { Ok(None) },
+    /// );
+    /// ```
+    pub fn new<N, L, F, Fut>(name: N, label: L, handler: F) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        L: Into<Cow<'static, str>>,
+        F: Fn(Request, Vec<String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = cot::Result<Option<Response>>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            permission: AdminPermission::Change,
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Sets the permission required to perform the action.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{AdminAction, AdminPermission};
+    /// use cot::request::Request;
+    ///
+    /// let action = AdminAction::new(
+    ///     "export_selected",
+    ///     "Export selected",
+    ///     |_request: Request, _ids: Vec<String>| async { Ok(None) },
+    /// )
+    /// .with_permission(AdminPermission::View);
+    /// assert_eq!(action.permission(), AdminPermission::View);
+    /// ```
+    #[must_use]
+    pub fn with_permission(mut self, permission: AdminPermission) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    /// Returns the name of the action.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the label of the action shown in the admin panel.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the permission required to perform the action.
+    #[must_use]
+    pub fn permission(&self) -> AdminPermission {
+        self.permission
+    }
+
+    /// Performs the action on the objects with the given IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error returned by the handler of the action.
+    pub async fn run(&self, request: Request, ids: Vec<String>) -> cot::Result<Option<Response>> {
+        self.handler.handle(request, ids).await
+    }
+}
+
+/// A column shown in the list of objects of a model in the admin panel.
+///
+/// The columns are usually declared with the
+/// `#[admin(list_display = [...])]` attribute of the
+/// [`AdminModel`](derive@AdminModel) derive macro.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::ListColumn;
+///
+/// let column = ListColumn::new("created_at", "Created at");
+/// assert_eq!(column.name(), "created_at");
+/// assert_eq!(column.label(), "Created at");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListColumn {
+    name: Cow<'static, str>,
+    label: Cow<'static, str>,
+    sortable: bool,
+}
+
+impl ListColumn {
+    /// Creates a new column with the given field name and the label shown in
+    /// the table header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListColumn;
+    ///
+    /// let column = ListColumn::new("is_active", "Is active");
+    /// ```
+    #[must_use]
+    pub fn new<N, L>(name: N, label: L) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        L: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            sortable: false,
+        }
+    }
+
+    /// Marks the column as sortable, so that the list of objects can be
+    /// sorted by clicking on its header.
+    ///
+    /// The requested ordering is passed to
+    /// [`AdminModelManager::get_objects`] in the [`ListQuery`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListColumn;
+    ///
+    /// let column = ListColumn::new("created_at", "Created at").sortable();
+    /// assert!(column.is_sortable());
+    /// ```
+    #[must_use]
+    pub fn sortable(mut self) -> Self {
+        self.sortable = true;
+        self
+    }
+
+    /// Returns the name of the field shown in this column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListColumn;
+    ///
+    /// let column = ListColumn::new("is_active", "Is active");
+    /// assert_eq!(column.name(), "is_active");
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the label shown in the table header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListColumn;
+    ///
+    /// let column = ListColumn::new("is_active", "Is active");
+    /// assert_eq!(column.label(), "Is active");
+    /// ```
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns whether the list of objects can be sorted by this column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListColumn;
+    ///
+    /// let column = ListColumn::new("is_active", "Is active");
+    /// assert!(!column.is_sortable());
+    /// ```
+    #[must_use]
+    pub fn is_sortable(&self) -> bool {
+        self.sortable
+    }
+}
+
+/// A value shown in a [`ListColumn`] of the admin model list.
+///
+/// Each variant is rendered differently, so that the values are easy to tell
+/// apart at a glance. Values of field types are converted to this using the
+/// [`ToListValue`] trait.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{ListValue, ToListValue};
+///
+/// assert_eq!(true.to_list_value(), ListValue::Bool(true));
+/// assert_eq!(None::<i32>.to_list_value(), ListValue::Empty);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListValue {
+    /// No value, for instance because the field is [`None`].
+    Empty,
+    /// A value shown as plain text.
+    Text(String),
+    /// A boolean value, shown as an icon.
+    Bool(bool),
+    /// A date and time, shown in the [configured time
+    /// zone](crate::config::AdminConfig::time_zone).
+    DateTime(DateTime<FixedOffset>),
+    /// A reference to another object, shown as a link to its edit page.
+    Link {
+        /// The [URL slug](AdminModel::url_name) of the referenced model.
+        model_url_name: Cow<'static, str>,
+        /// The ID of the referenced object.
+        id: String,
+        /// The text of the link.
+        text: String,
+    },
+}
+
+/// A type that can be shown in a column of the admin model list.
+///
+/// This is implemented for the common field types, such as numbers, strings,
+/// booleans, dates, and [`ForeignKey`](crate::db::ForeignKey)s. It has to be
+/// implemented for every field listed in the `#[admin(list_display = [...])]`
+/// attribute of the [`AdminModel`](derive@AdminModel) derive macro.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{ListValue, ToListValue};
+///
+/// struct Price(u64);
+///
+/// impl ToListValue for Price {
+///     fn to_list_value(&self) -> ListValue {
+///         ListValue::Text(format!("${}.{:02}", self.0 / 100, self.0 % 100))
+///     }
+/// }
+///
+/// assert_eq!(
+///     Price(1999).to_list_value(),
+///     ListValue::Text("$19.99".to_owned())
+/// );
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be shown in the admin model list",
+    label = "`{Self}` does not implement `ToListValue`",
+    note = "implement `cot::admin::ToListValue` for `{Self}`, or remove the field from `list_display`"
+)]
+pub trait ToListValue {
+    /// Converts the value to a [`ListValue`].
+    fn to_list_value(&self) -> ListValue;
+}
+
+macro_rules! impl_to_list_value_as_text {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToListValue for $ty {
+                fn to_list_value(&self) -> ListValue {
+                    ListValue::Text(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_list_value_as_text!(
+    i8,
+    i16,
+    i32,
+    i64,
+    u8,
+    u16,
+    u32,
+    u64,
+    f32,
+    f64,
+    char,
+    String,
+    NaiveDate,
+    NaiveTime,
+    NaiveDateTime,
+    crate::common_types::Email,
+    crate::common_types::Url,
+);
+
+impl ToListValue for bool {
+    fn to_list_value(&self) -> ListValue {
+        ListValue::Bool(*self)
+    }
+}
+
+impl ToListValue for DateTime<FixedOffset> {
+    fn to_list_value(&self) -> ListValue {
+        ListValue::DateTime(*self)
+    }
+}
+
+impl ToListValue for DateTime<Utc> {
+    fn to_list_value(&self) -> ListValue {
+        ListValue::DateTime(self.fixed_offset())
+    }
+}
+
+impl<T: ToListValue> ToListValue for Option<T> {
+    fn to_list_value(&self) -> ListValue {
+        self.as_ref()
+            .map_or(ListValue::Empty, ToListValue::to_list_value)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<const LIMIT: u32> ToListValue for crate::db::LimitedString<LIMIT> {
+    fn to_list_value(&self) -> ListValue {
+        ListValue::Text(self.to_string())
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T: ToListValue> ToListValue for crate::db::Auto<T> {
+    fn to_list_value(&self) -> ListValue {
+        match self {
+            Self::Fixed(value) => value.to_list_value(),
+            Self::Auto => ListValue::Empty,
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T> ToListValue for crate::db::ForeignKey<T>
+where
+    T: crate::db::Model + AdminModel,
+    T::PrimaryKey: std::fmt::Display,
+{
+    fn to_list_value(&self) -> ListValue {
+        let id = self.primary_key().to_string();
+        let text = self.model().map_or_else(|| id.clone(), AdminModel::display);
+
+        ListValue::Link {
+            model_url_name: Cow::Borrowed(T::url_name()),
+            id,
+            text,
+        }
+    }
+}
+
+/// The search, filters, and ordering requested for the list of objects of a
+/// model in the admin panel.
+///
+/// This is built from the URL query parameters of the model list page and
+/// passed to [`AdminModelManager::get_objects`] and
+/// [`AdminModelManager::get_total_object_counts`], which should only return
+/// the objects matching it.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{ListFilterValue, ListOrdering, ListQuery};
+///
+/// let query = ListQuery::new()
+///     .with_search("john")
+///     .with_filter("is_active", ListFilterValue::Bool(true))
+///     .with_ordering(ListOrdering::descending("last_login"));
+///
+/// assert_eq!(query.search(), Some("john"));
+/// assert_eq!(
+///     query.filter("is_active"),
+///     Some(&ListFilterValue::Bool(true))
+/// );
+/// assert_eq!(
+///     query.ordering().map(|ordering| ordering.field()),
+///     Some("last_login")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListQuery {
+    search: Option<String>,
+    filters: Vec<(String, ListFilterValue)>,
+    ordering: Option<ListOrdering>,
+}
+
+impl ListQuery {
+    /// Creates an empty query, matching all the objects in their default
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListQuery;
+    ///
+    /// let query = ListQuery::new();
+    /// assert_eq!(query.search(), None);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the text to search for in the
+    /// [search fields](AdminModelManager::search_fields) of the model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListQuery;
+    ///
+    /// let query = ListQuery::new().with_search("john");
+    /// assert_eq!(query.search(), Some("john"));
+    /// ```
+    #[must_use]
+    pub fn with_search<S: Into<String>>(mut self, search: S) -> Self {
+        self.search = Some(search.into());
+        self
+    }
+
+    /// Adds a filter on the field with the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilterValue, ListQuery};
+    ///
+    /// let query = ListQuery::new().with_filter("is_staff", ListFilterValue::Bool(false));
+    /// assert_eq!(
+    ///     query.filter("is_staff"),
+    ///     Some(&ListFilterValue::Bool(false))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_filter<N: Into<String>>(mut self, name: N, value: ListFilterValue) -> Self {
+        self.filters.push((name.into(), value));
+        self
+    }
+
+    /// Sets the ordering of the objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListOrdering, ListQuery};
+    ///
+    /// let query = ListQuery::new().with_ordering(ListOrdering::ascending("username"));
+    /// assert_eq!(query.ordering(), Some(&ListOrdering::ascending("username")));
+    /// ```
+    #[must_use]
+    pub fn with_ordering(mut self, ordering: ListOrdering) -> Self {
+        self.ordering = Some(ordering);
+        self
+    }
+
+    /// Returns the text to search for, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListQuery;
+    ///
+    /// assert_eq!(ListQuery::new().search(), None);
+    /// ```
+    #[must_use]
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    /// Returns an iterator over the names of the filtered fields and the
+    /// values they are filtered by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilterValue, ListQuery};
+    ///
+    /// let query = ListQuery::new().with_filter("is_active", ListFilterValue::Bool(true));
+    /// assert_eq!(
+    ///     query.filters().collect::<Vec<_>>(),
+    ///     [("is_active", &ListFilterValue::Bool(true))]
+    /// );
+    /// ```
+    pub fn filters(&self) -> impl Iterator<Item = (&str, &ListFilterValue)> {
+        self.filters
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns the value the field with the given name is filtered by, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListQuery;
+    ///
+    /// assert_eq!(ListQuery::new().filter("is_active"), None);
+    /// ```
+    #[must_use]
+    pub fn filter(&self, name: &str) -> Option<&ListFilterValue> {
+        self.filters()
+            .find(|(filter_name, _)| *filter_name == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the requested ordering of the objects, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListQuery;
+    ///
+    /// assert_eq!(ListQuery::new().ordering(), None);
+    /// ```
+    #[must_use]
+    pub fn ordering(&self) -> Option<&ListOrdering> {
+        self.ordering.as_ref()
+    }
+}
+
+/// The field the list of objects in the admin panel is sorted by, along with
+/// the sorting direction.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::ListOrdering;
+///
+/// let ordering = ListOrdering::descending("created_at");
+/// assert_eq!(ordering.field(), "created_at");
+/// assert!(ordering.is_descending());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListOrdering {
+    field: String,
+    descending: bool,
+}
+
+impl ListOrdering {
+    /// Sorts the objects by the given field, from the smallest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListOrdering;
+    ///
+    /// let ordering = ListOrdering::ascending("username");
+    /// assert!(!ordering.is_descending());
+    /// ```
+    #[must_use]
+    pub fn ascending<F: Into<String>>(field: F) -> Self {
+        Self {
+            field: field.into(),
+            descending: false,
+        }
+    }
+
+    /// Sorts the objects by the given field, from the largest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListOrdering;
+    ///
+    /// let ordering = ListOrdering::descending("username");
+    /// assert!(ordering.is_descending());
+    /// ```
+    #[must_use]
+    pub fn descending<F: Into<String>>(field: F) -> Self {
+        Self {
+            field: field.into(),
+            descending: true,
+        }
+    }
+
+    /// Returns the name of the field the objects are sorted by.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListOrdering;
+    ///
+    /// assert_eq!(ListOrdering::ascending("username").field(), "username");
+    /// ```
+    #[must_use]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Returns whether the objects are sorted from the largest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::ListOrdering;
+    ///
+    /// assert!(!ListOrdering::ascending("username").is_descending());
+    /// ```
+    #[must_use]
+    pub fn is_descending(&self) -> bool {
+        self.descending
+    }
+}
+
+/// A filter shown in the sidebar of the admin model list.
+///
+/// The filters are usually declared with the `#[admin(list_filter = [...])]`
+/// attribute of the [`AdminModel`](derive@AdminModel) derive macro.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{ListFilter, ListFilterKind};
+///
+/// let filter = ListFilter::new("is_active", "Is active", ListFilterKind::Bool);
+/// assert_eq!(filter.name(), "is_active");
+/// assert_eq!(filter.label(), "Is active");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListFilter {
+    name: Cow<'static, str>,
+    label: Cow<'static, str>,
+    kind: ListFilterKind,
+}
+
+impl ListFilter {
+    /// Creates a new filter on the field with the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilter, ListFilterKind};
+    ///
+    /// let filter = ListFilter::new("created_at", "Created at", ListFilterKind::DateRange);
+    /// ```
+    #[must_use]
+    pub fn new<N, L>(name: N, label: L, kind: ListFilterKind) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+        L: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            kind,
+        }
+    }
+
+    /// Returns the name of the filtered field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilter, ListFilterKind};
+    ///
+    /// let filter = ListFilter::new("is_active", "Is active", ListFilterKind::Bool);
+    /// assert_eq!(filter.name(), "is_active");
+    /// ```
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the label shown above the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilter, ListFilterKind};
+    ///
+    /// let filter = ListFilter::new("is_active", "Is active", ListFilterKind::Bool);
+    /// assert_eq!(filter.label(), "Is active");
+    /// ```
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the kind of the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{ListFilter, ListFilterKind};
+    ///
+    /// let filter = ListFilter::new("is_active", "Is active", ListFilterKind::Bool);
+    /// assert_eq!(filter.kind(), &ListFilterKind::Bool);
+    /// ```
+    #[must_use]
+    pub fn kind(&self) -> &ListFilterKind {
+        &self.kind
+    }
+}
+
+/// The kind of a [`ListFilter`], which determines how it's shown in the
+/// sidebar and what [`ListFilterValue`]s it produces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListFilterKind {
+    /// A yes/no filter, producing [`ListFilterValue::Bool`].
+    Bool,
+    /// A filter selecting one of the given choices, producing
+    /// [`ListFilterValue::Choice`]. Each choice is a pair of its ID and the
+    /// label shown in the sidebar.
+    Choice(Vec<(String, String)>),
+    /// A filter selecting a range of dates, producing
+    /// [`ListFilterValue::DateRange`].
+    DateRange,
+}
+
+/// The value a field is filtered by in a [`ListQuery`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListFilterValue {
+    /// Matches the objects where the field is equal to the given boolean.
+    Bool(bool),
+    /// Matches the objects where the field is equal to the choice with the
+    /// given ID.
+    Choice(String),
+    /// Matches the objects where the field is in the given range. Both bounds
+    /// are optional; the start is inclusive, and the end is exclusive.
+    ///
+    /// The bounds are the midnights that start the selected days in the
+    /// [configured time zone](crate::config::AdminConfig::time_zone).
+    DateRange {
+        /// The start of the range (inclusive).
+        start: Option<DateTime<FixedOffset>>,
+        /// The end of the range (exclusive).
+        end: Option<DateTime<FixedOffset>>,
+    },
+}
+
+/// A field type that the list of objects in the admin panel can be filtered
+/// by.
+///
+/// This is implemented for booleans, dates, date-times, and the
+/// [`SelectChoice`](crate::form::fields::SelectChoice) types that can be
+/// stored in the database. It has to be implemented for every field listed in
+/// the `#[admin(list_filter = [...])]` attribute of the
+/// [`AdminModel`](derive@AdminModel) derive macro.
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::{ListFilterField, ListFilterKind, ListFilterValue};
+/// use cot::db::query::Expr;
+///
+/// assert_eq!(bool::list_filter_kind(), ListFilterKind::Bool);
+/// assert_eq!(
+///     bool::list_filter_expr(Expr::field("is_active"), &ListFilterValue::Bool(true)),
+///     Some(Expr::eq(Expr::field("is_active"), Expr::value(true)))
+/// );
+/// ```
+#[cfg(feature = "db")]
+#[diagnostic::on_unimplemented(
+    message = "the admin model list cannot be filtered by `{Self}`",
+    label = "`{Self}` does not implement `ListFilterField`",
+    note = "implement `cot::admin::ListFilterField` for `{Self}`, or remove the field from `list_filter`"
+)]
+pub trait ListFilterField {
+    /// Returns the kind of the filter shown in the sidebar.
+    fn list_filter_kind() -> ListFilterKind;
+
+    /// Returns the expression matching the rows where `field` matches
+    /// `value`, or [`None`] if the value cannot be used to filter this type.
+    fn list_filter_expr(
+        field: crate::db::query::Expr,
+        value: &ListFilterValue,
+    ) -> Option<crate::db::query::Expr>;
+}
+
+#[cfg(feature = "db")]
+impl ListFilterField for bool {
+    fn list_filter_kind() -> ListFilterKind {
+        ListFilterKind::Bool
+    }
+
+    fn list_filter_expr(
+        field: crate::db::query::Expr,
+        value: &ListFilterValue,
+    ) -> Option<crate::db::query::Expr> {
+        use crate::db::query::Expr;
+
+        match value {
+            ListFilterValue::Bool(value) => Some(Expr::eq(field, Expr::value(*value))),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+macro_rules! impl_list_filter_field_for_date {
+    ($ty:ty, $convert:expr) => {
+        impl ListFilterField for $ty {
+            fn list_filter_kind() -> ListFilterKind {
+                ListFilterKind::DateRange
+            }
+
+            fn list_filter_expr(
+                field: crate::db::query::Expr,
+                value: &ListFilterValue,
+            ) -> Option<crate::db::query::Expr> {
+                date_range_expr(field, value, $convert)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "db")]
+impl_list_filter_field_for_date!(NaiveDate, |bound: DateTime<FixedOffset>| bound.date_naive());
+#[cfg(feature = "db")]
+impl_list_filter_field_for_date!(NaiveDateTime, |bound: DateTime<FixedOffset>| bound
+    .naive_local());
+#[cfg(feature = "db")]
+impl_list_filter_field_for_date!(DateTime<FixedOffset>, |bound: DateTime<FixedOffset>| bound
+    .to_utc()
+    .fixed_offset());
+#[cfg(feature = "db")]
+impl_list_filter_field_for_date!(DateTime<Utc>, |bound: DateTime<FixedOffset>| bound.to_utc());
+
+/// Returns the expression matching the rows where `field` is within the date
+/// range, with the bounds converted to the type of the field using `convert`.
+#[cfg(feature = "db")]
+fn date_range_expr<T, F>(
+    field: crate::db::query::Expr,
+    value: &ListFilterValue,
+    convert: F,
+) -> Option<crate::db::query::Expr>
+where
+    T: crate::db::ToDbFieldValue,
+    F: Fn(DateTime<FixedOffset>) -> T,
+{
+    use crate::db::query::Expr;
+
+    let ListFilterValue::DateRange { start, end } = value else {
+        return None;
+    };
+    let start = start.map(|start| Expr::gte(field.clone(), Expr::value(convert(start))));
+    let end = end.map(|end| Expr::lt(field, Expr::value(convert(end))));
+
+    match (start, end) {
+        (Some(start), Some(end)) => Some(Expr::and(start, end)),
+        (start, end) => start.or(end),
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T: ListFilterField> ListFilterField for Option<T> {
+    fn list_filter_kind() -> ListFilterKind {
+        T::list_filter_kind()
+    }
+
+    fn list_filter_expr(
+        field: crate::db::query::Expr,
+        value: &ListFilterValue,
+    ) -> Option<crate::db::query::Expr> {
+        T::list_filter_expr(field, value)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T> ListFilterField for T
+where
+    T: crate::form::fields::SelectChoice + crate::db::ToDbFieldValue,
+{
+    fn list_filter_kind() -> ListFilterKind {
+        use crate::form::fields::SelectChoice;
+
+        ListFilterKind::Choice(
+            T::default_choices()
+                .iter()
+                .map(|choice| (choice.id(), SelectChoice::to_string(choice)))
+                .collect(),
+        )
+    }
+
+    fn list_filter_expr(
+        field: crate::db::query::Expr,
+        value: &ListFilterValue,
+    ) -> Option<crate::db::query::Expr> {
+        use crate::db::query::Expr;
+
+        match value {
+            ListFilterValue::Choice(id) => T::from_str(id)
+                .ok()
+                .map(|choice| Expr::eq(field, Expr::value(choice))),
+            _ => None,
+        }
+    }
+}
+
+#[repr(transparent)]
+struct AdminModelManagers(Vec<Box<dyn AdminModelManager>>);
+
+impl FromRequestHead for AdminModelManagers {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let managers = head
+            .context()
+            .apps()
+            .iter()
+            .flat_map(|app| app.admin_model_managers())
+            .collect();
+        Ok(Self(managers))
+    }
+}
+
+/// The result of saving an object using the form data from a request.
+///
+/// This is returned by [`AdminModelManager::save_from_request`] and
+/// [`AdminModel::save_from_request`].
+#[derive(Debug)]
+pub enum SaveResult {
+    /// The object has been saved.
+    Saved(#[debug("..")] Box<dyn AdminModel>),
+    /// The form data is invalid; contains the form context with the errors to
+    /// show to the user.
+    Invalid(Box<dyn FormContext + Send>),
+}
+
+/// A trait for adding admin models to the app.
+///
+/// This exposes an API over [`AdminModel`] that is dyn-compatible and
+/// hence can be dynamically added to the project.
+///
+/// See [`DefaultAdminModelManager`] for an automatic implementation of this
+/// trait.
+#[async_trait]
+pub trait AdminModelManager: Send + Sync {
+    /// Returns the display name of the model.
+    fn name(&self) -> &str;
+
+    /// Returns the URL slug for the model.
+    fn url_name(&self) -> &str;
+
+    /// Returns the name of the database table of the model.
+    ///
+    /// This is used to find the model referenced by a
+    /// [`ForeignKeyField`](crate::form::fields::ForeignKeyField) when filling
+    /// it in with the autocomplete widget. The default implementation returns
+    /// `None`, which disables the widget for the fields referencing this model.
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the codename of the given admin permission for this model.
+    ///
+    /// By default, this is built from the [URL slug](Self::url_name) of the
+    /// model using [`AdminPermission::codename_for`].
+    fn permission_codename(&self, permission: AdminPermission) -> String {
+        permission.codename_for(self.url_name())
+    }
+
+    /// Returns the columns shown in the list of objects of this model.
+    ///
+    /// If this is empty (which is the default), the list shows the [display
+    /// text](AdminModel::display) of each object instead.
+    fn list_columns(&self) -> Vec<ListColumn> {
+        Vec::new()
+    }
+
+    /// Returns the names of the fields searched in the list of objects of
+    /// this model.
+    ///
+    /// If this is empty (which is the default), the search box is not shown.
+    fn search_fields(&self) -> Vec<Cow<'static, str>> {
+        Vec::new()
+    }
+
+    /// Returns the filters shown in the sidebar of the list of objects of this
+    /// model.
+    ///
+    /// If this is empty (which is the default), the sidebar is not shown.
+    fn list_filters(&self) -> Vec<ListFilter> {
+        Vec::new()
+    }
+
+    /// Returns the custom actions that can be performed on the objects
+    /// selected in the list of objects of this model.
+    ///
+    /// The built-in action removing the selected objects is always available
+    /// to the users having the [`AdminPermission::Delete`] permission, so it
+    /// should not be returned here. The default implementation returns no
+    /// custom actions.
+    fn actions(&self) -> Vec<AdminAction> {
+        Vec::new()
+    }
+
+    /// Returns the related models whose objects are edited inline on the edit
+    /// page of an object of this model.
+    ///
+    /// The default implementation returns no inlines.
+    fn inlines(&self) -> Vec<AdminInline> {
+        Vec::new()
+    }
+
+    /// Returns the IDs of the form fields shown, but not editable, on the edit
+    /// page of an object of this model for the given user.
+    ///
+    /// The read-only fields can still be filled in when creating a new object.
+    /// Override this to decide which fields the user can edit, for instance
+    /// based on their permissions. The default implementation returns no
+    /// fields.
+    fn readonly_fields(&self, user: &dyn User) -> Vec<Cow<'static, str>> {
+        let _ = user;
+        Vec::new()
+    }
+
+    /// Returns the IDs of the form fields not shown at all on the edit page of
+    /// an object of this model for the given user.
+    ///
+    /// The excluded fields keep their values when an existing object is
+    /// changed, and are left empty when a new object is created, so they
+    /// should accept empty values (like [`Option`] fields do). The default
+    /// implementation returns no fields.
+    fn excluded_fields(&self, user: &dyn User) -> Vec<Cow<'static, str>> {
+        let _ = user;
+        Vec::new()
+    }
+
+    /// Returns the ID of the form field holding the version of an object of
+    /// this model used for optimistic locking, if any.
+    ///
+    /// This field is rendered as a hidden input on the edit page instead of
+    /// being editable. The default implementation returns `None`.
+    fn version_field(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the list of objects of this model that match the given query.
+    async fn get_objects(
+        &self,
+        request: &Request,
+        query: &ListQuery,
+        pagination: Pagination,
+    ) -> cot::Result<Vec<Box<dyn AdminModel>>>;
+
+    /// Returns the total count of objects of this model that match the given
+    /// query.
+    async fn get_total_object_counts(
+        &self,
+        request: &Request,
+        query: &ListQuery,
+    ) -> cot::Result<u64>;
+
+    /// Returns the object with the given ID.
+    async fn get_object_by_id(
+        &self,
+        request: &Request,
+        id: &str,
+    ) -> cot::Result<Option<Box<dyn AdminModel>>>;
+
+    /// Returns the objects with the given IDs, skipping the IDs of the objects
+    /// that don't exist. The objects can be returned in any order.
+    ///
+    /// The default implementation calls [`Self::get_object_by_id`] for each
+    /// of the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the IDs is invalid.
+    ///
+    /// Returns an error if the objects could not be fetched, for example,
+    /// a database error.
+    async fn get_objects_by_ids(
+        &self,
+        request: &Request,
+        ids: &[String],
+    ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
+        let mut objects = Vec::with_capacity(ids.len());
+        for id in ids {
+            objects.extend(self.get_object_by_id(request, id).await?);
+        }
+        Ok(objects)
+    }
+
+    /// Returns an empty form context for this model.
+    fn form_context(&self) -> Box<dyn FormContext>;
+
+    /// Returns a form context pre-filled with the data from given object.
+    ///
+    /// It is guaranteed that `object` parameter is an object returned by one of
+    /// the [`Self::get_objects`], [`Self::get_object_by_id`], or
+    /// [`Self::save_from_request`] methods. This means
+    /// that if you always return the same object type from these methods,
+    /// you can safely downcast the object to the same type in this method
+    /// as well.
+    async fn form_context_from_object(&self, object: Box<dyn AdminModel>) -> Box<dyn FormContext>;
+
+    /// Returns a form context filled with the form data from given request,
+    /// including the validation errors, without saving anything.
+    ///
+    /// This is used to show the form again when the object cannot be saved
+    /// for reasons unrelated to its own form, such as invalid
+    /// [inlines](Self::inlines). The `object_id` and `non_editable_fields`
+    /// parameters have the same meaning as in [`Self::save_from_request`].
+    ///
+    /// The default implementation doesn't read the form data; it returns the
+    /// form of the object with the given ID as returned by
+    /// [`Self::form_context_from_object`], or an empty form as returned by
+    /// [`Self::form_context`], so the values submitted by the user are not
+    /// shown again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form data could not be read from the request.
+    async fn form_context_from_request(
+        &self,
+        request: &mut Request,
+        object_id: Option<&str>,
+        non_editable_fields: &[&str],
+    ) -> cot::Result<Box<dyn FormContext>> {
+        let _ = non_editable_fields;
+        let object = match object_id {
+            Some(object_id) => self.get_object_by_id(request, object_id).await?,
+            None => None,
+        };
+
+        match object {
+            Some(object) => Ok(self.form_context_from_object(object).await),
+            None => Ok(self.form_context()),
+        }
+    }
+
+    /// Saves the object with the given ID (or a new object if `object_id` is
+    /// `None`) by using the form data from given request.
+    ///
+    /// The values submitted for the fields with IDs in `non_editable_fields`
+    /// are ignored; these fields keep the values of the existing object, or
+    /// are left empty when a new object is created.
+    ///
+    /// Returns the saved object, or the form context with the validation
+    /// errors if the form data is invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object could not be saved, for instance
+    /// due to a database error.
+    async fn save_from_request(
+        &self,
+        request: &mut Request,
+        object_id: Option<&str>,
+        non_editable_fields: &[&str],
+    ) -> cot::Result<SaveResult>;
+
+    /// Removes the object with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object with the given ID does not exist.
+    ///
+    /// Returns an error if the object could not be removed, for example,
+    /// a database error.
+    async fn remove_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()>;
+
+    /// Removes the objects with the given IDs.
+    ///
+    /// The default implementation calls [`Self::remove_by_id`] for each of
+    /// the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the IDs is invalid.
+    ///
+    /// Returns an error if the objects could not be removed, for example,
+    /// a database error.
+    async fn remove_by_ids(&self, request: &mut Request, object_ids: &[String]) -> cot::Result<()> {
+        for object_id in object_ids {
+            self.remove_by_id(request, object_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the removed objects of this model can be restored.
+    ///
+    /// The default implementation returns `false`.
+    fn is_restorable(&self) -> bool {
+        false
+    }
 
-    /// Saves the object by using the form data from given request.
+    /// Returns the list of removed objects of this model that can be
+    /// restored.
     ///
-    /// # Errors
-    ///
-    /// Returns an error if the object could not be saved, for instance
-    /// due to a database error.
-    async fn save_from_request(
+    /// The default implementation returns an empty list.
+    async fn get_removed_objects(
         &self,
-        request: &mut Request,
-        object_id: Option<&str>,
-    ) -> cot::Result<Option<Box<dyn FormContext>>>;
+        request: &Request,
+        pagination: Pagination,
+    ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
+        let _ = (request, pagination);
+        Ok(Vec::new())
+    }
 
-    /// Removes the object with the given ID.
+    /// Returns the total count of removed objects of this model.
+    ///
+    /// The default implementation returns 0.
+    async fn get_total_removed_object_counts(&self, request: &Request) -> cot::Result<u64> {
+        let _ = request;
+        Ok(0)
+    }
+
+    /// Restores the removed object with the given ID.
+    ///
+    /// The default implementation always returns a "not found" error.
     ///
     /// # Errors
     ///
-    /// Returns an error if the object with the given ID does not exist.
+    /// Returns an error if there is no removed object with the given ID.
     ///
-    /// Returns an error if the object could not be removed, for example,
+    /// Returns an error if the object could not be restored, for example,
     /// a database error.
-    async fn remove_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()>;
+    async fn restore_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()> {
+        let _ = request;
+        Err(Error::from(NotFound::with_message(format!(
+            "Removed object with ID `{object_id}` not found"
+        ))))
+    }
 }
 
 /// A default implementation of [`AdminModelManager`] for an [`AdminModel`].
 #[derive(Debug)]
 pub struct DefaultAdminModelManager<T> {
+    actions: Vec<AdminAction>,
+    inlines: Vec<AdminInline>,
     phantom_data: PhantomData<T>,
 }
 
@@ -478,9 +3089,44 @@ impl<T> DefaultAdminModelManager<T> {
     #[must_use]
     pub const fn new() -> Self {
         Self {
+            actions: Vec::new(),
+            inlines: Vec::new(),
             phantom_data: PhantomData,
         }
     }
+
+    /// Adds a custom action that can be performed on the objects selected in
+    /// the list of objects of the model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::{AdminAction, AdminModelManager, DefaultAdminModelManager};
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::request::Request;
+    ///
+    /// let manager = DefaultAdminModelManager::<DatabaseUser>::new().with_action(AdminAction::new(
+    ///     "deactivate",
+    ///     "Deactivate selected",
+    ///     |_request: Request, _ids: Vec<String>| async { Ok(None) },
+    /// ));
+    /// assert_eq!(manager.actions().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn with_action(mut self, action: AdminAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Adds a related model whose objects are edited inline on the edit page
+    /// of an object of the model.
+    ///
+    /// See [`AdminInline`] for an example.
+    #[must_use]
+    pub fn with_inline(mut self, inline: AdminInline) -> Self {
+        self.inlines.push(inline);
+        self
+    }
 }
 
 #[async_trait]
@@ -493,22 +3139,65 @@ impl<T: AdminModel + Send + Sync + 'static> AdminModelManager for DefaultAdminMo
         T::url_name()
     }
 
-    async fn get_total_object_counts(&self, request: &Request) -> cot::Result<u64> {
-        T::get_total_object_counts(request).await
+    fn table_name(&self) -> Option<&str> {
+        T::table_name()
+    }
+
+    fn list_columns(&self) -> Vec<ListColumn> {
+        T::list_columns()
+    }
+
+    fn search_fields(&self) -> Vec<Cow<'static, str>> {
+        T::search_fields()
+    }
+
+    fn list_filters(&self) -> Vec<ListFilter> {
+        T::list_filters()
+    }
+
+    fn actions(&self) -> Vec<AdminAction> {
+        self.actions.clone()
+    }
+
+    fn inlines(&self) -> Vec<AdminInline> {
+        self.inlines.clone()
+    }
+
+    fn readonly_fields(&self, _user: &dyn User) -> Vec<Cow<'static, str>> {
+        T::readonly_fields()
+    }
+
+    fn excluded_fields(&self, _user: &dyn User) -> Vec<Cow<'static, str>> {
+        T::excluded_fields()
+    }
+
+    fn version_field(&self) -> Option<&str> {
+        T::version_field()
+    }
+
+    async fn get_total_object_counts(
+        &self,
+        request: &Request,
+        query: &ListQuery,
+    ) -> cot::Result<u64> {
+        T::get_total_object_counts(request, query).await
     }
 
     async fn get_objects(
         &self,
         request: &Request,
+        query: &ListQuery,
         pagination: Pagination,
     ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
         #[expect(trivial_casts)] // Upcast to the correct Box type
-        T::get_objects(request, pagination).await.map(|objects| {
-            objects
-                .into_iter()
-                .map(|object| Box::new(object) as Box<dyn AdminModel>)
-                .collect()
-        })
+        T::get_objects(request, query, pagination)
+            .await
+            .map(|objects| {
+                objects
+                    .into_iter()
+                    .map(|object| Box::new(object) as Box<dyn AdminModel>)
+                    .collect()
+            })
     }
 
     async fn get_object_by_id(
@@ -522,6 +3211,20 @@ impl<T: AdminModel + Send + Sync + 'static> AdminModelManager for DefaultAdminMo
             .map(|object| object.map(|object| Box::new(object) as Box<dyn AdminModel>))
     }
 
+    async fn get_objects_by_ids(
+        &self,
+        request: &Request,
+        ids: &[String],
+    ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
+        #[expect(trivial_casts)] // Upcast to the correct Box type
+        T::get_objects_by_ids(request, ids).await.map(|objects| {
+            objects
+                .into_iter()
+                .map(|object| Box::new(object) as Box<dyn AdminModel>)
+                .collect()
+        })
+    }
+
     fn form_context(&self) -> Box<dyn FormContext> {
         T::form_context()
     }
@@ -533,17 +3236,62 @@ impl<T: AdminModel + Send + Sync + 'static> AdminModelManager for DefaultAdminMo
         T::form_context_from_self(object_casted).await
     }
 
+    async fn form_context_from_request(
+        &self,
+        request: &mut Request,
+        object_id: Option<&str>,
+        non_editable_fields: &[&str],
+    ) -> cot::Result<Box<dyn FormContext>> {
+        #[expect(trivial_casts)] // Upcast to the correct Box type
+        T::form_context_from_request(request, object_id, non_editable_fields)
+            .await
+            .map(|context| context as Box<dyn FormContext>)
+    }
+
     async fn save_from_request(
         &self,
         request: &mut Request,
         object_id: Option<&str>,
-    ) -> cot::Result<Option<Box<dyn FormContext>>> {
-        T::save_from_request(request, object_id).await
+        non_editable_fields: &[&str],
+    ) -> cot::Result<SaveResult> {
+        T::save_from_request(request, object_id, non_editable_fields).await
     }
 
     async fn remove_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()> {
         T::remove_by_id(request, object_id).await
     }
+
+    async fn remove_by_ids(&self, request: &mut Request, object_ids: &[String]) -> cot::Result<()> {
+        T::remove_by_ids(request, object_ids).await
+    }
+
+    fn is_restorable(&self) -> bool {
+        T::is_restorable()
+    }
+
+    async fn get_removed_objects(
+        &self,
+        request: &Request,
+        pagination: Pagination,
+    ) -> cot::Result<Vec<Box<dyn AdminModel>>> {
+        #[expect(trivial_casts)] // Upcast to the correct Box type
+        T::get_removed_objects(request, pagination)
+            .await
+            .map(|objects| {
+                objects
+                    .into_iter()
+                    .map(|object| Box::new(object) as Box<dyn AdminModel>)
+                    .collect()
+            })
+    }
+
+    async fn get_total_removed_object_counts(&self, request: &Request) -> cot::Result<u64> {
+        T::get_total_removed_object_counts(request).await
+    }
+
+    async fn restore_by_id(&self, request: &mut Request, object_id: &str) -> cot::Result<()> {
+        T::restore_by_id(request, object_id).await
+    }
 }
 
 /// A model that can be managed by the admin panel.
@@ -554,13 +3302,18 @@ impl<T: AdminModel + Send + Sync + 'static> AdminModelManager for DefaultAdminMo
     note = "add #[derive(cot::admin::AdminModel)] to the struct to automatically derive the trait"
 )]
 pub trait AdminModel: Any + Send + 'static {
-    /// Get the objects of this model.
-    async fn get_objects(request: &Request, pagination: Pagination) -> cot::Result<Vec<Self>>
+    /// Get the objects of this model that match the given query.
+    async fn get_objects(
+        request: &Request,
+        query: &ListQuery,
+        pagination: Pagination,
+    ) -> cot::Result<Vec<Self>>
     where
         Self: Sized;
 
-    /// Get the total count of objects of this model.
-    async fn get_total_object_counts(request: &Request) -> cot::Result<u64>
+    /// Get the total count of objects of this model that match the given
+    /// query.
+    async fn get_total_object_counts(request: &Request, query: &ListQuery) -> cot::Result<u64>
     where
         Self: Sized;
 
@@ -569,6 +3322,30 @@ pub trait AdminModel: Any + Send + 'static {
     where
         Self: Sized;
 
+    /// Returns the objects with the given IDs, skipping the IDs of the objects
+    /// that don't exist. The objects can be returned in any order.
+    ///
+    /// The default implementation calls [`Self::get_object_by_id`] for each
+    /// of the IDs; the derived implementation fetches them with a single
+    /// query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the IDs is invalid.
+    ///
+    /// Returns an error if the objects could not be fetched, for example,
+    /// a database error.
+    async fn get_objects_by_ids(request: &Request, ids: &[String]) -> cot::Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        let mut objects = Vec::with_capacity(ids.len());
+        for id in ids {
+            objects.extend(Self::get_object_by_id(request, id).await?);
+        }
+        Ok(objects)
+    }
+
     /// Get the display name of this model.
     fn name() -> &'static str
     where
@@ -579,12 +3356,111 @@ pub trait AdminModel: Any + Send + 'static {
     where
         Self: Sized;
 
+    /// Get the name of the database table of this model, if it has one.
+    ///
+    /// The [`AdminModel`](derive@AdminModel) derive macro returns
+    /// [`Model::TABLE_NAME`](crate::db::Model::TABLE_NAME) here. The default
+    /// implementation returns `None`.
+    #[must_use]
+    fn table_name() -> Option<&'static str>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// Get the ID of this model instance as a [`String`].
     fn id(&self) -> String;
 
     /// Get the display text of this model instance.
     fn display(&self) -> String;
 
+    /// Get the columns shown in the list of objects of this model.
+    ///
+    /// The default implementation returns an empty list, in which case the
+    /// [display text](Self::display) of each object is shown instead.
+    #[must_use]
+    fn list_columns() -> Vec<ListColumn>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Get the values of the [list columns](Self::list_columns) for this model
+    /// instance, in the same order as the columns.
+    ///
+    /// The default implementation returns an empty list.
+    fn list_values(&self) -> Vec<ListValue> {
+        Vec::new()
+    }
+
+    /// Get the names of the fields searched in the list of objects of this
+    /// model.
+    ///
+    /// The default implementation returns an empty list, in which case the
+    /// search box is not shown.
+    #[must_use]
+    fn search_fields() -> Vec<Cow<'static, str>>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Get the filters shown in the sidebar of the list of objects of this
+    /// model.
+    ///
+    /// The default implementation returns an empty list, in which case the
+    /// sidebar is not shown.
+    #[must_use]
+    fn list_filters() -> Vec<ListFilter>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Get the IDs of the form fields shown, but not editable, on the edit
+    /// page of an object of this model.
+    ///
+    /// The default implementation returns an empty list.
+    #[must_use]
+    fn readonly_fields() -> Vec<Cow<'static, str>>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Get the IDs of the form fields not shown on the edit page of an object
+    /// of this model.
+    ///
+    /// The default implementation returns an empty list.
+    #[must_use]
+    fn excluded_fields() -> Vec<Cow<'static, str>>
+    where
+        Self: Sized,
+    {
+        Vec::new()
+    }
+
+    /// Get the ID of the form field holding the version of an object of this
+    /// model used for optimistic locking, if any.
+    ///
+    /// This field is rendered as a hidden input on the edit page, so that the
+    /// object is only saved if it hasn't been changed since the page was
+    /// opened (see
+    /// [`Model::VERSION_COLUMN`](crate::db::Model::VERSION_COLUMN)). The
+    /// default implementation returns `None`.
+    #[must_use]
+    fn version_field() -> Option<&'static str>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// Get the form context for this model.
     fn form_context() -> Box<dyn FormContext>
     where
@@ -593,7 +3469,31 @@ pub trait AdminModel: Any + Send + 'static {
     /// Get the form context with the data pre-filled from this model instance.
     async fn form_context_from_self(&self) -> Box<dyn FormContext>;
 
-    /// Save the model instance from the form data in the request.
+    /// Get the form context filled with the form data from the request,
+    /// including the validation errors, without saving anything.
+    ///
+    /// See [`Self::save_from_request`] for the meaning of the parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form data could not be read from the request.
+    async fn form_context_from_request(
+        request: &mut Request,
+        object_id: Option<&str>,
+        non_editable_fields: &[&str],
+    ) -> cot::Result<Box<dyn FormContext + Send>>
+    where
+        Self: Sized;
+
+    /// Save the model instance with the given ID (or a new instance if
+    /// `object_id` is `None`) from the form data in the request.
+    ///
+    /// The values submitted for the fields with IDs in `non_editable_fields`
+    /// are ignored; these fields keep the values of the existing instance, or
+    /// are left empty when a new instance is created.
+    ///
+    /// Returns the saved model instance, or the form context with the
+    /// validation errors if the form data is invalid.
     ///
     /// # Errors
     ///
@@ -602,7 +3502,8 @@ pub trait AdminModel: Any + Send + 'static {
     async fn save_from_request(
         request: &mut Request,
         object_id: Option<&str>,
-    ) -> cot::Result<Option<Box<dyn FormContext>>>
+        non_editable_fields: &[&str],
+    ) -> cot::Result<SaveResult>
     where
         Self: Sized;
 
@@ -617,10 +3518,98 @@ pub trait AdminModel: Any + Send + 'static {
     async fn remove_by_id(request: &mut Request, object_id: &str) -> cot::Result<()>
     where
         Self: Sized;
+
+    /// Remove the model instances with the given IDs.
+    ///
+    /// The default implementation calls [`Self::remove_by_id`] for each of
+    /// the IDs; the derived implementation removes them with a single query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the IDs is invalid.
+    ///
+    /// Returns an error if the objects could not be removed, for example,
+    /// a database error.
+    async fn remove_by_ids(request: &mut Request, object_ids: &[String]) -> cot::Result<()>
+    where
+        Self: Sized,
+    {
+        for object_id in object_ids {
+            Self::remove_by_id(request, object_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the removed instances of this model can be restored.
+    ///
+    /// This is the case for soft-deletable models (see
+    /// [`Model::SOFT_DELETE_COLUMN`](crate::db::Model::SOFT_DELETE_COLUMN)).
+    /// The default implementation returns `false`.
+    #[must_use]
+    fn is_restorable() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Get the removed objects of this model that can be restored.
+    ///
+    /// The default implementation returns an empty list.
+    async fn get_removed_objects(
+        request: &Request,
+        pagination: Pagination,
+    ) -> cot::Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        let _ = (request, pagination);
+        Ok(Vec::new())
+    }
+
+    /// Get the total count of removed objects of this model.
+    ///
+    /// The default implementation returns 0.
+    async fn get_total_removed_object_counts(request: &Request) -> cot::Result<u64>
+    where
+        Self: Sized,
+    {
+        let _ = request;
+        Ok(0)
+    }
+
+    /// Restore the removed model instance with the given ID.
+    ///
+    /// The default implementation always returns a "not found" error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no removed object with the given ID.
+    ///
+    /// Returns an error if the object could not be restored, for example,
+    /// a database error.
+    async fn restore_by_id(request: &mut Request, object_id: &str) -> cot::Result<()>
+    where
+        Self: Sized,
+    {
+        let _ = request;
+        Err(Error::from(NotFound::with_message(format!(
+            "Removed object with ID `{object_id}` not found"
+        ))))
+    }
 }
 
 /// The admin app.
 ///
+/// The admin panel requires the
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware),
+/// [`AuthMiddleware`](crate::middleware::AuthMiddleware), and
+/// [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) to be enabled.
+///
+/// Only active [staff members](User::is_staff) and
+/// [superusers](User::is_superuser) can log in to the admin panel. What they
+/// can do with each model is controlled by the [`AdminPermission`]s.
+///
 /// # Examples
 ///
 /// ```
@@ -672,6 +3661,11 @@ impl App for AdminApp {
         "cot_admin"
     }
 
+    #[cfg(feature = "db")]
+    fn migrations(&self) -> Vec<Box<crate::db::migrations::SyncDynMigration>> {
+        crate::db::migrations::wrap_migrations(log::migrations::MIGRATIONS)
+    }
+
     fn router(&self) -> Router {
         Router::with_urls([
             crate::router::Route::with_handler_and_name(
@@ -680,6 +3674,18 @@ impl App for AdminApp {
                 "index",
             ),
             crate::router::Route::with_handler_and_name("/login/", login, "login"),
+            #[cfg(feature = "json")]
+            crate::router::Route::with_handler_and_name(
+                "/autocomplete/",
+                AdminAuthenticated::new(autocomplete::autocomplete),
+                "autocomplete",
+            ),
+            #[cfg(feature = "db")]
+            crate::router::Route::with_handler_and_name(
+                "/login/two-factor/",
+                login_two_factor,
+                "login_two_factor",
+            ),
             crate::router::Route::with_handler_and_name(
                 "/{model_name}/",
                 AdminAuthenticated::new(view_model),
@@ -695,11 +3701,42 @@ impl App for AdminApp {
                 AdminAuthenticated::new(edit_model_instance),
                 "edit_model_instance",
             ),
+            #[cfg(feature = "db")]
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/{pk}/history/",
+                AdminAuthenticated::new(view_model_instance_history),
+                "view_model_instance_history",
+            ),
             crate::router::Route::with_handler_and_name(
                 "/{model_name}/{pk}/remove/",
                 AdminAuthenticated::new(remove_model_instance),
                 "remove_model_instance",
             ),
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/export/",
+                AdminAuthenticated::new(import_export::export_model),
+                "export_model",
+            ),
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/import/",
+                AdminAuthenticated::new(import_export::import_model),
+                "import_model",
+            ),
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/actions/",
+                AdminAuthenticated::new(run_model_action),
+                "run_model_action",
+            ),
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/removed/",
+                AdminAuthenticated::new(view_removed_model_instances),
+                "view_removed_model_instances",
+            ),
+            crate::router::Route::with_handler_and_name(
+                "/{model_name}/{pk}/restore/",
+                AdminAuthenticated::new(restore_model_instance),
+                "restore_model_instance",
+            ),
         ])
     }
 
@@ -713,3 +3750,250 @@ impl App for AdminApp {
         )]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<ListFilter> {
+        vec![
+            ListFilter::new("is_active", "Is active", ListFilterKind::Bool),
+            ListFilter::new(
+                "status",
+                "Status",
+                ListFilterKind::Choice(vec![("draft".to_owned(), "Draft".to_owned())]),
+            ),
+            ListFilter::new("created_at", "Created at", ListFilterKind::DateRange),
+        ]
+    }
+
+    fn columns() -> Vec<ListColumn> {
+        vec![
+            ListColumn::new("name", "Name").sortable(),
+            ListColumn::new("computed", "Computed"),
+        ]
+    }
+
+    #[test]
+    fn list_params_to_list_query() {
+        let params = ListParams::from_query(
+            "q=+john+&o=-name&f.is_active=false&f.status=draft&f.created_at.from=2024-03-01&page=2",
+        );
+
+        let query = params.to_list_query(&columns(), &filters(), Tz::Europe__Warsaw);
+
+        let start = DateTime::parse_from_rfc3339("2024-03-01T00:00:00+01:00").unwrap();
+        assert_eq!(
+            query,
+            ListQuery::new()
+                .with_search("john")
+                .with_ordering(ListOrdering::descending("name"))
+                .with_filter("is_active", ListFilterValue::Bool(false))
+                .with_filter("status", ListFilterValue::Choice("draft".to_owned()))
+                .with_filter(
+                    "created_at",
+                    ListFilterValue::DateRange {
+                        start: Some(start),
+                        end: None
+                    }
+                )
+        );
+    }
+
+    #[test]
+    fn list_params_to_list_query_date_range_end_is_exclusive() {
+        let params = ListParams::from_query("f.created_at.to=2024-12-31");
+
+        let query = params.to_list_query(&columns(), &filters(), Tz::UTC);
+
+        let end = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z").unwrap();
+        assert_eq!(
+            query.filter("created_at"),
+            Some(&ListFilterValue::DateRange {
+                start: None,
+                end: Some(end)
+            })
+        );
+    }
+
+    #[test]
+    fn list_params_to_list_query_ignores_invalid_params() {
+        let params = ListParams::from_query(
+            "q=+&o=computed&f.is_active=maybe&f.status=unknown&f.created_at.from=yesterday&f.other=1",
+        );
+
+        let query = params.to_list_query(&columns(), &filters(), Tz::UTC);
+
+        assert_eq!(query, ListQuery::new());
+    }
+
+    #[test]
+    fn list_params_urls() {
+        let params =
+            ListParams::from_query("q=john&f.created_at.from=2024-03-01&page=3&page_size=20");
+
+        assert_eq!(
+            params.url_with("f.is_active", "true"),
+            "?q=john&f.created_at.from=2024-03-01&page_size=20&f.is_active=true"
+        );
+        assert_eq!(params.url_without("f.created_at"), "?q=john&page_size=20");
+        assert_eq!(
+            params.page_url(4),
+            "?q=john&f.created_at.from=2024-03-01&page_size=20&page=4"
+        );
+        assert_eq!(
+            params.hidden_params("q"),
+            [("f.created_at.from", "2024-03-01"), ("page_size", "20")]
+        );
+    }
+
+    #[test]
+    fn list_params_sorting() {
+        let column = ListColumn::new("name", "Name").sortable();
+
+        let params = ListParams::from_query("");
+        assert_eq!(params.sort_direction(&column), "");
+        assert_eq!(params.sort_url(&column), "?o=name");
+
+        let params = ListParams::from_query("o=name");
+        assert_eq!(params.sort_direction(&column), "asc");
+        assert_eq!(params.sort_url(&column), "?o=-name");
+
+        let params = ListParams::from_query("o=-name");
+        assert_eq!(params.sort_direction(&column), "desc");
+        assert_eq!(params.sort_url(&column), "?o=name");
+    }
+
+    #[cfg(feature = "db")]
+    #[test]
+    fn list_filter_field_date_range() {
+        use crate::db::query::Expr;
+
+        let start = DateTime::parse_from_rfc3339("2024-03-01T00:00:00+01:00").unwrap();
+        let end = DateTime::parse_from_rfc3339("2024-03-02T00:00:00+01:00").unwrap();
+        let value = ListFilterValue::DateRange {
+            start: Some(start),
+            end: Some(end),
+        };
+
+        assert_eq!(
+            NaiveDate::list_filter_expr(Expr::field("day"), &value),
+            Some(Expr::and(
+                Expr::gte(
+                    Expr::field("day"),
+                    Expr::value(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())
+                ),
+                Expr::lt(
+                    Expr::field("day"),
+                    Expr::value(NaiveDate::from_ymd_opt(2024, 3, 2).unwrap())
+                ),
+            ))
+        );
+        assert_eq!(
+            <Option<DateTime<Utc>>>::list_filter_expr(Expr::field("created_at"), &value),
+            Some(Expr::and(
+                Expr::gte(Expr::field("created_at"), Expr::value(start.to_utc())),
+                Expr::lt(Expr::field("created_at"), Expr::value(end.to_utc())),
+            ))
+        );
+        assert_eq!(
+            bool::list_filter_expr(Expr::field("is_active"), &value),
+            None
+        );
+    }
+
+    #[test]
+    fn action_form_parse() {
+        let form = ActionForm::parse(b"csrf_token=abc&action=delete_selected&ids=1&ids=2");
+        assert_eq!(
+            form,
+            ActionForm {
+                action: "delete_selected".to_owned(),
+                ids: vec!["1".to_owned(), "2".to_owned()],
+                confirmed: false,
+            }
+        );
+
+        let form = ActionForm::parse(b"action=delete_selected&ids=a%26b&confirm=true");
+        assert_eq!(form.ids, vec!["a&b".to_owned()]);
+        assert!(form.confirmed);
+
+        let form = ActionForm::parse(b"action=delete_selected&ids=2&ids=1&ids=2");
+        assert_eq!(form.ids, vec!["2".to_owned(), "1".to_owned()]);
+    }
+
+    #[test]
+    fn model_permissions_has() {
+        let permissions = ModelPermissions {
+            view: true,
+            add: false,
+            change: false,
+            delete: true,
+        };
+
+        assert!(permissions.has(AdminPermission::View));
+        assert!(!permissions.has(AdminPermission::Add));
+        assert!(!permissions.has(AdminPermission::Change));
+        assert!(permissions.has(AdminPermission::Delete));
+    }
+
+    #[cot::test]
+    async fn admin_action_run() {
+        let action = AdminAction::new(
+            "count_selected",
+            "Count selected",
+            |_request: Request, ids: Vec<String>| async move {
+                Ok(Some(Html::new(ids.len().to_string()).into_response()?))
+            },
+        )
+        .with_permission(AdminPermission::View);
+
+        assert_eq!(action.name(), "count_selected");
+        assert_eq!(action.label(), "Count selected");
+        assert_eq!(action.permission(), AdminPermission::View);
+
+        let request = crate::test::TestRequestBuilder::get("/").build();
+        let response = action
+            .run(request, vec!["1".to_owned(), "2".to_owned()])
+            .await
+            .unwrap()
+            .unwrap();
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert_eq!(body, "2");
+    }
+
+    #[test]
+    fn form_values_changed_since() {
+        let previous = FormValues(vec![
+            ("title".to_owned(), "Hello".to_owned()),
+            ("body".to_owned(), "World".to_owned()),
+            ("published".to_owned(), String::new()),
+        ]);
+        let current = FormValues(vec![
+            ("title".to_owned(), "Hello".to_owned()),
+            ("body".to_owned(), "Everyone".to_owned()),
+            ("published".to_owned(), "on".to_owned()),
+        ]);
+
+        assert_eq!(current.changed_since(&previous), ["body", "published"]);
+        assert!(current.changed_since(&current).is_empty());
+        assert_eq!(
+            current.changed_since(&FormValues::default()),
+            ["title", "body", "published"]
+        );
+    }
+
+    #[test]
+    fn non_editable_fields() {
+        let fields = NonEditableFields {
+            readonly: vec![Cow::Borrowed("created_at")],
+            excluded: vec![Cow::Borrowed("password")],
+        };
+
+        assert_eq!(fields.ids(), ["created_at", "password"]);
+        assert!(fields.is_readonly("created_at"));
+        assert!(!fields.is_readonly("password"));
+        assert!(fields.is_excluded("password"));
+        assert!(!fields.is_excluded("title"));
+    }
+}
@@ -0,0 +1,450 @@
+//! The JSON endpoint used by the autocomplete widget of the
+//! [`ForeignKeyField`](crate::form::fields::ForeignKeyField)s in the admin
+//! panel.
+//!
+//! The endpoint finds the referenced model by its table name and returns a
+//! page of its objects matching the text typed by the user, each with its ID
+//! and [display text](super::AdminModel::display):
+//!
+//! ```json
+//! {"results": [{"id": "1", "text": "Buy milk"}], "more": false, "truncated": false}
+//! ```
+//!
+//! `truncated` is `true` when the model has no
+//! [search fields](super::AdminModelManager::search_fields) and the table was
+//! too large for its display texts to be searched in full, so some matching
+//! objects might be missing from the results.
+
+use cot_core::error::impl_into_cot_error;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    AdminModel, AdminModelManager, AdminModelManagers, BaseContext, ListQuery, Pagination,
+};
+use crate::Error;
+use crate::error::NotFound;
+use crate::json::Json;
+use crate::request::Request;
+use crate::request::extractors::UrlQuery;
+use crate::response::{IntoResponse, Response};
+
+/// The number of objects returned in a single page of results.
+const PAGE_SIZE: usize = 20;
+/// The number of objects fetched from the database at once when searching
+/// the display texts of the objects.
+const SCAN_BATCH_SIZE: usize = 100;
+/// The maximum number of objects whose display texts are searched, so that
+/// a single request can't go through a whole large table.
+const MAX_SCANNED_OBJECTS: usize = 1000;
+
+/// The requested page of results is too large.
+#[derive(Debug, Error)]
+#[error("invalid page number: {0}")]
+struct InvalidPage(u64);
+impl_into_cot_error!(InvalidPage, BAD_REQUEST);
+
+#[derive(Debug, Deserialize)]
+pub(super) struct AutocompleteParams {
+    /// The table name of the model to search.
+    model: String,
+    /// The text to search for.
+    #[serde(default)]
+    q: String,
+    /// The page of results, starting from 1.
+    page: Option<u64>,
+    /// The ID of a single object to return instead of searching, used to
+    /// show the display text of the currently selected object.
+    id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AutocompleteResponse {
+    results: Vec<AutocompleteResult>,
+    more: bool,
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AutocompleteResult {
+    id: String,
+    text: String,
+}
+
+impl AutocompleteResult {
+    fn from_object(object: &dyn AdminModel) -> Self {
+        Self {
+            id: object.id(),
+            text: object.display(),
+        }
+    }
+}
+
+pub(super) async fn autocomplete(
+    base_context: BaseContext,
+    AdminModelManagers(managers): AdminModelManagers,
+    UrlQuery(params): UrlQuery<AutocompleteParams>,
+    request: Request,
+) -> cot::Result<Response> {
+    let manager = managers
+        .into_iter()
+        .find(|manager| manager.table_name() == Some(params.model.as_str()))
+        .ok_or_else(|| {
+            Error::from(NotFound::with_message(format!(
+                "Model with table `{}` not found",
+                params.model
+            )))
+        })?;
+    base_context.check_view_permission(&*manager)?;
+
+    let found = if let Some(id) = &params.id {
+        let object = manager.get_object_by_id(&request, id).await?;
+        FoundObjects {
+            objects: object.into_iter().collect(),
+            more: false,
+            truncated: false,
+        }
+    } else {
+        let page = params.page.unwrap_or(1).max(1);
+        find_objects(&*manager, &request, params.q.trim(), page).await?
+    };
+
+    Json(AutocompleteResponse {
+        results: found
+            .objects
+            .iter()
+            .map(|object| AutocompleteResult::from_object(&**object))
+            .collect(),
+        more: found.more,
+        truncated: found.truncated,
+    })
+    .into_response()
+}
+
+/// A page of the objects found by [`find_objects`].
+struct FoundObjects {
+    objects: Vec<Box<dyn AdminModel>>,
+    /// Whether there are more matching objects after this page.
+    more: bool,
+    /// Whether the search stopped at [`MAX_SCANNED_OBJECTS`], so that some
+    /// matches might be missing.
+    truncated: bool,
+}
+
+/// Returns the given page of objects matching the search text.
+///
+/// The objects are searched in the [search
+/// fields](AdminModelManager::search_fields) of the model. If it has none,
+/// their display texts are searched instead, which requires fetching the
+/// objects from the database, so only the first [`MAX_SCANNED_OBJECTS`] of
+/// them are searched, and the results are marked as truncated if there are
+/// more.
+///
+/// # Errors
+///
+/// Returns a `400 Bad Request` error if the page number is too large.
+async fn find_objects(
+    manager: &dyn AdminModelManager,
+    request: &Request,
+    search: &str,
+    page: u64,
+) -> cot::Result<FoundObjects> {
+    // the offset has to fit in the signed integers used by the databases
+    let offset = (page - 1)
+        .checked_mul(PAGE_SIZE as u64)
+        .filter(|offset| i64::try_from(*offset).is_ok())
+        .ok_or(InvalidPage(page))?;
+
+    if search.is_empty() || !manager.search_fields().is_empty() {
+        let mut query = ListQuery::new();
+        if !search.is_empty() {
+            query = query.with_search(search);
+        }
+        // fetch one more object to know whether there is another page
+        let pagination = Pagination {
+            limit: PAGE_SIZE as u64 + 1,
+            offset,
+        };
+        let mut objects = manager.get_objects(request, &query, pagination).await?;
+        let more = objects.len() > PAGE_SIZE;
+        objects.truncate(PAGE_SIZE);
+        return Ok(FoundObjects {
+            objects,
+            more,
+            truncated: false,
+        });
+    }
+
+    let search = search.to_lowercase();
+    let mut to_skip = offset;
+    let mut objects = Vec::new();
+    let query = ListQuery::new();
+    let mut truncated = true;
+    for batch_page in 1..=(MAX_SCANNED_OBJECTS / SCAN_BATCH_SIZE) as u64 {
+        let batch = manager
+            .get_objects(
+                request,
+                &query,
+                Pagination::new(SCAN_BATCH_SIZE as u64, batch_page),
+            )
+            .await?;
+        let batch_len = batch.len();
+        for object in batch {
+            if !object.display().to_lowercase().contains(&search) {
+                continue;
+            }
+            if to_skip > 0 {
+                to_skip -= 1;
+            } else if objects.len() < PAGE_SIZE {
+                objects.push(object);
+            } else {
+                return Ok(FoundObjects {
+                    objects,
+                    more: true,
+                    truncated: false,
+                });
+            }
+        }
+        if batch_len < SCAN_BATCH_SIZE {
+            truncated = false;
+            break;
+        }
+    }
+
+    Ok(FoundObjects {
+        objects,
+        more: false,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Display, Formatter};
+
+    use cot::db::Auto;
+    use cot_macros::AdminModel;
+
+    use super::*;
+    use crate::StatusCode;
+    use crate::admin::DefaultAdminModelManager;
+    use crate::db::migrations::{
+        Field, Migration, MigrationDependency, Operation, wrap_migrations,
+    };
+    use crate::db::{DatabaseField, Identifier, Model, model};
+    use crate::form::Form;
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    #[admin(search_fields = [name])]
+    struct Author {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        name: String,
+    }
+
+    impl Display for Author {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Author {}", self.name)
+        }
+    }
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    struct Publisher {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        name: String,
+    }
+
+    impl Display for Publisher {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Publisher {}", self.name)
+        }
+    }
+
+    struct TestMigration;
+
+    impl Migration for TestMigration {
+        const APP_NAME: &'static str = "cot_autocomplete";
+        const MIGRATION_NAME: &'static str = "m_0001_initial";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[
+            Operation::create_model()
+                .table_name(<Author as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+                ])
+                .build(),
+            Operation::create_model()
+                .table_name(<Publisher as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+                ])
+                .build(),
+        ];
+    }
+
+    async fn setup(test_db: &mut TestDatabase) -> Request {
+        test_db
+            .add_migrations(wrap_migrations(&[&TestMigration]))
+            .run_migrations()
+            .await;
+
+        for index in 0..25 {
+            let name = if index % 2 == 0 { "Even" } else { "Odd" };
+            Author {
+                id: Auto::auto(),
+                name: format!("{name} {index}"),
+            }
+            .insert(&test_db.database())
+            .await
+            .unwrap();
+            Publisher {
+                id: Auto::auto(),
+                name: format!("{name} {index}"),
+            }
+            .insert(&test_db.database())
+            .await
+            .unwrap();
+        }
+
+        TestRequestBuilder::get("/")
+            .database(test_db.database())
+            .build()
+    }
+
+    fn texts(objects: &[Box<dyn AdminModel>]) -> Vec<String> {
+        objects.iter().map(|object| object.display()).collect()
+    }
+
+    #[test]
+    fn table_name() {
+        assert_eq!(
+            DefaultAdminModelManager::<Author>::new().table_name(),
+            Some(<Author as Model>::TABLE_NAME.as_str())
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn find_objects_pages(test_db: &mut TestDatabase) {
+        let request = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Author>::new();
+
+        let FoundObjects { objects, more, .. } =
+            find_objects(&manager, &request, "", 1).await.unwrap();
+        assert_eq!(objects.len(), PAGE_SIZE);
+        assert!(more);
+
+        let FoundObjects { objects, more, .. } =
+            find_objects(&manager, &request, "", 2).await.unwrap();
+        assert_eq!(objects.len(), 5);
+        assert!(!more);
+
+        let Err(error) = find_objects(&manager, &request, "", u64::MAX).await else {
+            panic!("Expected an error for a too large page");
+        };
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cot_macros::dbtest]
+    async fn find_objects_search_fields(test_db: &mut TestDatabase) {
+        let request = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Author>::new();
+
+        let FoundObjects { objects, more, .. } =
+            find_objects(&manager, &request, "odd", 1).await.unwrap();
+        assert_eq!(objects.len(), 12);
+        assert!(!more);
+        assert_eq!(texts(&objects)[0], "Author Odd 1");
+
+        // the display text is not searched when the model has search fields
+        let FoundObjects { objects, .. } =
+            find_objects(&manager, &request, "Author", 1).await.unwrap();
+        assert!(objects.is_empty());
+    }
+
+    #[cot_macros::dbtest]
+    async fn find_objects_display(test_db: &mut TestDatabase) {
+        let request = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Publisher>::new();
+
+        let FoundObjects { objects, more, .. } = find_objects(&manager, &request, "publisher", 1)
+            .await
+            .unwrap();
+        assert_eq!(objects.len(), PAGE_SIZE);
+        assert!(more);
+
+        let FoundObjects { objects, more, .. } = find_objects(&manager, &request, "publisher", 2)
+            .await
+            .unwrap();
+        assert_eq!(objects.len(), 5);
+        assert!(!more);
+
+        let FoundObjects {
+            objects,
+            more,
+            truncated,
+        } = find_objects(&manager, &request, "even 2", 1).await.unwrap();
+        assert_eq!(
+            texts(&objects),
+            [
+                "Publisher Even 2",
+                "Publisher Even 20",
+                "Publisher Even 22",
+                "Publisher Even 24"
+            ]
+        );
+        assert!(!more);
+        assert!(!truncated);
+    }
+
+    #[cot_macros::dbtest]
+    async fn find_objects_display_scan_limit(test_db: &mut TestDatabase) {
+        let request = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Publisher>::new();
+        let mut publishers: Vec<_> = (0..MAX_SCANNED_OBJECTS)
+            .map(|index| Publisher {
+                id: Auto::auto(),
+                name: format!("Filler {index}"),
+            })
+            .chain(std::iter::once(Publisher {
+                id: Auto::auto(),
+                name: "Needle".to_owned(),
+            }))
+            .collect();
+        test_db
+            .database()
+            .bulk_insert(&mut publishers)
+            .await
+            .unwrap();
+
+        let found = find_objects(&manager, &request, "even 2", 1).await.unwrap();
+        assert_eq!(found.objects.len(), 4);
+        assert!(found.truncated);
+        // the objects after the first `MAX_SCANNED_OBJECTS` are not searched,
+        // which is reported instead of silently returning no results
+        let found = find_objects(&manager, &request, "needle", 1).await.unwrap();
+        assert!(found.objects.is_empty());
+        assert!(!found.more);
+        assert!(found.truncated);
+
+        // searching the search fields is not limited
+        let found = find_objects(
+            &DefaultAdminModelManager::<Author>::new(),
+            &request,
+            "odd",
+            1,
+        )
+        .await
+        .unwrap();
+        assert!(!found.truncated);
+    }
+}
@@ -0,0 +1,1304 @@
+//! Exporting the objects of a model from the admin panel, and importing them
+//! from CSV files.
+//!
+//! The exported files contain the `pk` column with the ID of each object,
+//! followed by a column for each field of the model's [`Form`] (except the
+//! [excluded](super::AdminModelManager::excluded_fields) ones), holding the
+//! values shown in the edit form. Such a file can be edited and imported back:
+//! the rows with an empty `pk` create new objects, and the others change the
+//! existing ones.
+//!
+//! The values of the CSV files starting with a character that makes the
+//! spreadsheet applications treat them as formulas (such as `=`) are prefixed
+//! with a single quote when exporting, and the prefix is removed when
+//! importing.
+
+use bytes::Bytes;
+use cot_core::headers::{MULTIPART_FORM_CONTENT_TYPE, URLENCODED_FORM_CONTENT_TYPE};
+use derive_more::Debug;
+use futures_core::Stream;
+use futures_util::{StreamExt, stream};
+use thiserror::Error;
+
+use super::{
+    AdminModelManager, AdminModelManagers, BaseContext, FormValues, ListParams, ListQuery,
+    ModelPermissions, NonEditableFields, Pagination, get_manager, log_saved_object,
+};
+use crate::auth::{Auth, PermissionDenied, User};
+use crate::error::NotFound;
+use crate::form::fields::InMemoryUploadedFile;
+use crate::form::{
+    DynFormField, Form, FormContext, FormErrorTarget, FormFieldValidationError, FormResult,
+};
+use crate::html::Html;
+use crate::request::extractors::Path;
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Response};
+use crate::{Body, Error, Method, Template, reverse_redirect};
+
+/// The name of the column holding the IDs of the objects.
+const PK_COLUMN: &str = "pk";
+/// The URL query parameter selecting the format of the exported file.
+const FORMAT_PARAM: &str = "format";
+/// The number of objects fetched from the database at once when exporting.
+const EXPORT_BATCH_SIZE: u64 = 100;
+/// The maximum number of rows in an imported file.
+const MAX_IMPORT_ROWS: usize = 1000;
+/// The characters that make the spreadsheet applications treat a value as a
+/// formula when it starts with one of them.
+const CSV_FORMULA_CHARS: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+/// The prefix added to the exported CSV values that could be treated as
+/// formulas.
+const CSV_ESCAPE_CHAR: char = '\'';
+
+/// The format of an exported file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl ExportFormat {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "" | "csv" => Some(Self::Csv),
+            #[cfg(feature = "json")]
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            #[cfg(feature = "json")]
+            Self::Json => "json",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            #[cfg(feature = "json")]
+            Self::Json => "application/json",
+        }
+    }
+
+    /// Returns the data written before the objects.
+    fn header(self, fields: &[String]) -> String {
+        match self {
+            Self::Csv => {
+                let mut header = String::new();
+                write_csv_record(
+                    &mut header,
+                    std::iter::once(PK_COLUMN).chain(fields.iter().map(String::as_str)),
+                );
+                header
+            }
+            #[cfg(feature = "json")]
+            Self::Json => "[".to_owned(),
+        }
+    }
+
+    /// Returns the data written after the objects.
+    fn footer(self) -> String {
+        match self {
+            Self::Csv => String::new(),
+            #[cfg(feature = "json")]
+            Self::Json => "\n]\n".to_owned(),
+        }
+    }
+
+    /// Writes a single object; `index` is the index of the object in the
+    /// whole file.
+    fn write_object(
+        self,
+        out: &mut String,
+        index: u64,
+        object_id: &str,
+        fields: &[String],
+        values: &FormValues,
+    ) {
+        match self {
+            Self::Csv => write_csv_record(
+                out,
+                std::iter::once(object_id).chain(fields.iter().map(|field| values.get(field))),
+            ),
+            #[cfg(feature = "json")]
+            Self::Json => {
+                let object: serde_json::Map<String, serde_json::Value> =
+                    std::iter::once((PK_COLUMN, object_id))
+                        .chain(
+                            fields
+                                .iter()
+                                .map(|field| (field.as_str(), values.get(field))),
+                        )
+                        .map(|(key, value)| (key.to_owned(), value.into()))
+                        .collect();
+                out.push_str(if index == 0 { "\n" } else { ",\n" });
+                out.push_str(&serde_json::Value::Object(object).to_string());
+            }
+        }
+    }
+}
+
+/// Returns the IDs of the form fields that are exported and can be imported
+/// by the user.
+fn exported_fields(manager: &dyn AdminModelManager, user: &dyn User) -> Vec<String> {
+    let excluded = manager.excluded_fields(user);
+    manager
+        .form_context()
+        .fields()
+        .map(DynFormField::dyn_id)
+        .filter(|id| !excluded.iter().any(|field| field == id))
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Exports the objects shown in the (filtered and sorted) list of objects of
+/// a model.
+///
+/// The file is streamed, fetching the objects from the database in batches.
+pub(super) async fn export_model(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path(model_name): Path<String>,
+    request: Request,
+) -> cot::Result<Response> {
+    let manager = get_manager(managers, &model_name)?;
+    base_context.check_view_permission(&*manager)?;
+
+    let params = ListParams::from_request(&request);
+    let format = ExportFormat::from_name(params.get(FORMAT_PARAM)).ok_or_else(|| {
+        Error::from(NotFound::with_message(format!(
+            "Export format `{}` not supported",
+            params.get(FORMAT_PARAM)
+        )))
+    })?;
+    let time_zone = request.project_config().admin.time_zone;
+    let query = params.to_list_query(&manager.list_columns(), &manager.list_filters(), time_zone);
+    let fields = exported_fields(&*manager, &*base_context.auth.user());
+    let content_disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        manager.url_name(),
+        format.extension()
+    );
+
+    Body::streaming(export_stream(manager, request, query, fields, format))
+        .with_content_type(format.content_type())
+        .with_header(http::header::CONTENT_DISPOSITION, content_disposition)
+        .into_response()
+}
+
+/// The state of an export, kept between the batches of objects.
+#[derive(Debug)]
+struct ExportState {
+    #[debug("..")]
+    manager: Box<dyn AdminModelManager>,
+    request: Request,
+    query: ListQuery,
+    fields: Vec<String>,
+    format: ExportFormat,
+    page: u64,
+    exported: u64,
+    done: bool,
+}
+
+impl ExportState {
+    async fn next_batch(&mut self) -> cot::Result<Bytes> {
+        let objects = self
+            .manager
+            .get_objects(
+                &self.request,
+                &self.query,
+                Pagination::new(EXPORT_BATCH_SIZE, self.page),
+            )
+            .await?;
+        self.page += 1;
+        self.done = u64::try_from(objects.len()).is_ok_and(|count| count < EXPORT_BATCH_SIZE);
+
+        let mut out = String::new();
+        for object in objects {
+            let object_id = object.id();
+            let values = FormValues::new(&*self.manager.form_context_from_object(object).await);
+            self.format
+                .write_object(&mut out, self.exported, &object_id, &self.fields, &values);
+            self.exported += 1;
+        }
+        Ok(Bytes::from(out))
+    }
+}
+
+/// Returns the stream of the exported file, fetching the objects in batches
+/// until there are no more of them.
+fn export_stream(
+    manager: Box<dyn AdminModelManager>,
+    request: Request,
+    query: ListQuery,
+    fields: Vec<String>,
+    format: ExportFormat,
+) -> impl Stream<Item = cot::Result<Bytes>> + Send + 'static {
+    let header = Bytes::from(format.header(&fields));
+    let footer = Bytes::from(format.footer());
+    let state = ExportState {
+        manager,
+        request,
+        query,
+        fields,
+        format,
+        page: 1,
+        exported: 0,
+        done: false,
+    };
+
+    let objects = stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        let batch = state.next_batch().await;
+        // stop after the first error
+        state.done |= batch.is_err();
+        Some((batch, state))
+    });
+
+    stream::once(async { Ok(header) })
+        .chain(objects)
+        .chain(stream::once(async { Ok(footer) }))
+}
+
+/// Writes a single record of a CSV file, quoting the values when needed.
+///
+/// The values that could be treated as formulas are prefixed with
+/// [`CSV_ESCAPE_CHAR`], and so are the values already starting with it, so
+/// that [`unescape_csv_value`] can always remove the prefix.
+fn write_csv_record<'a>(out: &mut String, values: impl IntoIterator<Item = &'a str>) {
+    for (index, value) in values.into_iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        let escaped;
+        let value = if value.starts_with(CSV_FORMULA_CHARS) || value.starts_with(CSV_ESCAPE_CHAR) {
+            escaped = format!("{CSV_ESCAPE_CHAR}{value}");
+            &escaped
+        } else {
+            value
+        };
+        if value.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&value.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(value);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Removes the prefix added by [`write_csv_record`] to a value that could be
+/// treated as a formula.
+fn unescape_csv_value(value: String) -> String {
+    match value.strip_prefix(CSV_ESCAPE_CHAR) {
+        Some(rest) if rest.starts_with(CSV_FORMULA_CHARS) || rest.starts_with(CSV_ESCAPE_CHAR) => {
+            rest.to_owned()
+        }
+        _ => value,
+    }
+}
+
+/// An error that occurred while parsing a CSV file.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("the quoted value starting in line {line} is not terminated")]
+struct CsvError {
+    line: usize,
+}
+
+/// Parses a CSV file (as described in RFC 4180) into records, skipping empty
+/// lines.
+fn parse_csv(input: &str) -> Result<Vec<Vec<String>>, CsvError> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut value = String::new();
+    let mut record_started = false;
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut quote_line = 1;
+
+    let mut chars = input.chars().peekable();
+    while let Some(char) = chars.next() {
+        if in_quotes {
+            match char {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    value.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if char == '\n' {
+                        line += 1;
+                    }
+                    value.push(char);
+                }
+            }
+            continue;
+        }
+
+        match char {
+            '"' => {
+                in_quotes = true;
+                quote_line = line;
+                record_started = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut value));
+                record_started = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                line += 1;
+                if record_started {
+                    record.push(std::mem::take(&mut value));
+                    records.push(std::mem::take(&mut record));
+                    record_started = false;
+                }
+            }
+            _ => {
+                value.push(char);
+                record_started = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(CsvError { line: quote_line });
+    }
+    if record_started {
+        record.push(value);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// The form uploading the file to import.
+#[derive(Debug, Form)]
+struct ImportForm {
+    file: InMemoryUploadedFile,
+}
+
+/// A CSV file to import, checked against the fields of the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ImportData {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl ImportData {
+    /// Parses the CSV file, whose columns must be the `pk` column or some of
+    /// the given fields.
+    fn parse(csv: &str, fields: &[String]) -> Result<Self, String> {
+        let mut records = parse_csv(csv)
+            .map_err(|error| error.to_string())?
+            .into_iter();
+        let columns: Vec<String> = records
+            .next()
+            .ok_or("the file is empty")?
+            .into_iter()
+            .map(|column| column.trim().to_owned())
+            .collect();
+
+        for (index, column) in columns.iter().enumerate() {
+            if column != PK_COLUMN && !fields.contains(column) {
+                return Err(format!("unknown column `{column}`"));
+            }
+            if columns[..index].contains(column) {
+                return Err(format!("duplicate column `{column}`"));
+            }
+        }
+
+        let rows: Vec<Vec<String>> = records
+            .map(|row| row.into_iter().map(unescape_csv_value).collect())
+            .collect();
+        if rows.len() > MAX_IMPORT_ROWS {
+            return Err(format!(
+                "the file has {} rows, but at most {MAX_IMPORT_ROWS} can be imported at once",
+                rows.len()
+            ));
+        }
+        for (index, row) in rows.iter().enumerate() {
+            if row.len() != columns.len() {
+                return Err(format!(
+                    "row {} has {} values, expected {}",
+                    Self::row_number(index),
+                    row.len(),
+                    columns.len()
+                ));
+            }
+        }
+
+        Ok(Self { columns, rows })
+    }
+
+    /// Returns the number of the row with the given index, as shown to the
+    /// user (counting the header as row 1).
+    fn row_number(index: usize) -> usize {
+        index + 2
+    }
+
+    fn object_id(&self, row: &[String]) -> Option<String> {
+        self.columns
+            .iter()
+            .zip(row)
+            .find(|(column, _)| *column == PK_COLUMN)
+            .map(|(_, value)| value.trim().to_owned())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Returns the row as form data, as if the edit form was submitted.
+    fn form_data(&self, row: &[String]) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(
+                self.columns
+                    .iter()
+                    .zip(row)
+                    .filter(|(column, _)| *column != PK_COLUMN),
+            )
+            .finish()
+    }
+}
+
+/// The change of a field's value made by an imported row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldChange {
+    field: String,
+    old_value: String,
+    new_value: String,
+}
+
+/// An imported row, validated using the model's form.
+#[derive(Debug, Clone)]
+struct ImportRow {
+    number: usize,
+    object_id: Option<String>,
+    object_repr: Option<String>,
+    changes: Vec<FieldChange>,
+    errors: Vec<String>,
+    /// The row as form data.
+    form_data: String,
+    /// The form fields whose values are not taken from the row.
+    ignored_fields: Vec<String>,
+    /// The values of the form fields before the import.
+    previous_values: FormValues,
+}
+
+impl ImportRow {
+    fn new(number: usize, object_id: Option<String>) -> Self {
+        Self {
+            number,
+            object_id,
+            object_repr: None,
+            changes: Vec::new(),
+            errors: Vec::new(),
+            form_data: String::new(),
+            ignored_fields: Vec::new(),
+            previous_values: FormValues::default(),
+        }
+    }
+
+    fn with_error(mut self, error: String) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Returns whether saving the row creates or changes an object.
+    fn has_changes(&self) -> bool {
+        self.object_id.is_none() || !self.changes.is_empty()
+    }
+
+    fn status(&self) -> &'static str {
+        if !self.errors.is_empty() {
+            "Error"
+        } else if self.object_id.is_none() {
+            "Create"
+        } else if self.changes.is_empty() {
+            "No changes"
+        } else {
+            "Change"
+        }
+    }
+}
+
+/// Returns all the validation errors in the form, prefixed with the names of
+/// the fields they belong to.
+fn form_errors(form_context: &dyn FormContext) -> Vec<String> {
+    let form_errors = form_context
+        .errors_for(FormErrorTarget::Form)
+        .iter()
+        .map(ToString::to_string);
+    let field_errors = form_context.fields().flat_map(|field| {
+        form_context
+            .errors_for(FormErrorTarget::Field(field.dyn_id()))
+            .iter()
+            .map(|error| format!("{}: {error}", field.dyn_options().name))
+    });
+    form_errors.chain(field_errors).collect()
+}
+
+/// Replaces the body of the request with the given form data.
+fn set_form_data(request: &mut Request, form_data: &str) {
+    *request.body_mut() = Body::fixed(form_data.to_owned());
+    request.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static(URLENCODED_FORM_CONTENT_TYPE),
+    );
+}
+
+/// Validates a row of an imported file using the model's form, and finds out
+/// how it changes the object.
+async fn validate_row(
+    request: &mut Request,
+    manager: &dyn AdminModelManager,
+    (user, permissions): (&(dyn User + Send + Sync), ModelPermissions),
+    data: &ImportData,
+    index: usize,
+) -> cot::Result<ImportRow> {
+    let row = &data.rows[index];
+    let object_id = data.object_id(row);
+    let mut import_row = ImportRow::new(ImportData::row_number(index), object_id.clone());
+
+    let non_editable_fields = NonEditableFields::new(manager, user, object_id.as_deref());
+    let mut ignored_fields: Vec<String> = non_editable_fields
+        .ids()
+        .into_iter()
+        .map(ToOwned::to_owned)
+        .collect();
+
+    if let Some(object_id) = &object_id {
+        if !permissions.change {
+            return Ok(
+                import_row.with_error("You don't have permission to change this object".to_owned())
+            );
+        }
+        let Some(object) = manager.get_object_by_id(request, object_id).await? else {
+            return Ok(import_row.with_error(format!("Object with ID `{object_id}` not found")));
+        };
+        import_row.object_repr = Some(object.display());
+        import_row.previous_values =
+            FormValues::new(&*manager.form_context_from_object(object).await);
+        // the fields missing from the file keep their values
+        ignored_fields.extend(
+            import_row
+                .previous_values
+                .ids()
+                .filter(|id| !data.columns.iter().any(|column| column == id))
+                .map(ToOwned::to_owned),
+        );
+    } else {
+        if !permissions.add {
+            return Ok(
+                import_row.with_error("You don't have permission to create new objects".to_owned())
+            );
+        }
+        import_row.previous_values = FormValues::new(&*manager.form_context());
+    }
+
+    import_row.form_data = data.form_data(row);
+    set_form_data(request, &import_row.form_data);
+    let ignored: Vec<&str> = ignored_fields.iter().map(String::as_str).collect();
+    let form_context = manager
+        .form_context_from_request(request, object_id.as_deref(), &ignored)
+        .await?;
+
+    let values = FormValues::new(&*form_context);
+    import_row.changes = form_context
+        .fields()
+        .filter(|field| {
+            values
+                .changed_since(&import_row.previous_values)
+                .iter()
+                .any(|id| id == field.dyn_id())
+        })
+        .map(|field| FieldChange {
+            field: field.dyn_options().name.clone(),
+            old_value: import_row.previous_values.get(field.dyn_id()).to_owned(),
+            new_value: values.get(field.dyn_id()).to_owned(),
+        })
+        .collect();
+    import_row.errors = form_errors(&*form_context);
+    import_row.ignored_fields = ignored_fields;
+
+    Ok(import_row)
+}
+
+/// Validates all the rows of an imported file.
+async fn validate_import(
+    request: &mut Request,
+    manager: &dyn AdminModelManager,
+    (user, permissions): (&(dyn User + Send + Sync), ModelPermissions),
+    data: &ImportData,
+) -> cot::Result<Vec<ImportRow>> {
+    let mut rows = Vec::with_capacity(data.rows.len());
+    for index in 0..data.rows.len() {
+        rows.push(validate_row(request, manager, (user, permissions), data, index).await?);
+    }
+    Ok(rows)
+}
+
+/// Saves the validated rows of an imported file, recording them in the admin
+/// log.
+///
+/// The rows are saved one by one; if a row turns out to be invalid after all
+/// (for instance, because the object has been changed in the meantime), it's
+/// returned with the errors, and the rows before it stay saved.
+async fn apply_import(
+    auth: &Auth,
+    request: &mut Request,
+    manager: &dyn AdminModelManager,
+    rows: Vec<ImportRow>,
+) -> cot::Result<Option<ImportRow>> {
+    for mut row in rows.into_iter().filter(ImportRow::has_changes) {
+        set_form_data(request, &row.form_data);
+        let ignored: Vec<&str> = row.ignored_fields.iter().map(String::as_str).collect();
+        match manager
+            .save_from_request(request, row.object_id.as_deref(), &ignored)
+            .await?
+        {
+            super::SaveResult::Saved(object) => {
+                log_saved_object(
+                    auth,
+                    request,
+                    manager,
+                    row.object_id.is_none(),
+                    object,
+                    &row.previous_values,
+                )
+                .await?;
+            }
+            super::SaveResult::Invalid(form_context) => {
+                row.errors = form_errors(&*form_context);
+                return Ok(Some(row));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// The validated rows of an imported file, shown to the user before they're
+/// saved.
+#[derive(Debug)]
+struct ImportPreview {
+    /// The imported file, sent again when the import is confirmed.
+    data: String,
+    rows: Vec<ImportRow>,
+    message: Option<String>,
+}
+
+impl ImportPreview {
+    fn has_errors(&self) -> bool {
+        self.rows.iter().any(|row| !row.errors.is_empty())
+    }
+
+    fn can_be_imported(&self) -> bool {
+        !self.has_errors() && self.message.is_none()
+    }
+
+    fn change_count(&self) -> usize {
+        self.rows.iter().filter(|row| row.has_changes()).count()
+    }
+}
+
+/// The file sent to the import page.
+#[derive(Debug)]
+enum ImportSubmission {
+    /// The file has just been uploaded and should be previewed.
+    Upload(String),
+    /// The import of the previewed file has been confirmed.
+    Confirm(String),
+    /// The upload form is invalid.
+    Invalid(Box<<ImportForm as Form>::Context>),
+}
+
+impl ImportSubmission {
+    async fn from_request(request: &mut Request) -> cot::Result<Self> {
+        if request
+            .content_type()
+            .is_some_and(|content_type| content_type == URLENCODED_FORM_CONTENT_TYPE)
+        {
+            let body = std::mem::take(request.body_mut()).into_bytes().await?;
+            let data = form_urlencoded::parse(&body)
+                .find(|(key, _)| key == "data")
+                .map(|(_, value)| value.into_owned())
+                .unwrap_or_default();
+            return Ok(Self::Confirm(data));
+        }
+        if !request.content_type().is_some_and(|content_type| {
+            content_type
+                .as_bytes()
+                .starts_with(MULTIPART_FORM_CONTENT_TYPE.as_bytes())
+        }) {
+            return Ok(Self::Invalid(Box::new(Self::invalid_file(
+                FormFieldValidationError::from_static("This field is required."),
+            ))));
+        }
+
+        Ok(match ImportForm::from_request(request).await? {
+            FormResult::Ok(form) => match String::from_utf8(form.file.content().to_vec()) {
+                Ok(data) => Self::Upload(data),
+                Err(_) => Self::Invalid(Box::new(Self::invalid_file(
+                    FormFieldValidationError::from_static("The file is not valid UTF-8 text."),
+                ))),
+            },
+            FormResult::ValidationError(context) => Self::Invalid(Box::new(context)),
+        })
+    }
+
+    fn invalid_file(error: FormFieldValidationError) -> <ImportForm as Form>::Context {
+        let mut context = <ImportForm as Form>::Context::new();
+        context.add_error(FormErrorTarget::Field("file"), error);
+        context
+    }
+}
+
+/// Parses and validates the imported file.
+///
+/// Returns the upload form with the error if the file is invalid as a whole.
+async fn preview_import(
+    request: &mut Request,
+    manager: &dyn AdminModelManager,
+    (user, permissions): (&(dyn User + Send + Sync), ModelPermissions),
+    fields: &[String],
+    data: String,
+) -> cot::Result<Result<ImportPreview, <ImportForm as Form>::Context>> {
+    let import_data = match ImportData::parse(&data, fields) {
+        Ok(import_data) => import_data,
+        Err(error) => {
+            return Ok(Err(ImportSubmission::invalid_file(
+                FormFieldValidationError::from_string(format!("Invalid file: {error}")),
+            )));
+        }
+    };
+    let rows = validate_import(request, manager, (user, permissions), &import_data).await?;
+
+    Ok(Ok(ImportPreview {
+        data,
+        rows,
+        message: None,
+    }))
+}
+
+/// Imports the objects of a model from a CSV file.
+///
+/// The uploaded file is validated using the model's form, and the changes are
+/// shown to the user, who has to confirm them before they are saved.
+pub(super) async fn import_model(
+    base_context: BaseContext,
+    managers: AdminModelManagers,
+    Path(model_name): Path<String>,
+    mut request: Request,
+) -> cot::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "admin/model_import.html")]
+    struct ModelImportTemplate<'a> {
+        ctx: &'a BaseContext,
+        #[debug("..")]
+        model: &'a dyn AdminModelManager,
+        form: <ImportForm as Form>::Context,
+        fields: Vec<String>,
+        preview: Option<ImportPreview>,
+    }
+
+    impl ModelImportTemplate<'_> {
+        fn shows_upload_form(&self) -> bool {
+            self.preview
+                .as_ref()
+                .is_none_or(|preview| !preview.can_be_imported())
+        }
+    }
+
+    let manager = get_manager(managers, &model_name)?;
+    let permissions = base_context.permissions(&*manager);
+    if !permissions.add && !permissions.change {
+        return Err(PermissionDenied::new().into());
+    }
+    let user = base_context.auth.user();
+    let fields = exported_fields(&*manager, &*user);
+
+    let (form, preview) = 'preview: {
+        if request.method() != Method::POST {
+            break 'preview (<ImportForm as Form>::Context::new(), None);
+        }
+        let (data, confirmed) = match ImportSubmission::from_request(&mut request).await? {
+            ImportSubmission::Upload(data) => (data, false),
+            ImportSubmission::Confirm(data) => (data, true),
+            ImportSubmission::Invalid(form) => break 'preview (*form, None),
+        };
+        // the file is validated again when the import is confirmed, as the
+        // objects could have been changed since the preview has been shown
+        let mut preview = match preview_import(
+            &mut request,
+            &*manager,
+            (&*user, permissions),
+            &fields,
+            data,
+        )
+        .await?
+        {
+            Ok(preview) => preview,
+            Err(form) => break 'preview (form, None),
+        };
+
+        if confirmed && !preview.has_errors() {
+            let Some(failed_row) =
+                apply_import(&base_context.auth, &mut request, &*manager, preview.rows).await?
+            else {
+                return Ok(reverse_redirect!(
+                    base_context.urls,
+                    "view_model",
+                    model_name = manager.url_name()
+                )?);
+            };
+            preview.message = Some(format!(
+                "Row {} could not be saved; the rows before it have been imported.",
+                failed_row.number
+            ));
+            preview.rows = vec![failed_row];
+        }
+        (<ImportForm as Form>::Context::new(), Some(preview))
+    };
+
+    let template = ModelImportTemplate {
+        ctx: &base_context,
+        model: &*manager,
+        form,
+        fields,
+        preview,
+    };
+
+    Html::new(template.render()?).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Debug, Display, Formatter};
+
+    use chrono::{DateTime, FixedOffset};
+    use cot::db::Auto;
+    use cot_macros::AdminModel;
+    use futures_util::TryStreamExt;
+
+    use super::*;
+    use crate::admin::log::LogEntry;
+    use crate::admin::{AdminModel as _, DefaultAdminModelManager, LogEntryAction};
+    use crate::auth::AnonymousUser;
+    use crate::db::migrations::{
+        Field, Migration, MigrationDependency, Operation, wrap_migrations,
+    };
+    use crate::db::{DatabaseField, Identifier, Model, model};
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    struct Book {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        title: String,
+        pages: i32,
+    }
+
+    impl Display for Book {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.title)
+        }
+    }
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    struct Event {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        name: String,
+        starts_at: DateTime<FixedOffset>,
+    }
+
+    impl Display for Event {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.name)
+        }
+    }
+
+    struct TestMigration;
+
+    impl Migration for TestMigration {
+        const APP_NAME: &'static str = "cot_import_export";
+        const MIGRATION_NAME: &'static str = "m_0001_initial";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[
+            Operation::create_model()
+                .table_name(<Book as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+                    Field::new(Identifier::new("pages"), <i32 as DatabaseField>::TYPE),
+                ])
+                .build(),
+            Operation::create_model()
+                .table_name(<Event as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+                    Field::new(
+                        Identifier::new("starts_at"),
+                        <DateTime<FixedOffset> as DatabaseField>::TYPE,
+                    ),
+                ])
+                .build(),
+        ];
+    }
+
+    const ALL_PERMISSIONS: ModelPermissions = ModelPermissions {
+        view: true,
+        add: true,
+        change: true,
+        delete: true,
+    };
+
+    async fn setup(test_db: &mut TestDatabase) -> Vec<Book> {
+        test_db
+            .add_migrations(wrap_migrations(&[&TestMigration]))
+            .run_migrations()
+            .await;
+
+        let mut books = Vec::new();
+        for (title, pages) in [("Dune", 412), ("Emma, \"a novel\"", 474)] {
+            let mut book = Book {
+                id: Auto::auto(),
+                title: title.to_owned(),
+                pages,
+            };
+            book.insert(&test_db.database()).await.unwrap();
+            books.push(book);
+        }
+        books
+    }
+
+    fn fields() -> Vec<String> {
+        ["id", "title", "pages"].map(ToOwned::to_owned).to_vec()
+    }
+
+    async fn export(test_db: &TestDatabase, format: ExportFormat) -> String {
+        let request = TestRequestBuilder::get("/")
+            .database(test_db.database())
+            .build();
+        let body: Vec<Bytes> = export_stream(
+            Box::new(DefaultAdminModelManager::<Book>::new()),
+            request,
+            ListQuery::new(),
+            fields(),
+            format,
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        String::from_utf8(body.concat()).unwrap()
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let mut csv = String::new();
+        write_csv_record(&mut csv, ["plain", "with, comma", "with \"quotes\""]);
+        write_csv_record(&mut csv, ["multi\nline", ""]);
+        assert_eq!(
+            csv,
+            "plain,\"with, comma\",\"with \"\"quotes\"\"\"\r\n\"multi\nline\",\r\n"
+        );
+
+        assert_eq!(
+            parse_csv(&format!("\u{feff}{csv}\n\n")).unwrap(),
+            [
+                vec!["plain", "with, comma", "with \"quotes\""],
+                vec!["multi\nline", ""],
+            ]
+        );
+        assert_eq!(parse_csv("a,b\nc,d").unwrap(), [["a", "b"], ["c", "d"]]);
+        assert_eq!(parse_csv("a,b\n\"c,d\n").unwrap_err(), CsvError { line: 2 });
+    }
+
+    #[test]
+    fn csv_formula_escaping() {
+        let values = [
+            "=1+1", "+1", "-1", "@SUM(A1)", "\tTab", "\rCR", "'quoted", "'=1", "a=b", "",
+        ];
+        let mut csv = String::new();
+        write_csv_record(&mut csv, values);
+        assert_eq!(
+            csv,
+            "'=1+1,'+1,'-1,'@SUM(A1),'\tTab,\"'\rCR\",''quoted,''=1,a=b,\r\n"
+        );
+        assert_eq!(
+            parse_csv(&csv)
+                .unwrap()
+                .remove(0)
+                .into_iter()
+                .map(unescape_csv_value)
+                .collect::<Vec<_>>(),
+            values
+        );
+
+        // the prefix is removed when importing, but only if it was added
+        // when exporting
+        let data = ImportData::parse("title\n'=1+1\n'quoted\n", &fields()).unwrap();
+        assert_eq!(data.rows, [["=1+1"], ["'quoted"]]);
+    }
+
+    #[test]
+    fn import_data_parse() {
+        let data = ImportData::parse("pk,title\n1,Dune\n,Emma\n", &fields()).unwrap();
+        assert_eq!(data.columns, ["pk", "title"]);
+        assert_eq!(data.object_id(&data.rows[0]).as_deref(), Some("1"));
+        assert_eq!(data.object_id(&data.rows[1]), None);
+        assert_eq!(data.form_data(&data.rows[1]), "title=Emma");
+
+        assert_eq!(
+            ImportData::parse("", &fields()).unwrap_err(),
+            "the file is empty"
+        );
+        assert_eq!(
+            ImportData::parse("pk,author\n", &fields()).unwrap_err(),
+            "unknown column `author`"
+        );
+        assert_eq!(
+            ImportData::parse("title,title\n", &fields()).unwrap_err(),
+            "duplicate column `title`"
+        );
+        assert_eq!(
+            ImportData::parse("pk,title\n1\n", &fields()).unwrap_err(),
+            "row 2 has 1 values, expected 2"
+        );
+        let too_many_rows = format!("title\n{}", "Dune\n".repeat(MAX_IMPORT_ROWS + 1));
+        assert!(ImportData::parse(&too_many_rows, &fields()).is_err());
+    }
+
+    #[cot_macros::dbtest]
+    async fn export_csv(test_db: &mut TestDatabase) {
+        let books = setup(test_db).await;
+
+        assert_eq!(
+            export(test_db, ExportFormat::Csv).await,
+            format!(
+                "pk,id,title,pages\r\n\
+                {0},{0},Dune,412\r\n\
+                {1},{1},\"Emma, \"\"a novel\"\"\",474\r\n",
+                books[0].id(),
+                books[1].id()
+            )
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[cot_macros::dbtest]
+    async fn export_json(test_db: &mut TestDatabase) {
+        let books = setup(test_db).await;
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export(test_db, ExportFormat::Json).await).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"pk": books[0].id(), "id": books[0].id(), "title": "Dune", "pages": "412"},
+                {"pk": books[1].id(), "id": books[1].id(), "title": "Emma, \"a novel\"", "pages": "474"},
+            ])
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn import_preview(test_db: &mut TestDatabase) {
+        let books = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Book>::new();
+        let csv = format!(
+            "pk,title,pages\n{},Dune,500\n{},Emma,474\n,Ulysses,730\n,Nameless,many\n999,Missing,1\n",
+            books[0].id(),
+            books[1].id()
+        );
+        let data = ImportData::parse(&csv, &fields()).unwrap();
+        let mut request = TestRequestBuilder::post("/")
+            .database(test_db.database())
+            .build();
+
+        let rows = validate_import(
+            &mut request,
+            &manager,
+            (&AnonymousUser, ALL_PERMISSIONS),
+            &data,
+        )
+        .await
+        .unwrap();
+
+        let statuses: Vec<_> = rows.iter().map(ImportRow::status).collect();
+        assert_eq!(statuses, ["Change", "Change", "Create", "Error", "Error"]);
+        assert_eq!(rows[0].object_repr.as_deref(), Some("Dune"));
+        assert_eq!(
+            rows[0].changes,
+            [FieldChange {
+                field: "Pages".to_owned(),
+                old_value: "412".to_owned(),
+                new_value: "500".to_owned(),
+            }]
+        );
+        assert_eq!(rows[1].changes[0].field, "Title");
+        assert_eq!(rows[3].errors.len(), 1);
+        assert!(rows[3].errors[0].starts_with("Pages: "));
+        assert_eq!(rows[4].errors, ["Object with ID `999` not found"]);
+
+        let rows = validate_import(
+            &mut request,
+            &manager,
+            (
+                &AnonymousUser,
+                ModelPermissions {
+                    add: false,
+                    ..ALL_PERMISSIONS
+                },
+            ),
+            &data,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            rows[2].errors,
+            ["You don't have permission to create new objects"]
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn import_apply(test_db: &mut TestDatabase) {
+        test_db.with_auth();
+        test_db.add_migrations(crate::admin::log::migrations::MIGRATIONS.to_vec());
+        let books = setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Book>::new();
+        let csv = format!(
+            "pk,title,pages\n{},Dune,500\n{},\"Emma, \"\"a novel\"\"\",474\n,Ulysses,730\n",
+            books[0].id(),
+            books[1].id()
+        );
+        let data = ImportData::parse(&csv, &fields()).unwrap();
+        let mut request = TestRequestBuilder::post("/")
+            .with_db_auth(test_db.database())
+            .await
+            .build();
+        let auth = request.extensions().get::<Auth>().unwrap().clone();
+
+        let rows = validate_import(
+            &mut request,
+            &manager,
+            (&AnonymousUser, ALL_PERMISSIONS),
+            &data,
+        )
+        .await
+        .unwrap();
+        let failed_row = apply_import(&auth, &mut request, &manager, rows)
+            .await
+            .unwrap();
+        assert!(failed_row.is_none());
+
+        let mut books = Book::objects().all(&test_db.database()).await.unwrap();
+        books.sort_by_key(|book| book.id.unwrap());
+        let books: Vec<_> = books
+            .into_iter()
+            .map(|book| (book.title, book.pages))
+            .collect();
+        assert_eq!(
+            books,
+            [
+                ("Dune".to_owned(), 500),
+                ("Emma, \"a novel\"".to_owned(), 474),
+                ("Ulysses".to_owned(), 730),
+            ]
+        );
+
+        // the unchanged row is not saved, so it's not logged
+        let entries = LogEntry::recent(&test_db.database(), &[manager.url_name()], 10)
+            .await
+            .unwrap();
+        let actions: Vec<_> = entries.iter().map(LogEntry::action).collect();
+        assert_eq!(actions.len(), 2);
+        assert!(actions.contains(&LogEntryAction::Create));
+        assert!(actions.contains(&LogEntryAction::Change));
+    }
+
+    #[cot_macros::dbtest]
+    async fn csv_round_trip_objects(test_db: &mut TestDatabase) {
+        test_db.with_auth();
+        test_db.add_migrations(crate::admin::log::migrations::MIGRATIONS.to_vec());
+        setup(test_db).await;
+        let manager = DefaultAdminModelManager::<Event>::new();
+        let fields = ["name", "starts_at"].map(ToOwned::to_owned).to_vec();
+        let starts_at = DateTime::parse_from_rfc3339("2024-06-01T18:30:15+02:00").unwrap();
+        let mut event = Event {
+            id: Auto::auto(),
+            name: "=HYPERLINK(\"https://example.com\")".to_owned(),
+            starts_at,
+        };
+        event.insert(&test_db.database()).await.unwrap();
+
+        let request = TestRequestBuilder::get("/")
+            .database(test_db.database())
+            .build();
+        let body: Vec<Bytes> = export_stream(
+            Box::new(DefaultAdminModelManager::<Event>::new()),
+            request,
+            ListQuery::new(),
+            fields.clone(),
+            ExportFormat::Csv,
+        )
+        .try_collect()
+        .await
+        .unwrap();
+        let csv = String::from_utf8(body.concat()).unwrap();
+        assert!(csv.contains(",\"'=HYPERLINK("));
+
+        let mut request = TestRequestBuilder::post("/")
+            .with_db_auth(test_db.database())
+            .await
+            .build();
+        let auth = request.extensions().get::<Auth>().unwrap().clone();
+
+        // importing the exported file doesn't change anything
+        let data = ImportData::parse(&csv, &fields).unwrap();
+        let rows = validate_import(
+            &mut request,
+            &manager,
+            (&AnonymousUser, ALL_PERMISSIONS),
+            &data,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows[0].status(), "No changes");
+
+        // a copy of the object created from the file has the same values
+        let csv = csv.replacen(&format!("\r\n{},", event.id()), "\r\n,", 1);
+        let data = ImportData::parse(&csv, &fields).unwrap();
+        let rows = validate_import(
+            &mut request,
+            &manager,
+            (&AnonymousUser, ALL_PERMISSIONS),
+            &data,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows[0].status(), "Create");
+        let failed_row = apply_import(&auth, &mut request, &manager, rows)
+            .await
+            .unwrap();
+        assert!(failed_row.is_none());
+
+        let mut events = Event::objects().all(&test_db.database()).await.unwrap();
+        events.sort_by_key(|event| event.id.unwrap());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].name, event.name);
+        assert_eq!(events[1].starts_at, starts_at);
+    }
+}
@@ -0,0 +1,925 @@
+//! Inline editing of related objects on the edit page of an object.
+
+#[cfg(feature = "db")]
+use std::collections::HashMap;
+#[cfg(feature = "db")]
+use std::marker::PhantomData;
+#[cfg(feature = "db")]
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_more::Debug;
+
+use super::ModelPermissions;
+use super::log::LogEntryAction;
+#[cfg(feature = "db")]
+use super::{AdminModel, FormValues};
+#[cfg(feature = "db")]
+use crate::auth::PermissionDenied;
+#[cfg(feature = "db")]
+use crate::db::query::{Expr, FieldRef, Order};
+#[cfg(feature = "db")]
+use crate::db::{ForeignKey, Identifier, Model};
+#[cfg(feature = "db")]
+use crate::error::NotFound;
+use crate::form::FormContext;
+#[cfg(feature = "db")]
+use crate::form::{Form, FormErrorTarget, FormFieldValue, FormSet, FormSetChange, FormSetResult};
+use crate::request::Request;
+#[cfg(feature = "db")]
+use crate::request::RequestExt;
+
+/// The objects of a related model edited inline on the edit page of an
+/// object in the admin panel.
+///
+/// The related model is a model with a [`ForeignKey`](crate::db::ForeignKey)
+/// pointing to the model of the edited object, such as comments of a blog
+/// post. The related objects are shown as a set of forms below the form of
+/// the object; the forms are validated together with the form of the object,
+/// and nothing is saved unless all of them are valid. New related objects can
+/// be added using the spare empty forms, and the existing ones can be removed.
+///
+/// Note that the changes are not saved in a database transaction; if saving
+/// any of the objects fails (for instance, because of a database constraint),
+/// the changes saved before are kept.
+///
+/// Editing the related objects requires the [`AdminPermission::Change`]
+/// permission for the related model; adding and removing them additionally
+/// requires the [`AdminPermission::Add`] and [`AdminPermission::Delete`]
+/// permissions, respectively.
+///
+/// [`AdminPermission::Change`]: super::AdminPermission::Change
+/// [`AdminPermission::Add`]: super::AdminPermission::Add
+/// [`AdminPermission::Delete`]: super::AdminPermission::Delete
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::{Display, Formatter};
+///
+/// use cot::admin::{AdminInline, AdminModel, AdminModelManager, DefaultAdminModelManager};
+/// use cot::db::{Auto, ForeignKey, model};
+/// use cot::form::Form;
+///
+/// #[derive(Debug, Form, AdminModel)]
+/// #[model]
+/// struct Post {
+///     #[model(primary_key)]
+///     id: Auto<i64>,
+///     title: String,
+/// }
+///
+/// impl Display for Post {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", self.title)
+///     }
+/// }
+///
+/// #[derive(Debug, Form, AdminModel)]
+/// #[model]
+/// struct Comment {
+///     #[model(primary_key)]
+///     id: Auto<i64>,
+///     post: ForeignKey<Post>,
+///     text: String,
+/// }
+///
+/// impl Display for Comment {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", self.text)
+///     }
+/// }
+///
+/// let manager = DefaultAdminModelManager::<Post>::new()
+///     .with_inline(AdminInline::new::<Comment>(&CommentFields::post));
+/// assert_eq!(manager.inlines()[0].url_name(), "comment");
+/// ```
+#[derive(Debug, Clone)]
+pub struct AdminInline {
+    extra: usize,
+    #[debug("..")]
+    inner: Arc<dyn DynAdminInline>,
+}
+
+impl AdminInline {
+    /// The default number of spare empty forms for adding new objects.
+    pub const DEFAULT_EXTRA: usize = 3;
+
+    /// Creates an inline for the related model `C`, using the given foreign
+    /// key field of `C` pointing to the model of the edited object.
+    ///
+    /// The foreign key field is not shown in the forms; it's always set to
+    /// the edited object.
+    ///
+    /// See the [type-level documentation](Self) for an example.
+    #[cfg(feature = "db")]
+    #[must_use]
+    pub fn new<C>(
+        foreign_key: &FieldRef<ForeignKey<impl Model<PrimaryKey: FromStr> + Sync + 'static>>,
+    ) -> Self
+    where
+        C: AdminModel + Model<PrimaryKey: FromStr> + Form<Context: 'static> + Send + Sync,
+    {
+        Self::from_foreign_key::<C, _>(foreign_key)
+    }
+
+    #[cfg(feature = "db")]
+    fn from_foreign_key<C, P>(foreign_key: &FieldRef<ForeignKey<P>>) -> Self
+    where
+        C: AdminModel + Model<PrimaryKey: FromStr> + Form<Context: 'static> + Send + Sync,
+        P: Model<PrimaryKey: FromStr> + Send + Sync + 'static,
+    {
+        Self {
+            extra: Self::DEFAULT_EXTRA,
+            inner: Arc::new(ModelInline::<C, P> {
+                foreign_key: foreign_key.identifier(),
+                phantom_data: PhantomData,
+            }),
+        }
+    }
+
+    /// Sets the number of spare empty forms for adding new objects.
+    ///
+    /// The default is [`Self::DEFAULT_EXTRA`].
+    #[must_use]
+    pub fn with_extra(mut self, extra: usize) -> Self {
+        self.extra = extra;
+        self
+    }
+
+    /// Returns the number of spare empty forms for adding new objects.
+    #[must_use]
+    pub fn extra(&self) -> usize {
+        self.extra
+    }
+
+    /// Returns the display name of the related model.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    /// Returns the URL slug of the related model.
+    ///
+    /// This is also used as the prefix of the forms.
+    #[must_use]
+    pub fn url_name(&self) -> &str {
+        self.inner.url_name()
+    }
+
+    /// Returns the name of the foreign key field of the related model.
+    #[must_use]
+    pub fn foreign_key(&self) -> &str {
+        self.inner.foreign_key()
+    }
+
+    pub(super) async fn formset(
+        &self,
+        request: &Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet> {
+        let extra = if permissions.add { self.extra } else { 0 };
+        self.inner
+            .formset(request, parent_id, extra, permissions)
+            .await
+    }
+
+    pub(super) async fn formset_from_request(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet> {
+        self.inner
+            .formset_from_request(request, parent_id, permissions)
+            .await
+    }
+
+    pub(super) async fn validate(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<Result<Box<dyn InlineChanges>, InlineFormSet>> {
+        self.inner.validate(request, parent_id, permissions).await
+    }
+}
+
+#[async_trait]
+trait DynAdminInline: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn url_name(&self) -> &str;
+
+    fn foreign_key(&self) -> &str;
+
+    /// Returns the forms of the existing related objects, followed by `extra`
+    /// empty forms.
+    async fn formset(
+        &self,
+        request: &Request,
+        parent_id: &str,
+        extra: usize,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet>;
+
+    /// Returns the forms submitted in the request, without validating them.
+    async fn formset_from_request(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet>;
+
+    /// Validates the forms submitted in the request, returning the changes to
+    /// save, or the forms with the validation errors.
+    async fn validate(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<Result<Box<dyn InlineChanges>, InlineFormSet>>;
+}
+
+/// The validated changes to the related objects, ready to be saved.
+#[async_trait]
+pub(super) trait InlineChanges: Send {
+    /// Saves the changes, returning the changed objects.
+    async fn save(self: Box<Self>, request: &mut Request) -> crate::Result<Vec<InlineChange>>;
+}
+
+/// A related object changed by saving [`InlineChanges`].
+#[derive(Debug)]
+pub(super) struct InlineChange {
+    pub(super) object_id: String,
+    pub(super) object_repr: String,
+    pub(super) action: LogEntryAction,
+    pub(super) changed_fields: Vec<String>,
+}
+
+/// A set of forms of the related objects, prepared to be rendered.
+#[derive(Debug)]
+pub(super) struct InlineFormSet {
+    pub(super) name: String,
+    pub(super) count_field_name: String,
+    pub(super) can_delete: bool,
+    pub(super) forms: Vec<InlineForm>,
+}
+
+/// A single form of a related object, prepared to be rendered.
+#[derive(Debug)]
+pub(super) struct InlineForm {
+    pub(super) label: String,
+    pub(super) object_id: Option<String>,
+    pub(super) object_id_field_name: String,
+    pub(super) remove_field_name: String,
+    pub(super) removed: bool,
+    /// The ID of the foreign key field, which is not rendered.
+    pub(super) foreign_key_id: String,
+    /// The ID of the version field, which is rendered as a hidden input.
+    pub(super) version_field_id: Option<String>,
+    pub(super) context: Box<dyn FormContext + Send>,
+}
+
+#[cfg(feature = "db")]
+struct ModelInline<C, P> {
+    foreign_key: Identifier,
+    phantom_data: PhantomData<fn() -> (C, P)>,
+}
+
+#[cfg(feature = "db")]
+impl<C, P> ModelInline<C, P>
+where
+    C: AdminModel + Model<PrimaryKey: FromStr> + Form<Context: 'static> + Send + Sync,
+    P: Model<PrimaryKey: FromStr> + Send + Sync + 'static,
+{
+    /// Returns the existing objects related to the object with the given ID.
+    async fn related_objects(&self, request: &Request, parent_id: &str) -> crate::Result<Vec<C>> {
+        let parent_pk = parse_id::<P>(parent_id)?;
+
+        Ok(C::objects()
+            .filter(Expr::eq(
+                Expr::field(self.foreign_key),
+                Expr::value(ForeignKey::<P>::PrimaryKey(parent_pk)),
+            ))
+            .order_by(Expr::field(C::PRIMARY_KEY_NAME), Order::Asc)
+            .all(request.context().database())
+            .await?)
+    }
+
+    fn to_inline_formset(
+        &self,
+        formset: FormSet<C>,
+        related_objects: &HashMap<String, C>,
+        permissions: ModelPermissions,
+    ) -> InlineFormSet {
+        let count_field_name = formset.count_field_name();
+        let forms = formset
+            .into_forms()
+            .into_iter()
+            .map(|form| {
+                let label = match form.object_id().and_then(|id| related_objects.get(id)) {
+                    Some(object) => object.display(),
+                    None => format!("New {}", C::name()),
+                };
+                InlineForm {
+                    label,
+                    object_id: form.object_id().map(ToOwned::to_owned),
+                    object_id_field_name: form.object_id_field_name(),
+                    remove_field_name: form.remove_field_name(),
+                    removed: form.is_removed(),
+                    foreign_key_id: format!("{}{}", form.prefix(), self.foreign_key),
+                    version_field_id: C::version_field()
+                        .map(|field| format!("{}{}", form.prefix(), field)),
+                    context: Box::new(form.into_context()),
+                }
+            })
+            .collect();
+
+        InlineFormSet {
+            name: C::name().to_owned(),
+            count_field_name,
+            can_delete: permissions.delete,
+            forms,
+        }
+    }
+
+    async fn related_objects_by_id(
+        &self,
+        request: &Request,
+        parent_id: &str,
+    ) -> crate::Result<HashMap<String, C>> {
+        Ok(self
+            .related_objects(request, parent_id)
+            .await?
+            .into_iter()
+            .map(|object| (object.id(), object))
+            .collect())
+    }
+}
+
+#[cfg(feature = "db")]
+#[async_trait]
+impl<C, P> DynAdminInline for ModelInline<C, P>
+where
+    C: AdminModel + Model<PrimaryKey: FromStr> + Form<Context: 'static> + Send + Sync,
+    P: Model<PrimaryKey: FromStr> + Send + Sync + 'static,
+{
+    fn name(&self) -> &str {
+        C::name()
+    }
+
+    fn url_name(&self) -> &str {
+        C::url_name()
+    }
+
+    fn foreign_key(&self) -> &str {
+        self.foreign_key.as_str()
+    }
+
+    async fn formset(
+        &self,
+        request: &Request,
+        parent_id: &str,
+        extra: usize,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet> {
+        let related_objects = self.related_objects(request, parent_id).await?;
+
+        let mut formset = FormSet::new(C::url_name());
+        for object in &related_objects {
+            formset.add_object(object.id(), object).await;
+        }
+        for _ in 0..extra {
+            formset.add_form();
+        }
+
+        let related_objects = related_objects
+            .into_iter()
+            .map(|object| (object.id(), object))
+            .collect();
+        Ok(self.to_inline_formset(formset, &related_objects, permissions))
+    }
+
+    async fn formset_from_request(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<InlineFormSet> {
+        let related_objects = self.related_objects_by_id(request, parent_id).await?;
+        let formset = FormSet::<C>::build_from_request(request, C::url_name()).await?;
+
+        Ok(self.to_inline_formset(formset, &related_objects, permissions))
+    }
+
+    async fn validate(
+        &self,
+        request: &mut Request,
+        parent_id: &str,
+        permissions: ModelPermissions,
+    ) -> crate::Result<Result<Box<dyn InlineChanges>, InlineFormSet>> {
+        let mut related_objects = self.related_objects_by_id(request, parent_id).await?;
+        let mut formset = FormSet::<C>::build_from_request(request, C::url_name()).await?;
+
+        for form in formset.forms_mut() {
+            if let Some(object_id) = form.object_id() {
+                // don't let the users edit the objects related to other objects
+                if !related_objects.contains_key(object_id) {
+                    return Err(NotFound::with_message(format!(
+                        "Object with ID `{object_id}` not found in model `{}`",
+                        C::name()
+                    ))
+                    .into());
+                }
+            } else if form.is_removed() || form.is_empty() {
+                continue;
+            }
+
+            let foreign_key = self.foreign_key.as_str();
+            let context = form.context_mut();
+            if let Err(error) = context
+                .set_value(foreign_key, FormFieldValue::new_text(parent_id))
+                .await
+            {
+                context.add_error(FormErrorTarget::Field(foreign_key), error);
+            }
+        }
+
+        let changes = match formset.into_result().await {
+            FormSetResult::Ok(changes) => changes,
+            FormSetResult::ValidationError(formset) => {
+                return Ok(Err(self.to_inline_formset(
+                    formset,
+                    &related_objects,
+                    permissions,
+                )));
+            }
+        };
+
+        let mut pending = Vec::with_capacity(changes.len());
+        for change in changes {
+            let change = match change {
+                FormSetChange::Add(object) => {
+                    if !permissions.add {
+                        return Err(PermissionDenied::new().into());
+                    }
+                    PendingChange::Add(object)
+                }
+                FormSetChange::Change {
+                    object_id,
+                    form: mut object,
+                } => {
+                    let previous = related_objects
+                        .remove(&object_id)
+                        .expect("object IDs should have been checked by now");
+                    object.set_primary_key(previous.primary_key().clone());
+
+                    let previous_values = FormValues::new(&previous.to_context().await);
+                    let changed_fields =
+                        FormValues::new(&object.to_context().await).changed_since(&previous_values);
+                    if changed_fields.is_empty() {
+                        continue;
+                    }
+                    PendingChange::Change(object, changed_fields)
+                }
+                FormSetChange::Remove { object_id } => {
+                    if !permissions.delete {
+                        return Err(PermissionDenied::new().into());
+                    }
+                    let object = related_objects
+                        .remove(&object_id)
+                        .expect("object IDs should have been checked by now");
+                    PendingChange::Remove(object)
+                }
+            };
+            pending.push(change);
+        }
+
+        Ok(Ok(Box::new(pending)))
+    }
+}
+
+#[cfg(feature = "db")]
+enum PendingChange<C> {
+    Add(C),
+    Change(C, Vec<String>),
+    Remove(C),
+}
+
+#[cfg(feature = "db")]
+#[async_trait]
+impl<C> InlineChanges for Vec<PendingChange<C>>
+where
+    C: AdminModel + Model + Send + Sync,
+{
+    async fn save(self: Box<Self>, request: &mut Request) -> crate::Result<Vec<InlineChange>> {
+        let mut changes = Vec::with_capacity(self.len());
+
+        for change in *self {
+            let (object_id, object_repr, action, changed_fields) = match change {
+                PendingChange::Add(mut object) => {
+                    object.insert(request.context().database()).await?;
+                    (
+                        object.id(),
+                        object.display(),
+                        LogEntryAction::Create,
+                        Vec::new(),
+                    )
+                }
+                PendingChange::Change(mut object, changed_fields) => {
+                    object.update(request.context().database()).await?;
+                    (
+                        object.id(),
+                        object.display(),
+                        LogEntryAction::Change,
+                        changed_fields,
+                    )
+                }
+                PendingChange::Remove(object) => {
+                    let object_id = object.id();
+                    C::remove_by_id(request, &object_id).await?;
+                    (
+                        object_id,
+                        object.display(),
+                        LogEntryAction::Delete,
+                        Vec::new(),
+                    )
+                }
+            };
+            changes.push(InlineChange {
+                object_id,
+                object_repr,
+                action,
+                changed_fields,
+            });
+        }
+
+        Ok(changes)
+    }
+}
+
+#[cfg(feature = "db")]
+fn parse_id<T: Model<PrimaryKey: FromStr>>(id: &str) -> crate::Result<T::PrimaryKey> {
+    T::PrimaryKey::from_str(id).map_err(|_| {
+        NotFound::with_message(format!("invalid ID for model `{}`: `{id}`", T::TABLE_NAME)).into()
+    })
+}
+
+#[cfg(all(test, feature = "db"))]
+mod tests {
+    use std::fmt::{Debug, Display, Formatter};
+
+    use cot::db::Auto;
+    use cot_macros::AdminModel;
+
+    use super::*;
+    use crate::db::migrations::{
+        Field, Migration, MigrationDependency, Operation, wrap_migrations,
+    };
+    use crate::db::{DatabaseField, ForeignKeyOnDeletePolicy, ForeignKeyOnUpdatePolicy, model};
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    struct Post {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        title: String,
+    }
+
+    impl Display for Post {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.title)
+        }
+    }
+
+    #[derive(Debug, Form, AdminModel)]
+    #[model]
+    struct Comment {
+        #[model(primary_key)]
+        id: Auto<i64>,
+        post: ForeignKey<Post>,
+        author: String,
+        text: String,
+    }
+
+    impl Display for Comment {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.text)
+        }
+    }
+
+    struct TestMigration;
+
+    impl Migration for TestMigration {
+        const APP_NAME: &'static str = "cot";
+        const MIGRATION_NAME: &'static str = "m_0001_initial";
+        const DEPENDENCIES: &'static [MigrationDependency] = &[];
+        const OPERATIONS: &'static [Operation] = &[
+            Operation::create_model()
+                .table_name(<Post as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+                ])
+                .build(),
+            Operation::create_model()
+                .table_name(<Comment as Model>::TABLE_NAME)
+                .fields(&[
+                    Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                        .primary_key()
+                        .auto(),
+                    Field::new(
+                        Identifier::new("post"),
+                        <ForeignKey<Post> as DatabaseField>::TYPE,
+                    )
+                    .foreign_key(
+                        <Post as Model>::TABLE_NAME,
+                        <Post as Model>::PRIMARY_KEY_NAME,
+                        ForeignKeyOnDeletePolicy::Restrict,
+                        ForeignKeyOnUpdatePolicy::Restrict,
+                    ),
+                    Field::new(Identifier::new("author"), <String as DatabaseField>::TYPE),
+                    Field::new(Identifier::new("text"), <String as DatabaseField>::TYPE),
+                ])
+                .build(),
+        ];
+    }
+
+    const ALL_PERMISSIONS: ModelPermissions = ModelPermissions {
+        view: true,
+        add: true,
+        change: true,
+        delete: true,
+    };
+
+    struct Fixture {
+        post: Post,
+        first: Comment,
+        second: Comment,
+        other: Comment,
+    }
+
+    async fn setup(test_db: &mut TestDatabase) -> Fixture {
+        test_db
+            .add_migrations(wrap_migrations(&[&TestMigration]))
+            .run_migrations()
+            .await;
+        let db = test_db.database();
+
+        let mut post = Post {
+            id: Auto::auto(),
+            title: "Post".to_owned(),
+        };
+        post.insert(&db).await.unwrap();
+        let mut other_post = Post {
+            id: Auto::auto(),
+            title: "Other post".to_owned(),
+        };
+        other_post.insert(&db).await.unwrap();
+
+        let mut comments = Vec::new();
+        for (post, author, text) in [
+            (&post, "Alice", "First"),
+            (&post, "Bob", "Second"),
+            (&other_post, "Carol", "Other"),
+        ] {
+            let mut comment = Comment {
+                id: Auto::auto(),
+                post: ForeignKey::from(post),
+                author: author.to_owned(),
+                text: text.to_owned(),
+            };
+            comment.insert(&db).await.unwrap();
+            comments.push(comment);
+        }
+        let [first, second, other] = comments.try_into().unwrap();
+
+        Fixture {
+            post,
+            first,
+            second,
+            other,
+        }
+    }
+
+    fn inline() -> AdminInline {
+        AdminInline::new::<Comment>(&CommentFields::post).with_extra(2)
+    }
+
+    fn post_request(test_db: &TestDatabase, data: &[(&str, &str)]) -> Request {
+        TestRequestBuilder::post("/")
+            .database(test_db.database())
+            .form_data(data)
+            .build()
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_formset(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let request = TestRequestBuilder::get("/")
+            .database(test_db.database())
+            .build();
+
+        let formset = inline()
+            .formset(&request, &fixture.post.id(), ALL_PERMISSIONS)
+            .await
+            .unwrap();
+
+        assert_eq!(formset.count_field_name, "comment-count");
+        assert!(formset.can_delete);
+        let labels: Vec<_> = formset
+            .forms
+            .iter()
+            .map(|form| form.label.as_str())
+            .collect();
+        assert_eq!(labels, ["First", "Second", "New Comment", "New Comment"]);
+        assert_eq!(formset.forms[0].object_id, Some(fixture.first.id()));
+        assert_eq!(formset.forms[0].foreign_key_id, "comment-0-post");
+        assert_eq!(formset.forms[2].object_id, None);
+
+        let formset = inline()
+            .formset(
+                &request,
+                &fixture.post.id(),
+                ModelPermissions {
+                    add: false,
+                    delete: false,
+                    ..ALL_PERMISSIONS
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(formset.forms.len(), 2);
+        assert!(!formset.can_delete);
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_validate_and_save(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let first_id = fixture.first.id();
+        let second_id = fixture.second.id();
+        let mut request = post_request(
+            test_db,
+            &[
+                ("comment-count", "4"),
+                ("comment-0-object-id", &first_id),
+                ("comment-0-author", "Alice"),
+                ("comment-0-text", "First (edited)"),
+                ("comment-1-object-id", &second_id),
+                ("comment-1-remove-form", "on"),
+                ("comment-1-author", "Bob"),
+                ("comment-1-text", "Second"),
+                ("comment-2-author", "Dave"),
+                ("comment-2-text", "New"),
+                ("comment-3-author", ""),
+                ("comment-3-text", ""),
+            ],
+        );
+
+        let changes = inline()
+            .validate(&mut request, &fixture.post.id(), ALL_PERMISSIONS)
+            .await
+            .unwrap()
+            .unwrap();
+        let changes = changes.save(&mut request).await.unwrap();
+
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|change| (change.object_repr.as_str(), change.action))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("First (edited)", LogEntryAction::Change),
+                ("Second", LogEntryAction::Delete),
+                ("New", LogEntryAction::Create),
+            ]
+        );
+        assert_eq!(changes[0].object_id, first_id);
+        assert_eq!(changes[0].changed_fields, ["text"]);
+
+        let comments = Comment::objects()
+            .order_by(Expr::field("id"), Order::Asc)
+            .all(&test_db.database())
+            .await
+            .unwrap();
+        let comments: Vec<_> = comments
+            .iter()
+            .map(|comment| (comment.text.as_str(), *comment.post.primary_key()))
+            .collect();
+        assert_eq!(
+            comments,
+            [
+                ("First (edited)", fixture.post.id),
+                ("Other", *fixture.other.post.primary_key()),
+                ("New", fixture.post.id),
+            ]
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_validate_unchanged(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let first_id = fixture.first.id();
+        let mut request = post_request(
+            test_db,
+            &[
+                ("comment-count", "1"),
+                ("comment-0-object-id", &first_id),
+                ("comment-0-author", "Alice"),
+                ("comment-0-text", "First"),
+            ],
+        );
+
+        let changes = inline()
+            .validate(&mut request, &fixture.post.id(), ALL_PERMISSIONS)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(changes.save(&mut request).await.unwrap().is_empty());
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_validate_invalid(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let mut request = post_request(
+            test_db,
+            &[
+                ("comment-count", "2"),
+                ("comment-0-author", "Dave"),
+                ("comment-1-author", ""),
+                ("comment-1-text", ""),
+            ],
+        );
+
+        let Err(formset) = inline()
+            .validate(&mut request, &fixture.post.id(), ALL_PERMISSIONS)
+            .await
+            .unwrap()
+        else {
+            panic!("Expected a validation error");
+        };
+
+        assert_eq!(formset.forms.len(), 2);
+        assert!(formset.forms[0].context.has_errors());
+        assert!(!formset.forms[1].context.has_errors());
+        assert_eq!(
+            Comment::objects().count(&test_db.database()).await.unwrap(),
+            3
+        );
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_validate_other_parent(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let other_id = fixture.other.id();
+        let mut request = post_request(
+            test_db,
+            &[
+                ("comment-count", "1"),
+                ("comment-0-object-id", &other_id),
+                ("comment-0-author", "Mallory"),
+                ("comment-0-text", "Hijacked"),
+            ],
+        );
+
+        let result = inline()
+            .validate(&mut request, &fixture.post.id(), ALL_PERMISSIONS)
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[cot_macros::dbtest]
+    async fn inline_validate_without_add_permission(test_db: &mut TestDatabase) {
+        let fixture = setup(test_db).await;
+        let mut request = post_request(
+            test_db,
+            &[
+                ("comment-count", "1"),
+                ("comment-0-author", "Dave"),
+                ("comment-0-text", "New"),
+            ],
+        );
+
+        let result = inline()
+            .validate(
+                &mut request,
+                &fixture.post.id(),
+                ModelPermissions {
+                    add: false,
+                    ..ALL_PERMISSIONS
+                },
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! Audit log of the changes made in the admin panel.
+//!
+//! Every object created, changed, removed, or restored through the admin panel
+//! is recorded as a [`LogEntry`] (provided that the database is enabled), so
+//! that it's possible to find out who changed an object and when. The entries
+//! are shown on the history page of each object and in the recent actions
+//! panel on the admin index page.
+//!
+//! The entries are written after the changes they record are saved, and not in
+//! the same database transaction, so a change can be missing from the log if
+//! writing its entry fails (in which case the request fails with an error, even
+//! though the change has been saved).
+
+#[cfg(feature = "db")]
+pub mod migrations;
+
+#[cfg(feature = "db")]
+use std::borrow::Cow;
+
+#[cfg(feature = "db")]
+use chrono::{DateTime, FixedOffset, Utc};
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+#[cfg(feature = "db")]
+use cot::db::Auto;
+#[cfg(feature = "db")]
+use thiserror::Error;
+
+#[cfg(feature = "db")]
+use crate::auth::{User, UserId};
+#[cfg(feature = "db")]
+use crate::db::query::{Expr, Order};
+#[cfg(feature = "db")]
+use crate::db::{
+    ColumnType, DatabaseBackend, DatabaseError, DatabaseField, DbValue, FromDbValue, Model,
+    SqlxValueRef, ToDbValue, model,
+};
+
+#[cfg(feature = "db")]
+const MAX_ACTION_LENGTH: u32 = 16;
+
+/// The kind of action recorded in a [`LogEntry`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::admin::log::LogEntryAction;
+///
+/// assert_eq!(LogEntryAction::Change.as_str(), "change");
+/// assert_eq!(LogEntryAction::Change.label(), "Changed");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LogEntryAction {
+    /// The object has been created.
+    Create,
+    /// The object has been changed.
+    Change,
+    /// The object has been removed.
+    Delete,
+    /// The removed object has been restored.
+    Restore,
+}
+
+impl LogEntryAction {
+    /// All the log entry actions.
+    pub const ALL: [Self; 4] = [Self::Create, Self::Change, Self::Delete, Self::Restore];
+
+    /// Returns the name of the action, as stored in the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::log::LogEntryAction;
+    ///
+    /// assert_eq!(LogEntryAction::Delete.as_str(), "delete");
+    /// ```
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Change => "change",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+        }
+    }
+
+    /// Returns the human-readable label of the action.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::admin::log::LogEntryAction;
+    ///
+    /// assert_eq!(LogEntryAction::Create.label(), "Created");
+    /// ```
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Create => "Created",
+            Self::Change => "Changed",
+            Self::Delete => "Removed",
+            Self::Restore => "Restored",
+        }
+    }
+
+    #[cfg(feature = "db")]
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.as_str() == name)
+    }
+}
+
+#[cfg(feature = "db")]
+#[derive(Debug, Error)]
+#[error("unknown log entry action: `{0}`")]
+struct UnknownLogEntryAction(String);
+
+#[cfg(feature = "db")]
+fn decode_action(name: String) -> crate::db::Result<LogEntryAction> {
+    LogEntryAction::from_name(&name)
+        .ok_or_else(|| DatabaseError::value_decode(UnknownLogEntryAction(name)))
+}
+
+#[cfg(feature = "db")]
+impl DatabaseField for LogEntryAction {
+    const TYPE: ColumnType = ColumnType::String(MAX_ACTION_LENGTH);
+}
+
+#[cfg(feature = "db")]
+impl FromDbValue for LogEntryAction {
+    #[cfg(feature = "sqlite")]
+    fn from_sqlite(value: crate::db::impl_sqlite::SqliteValueRef<'_>) -> crate::db::Result<Self> {
+        decode_action(value.get::<String>()?)
+    }
+
+    #[cfg(feature = "postgres")]
+    fn from_postgres(
+        value: crate::db::impl_postgres::PostgresValueRef<'_>,
+    ) -> crate::db::Result<Self> {
+        decode_action(value.get::<String>()?)
+    }
+
+    #[cfg(feature = "mysql")]
+    fn from_mysql(value: crate::db::impl_mysql::MySqlValueRef<'_>) -> crate::db::Result<Self> {
+        decode_action(value.get::<String>()?)
+    }
+}
+
+#[cfg(feature = "db")]
+impl ToDbValue for LogEntryAction {
+    fn to_db_value(&self) -> DbValue {
+        self.as_str().into()
+    }
+}
+
+/// A record of an action performed on an object in the admin panel.
+///
+/// The entry keeps the ID and the username of the user who performed the
+/// action, as well as the ID and the display text of the object, so that it
+/// remains meaningful after the user or the object has been removed.
+#[cfg(feature = "db")]
+#[derive(Debug, Clone)]
+#[model]
+pub struct LogEntry {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user_id: Option<String>,
+    username: Option<String>,
+    model_name: String,
+    object_id: String,
+    object_repr: String,
+    action: LogEntryAction,
+    changed_fields: String,
+    timestamp: DateTime<FixedOffset>,
+}
+
+#[cfg(feature = "db")]
+impl LogEntry {
+    /// Records an action performed by the user on an object and saves it to
+    /// the database.
+    ///
+    /// `model_name` is the [URL name](crate::admin::AdminModel::url_name) of
+    /// the model of the object, and `changed_fields` are the names of the
+    /// fields whose values have been changed by the action.
+    ///
+    /// The entries are recorded automatically for the changes made through
+    /// the admin panel; this is useful for custom
+    /// [actions](crate::admin::AdminAction) changing the objects.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry could not be saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::admin::log::{LogEntry, LogEntryAction};
+    /// use cot::auth::User;
+    /// use cot::db::Database;
+    ///
+    /// async fn log_shipped(
+    ///     db: &Database,
+    ///     user: &(dyn User + Send + Sync),
+    ///     order_id: &str,
+    /// ) -> cot::Result<()> {
+    ///     LogEntry::record(
+    ///         db,
+    ///         user,
+    ///         "order",
+    ///         order_id,
+    ///         &format!("Order #{order_id}"),
+    ///         LogEntryAction::Change,
+    ///         ["status"],
+    ///     )
+    ///     .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn record<DB, F>(
+        db: &DB,
+        user: &(dyn User + Send + Sync),
+        model_name: &str,
+        object_id: &str,
+        object_repr: &str,
+        action: LogEntryAction,
+        changed_fields: F,
+    ) -> crate::db::Result<Self>
+    where
+        DB: DatabaseBackend,
+        F: IntoIterator<Item: AsRef<str>>,
+    {
+        let changed_fields: Vec<_> = changed_fields
+            .into_iter()
+            .map(|field| field.as_ref().to_owned())
+            .collect();
+
+        let mut entry = Self {
+            id: Auto::auto(),
+            user_id: user.id().map(|id| match id {
+                UserId::Int(id) => id.to_string(),
+                UserId::String(id) => id,
+            }),
+            username: user.username().map(Cow::into_owned),
+            model_name: model_name.to_owned(),
+            object_id: object_id.to_owned(),
+            object_repr: object_repr.to_owned(),
+            action,
+            changed_fields: changed_fields.join(" "),
+            timestamp: Utc::now().fixed_offset(),
+        };
+        entry.insert(db).await?;
+
+        Ok(entry)
+    }
+
+    /// Returns the log entries of the object with the given ID of the model
+    /// with the given URL name, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entries could not be fetched from the database.
+    pub async fn for_object<DB: DatabaseBackend>(
+        db: &DB,
+        model_name: &str,
+        object_id: &str,
+    ) -> crate::db::Result<Vec<Self>> {
+        Self::objects()
+            .filter(Expr::and(
+                Expr::eq(Expr::field("model_name"), Expr::value(model_name)),
+                Expr::eq(Expr::field("object_id"), Expr::value(object_id)),
+            ))
+            .order_by(Expr::field("timestamp"), Order::Desc)
+            .order_by(Expr::field("id"), Order::Desc)
+            .all(db)
+            .await
+    }
+
+    /// Returns at most `limit` most recent log entries of the models with the
+    /// given URL names, newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entries could not be fetched from the database.
+    pub async fn recent<DB: DatabaseBackend>(
+        db: &DB,
+        model_names: &[&str],
+        limit: u64,
+    ) -> crate::db::Result<Vec<Self>> {
+        let Some(filter) = model_names
+            .iter()
+            .map(|model_name| Expr::eq(Expr::field("model_name"), Expr::value(*model_name)))
+            .reduce(Expr::or)
+        else {
+            return Ok(Vec::new());
+        };
+
+        Self::objects()
+            .filter(filter)
+            .order_by(Expr::field("timestamp"), Order::Desc)
+            .order_by(Expr::field("id"), Order::Desc)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    /// Returns the ID of the user who performed the action, or [`None`] if
+    /// the user had no ID.
+    #[must_use]
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    /// Returns the username of the user who performed the action, or [`None`]
+    /// if the user had no username.
+    #[must_use]
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    /// Returns the URL name of the model of the object.
+    #[must_use]
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    /// Returns the ID of the object.
+    #[must_use]
+    pub fn object_id(&self) -> &str {
+        &self.object_id
+    }
+
+    /// Returns the display text of the object at the time of the action.
+    #[must_use]
+    pub fn object_repr(&self) -> &str {
+        &self.object_repr
+    }
+
+    /// Returns the kind of the action.
+    #[must_use]
+    pub fn action(&self) -> LogEntryAction {
+        self.action
+    }
+
+    /// Returns the names of the fields changed by the action.
+    pub fn changed_fields(&self) -> impl Iterator<Item = &str> {
+        self.changed_fields.split_whitespace()
+    }
+
+    /// Returns the time the action was performed.
+    #[must_use]
+    pub fn timestamp(&self) -> DateTime<FixedOffset> {
+        self.timestamp
+    }
+}
+
+#[cfg(all(test, feature = "db"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_entry_action_from_name() {
+        for action in LogEntryAction::ALL {
+            assert_eq!(LogEntryAction::from_name(action.as_str()), Some(action));
+        }
+        assert_eq!(LogEntryAction::from_name("publish"), None);
+    }
+}
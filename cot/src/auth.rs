@@ -5,17 +5,49 @@
 //! verification.
 //!
 //! For the default way to store users in the database, see the [`db`] module.
+//! For resetting forgotten passwords, see the [`password_reset`] module, and
+//! for registering new users and verifying their email addresses, the
+//! `signup` module.
+//! For authenticating API clients with bearer tokens, see the `api_token`
+//! module, for JSON Web Tokens, the `jwt` module, and for HTTP Basic
+//! authentication, the [`basic`] module. Two-factor
+//! authentication with one-time codes is provided by the `totp` module, and
+//! logging in with external identity providers by the `oauth` module.
+//! To restrict views to logged-in users, or users with specific permissions,
+//! see the [`guard`] module.
+//! Repeated failed login attempts can be throttled with the [`throttle`]
+//! module, and weak passwords rejected with the [`password_validation`]
+//! module.
 
+#[cfg(feature = "db")]
+pub mod api_token;
+pub mod basic;
 #[cfg(feature = "db")]
 pub mod db;
+pub mod guard;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+#[cfg(feature = "db")]
+pub mod oauth;
+pub mod password_reset;
+pub mod password_validation;
+#[cfg(feature = "db")]
+pub mod signup;
+pub mod throttle;
+#[cfg(feature = "db")]
+pub mod totp;
 
 use std::any::Any;
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::net::IpAddr;
 use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
 /// backwards compatible shim for form Password type.
 use async_trait::async_trait;
 use chrono::{DateTime, FixedOffset};
+use cot_core::StatusCode;
 use cot_core::error::impl_into_cot_error;
 use derive_more::with_trait::Debug;
 #[cfg(test)]
@@ -25,7 +57,10 @@ use serde::{Deserialize, Serialize};
 use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+use crate::auth::password_validation::{PasswordValidationError, PasswordValidators};
+use crate::auth::throttle::LoginThrottle;
 use crate::config::SecretKey;
+use crate::csrf::CsrfToken;
 #[cfg(feature = "db")]
 use crate::db::{ColumnType, DatabaseField, DbValue, FromDbValue, SqlxValueRef, ToDbValue};
 use crate::request::{Request, RequestExt};
@@ -54,8 +89,39 @@ pub enum AuthError {
     /// supported.
     #[error("{ERROR_PREFIX} tried to get a user by an unsupported user ID type")]
     UserIdTypeNotSupported,
+    /// The operation requires a session, but the user has been authenticated
+    /// without one (for instance, with an API token).
+    #[error("{ERROR_PREFIX} the operation requires a session")]
+    SessionRequired,
+    /// There have been too many failed login attempts for the username or
+    /// from the IP address, so they are temporarily locked out. See the
+    /// [`throttle`] module.
+    #[error("{ERROR_PREFIX} too many failed login attempts; retry after {retry_after:?}")]
+    TooManyAttempts {
+        /// The time after which the login can be attempted again.
+        retry_after: Duration,
+    },
+    /// The password has been rejected by the
+    /// [password validators](password_validation).
+    #[error("{ERROR_PREFIX} the password is invalid: {0}")]
+    PasswordInvalid(#[from] PasswordValidationError),
+    /// The credentials are valid, but the user hasn't verified their email
+    /// address yet, which is
+    /// [required to log
+    /// in](crate::config::EmailVerificationConfig::required_for_login).
+    #[error("{ERROR_PREFIX} the email address of the user has not been verified")]
+    EmailNotVerified,
+}
+impl From<AuthError> for crate::Error {
+    fn from(error: AuthError) -> Self {
+        let status_code = match error {
+            // the user is known; it's the new password that has been rejected
+            AuthError::PasswordInvalid(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        crate::Error::with_status(error, status_code)
+    }
 }
-impl_into_cot_error!(AuthError, UNAUTHORIZED);
 
 impl AuthError {
     /// Creates a new [`AuthError::UserBackend`] error from a backend error.
@@ -152,6 +218,63 @@ pub trait User {
         None
     }
 
+    /// Returns whether the user is a superuser.
+    ///
+    /// A superuser implicitly has all the permissions, without them having to
+    /// be granted explicitly (see [`User::has_perm`]).
+    ///
+    /// [`AnonymousUser`] always returns `false`.
+    fn is_superuser(&self) -> bool {
+        false
+    }
+
+    /// Returns whether the user is a staff member.
+    ///
+    /// Staff members are allowed to log in to the
+    /// [admin panel](crate::admin::AdminApp). Note that what they can actually
+    /// do there still depends on their permissions.
+    ///
+    /// [`AnonymousUser`] always returns `false`.
+    fn is_staff(&self) -> bool {
+        false
+    }
+
+    /// Returns whether the user has the given permission.
+    ///
+    /// Permissions are identified by their codenames, such as
+    /// `"database-user.change"` (see
+    /// [`AdminPermission`](crate::admin::AdminPermission) for the
+    /// permissions used by the admin panel).
+    ///
+    /// By default, this returns `true` for active superusers and `false` for
+    /// everyone else. User types that store the permissions granted to them
+    /// should override this method; see [`UserWithPermissions`] for a
+    /// ready-made implementation. The permissions granted through the
+    /// [`AuthBackend`] are only taken into account for the current user after
+    /// [`Auth::load_permissions`] is called.
+    ///
+    /// [`AnonymousUser`] always returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::Auth;
+    /// use cot::html::Html;
+    ///
+    /// async fn view(auth: Auth) -> cot::Result<Html> {
+    ///     auth.load_permissions().await?;
+    ///     if auth.user().has_perm("article.publish") {
+    ///         Ok(Html::new("You can publish articles!"))
+    ///     } else {
+    ///         Ok(Html::new("You cannot publish articles."))
+    ///     }
+    /// }
+    /// ```
+    fn has_perm(&self, perm: &str) -> bool {
+        let _ = perm;
+        self.is_active() && self.is_superuser()
+    }
+
     /// Returns the user's session authentication hash.
     ///
     /// This is used to verify that the session hash stored in the session
@@ -291,22 +414,83 @@ impl UserId {
 }
 
 /// A helper wrapper over `Arc<dyn User>` to provide a `Debug` implementation.
-#[repr(transparent)]
-struct UserWrapper(Arc<dyn User + Send + Sync>);
+///
+/// It also keeps track of whether the user's permissions have already been
+/// loaded with [`Auth::load_permissions`].
+struct UserWrapper {
+    user: Arc<dyn User + Send + Sync>,
+    permissions_loaded: bool,
+}
+
+impl UserWrapper {
+    fn new(user: Arc<dyn User + Send + Sync>) -> Self {
+        Self {
+            user,
+            permissions_loaded: false,
+        }
+    }
+}
 
 impl Debug for UserWrapper {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Arc<dyn User + Send + Sync>")
-            .field("id", &self.0.id())
-            .field("username", &self.0.username())
-            .field("is_active", &self.0.is_active())
-            .field("is_authenticated", &self.0.is_authenticated())
-            .field("last_login", &self.0.last_login())
-            .field("joined", &self.0.joined())
+            .field("id", &self.user.id())
+            .field("username", &self.user.username())
+            .field("is_active", &self.user.is_active())
+            .field("is_authenticated", &self.user.is_authenticated())
+            .field("last_login", &self.user.last_login())
+            .field("joined", &self.user.joined())
+            .field("permissions_loaded", &self.permissions_loaded)
             .finish()
     }
 }
 
+/// A user behind an [`Arc`], so that the current user can be wrapped in
+/// [`UserWithPermissions`] once their permissions are loaded.
+struct SharedUser(Arc<dyn User + Send + Sync>);
+
+impl User for SharedUser {
+    fn id(&self) -> Option<UserId> {
+        self.0.id()
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        self.0.username()
+    }
+
+    fn is_active(&self) -> bool {
+        self.0.is_active()
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.0.is_authenticated()
+    }
+
+    fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.0.last_login()
+    }
+
+    fn joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.0.joined()
+    }
+
+    fn is_superuser(&self) -> bool {
+        self.0.is_superuser()
+    }
+
+    fn is_staff(&self) -> bool {
+        self.0.is_staff()
+    }
+
+    fn has_perm(&self, perm: &str) -> bool {
+        self.0.has_perm(perm)
+    }
+
+    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
+        self.0.session_auth_hash(secret_key)
+    }
+}
+
 /// An anonymous, unauthenticated user.
 ///
 /// This is used to represent a user that is not authenticated. It is returned
@@ -323,6 +507,229 @@ impl PartialEq for AnonymousUser {
 
 impl User for AnonymousUser {}
 
+/// A user together with the set of permissions that have been granted to it.
+///
+/// This is a helper for the [`AuthBackend`] implementations that load the
+/// user's permissions along with the user. It implements the [`User`] trait by
+/// delegating to the wrapped user, except for [`User::has_perm`], which also
+/// checks the stored permission set. Since permissions are only granted to
+/// active users, an inactive user has no permissions, even if they are in the
+/// set.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::{AnonymousUser, User, UserWithPermissions};
+///
+/// let user = UserWithPermissions::new(AnonymousUser, ["article.publish"]);
+/// assert!(user.permissions().any(|perm| perm == "article.publish"));
+/// // anonymous users are never active, so they don't have any permissions
+/// assert!(!user.has_perm("article.publish"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserWithPermissions<U> {
+    user: U,
+    permissions: HashSet<String>,
+}
+
+impl<U: User> UserWithPermissions<U> {
+    /// Creates a new [`UserWithPermissions`] from a user and the codenames of
+    /// the permissions granted to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{AnonymousUser, UserWithPermissions};
+    ///
+    /// let user = UserWithPermissions::new(AnonymousUser, ["article.publish", "article.review"]);
+    /// ```
+    #[must_use]
+    pub fn new<I, P>(user: U, permissions: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        Self {
+            user,
+            permissions: permissions.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the wrapped user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{AnonymousUser, UserWithPermissions};
+    ///
+    /// let user = UserWithPermissions::new(AnonymousUser, ["article.publish"]);
+    /// assert_eq!(user.user(), &AnonymousUser);
+    /// ```
+    #[must_use]
+    pub fn user(&self) -> &U {
+        &self.user
+    }
+
+    /// Consumes this object, returning the wrapped user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{AnonymousUser, UserWithPermissions};
+    ///
+    /// let user = UserWithPermissions::new(AnonymousUser, ["article.publish"]);
+    /// assert_eq!(user.into_user(), AnonymousUser);
+    /// ```
+    #[must_use]
+    pub fn into_user(self) -> U {
+        self.user
+    }
+
+    /// Returns the codenames of the permissions granted to the user, in no
+    /// particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::{AnonymousUser, UserWithPermissions};
+    ///
+    /// let user = UserWithPermissions::new(AnonymousUser, ["article.publish"]);
+    /// assert_eq!(
+    ///     user.permissions().collect::<Vec<_>>(),
+    ///     vec!["article.publish"]
+    /// );
+    /// ```
+    pub fn permissions(&self) -> impl Iterator<Item = &str> {
+        self.permissions.iter().map(String::as_str)
+    }
+}
+
+impl<U: User> User for UserWithPermissions<U> {
+    fn id(&self) -> Option<UserId> {
+        self.user.id()
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        self.user.username()
+    }
+
+    fn is_active(&self) -> bool {
+        self.user.is_active()
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.user.is_authenticated()
+    }
+
+    fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.last_login()
+    }
+
+    fn joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.user.joined()
+    }
+
+    fn is_superuser(&self) -> bool {
+        self.user.is_superuser()
+    }
+
+    fn is_staff(&self) -> bool {
+        self.user.is_staff()
+    }
+
+    fn has_perm(&self, perm: &str) -> bool {
+        self.user.has_perm(perm) || (self.user.is_active() && self.permissions.contains(perm))
+    }
+
+    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
+        self.user.session_auth_hash(secret_key)
+    }
+}
+
+/// An error returned when a user is not allowed to access a resource.
+///
+/// This results in a `403 Forbidden` response.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::{Auth, PermissionDenied};
+/// use cot::html::Html;
+///
+/// async fn publish(auth: Auth) -> cot::Result<Html> {
+///     auth.load_permissions().await?;
+///     if !auth.user().has_perm("article.publish") {
+///         return Err(PermissionDenied::new().into());
+///     }
+///
+///     Ok(Html::new("Published!"))
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Error)]
+#[error("permission denied")]
+#[non_exhaustive]
+pub struct PermissionDenied;
+impl_into_cot_error!(PermissionDenied, FORBIDDEN);
+
+impl PermissionDenied {
+    /// Creates a new [`PermissionDenied`] error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::PermissionDenied;
+    ///
+    /// let error = PermissionDenied::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// An error returned when a resource can only be accessed by authenticated
+/// users, but the user is not logged in.
+///
+/// This results in a `401 Unauthorized` response. It is returned by the
+/// [`LoginRequired`](guard::LoginRequired) handler wrapper and the
+/// [`RequireUser`](guard::RequireUser) extractor.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::{Auth, AuthenticationRequired};
+/// use cot::html::Html;
+///
+/// async fn profile(auth: Auth) -> cot::Result<Html> {
+///     if !auth.user().is_authenticated() {
+///         return Err(AuthenticationRequired::new().into());
+///     }
+///
+///     Ok(Html::new("Your profile"))
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Error)]
+#[error("authentication required")]
+#[non_exhaustive]
+pub struct AuthenticationRequired;
+impl_into_cot_error!(AuthenticationRequired, UNAUTHORIZED);
+
+impl AuthenticationRequired {
+    /// Creates a new [`AuthenticationRequired`] error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::AuthenticationRequired;
+    ///
+    /// let error = AuthenticationRequired::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
 /// A session authentication hash.
 ///
 /// This is used to verify that the session hash stored in the session object is
@@ -526,6 +933,43 @@ impl PasswordHash {
         Self(hash)
     }
 
+    /// Creates a new password hash from a password, after checking the
+    /// password with the given validators.
+    ///
+    /// The username of the user the password is set for should be passed, if
+    /// known, so that the validators can check the password against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::PasswordInvalid`] if the password doesn't pass the
+    /// validation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::PasswordHash;
+    /// use cot::auth::password_validation::{MinimumLengthValidator, PasswordValidators};
+    /// use cot::common_types::Password;
+    ///
+    /// let validators = PasswordValidators::new().validator(MinimumLengthValidator::new(8));
+    ///
+    /// assert!(
+    ///     PasswordHash::from_password_validated(&Password::new("short"), None, &validators).is_err()
+    /// );
+    /// assert!(
+    ///     PasswordHash::from_password_validated(&Password::new("long enough"), None, &validators)
+    ///         .is_ok()
+    /// );
+    /// ```
+    pub fn from_password_validated(
+        password: &crate::common_types::Password,
+        username: Option<&str>,
+        validators: &PasswordValidators,
+    ) -> Result<Self> {
+        validators.validate(password, username)?;
+        Ok(Self::from_password(password))
+    }
+
     /// Verifies a password against the hash.
     ///
     /// This method returns one of the following values:
@@ -724,6 +1168,29 @@ impl Auth {
         })
     }
 
+    /// Creates an [`Auth`] object for a user that has been authenticated
+    /// without a session, for instance, with a token sent in a request
+    /// header.
+    ///
+    /// Logging in and out with such an object only changes the user for the
+    /// current request.
+    pub(crate) fn stateless(
+        backend: Arc<dyn AuthBackend>,
+        user: Box<dyn User + Send + Sync>,
+        secret_key: SecretKey,
+    ) -> Self {
+        Self {
+            inner: Arc::new(AuthInner {
+                session: None,
+                backend,
+                secret_key,
+                user: Mutex::new(UserWrapper::new(Arc::from(user))),
+                throttle: None,
+                client_ip: None,
+            }),
+        }
+    }
+
     /// Returns the current user.
     ///
     /// This uses the auth backend configured in
@@ -735,6 +1202,40 @@ impl Auth {
         self.inner.user()
     }
 
+    /// Loads the permissions of the current user, so that they are taken into
+    /// account by [`User::has_perm`] on the object returned by
+    /// [`user`](Self::user).
+    ///
+    /// The permissions are not loaded together with the user, so that the
+    /// requests that don't check them don't have to pay for the queries. They
+    /// are fetched with [`AuthBackend::get_permissions`] the first time this
+    /// method is called, and stay loaded until the user is logged in or out.
+    /// The [`PermissionRequired`](guard::PermissionRequired) guard and the
+    /// [admin panel](crate::admin::AdminApp) call this method automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the [`AuthBackend`] fails to fetch the permissions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::Auth;
+    /// use cot::html::Html;
+    ///
+    /// async fn view(auth: Auth) -> cot::Result<Html> {
+    ///     auth.load_permissions().await?;
+    ///     if auth.user().has_perm("article.publish") {
+    ///         Ok(Html::new("You can publish articles!"))
+    ///     } else {
+    ///         Ok(Html::new("You cannot publish articles."))
+    ///     }
+    /// }
+    /// ```
+    pub async fn load_permissions(&self) -> Result<()> {
+        self.inner.load_permissions().await
+    }
+
     /// Authenticates a user with the given credentials.
     ///
     /// This uses the auth backend configured in
@@ -746,10 +1247,18 @@ impl Auth {
     /// credentials are valid and returns the user object. To log the user
     /// in the current session, use the [`login`](Self::login) method.
     ///
+    /// If the [login throttling](crate::config::ProjectConfig::login_throttle)
+    /// is enabled, the failed attempts are recorded, and the attempts for
+    /// usernames and IP addresses that have failed too many times are
+    /// rejected without even checking the credentials.
+    ///
     /// # Errors
     ///
     /// Returns an error if the [`AuthBackend`] accepts the credentials but
     /// fails to fetch the user object.
+    ///
+    /// Returns [`AuthError::TooManyAttempts`] if the login attempt has been
+    /// throttled.
     pub async fn authenticate(
         &self,
         credentials: &(dyn Any + Send + Sync),
@@ -763,20 +1272,115 @@ impl Auth {
     /// in the session object and can be accessed using the [`user`](Self::user)
     /// method.
     ///
+    /// If the [`AuthBackend`] requires the user to verify a second
+    /// authentication factor (see [`AuthBackend::requires_second_factor`]),
+    /// the user is not logged in yet. Instead, the session is marked as
+    /// "password verified, second factor pending" (as with
+    /// [`login_pending_second_factor`](Self::login_pending_second_factor)),
+    /// and the login has to be finished with
+    /// [`complete_second_factor`](Self::complete_second_factor) once the
+    /// second factor is checked. Since the pending login is kept in the
+    /// session, such users cannot be logged in without one.
+    ///
+    /// Logging in changes the session ID and the [CSRF token](CsrfToken) of
+    /// the session, so that the ones obtained before cannot be used
+    /// afterwards.
+    ///
     /// # Errors
     ///
     /// Returns an error if the user object cannot be stored in the session
     /// object.
+    ///
+    /// Returns [`AuthError::SessionRequired`] if the user has to verify a
+    /// second factor, but there is no session (for instance, because the
+    /// current user has been authenticated with an API token).
     pub async fn login(&self, user: Box<dyn User + Send + Sync + 'static>) -> Result<()> {
+        if self.inner.backend.requires_second_factor(&*user).await? {
+            return self.inner.login_pending_second_factor(&*user).await;
+        }
+
         self.inner.login(user).await
     }
 
+    /// Marks the session as "password verified, second factor pending" for
+    /// the given user.
+    ///
+    /// The user is *not* logged in: [`user`](Self::user) keeps returning the
+    /// [`AnonymousUser`] until
+    /// [`complete_second_factor`](Self::complete_second_factor) is called.
+    /// This is called automatically by [`login`](Self::login) if
+    /// the [`AuthBackend`] requires a second factor for the user, but can also
+    /// be used directly, for instance to force a user to enroll in two-factor
+    /// authentication.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user ID cannot be stored in the session object.
+    pub async fn login_pending_second_factor(
+        &self,
+        user: Box<dyn User + Send + Sync + 'static>,
+    ) -> Result<()> {
+        self.inner.login_pending_second_factor(&*user).await
+    }
+
+    /// Returns the user who has verified their password, but still has to
+    /// verify their second authentication factor.
+    ///
+    /// Returns [`None`] if there is no login pending, or if the user has
+    /// changed their password since.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be read, or if the
+    /// [`AuthBackend`] fails to fetch the user object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::Auth;
+    /// use cot::html::Html;
+    ///
+    /// async fn two_factor(auth: Auth) -> cot::Result<Html> {
+    ///     match auth.pending_second_factor_user().await? {
+    ///         Some(user) => Ok(Html::new(format!(
+    ///             "Enter the code from your app, {}",
+    ///             user.username().unwrap_or_default()
+    ///         ))),
+    ///         None => Ok(Html::new("There is no login pending")),
+    ///     }
+    /// }
+    /// ```
+    pub async fn pending_second_factor_user(&self) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        self.inner.pending_second_factor_user().await
+    }
+
+    /// Finishes logging in the user whose second authentication factor is
+    /// pending, once the second factor has been verified.
+    ///
+    /// Returns `false` if there was no login pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be read or modified, or if the
+    /// [`AuthBackend`] fails to fetch the user object.
+    pub async fn complete_second_factor(&self) -> Result<bool> {
+        let Some(user) = self.inner.pending_second_factor_user().await? else {
+            return Ok(false);
+        };
+
+        self.inner.login(user).await?;
+        Ok(true)
+    }
+
     /// Logs out the current user.
     ///
     /// This removes the user object from the session object and logs the user
     /// out. Subsequent calls to [`user`](Self::user) will return the
     /// [`AnonymousUser`] object, unless a user is logged in again.
     ///
+    /// All the data stored in the session is removed, including the [CSRF
+    /// token](CsrfToken), so a new one is generated for the next request.
+    ///
     /// # Errors
     ///
     /// Returns an error if the user object cannot be removed from the session
@@ -784,11 +1388,41 @@ impl Auth {
     pub async fn logout(&self) -> Result<()> {
         self.inner.logout().await
     }
+
+    /// Updates the session of the current user after their password has
+    /// changed.
+    ///
+    /// Changing the password changes the user's
+    /// [`session_auth_hash`](User::session_auth_hash), which logs them out of
+    /// all their sessions. Calling this method with the updated user object
+    /// keeps them logged in in the current session, while the other sessions
+    /// stay logged out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the session cannot be modified.
+    pub async fn update_session_auth_hash(
+        &self,
+        user: Box<dyn User + Send + Sync + 'static>,
+    ) -> Result<()> {
+        if let Some(session) = &self.inner.session {
+            session.cycle_id().await?;
+            if let Some(session_auth_hash) = user.session_auth_hash(&self.inner.secret_key) {
+                session
+                    .insert(SESSION_HASH_SESSION_KEY, session_auth_hash.as_bytes())
+                    .await?;
+            }
+        }
+        *self.inner.user_lock() = UserWrapper::new(Arc::from(user));
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 struct AuthInner {
-    session: Session,
+    // `None` if the user has been authenticated without a session
+    session: Option<Session>,
     #[debug("..")]
     backend: Arc<dyn AuthBackend>,
     secret_key: SecretKey,
@@ -798,6 +1432,9 @@ struct AuthInner {
     // reference to the same `AuthInner` object with a mutable `user`.
     #[debug("..")]
     user: Mutex<UserWrapper>,
+    // `None` if the login throttling is disabled
+    throttle: Option<LoginThrottle>,
+    client_ip: Option<IpAddr>,
 }
 
 impl AuthInner {
@@ -816,10 +1453,12 @@ impl AuthInner {
             );
 
         Ok(Self {
-            session,
+            session: Some(session),
             backend,
             secret_key,
-            user: Mutex::new(UserWrapper(user)),
+            user: Mutex::new(UserWrapper::new(user)),
+            throttle: None,
+            client_ip: None,
         })
     }
 
@@ -830,42 +1469,145 @@ impl AuthInner {
         let backend = request.context().auth_backend().clone();
         let secret_key = config.secret_key.clone();
 
-        Self::new(session, backend, secret_key, &config.fallback_secret_keys).await
+        let mut inner =
+            Self::new(session, backend, secret_key, &config.fallback_secret_keys).await?;
+        inner.throttle = LoginThrottle::from_request(request);
+        if inner.throttle.is_some() {
+            inner.client_ip = LoginThrottle::client_ip(request);
+        }
+
+        Ok(inner)
     }
 
     fn user(&self) -> Arc<dyn User + Send + Sync> {
-        Arc::clone(&self.user_lock().0)
+        Arc::clone(&self.user_lock().user)
+    }
+
+    async fn load_permissions(&self) -> Result<()> {
+        let user = {
+            let user_wrapper = self.user_lock();
+            if user_wrapper.permissions_loaded {
+                return Ok(());
+            }
+            Arc::clone(&user_wrapper.user)
+        };
+
+        let permissions = if user.is_authenticated() {
+            self.backend.get_permissions(&*user).await?
+        } else {
+            HashSet::new()
+        };
+
+        let mut user_wrapper = self.user_lock();
+        // the user might have been logged in or out in the meantime
+        if Arc::ptr_eq(&user_wrapper.user, &user) {
+            *user_wrapper = UserWrapper {
+                user: Arc::new(UserWithPermissions::new(SharedUser(user), permissions)),
+                permissions_loaded: true,
+            };
+        }
+
+        Ok(())
     }
 
     async fn authenticate(
         &self,
         credentials: &(dyn Any + Send + Sync),
     ) -> Result<Option<Box<dyn User + Send + Sync>>> {
-        self.backend.authenticate(credentials).await
+        match &self.throttle {
+            Some(throttle) => {
+                throttle
+                    .authenticate(&*self.backend, credentials, self.client_ip)
+                    .await
+            }
+            None => self.backend.authenticate(credentials).await,
+        }
     }
 
     async fn login(&self, user: Box<dyn User + Send + Sync + 'static>) -> Result<()> {
-        // Mitigate the session fixation attack by changing the session ID:
-        // https://cheatsheetseries.owasp.org/cheatsheets/Session_Management_Cheat_Sheet.html#renew-the-session-id-after-any-privilege-level-change
-        self.session.cycle_id().await?;
+        if let Some(session) = &self.session {
+            // Mitigate the session fixation attack by changing the session ID:
+            // https://cheatsheetseries.owasp.org/cheatsheets/Session_Management_Cheat_Sheet.html#renew-the-session-id-after-any-privilege-level-change
+            session.cycle_id().await?;
+            CsrfToken::rotate(session).await?;
+
+            session.remove_value(PENDING_USER_ID_SESSION_KEY).await?;
+            session
+                .remove_value(PENDING_SESSION_HASH_SESSION_KEY)
+                .await?;
+            if let Some(user_id) = user.id() {
+                session.insert(USER_ID_SESSION_KEY, user_id).await?;
+            }
+            let secret_key = &self.secret_key;
+            if let Some(session_auth_hash) = user.session_auth_hash(secret_key) {
+                session
+                    .insert(SESSION_HASH_SESSION_KEY, session_auth_hash.as_bytes())
+                    .await?;
+            }
+
+            self.backend.user_logged_in(&*user).await?;
+        }
+        *self.user_lock() = UserWrapper::new(Arc::from(user));
+
+        Ok(())
+    }
+
+    async fn login_pending_second_factor(&self, user: &(dyn User + Send + Sync)) -> Result<()> {
+        let Some(session) = &self.session else {
+            return Err(AuthError::SessionRequired);
+        };
+        // The privilege level changes here as well, see `login`
+        session.cycle_id().await?;
+        session.remove_value(USER_ID_SESSION_KEY).await?;
+        session.remove_value(SESSION_HASH_SESSION_KEY).await?;
 
         if let Some(user_id) = user.id() {
-            self.session.insert(USER_ID_SESSION_KEY, user_id).await?;
+            session.insert(PENDING_USER_ID_SESSION_KEY, user_id).await?;
         }
-        let secret_key = &self.secret_key;
-        if let Some(session_auth_hash) = user.session_auth_hash(secret_key) {
-            self.session
-                .insert(SESSION_HASH_SESSION_KEY, session_auth_hash.as_bytes())
+        if let Some(session_auth_hash) = user.session_auth_hash(&self.secret_key) {
+            session
+                .insert(
+                    PENDING_SESSION_HASH_SESSION_KEY,
+                    session_auth_hash.as_bytes(),
+                )
                 .await?;
         }
-        *self.user_lock() = UserWrapper(Arc::from(user));
+        *self.user_lock() = UserWrapper::new(Arc::new(AnonymousUser));
 
         Ok(())
     }
 
+    async fn pending_second_factor_user(&self) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        let Some(session) = &self.session else {
+            return Ok(None);
+        };
+        let Some(user_id) = session.get::<UserId>(PENDING_USER_ID_SESSION_KEY).await? else {
+            return Ok(None);
+        };
+        let Some(user) = self.backend.get_by_id(user_id).await? else {
+            return Ok(None);
+        };
+
+        // Same as for a logged in user, a password change invalidates the
+        // pending login
+        if let Some(user_hash) = user.session_auth_hash(&self.secret_key) {
+            let stored_hash = session
+                .get::<Vec<u8>>(PENDING_SESSION_HASH_SESSION_KEY)
+                .await?
+                .unwrap_or_default();
+            if user_hash != SessionAuthHash::new(&stored_hash) {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(user))
+    }
+
     async fn logout(&self) -> Result<()> {
-        self.session.flush().await?;
-        *self.user_lock() = UserWrapper(Arc::new(AnonymousUser));
+        if let Some(session) = &self.session {
+            session.flush().await?;
+        }
+        *self.user_lock() = UserWrapper::new(Arc::new(AnonymousUser));
 
         Ok(())
     }
@@ -882,6 +1624,8 @@ impl AuthInner {
 
 const USER_ID_SESSION_KEY: &str = "__cot_auth_user_id";
 const SESSION_HASH_SESSION_KEY: &str = "__cot_auth_session_hash";
+const PENDING_USER_ID_SESSION_KEY: &str = "__cot_auth_pending_user_id";
+const PENDING_SESSION_HASH_SESSION_KEY: &str = "__cot_auth_pending_session_hash";
 
 async fn get_user_with_saved_id(
     session: &Session,
@@ -1000,6 +1744,71 @@ pub trait AuthBackend: Send + Sync {
     /// }
     /// ```
     async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>>;
+
+    /// Returns whether the user has to verify a second authentication factor
+    /// (such as a one-time code) before being logged in.
+    ///
+    /// If this returns `true`, [`Auth::login`] only marks the session as
+    /// "password verified, second factor pending", and the login has to be
+    /// finished with [`Auth::complete_second_factor`].
+    ///
+    /// The default implementation always returns `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the information cannot be fetched.
+    async fn requires_second_factor(&self, user: &(dyn User + Send + Sync)) -> Result<bool> {
+        let _ = user;
+        Ok(false)
+    }
+
+    /// Returns the codenames of the permissions granted to the user.
+    ///
+    /// This is called by [`Auth::load_permissions`], so that the permissions
+    /// are only fetched for the requests that check them. The permissions
+    /// returned are checked by [`User::has_perm`] in addition to the ones the
+    /// user object already reports.
+    ///
+    /// The default implementation returns an empty set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the permissions cannot be fetched.
+    async fn get_permissions(&self, user: &(dyn User + Send + Sync)) -> Result<HashSet<String>> {
+        let _ = user;
+        Ok(HashSet::new())
+    }
+
+    /// Returns the identifier of the user the credentials belong to (such as
+    /// the username), if the credentials type is supported and contains one.
+    ///
+    /// This is used to keep track of the failed login attempts per user when
+    /// the [login throttling](crate::config::ProjectConfig::login_throttle)
+    /// is enabled.
+    ///
+    /// The default implementation always returns `None`, which means that the
+    /// failed attempts are only tracked per IP address.
+    fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+        let _ = credentials;
+        None
+    }
+
+    /// Called after the user has been logged in to a session with
+    /// [`Auth::login`] (or [`Auth::complete_second_factor`]).
+    ///
+    /// This can be used to update the stored information about the user, such
+    /// as the time of their last login. It is not called for the users
+    /// authenticated without a session, for instance, with an API token.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user data could not be updated.
+    async fn user_logged_in(&self, user: &(dyn User + Send + Sync)) -> Result<()> {
+        let _ = user;
+        Ok(())
+    }
 }
 
 /// A no-op authentication backend.
@@ -1022,7 +1831,9 @@ impl AuthBackend for NoAuthBackend {
 
 #[cfg(test)]
 mod tests {
+    use std::net::SocketAddr;
     use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use mockall::predicate::eq;
 
@@ -1085,6 +1896,17 @@ mod tests {
             .build()
     }
 
+    #[test]
+    fn auth_error_status_code() {
+        let error = crate::Error::from(AuthError::PasswordInvalid(PasswordValidationError::new(
+            "This password is too short.",
+        )));
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+
+        let error = crate::Error::from(AuthError::SessionRequired);
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
     #[test]
     fn anonymous_user() {
         let anonymous_user = AnonymousUser;
@@ -1103,6 +1925,46 @@ mod tests {
         assert_eq!(anonymous_user, anonymous_user2);
     }
 
+    #[test]
+    fn anonymous_user_permissions() {
+        let anonymous_user = AnonymousUser;
+        assert!(!anonymous_user.is_superuser());
+        assert!(!anonymous_user.is_staff());
+        assert!(!anonymous_user.has_perm("article.publish"));
+    }
+
+    #[test]
+    fn user_with_permissions() {
+        let mut mock_user = MockUser::new();
+        mock_user.expect_is_active().return_const(true);
+        mock_user.expect_has_perm().return_const(false);
+        let user = UserWithPermissions::new(mock_user, ["article.publish"]);
+
+        assert!(user.has_perm("article.publish"));
+        assert!(!user.has_perm("article.remove"));
+    }
+
+    #[test]
+    fn user_with_permissions_inactive() {
+        let mut mock_user = MockUser::new();
+        mock_user.expect_is_active().return_const(false);
+        mock_user.expect_has_perm().return_const(false);
+        let user = UserWithPermissions::new(mock_user, ["article.publish"]);
+
+        assert!(!user.has_perm("article.publish"));
+    }
+
+    #[test]
+    fn user_with_permissions_superuser() {
+        let mut mock_user = MockUser::new();
+        mock_user.expect_has_perm().return_const(true);
+        mock_user.expect_is_superuser().return_const(true);
+        let user = UserWithPermissions::new(mock_user, Vec::<String>::new());
+
+        assert!(user.is_superuser());
+        assert!(user.has_perm("article.publish"));
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn password_hash() {
@@ -1208,6 +2070,80 @@ mod tests {
         assert_eq!(user.username(), Some(Cow::from("mockuser")));
     }
 
+    struct PasswordAuthBackend;
+
+    #[async_trait]
+    impl AuthBackend for PasswordAuthBackend {
+        async fn authenticate(
+            &self,
+            credentials: &(dyn Any + Send + Sync),
+        ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            let (_, password) = credentials
+                .downcast_ref::<(&str, &str)>()
+                .ok_or(AuthError::CredentialsTypeNotSupported)?;
+            if *password == "correct" {
+                Ok(Some(Box::new(MockUser::new())))
+            } else {
+                Ok(None)
+            }
+        }
+
+        async fn get_by_id(&self, _id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+
+        fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+            credentials
+                .downcast_ref::<(&str, &str)>()
+                .map(|(username, _)| (*username).to_owned())
+        }
+    }
+
+    #[cot::test]
+    async fn authenticate_throttled() {
+        let config = ProjectConfig::builder()
+            .login_throttle(
+                crate::config::LoginThrottleConfig::builder()
+                    .enabled(true)
+                    .max_attempts(2)
+                    .max_attempts_per_ip(3)
+                    .build(),
+            )
+            .build();
+        let mut request = TestRequestBuilder::get("/")
+            .with_session()
+            .config(config)
+            .auth_backend(PasswordAuthBackend)
+            .build();
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(SocketAddr::from((
+                [192, 0, 2, 1],
+                1234,
+            ))));
+        let auth = Auth::from_request(&mut request).await.unwrap();
+
+        let wrong: &(dyn Any + Send + Sync) = &("throttled_user", "wrong");
+        let correct: &(dyn Any + Send + Sync) = &("throttled_user", "correct");
+        assert!(auth.authenticate(wrong).await.unwrap().is_none());
+        assert!(auth.authenticate(correct).await.unwrap().is_some());
+
+        // a successful login resets the counter for the username
+        assert!(auth.authenticate(wrong).await.unwrap().is_none());
+        assert!(auth.authenticate(wrong).await.unwrap().is_none());
+        assert!(matches!(
+            auth.authenticate(correct).await,
+            Err(AuthError::TooManyAttempts { .. })
+        ));
+
+        // ...but not for the IP address
+        let other: &(dyn Any + Send + Sync) = &("other_throttled_user", "correct");
+        assert!(matches!(
+            auth.authenticate(other).await,
+            Err(AuthError::TooManyAttempts { .. })
+        ));
+    }
+
     #[cot::test]
     async fn login_logout() {
         let mut request = test_request(MockUser::new);
@@ -1231,6 +2167,139 @@ mod tests {
         assert!(session.is_empty().await);
     }
 
+    struct PermissionsAuthBackend {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl AuthBackend for PermissionsAuthBackend {
+        async fn authenticate(
+            &self,
+            _credentials: &(dyn Any + Send + Sync),
+        ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+
+        async fn get_by_id(&self, _id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+
+        async fn get_permissions(
+            &self,
+            _user: &(dyn User + Send + Sync),
+        ) -> Result<HashSet<String>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(HashSet::from(["article.publish".to_owned()]))
+        }
+    }
+
+    #[cot::test]
+    async fn load_permissions() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut request = test_request_with_auth_backend(PermissionsAuthBackend {
+            calls: Arc::clone(&calls),
+        });
+        let auth = Auth::from_request(&mut request).await.unwrap();
+
+        // anonymous users don't have any permissions to load
+        auth.load_permissions().await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let mut mock_user = MockUser::new();
+        mock_user.expect_id().return_const(UserId::Int(1));
+        mock_user.expect_is_authenticated().return_const(true);
+        mock_user.expect_is_active().return_const(true);
+        mock_user.expect_session_auth_hash().return_const(None);
+        mock_user.expect_has_perm().return_const(false);
+        auth.login(Box::new(mock_user)).await.unwrap();
+
+        // the permissions are not loaded along with the user
+        assert!(!auth.user().has_perm("article.publish"));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        auth.load_permissions().await.unwrap();
+        auth.load_permissions().await.unwrap();
+        assert!(auth.user().has_perm("article.publish"));
+        assert!(!auth.user().has_perm("article.delete"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // logging out drops the permissions
+        auth.logout().await.unwrap();
+        assert!(!auth.user().has_perm("article.publish"));
+    }
+
+    struct SecondFactorAuthBackend;
+
+    #[async_trait]
+    impl AuthBackend for SecondFactorAuthBackend {
+        async fn authenticate(
+            &self,
+            _credentials: &(dyn Any + Send + Sync),
+        ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+
+        async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+            let mut mock_user = MockUser::new();
+            mock_user.expect_id().return_const(id);
+            mock_user.expect_session_auth_hash().return_const(None);
+            mock_user
+                .expect_username()
+                .return_const(Some(Cow::from("mockuser")));
+            Ok(Some(Box::new(mock_user)))
+        }
+
+        async fn requires_second_factor(&self, _user: &(dyn User + Send + Sync)) -> Result<bool> {
+            Ok(true)
+        }
+    }
+
+    #[cot::test]
+    async fn login_second_factor_without_session() {
+        let auth = Auth::stateless(
+            Arc::new(SecondFactorAuthBackend),
+            Box::new(AnonymousUser),
+            SecretKey::new(TEST_KEY_1),
+        );
+
+        let mut mock_user = MockUser::new();
+        mock_user.expect_id().return_const(UserId::Int(1));
+        mock_user.expect_session_auth_hash().return_const(None);
+        let result = auth.login(Box::new(mock_user)).await;
+
+        assert!(matches!(result, Err(AuthError::SessionRequired)));
+        assert!(!auth.user().is_authenticated());
+    }
+
+    #[cot::test]
+    async fn login_pending_second_factor() {
+        let mut request = test_request_with_auth_backend(SecondFactorAuthBackend);
+        let session = Session::from_request(&request).clone();
+        let auth = Auth::from_request(&mut request).await.unwrap();
+
+        let mut mock_user = MockUser::new();
+        mock_user.expect_id().return_const(UserId::Int(1));
+        mock_user.expect_session_auth_hash().return_const(None);
+        auth.login(Box::new(mock_user)).await.unwrap();
+
+        assert!(!auth.user().is_authenticated());
+        assert_eq!(
+            session.get::<UserId>(USER_ID_SESSION_KEY).await.unwrap(),
+            None
+        );
+        let pending_user = auth.pending_second_factor_user().await.unwrap().unwrap();
+        assert_eq!(pending_user.id(), Some(UserId::Int(1)));
+
+        assert!(auth.complete_second_factor().await.unwrap());
+        assert_eq!(auth.user().username(), Some(Cow::from("mockuser")));
+        assert_eq!(
+            session.get::<UserId>(USER_ID_SESSION_KEY).await.unwrap(),
+            Some(UserId::Int(1))
+        );
+        assert!(auth.pending_second_factor_user().await.unwrap().is_none());
+        assert!(!auth.complete_second_factor().await.unwrap());
+    }
+
     /// Test the session fixation attack mitigation
     #[cot::test]
     async fn login_cycle_id() {
@@ -1265,6 +2334,31 @@ mod tests {
         assert!(id_1 != id_2);
     }
 
+    #[cot::test]
+    async fn login_logout_rotate_csrf_token() {
+        let mut request = test_request(MockUser::new);
+        let session = Session::from_request(&request).clone();
+        let auth = Auth::from_request(&mut request).await.unwrap();
+        let token = CsrfToken::from_session(&session).await.unwrap();
+
+        let mut mock_user = MockUser::new();
+        mock_user.expect_id().return_const(UserId::Int(1));
+        mock_user.expect_session_auth_hash().return_const(None);
+        mock_user
+            .expect_username()
+            .return_const(Some(Cow::from("mockuser")));
+        auth.login(Box::new(mock_user)).await.unwrap();
+
+        let logged_in_token = CsrfToken::from_session(&session).await.unwrap();
+        assert_ne!(logged_in_token, token);
+
+        auth.logout().await.unwrap();
+        assert_ne!(
+            CsrfToken::from_session(&session).await.unwrap(),
+            logged_in_token
+        );
+    }
+
     /// Test that the user is logged out when there is an invalid user ID in the
     /// session (can happen if the user is deleted from the database)
     #[cot::test]
@@ -1383,7 +2477,7 @@ mod tests {
     #[test]
     fn user_wrapper_with_anonymous_user() {
         let anon_user = AnonymousUser;
-        let user_wrapper = UserWrapper(Arc::new(anon_user));
+        let user_wrapper = UserWrapper::new(Arc::new(anon_user));
 
         let debug_output = format!("{user_wrapper:?}");
 
@@ -1409,7 +2503,7 @@ mod tests {
         mock_user.expect_last_login().return_const(Some(now));
         mock_user.expect_joined().return_const(Some(now));
 
-        let user_wrapper = UserWrapper(Arc::new(mock_user));
+        let user_wrapper = UserWrapper::new(Arc::new(mock_user));
 
         let debug_output = format!("{user_wrapper:?}");
 
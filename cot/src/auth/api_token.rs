@@ -0,0 +1,618 @@
+//! API token authentication.
+//!
+//! This module provides [`ApiToken`], a model storing per-user tokens that can
+//! be used to authenticate the requests to JSON APIs, where cookie-based
+//! sessions are not an option. The tokens are sent in the `Authorization`
+//! header using the `Bearer` scheme and are verified by the
+//! [`ApiTokenMiddleware`](crate::middleware::ApiTokenMiddleware), which makes
+//! the owner of the token available through the [`Auth`](crate::auth::Auth)
+//! extractor without using a session.
+//!
+//! Only a hash of every token is stored in the database. The token itself is
+//! returned only once, when it is created, so it has to be shown to the user
+//! right away.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::Auth;
+//! use cot::auth::api_token::ApiToken;
+//! use cot::json::Json;
+//!
+//! async fn orders(auth: Auth, token: ApiToken) -> cot::Result<Json<Vec<String>>> {
+//!     token.require_scope("orders:read")?;
+//!
+//!     let user = auth.user();
+//!     // ...
+//! #   let _ = user;
+//!     Ok(Json(Vec::new()))
+//! }
+//! ```
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+use cot::db::Auto;
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+
+use crate::auth::db::{DatabaseUser, DatabaseUserBackend};
+use crate::auth::{AuthBackend, AuthError, PermissionDenied, Result, User, UserId};
+use crate::db::{Database, DatabaseBackend, ForeignKey, LimitedString, Model, model, query};
+
+pub(crate) const MAX_API_TOKEN_NAME_LENGTH: u32 = 100;
+pub(crate) const API_TOKEN_HASH_LENGTH: u32 = 64;
+
+/// An error returned when a request cannot be authenticated with an API
+/// token.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ApiTokenError {
+    /// The request does not contain an API token.
+    #[error("the request does not contain an API token")]
+    Missing,
+    /// The API token sent in the request does not exist, has been revoked, or
+    /// has expired.
+    #[error("the API token is invalid or has expired")]
+    Invalid,
+    /// The owner of the token has to verify a second authentication factor
+    /// (see [`AuthBackend::requires_second_factor`]), which cannot be done
+    /// with an API token.
+    #[error("the user has to verify a second authentication factor")]
+    SecondFactorRequired,
+}
+impl_into_cot_error!(ApiTokenError, UNAUTHORIZED);
+
+/// An error that occurs when creating an API token.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum CreateApiTokenError {
+    /// The name of the token is too long.
+    #[error("API token name is too long (max {MAX_API_TOKEN_NAME_LENGTH} characters, got {0})")]
+    NameTooLong(usize),
+    /// One of the scopes is empty or contains whitespace.
+    #[error("invalid API token scope: `{0}`")]
+    InvalidScope(String),
+}
+
+/// An API token of a [`DatabaseUser`].
+///
+/// A token has a name, which helps the user tell their tokens apart, a set of
+/// scopes, which can be used to limit what the token can be used for, and an
+/// optional expiration time.
+#[derive(Debug, Clone)]
+#[model]
+pub struct ApiToken {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    name: LimitedString<MAX_API_TOKEN_NAME_LENGTH>,
+    #[model(unique)]
+    token_hash: LimitedString<API_TOKEN_HASH_LENGTH>,
+    scopes: String,
+    created_at: DateTime<FixedOffset>,
+    expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl ApiToken {
+    /// Creates a new API token for the user and saves it to the database.
+    ///
+    /// Returns the token object together with the token itself, which should
+    /// be shown to the user. The token cannot be retrieved again later, since
+    /// only its hash is stored in the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is too long, if any of the scopes is empty
+    /// or contains whitespace, or if the token could not be saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::Database;
+    ///
+    /// async fn create_token(db: &Database, user: &DatabaseUser) -> cot::Result<String> {
+    ///     let (_, token) = ApiToken::create(db, user, "CI", ["orders:read"], None).await?;
+    ///     Ok(token)
+    /// }
+    /// ```
+    pub async fn create<DB, N, S>(
+        db: &DB,
+        user: &DatabaseUser,
+        name: N,
+        scopes: S,
+        expires_at: Option<DateTime<FixedOffset>>,
+    ) -> Result<(Self, String)>
+    where
+        DB: DatabaseBackend,
+        N: Into<String>,
+        S: IntoIterator<Item: Into<String>>,
+    {
+        let name = name.into();
+        let name_length = name.len();
+        let name = LimitedString::<MAX_API_TOKEN_NAME_LENGTH>::new(name)
+            .map_err(|_| AuthError::backend_error(CreateApiTokenError::NameTooLong(name_length)))?;
+
+        let scopes = scopes
+            .into_iter()
+            .map(Into::into)
+            .map(|scope: String| {
+                if scope.is_empty() || scope.contains(char::is_whitespace) {
+                    Err(AuthError::backend_error(CreateApiTokenError::InvalidScope(
+                        scope,
+                    )))
+                } else {
+                    Ok(scope)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let token = crate::utils::random::random_token::<32>();
+        let mut api_token = Self {
+            id: Auto::auto(),
+            user: ForeignKey::from(user),
+            name,
+            token_hash: hash_token(&token),
+            scopes: scopes.join(" "),
+            created_at: Utc::now().fixed_offset(),
+            expires_at,
+        };
+        api_token
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok((api_token, token))
+    }
+
+    /// Returns all the API tokens of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::Database;
+    ///
+    /// async fn token_names(db: &Database, user: &DatabaseUser) -> cot::Result<Vec<String>> {
+    ///     let tokens = ApiToken::list_for_user(db, user).await?;
+    ///     Ok(tokens.iter().map(|token| token.name().to_owned()).collect())
+    /// }
+    /// ```
+    pub async fn list_for_user<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+    ) -> Result<Vec<Self>> {
+        let user = ForeignKey::from(user);
+        let tokens = query!(ApiToken, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(tokens)
+    }
+
+    /// Revokes the token by removing it from the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::db::Database;
+    ///
+    /// async fn revoke(db: &Database, token: ApiToken) -> cot::Result<()> {
+    ///     token.revoke(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn revoke<DB: DatabaseBackend>(self, db: &DB) -> Result<()> {
+        let id = self.id;
+        query!(ApiToken, $id == id)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Revokes all the API tokens of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::Database;
+    ///
+    /// async fn revoke_all(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     ApiToken::revoke_all_for_user(db, user).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn revoke_all_for_user<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+    ) -> Result<()> {
+        let user = ForeignKey::from(user);
+        query!(ApiToken, $user == user)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Finds the token and its owner, given the token sent by the client.
+    ///
+    /// Returns [`None`] if the token does not exist, has expired, or belongs
+    /// to a user who is not [active](DatabaseUser::is_active).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::db::Database;
+    ///
+    /// async fn is_valid(db: &Database, token: &str) -> cot::Result<bool> {
+    ///     Ok(ApiToken::authenticate(db, token).await?.is_some())
+    /// }
+    /// ```
+    pub async fn authenticate<DB: DatabaseBackend>(
+        db: &DB,
+        token: &str,
+    ) -> Result<Option<(Self, DatabaseUser)>> {
+        let token_hash = hash_token(token);
+        let api_token = query!(ApiToken, $token_hash == token_hash)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        let Some(mut api_token) = api_token else {
+            return Ok(None);
+        };
+        if api_token.is_expired() {
+            return Ok(None);
+        }
+
+        let user = api_token
+            .user
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .clone();
+        // the tokens of deactivated users stop working as well
+        if !user.is_active() {
+            return Ok(None);
+        }
+
+        Ok(Some((api_token, user)))
+    }
+
+    /// Returns the ID of the token.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the token has not been saved to the database.
+    #[must_use]
+    pub fn id(&self) -> i64 {
+        match self.id {
+            Auto::Fixed(id) => id,
+            Auto::Auto => unreachable!("ApiToken constructed with an unknown ID"),
+        }
+    }
+
+    /// Returns the ID of the user that owns the token.
+    #[must_use]
+    pub fn user_id(&self) -> i64 {
+        match self.user.primary_key() {
+            Auto::Fixed(id) => *id,
+            Auto::Auto => unreachable!("ApiToken constructed with an unknown user ID"),
+        }
+    }
+
+    /// Returns the name of the token.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the scopes of the token.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    ///
+    /// fn print_scopes(token: &ApiToken) {
+    ///     for scope in token.scopes() {
+    ///         println!("{scope}");
+    ///     }
+    /// }
+    /// ```
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.scopes.split_whitespace()
+    }
+
+    /// Returns whether the token has the given scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    ///
+    /// fn can_read_orders(token: &ApiToken) -> bool {
+    ///     token.has_scope("orders:read")
+    /// }
+    /// ```
+    #[must_use]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|token_scope| token_scope == scope)
+    }
+
+    /// Returns an error if the token doesn't have the given scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionDenied`] if the token doesn't have the scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiToken;
+    /// use cot::json::Json;
+    ///
+    /// async fn orders(token: ApiToken) -> cot::Result<Json<Vec<String>>> {
+    ///     token.require_scope("orders:read")?;
+    ///     Ok(Json(Vec::new()))
+    /// }
+    /// ```
+    pub fn require_scope(&self, scope: &str) -> std::result::Result<(), PermissionDenied> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(PermissionDenied::new())
+        }
+    }
+
+    /// Returns the time the token was created at.
+    #[must_use]
+    pub fn created_at(&self) -> DateTime<FixedOffset> {
+        self.created_at
+    }
+
+    /// Returns the time the token expires at, or [`None`] if it never
+    /// expires.
+    #[must_use]
+    pub fn expires_at(&self) -> Option<DateTime<FixedOffset>> {
+        self.expires_at
+    }
+
+    /// Returns whether the token has expired.
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+}
+
+impl Display for ApiToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn hash_token(token: &str) -> LimitedString<API_TOKEN_HASH_LENGTH> {
+    // The tokens are long random strings, so a fast hash function is enough to
+    // make them impossible to recover from the hashes.
+    let hash = blake3::hash(token.as_bytes()).to_hex();
+    LimitedString::new(hash.as_str()).expect("token hash should fit in the column")
+}
+
+/// Credentials for authenticating a user with an API token.
+///
+/// These are accepted by [`ApiTokenBackend`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::api_token::ApiTokenCredentials;
+///
+/// let credentials = ApiTokenCredentials::new("0123456789abcdef".to_string());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiTokenCredentials {
+    token: String,
+}
+
+impl ApiTokenCredentials {
+    /// Creates new API token credentials.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiTokenCredentials;
+    ///
+    /// let credentials = ApiTokenCredentials::new("0123456789abcdef".to_string());
+    /// ```
+    #[must_use]
+    pub fn new(token: String) -> Self {
+        Self { token }
+    }
+
+    /// Returns the token.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiTokenCredentials;
+    ///
+    /// let credentials = ApiTokenCredentials::new("0123456789abcdef".to_string());
+    /// assert_eq!(credentials.token(), "0123456789abcdef");
+    /// ```
+    #[must_use]
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+/// An authentication backend for API tokens.
+///
+/// This backend accepts [`ApiTokenCredentials`]. All the other credential
+/// types, as well as getting users by their IDs, are handled the same way as
+/// in [`DatabaseUserBackend`], so this backend can be used in place of it in
+/// projects that use both sessions and API tokens.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use cot::Project;
+/// use cot::auth::AuthBackend;
+/// use cot::auth::api_token::ApiTokenBackend;
+/// use cot::project::AuthBackendContext;
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+///         Arc::new(ApiTokenBackend::new(context.database().clone()))
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiTokenBackend {
+    database: Database,
+    user_backend: DatabaseUserBackend,
+}
+
+impl ApiTokenBackend {
+    /// Creates a new API token backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::api_token::ApiTokenBackend;
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let backend = ApiTokenBackend::new(Database::new("sqlite::memory:").await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(database: Database) -> Self {
+        Self {
+            user_backend: DatabaseUserBackend::new(database.clone()),
+            database,
+        }
+    }
+}
+
+#[async_trait]
+impl AuthBackend for ApiTokenBackend {
+    async fn authenticate(
+        &self,
+        credentials: &(dyn Any + Send + Sync),
+    ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        let Some(credentials) = credentials.downcast_ref::<ApiTokenCredentials>() else {
+            return self.user_backend.authenticate(credentials).await;
+        };
+
+        let Some((_, user)) = ApiToken::authenticate(&self.database, credentials.token()).await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Box::new(user)))
+    }
+
+    async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        self.user_backend.get_by_id(id).await
+    }
+
+    async fn requires_second_factor(&self, user: &(dyn User + Send + Sync)) -> Result<bool> {
+        self.user_backend.requires_second_factor(user).await
+    }
+
+    async fn get_permissions(&self, user: &(dyn User + Send + Sync)) -> Result<HashSet<String>> {
+        self.user_backend.get_permissions(user).await
+    }
+
+    fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+        self.user_backend.login_identifier(credentials)
+    }
+
+    async fn user_logged_in(&self, user: &(dyn User + Send + Sync)) -> Result<()> {
+        self.user_backend.user_logged_in(user).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_token(scopes: &str, expires_at: Option<DateTime<FixedOffset>>) -> ApiToken {
+        ApiToken {
+            id: Auto::fixed(1),
+            user: ForeignKey::PrimaryKey(Auto::fixed(1)),
+            name: LimitedString::new("test").unwrap(),
+            token_hash: hash_token("token"),
+            scopes: scopes.to_owned(),
+            created_at: Utc::now().fixed_offset(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn api_token_scopes() {
+        let token = api_token("orders:read orders:write", None);
+
+        assert_eq!(
+            token.scopes().collect::<Vec<_>>(),
+            vec!["orders:read", "orders:write"]
+        );
+        assert!(token.has_scope("orders:read"));
+        assert!(!token.has_scope("orders"));
+        assert!(token.require_scope("orders:write").is_ok());
+        assert!(token.require_scope("users:read").is_err());
+    }
+
+    #[test]
+    fn api_token_expiry() {
+        let now = Utc::now().fixed_offset();
+
+        assert!(!api_token("", None).is_expired());
+        assert!(!api_token("", Some(now + chrono::Duration::hours(1))).is_expired());
+        assert!(api_token("", Some(now - chrono::Duration::hours(1))).is_expired());
+    }
+
+    #[test]
+    fn hash_token_is_stable() {
+        assert_eq!(hash_token("token"), hash_token("token"));
+        assert_ne!(hash_token("token"), hash_token("other"));
+        assert_eq!(hash_token("token").len(), 64);
+    }
+}
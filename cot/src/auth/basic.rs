@@ -0,0 +1,497 @@
+//! HTTP Basic authentication.
+//!
+//! This module provides [`BasicAuthCredentials`], the credentials sent by the
+//! clients in the `Authorization: Basic` header, and [`BasicAuthBackend`], an
+//! authentication backend that checks them against a static list of users
+//! from the [config](crate::config::BasicAuthConfig) and, if the database is
+//! enabled, against the [`DatabaseUser`](crate::auth::db::DatabaseUser)s. The
+//! credentials are verified on every request by the
+//! [`BasicAuthMiddleware`](crate::middleware::BasicAuthMiddleware), which
+//! makes the user available through the [`Auth`](crate::auth::Auth) extractor
+//! without using a session.
+//!
+//! Since the password is sent with every request, HTTP Basic authentication
+//! should only be used over HTTPS. It is best suited for internal tools and
+//! webhooks, where the clients can't go through a login form.
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::Engine;
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+
+#[cfg(feature = "db")]
+use crate::auth::db::DatabaseUserBackend;
+use crate::auth::{
+    AuthBackend, AuthError, PasswordHash, PasswordVerificationResult, Result, User, UserId,
+};
+use crate::common_types::Password;
+#[cfg(feature = "db")]
+use crate::db::Database;
+
+/// The default realm sent in the `WWW-Authenticate` header.
+pub const DEFAULT_BASIC_AUTH_REALM: &str = "Restricted";
+
+const BASIC_SCHEME: &str = "Basic";
+
+/// An error returned when a request cannot be authenticated with HTTP Basic
+/// authentication.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BasicAuthError {
+    /// The request does not contain HTTP Basic credentials.
+    #[error("the request does not contain HTTP Basic credentials")]
+    Missing,
+    /// The credentials sent in the request are malformed or invalid.
+    #[error("the HTTP Basic credentials are invalid")]
+    Invalid,
+    /// The user has to verify a second authentication factor (see
+    /// [`AuthBackend::requires_second_factor`]), which cannot be done with
+    /// HTTP Basic authentication.
+    #[error("the user has to verify a second authentication factor")]
+    SecondFactorRequired,
+}
+impl_into_cot_error!(BasicAuthError, UNAUTHORIZED);
+
+/// Credentials sent in the `Authorization: Basic` header.
+///
+/// Can be passed to [`Auth::authenticate`](crate::auth::Auth::authenticate) to
+/// authenticate a user when using the [`BasicAuthBackend`] or the
+/// [`DatabaseUserBackend`](crate::auth::db::DatabaseUserBackend).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::basic::BasicAuthCredentials;
+/// use cot::common_types::Password;
+///
+/// let credentials = BasicAuthCredentials::new("deploy".to_string(), Password::new("password123"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BasicAuthCredentials {
+    username: String,
+    password: Password,
+}
+
+impl BasicAuthCredentials {
+    /// Creates new HTTP Basic credentials.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials = BasicAuthCredentials::new("deploy".to_string(), Password::new("password123"));
+    /// ```
+    #[must_use]
+    pub fn new(username: String, password: Password) -> Self {
+        Self { username, password }
+    }
+
+    /// Parses the credentials from the value of the `Authorization` header.
+    ///
+    /// Returns [`None`] if the header uses a different authentication scheme.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BasicAuthError::Invalid`] if the header uses the `Basic`
+    /// scheme, but the credentials are not correctly encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthCredentials;
+    ///
+    /// let credentials =
+    ///     BasicAuthCredentials::from_authorization_header("Basic ZGVwbG95OnNlY3JldA==")?.unwrap();
+    /// assert_eq!(credentials.username(), "deploy");
+    /// assert_eq!(credentials.password().as_str(), "secret");
+    ///
+    /// assert!(BasicAuthCredentials::from_authorization_header("Bearer token")?.is_none());
+    /// # Ok::<(), cot::auth::basic::BasicAuthError>(())
+    /// ```
+    pub fn from_authorization_header(
+        value: &str,
+    ) -> std::result::Result<Option<Self>, BasicAuthError> {
+        let Some((scheme, encoded)) = value.trim().split_once(' ') else {
+            return Ok(None);
+        };
+        if !scheme.eq_ignore_ascii_case(BASIC_SCHEME) {
+            return Ok(None);
+        }
+
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(|_| BasicAuthError::Invalid)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| BasicAuthError::Invalid)?;
+        let (username, password) = decoded.split_once(':').ok_or(BasicAuthError::Invalid)?;
+
+        Ok(Some(Self::new(
+            username.to_owned(),
+            Password::new(password),
+        )))
+    }
+
+    /// Returns the username.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials = BasicAuthCredentials::new("deploy".to_string(), Password::new("password123"));
+    /// assert_eq!(credentials.username(), "deploy");
+    /// ```
+    #[must_use]
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Returns the password.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials = BasicAuthCredentials::new("deploy".to_string(), Password::new("password123"));
+    /// assert_eq!(credentials.password().as_str(), "password123");
+    /// ```
+    #[must_use]
+    pub fn password(&self) -> &Password {
+        &self.password
+    }
+}
+
+/// A user listed in the [HTTP Basic authentication
+/// config](crate::config::BasicAuthConfig::users).
+///
+/// These users are always active, but they have no permissions and cannot log
+/// in to the admin panel.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::basic::BasicAuthUser;
+/// use cot::auth::{User, UserId};
+///
+/// let user = BasicAuthUser::new("deploy");
+/// assert_eq!(user.id(), Some(UserId::String("deploy".to_owned())));
+/// assert!(user.is_authenticated());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BasicAuthUser {
+    username: String,
+}
+
+impl BasicAuthUser {
+    /// Creates a new user with the given username.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthUser;
+    ///
+    /// let user = BasicAuthUser::new("deploy");
+    /// ```
+    #[must_use]
+    pub fn new<S: Into<String>>(username: S) -> Self {
+        Self {
+            username: username.into(),
+        }
+    }
+}
+
+impl User for BasicAuthUser {
+    fn id(&self) -> Option<UserId> {
+        Some(UserId::String(self.username.clone()))
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(&self.username))
+    }
+
+    fn is_active(&self) -> bool {
+        true
+    }
+
+    fn is_authenticated(&self) -> bool {
+        true
+    }
+}
+
+/// An authentication backend for HTTP Basic authentication.
+///
+/// This backend accepts [`BasicAuthCredentials`] and checks them against the
+/// static list of users it has been created with. If the database has been
+/// [set](Self::database), the credentials of the users that are not on the
+/// list are checked against the
+/// [`DatabaseUser`](crate::auth::db::DatabaseUser)s, and all the other
+/// credential types are handled the same way as in [`DatabaseUserBackend`].
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use cot::Project;
+/// use cot::auth::basic::BasicAuthBackend;
+/// use cot::auth::{AuthBackend, PasswordHash};
+/// use cot::common_types::Password;
+/// use cot::project::AuthBackendContext;
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+///         Arc::new(BasicAuthBackend::new().user(
+///             "deploy",
+///             PasswordHash::from_password(&Password::new("password123")),
+///         ))
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BasicAuthBackend {
+    users: Arc<BTreeMap<String, PasswordHash>>,
+    #[cfg(feature = "db")]
+    user_backend: Option<DatabaseUserBackend>,
+}
+
+impl BasicAuthBackend {
+    /// Creates a new HTTP Basic authentication backend without any users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthBackend;
+    ///
+    /// let backend = BasicAuthBackend::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new HTTP Basic authentication backend with the users listed
+    /// in the config.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::PasswordHashInvalid`] if any of the password
+    /// hashes in the config is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthBackend;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let backend = BasicAuthBackend::from_config(&ProjectConfig::default().basic_auth)?;
+    /// # Ok::<(), cot::auth::AuthError>(())
+    /// ```
+    pub fn from_config(config: &crate::config::BasicAuthConfig) -> Result<Self> {
+        let users = config
+            .users
+            .iter()
+            .map(|(username, hash)| Ok((username.clone(), PasswordHash::new(hash.clone())?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            users: Arc::new(users),
+            ..Self::default()
+        })
+    }
+
+    /// Adds a user with the given username and password hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::PasswordHash;
+    /// use cot::auth::basic::BasicAuthBackend;
+    /// use cot::common_types::Password;
+    ///
+    /// let backend = BasicAuthBackend::new().user(
+    ///     "deploy",
+    ///     PasswordHash::from_password(&Password::new("password123")),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn user<S: Into<String>>(mut self, username: S, password_hash: PasswordHash) -> Self {
+        Arc::make_mut(&mut self.users).insert(username.into(), password_hash);
+        self
+    }
+
+    /// Makes the backend accept the
+    /// [`DatabaseUser`](crate::auth::db::DatabaseUser)s stored in the given
+    /// database, in addition to the users it has been created with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::basic::BasicAuthBackend;
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let backend = BasicAuthBackend::new().database(Database::new("sqlite::memory:").await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "db")]
+    #[must_use]
+    pub fn database(mut self, database: Database) -> Self {
+        self.user_backend = Some(DatabaseUserBackend::new(database));
+        self
+    }
+}
+
+#[async_trait]
+impl AuthBackend for BasicAuthBackend {
+    async fn authenticate(
+        &self,
+        credentials: &(dyn Any + Send + Sync),
+    ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        if let Some(basic_credentials) = credentials.downcast_ref::<BasicAuthCredentials>()
+            && let Some(password_hash) = self.users.get(basic_credentials.username())
+        {
+            return Ok(match password_hash.verify(basic_credentials.password()) {
+                PasswordVerificationResult::Ok | PasswordVerificationResult::OkObsolete(_) => {
+                    Some(Box::new(BasicAuthUser::new(basic_credentials.username())))
+                }
+                PasswordVerificationResult::Invalid => None,
+            });
+        }
+
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.authenticate(credentials).await;
+        }
+
+        if credentials.is::<BasicAuthCredentials>() {
+            Ok(None)
+        } else {
+            Err(AuthError::CredentialsTypeNotSupported)
+        }
+    }
+
+    async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        if let UserId::String(username) = &id
+            && self.users.contains_key(username)
+        {
+            return Ok(Some(Box::new(BasicAuthUser::new(username.clone()))));
+        }
+
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.get_by_id(id).await;
+        }
+
+        Ok(None)
+    }
+
+    #[cfg_attr(not(feature = "db"), expect(unused_variables))]
+    async fn requires_second_factor(&self, user: &(dyn User + Send + Sync)) -> Result<bool> {
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.requires_second_factor(user).await;
+        }
+
+        Ok(false)
+    }
+
+    #[cfg_attr(not(feature = "db"), expect(unused_variables))]
+    async fn get_permissions(&self, user: &(dyn User + Send + Sync)) -> Result<HashSet<String>> {
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.get_permissions(user).await;
+        }
+
+        Ok(HashSet::new())
+    }
+
+    fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+        if let Some(credentials) = credentials.downcast_ref::<BasicAuthCredentials>() {
+            return Some(credentials.username().to_owned());
+        }
+
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.login_identifier(credentials);
+        }
+
+        None
+    }
+
+    #[cfg_attr(not(feature = "db"), expect(unused_variables))]
+    async fn user_logged_in(&self, user: &(dyn User + Send + Sync)) -> Result<()> {
+        #[cfg(feature = "db")]
+        if let Some(user_backend) = &self.user_backend {
+            return user_backend.user_logged_in(user).await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_from_authorization_header() {
+        let credentials =
+            BasicAuthCredentials::from_authorization_header("basic dXNlcjpwYXNzOndvcmQ=")
+                .unwrap()
+                .unwrap();
+        assert_eq!(credentials.username(), "user");
+        assert_eq!(credentials.password().as_str(), "pass:word");
+
+        assert!(
+            BasicAuthCredentials::from_authorization_header("Bearer token")
+                .unwrap()
+                .is_none()
+        );
+        assert!(BasicAuthCredentials::from_authorization_header("Basic !!!").is_err());
+        // "user" without a colon
+        assert!(BasicAuthCredentials::from_authorization_header("Basic dXNlcg==").is_err());
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn backend_static_users() {
+        let backend = BasicAuthBackend::new().user(
+            "deploy",
+            PasswordHash::from_password(&Password::new("password123")),
+        );
+
+        let valid = BasicAuthCredentials::new("deploy".to_owned(), Password::new("password123"));
+        let user = backend.authenticate(&valid).await.unwrap().unwrap();
+        assert_eq!(user.username().as_deref(), Some("deploy"));
+
+        let invalid = BasicAuthCredentials::new("deploy".to_owned(), Password::new("invalid"));
+        assert!(backend.authenticate(&invalid).await.unwrap().is_none());
+        let unknown = BasicAuthCredentials::new("unknown".to_owned(), Password::new("password123"));
+        assert!(backend.authenticate(&unknown).await.unwrap().is_none());
+        assert!(matches!(
+            backend.authenticate(&"unsupported").await,
+            Err(AuthError::CredentialsTypeNotSupported)
+        ));
+
+        assert!(
+            backend
+                .get_by_id(UserId::String("deploy".to_owned()))
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(backend.get_by_id(UserId::Int(1)).await.unwrap().is_none());
+    }
+}
@@ -5,9 +5,12 @@
 
 use std::any::Any;
 use std::borrow::Cow;
-use std::fmt::{Display, Formatter};
+use std::collections::HashSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
 
 use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset, Utc};
 // Importing `Auto` from `cot` instead of `crate` so that the migration generator
 // can figure out it's an autogenerated field
 use cot::db::Auto;
@@ -15,23 +18,41 @@ use cot_macros::AdminModel;
 use thiserror::Error;
 
 use crate::App;
-use crate::admin::{AdminModelManager, DefaultAdminModelManager};
+use crate::admin::{AdminModelManager, AdminPermission, DefaultAdminModelManager};
+use crate::auth::basic::BasicAuthCredentials;
+use crate::auth::password_validation::PasswordValidators;
+use crate::auth::totp::TotpDevice;
 use crate::auth::{
     AuthBackend, AuthError, PasswordHash, PasswordVerificationResult, Result, SessionAuthHash,
     User, UserId,
 };
-use crate::common_types::Password;
+use crate::common_types::{Email, Password};
 use crate::config::SecretKey;
 use crate::db::migrations::SyncDynMigration;
-use crate::db::{Database, DatabaseBackend, LimitedString, Model, model, query};
+use crate::db::query::{Expr, Query};
+use crate::db::{Database, DatabaseBackend, ForeignKey, LimitedString, Model, model, query};
 use crate::form::Form;
 
+pub(crate) mod cli;
 pub mod migrations;
 
 pub(crate) const MAX_USERNAME_LENGTH: u32 = 255;
+pub(crate) const MAX_PERMISSION_CODENAME_LENGTH: u32 = 255;
+pub(crate) const MAX_PERMISSION_NAME_LENGTH: u32 = 255;
+pub(crate) const MAX_GROUP_NAME_LENGTH: u32 = 150;
 
 /// A user stored in the database.
+///
+/// Apart from the permissions granted to the user directly, the user also has
+/// all the permissions of the [`Group`]s they belong to. Superusers implicitly
+/// have all the permissions.
 #[derive(Debug, Clone, Form, AdminModel)]
+#[admin(
+    list_display = [username, email, is_active, is_staff, is_superuser, last_login],
+    search_fields = [username, email],
+    list_filter = [is_active, is_staff, is_superuser, last_login],
+    readonly = [last_login, date_joined],
+)]
 #[model]
 pub struct DatabaseUser {
     #[model(primary_key)]
@@ -39,6 +60,13 @@ pub struct DatabaseUser {
     #[model(unique)]
     username: LimitedString<MAX_USERNAME_LENGTH>,
     password: PasswordHash,
+    is_superuser: bool,
+    is_staff: bool,
+    email: Option<Email>,
+    email_verified: bool,
+    is_active: bool,
+    last_login: Option<DateTime<FixedOffset>>,
+    date_joined: Option<DateTime<FixedOffset>>,
 }
 
 /// An error that occurs when creating a user.
@@ -50,6 +78,29 @@ pub enum CreateUserError {
     UsernameTooLong(usize),
 }
 
+/// An error that occurs when creating a permission.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum CreatePermissionError {
+    /// The permission codename is too long.
+    #[error(
+        "permission codename is too long (max {MAX_PERMISSION_CODENAME_LENGTH} characters, got {0})"
+    )]
+    CodenameTooLong(usize),
+    /// The permission name is too long.
+    #[error("permission name is too long (max {MAX_PERMISSION_NAME_LENGTH} characters, got {0})")]
+    NameTooLong(usize),
+}
+
+/// An error that occurs when creating a group.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum CreateGroupError {
+    /// The group name is too long.
+    #[error("group name is too long (max {MAX_GROUP_NAME_LENGTH} characters, got {0})")]
+    NameTooLong(usize),
+}
+
 impl DatabaseUser {
     #[must_use]
     fn new(
@@ -61,6 +112,13 @@ impl DatabaseUser {
             id,
             username,
             password: PasswordHash::from_password(password),
+            is_superuser: false,
+            is_staff: false,
+            email: None,
+            email_verified: false,
+            is_active: true,
+            last_login: None,
+            date_joined: Some(Utc::now().fixed_offset()),
         }
     }
 
@@ -100,14 +158,118 @@ impl DatabaseUser {
         db: &DB,
         username: T,
         password: U,
+    ) -> Result<Self> {
+        Self::create_user_impl(db, username.into(), &password.into(), false).await
+    }
+
+    /// Creates a new user and saves it to the database, after checking the
+    /// password with the given validators.
+    ///
+    /// The validators configured for the project can be obtained with
+    /// [`PasswordValidators::from_config`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::PasswordInvalid`] if the password doesn't pass the
+    /// validation.
+    ///
+    /// Returns an error if the user could not be saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::password_validation::PasswordValidators;
+    /// use cot::common_types::Password;
+    /// use cot::db::Database;
+    /// use cot::html::Html;
+    /// use cot::request::{Request, RequestExt};
+    ///
+    /// async fn view(request: Request, db: Database) -> cot::Result<Html> {
+    ///     let validators = PasswordValidators::from_config(request.context().config());
+    ///     let user = DatabaseUser::create_user_validated(
+    ///         &db,
+    ///         "testuser",
+    ///         &Password::new("correct horse battery staple"),
+    ///         &validators,
+    ///     )
+    ///     .await?;
+    ///
+    ///     Ok(Html::new("User created!"))
+    /// }
+    /// ```
+    pub async fn create_user_validated<DB: DatabaseBackend, T: Into<String>, U: Into<Password>>(
+        db: &DB,
+        username: T,
+        password: U,
+        validators: &PasswordValidators,
     ) -> Result<Self> {
         let username = username.into();
+        let password = password.into();
+        validators.validate(&password, Some(&username))?;
+
+        Self::create_user_impl(db, username, &password, false).await
+    }
+
+    /// Creates a new superuser and saves it to the database.
+    ///
+    /// Superusers have all the permissions and are allowed to log in to the
+    /// [admin panel](crate::admin::AdminApp).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be saved.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::User;
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::Password;
+    /// use cot::db::Database;
+    /// use cot::html::Html;
+    ///
+    /// async fn view(db: Database) -> cot::Result<Html> {
+    ///     let user =
+    ///         DatabaseUser::create_superuser(&db, "admin", &Password::new("password123")).await?;
+    ///     assert!(user.is_superuser());
+    ///     assert!(user.is_staff());
+    ///
+    ///     Ok(Html::new("Superuser created!"))
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::{TestDatabase, TestRequestBuilder};
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub async fn create_superuser<DB: DatabaseBackend, T: Into<String>, U: Into<Password>>(
+        db: &DB,
+        username: T,
+        password: U,
+    ) -> Result<Self> {
+        Self::create_user_impl(db, username.into(), &password.into(), true).await
+    }
+
+    async fn create_user_impl<DB: DatabaseBackend>(
+        db: &DB,
+        username: String,
+        password: &Password,
+        is_superuser: bool,
+    ) -> Result<Self> {
         let username_length = username.len();
         let username = LimitedString::<MAX_USERNAME_LENGTH>::new(username).map_err(|_| {
             AuthError::backend_error(CreateUserError::UsernameTooLong(username_length))
         })?;
 
-        let mut user = Self::new(Auto::auto(), username, &password.into());
+        let mut user = Self::new(Auto::auto(), username, password);
+        user.is_superuser = is_superuser;
+        user.is_staff = is_superuser;
         user.insert(db).await.map_err(AuthError::backend_error)?;
 
         Ok(user)
@@ -213,6 +375,48 @@ impl DatabaseUser {
         Ok(db_user)
     }
 
+    /// Retrieves all the users with the given email address.
+    ///
+    /// Since the email address is not required to be unique, this can return
+    /// more than one user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::Email;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let email = Email::try_from("user@example.com").unwrap();
+    ///     let users = DatabaseUser::get_by_email(db, &email).await?;
+    ///     assert!(users.is_empty());
+    ///     Ok(())
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// #     use cot::test::TestDatabase;
+    /// #     let mut test_database = TestDatabase::new_sqlite().await?;
+    /// #     test_database.with_auth().run_migrations().await;
+    /// #     view(&test_database.database()).await?;
+    /// #     test_database.cleanup().await?;
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub async fn get_by_email<DB: DatabaseBackend>(db: &DB, email: &Email) -> Result<Vec<Self>> {
+        let users = query!(DatabaseUser, $email == Some(email.clone()))
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(users)
+    }
+
     /// Authenticates a user using the provided credentials.
     ///
     /// # Errors
@@ -232,30 +436,20 @@ impl DatabaseUser {
             .await
             .map_err(AuthError::backend_error)?;
 
-        if let Some(mut user) = user {
-            let password_hash = &user.password;
-            match password_hash.verify(credentials.password()) {
-                PasswordVerificationResult::Ok => Ok(Some(user)),
-                PasswordVerificationResult::OkObsolete(new_hash) => {
-                    user.password = new_hash;
-                    user.save(db).await.map_err(AuthError::backend_error)?;
-                    Ok(Some(user))
-                }
-                PasswordVerificationResult::Invalid => Ok(None),
-            }
-        } else {
-            // SECURITY: If no user was found, run the same hashing function to prevent
-            // timing attacks from being used to determine if a user exists. Additionally,
-            // do something with the result to prevent the compiler from optimizing out the
-            // operation.
-            // TODO: benchmark this to make sure it works as expected
-            let dummy_hash = PasswordHash::from_password(credentials.password());
-            if let PasswordVerificationResult::Invalid = dummy_hash.verify(credentials.password()) {
-                unreachable!(
-                    "Password hash verification should never fail for a newly generated hash"
-                );
+        match check_password(
+            user.as_ref().map(|user| &user.password),
+            credentials.password(),
+        ) {
+            PasswordVerificationResult::Ok => Ok(user),
+            PasswordVerificationResult::OkObsolete(new_hash) => {
+                let Some(mut user) = user else {
+                    return Ok(None);
+                };
+                user.password = new_hash;
+                user.save(db).await.map_err(AuthError::backend_error)?;
+                Ok(Some(user))
             }
-            Ok(None)
+            PasswordVerificationResult::Invalid => Ok(None),
         }
     }
 
@@ -341,175 +535,1666 @@ impl DatabaseUser {
     pub fn username(&self) -> &str {
         &self.username
     }
-}
 
-impl User for DatabaseUser {
-    fn id(&self) -> Option<UserId> {
-        Some(UserId::Int(self.id()))
+    /// Returns whether the user is a superuser.
+    ///
+    /// Superusers implicitly have all the permissions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    ///
+    /// fn is_admin(user: &DatabaseUser) -> bool {
+    ///     user.is_superuser()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_superuser(&self) -> bool {
+        self.is_superuser
     }
 
-    fn username(&self) -> Option<Cow<'_, str>> {
-        Some(Cow::from(self.username.as_str()))
+    /// Sets whether the user is a superuser.
+    ///
+    /// Note that this only changes the object in memory; you need to
+    /// [`save`](Model::save) the user for the change to be persisted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::{Database, Model};
+    ///
+    /// async fn promote(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_superuser(true);
+    ///     user.save(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_superuser(&mut self, is_superuser: bool) {
+        self.is_superuser = is_superuser;
     }
 
-    fn is_active(&self) -> bool {
-        true
+    /// Returns whether the user is a staff member, i.e. whether they are
+    /// allowed to log in to the [admin panel](crate::admin::AdminApp).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    ///
+    /// fn can_use_admin(user: &DatabaseUser) -> bool {
+    ///     user.is_staff()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_staff(&self) -> bool {
+        self.is_staff
     }
 
-    fn is_authenticated(&self) -> bool {
-        true
+    /// Sets whether the user is a staff member.
+    ///
+    /// Note that this only changes the object in memory; you need to
+    /// [`save`](Model::save) the user for the change to be persisted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::{Database, Model};
+    ///
+    /// async fn make_staff(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_staff(true);
+    ///     user.save(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_staff(&mut self, is_staff: bool) {
+        self.is_staff = is_staff;
     }
 
-    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
-        const SESSION_AUTH_HASH_CONTEXT: &str = "cot.rs session auth hash v1";
-
-        let key = blake3::derive_key(SESSION_AUTH_HASH_CONTEXT, secret_key.as_bytes());
-        let hash = blake3::keyed_hash(&key, self.password.as_str().as_bytes());
-
-        Some(SessionAuthHash::new(hash.as_slice()))
+    /// Returns the email address of the user, if set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    ///
+    /// fn has_email(user: &DatabaseUser) -> bool {
+    ///     user.email().is_some()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn email(&self) -> Option<&Email> {
+        self.email.as_ref()
     }
-}
 
-impl Display for DatabaseUser {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.username)
+    /// Sets the email address of the user.
+    ///
+    /// If the email address is different from the current one, it is marked
+    /// as [not verified](Self::is_email_verified). Note that this only changes
+    /// the object in memory; you need to [`save`](Model::save) the user for
+    /// the change to be persisted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::Email;
+    /// use cot::db::{Database, Model};
+    ///
+    /// async fn set_email(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+    ///     user.save(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_email(&mut self, email: Option<Email>) {
+        if self.email != email {
+            self.email_verified = false;
+        }
+        self.email = email;
     }
-}
-
-/// Credentials for authenticating a user stored in the database.
-///
-/// This struct is used to authenticate a user stored in the database. It
-/// contains the username and password of the user.
-///
-/// Can be passed to [`Auth::authenticate`](crate::auth::Auth::authenticate) to
-/// authenticate a user when using the [`DatabaseUserBackend`].
-#[derive(Debug, Clone)]
-pub struct DatabaseUserCredentials {
-    username: String,
-    password: Password,
-}
 
-impl DatabaseUserCredentials {
-    /// Create a new instance of the database user credentials.
+    /// Returns whether the user has verified that they own their email
+    /// address, for instance, by opening the link sent to them by the
+    /// [`SignupApp`](crate::auth::signup::SignupApp).
     ///
     /// # Example
     ///
     /// ```
-    /// use cot::auth::db::DatabaseUserCredentials;
-    /// use cot::common_types::Password;
+    /// use cot::auth::db::DatabaseUser;
     ///
-    /// let credentials =
-    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
+    /// fn can_send_newsletter(user: &DatabaseUser) -> bool {
+    ///     user.email().is_some() && user.is_email_verified()
+    /// }
     /// ```
     #[must_use]
-    pub fn new(username: String, password: Password) -> Self {
-        Self { username, password }
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified
     }
 
-    /// Returns the username of the user.
+    /// Sets whether the user has verified their email address.
+    ///
+    /// Note that this only changes the object in memory; you need to
+    /// [`save`](Model::save) the user for the change to be persisted.
     ///
     /// # Example
     ///
     /// ```
-    /// use cot::auth::db::DatabaseUserCredentials;
-    /// use cot::common_types::Password;
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::{Database, Model};
     ///
-    /// let credentials =
-    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
-    /// assert_eq!(credentials.username(), "testuser");
+    /// async fn mark_verified(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_email_verified(true);
+    ///     user.save(db).await?;
+    ///     Ok(())
+    /// }
     /// ```
-    #[must_use]
-    pub fn username(&self) -> &str {
-        &self.username
+    pub fn set_email_verified(&mut self, email_verified: bool) {
+        self.email_verified = email_verified;
     }
 
-    /// Returns the password of the user.
+    /// Returns whether the user account is active. Inactive users cannot log
+    /// in.
     ///
     /// # Example
     ///
     /// ```
-    /// use cot::auth::db::DatabaseUserCredentials;
-    /// use cot::common_types::Password;
+    /// use cot::auth::db::DatabaseUser;
     ///
-    /// let credentials =
-    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
-    /// assert!(!credentials.password().as_str().is_empty());
+    /// fn can_log_in(user: &DatabaseUser) -> bool {
+    ///     user.is_active()
+    /// }
     /// ```
     #[must_use]
-    pub fn password(&self) -> &Password {
-        &self.password
+    pub fn is_active(&self) -> bool {
+        self.is_active
     }
-}
-
-/// The authentication backend for users stored in the database.
-///
-/// This is the default authentication backend for Cot. It authenticates
-/// users stored in the database using the [`DatabaseUser`] model.
-///
-/// This backend supports authenticating users using the
-/// [`DatabaseUserCredentials`] struct and ignores all other credential types.
-#[derive(Debug, Clone)]
-pub struct DatabaseUserBackend {
-    database: Database,
-}
 
-impl DatabaseUserBackend {
-    /// Create a new instance of the database user authentication backend.
+    /// Sets whether the user account is active.
+    ///
+    /// Deactivating a user is usually preferable to deleting them, since it
+    /// doesn't remove the data that refers to the user. Note that this only
+    /// changes the object in memory; you need to [`save`](Model::save) the
+    /// user for the change to be persisted.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::sync::Arc;
-    ///
-    /// use cot::auth::AuthBackend;
-    /// use cot::auth::db::DatabaseUserBackend;
-    /// use cot::config::ProjectConfig;
-    /// use cot::project::{AuthBackendContext, WithApps};
-    /// use cot::{Project, ProjectContext};
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::{Database, Model};
     ///
-    /// struct HelloProject;
-    /// impl Project for HelloProject {
-    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
-    ///         Arc::new(DatabaseUserBackend::new(context.database().clone()))
-    ///         // note that it's usually better to just set the auth backend in the config
-    ///     }
+    /// async fn deactivate(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_active(false);
+    ///     user.save(db).await?;
+    ///     Ok(())
     /// }
     /// ```
-    #[must_use]
-    pub fn new(database: Database) -> Self {
-        Self { database }
+    pub fn set_active(&mut self, is_active: bool) {
+        self.is_active = is_active;
+    }
+
+    /// Returns the time the user last logged in, or [`None`] if they have
+    /// never logged in.
+    ///
+    /// This is updated automatically by the [`DatabaseUserBackend`] every time
+    /// the user logs in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    ///
+    /// fn has_logged_in(user: &DatabaseUser) -> bool {
+    ///     user.last_login().is_some()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.last_login
+    }
+
+    /// Returns the time the user account was created, or [`None`] if the user
+    /// was created before this information was tracked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    ///
+    /// fn joined_year(user: &DatabaseUser) -> Option<i32> {
+    ///     use chrono::Datelike;
+    ///
+    ///     user.date_joined().map(|date| date.year())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn date_joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.date_joined
+    }
+
+    /// Sets the password of the user.
+    ///
+    /// Changing the password invalidates all the sessions of the user, as
+    /// well as all the password reset tokens generated for them. Note that
+    /// this only changes the object in memory; you need to
+    /// [`save`](Model::save) the user for the change to be persisted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::common_types::Password;
+    /// use cot::db::{Database, Model};
+    ///
+    /// async fn change_password(db: &Database, mut user: DatabaseUser) -> cot::Result<()> {
+    ///     user.set_password(&Password::new("new_password"));
+    ///     user.save(db).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_password(&mut self, password: &Password) {
+        self.password = PasswordHash::from_password(password);
+    }
+
+    /// Returns the codenames of all the permissions of the user, including
+    /// the ones granted through the groups the user belongs to.
+    ///
+    /// Note that this does not take into account the fact that superusers
+    /// implicitly have all the permissions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Permission};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     let permission =
+    ///         Permission::get_or_create(db, "article.publish", "Can publish articles").await?;
+    ///     user.add_permission(db, &permission).await?;
+    ///
+    ///     let permissions = user.get_permissions(db).await?;
+    ///     assert!(permissions.contains("article.publish"));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_permissions<DB: DatabaseBackend>(&self, db: &DB) -> Result<HashSet<String>> {
+        Self::get_permissions_by_id(db, self.id()).await
+    }
+
+    async fn get_permissions_by_id<DB: DatabaseBackend>(
+        db: &DB,
+        id: i64,
+    ) -> Result<HashSet<String>> {
+        let user = ForeignKey::<Self>::PrimaryKey(Auto::fixed(id));
+
+        let mut permission_ids: Vec<_> = query!(UserPermission, $user == user.clone())
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|user_permission| *user_permission.permission.primary_key())
+            .collect();
+        let group_ids = query!(UserGroup, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|user_group| *user_group.group.primary_key());
+        if let Some(filter) = any_of("group", group_ids) {
+            permission_ids.extend(
+                Query::<GroupPermission>::new()
+                    .filter(filter)
+                    .all(db)
+                    .await
+                    .map_err(AuthError::backend_error)?
+                    .into_iter()
+                    .map(|group_permission| *group_permission.permission.primary_key()),
+            );
+        }
+
+        let Some(filter) = any_of("id", permission_ids) else {
+            return Ok(HashSet::new());
+        };
+        let permissions = Query::<Permission>::new()
+            .filter(filter)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|permission| permission.codename.to_string())
+            .collect();
+
+        Ok(permissions)
+    }
+
+    /// Grants a permission to the user. Does nothing if the user already has
+    /// the permission granted directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Permission};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     let permission =
+    ///         Permission::get_or_create(db, "article.publish", "Can publish articles").await?;
+    ///     user.add_permission(db, &permission).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn add_permission<DB: DatabaseBackend>(
+        &self,
+        db: &DB,
+        permission: &Permission,
+    ) -> Result<()> {
+        let user = ForeignKey::from(self);
+        let permission = ForeignKey::from(permission);
+
+        let exists =
+            query!(UserPermission, $user == user.clone() && $permission == permission.clone())
+                .exists(db)
+                .await
+                .map_err(AuthError::backend_error)?;
+        if !exists {
+            UserPermission {
+                id: Auto::auto(),
+                user,
+                permission,
+            }
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Revokes a permission granted directly to the user. Note that the user
+    /// might still have the permission through one of their groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Permission};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser, permission: &Permission) -> cot::Result<()> {
+    ///     user.remove_permission(db, permission).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn remove_permission<DB: DatabaseBackend>(
+        &self,
+        db: &DB,
+        permission: &Permission,
+    ) -> Result<()> {
+        let user = ForeignKey::from(self);
+        let permission = ForeignKey::from(permission);
+
+        query!(UserPermission, $user == user && $permission == permission)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Returns the groups the user belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     for group in user.get_groups(db).await? {
+    ///         println!("{}", group.name());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_groups<DB: DatabaseBackend>(&self, db: &DB) -> Result<Vec<Group>> {
+        let user = ForeignKey::from(self);
+
+        let group_ids = query!(UserGroup, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|user_group| *user_group.group.primary_key());
+        let Some(filter) = any_of("id", group_ids) else {
+            return Ok(Vec::new());
+        };
+
+        Query::<Group>::new()
+            .filter(filter)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Adds the user to a group. Does nothing if the user already belongs to
+    /// the group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Group};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     let group = Group::create(db, "editors").await?;
+    ///     user.add_to_group(db, &group).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn add_to_group<DB: DatabaseBackend>(&self, db: &DB, group: &Group) -> Result<()> {
+        let user = ForeignKey::from(self);
+        let group = ForeignKey::from(group);
+
+        let exists = query!(UserGroup, $user == user.clone() && $group == group.clone())
+            .exists(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        if !exists {
+            UserGroup {
+                id: Auto::auto(),
+                user,
+                group,
+            }
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the user from a group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, Group};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, user: &DatabaseUser, group: &Group) -> cot::Result<()> {
+    ///     user.remove_from_group(db, group).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn remove_from_group<DB: DatabaseBackend>(
+        &self,
+        db: &DB,
+        group: &Group,
+    ) -> Result<()> {
+        let user = ForeignKey::from(self);
+        let group = ForeignKey::from(group);
+
+        query!(UserGroup, $user == user && $group == group)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+}
+
+impl User for DatabaseUser {
+    fn id(&self) -> Option<UserId> {
+        Some(UserId::Int(self.id()))
+    }
+
+    fn username(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::from(self.username.as_str()))
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn is_authenticated(&self) -> bool {
+        true
+    }
+
+    fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+        self.last_login
+    }
+
+    fn joined(&self) -> Option<DateTime<FixedOffset>> {
+        self.date_joined
+    }
+
+    fn is_superuser(&self) -> bool {
+        self.is_superuser
+    }
+
+    fn is_staff(&self) -> bool {
+        self.is_staff
+    }
+
+    fn session_auth_hash(&self, secret_key: &SecretKey) -> Option<SessionAuthHash> {
+        const SESSION_AUTH_HASH_CONTEXT: &str = "cot.rs session auth hash v1";
+
+        let key = blake3::derive_key(SESSION_AUTH_HASH_CONTEXT, secret_key.as_bytes());
+        let hash = blake3::keyed_hash(&key, self.password.as_str().as_bytes());
+
+        Some(SessionAuthHash::new(hash.as_slice()))
+    }
+}
+
+impl Display for DatabaseUser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.username)
+    }
+}
+
+/// A permission that can be granted to [`DatabaseUser`]s and [`Group`]s.
+///
+/// A permission is identified by its codename, such as `"article.publish"`,
+/// which is what is passed to [`User::has_perm`]. The permissions for
+/// viewing, adding, changing, and deleting the objects of every model
+/// registered in the [admin panel](crate::admin::AdminApp) are created
+/// automatically when the project starts (see
+/// [`AdminPermission`](crate::admin::AdminPermission)).
+#[derive(Debug, Clone, Form, AdminModel)]
+#[admin(list_display = [codename, name], search_fields = [codename, name])]
+#[model]
+pub struct Permission {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    #[model(unique)]
+    codename: LimitedString<MAX_PERMISSION_CODENAME_LENGTH>,
+    name: LimitedString<MAX_PERMISSION_NAME_LENGTH>,
+}
+
+impl Permission {
+    /// Returns the permission with the given codename, creating it if it
+    /// doesn't exist yet.
+    ///
+    /// The `name` is a human-readable description of the permission; it's
+    /// only used when the permission is created.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the codename or the name is too long, or if there
+    /// was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::Permission;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let permission =
+    ///         Permission::get_or_create(db, "article.publish", "Can publish articles").await?;
+    ///     assert_eq!(permission.codename(), "article.publish");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_or_create<DB: DatabaseBackend, C: Into<String>, N: Into<String>>(
+        db: &DB,
+        codename: C,
+        name: N,
+    ) -> Result<Self> {
+        let codename = codename.into();
+        let codename_length = codename.len();
+        let codename =
+            LimitedString::<MAX_PERMISSION_CODENAME_LENGTH>::new(codename).map_err(|_| {
+                AuthError::backend_error(CreatePermissionError::CodenameTooLong(codename_length))
+            })?;
+
+        let permission = query!(Permission, $codename == codename.clone())
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        if let Some(permission) = permission {
+            return Ok(permission);
+        }
+
+        let name = name.into();
+        let name_length = name.len();
+        let name = LimitedString::<MAX_PERMISSION_NAME_LENGTH>::new(name).map_err(|_| {
+            AuthError::backend_error(CreatePermissionError::NameTooLong(name_length))
+        })?;
+        let mut permission = Self {
+            id: Auto::auto(),
+            codename,
+            name,
+        };
+        permission
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(permission)
+    }
+
+    /// Retrieves a permission by its codename. It returns [`None`] if the
+    /// permission does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::Permission;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let permission = Permission::get_by_codename(db, "database-user.change").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_by_codename<DB: DatabaseBackend>(
+        db: &DB,
+        codename: &str,
+    ) -> Result<Option<Self>> {
+        let Ok(codename) = LimitedString::<MAX_PERMISSION_CODENAME_LENGTH>::new(codename) else {
+            return Ok(None);
+        };
+
+        query!(Permission, $codename == codename)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns the codename of the permission.
+    #[must_use]
+    pub fn codename(&self) -> &str {
+        &self.codename
+    }
+
+    /// Returns the human-readable name of the permission.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.codename)
+    }
+}
+
+/// A group of [`DatabaseUser`]s.
+///
+/// Groups are a way to grant the same set of permissions to many users at
+/// once: users have all the permissions of the groups they belong to.
+#[derive(Debug, Clone, Form, AdminModel)]
+#[admin(search_fields = [name])]
+#[model]
+pub struct Group {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    #[model(unique)]
+    name: LimitedString<MAX_GROUP_NAME_LENGTH>,
+}
+
+impl Group {
+    /// Creates a new group and saves it to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is too long, or if the group could not
+    /// be saved (for instance, because a group with the same name already
+    /// exists).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::Group;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let group = Group::create(db, "editors").await?;
+    ///     assert_eq!(group.name(), "editors");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create<DB: DatabaseBackend, T: Into<String>>(db: &DB, name: T) -> Result<Self> {
+        let name = name.into();
+        let name_length = name.len();
+        let name = LimitedString::<MAX_GROUP_NAME_LENGTH>::new(name)
+            .map_err(|_| AuthError::backend_error(CreateGroupError::NameTooLong(name_length)))?;
+
+        let mut group = Self {
+            id: Auto::auto(),
+            name,
+        };
+        group.insert(db).await.map_err(AuthError::backend_error)?;
+
+        Ok(group)
+    }
+
+    /// Retrieves a group by its name. It returns [`None`] if the group does
+    /// not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::Group;
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let group = Group::get_by_name(db, "editors").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_by_name<DB: DatabaseBackend>(db: &DB, name: &str) -> Result<Option<Self>> {
+        let Ok(name) = LimitedString::<MAX_GROUP_NAME_LENGTH>::new(name) else {
+            return Ok(None);
+        };
+
+        query!(Group, $name == name)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns the name of the group.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Grants a permission to all the members of the group. Does nothing if
+    /// the group already has the permission.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{Group, Permission};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database) -> cot::Result<()> {
+    ///     let group = Group::create(db, "editors").await?;
+    ///     let permission =
+    ///         Permission::get_or_create(db, "article.publish", "Can publish articles").await?;
+    ///     group.add_permission(db, &permission).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn add_permission<DB: DatabaseBackend>(
+        &self,
+        db: &DB,
+        permission: &Permission,
+    ) -> Result<()> {
+        let group = ForeignKey::from(self);
+        let permission = ForeignKey::from(permission);
+
+        let exists =
+            query!(GroupPermission, $group == group.clone() && $permission == permission.clone())
+                .exists(db)
+                .await
+                .map_err(AuthError::backend_error)?;
+        if !exists {
+            GroupPermission {
+                id: Auto::auto(),
+                group,
+                permission,
+            }
+            .insert(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Revokes a permission from the group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{Group, Permission};
+    /// use cot::db::Database;
+    ///
+    /// async fn view(db: &Database, group: &Group, permission: &Permission) -> cot::Result<()> {
+    ///     group.remove_permission(db, permission).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn remove_permission<DB: DatabaseBackend>(
+        &self,
+        db: &DB,
+        permission: &Permission,
+    ) -> Result<()> {
+        let group = ForeignKey::from(self);
+        let permission = ForeignKey::from(permission);
+
+        query!(GroupPermission, $group == group && $permission == permission)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+}
+
+impl Display for Group {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A permission granted directly to a user.
+#[derive(Debug, Clone, Form, AdminModel)]
+#[admin(list_display = [id, user, permission])]
+#[model]
+struct UserPermission {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    permission: ForeignKey<Permission>,
+}
+
+impl Display for UserPermission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "user #{} has permission #{}",
+            self.user.primary_key(),
+            self.permission.primary_key()
+        )
+    }
+}
+
+/// A membership of a user in a group.
+#[derive(Debug, Clone, Form, AdminModel)]
+#[admin(list_display = [id, user, group])]
+#[model]
+struct UserGroup {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    group: ForeignKey<Group>,
+}
+
+impl Display for UserGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "user #{} in group #{}",
+            self.user.primary_key(),
+            self.group.primary_key()
+        )
+    }
+}
+
+/// A permission granted to a group.
+#[derive(Debug, Clone, Form, AdminModel)]
+#[admin(list_display = [id, group, permission])]
+#[model]
+struct GroupPermission {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    group: ForeignKey<Group>,
+    permission: ForeignKey<Permission>,
+}
+
+impl Display for GroupPermission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "group #{} has permission #{}",
+            self.group.primary_key(),
+            self.permission.primary_key()
+        )
+    }
+}
+
+/// Builds an expression matching the rows where `column` is equal to any of
+/// the given IDs, or returns [`None`] if there are no IDs.
+fn any_of<I: IntoIterator<Item = Auto<i64>>>(column: &'static str, ids: I) -> Option<Expr> {
+    ids.into_iter()
+        .map(|id| Expr::eq(Expr::field(column), Expr::value(id)))
+        .reduce(Expr::or)
+}
+
+/// Creates the [`AdminPermission`]s for all the models registered in the admin
+/// panel by the given apps, if they don't exist yet.
+///
+/// This does nothing if the [`DatabaseUserApp`] is not registered in the
+/// project, as the permission table wouldn't exist in that case.
+pub(crate) async fn register_admin_permissions(db: &Database, apps: &[Box<dyn App>]) -> Result<()> {
+    if !apps.iter().any(|app| app.name() == DatabaseUserApp.name()) {
+        return Ok(());
+    }
+
+    for manager in apps.iter().flat_map(|app| app.admin_model_managers()) {
+        for permission in AdminPermission::ALL {
+            Permission::get_or_create(
+                db,
+                manager.permission_codename(permission),
+                permission.description_for(manager.name()),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Credentials for authenticating a user stored in the database.
+///
+/// This struct is used to authenticate a user stored in the database. It
+/// contains the username and password of the user.
+///
+/// Can be passed to [`Auth::authenticate`](crate::auth::Auth::authenticate) to
+/// authenticate a user when using the [`DatabaseUserBackend`].
+#[derive(Debug, Clone)]
+pub struct DatabaseUserCredentials {
+    username: String,
+    password: Password,
+}
+
+impl DatabaseUserCredentials {
+    /// Create a new instance of the database user credentials.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials =
+    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
+    /// ```
+    #[must_use]
+    pub fn new(username: String, password: Password) -> Self {
+        Self { username, password }
+    }
+
+    /// Returns the username of the user.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials =
+    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
+    /// assert_eq!(credentials.username(), "testuser");
+    /// ```
+    #[must_use]
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// Returns the password of the user.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUserCredentials;
+    /// use cot::common_types::Password;
+    ///
+    /// let credentials =
+    ///     DatabaseUserCredentials::new(String::from("testuser"), Password::new("password123"));
+    /// assert!(!credentials.password().as_str().is_empty());
+    /// ```
+    #[must_use]
+    pub fn password(&self) -> &Password {
+        &self.password
+    }
+}
+
+impl From<&BasicAuthCredentials> for DatabaseUserCredentials {
+    fn from(credentials: &BasicAuthCredentials) -> Self {
+        Self::new(
+            credentials.username().to_owned(),
+            credentials.password().clone(),
+        )
+    }
+}
+
+/// A user model that can be used with the [`DatabaseUserBackend`].
+///
+/// This is implemented by [`DatabaseUser`], but can also be implemented for
+/// custom models, for instance, to store additional data about the users,
+/// such as their display name. The backend uses the methods of this trait to
+/// look up the users, verify their passwords, and record the time of their
+/// last login; everything else (such as whether the user is
+/// [active](User::is_active)) is provided by the [`User`] trait.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use async_trait::async_trait;
+/// use chrono::{DateTime, FixedOffset};
+/// use cot::auth::db::{DatabaseUserBackend, DatabaseUserModel};
+/// use cot::auth::{AuthError, PasswordHash, User, UserId};
+/// use cot::db::{Auto, Database, LimitedString, Model, model, query};
+///
+/// #[model]
+/// struct MyUser {
+///     #[model(primary_key)]
+///     id: Auto<i64>,
+///     #[model(unique)]
+///     username: LimitedString<150>,
+///     password: PasswordHash,
+///     display_name: String,
+///     is_active: bool,
+///     last_login: Option<DateTime<FixedOffset>>,
+///     date_joined: DateTime<FixedOffset>,
+/// }
+///
+/// impl User for MyUser {
+///     fn id(&self) -> Option<UserId> {
+///         Some(UserId::Int(self.id.unwrap()))
+///     }
+///
+///     fn username(&self) -> Option<Cow<'_, str>> {
+///         Some(Cow::from(self.username.as_str()))
+///     }
+///
+///     fn is_active(&self) -> bool {
+///         self.is_active
+///     }
+///
+///     fn is_authenticated(&self) -> bool {
+///         true
+///     }
+///
+///     fn last_login(&self) -> Option<DateTime<FixedOffset>> {
+///         self.last_login
+///     }
+///
+///     fn joined(&self) -> Option<DateTime<FixedOffset>> {
+///         Some(self.date_joined)
+///     }
+/// }
+///
+/// #[async_trait]
+/// impl DatabaseUserModel for MyUser {
+///     async fn get_by_user_id(db: &Database, id: UserId) -> cot::auth::Result<Option<Self>> {
+///         let UserId::Int(id) = id else {
+///             return Err(AuthError::UserIdTypeNotSupported);
+///         };
+///         query!(MyUser, $id == id)
+///             .get(db)
+///             .await
+///             .map_err(AuthError::backend_error)
+///     }
+///
+///     async fn get_by_username(db: &Database, username: &str) -> cot::auth::Result<Option<Self>> {
+///         let Ok(username) = LimitedString::<150>::new(username) else {
+///             return Ok(None);
+///         };
+///         query!(MyUser, $username == username)
+///             .get(db)
+///             .await
+///             .map_err(AuthError::backend_error)
+///     }
+///
+///     fn password_hash(&self) -> &PasswordHash {
+///         &self.password
+///     }
+///
+///     fn set_password_hash(&mut self, password_hash: PasswordHash) {
+///         self.password = password_hash;
+///     }
+///
+///     async fn update_last_login(
+///         db: &Database,
+///         id: UserId,
+///         last_login: DateTime<FixedOffset>,
+///     ) -> cot::auth::Result<()> {
+///         if let Some(mut user) = Self::get_by_user_id(db, id).await? {
+///             user.last_login = Some(last_login);
+///             user.save(db).await.map_err(AuthError::backend_error)?;
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// fn backend(database: Database) -> DatabaseUserBackend<MyUser> {
+///     DatabaseUserBackend::with_user_model(database)
+/// }
+/// ```
+#[async_trait]
+pub trait DatabaseUserModel: Model + User + Send + Sync + 'static {
+    /// Retrieves a user by their ID. Returns [`None`] if the user does not
+    /// exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ID type is not supported, or if there was an
+    /// error querying the database.
+    async fn get_by_user_id(db: &Database, id: UserId) -> Result<Option<Self>>;
+
+    /// Retrieves a user by their username. Returns [`None`] if the user does
+    /// not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    async fn get_by_username(db: &Database, username: &str) -> Result<Option<Self>>;
+
+    /// Returns the hash of the user's password.
+    fn password_hash(&self) -> &PasswordHash;
+
+    /// Sets the hash of the user's password. This is used to upgrade the
+    /// hashes created with obsolete algorithms.
+    fn set_password_hash(&mut self, password_hash: PasswordHash);
+
+    /// Stores the time the user with the given ID last logged in. This is
+    /// called by the backend every time the user logs in.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    async fn update_last_login(
+        db: &Database,
+        id: UserId,
+        last_login: DateTime<FixedOffset>,
+    ) -> Result<()> {
+        let _ = (db, id, last_login);
+        Ok(())
+    }
+
+    /// Returns whether the user has verified their email address. Used when
+    /// the verified email address is
+    /// [required](DatabaseUserBackend::require_verified_email) to log in.
+    ///
+    /// The default implementation always returns `true`.
+    fn is_email_verified(&self) -> bool {
+        true
+    }
+
+    /// Returns whether the user with the given ID has enabled a second
+    /// authentication factor (see [`AuthBackend::requires_second_factor`]).
+    ///
+    /// The default implementation always returns `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    async fn second_factor_enabled(db: &Database, id: UserId) -> Result<bool> {
+        let _ = (db, id);
+        Ok(false)
+    }
+
+    /// Authenticates a user using the provided credentials.
+    ///
+    /// The default implementation looks the user up with
+    /// [`get_by_username`](Self::get_by_username) and verifies their password,
+    /// upgrading the password hash if it was created with an obsolete
+    /// algorithm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    async fn authenticate(
+        db: &Database,
+        credentials: &DatabaseUserCredentials,
+    ) -> Result<Option<Self>> {
+        let user = Self::get_by_username(db, credentials.username()).await?;
+
+        match check_password(
+            user.as_ref().map(Self::password_hash),
+            credentials.password(),
+        ) {
+            PasswordVerificationResult::Ok => Ok(user),
+            PasswordVerificationResult::OkObsolete(new_hash) => {
+                let Some(mut user) = user else {
+                    return Ok(None);
+                };
+                user.set_password_hash(new_hash);
+                user.save(db).await.map_err(AuthError::backend_error)?;
+                Ok(Some(user))
+            }
+            PasswordVerificationResult::Invalid => Ok(None),
+        }
+    }
+
+    /// Returns the codenames of the permissions granted to the user with the
+    /// given ID.
+    ///
+    /// This is used to implement [`AuthBackend::get_permissions`], so it's
+    /// not called for superusers, who implicitly have all the permissions.
+    /// The default implementation returns an empty set. [`DatabaseUser`]
+    /// overrides it to return the permissions granted directly and through
+    /// the groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    async fn get_permissions(db: &Database, id: UserId) -> Result<HashSet<String>> {
+        let _ = (db, id);
+        Ok(HashSet::new())
+    }
+}
+
+#[async_trait]
+impl DatabaseUserModel for DatabaseUser {
+    async fn get_by_user_id(db: &Database, id: UserId) -> Result<Option<Self>> {
+        let UserId::Int(id) = id else {
+            return Err(AuthError::UserIdTypeNotSupported);
+        };
+
+        DatabaseUser::get_by_id(db, id).await
+    }
+
+    async fn get_by_username(db: &Database, username: &str) -> Result<Option<Self>> {
+        DatabaseUser::get_by_username(db, username).await
+    }
+
+    fn password_hash(&self) -> &PasswordHash {
+        &self.password
+    }
+
+    fn set_password_hash(&mut self, password_hash: PasswordHash) {
+        self.password = password_hash;
+    }
+
+    async fn update_last_login(
+        db: &Database,
+        id: UserId,
+        last_login: DateTime<FixedOffset>,
+    ) -> Result<()> {
+        let UserId::Int(id) = id else {
+            return Err(AuthError::UserIdTypeNotSupported);
+        };
+
+        // Only the login time is updated, so that the changes made to the other
+        // fields in the meantime (for instance, in the admin panel) are not
+        // overwritten
+        let statement = sea_query::Query::update()
+            .table(<Self as Model>::TABLE_NAME)
+            .value(
+                <Self as Model>::Fields::last_login.identifier(),
+                Expr::value(last_login).as_sea_query_expr(),
+            )
+            .and_where(
+                Expr::eq(
+                    Expr::field(<Self as Model>::Fields::id.identifier()),
+                    Expr::value(id),
+                )
+                .as_sea_query_expr(),
+            )
+            .to_owned();
+        db.execute_statement(&statement)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    fn is_email_verified(&self) -> bool {
+        self.email_verified
+    }
+
+    async fn second_factor_enabled(db: &Database, id: UserId) -> Result<bool> {
+        let UserId::Int(id) = id else {
+            return Ok(false);
+        };
+
+        TotpDevice::is_enabled_for(db, id).await
+    }
+
+    async fn authenticate(
+        db: &Database,
+        credentials: &DatabaseUserCredentials,
+    ) -> Result<Option<Self>> {
+        DatabaseUser::authenticate(db, credentials).await
+    }
+
+    async fn get_permissions(db: &Database, id: UserId) -> Result<HashSet<String>> {
+        let UserId::Int(id) = id else {
+            return Err(AuthError::UserIdTypeNotSupported);
+        };
+
+        DatabaseUser::get_permissions_by_id(db, id).await
+    }
+}
+
+/// Verifies the password against the hash of the user's password.
+///
+/// If there is no user, the password is hashed anyway, so that the time it
+/// takes to respond doesn't disclose whether the user exists.
+fn check_password(
+    password_hash: Option<&PasswordHash>,
+    password: &Password,
+) -> PasswordVerificationResult {
+    if let Some(password_hash) = password_hash {
+        password_hash.verify(password)
+    } else {
+        // SECURITY: If no user was found, run the same hashing function to prevent
+        // timing attacks from being used to determine if a user exists. Additionally,
+        // do something with the result to prevent the compiler from optimizing out the
+        // operation.
+        // TODO: benchmark this to make sure it works as expected
+        let dummy_hash = PasswordHash::from_password(password);
+        if let PasswordVerificationResult::Invalid = dummy_hash.verify(password) {
+            unreachable!("Password hash verification should never fail for a newly generated hash");
+        }
+        PasswordVerificationResult::Invalid
+    }
+}
+
+/// The authentication backend for users stored in the database.
+///
+/// This is the default authentication backend for Cot. It authenticates
+/// users stored in the database using the [`DatabaseUser`] model, or a custom
+/// user model implementing [`DatabaseUserModel`]. Inactive users cannot log
+/// in, and the time of the [last login](User::last_login) is updated every
+/// time a user logs in.
+///
+/// This backend supports authenticating users using the
+/// [`DatabaseUserCredentials`] and [`BasicAuthCredentials`] structs and
+/// ignores all other credential types.
+///
+/// Users who have enabled the [TOTP two-factor
+/// authentication](crate::auth::totp) have to verify a one-time code when
+/// logging in (see [`AuthBackend::requires_second_factor`]). This can also be
+/// required for all staff users with
+/// [`require_second_factor_for_staff`](Self::require_second_factor_for_staff).
+/// Similarly, the users who haven't [verified their email
+/// address](DatabaseUser::is_email_verified) can be prevented from logging in
+/// with [`require_verified_email`](Self::require_verified_email).
+pub struct DatabaseUserBackend<U = DatabaseUser> {
+    database: Database,
+    second_factor_required_for_staff: bool,
+    verified_email_required: bool,
+    user_model: PhantomData<fn() -> U>,
+}
+
+impl<U> Debug for DatabaseUserBackend<U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseUserBackend")
+            .field("database", &self.database)
+            .field(
+                "second_factor_required_for_staff",
+                &self.second_factor_required_for_staff,
+            )
+            .field("verified_email_required", &self.verified_email_required)
+            .field("user_model", &std::any::type_name::<U>())
+            .finish()
+    }
+}
+
+impl<U> Clone for DatabaseUserBackend<U> {
+    fn clone(&self) -> Self {
+        Self {
+            database: self.database.clone(),
+            second_factor_required_for_staff: self.second_factor_required_for_staff,
+            verified_email_required: self.verified_email_required,
+            user_model: PhantomData,
+        }
+    }
+}
+
+impl DatabaseUserBackend {
+    /// Create a new instance of the database user authentication backend.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::auth::AuthBackend;
+    /// use cot::auth::db::DatabaseUserBackend;
+    /// use cot::config::ProjectConfig;
+    /// use cot::project::{AuthBackendContext, WithApps};
+    /// use cot::{Project, ProjectContext};
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+    ///         Arc::new(DatabaseUserBackend::new(context.database().clone()))
+    ///         // note that it's usually better to just set the auth backend in the config
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn new(database: Database) -> Self {
+        Self::with_user_model(database)
+    }
+}
+
+impl<U: DatabaseUserModel> DatabaseUserBackend<U> {
+    /// Create a new instance of the database user authentication backend that
+    /// uses a custom user model.
+    ///
+    /// See [`DatabaseUserModel`] for an example of such a model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::{DatabaseUser, DatabaseUserBackend};
+    /// use cot::db::Database;
+    ///
+    /// fn backend(database: Database) -> DatabaseUserBackend {
+    ///     DatabaseUserBackend::<DatabaseUser>::with_user_model(database)
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_user_model(database: Database) -> Self {
+        Self {
+            database,
+            second_factor_required_for_staff: false,
+            verified_email_required: false,
+            user_model: PhantomData,
+        }
+    }
+
+    /// Sets whether the staff users have to verify a second authentication
+    /// factor when logging in, even if they haven't enabled the two-factor
+    /// authentication yet.
+    ///
+    /// This is set automatically from
+    /// [`TwoFactorConfig::required_for_staff`](crate::config::TwoFactorConfig::required_for_staff)
+    /// when the backend is created from the project config.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::Project;
+    /// use cot::auth::AuthBackend;
+    /// use cot::auth::db::DatabaseUserBackend;
+    /// use cot::project::AuthBackendContext;
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+    ///         Arc::new(
+    ///             DatabaseUserBackend::new(context.database().clone())
+    ///                 .require_second_factor_for_staff(true),
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn require_second_factor_for_staff(mut self, required: bool) -> Self {
+        self.second_factor_required_for_staff = required;
+        self
+    }
+
+    /// Sets whether the users have to verify their email address before they
+    /// can log in.
+    ///
+    /// If enabled, [`authenticate`](AuthBackend::authenticate) returns
+    /// [`AuthError::EmailNotVerified`] for the users whose email address is
+    /// not verified, even if they provide the correct password. This is set
+    /// automatically from
+    /// [`EmailVerificationConfig::required_for_login`](crate::config::EmailVerificationConfig::required_for_login)
+    /// when the backend is created from the project config.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use cot::Project;
+    /// use cot::auth::AuthBackend;
+    /// use cot::auth::db::DatabaseUserBackend;
+    /// use cot::project::AuthBackendContext;
+    ///
+    /// struct HelloProject;
+    /// impl Project for HelloProject {
+    ///     fn auth_backend(&self, context: &AuthBackendContext) -> Arc<dyn AuthBackend> {
+    ///         Arc::new(
+    ///             DatabaseUserBackend::new(context.database().clone()).require_verified_email(true),
+    ///         )
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn require_verified_email(mut self, required: bool) -> Self {
+        self.verified_email_required = required;
+        self
     }
 }
 
 #[async_trait]
-impl AuthBackend for DatabaseUserBackend {
+impl<U: DatabaseUserModel> AuthBackend for DatabaseUserBackend<U> {
     async fn authenticate(
         &self,
         credentials: &(dyn Any + Send + Sync),
     ) -> Result<Option<Box<dyn User + Send + Sync>>> {
-        if let Some(credentials) = credentials.downcast_ref::<DatabaseUserCredentials>() {
-            #[expect(trivial_casts)] // Upcast to the correct Box type
-            Ok(DatabaseUser::authenticate(&self.database, credentials)
-                .await
-                .map(|user| user.map(|user| Box::new(user) as Box<dyn User + Send + Sync>))?)
+        let credentials =
+            if let Some(credentials) = credentials.downcast_ref::<DatabaseUserCredentials>() {
+                Some(Cow::Borrowed(credentials))
+            } else {
+                credentials
+                    .downcast_ref::<BasicAuthCredentials>()
+                    .map(|credentials| Cow::Owned(DatabaseUserCredentials::from(credentials)))
+            };
+
+        if let Some(credentials) = credentials {
+            let Some(user) = U::authenticate(&self.database, &credentials).await? else {
+                return Ok(None);
+            };
+            if !user.is_active() {
+                return Ok(None);
+            }
+            if self.verified_email_required && !DatabaseUserModel::is_email_verified(&user) {
+                return Err(AuthError::EmailNotVerified);
+            }
+
+            Ok(Some(Box::new(user)))
         } else {
             Err(AuthError::CredentialsTypeNotSupported)
         }
     }
 
     async fn get_by_id(&self, id: UserId) -> Result<Option<Box<dyn User + Send + Sync>>> {
-        let UserId::Int(id) = id else {
-            return Err(AuthError::UserIdTypeNotSupported);
+        let Some(user) = U::get_by_user_id(&self.database, id).await? else {
+            return Ok(None);
+        };
+        // deactivating a user logs them out of all their sessions
+        if !user.is_active() {
+            return Ok(None);
+        }
+
+        Ok(Some(Box::new(user)))
+    }
+
+    async fn get_permissions(&self, user: &(dyn User + Send + Sync)) -> Result<HashSet<String>> {
+        // superusers have all the permissions anyway, so there's no need to
+        // query the database
+        if user.is_superuser() {
+            return Ok(HashSet::new());
+        }
+        let Some(id) = user.id() else {
+            return Ok(HashSet::new());
+        };
+
+        U::get_permissions(&self.database, id).await
+    }
+
+    async fn requires_second_factor(&self, user: &(dyn User + Send + Sync)) -> Result<bool> {
+        if self.second_factor_required_for_staff && user.is_staff() {
+            return Ok(true);
+        }
+        let Some(id) = user.id() else {
+            return Ok(false);
+        };
+
+        U::second_factor_enabled(&self.database, id).await
+    }
+
+    fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+        if let Some(credentials) = credentials.downcast_ref::<DatabaseUserCredentials>() {
+            Some(credentials.username().to_owned())
+        } else {
+            credentials
+                .downcast_ref::<BasicAuthCredentials>()
+                .map(|credentials| credentials.username().to_owned())
+        }
+    }
+
+    async fn user_logged_in(&self, user: &(dyn User + Send + Sync)) -> Result<()> {
+        let Some(id) = user.id() else {
+            return Ok(());
         };
 
-        #[expect(trivial_casts)] // Upcast to the correct Box type
-        Ok(DatabaseUser::get_by_id(&self.database, id)
-            .await?
-            .map(|user| Box::new(user) as Box<dyn User + Send + Sync>))
+        U::update_last_login(&self.database, id, Utc::now().fixed_offset()).await
     }
 }
 
 /// An app that provides authentication via a user model stored in the database.
+///
+/// This app contributes the database migrations and the admin panel models.
+/// The views for registering new users and resetting their passwords are
+/// provided by the [`SignupApp`](crate::auth::signup::SignupApp) and the
+/// [`PasswordResetApp`](crate::auth::password_reset::PasswordResetApp) (both
+/// need the `email` feature to be enabled).
 #[derive(Debug, Copy, Clone)]
 pub struct DatabaseUserApp;
 
@@ -561,7 +2246,14 @@ impl App for DatabaseUserApp {
     }
 
     fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
-        vec![Box::new(DefaultAdminModelManager::<DatabaseUser>::new())]
+        vec![
+            Box::new(DefaultAdminModelManager::<DatabaseUser>::new()),
+            Box::new(DefaultAdminModelManager::<Group>::new()),
+            Box::new(DefaultAdminModelManager::<Permission>::new()),
+            Box::new(DefaultAdminModelManager::<UserGroup>::new()),
+            Box::new(DefaultAdminModelManager::<UserPermission>::new()),
+            Box::new(DefaultAdminModelManager::<GroupPermission>::new()),
+        ]
     }
 
     fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
@@ -626,6 +2318,41 @@ mod tests {
         assert_eq!(user.username(), username);
     }
 
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn create_user_validated() {
+        use crate::auth::password_validation::{
+            MinimumLengthValidator, UsernameSimilarityValidator,
+        };
+
+        let mut mock_db = MockDatabaseBackend::new();
+        mock_db
+            .expect_insert::<DatabaseUser>()
+            .times(1)
+            .returning(|_| Ok(()));
+        let validators = PasswordValidators::new()
+            .validator(MinimumLengthValidator::new(8))
+            .validator(UsernameSimilarityValidator::new());
+
+        let result =
+            DatabaseUser::create_user_validated(&mock_db, "testuser", "testuser1", &validators)
+                .await;
+        assert!(matches!(result, Err(AuthError::PasswordInvalid(_))));
+        let result =
+            DatabaseUser::create_user_validated(&mock_db, "testuser", "short", &validators).await;
+        assert!(matches!(result, Err(AuthError::PasswordInvalid(_))));
+
+        let user = DatabaseUser::create_user_validated(
+            &mock_db,
+            "testuser",
+            "correct horse battery staple",
+            &validators,
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.username(), "testuser");
+    }
+
     #[cot::test]
     #[cfg_attr(miri, ignore)]
     async fn get_by_id() {
@@ -706,4 +2433,105 @@ mod tests {
             .unwrap();
         assert!(result.is_none());
     }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn backend_updates_last_login() {
+        let mut db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        assert!(user.date_joined().is_some());
+        assert!(user.last_login().is_none());
+
+        let backend = DatabaseUserBackend::new(db.database());
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_string(), Password::new("password123"));
+        let auth_user = backend.authenticate(&credentials).await.unwrap().unwrap();
+        backend.user_logged_in(auth_user.as_ref()).await.unwrap();
+
+        let user = DatabaseUser::get_by_id(&db.database(), user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.last_login().is_some());
+        assert_eq!(User::last_login(&user), user.last_login());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn admin_keeps_login_dates() {
+        let mut db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        let last_login = DateTime::parse_from_rfc3339("2024-01-01T12:34:56+02:00").unwrap();
+        DatabaseUser::update_last_login(&db.database(), UserId::Int(user.id()), last_login)
+            .await
+            .unwrap();
+
+        let readonly_fields = <DatabaseUser as crate::admin::AdminModel>::readonly_fields();
+        let readonly_fields: Vec<&str> = readonly_fields.iter().map(AsRef::as_ref).collect();
+        let mut request = crate::test::TestRequestBuilder::post("/")
+            .database(db.database())
+            .form_data(&[
+                ("username", "newname"),
+                ("password", "password123"),
+                ("is_superuser", "false"),
+                ("is_staff", "false"),
+                ("email_verified", "false"),
+                ("is_active", "true"),
+                ("last_login", "2000-01-01T00:00"),
+                ("date_joined", "2000-01-01T00:00"),
+            ])
+            .build();
+        let result = DefaultAdminModelManager::<DatabaseUser>::new()
+            .save_from_request(&mut request, Some(&user.id().to_string()), &readonly_fields)
+            .await
+            .unwrap();
+        assert!(matches!(result, crate::admin::SaveResult::Saved(_)));
+
+        let saved = DatabaseUser::get_by_id(&db.database(), user.id())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.username(), "newname");
+        assert_eq!(saved.last_login(), Some(last_login));
+        assert_eq!(saved.date_joined(), user.date_joined());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn backend_rejects_inactive_user() {
+        let mut db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        user.set_active(false);
+        user.save(&db.database()).await.unwrap();
+
+        let backend = DatabaseUserBackend::new(db.database());
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_string(), Password::new("password123"));
+        assert!(backend.authenticate(&credentials).await.unwrap().is_none());
+        assert!(
+            backend
+                .get_by_id(UserId::Int(user.id()))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
 }
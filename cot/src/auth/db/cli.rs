@@ -0,0 +1,538 @@
+//! CLI tasks for managing the [`DatabaseUser`]s.
+//!
+//! The tasks are registered automatically when the
+//! [database authentication
+//! backend](crate::config::AuthBackendConfig::Database) is configured.
+
+use std::fmt::Display;
+use std::io::{BufRead, StdinLock, Stdout, Write};
+
+use async_trait::async_trait;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use cot_core::error::impl_into_cot_error;
+use thiserror::Error;
+
+use crate::auth::db::DatabaseUser;
+use crate::auth::password_validation::{PasswordValidationError, PasswordValidators};
+use crate::cli::CliTask;
+use crate::common_types::{Email, EmailParseError, Password};
+use crate::db::{Database, Model};
+use crate::project::WithConfig;
+use crate::{Bootstrapper, Result};
+
+pub(crate) const CREATE_SUPERUSER_SUBCOMMAND: &str = "create-superuser";
+pub(crate) const CHANGE_PASSWORD_SUBCOMMAND: &str = "change-password";
+const USERNAME_PARAM: &str = "username";
+const EMAIL_PARAM: &str = "email";
+const NO_INPUT_PARAM: &str = "no-input";
+/// The environment variable the password is read from when `--no-input` is
+/// used.
+const PASSWORD_ENV_VAR: &str = "COT_PASSWORD";
+
+#[derive(Debug, Error)]
+enum UserTaskError {
+    #[error("the --{USERNAME_PARAM} option is required when --{NO_INPUT_PARAM} is used")]
+    UsernameRequired,
+    #[error(
+        "the {PASSWORD_ENV_VAR} environment variable must be set when --{NO_INPUT_PARAM} is used"
+    )]
+    PasswordRequired,
+    #[error("user `{0}` already exists")]
+    UserExists(String),
+    #[error("user `{0}` does not exist")]
+    UserNotFound(String),
+    #[error("invalid email address: {0}")]
+    InvalidEmail(#[from] EmailParseError),
+    #[error("the password is invalid: {0}")]
+    InvalidPassword(#[from] PasswordValidationError),
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+    #[error("could not communicate with the terminal: {0}")]
+    Io(#[from] std::io::Error),
+}
+impl_into_cot_error!(UserTaskError);
+
+/// Creates a new superuser, prompting for the details that weren't passed on
+/// the command line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CreateSuperuser;
+
+#[async_trait(?Send)]
+impl CliTask for CreateSuperuser {
+    fn subcommand(&self) -> Command {
+        Command::new(CREATE_SUPERUSER_SUBCOMMAND)
+            .about("Creates a superuser that can log in to the admin panel")
+            .arg(
+                Arg::new(USERNAME_PARAM)
+                    .help("The username of the superuser")
+                    .long("username")
+                    .value_name("USERNAME"),
+            )
+            .arg(
+                Arg::new(EMAIL_PARAM)
+                    .help("The email address of the superuser")
+                    .long("email")
+                    .value_name("EMAIL"),
+            )
+            .arg(no_input_arg())
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.boot().await?;
+        let context = bootstrapper.context();
+        let database = context.database();
+        crate::project::ensure_database_migrated(database, context.apps()).await?;
+
+        create_superuser(
+            database,
+            &PasswordValidators::from_config(context.config()),
+            matches,
+            std::env::var(PASSWORD_ENV_VAR).ok(),
+            &mut Console::stdio(),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Changes the password of an existing user.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ChangePassword;
+
+#[async_trait(?Send)]
+impl CliTask for ChangePassword {
+    fn subcommand(&self) -> Command {
+        Command::new(CHANGE_PASSWORD_SUBCOMMAND)
+            .about("Changes the password of a user")
+            .arg(
+                Arg::new(USERNAME_PARAM)
+                    .help("The username of the user to change the password for")
+                    .required(true),
+            )
+            .arg(no_input_arg())
+    }
+
+    async fn execute(
+        &mut self,
+        matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.boot().await?;
+        let context = bootstrapper.context();
+        let database = context.database();
+        crate::project::ensure_database_migrated(database, context.apps()).await?;
+
+        change_password(
+            database,
+            &PasswordValidators::from_config(context.config()),
+            matches,
+            std::env::var(PASSWORD_ENV_VAR).ok(),
+            &mut Console::stdio(),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn no_input_arg() -> Arg {
+    Arg::new(NO_INPUT_PARAM)
+        .help(format!(
+            "Don't prompt for any input; the password is read from the {PASSWORD_ENV_VAR} \
+            environment variable"
+        ))
+        .long("no-input")
+        .action(ArgAction::SetTrue)
+}
+
+async fn create_superuser<R: BufRead, W: Write>(
+    database: &Database,
+    validators: &PasswordValidators,
+    matches: &ArgMatches,
+    env_password: Option<String>,
+    console: &mut Console<R, W>,
+) -> Result<DatabaseUser> {
+    let username = matches.get_one::<String>(USERNAME_PARAM);
+    let email = matches
+        .get_one::<String>(EMAIL_PARAM)
+        .map(|email| Email::try_from(email.as_str()))
+        .transpose()
+        .map_err(UserTaskError::from)?;
+
+    let (username, email, password) = if matches.get_flag(NO_INPUT_PARAM) {
+        let username = username.ok_or(UserTaskError::UsernameRequired)?.clone();
+        ensure_username_available(database, &username).await?;
+        let password = Password::new(env_password.ok_or(UserTaskError::PasswordRequired)?);
+        validators
+            .validate(&password, Some(&username))
+            .map_err(UserTaskError::from)?;
+
+        (username, email, password)
+    } else {
+        let username = if let Some(username) = username {
+            ensure_username_available(database, username).await?;
+            username.clone()
+        } else {
+            prompt_username(database, console).await?
+        };
+        let email = match email {
+            Some(email) => Some(email),
+            None => prompt_email(console)?,
+        };
+        let password = prompt_new_password(console, validators, &username, true)?;
+
+        (username, email, password)
+    };
+
+    let mut user = DatabaseUser::create_superuser(database, username, &password).await?;
+    if email.is_some() {
+        user.set_email(email);
+        // the address was provided by someone with access to the server
+        user.set_email_verified(true);
+        user.save(database).await?;
+    }
+
+    console.print(format_args!(
+        "Superuser \"{}\" created successfully.",
+        user.username()
+    ))?;
+    Ok(user)
+}
+
+async fn change_password<R: BufRead, W: Write>(
+    database: &Database,
+    validators: &PasswordValidators,
+    matches: &ArgMatches,
+    env_password: Option<String>,
+    console: &mut Console<R, W>,
+) -> Result<DatabaseUser> {
+    let username = matches
+        .get_one::<String>(USERNAME_PARAM)
+        .expect("required argument");
+    let mut user = DatabaseUser::get_by_username(database, username)
+        .await?
+        .ok_or_else(|| UserTaskError::UserNotFound(username.clone()))?;
+
+    let password = if matches.get_flag(NO_INPUT_PARAM) {
+        let password = Password::new(env_password.ok_or(UserTaskError::PasswordRequired)?);
+        validators
+            .validate(&password, Some(username))
+            .map_err(UserTaskError::from)?;
+        password
+    } else {
+        console.print(format_args!("Changing password for user \"{username}\""))?;
+        prompt_new_password(console, validators, username, false)?
+    };
+
+    user.set_password(&password);
+    user.save(database).await?;
+
+    console.print(format_args!(
+        "Password changed successfully for user \"{username}\"."
+    ))?;
+    Ok(user)
+}
+
+async fn ensure_username_available(database: &Database, username: &str) -> Result<()> {
+    if DatabaseUser::get_by_username(database, username)
+        .await?
+        .is_some()
+    {
+        return Err(UserTaskError::UserExists(username.to_owned()).into());
+    }
+
+    Ok(())
+}
+
+async fn prompt_username<R: BufRead, W: Write>(
+    database: &Database,
+    console: &mut Console<R, W>,
+) -> Result<String> {
+    loop {
+        let username = console.prompt("Username: ")?;
+        let username = username.trim();
+        if username.is_empty() {
+            console.print("Error: the username cannot be empty.")?;
+        } else if let Err(error) = ensure_username_available(database, username).await {
+            console.print(format_args!("Error: {error}"))?;
+        } else {
+            return Ok(username.to_owned());
+        }
+    }
+}
+
+fn prompt_email<R: BufRead, W: Write>(console: &mut Console<R, W>) -> Result<Option<Email>> {
+    loop {
+        let email = console.prompt("Email address (optional): ")?;
+        let email = email.trim();
+        if email.is_empty() {
+            return Ok(None);
+        }
+
+        match Email::try_from(email) {
+            Ok(email) => return Ok(Some(email)),
+            Err(error) => console.print(format_args!("Error: {error}"))?,
+        }
+    }
+}
+
+fn prompt_new_password<R: BufRead, W: Write>(
+    console: &mut Console<R, W>,
+    validators: &PasswordValidators,
+    username: &str,
+    allow_bypass: bool,
+) -> Result<Password> {
+    loop {
+        let password = console.prompt_password("Password: ")?;
+        let confirmation = console.prompt_password("Password (again): ")?;
+
+        if password.as_str() != confirmation.as_str() {
+            console.print("Error: the passwords don't match.")?;
+            continue;
+        }
+        if password.as_str().is_empty() {
+            console.print("Error: the password cannot be empty.")?;
+            continue;
+        }
+        if let Err(error) = validators.validate(&password, Some(username)) {
+            for message in error.messages() {
+                console.print(format_args!("Error: {message}"))?;
+            }
+            if !allow_bypass
+                || !console.confirm("Bypass password validation and use it anyway? [y/N]: ")?
+            {
+                continue;
+            }
+        }
+
+        return Ok(password);
+    }
+}
+
+/// The terminal the tasks interact with the user through.
+struct Console<R, W> {
+    input: R,
+    output: W,
+    hide_passwords: bool,
+}
+
+impl Console<StdinLock<'static>, Stdout> {
+    fn stdio() -> Self {
+        Self {
+            input: std::io::stdin().lock(),
+            output: std::io::stdout(),
+            hide_passwords: true,
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    fn print(&mut self, message: impl Display) -> Result<()> {
+        writeln!(self.output, "{message}").map_err(UserTaskError::from)?;
+        Ok(())
+    }
+
+    fn prompt(&mut self, prompt: &str) -> Result<String> {
+        write!(self.output, "{prompt}").map_err(UserTaskError::from)?;
+        self.output.flush().map_err(UserTaskError::from)?;
+
+        let mut line = String::new();
+        if self
+            .input
+            .read_line(&mut line)
+            .map_err(UserTaskError::from)?
+            == 0
+        {
+            return Err(UserTaskError::UnexpectedEof.into());
+        }
+
+        Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+    }
+
+    fn prompt_password(&mut self, prompt: &str) -> Result<Password> {
+        let password = if self.hide_passwords {
+            rpassword::prompt_password(prompt).map_err(UserTaskError::from)?
+        } else {
+            self.prompt(prompt)?
+        };
+
+        Ok(Password::new(password))
+    }
+
+    fn confirm(&mut self, prompt: &str) -> Result<bool> {
+        let answer = self.prompt(prompt)?;
+
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::auth::password_validation::MinimumLengthValidator;
+    use crate::test::TestDatabase;
+
+    fn test_console(input: &str) -> Console<Cursor<Vec<u8>>, Vec<u8>> {
+        Console {
+            input: Cursor::new(input.as_bytes().to_vec()),
+            output: Vec::new(),
+            hide_passwords: false,
+        }
+    }
+
+    fn validators() -> PasswordValidators {
+        PasswordValidators::new().validator(MinimumLengthValidator::new(8))
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn create_superuser_interactive() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let matches = CreateSuperuser
+            .subcommand()
+            .get_matches_from(["create-superuser"]);
+        let mut console = test_console(
+            "\nadmin\ninvalid\nadmin@example.com\nshort\nshort\nn\n\
+            password123\npassword1234\npassword123\npassword123\n",
+        );
+
+        let user = create_superuser(&db.database(), &validators(), &matches, None, &mut console)
+            .await
+            .unwrap();
+
+        assert_eq!(user.username(), "admin");
+        assert!(user.is_superuser());
+        assert_eq!(user.email().unwrap().as_str(), "admin@example.com");
+        let output = String::from_utf8(console.output).unwrap();
+        assert!(output.contains("the username cannot be empty"));
+        assert!(output.contains("the passwords don't match"));
+        assert!(output.contains("Superuser \"admin\" created successfully."));
+        let credentials = crate::auth::db::DatabaseUserCredentials::new(
+            "admin".to_owned(),
+            Password::new("password123"),
+        );
+        assert!(
+            DatabaseUser::authenticate(&db.database(), &credentials)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn create_superuser_no_input() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let matches = CreateSuperuser.subcommand().get_matches_from([
+            "create-superuser",
+            "--no-input",
+            "--username",
+            "admin",
+        ]);
+
+        let result = create_superuser(
+            &db.database(),
+            &validators(),
+            &matches,
+            None,
+            &mut test_console(""),
+        )
+        .await;
+        assert!(result.is_err());
+        let result = create_superuser(
+            &db.database(),
+            &validators(),
+            &matches,
+            Some("short".to_owned()),
+            &mut test_console(""),
+        )
+        .await;
+        assert!(result.is_err());
+
+        let user = create_superuser(
+            &db.database(),
+            &validators(),
+            &matches,
+            Some("password123".to_owned()),
+            &mut test_console(""),
+        )
+        .await
+        .unwrap();
+        assert!(user.is_superuser());
+
+        let result = create_superuser(
+            &db.database(),
+            &validators(),
+            &matches,
+            Some("password123".to_owned()),
+            &mut test_console(""),
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("already exists"));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn change_password_interactive() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        let matches = ChangePassword
+            .subcommand()
+            .get_matches_from(["change-password", "testuser"]);
+        let mut console = test_console("short\nshort\nnewpassword\nnewpassword\n");
+
+        change_password(&db.database(), &validators(), &matches, None, &mut console)
+            .await
+            .unwrap();
+
+        let output = String::from_utf8(console.output).unwrap();
+        assert!(output.contains("Error: "));
+        assert!(output.contains("Password changed successfully"));
+        let credentials = crate::auth::db::DatabaseUserCredentials::new(
+            "testuser".to_owned(),
+            Password::new("newpassword"),
+        );
+        assert!(
+            DatabaseUser::authenticate(&db.database(), &credentials)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let matches = ChangePassword.subcommand().get_matches_from([
+            "change-password",
+            "nonexistent",
+            "--no-input",
+        ]);
+        let result = change_password(
+            &db.database(),
+            &validators(),
+            &matches,
+            Some("password123".to_owned()),
+            &mut test_console(""),
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+    }
+}
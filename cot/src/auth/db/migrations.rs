@@ -3,5 +3,21 @@
 //! Generated by cot CLI 0.1.0 on 2025-02-13 10:29:03+00:00
 
 pub mod m_0001_initial;
+pub mod m_0002_permissions;
+pub mod m_0003_user_email;
+pub mod m_0004_api_tokens;
+pub mod m_0005_totp;
+pub mod m_0006_oauth_identities;
+pub mod m_0007_user_email_verified;
+pub mod m_0008_user_activity;
 /// The list of migrations for current app.
-pub const MIGRATIONS: &[&::cot::db::migrations::SyncDynMigration] = &[&m_0001_initial::Migration];
+pub const MIGRATIONS: &[&::cot::db::migrations::SyncDynMigration] = &[
+    &m_0001_initial::Migration,
+    &m_0002_permissions::Migration,
+    &m_0003_user_email::Migration,
+    &m_0004_api_tokens::Migration,
+    &m_0005_totp::Migration,
+    &m_0006_oauth_identities::Migration,
+    &m_0007_user_email_verified::Migration,
+    &m_0008_user_activity::Migration,
+];
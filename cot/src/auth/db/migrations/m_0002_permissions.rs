@@ -0,0 +1,297 @@
+//! Adds the superuser and staff flags to the users, as well as the
+//! permissions and groups.
+
+use sea_query::{ColumnDef, Expr, Query, Table};
+
+use crate::db::migrations::{MigrationContext, migration_op};
+use crate::db::{Identifier, Result};
+
+const USER_TABLE_NAME: Identifier = Identifier::new("cot__database_user");
+const USER_FLAG_COLUMNS: [Identifier; 2] =
+    [Identifier::new("is_superuser"), Identifier::new("is_staff")];
+
+// The flags are added in a custom operation, because the columns need to have a
+// default value for the rows of the users that already exist. Before the flags
+// were introduced, every user could log in to the admin panel, so the users
+// that already exist are marked as superusers and staff members, so that they
+// are not locked out of it.
+#[migration_op]
+async fn add_user_flags(ctx: MigrationContext<'_>) -> Result<()> {
+    for column in USER_FLAG_COLUMNS {
+        let statement = Table::alter()
+            .table(USER_TABLE_NAME)
+            .add_column(ColumnDef::new(column).boolean().not_null().default(false))
+            .to_owned();
+        ctx.db.execute_schema(statement).await?;
+    }
+
+    let mut statement = Query::update();
+    statement.table(USER_TABLE_NAME);
+    for column in USER_FLAG_COLUMNS {
+        statement.value(column, Expr::value(true));
+    }
+    ctx.db.execute_statement(&statement).await?;
+
+    Ok(())
+}
+
+#[migration_op]
+async fn remove_user_flags(ctx: MigrationContext<'_>) -> Result<()> {
+    for column in USER_FLAG_COLUMNS {
+        let statement = Table::alter()
+            .table(USER_TABLE_NAME)
+            .drop_column(column)
+            .to_owned();
+        ctx.db.execute_schema(statement).await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0002_permissions";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] =
+        &[::cot::db::migrations::MigrationDependency::migration(
+            "cot",
+            "m_0001_initial",
+        )];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::custom(add_user_flags)
+            .backwards(remove_user_flags)
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__permission"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("codename"),
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_PERMISSION_CODENAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_PERMISSION_CODENAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                )
+                .unique(),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("name"),
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_PERMISSION_NAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_PERMISSION_NAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__group"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("name"),
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_GROUP_NAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::db::MAX_GROUP_NAME_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                )
+                .unique(),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__user_permission"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("user"),
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("permission"),
+                    <crate::db::ForeignKey<crate::auth::db::Permission> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::Permission as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::Permission as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::Permission> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__user_group"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("user"),
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("group"),
+                    <crate::db::ForeignKey<crate::auth::db::Group> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::Group as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::Group as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::Group> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__group_permission"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("group"),
+                    <crate::db::ForeignKey<crate::auth::db::Group> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::Group as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::Group as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::Group> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("permission"),
+                    <crate::db::ForeignKey<crate::auth::db::Permission> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::Permission as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::Permission as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::Permission> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _DatabaseUser {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    username: crate::db::LimitedString<{ crate::auth::db::MAX_USERNAME_LENGTH }>,
+    password: crate::auth::PasswordHash,
+    is_superuser: bool,
+    is_staff: bool,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _Permission {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    codename: crate::db::LimitedString<{ crate::auth::db::MAX_PERMISSION_CODENAME_LENGTH }>,
+    name: crate::db::LimitedString<{ crate::auth::db::MAX_PERMISSION_NAME_LENGTH }>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _Group {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    name: crate::db::LimitedString<{ crate::auth::db::MAX_GROUP_NAME_LENGTH }>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _UserPermission {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    permission: crate::db::ForeignKey<crate::auth::db::Permission>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _UserGroup {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    group: crate::db::ForeignKey<crate::auth::db::Group>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _GroupPermission {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    group: crate::db::ForeignKey<crate::auth::db::Group>,
+    permission: crate::db::ForeignKey<crate::auth::db::Permission>,
+}
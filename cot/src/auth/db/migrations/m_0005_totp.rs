@@ -0,0 +1,125 @@
+//! Adds the TOTP devices and recovery codes of the users.
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0005_totp";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] =
+        &[::cot::db::migrations::MigrationDependency::migration(
+            "cot",
+            "m_0004_api_tokens",
+        )];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__totp_device"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("user"),
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("secret"),
+                    <crate::db::LimitedString<
+                        { crate::auth::totp::TOTP_SECRET_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::totp::TOTP_SECRET_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("confirmed"),
+                    <bool as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(<bool as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("last_used_step"),
+                    <i64 as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(<i64 as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("created_at"),
+                    <chrono::DateTime<chrono::FixedOffset> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <chrono::DateTime<chrono::FixedOffset> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__totp_recovery_code"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("user"),
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("code_hash"),
+                    <crate::db::LimitedString<
+                        { crate::auth::totp::RECOVERY_CODE_HASH_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::totp::RECOVERY_CODE_HASH_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _TotpDevice {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    secret: crate::db::LimitedString<{ crate::auth::totp::TOTP_SECRET_LENGTH }>,
+    confirmed: bool,
+    last_used_step: i64,
+    created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _TotpRecoveryCode {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    code_hash: crate::db::LimitedString<{ crate::auth::totp::RECOVERY_CODE_HASH_LENGTH }>,
+}
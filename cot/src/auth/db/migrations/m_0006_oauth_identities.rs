@@ -0,0 +1,119 @@
+//! Adds the external identities of the users.
+
+use sea_query::Index;
+
+use crate::db::migrations::{MigrationContext, migration_op};
+use crate::db::{Identifier, Result};
+
+const IDENTITY_TABLE_NAME: Identifier = Identifier::new("cot__oauth_identity");
+const IDENTITY_INDEX_NAME: &str = "cot__oauth_identity_provider_subject";
+
+// The unique constraint spans two columns, which the model operations can't
+// express, so it's created in a custom operation. It guarantees that an
+// identity is never linked to two users, even if they are linked concurrently.
+#[migration_op]
+async fn add_identity_index(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Index::create()
+        .name(IDENTITY_INDEX_NAME)
+        .table(IDENTITY_TABLE_NAME)
+        .col(Identifier::new("provider"))
+        .col(Identifier::new("subject"))
+        .unique()
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    Ok(())
+}
+
+#[migration_op]
+async fn remove_identity_index(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Index::drop()
+        .name(IDENTITY_INDEX_NAME)
+        .table(IDENTITY_TABLE_NAME)
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0006_oauth_identities";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] =
+        &[::cot::db::migrations::MigrationDependency::migration(
+            "cot",
+            "m_0005_totp",
+        )];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::create_model()
+            .table_name(::cot::db::Identifier::new("cot__oauth_identity"))
+            .fields(&[
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("id"),
+                    <cot::db::Auto<i64> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .auto()
+                .primary_key()
+                .set_null(<cot::db::Auto<i64> as ::cot::db::DatabaseField>::NULLABLE),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("user"),
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .foreign_key(
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::TABLE_NAME,
+                    <crate::auth::db::DatabaseUser as ::cot::db::Model>::PRIMARY_KEY_NAME,
+                    ::cot::db::ForeignKeyOnDeletePolicy::Restrict,
+                    ::cot::db::ForeignKeyOnUpdatePolicy::Restrict,
+                )
+                .set_null(
+                    <crate::db::ForeignKey<crate::auth::db::DatabaseUser> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("provider"),
+                    <crate::db::LimitedString<
+                        { crate::auth::oauth::MAX_OAUTH_PROVIDER_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::oauth::MAX_OAUTH_PROVIDER_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("subject"),
+                    <crate::db::LimitedString<
+                        { crate::auth::oauth::MAX_OAUTH_SUBJECT_LENGTH },
+                    > as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <crate::db::LimitedString<
+                        { crate::auth::oauth::MAX_OAUTH_SUBJECT_LENGTH },
+                    > as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("created_at"),
+                    <chrono::DateTime<chrono::FixedOffset> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <chrono::DateTime<chrono::FixedOffset> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            ])
+            .build(),
+        ::cot::db::migrations::Operation::custom(add_identity_index)
+            .backwards(remove_identity_index)
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration", table_name = "oauth_identity")]
+struct _OAuthIdentity {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    user: crate::db::ForeignKey<crate::auth::db::DatabaseUser>,
+    provider: crate::db::LimitedString<{ crate::auth::oauth::MAX_OAUTH_PROVIDER_LENGTH }>,
+    subject: crate::db::LimitedString<{ crate::auth::oauth::MAX_OAUTH_SUBJECT_LENGTH }>,
+    created_at: chrono::DateTime<chrono::FixedOffset>,
+}
@@ -0,0 +1,77 @@
+//! Adds the email verification flag to the users.
+
+use sea_query::{ColumnDef, Expr, Query, Table};
+
+use crate::db::migrations::{MigrationContext, migration_op};
+use crate::db::{Identifier, Result};
+
+const USER_TABLE_NAME: Identifier = Identifier::new("cot__database_user");
+const EMAIL_VERIFIED_COLUMN: Identifier = Identifier::new("email_verified");
+
+// The flag is added in a custom operation, because the column needs to have a
+// default value for the rows of the users that already exist. The users that
+// existed before email verification was introduced are marked as verified, so
+// that enabling `EmailVerificationConfig::required_for_login` doesn't lock them
+// out.
+#[migration_op]
+async fn add_email_verified(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Table::alter()
+        .table(USER_TABLE_NAME)
+        .add_column(
+            ColumnDef::new(EMAIL_VERIFIED_COLUMN)
+                .boolean()
+                .not_null()
+                .default(false),
+        )
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    let statement = Query::update()
+        .table(USER_TABLE_NAME)
+        .value(EMAIL_VERIFIED_COLUMN, Expr::value(true))
+        .to_owned();
+    ctx.db.execute_statement(&statement).await?;
+
+    Ok(())
+}
+
+#[migration_op]
+async fn remove_email_verified(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Table::alter()
+        .table(USER_TABLE_NAME)
+        .drop_column(EMAIL_VERIFIED_COLUMN)
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0007_user_email_verified";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] =
+        &[::cot::db::migrations::MigrationDependency::migration(
+            "cot",
+            "m_0006_oauth_identities",
+        )];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] =
+        &[::cot::db::migrations::Operation::custom(add_email_verified)
+            .backwards(remove_email_verified)
+            .build()];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _DatabaseUser {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    username: crate::db::LimitedString<{ crate::auth::db::MAX_USERNAME_LENGTH }>,
+    password: crate::auth::PasswordHash,
+    is_superuser: bool,
+    is_staff: bool,
+    email: Option<crate::common_types::Email>,
+    email_verified: bool,
+}
@@ -0,0 +1,97 @@
+//! Adds the active flag, the last login time, and the date joined to the
+//! users.
+
+use sea_query::{ColumnDef, Table};
+
+use crate::db::migrations::{MigrationContext, migration_op};
+use crate::db::{Identifier, Result};
+
+const USER_TABLE_NAME: Identifier = Identifier::new("cot__database_user");
+const IS_ACTIVE_COLUMN: Identifier = Identifier::new("is_active");
+
+// The flag is added in a custom operation, because the column needs to have a
+// default value for the rows of the users that already exist.
+#[migration_op]
+async fn add_is_active(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Table::alter()
+        .table(USER_TABLE_NAME)
+        .add_column(
+            ColumnDef::new(IS_ACTIVE_COLUMN)
+                .boolean()
+                .not_null()
+                .default(true),
+        )
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    Ok(())
+}
+
+#[migration_op]
+async fn remove_is_active(ctx: MigrationContext<'_>) -> Result<()> {
+    let statement = Table::alter()
+        .table(USER_TABLE_NAME)
+        .drop_column(IS_ACTIVE_COLUMN)
+        .to_owned();
+    ctx.db.execute_schema(statement).await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Migration;
+impl ::cot::db::migrations::Migration for Migration {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0008_user_activity";
+    const DEPENDENCIES: &'static [::cot::db::migrations::MigrationDependency] =
+        &[::cot::db::migrations::MigrationDependency::migration(
+            "cot",
+            "m_0007_user_email_verified",
+        )];
+    const OPERATIONS: &'static [::cot::db::migrations::Operation] = &[
+        ::cot::db::migrations::Operation::custom(add_is_active)
+            .backwards(remove_is_active)
+            .build(),
+        ::cot::db::migrations::Operation::add_field()
+            .table_name(::cot::db::Identifier::new("cot__database_user"))
+            .field(
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("last_login"),
+                    <Option<chrono::DateTime<chrono::FixedOffset>> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <Option<chrono::DateTime<chrono::FixedOffset>> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            )
+            .build(),
+        ::cot::db::migrations::Operation::add_field()
+            .table_name(::cot::db::Identifier::new("cot__database_user"))
+            .field(
+                ::cot::db::migrations::Field::new(
+                    ::cot::db::Identifier::new("date_joined"),
+                    <Option<chrono::DateTime<chrono::FixedOffset>> as ::cot::db::DatabaseField>::TYPE,
+                )
+                .set_null(
+                    <Option<chrono::DateTime<chrono::FixedOffset>> as ::cot::db::DatabaseField>::NULLABLE,
+                ),
+            )
+            .build(),
+    ];
+}
+
+#[derive(::core::fmt::Debug)]
+#[::cot::db::model(model_type = "migration")]
+struct _DatabaseUser {
+    #[model(primary_key)]
+    id: cot::db::Auto<i64>,
+    #[model(unique)]
+    username: crate::db::LimitedString<{ crate::auth::db::MAX_USERNAME_LENGTH }>,
+    password: crate::auth::PasswordHash,
+    is_superuser: bool,
+    is_staff: bool,
+    email: Option<crate::common_types::Email>,
+    email_verified: bool,
+    is_active: bool,
+    last_login: Option<chrono::DateTime<chrono::FixedOffset>>,
+    date_joined: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
@@ -0,0 +1,650 @@
+//! Restricting access to views to logged-in users.
+//!
+//! This module provides the [`LoginRequired`] handler wrapper, which can guard
+//! a single request handler or a whole [`Router`], and the [`RequireUser`]
+//! extractor. Both reject requests from anonymous (and inactive) users.
+//!
+//! When a login route is configured with [`LoginRequired::login_route`],
+//! requests sent by web browsers (i.e., the ones that explicitly accept
+//! `text/html`) are redirected to the login page, with the original URL passed
+//! in the `next` query parameter, which the login view can read with
+//! [`next_url`] to send the user back after logging in. All other requests,
+//! such as the ones sent to API endpoints, get a `401 Unauthorized` response.
+//! Users that are logged in, but lack one of the
+//! [required permissions](LoginRequired::permission), get a `403 Forbidden`
+//! response.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::guard::LoginRequired;
+//! use cot::html::Html;
+//! use cot::router::{Route, Router};
+//!
+//! async fn dashboard() -> Html {
+//!     Html::new("Dashboard")
+//! }
+//!
+//! async fn reports() -> Html {
+//!     Html::new("Reports")
+//! }
+//!
+//! async fn login() -> Html {
+//!     Html::new("Login")
+//! }
+//!
+//! let reports_router = Router::with_urls([Route::with_handler("/", reports)]);
+//!
+//! let router = Router::with_urls([
+//!     Route::with_handler_and_name("/login/", login, "login"),
+//!     Route::with_handler(
+//!         "/dashboard/",
+//!         LoginRequired::new(dashboard).login_route("login"),
+//!     ),
+//!     Route::with_router(
+//!         "/reports",
+//!         LoginRequired::new(reports_router)
+//!             .login_route("login")
+//!             .permission("reports.view")
+//!             .into_router(),
+//!     ),
+//! ]);
+//! ```
+
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use cot_core::handler::BoxRequestHandler;
+
+use crate::auth::{Auth, AuthenticationRequired, PermissionDenied, User};
+use crate::project::headers_accept_html;
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Redirect, Response};
+use crate::router::{HandlerWrapper, Router, split_view_name};
+use crate::{RequestHandler, reverse_param_map};
+
+/// A request handler wrapper that only lets authenticated users through.
+///
+/// Anonymous and inactive users are either redirected to the [login
+/// route](Self::login_route), if one is configured and the request comes from
+/// a web browser, or get a `401 Unauthorized` response. Authenticated users
+/// that lack any of the [required permissions](Self::permission) get a `403
+/// Forbidden` response.
+///
+/// The wrapper can be used with a single request handler, or with a whole
+/// [`Router`] through [`LoginRequired::into_router`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::guard::LoginRequired;
+/// use cot::html::Html;
+/// use cot::router::{Route, Router};
+///
+/// async fn publish() -> Html {
+///     Html::new("Published")
+/// }
+///
+/// let router = Router::with_urls([Route::with_handler(
+///     "/publish/",
+///     LoginRequired::new(publish).permission("article.publish"),
+/// )]);
+/// ```
+#[derive(Clone)]
+pub struct LoginRequired<H> {
+    handler: H,
+    guard: Arc<Guard>,
+}
+
+impl<H> Debug for LoginRequired<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoginRequired")
+            .field("handler", &"..")
+            .field("guard", &self.guard)
+            .finish()
+    }
+}
+
+impl<H> LoginRequired<H> {
+    /// Wraps the given request handler (or [`Router`]) so that it can only be
+    /// accessed by authenticated users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::guard::LoginRequired;
+    /// use cot::html::Html;
+    ///
+    /// async fn dashboard() -> Html {
+    ///     Html::new("Dashboard")
+    /// }
+    ///
+    /// let handler = LoginRequired::new(dashboard);
+    /// ```
+    #[must_use]
+    pub fn new(handler: H) -> Self {
+        Self {
+            handler,
+            guard: Arc::new(Guard::default()),
+        }
+    }
+
+    /// Sets the name of the route that anonymous users coming from a web
+    /// browser are redirected to.
+    ///
+    /// The route name can be prefixed with the app name, e.g.
+    /// `"admin:login"`. If it isn't, the app that the guarded route belongs to
+    /// is used. The URL of the original request is passed in the `next` query
+    /// parameter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::guard::LoginRequired;
+    /// use cot::html::Html;
+    ///
+    /// async fn dashboard() -> Html {
+    ///     Html::new("Dashboard")
+    /// }
+    ///
+    /// let handler = LoginRequired::new(dashboard).login_route("login");
+    /// ```
+    #[must_use]
+    pub fn login_route(mut self, login_route: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.guard).login_route = Some(login_route.into());
+        self
+    }
+
+    /// Requires the user to have the given permission.
+    ///
+    /// This can be called multiple times; the user has to have all the
+    /// permissions to access the route. The permissions are checked with
+    /// [`User::has_perm`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::guard::LoginRequired;
+    /// use cot::html::Html;
+    ///
+    /// async fn publish() -> Html {
+    ///     Html::new("Published")
+    /// }
+    ///
+    /// let handler = LoginRequired::new(publish)
+    ///     .permission("article.change")
+    ///     .permission("article.publish");
+    /// ```
+    #[must_use]
+    pub fn permission(mut self, permission: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.guard)
+            .permissions
+            .push(permission.into());
+        self
+    }
+}
+
+impl LoginRequired<Router> {
+    /// Returns the wrapped router with all its routes, including the ones in
+    /// the nested routers, guarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::guard::LoginRequired;
+    /// use cot::html::Html;
+    /// use cot::router::{Route, Router};
+    ///
+    /// async fn reports() -> Html {
+    ///     Html::new("Reports")
+    /// }
+    ///
+    /// let router = Router::with_urls([Route::with_handler("/", reports)]);
+    /// let router = LoginRequired::new(router).into_router();
+    /// ```
+    #[must_use]
+    pub fn into_router(self) -> Router {
+        self.handler.wrap_handlers(&RouterGuard(self.guard))
+    }
+}
+
+impl<T, H: RequestHandler<T> + Send + Sync> RequestHandler<T> for LoginRequired<H> {
+    async fn handle(&self, mut request: Request) -> crate::Result<Response> {
+        if let Some(response) = self.guard.check(&mut request).await? {
+            return Ok(response);
+        }
+
+        self.handler.handle(request).await
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<H: crate::openapi::AsApiRoute> crate::openapi::AsApiRoute for LoginRequired<H> {
+    fn as_api_route(
+        &self,
+        route_context: &crate::openapi::RouteContext<'_>,
+        schema_generator: &mut schemars::SchemaGenerator,
+    ) -> aide::openapi::PathItem {
+        self.handler.as_api_route(route_context, schema_generator)
+    }
+}
+
+/// An extractor that returns the currently logged-in user.
+///
+/// Unlike [`Auth::user`], this fails with a `401 Unauthorized` error when the
+/// user is anonymous or inactive, so the handler can only be reached by
+/// authenticated users.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::guard::RequireUser;
+/// use cot::html::Html;
+///
+/// async fn profile(RequireUser(user): RequireUser) -> Html {
+///     Html::new(format!("Hello, {}!", user.username().unwrap_or_default()))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RequireUser(pub Arc<dyn User + Send + Sync>);
+
+impl Debug for RequireUser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RequireUser")
+            .field(&self.0.username())
+            .finish()
+    }
+}
+
+impl RequireUser {
+    pub(crate) fn from_auth(auth: &Auth) -> Result<Self, AuthenticationRequired> {
+        let user = auth.user();
+        if is_logged_in(&*user) {
+            Ok(Self(user))
+        } else {
+            Err(AuthenticationRequired::new())
+        }
+    }
+}
+
+fn is_logged_in(user: &dyn User) -> bool {
+    user.is_authenticated() && user.is_active()
+}
+
+#[derive(Debug, Clone, Default)]
+struct Guard {
+    login_route: Option<String>,
+    permissions: Vec<String>,
+}
+
+impl Guard {
+    /// Checks whether the request is allowed to proceed. Returns a response to
+    /// send instead of calling the handler (i.e., a redirect to the login
+    /// page), or an error if the access is denied.
+    async fn check(&self, request: &mut Request) -> crate::Result<Option<Response>> {
+        let auth: Auth = request.extract_from_head().await?;
+        let user = auth.user();
+
+        if !is_logged_in(&*user) {
+            if let Some(login_route) = &self.login_route
+                && headers_accept_html(request.headers())
+            {
+                return login_redirect(request, login_route).map(Some);
+            }
+
+            return Err(AuthenticationRequired::new().into());
+        }
+
+        if !self.permissions.is_empty() {
+            auth.load_permissions().await?;
+            let user = auth.user();
+            if self.permissions.iter().any(|perm| !user.has_perm(perm)) {
+                return Err(PermissionDenied::new().into());
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn login_redirect(request: &Request, login_route: &str) -> crate::Result<Response> {
+    let (app_name, view_name) = split_view_name(login_route);
+    let app_name = app_name.or_else(|| request.app_name());
+    let login_url = request
+        .router()
+        .reverse(app_name, view_name, &reverse_param_map!())?;
+
+    let next = request
+        .uri()
+        .path_and_query()
+        .map_or_else(|| request.uri().path(), |path| path.as_str());
+    let next: String = form_urlencoded::byte_serialize(next.as_bytes()).collect();
+
+    Redirect::new(format!("{login_url}?next={next}")).into_response()
+}
+
+/// Returns the URL passed in the `next` query parameter by
+/// [`LoginRequired`], which the login view should redirect to after the user
+/// logs in.
+///
+/// Only the paths on the same site (i.e., starting with a single `/`) are
+/// returned; for any other value, including absolute and protocol-relative
+/// URLs, `None` is returned, so that the parameter can't be used to redirect
+/// the users to a malicious site.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::guard::next_url;
+/// use cot::request::Request;
+/// use cot::response::Redirect;
+///
+/// async fn login(request: Request) -> Redirect {
+///     // ... log the user in ...
+///     Redirect::new(next_url(&request).unwrap_or_else(|| "/".to_owned()))
+/// }
+/// ```
+#[must_use]
+pub fn next_url(request: &Request) -> Option<String> {
+    let query = request.uri().query()?;
+    let next = form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "next")
+        .map(|(_, value)| value.into_owned())?;
+
+    is_local_path(&next).then_some(next)
+}
+
+fn is_local_path(url: &str) -> bool {
+    // Browsers treat backslashes as forward slashes, so `/\example.com` is a
+    // protocol-relative URL just like `//example.com`
+    url.starts_with('/')
+        && !url[1..].starts_with(['/', '\\'])
+        && !url.contains(|c: char| c.is_control())
+        && url.parse::<http::uri::PathAndQuery>().is_ok()
+}
+
+/// Wraps all the handlers of a router with a [`Guard`].
+struct RouterGuard(Arc<Guard>);
+
+impl HandlerWrapper for RouterGuard {
+    fn wrap(
+        &self,
+        handler: Arc<dyn BoxRequestHandler + Send + Sync>,
+    ) -> Arc<dyn BoxRequestHandler + Send + Sync> {
+        Arc::new(GuardedHandler {
+            handler,
+            guard: Arc::clone(&self.0),
+        })
+    }
+
+    #[cfg(feature = "openapi")]
+    fn wrap_api(
+        &self,
+        handler: Arc<dyn crate::openapi::BoxApiEndpointRequestHandler + Send + Sync>,
+    ) -> Arc<dyn crate::openapi::BoxApiEndpointRequestHandler + Send + Sync> {
+        Arc::new(GuardedHandler {
+            handler,
+            guard: Arc::clone(&self.0),
+        })
+    }
+}
+
+struct GuardedHandler<H: ?Sized> {
+    handler: Arc<H>,
+    guard: Arc<Guard>,
+}
+
+impl<H: BoxRequestHandler + Send + Sync + ?Sized> BoxRequestHandler for GuardedHandler<H> {
+    fn handle(
+        &self,
+        mut request: Request,
+    ) -> std::pin::Pin<Box<dyn Future<Output = crate::Result<Response>> + Send + '_>> {
+        Box::pin(async move {
+            if let Some(response) = self.guard.check(&mut request).await? {
+                return Ok(response);
+            }
+
+            self.handler.handle(request).await
+        })
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<H: crate::openapi::BoxApiEndpointRequestHandler + Send + Sync + ?Sized>
+    crate::openapi::AsApiRoute for GuardedHandler<H>
+{
+    fn as_api_route(
+        &self,
+        route_context: &crate::openapi::RouteContext<'_>,
+        schema_generator: &mut schemars::SchemaGenerator,
+    ) -> aide::openapi::PathItem {
+        self.handler.as_api_route(route_context, schema_generator)
+    }
+}
+
+#[cfg(feature = "openapi")]
+impl<H: crate::openapi::BoxApiEndpointRequestHandler + Send + Sync + ?Sized>
+    crate::openapi::BoxApiEndpointRequestHandler for GuardedHandler<H>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use async_trait::async_trait;
+    use cot_core::StatusCode;
+
+    use super::*;
+    use crate::auth::{AuthBackend, UserId};
+    use crate::html::Html;
+    use crate::router::Route;
+    use crate::test::TestRequestBuilder;
+
+    #[derive(Debug, Clone)]
+    struct TestUser {
+        permissions: Vec<&'static str>,
+    }
+
+    impl User for TestUser {
+        fn id(&self) -> Option<UserId> {
+            Some(UserId::Int(1))
+        }
+
+        fn username(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::from("testuser"))
+        }
+
+        fn is_active(&self) -> bool {
+            true
+        }
+
+        fn is_authenticated(&self) -> bool {
+            true
+        }
+
+        fn has_perm(&self, perm: &str) -> bool {
+            self.permissions.contains(&perm)
+        }
+    }
+
+    struct TestAuthBackend;
+
+    #[async_trait]
+    impl AuthBackend for TestAuthBackend {
+        async fn authenticate(
+            &self,
+            _credentials: &(dyn std::any::Any + Send + Sync),
+        ) -> crate::auth::Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+
+        async fn get_by_id(
+            &self,
+            _id: UserId,
+        ) -> crate::auth::Result<Option<Box<dyn User + Send + Sync>>> {
+            Ok(None)
+        }
+    }
+
+    async fn index() -> Html {
+        Html::new("index")
+    }
+
+    fn test_router() -> Router {
+        Router::with_urls([
+            Route::with_handler_and_name("/login/", index, "login"),
+            Route::with_handler_and_name("/", index, "index"),
+        ])
+    }
+
+    async fn test_request(url: &str, user: Option<TestUser>, accept_html: bool) -> Request {
+        let mut request = TestRequestBuilder::get(url)
+            .with_session()
+            .auth_backend(TestAuthBackend)
+            .router(test_router())
+            .build();
+        if accept_html {
+            request.headers_mut().insert(
+                http::header::ACCEPT,
+                http::HeaderValue::from_static("text/html"),
+            );
+        }
+
+        let auth = Auth::from_request(&mut request).await.unwrap();
+        request.extensions_mut().insert(auth.clone());
+        if let Some(user) = user {
+            auth.login(Box::new(user)).await.unwrap();
+        }
+
+        request
+    }
+
+    #[cot::test]
+    async fn login_required_redirects_browsers() {
+        let request = test_request("/secret/?page=2", None, true).await;
+        let handler = LoginRequired::new(index).login_route("login");
+
+        let response = handler.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(
+            response.headers().get(http::header::LOCATION).unwrap(),
+            "/login/?next=%2Fsecret%2F%3Fpage%3D2"
+        );
+    }
+
+    #[test]
+    fn next_url_local_paths_only() {
+        let next = |url: &str| next_url(&TestRequestBuilder::get(url).build());
+
+        assert_eq!(
+            next("/login/?next=%2Fsecret%2F%3Fpage%3D2").as_deref(),
+            Some("/secret/?page=2")
+        );
+        assert_eq!(next("/login/?next=/").as_deref(), Some("/"));
+        assert_eq!(next("/login/"), None);
+        assert_eq!(next("/login/?next="), None);
+        assert_eq!(next("/login/?next=https%3A%2F%2Fexample.com%2F"), None);
+        assert_eq!(next("/login/?next=%2F%2Fexample.com%2F"), None);
+        assert_eq!(next("/login/?next=%2F%5Cexample.com%2F"), None);
+        assert_eq!(next("/login/?next=%2F%09%2Fexample.com%2F"), None);
+        assert_eq!(next("/login/?next=secret%2F"), None);
+    }
+
+    #[cot::test]
+    async fn login_required_unauthorized_for_api() {
+        let request = test_request("/secret/", None, false).await;
+        let handler = LoginRequired::new(index).login_route("login");
+
+        let error = handler.handle(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[cot::test]
+    async fn login_required_unauthorized_without_login_route() {
+        let request = test_request("/secret/", None, true).await;
+        let handler = LoginRequired::new(index);
+
+        let error = handler.handle(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[cot::test]
+    async fn login_required_authenticated() {
+        let user = TestUser {
+            permissions: vec![],
+        };
+        let request = test_request("/secret/", Some(user), true).await;
+        let handler = LoginRequired::new(index).login_route("login");
+
+        let response = handler.handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn login_required_permission() {
+        let user = TestUser {
+            permissions: vec!["article.change"],
+        };
+        let handler = LoginRequired::new(index)
+            .permission("article.change")
+            .permission("article.publish");
+        let request = test_request("/secret/", Some(user.clone()), true).await;
+        let error = handler.handle(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+
+        let handler = LoginRequired::new(index).permission("article.change");
+        let request = test_request("/secret/", Some(user), true).await;
+        let response = handler.handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn login_required_router() {
+        let nested = Router::with_urls([Route::with_handler("/secret/", index)]);
+        let router = Router::with_urls([
+            Route::with_handler("/public/", index),
+            Route::with_router(
+                "/private",
+                LoginRequired::new(Router::with_urls([Route::with_router("/nested", nested)]))
+                    .into_router(),
+            ),
+        ]);
+
+        let request = test_request("/public/", None, false).await;
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = test_request("/private/nested/secret/", None, false).await;
+        let error = router.handle(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+
+        let user = TestUser {
+            permissions: vec![],
+        };
+        let request = test_request("/private/nested/secret/", Some(user), false).await;
+        let response = router.handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn require_user() {
+        let mut request = test_request("/", None, false).await;
+        let error = request
+            .extract_from_head::<RequireUser>()
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+
+        let user = TestUser {
+            permissions: vec![],
+        };
+        let mut request = test_request("/", Some(user), false).await;
+        let RequireUser(user) = request.extract_from_head::<RequireUser>().await.unwrap();
+        assert_eq!(user.username(), Some(Cow::from("testuser")));
+    }
+}
@@ -0,0 +1,442 @@
+//! OAuth 2.0 and OpenID Connect login.
+//!
+//! This module provides [`OAuthIdentity`], a model linking the accounts of
+//! external identity providers (such as Google, GitHub or a company's single
+//! sign-on) to [`DatabaseUser`]s.
+//!
+//! With the `oauth` feature enabled, this module also provides:
+//!
+//! * [`OAuthClient`], which implements the authorization code flow with [PKCE](https://www.rfc-editor.org/rfc/rfc7636)
+//!   against a provider configured in
+//!   [`OAuthConfig`](crate::config::OAuthConfig): building the authorization
+//!   URL, exchanging the code for tokens, validating the ID token and fetching
+//!   the user information,
+//! * [`OAuthApp`], which contains ready-made views implementing the entire
+//!   login flow and logging the users in with [`Auth::login`],
+//! * [`OAuthUserLinker`], which decides which user an external identity belongs
+//!   to. The default implementation, [`DatabaseUserLinker`], uses the
+//!   [`OAuthIdentity`] model, but custom implementations can link the
+//!   identities to any other [`User`](crate::auth::User).
+//!
+//! The login flow can be tested without a real identity provider using
+//! [`MockOAuthProvider`](crate::test::MockOAuthProvider).
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::db::DatabaseUser;
+//! use cot::auth::oauth::OAuthIdentity;
+//! use cot::db::Database;
+//!
+//! async fn linked_providers(db: &Database, user: &DatabaseUser) -> cot::Result<Vec<String>> {
+//!     let identities = OAuthIdentity::list_for_user(db, user).await?;
+//!     Ok(identities
+//!         .iter()
+//!         .map(|identity| identity.provider().to_owned())
+//!         .collect())
+//! }
+//! ```
+//!
+//! [`Auth::login`]: crate::auth::Auth::login
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, FixedOffset, Utc};
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+use cot::db::Auto;
+use thiserror::Error;
+
+use crate::auth::db::DatabaseUser;
+use crate::auth::{AuthError, Result};
+use crate::db::{DatabaseBackend, DatabaseError, ForeignKey, LimitedString, Model, model, query};
+
+pub(crate) const MAX_OAUTH_PROVIDER_LENGTH: u32 = 100;
+pub(crate) const MAX_OAUTH_SUBJECT_LENGTH: u32 = 255;
+
+/// An error that occurs when linking an external identity to a user.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum OAuthIdentityError {
+    /// The external identity is already linked to a user.
+    #[error("the `{provider}` identity is already linked to a user")]
+    AlreadyLinked {
+        /// The name of the provider.
+        provider: String,
+    },
+    /// The name of the provider is too long.
+    #[error(
+        "OAuth provider name is too long (max {MAX_OAUTH_PROVIDER_LENGTH} characters, got {0})"
+    )]
+    ProviderTooLong(usize),
+    /// The subject (the user identifier at the provider) is too long.
+    #[error("OAuth subject is too long (max {MAX_OAUTH_SUBJECT_LENGTH} characters, got {0})")]
+    SubjectTooLong(usize),
+}
+
+/// An external identity linked to a [`DatabaseUser`].
+///
+/// The identity is identified by the name of the provider (as configured in
+/// [`OAuthConfig`](crate::config::OAuthConfig)) and the subject, which is the
+/// unique, never reassigned identifier of the user at that provider (the
+/// `sub` claim in OpenID Connect). A user can have identities from many
+/// providers, but each identity belongs to a single user.
+#[derive(Debug, Clone)]
+#[model(table_name = "oauth_identity")]
+pub struct OAuthIdentity {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    provider: LimitedString<MAX_OAUTH_PROVIDER_LENGTH>,
+    subject: LimitedString<MAX_OAUTH_SUBJECT_LENGTH>,
+    created_at: DateTime<FixedOffset>,
+}
+
+impl OAuthIdentity {
+    /// Links the external identity to the user and saves it to the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the identity is already linked to a user, if the
+    /// provider name or the subject are too long, or if there was an error
+    /// querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::oauth::OAuthIdentity;
+    /// use cot::db::Database;
+    ///
+    /// async fn link_github(db: &Database, user: &DatabaseUser, id: &str) -> cot::Result<()> {
+    ///     OAuthIdentity::link(db, user, "github", id).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn link<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Self> {
+        let (provider, subject) = identity_key(provider, subject)?;
+
+        let mut identity = Self {
+            id: Auto::auto(),
+            user: ForeignKey::from(user),
+            provider,
+            subject,
+            created_at: Utc::now().fixed_offset(),
+        };
+        // the identity is unique in the database, so linking it can't race with
+        // linking it to another user
+        match identity.insert(db).await {
+            Ok(()) => Ok(identity),
+            Err(DatabaseError::UniqueViolation) => Err(AuthError::backend_error(
+                OAuthIdentityError::AlreadyLinked {
+                    provider: identity.provider.to_string(),
+                },
+            )),
+            Err(error) => Err(AuthError::backend_error(error)),
+        }
+    }
+
+    /// Returns the identity with given provider name and subject, if it's
+    /// linked to any user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthIdentity;
+    /// use cot::db::Database;
+    ///
+    /// async fn is_linked(db: &Database, id: &str) -> cot::Result<bool> {
+    ///     Ok(OAuthIdentity::get(db, "github", id).await?.is_some())
+    /// }
+    /// ```
+    pub async fn get<DB: DatabaseBackend>(
+        db: &DB,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<Self>> {
+        let Ok((provider, subject)) = identity_key(provider, subject) else {
+            // such an identity could have never been linked
+            return Ok(None);
+        };
+
+        query!(OAuthIdentity, $provider == provider && $subject == subject)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Returns all the identities linked to the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::oauth::OAuthIdentity;
+    /// use cot::db::Database;
+    ///
+    /// async fn identity_count(db: &Database, user: &DatabaseUser) -> cot::Result<usize> {
+    ///     Ok(OAuthIdentity::list_for_user(db, user).await?.len())
+    /// }
+    /// ```
+    pub async fn list_for_user<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+    ) -> Result<Vec<Self>> {
+        let user = ForeignKey::from(user);
+        query!(OAuthIdentity, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Removes the user's identity from given provider, so that it can no
+    /// longer be used to log in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::oauth::OAuthIdentity;
+    /// use cot::db::Database;
+    ///
+    /// async fn unlink_github(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     OAuthIdentity::unlink(db, user, "github").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unlink<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+        provider: &str,
+    ) -> Result<()> {
+        let Ok(provider) = LimitedString::<MAX_OAUTH_PROVIDER_LENGTH>::new(provider) else {
+            return Ok(());
+        };
+        let user = ForeignKey::from(user);
+        query!(OAuthIdentity, $user == user && $provider == provider)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Returns the user the identity is linked to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::oauth::OAuthIdentity;
+    /// use cot::db::Database;
+    ///
+    /// async fn github_user(db: &Database, id: &str) -> cot::Result<Option<DatabaseUser>> {
+    ///     match OAuthIdentity::get(db, "github", id).await? {
+    ///         Some(identity) => Ok(identity.user(db).await?),
+    ///         None => Ok(None),
+    ///     }
+    /// }
+    /// ```
+    pub async fn user<DB: DatabaseBackend>(&self, db: &DB) -> Result<Option<DatabaseUser>> {
+        DatabaseUser::get_by_id(db, self.user_id()).await
+    }
+
+    /// Returns the ID of the identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the identity has not been saved to the database.
+    #[must_use]
+    pub fn id(&self) -> i64 {
+        match self.id {
+            Auto::Fixed(id) => id,
+            Auto::Auto => unreachable!("OAuthIdentity constructed with an unknown ID"),
+        }
+    }
+
+    /// Returns the ID of the user the identity is linked to.
+    #[must_use]
+    pub fn user_id(&self) -> i64 {
+        match self.user.primary_key() {
+            Auto::Fixed(id) => *id,
+            Auto::Auto => unreachable!("OAuthIdentity constructed with an unknown user ID"),
+        }
+    }
+
+    /// Returns the name of the provider.
+    #[must_use]
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    /// Returns the identifier of the user at the provider.
+    #[must_use]
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the time the identity was linked at.
+    #[must_use]
+    pub fn created_at(&self) -> DateTime<FixedOffset> {
+        self.created_at
+    }
+}
+
+impl Display for OAuthIdentity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} identity {}", self.provider, self.subject)
+    }
+}
+
+fn identity_key(
+    provider: &str,
+    subject: &str,
+) -> Result<(
+    LimitedString<MAX_OAUTH_PROVIDER_LENGTH>,
+    LimitedString<MAX_OAUTH_SUBJECT_LENGTH>,
+)> {
+    let provider_length = provider.len();
+    let provider = LimitedString::new(provider).map_err(|_| {
+        AuthError::backend_error(OAuthIdentityError::ProviderTooLong(provider_length))
+    })?;
+    let subject_length = subject.len();
+    let subject = LimitedString::new(subject).map_err(|_| {
+        AuthError::backend_error(OAuthIdentityError::SubjectTooLong(subject_length))
+    })?;
+
+    Ok((provider, subject))
+}
+
+#[cfg(feature = "oauth")]
+mod app;
+#[cfg(feature = "oauth")]
+mod client;
+#[cfg(feature = "oauth")]
+pub use app::{DatabaseUserLinker, OAuthApp, OAuthUserLinker};
+#[cfg(feature = "oauth")]
+pub(crate) use client::code_challenge;
+#[cfg(feature = "oauth")]
+pub use client::{
+    AuthorizationRequest, OAuthClient, OAuthConfigError, OAuthError, OAuthTokenResponse,
+    OAuthUserInfo,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestDatabase;
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_identity_link() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+
+        let identity = OAuthIdentity::link(&db.database(), &user, "github", "12345")
+            .await
+            .unwrap();
+        assert_eq!(identity.user_id(), user.id());
+        assert_eq!(identity.provider(), "github");
+        assert_eq!(identity.subject(), "12345");
+
+        let found = OAuthIdentity::get(&db.database(), "github", "12345")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id(), identity.id());
+        assert_eq!(
+            found.user(&db.database()).await.unwrap().unwrap().id(),
+            user.id()
+        );
+        assert!(
+            OAuthIdentity::get(&db.database(), "google", "12345")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // an identity can only be linked once
+        let other = DatabaseUser::create_user(&db.database(), "other", "password123")
+            .await
+            .unwrap();
+        let result = OAuthIdentity::link(&db.database(), &other, "github", "12345").await;
+        let Err(AuthError::UserBackend(error)) = result else {
+            panic!("expected the identity to be already linked, got {result:?}");
+        };
+        assert!(matches!(
+            error.downcast_ref::<OAuthIdentityError>(),
+            Some(OAuthIdentityError::AlreadyLinked { .. })
+        ));
+
+        OAuthIdentity::link(&db.database(), &user, "google", "abc")
+            .await
+            .unwrap();
+        let identities = OAuthIdentity::list_for_user(&db.database(), &user)
+            .await
+            .unwrap();
+        assert_eq!(identities.len(), 2);
+
+        OAuthIdentity::unlink(&db.database(), &user, "github")
+            .await
+            .unwrap();
+        assert!(
+            OAuthIdentity::get(&db.database(), "github", "12345")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_identity_too_long() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        let subject = "a".repeat(MAX_OAUTH_SUBJECT_LENGTH as usize + 1);
+
+        assert!(
+            OAuthIdentity::link(&db.database(), &user, "github", &subject)
+                .await
+                .is_err()
+        );
+        assert!(
+            OAuthIdentity::get(&db.database(), "github", &subject)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,750 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_more::with_trait::Debug;
+use serde::Deserialize;
+
+use crate::auth::db::{DatabaseUser, MAX_USERNAME_LENGTH};
+use crate::auth::oauth::{
+    AuthorizationRequest, OAuthClient, OAuthConfigError, OAuthError, OAuthIdentity, OAuthUserInfo,
+};
+use crate::auth::{Auth, User};
+use crate::common_types::Email;
+use crate::db::{Database, Model};
+use crate::error::NotFound;
+use crate::request::extractors::{Path, UrlQuery};
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Redirect, Response};
+use crate::router::{Route, Router, Urls};
+use crate::session::Session;
+use crate::{App, Error, reverse};
+
+const AUTHORIZATION_REQUEST_SESSION_KEY: &str = "__cot_oauth_authorization_request";
+
+/// Decides which user an external identity belongs to.
+///
+/// This is used by the [`OAuthApp`] once the user has been authenticated by
+/// the identity provider. The default implementation, [`DatabaseUserLinker`],
+/// links the identities to [`DatabaseUser`]s; implement this trait to link
+/// them to a custom [`User`] type instead. Note that the user returned has to
+/// be retrievable by the project's [`AuthBackend`](crate::auth::AuthBackend),
+/// since it's logged in with [`Auth::login`].
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use cot::auth::oauth::{OAuthUserInfo, OAuthUserLinker};
+/// use cot::auth::{Auth, User};
+/// use cot::db::Database;
+///
+/// /// Only lets in the users with a verified company email address.
+/// #[derive(Debug)]
+/// struct CompanyLinker;
+///
+/// #[async_trait]
+/// impl OAuthUserLinker for CompanyLinker {
+///     async fn resolve_user(
+///         &self,
+///         database: &Database,
+///         auth: &Auth,
+///         user_info: &OAuthUserInfo,
+///     ) -> cot::Result<Option<Box<dyn User + Send + Sync>>> {
+///         let company_email = user_info.email_verified
+///             && user_info
+///                 .email
+///                 .as_deref()
+///                 .is_some_and(|email| email.ends_with("@example.com"));
+///         if !company_email {
+///             return Ok(None);
+///         }
+///
+///         // look the user up in the database...
+/// #       let _ = (database, auth);
+///         Ok(None)
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait OAuthUserLinker: Send + Sync {
+    /// Returns the user the external identity belongs to, or `None` if the
+    /// user is not allowed to log in.
+    ///
+    /// `auth` can be used to check whether a user is already logged in, in
+    /// which case the identity is usually linked to their account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user could not be retrieved or created.
+    async fn resolve_user(
+        &self,
+        database: &Database,
+        auth: &Auth,
+        user_info: &OAuthUserInfo,
+    ) -> crate::Result<Option<Box<dyn User + Send + Sync>>>;
+}
+
+/// An [`OAuthUserLinker`] linking the external identities to
+/// [`DatabaseUser`]s using the [`OAuthIdentity`] model.
+///
+/// The user is resolved as follows:
+///
+/// 1. if the identity is already linked to a user, that user is logged in,
+///    provided they are [active](User::is_active),
+/// 2. otherwise, if a [`DatabaseUser`] is currently logged in, the identity is
+///    linked to their account,
+/// 3. otherwise, a new user is created (unless disabled with
+///    [`create_users`](Self::create_users)) and the identity is linked to it.
+///    The new user has a random password and the username preferred by the user
+///    at the provider (made unique if needed). The email address is only copied
+///    over if the provider has verified it.
+///
+/// Existing users are never matched by their email address, since that would
+/// let anyone who can register the address at the provider take over the
+/// account.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::oauth::{DatabaseUserLinker, OAuthApp};
+///
+/// let app = OAuthApp::new("https://example.com")
+///     .user_linker(DatabaseUserLinker::new().create_users(false));
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct DatabaseUserLinker {
+    create_users: bool,
+}
+
+impl DatabaseUserLinker {
+    /// Creates a new linker that creates the users who log in for the first
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::DatabaseUserLinker;
+    ///
+    /// let linker = DatabaseUserLinker::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self { create_users: true }
+    }
+
+    /// Sets whether new users are created for the identities not linked to
+    /// any user yet. If disabled, only the existing users can link their
+    /// identities (while logged in), and then use them to log in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::DatabaseUserLinker;
+    ///
+    /// let linker = DatabaseUserLinker::new().create_users(false);
+    /// ```
+    #[must_use]
+    pub fn create_users(mut self, create_users: bool) -> Self {
+        self.create_users = create_users;
+        self
+    }
+
+    async fn create_user(
+        database: &Database,
+        user_info: &OAuthUserInfo,
+    ) -> crate::Result<DatabaseUser> {
+        let base_username = user_info
+            .preferred_username
+            .clone()
+            .or_else(|| {
+                user_info
+                    .email
+                    .as_deref()
+                    .and_then(|email| email.split('@').next())
+                    .map(ToOwned::to_owned)
+            })
+            .filter(|username| !username.is_empty())
+            .unwrap_or_else(|| format!("{}_{}", user_info.provider, user_info.subject));
+        // leave some room for the suffix making the username unique
+        let base_username: String = base_username
+            .chars()
+            .take(MAX_USERNAME_LENGTH as usize - 9)
+            .collect();
+
+        let mut username = base_username.clone();
+        while DatabaseUser::get_by_username(database, &username)
+            .await?
+            .is_some()
+        {
+            username = format!(
+                "{base_username}_{}",
+                crate::utils::random::random_token::<4>()
+            );
+        }
+
+        let mut user = DatabaseUser::create_user(
+            database,
+            username,
+            crate::utils::random::random_token::<32>(),
+        )
+        .await?;
+        if user_info.email_verified
+            && let Some(email) = user_info
+                .email
+                .as_deref()
+                .and_then(|email| Email::try_from(email).ok())
+        {
+            user.set_email(Some(email));
+            user.set_email_verified(true);
+            user.save(database).await?;
+        }
+
+        Ok(user)
+    }
+}
+
+impl Default for DatabaseUserLinker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OAuthUserLinker for DatabaseUserLinker {
+    async fn resolve_user(
+        &self,
+        database: &Database,
+        auth: &Auth,
+        user_info: &OAuthUserInfo,
+    ) -> crate::Result<Option<Box<dyn User + Send + Sync>>> {
+        if let Some(identity) =
+            OAuthIdentity::get(database, &user_info.provider, &user_info.subject).await?
+        {
+            let Some(user) = identity.user(database).await?.filter(User::is_active) else {
+                return Ok(None);
+            };
+            return Ok(Some(Box::new(user)));
+        }
+
+        let current_user_id = auth.user().id().and_then(|id| id.as_int());
+        let current_user = match current_user_id {
+            Some(id) => DatabaseUser::get_by_id(database, id).await?,
+            None => None,
+        };
+        let user = match current_user {
+            Some(user) => user,
+            None if self.create_users => Self::create_user(database, user_info).await?,
+            None => return Ok(None),
+        };
+        OAuthIdentity::link(database, &user, &user_info.provider, &user_info.subject).await?;
+
+        Ok(Some(Box::new(user)))
+    }
+}
+
+/// An app that implements logging in with OAuth 2.0 / OpenID Connect
+/// identity providers.
+///
+/// The providers are configured in
+/// [`ProjectConfig::oauth`](crate::config::ProjectConfig::oauth). The app
+/// provides the following views for each of them:
+///
+/// * `oauth_login` (`/{provider}/login/`) – redirects the user to the
+///   provider's authorization endpoint. The pending [`AuthorizationRequest`] is
+///   stored in the session.
+/// * `oauth_callback` (`/{provider}/callback/`) – the redirect URI that has to
+///   be registered at the provider. It checks the `state` parameter, exchanges
+///   the authorization code for tokens with the PKCE code verifier, validates
+///   the ID token and fetches the user information. The user is then resolved
+///   with the [`OAuthUserLinker`] (by default, [`DatabaseUserLinker`]), logged
+///   in with [`Auth::login`], and redirected to the [login redirect
+///   URL](Self::login_redirect_url).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::oauth::OAuthApp;
+/// use cot::project::RegisterAppsContext;
+/// use cot::{AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register_with_views(OAuthApp::new("https://example.com"), "/oauth");
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OAuthApp {
+    site_url: String,
+    login_redirect_url: String,
+    #[debug("..")]
+    user_linker: Arc<dyn OAuthUserLinker>,
+}
+
+impl OAuthApp {
+    /// Creates a new OAuth login app.
+    ///
+    /// `site_url` is the URL of the website (such as `https://example.com`)
+    /// that is used to create the absolute redirect URI sent to the
+    /// providers. It is not derived from the request, since the `Host` header
+    /// can be forged by an attacker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthApp;
+    ///
+    /// let app = OAuthApp::new("https://example.com");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(site_url: T) -> Self {
+        Self {
+            site_url: site_url.into().trim_end_matches('/').to_owned(),
+            login_redirect_url: "/".to_owned(),
+            user_linker: Arc::new(DatabaseUserLinker::new()),
+        }
+    }
+
+    /// Sets the URL the users are redirected to after logging in. The
+    /// default is `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthApp;
+    ///
+    /// let app = OAuthApp::new("https://example.com").login_redirect_url("/dashboard/");
+    /// ```
+    #[must_use]
+    pub fn login_redirect_url<T: Into<String>>(mut self, login_redirect_url: T) -> Self {
+        self.login_redirect_url = login_redirect_url.into();
+        self
+    }
+
+    /// Sets the [`OAuthUserLinker`] used to decide which user an external
+    /// identity belongs to. The default is [`DatabaseUserLinker`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::{DatabaseUserLinker, OAuthApp};
+    ///
+    /// let app = OAuthApp::new("https://example.com")
+    ///     .user_linker(DatabaseUserLinker::new().create_users(false));
+    /// ```
+    #[must_use]
+    pub fn user_linker<T: OAuthUserLinker + 'static>(mut self, user_linker: T) -> Self {
+        self.user_linker = Arc::new(user_linker);
+        self
+    }
+
+    fn client(request: &Request, provider: &str) -> crate::Result<OAuthClient> {
+        match OAuthClient::from_config(request.project_config(), provider) {
+            Ok(client) => Ok(client),
+            Err(OAuthConfigError::UnknownProvider(_)) => Err(Error::from(NotFound::with_message(
+                format!("unknown OAuth provider `{provider}`"),
+            ))),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl App for OAuthApp {
+    fn name(&self) -> &'static str {
+        "cot_oauth"
+    }
+
+    fn router(&self) -> Router {
+        let app = Arc::new(self.clone());
+        let callback_app = Arc::clone(&app);
+
+        Router::with_urls([
+            Route::with_handler_and_name(
+                "/{provider}/login/",
+                move |urls: Urls, session: Session, path: Path<String>, request: Request| {
+                    oauth_login(Arc::clone(&app), urls, session, path, request)
+                },
+                "oauth_login",
+            ),
+            Route::with_handler_and_name(
+                "/{provider}/callback/",
+                move |session: Session,
+                      auth: Auth,
+                      database: Database,
+                      path: Path<String>,
+                      query: UrlQuery<CallbackQuery>,
+                      request: Request| {
+                    oauth_callback(
+                        Arc::clone(&callback_app),
+                        session,
+                        auth,
+                        database,
+                        path,
+                        query,
+                        request,
+                    )
+                },
+                "oauth_callback",
+            ),
+        ])
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+async fn oauth_login(
+    app: Arc<OAuthApp>,
+    urls: Urls,
+    session: Session,
+    Path(provider): Path<String>,
+    request: Request,
+) -> crate::Result<Response> {
+    let client = OAuthApp::client(&request, &provider)?;
+
+    let callback_path = reverse!(urls, "oauth_callback", provider = provider)?;
+    let redirect_uri = format!("{}{callback_path}", app.site_url);
+    let authorization_request = client.authorization_request(&redirect_uri)?;
+    session
+        .insert(AUTHORIZATION_REQUEST_SESSION_KEY, &authorization_request)
+        .await?;
+
+    Redirect::new(authorization_request.url).into_response()
+}
+
+async fn oauth_callback(
+    app: Arc<OAuthApp>,
+    session: Session,
+    auth: Auth,
+    database: Database,
+    Path(provider): Path<String>,
+    UrlQuery(query): UrlQuery<CallbackQuery>,
+    request: Request,
+) -> crate::Result<Response> {
+    // the authorization request is removed right away, so that it can only be
+    // used once
+    let authorization_request = session
+        .remove::<AuthorizationRequest>(AUTHORIZATION_REQUEST_SESSION_KEY)
+        .await?
+        .filter(|authorization_request| {
+            authorization_request.provider == provider
+                && query
+                    .state
+                    .as_deref()
+                    .is_some_and(|state| authorization_request.verify_state(state))
+        })
+        .ok_or(OAuthError::InvalidState)?;
+    if let Some(error) = query.error {
+        return Err(OAuthError::Provider {
+            error,
+            description: query.error_description,
+        }
+        .into());
+    }
+    let code = query.code.ok_or(OAuthError::MissingCode)?;
+
+    let client = OAuthApp::client(&request, &provider)?;
+    let user_info = client.authenticate(&code, &authorization_request).await?;
+    let user = app
+        .user_linker
+        .resolve_user(&database, &auth, &user_info)
+        .await?
+        .ok_or(OAuthError::UserNotAllowed)?;
+    auth.login(user).await?;
+
+    Redirect::new(app.login_redirect_url.clone()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use cot_core::StatusCode;
+
+    use super::*;
+    use crate::auth::db::DatabaseUserCredentials;
+    use crate::common_types::Password;
+    use crate::config::{OAuthConfig, ProjectConfig};
+    use crate::test::{MockOAuthProvider, MockOAuthUser, TestDatabase, TestRequestBuilder};
+
+    fn test_config(provider: &MockOAuthProvider) -> ProjectConfig {
+        ProjectConfig::builder()
+            .oauth(
+                OAuthConfig::builder()
+                    .provider("mock", provider.provider_config())
+                    .build(),
+            )
+            .build()
+    }
+
+    fn build_request(
+        url: &str,
+        config: &ProjectConfig,
+        db: &TestDatabase,
+        session: &Session,
+        auth: &Auth,
+    ) -> Request {
+        let mut request = TestRequestBuilder::get(url)
+            .config(config.clone())
+            .router(test_app().router())
+            .database(db.database())
+            .session(session.clone())
+            .build();
+        request.extensions_mut().insert(auth.clone());
+        request
+    }
+
+    fn test_app() -> OAuthApp {
+        OAuthApp::new("https://example.com/").login_redirect_url("/welcome/")
+    }
+
+    fn location(response: &Response) -> String {
+        response.headers()[http::header::LOCATION]
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    /// Runs the whole login flow and returns the final response.
+    async fn log_in(
+        provider: &MockOAuthProvider,
+        db: &TestDatabase,
+        session: &Session,
+        auth: &Auth,
+    ) -> crate::Result<Response> {
+        let config = test_config(provider);
+
+        let request = build_request("/mock/login/", &config, db, session, auth);
+        let response = test_app().router().handle(request).await?;
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let authorize_url = location(&response);
+        assert!(authorize_url.starts_with(&provider.url()));
+
+        let callback_url = provider.authorize(&authorize_url);
+        let callback_url = url::Url::parse(&callback_url).unwrap();
+        assert_eq!(callback_url.path(), "/mock/callback/");
+        let path = format!("{}?{}", callback_url.path(), callback_url.query().unwrap());
+
+        let request = build_request(&path, &config, db, session, auth);
+        test_app().router().handle(request).await
+    }
+
+    async fn test_auth(db: &TestDatabase) -> (Session, Auth) {
+        let request = TestRequestBuilder::get("/")
+            .with_db_auth(db.database())
+            .await
+            .build();
+        let session = Session::from_request(&request).clone();
+        let auth = request.extensions().get::<Auth>().unwrap().clone();
+        (session, auth)
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_login_creates_user() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let provider = MockOAuthProvider::start(
+            MockOAuthUser::new("user-1")
+                .email("alice@example.com")
+                .preferred_username("alice"),
+        )
+        .await;
+        let (session, auth) = test_auth(&db).await;
+
+        let response = log_in(&provider, &db, &session, &auth).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(location(&response), "/welcome/");
+        assert_eq!(auth.user().username().as_deref(), Some("alice"));
+        let user = DatabaseUser::get_by_username(&db.database(), "alice")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.email().map(Email::as_str), Some("alice@example.com"));
+        let identity = OAuthIdentity::get(&db.database(), "mock", "user-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.user_id(), user.id());
+
+        // logging in again uses the same user
+        auth.logout().await.unwrap();
+        log_in(&provider, &db, &session, &auth).await.unwrap();
+        assert_eq!(auth.user().id().and_then(|id| id.as_int()), Some(user.id()));
+
+        provider.close().await;
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_login_links_current_user() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let provider = MockOAuthProvider::start(
+            MockOAuthUser::new("user-1").preferred_username("someone-else"),
+        )
+        .await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        let (session, auth) = test_auth(&db).await;
+        auth.login(Box::new(user.clone())).await.unwrap();
+
+        log_in(&provider, &db, &session, &auth).await.unwrap();
+
+        let identity = OAuthIdentity::get(&db.database(), "mock", "user-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(identity.user_id(), user.id());
+        assert!(
+            DatabaseUser::get_by_username(&db.database(), "someone-else")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // the password still works
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_owned(), Password::new("password123"));
+        assert!(
+            DatabaseUser::authenticate(&db.database(), &credentials)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        provider.close().await;
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_login_unique_username() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let provider =
+            MockOAuthProvider::start(MockOAuthUser::new("user-1").preferred_username("alice"))
+                .await;
+        DatabaseUser::create_user(&db.database(), "alice", "password123")
+            .await
+            .unwrap();
+        let (session, auth) = test_auth(&db).await;
+
+        log_in(&provider, &db, &session, &auth).await.unwrap();
+
+        let username = auth.user().username().unwrap().into_owned();
+        assert!(username.starts_with("alice_"));
+
+        provider.close().await;
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_login_without_creating_users() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+        let (session, auth) = test_auth(&db).await;
+        let config = test_config(&provider);
+        let app = test_app().user_linker(DatabaseUserLinker::new().create_users(false));
+
+        let request = build_request("/mock/login/", &config, &db, &session, &auth);
+        let response = app.router().handle(request).await.unwrap();
+        let callback_url = provider.authorize(&location(&response));
+        let callback_url = url::Url::parse(&callback_url).unwrap();
+        let path = format!("{}?{}", callback_url.path(), callback_url.query().unwrap());
+        let request = build_request(&path, &config, &db, &session, &auth);
+        let error = app.router().handle(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert!(!auth.user().is_authenticated());
+
+        provider.close().await;
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_callback_invalid_state() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+        let (session, auth) = test_auth(&db).await;
+        let config = test_config(&provider);
+
+        let request = build_request("/mock/login/", &config, &db, &session, &auth);
+        let response = test_app().router().handle(request).await.unwrap();
+        let callback_url = provider.authorize(&location(&response));
+        let callback_url = url::Url::parse(&callback_url).unwrap();
+        let code = callback_url
+            .query_pairs()
+            .find(|(key, _)| key == "code")
+            .unwrap()
+            .1
+            .into_owned();
+
+        let request = build_request(
+            &format!("/mock/callback/?code={code}&state=forged"),
+            &config,
+            &db,
+            &session,
+            &auth,
+        );
+        let error = test_app().router().handle(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+
+        // the pending request is gone, so the valid callback doesn't work anymore
+        // either
+        let path = format!("{}?{}", callback_url.path(), callback_url.query().unwrap());
+        let request = build_request(&path, &config, &db, &session, &auth);
+        let error = test_app().router().handle(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert!(!auth.user().is_authenticated());
+
+        provider.close().await;
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn oauth_login_unknown_provider() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let (session, auth) = test_auth(&db).await;
+
+        let request = build_request(
+            "/unknown/login/",
+            &ProjectConfig::default(),
+            &db,
+            &session,
+            &auth,
+        );
+        let error = test_app().router().handle(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    }
+}
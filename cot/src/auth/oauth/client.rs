@@ -0,0 +1,1037 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use cot_core::error::impl_into_cot_error;
+use derive_more::with_trait::Debug;
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+use crate::config::{JwtAlgorithm, OAuthProviderConfig, ProjectConfig};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An error that occurs during the OAuth login flow.
+///
+/// These errors are caused either by the user (for instance, by denying the
+/// access at the provider, or by reusing an old callback URL), or by the
+/// provider returning an unexpected response.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OAuthError {
+    /// The `state` parameter of the callback doesn't match the one of the
+    /// pending authorization request, or there is no pending request.
+    #[error("the OAuth state is invalid or the login has expired")]
+    InvalidState,
+    /// The callback does not contain the authorization code.
+    #[error("the OAuth callback does not contain an authorization code")]
+    MissingCode,
+    /// The provider returned an error, either in the callback or in the
+    /// token endpoint response.
+    #[error("the OAuth provider returned an error: {error}")]
+    Provider {
+        /// The error code, such as `access_denied` or `invalid_grant`.
+        error: String,
+        /// The human-readable description of the error, if provided.
+        description: Option<String>,
+    },
+    /// The request to the provider failed.
+    #[error("the request to the OAuth provider failed: {0}")]
+    Http(#[source] reqwest::Error),
+    /// The provider returned a response that could not be understood.
+    #[error("the OAuth provider returned an invalid response: {0}")]
+    InvalidResponse(String),
+    /// The ID token is malformed, has an invalid signature, has expired, or
+    /// its claims don't match the configuration.
+    #[error("the ID token is invalid: {0}")]
+    InvalidIdToken(#[source] jsonwebtoken::errors::Error),
+    /// There is no key to verify the signature of the ID token with.
+    #[error("no key found for the ID token")]
+    UnknownKey,
+    /// The provider returned an ID token, but no
+    /// [issuer](OAuthProviderConfig::issuer) is configured to validate it
+    /// against.
+    #[error("the ID token cannot be validated because no issuer is configured")]
+    IssuerNotConfigured,
+    /// The `nonce` claim of the ID token doesn't match the one of the
+    /// authorization request.
+    #[error("the ID token nonce does not match")]
+    InvalidNonce,
+    /// The user information does not contain the subject, or the subject
+    /// doesn't match the one of the ID token.
+    #[error("the OAuth user information does not identify the user")]
+    InvalidSubject,
+    /// The [`OAuthUserLinker`](crate::auth::oauth::OAuthUserLinker) did not
+    /// allow the user to log in.
+    #[error("the user is not allowed to log in with this identity")]
+    UserNotAllowed,
+}
+impl_into_cot_error!(OAuthError, BAD_REQUEST);
+
+/// An error that occurs when creating an [`OAuthClient`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OAuthConfigError {
+    /// The provider is not configured in
+    /// [`OAuthConfig`](crate::config::OAuthConfig).
+    #[error("unknown OAuth provider `{0}`")]
+    UnknownProvider(String),
+    /// One of the provider URLs is invalid.
+    #[error("invalid OAuth provider URL `{url}`: {source}")]
+    InvalidUrl {
+        /// The invalid URL.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: url::ParseError,
+    },
+    /// The HTTP client could not be created.
+    #[error("could not create the OAuth HTTP client: {0}")]
+    HttpClient(String),
+}
+impl_into_cot_error!(OAuthConfigError);
+
+/// A pending authorization request.
+///
+/// This is created by [`OAuthClient::authorization_request`] when the user
+/// starts logging in. The user should be redirected to [`url`](Self::url),
+/// and the request itself has to be stored (usually in the session) until the
+/// provider redirects the user back, since the secrets it contains are needed
+/// to finish the login with [`OAuthClient::authenticate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizationRequest {
+    /// The name of the provider.
+    pub provider: String,
+    /// The URL of the provider's authorization endpoint the user should be
+    /// redirected to.
+    pub url: String,
+    /// The URL the provider redirects the user back to.
+    pub redirect_uri: String,
+    /// The random value protecting the callback against CSRF.
+    #[debug("..")]
+    pub state: String,
+    /// The random value binding the ID token to this request.
+    #[debug("..")]
+    pub nonce: String,
+    /// The PKCE code verifier.
+    #[debug("..")]
+    pub code_verifier: String,
+}
+
+impl AuthorizationRequest {
+    /// Returns whether the `state` parameter of the callback matches this
+    /// request.
+    ///
+    /// The comparison is done in constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthClient;
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let client = OAuthClient::new(
+    ///     "example",
+    ///     OAuthProviderConfig::builder()
+    ///         .client_id("client")
+    ///         .authorization_endpoint("https://example.com/authorize")
+    ///         .token_endpoint("https://example.com/token")
+    ///         .build(),
+    /// )?;
+    /// let request = client.authorization_request("https://my.site/callback/")?;
+    ///
+    /// assert!(request.verify_state(&request.state.clone()));
+    /// assert!(!request.verify_state("forged"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn verify_state(&self, state: &str) -> bool {
+        self.state.as_bytes().ct_eq(state.as_bytes()).into()
+    }
+}
+
+/// The response of the provider's token endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[non_exhaustive]
+pub struct OAuthTokenResponse {
+    /// The access token, which can be used to call the provider's APIs on
+    /// behalf of the user.
+    #[debug("..")]
+    pub access_token: String,
+    /// The type of the access token, usually `Bearer`.
+    pub token_type: String,
+    /// The number of seconds after which the access token expires.
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+    /// The refresh token, if issued by the provider.
+    #[serde(default)]
+    #[debug("..")]
+    pub refresh_token: Option<String>,
+    /// The OpenID Connect ID token, if the `openid` scope was requested.
+    #[serde(default)]
+    #[debug("..")]
+    pub id_token: Option<String>,
+    /// The scopes granted by the user, if different from the requested ones.
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+/// The information about a user logged in with an identity provider.
+///
+/// This is created from the claims of the ID token and the response of the
+/// provider's userinfo endpoint.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct OAuthUserInfo {
+    /// The name of the provider.
+    pub provider: String,
+    /// The unique identifier of the user at the provider.
+    pub subject: String,
+    /// The email address of the user.
+    pub email: Option<String>,
+    /// Whether the provider has verified that the email address belongs to
+    /// the user.
+    pub email_verified: bool,
+    /// The full name of the user.
+    pub name: Option<String>,
+    /// The username the user prefers to be referred to with.
+    pub preferred_username: Option<String>,
+    /// All the claims returned by the provider.
+    pub claims: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OAuthUserInfo {
+    /// Creates the user information from the claims returned by the
+    /// provider.
+    ///
+    /// The subject is read from the `sub` claim, as defined by OpenID
+    /// Connect, or from the `id` claim, which is used by some plain OAuth 2.0
+    /// providers, such as GitHub.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OAuthError::InvalidSubject`] if the claims don't contain the
+    /// subject.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthUserInfo;
+    ///
+    /// let claims = serde_json::json!({"sub": "123", "email": "alice@example.com"});
+    /// let user_info =
+    ///     OAuthUserInfo::from_claims("example", claims.as_object().unwrap().clone()).unwrap();
+    ///
+    /// assert_eq!(user_info.subject, "123");
+    /// assert_eq!(user_info.email.as_deref(), Some("alice@example.com"));
+    /// assert!(!user_info.email_verified);
+    /// ```
+    pub fn from_claims<T: Into<String>>(
+        provider: T,
+        claims: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, OAuthError> {
+        let subject = subject_claim(&claims).ok_or(OAuthError::InvalidSubject)?;
+        let string_claim = |name: &str| {
+            claims
+                .get(name)
+                .and_then(serde_json::Value::as_str)
+                .map(ToOwned::to_owned)
+        };
+
+        Ok(Self {
+            provider: provider.into(),
+            subject,
+            email: string_claim("email"),
+            email_verified: claims
+                .get("email_verified")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false),
+            name: string_claim("name"),
+            preferred_username: string_claim("preferred_username")
+                .or_else(|| string_claim("login")),
+            claims,
+        })
+    }
+}
+
+/// A client for an OAuth 2.0 / OpenID Connect identity provider.
+///
+/// The client implements the authorization code flow with
+/// [PKCE](https://www.rfc-editor.org/rfc/rfc7636):
+///
+/// 1. [`authorization_request`](Self::authorization_request) creates the URL
+///    the user is redirected to, along with the secrets that have to be stored
+///    until the user comes back,
+/// 2. after the user authorizes the project, the provider redirects them back
+///    with an authorization code, which is passed to
+///    [`authenticate`](Self::authenticate). This exchanges the code for tokens,
+///    validates the ID token and fetches the information about the user.
+///
+/// Most projects should use [`OAuthApp`](crate::auth::oauth::OAuthApp),
+/// which implements the entire flow, instead of using the client directly.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::oauth::OAuthClient;
+/// use cot::config::OAuthProviderConfig;
+///
+/// # fn main() -> cot::Result<()> {
+/// let client = OAuthClient::new(
+///     "example",
+///     OAuthProviderConfig::builder()
+///         .client_id("client")
+///         .authorization_endpoint("https://example.com/authorize")
+///         .token_endpoint("https://example.com/token")
+///         .build(),
+/// )?;
+///
+/// let request = client.authorization_request("https://my.site/oauth/example/callback/")?;
+/// assert!(request.url.starts_with("https://example.com/authorize?"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    name: String,
+    config: OAuthProviderConfig,
+    #[debug("..")]
+    http: reqwest::Client,
+}
+
+impl OAuthClient {
+    /// Creates a new client for the provider with given name and
+    /// configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client could not be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthClient;
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let client = OAuthClient::new(
+    ///     "example",
+    ///     OAuthProviderConfig::builder()
+    ///         .client_id("client")
+    ///         .authorization_endpoint("https://example.com/authorize")
+    ///         .token_endpoint("https://example.com/token")
+    ///         .build(),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new<T: Into<String>>(
+        name: T,
+        config: OAuthProviderConfig,
+    ) -> Result<Self, OAuthConfigError> {
+        Ok(Self {
+            name: name.into(),
+            config,
+            http: http_client()?,
+        })
+    }
+
+    /// Creates a new client for the provider with given name configured in
+    /// [`ProjectConfig::oauth`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider is not configured, or if the HTTP
+    /// client could not be created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthClient;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [oauth.providers.example]
+    /// client_id = "client"
+    /// authorization_endpoint = "https://example.com/authorize"
+    /// token_endpoint = "https://example.com/token"
+    /// "#,
+    /// )?;
+    ///
+    /// let client = OAuthClient::from_config(&config, "example")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_config(config: &ProjectConfig, provider: &str) -> Result<Self, OAuthConfigError> {
+        let provider_config = config
+            .oauth
+            .providers
+            .get(provider)
+            .ok_or_else(|| OAuthConfigError::UnknownProvider(provider.to_owned()))?;
+
+        Self::new(provider, provider_config.clone())
+    }
+
+    /// Returns the name of the provider.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the configuration of the provider.
+    #[must_use]
+    pub fn config(&self) -> &OAuthProviderConfig {
+        &self.config
+    }
+
+    /// Creates a new authorization request, with a random state, nonce and
+    /// PKCE code verifier.
+    ///
+    /// `redirect_uri` is the absolute URL of the callback view, which has to
+    /// be registered at the provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the authorization endpoint is not a valid URL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthClient;
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// # fn main() -> cot::Result<()> {
+    /// let client = OAuthClient::new(
+    ///     "example",
+    ///     OAuthProviderConfig::builder()
+    ///         .client_id("client")
+    ///         .authorization_endpoint("https://example.com/authorize")
+    ///         .token_endpoint("https://example.com/token")
+    ///         .build(),
+    /// )?;
+    ///
+    /// let request = client.authorization_request("https://my.site/callback/")?;
+    /// assert!(request.url.contains("code_challenge_method=S256"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn authorization_request(
+        &self,
+        redirect_uri: &str,
+    ) -> Result<AuthorizationRequest, OAuthConfigError> {
+        let state = crate::utils::random::random_token::<32>();
+        let nonce = crate::utils::random::random_token::<32>();
+        let code_verifier = crate::utils::random::random_token::<32>();
+
+        let mut url = url::Url::parse(&self.config.authorization_endpoint).map_err(|source| {
+            OAuthConfigError::InvalidUrl {
+                url: self.config.authorization_endpoint.clone(),
+                source,
+            }
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", &self.config.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &code_challenge(&code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(AuthorizationRequest {
+            provider: self.name.clone(),
+            url: url.into(),
+            redirect_uri: redirect_uri.to_owned(),
+            state,
+            nonce,
+            code_verifier,
+        })
+    }
+
+    /// Finishes the login: exchanges the authorization code for tokens,
+    /// validates the ID token (if the provider returned one) and fetches the
+    /// user information from the userinfo endpoint (if it's configured).
+    ///
+    /// The `state` parameter of the callback should be checked with
+    /// [`AuthorizationRequest::verify_state`] before calling this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request to the provider fails, if the
+    /// provider returns an error, if the ID token is invalid, or if the
+    /// provider returned no information about the user.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::auth::oauth::{AuthorizationRequest, OAuthClient, OAuthUserInfo};
+    ///
+    /// async fn callback(
+    ///     client: &OAuthClient,
+    ///     request: &AuthorizationRequest,
+    ///     code: &str,
+    /// ) -> cot::Result<OAuthUserInfo> {
+    ///     Ok(client.authenticate(code, request).await?)
+    /// }
+    /// ```
+    pub async fn authenticate(
+        &self,
+        code: &str,
+        request: &AuthorizationRequest,
+    ) -> Result<OAuthUserInfo, OAuthError> {
+        let tokens = self
+            .exchange_code(code, &request.code_verifier, &request.redirect_uri)
+            .await?;
+
+        let mut claims = match &tokens.id_token {
+            Some(id_token) => Some(self.validate_id_token(id_token, &request.nonce).await?),
+            None => None,
+        };
+        if self.config.userinfo_endpoint.is_some() {
+            let user_info = self.userinfo(&tokens.access_token).await?;
+            claims = Some(match claims {
+                Some(mut claims) => {
+                    // OpenID Connect Core 1.0, section 5.3.2: the subject of the userinfo
+                    // response must match the one of the ID token
+                    if subject_claim(&claims) != subject_claim(&user_info) {
+                        return Err(OAuthError::InvalidSubject);
+                    }
+                    claims.extend(user_info);
+                    claims
+                }
+                None => user_info,
+            });
+        }
+
+        let claims = claims.ok_or_else(|| {
+            OAuthError::InvalidResponse(
+                "no ID token was returned and no userinfo endpoint is configured".to_owned(),
+            )
+        })?;
+        OAuthUserInfo::from_claims(self.name.clone(), claims)
+    }
+
+    /// Exchanges the authorization code for tokens at the provider's token
+    /// endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, or if the provider returns an
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::auth::oauth::{AuthorizationRequest, OAuthClient};
+    ///
+    /// async fn access_token(
+    ///     client: &OAuthClient,
+    ///     request: &AuthorizationRequest,
+    ///     code: &str,
+    /// ) -> cot::Result<String> {
+    ///     let tokens = client
+    ///         .exchange_code(code, &request.code_verifier, &request.redirect_uri)
+    ///         .await?;
+    ///     Ok(tokens.access_token)
+    /// }
+    /// ```
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        redirect_uri: &str,
+    ) -> Result<OAuthTokenResponse, OAuthError> {
+        let client_secret = self
+            .config
+            .client_secret
+            .as_ref()
+            .map(|secret| String::from_utf8_lossy(secret.as_bytes()).into_owned());
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(client_secret) = &client_secret {
+            params.push(("client_secret", client_secret));
+        }
+
+        let response = self
+            .http
+            .post(&self.config.token_endpoint)
+            .header(http::header::ACCEPT, "application/json")
+            .form(&params)
+            .send()
+            .await
+            .map_err(OAuthError::Http)?;
+
+        if !response.status().is_success() {
+            return Err(provider_error(response).await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|error| OAuthError::InvalidResponse(error.to_string()))
+    }
+
+    /// Validates the ID token returned by the provider and returns its
+    /// claims.
+    ///
+    /// The token has to be signed with one of the
+    /// [configured algorithms](OAuthProviderConfig::id_token_algorithms).
+    /// The signature is verified with the client secret for
+    /// [`JwtAlgorithm::Hs256`], and with the key from the provider's JSON Web
+    /// Key Set otherwise. The audience has to be the client ID, the issuer
+    /// has to match the configured one, and the `nonce` claim has to be
+    /// equal to the nonce of the authorization request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is invalid, if no issuer is configured,
+    /// or if the key set could not be fetched.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::auth::oauth::{AuthorizationRequest, OAuthClient};
+    ///
+    /// async fn email(
+    ///     client: &OAuthClient,
+    ///     request: &AuthorizationRequest,
+    ///     id_token: &str,
+    /// ) -> cot::Result<Option<String>> {
+    ///     let claims = client.validate_id_token(id_token, &request.nonce).await?;
+    ///     Ok(claims
+    ///         .get("email")
+    ///         .and_then(|email| email.as_str())
+    ///         .map(ToOwned::to_owned))
+    /// }
+    /// ```
+    pub async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, OAuthError> {
+        let issuer = self
+            .config
+            .issuer
+            .as_ref()
+            .ok_or(OAuthError::IssuerNotConfigured)?;
+        let header = jsonwebtoken::decode_header(id_token).map_err(OAuthError::InvalidIdToken)?;
+        if !self
+            .config
+            .id_token_algorithms
+            .iter()
+            .any(|&algorithm| id_token_algorithm(algorithm) == header.alg)
+        {
+            return Err(OAuthError::InvalidIdToken(
+                jsonwebtoken::errors::ErrorKind::InvalidAlgorithm.into(),
+            ));
+        }
+        let key = if header.alg == Algorithm::HS256 {
+            let secret = self
+                .config
+                .client_secret
+                .as_ref()
+                .ok_or(OAuthError::UnknownKey)?;
+            DecodingKey::from_secret(secret.as_bytes())
+        } else {
+            let jwks = self.fetch_jwks().await?;
+            let jwk = match &header.kid {
+                Some(kid) => jwks.find(kid),
+                None if jwks.keys.len() == 1 => jwks.keys.first(),
+                None => None,
+            }
+            .ok_or(OAuthError::UnknownKey)?;
+            DecodingKey::from_jwk(jwk).map_err(OAuthError::InvalidIdToken)?
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[issuer]);
+        validation.set_required_spec_claims(&["exp", "aud", "iss", "sub"]);
+        let claims = jsonwebtoken::decode::<serde_json::Map<String, serde_json::Value>>(
+            id_token,
+            &key,
+            &validation,
+        )
+        .map_err(OAuthError::InvalidIdToken)?
+        .claims;
+
+        let token_nonce = claims
+            .get("nonce")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        if !bool::from(token_nonce.as_bytes().ct_eq(nonce.as_bytes())) {
+            return Err(OAuthError::InvalidNonce);
+        }
+
+        Ok(claims)
+    }
+
+    /// Fetches the information about the user from the provider's userinfo
+    /// endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the userinfo endpoint is not configured, if the
+    /// request fails, or if the provider returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use cot::auth::oauth::OAuthClient;
+    ///
+    /// async fn claims(
+    ///     client: &OAuthClient,
+    ///     access_token: &str,
+    /// ) -> cot::Result<serde_json::Map<String, serde_json::Value>> {
+    ///     Ok(client.userinfo(access_token).await?)
+    /// }
+    /// ```
+    pub async fn userinfo(
+        &self,
+        access_token: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, OAuthError> {
+        let endpoint = self.config.userinfo_endpoint.as_ref().ok_or_else(|| {
+            OAuthError::InvalidResponse("no userinfo endpoint is configured".to_owned())
+        })?;
+
+        let response = self
+            .http
+            .get(endpoint)
+            .header(http::header::ACCEPT, "application/json")
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(OAuthError::Http)?;
+
+        if !response.status().is_success() {
+            return Err(provider_error(response).await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|error| OAuthError::InvalidResponse(error.to_string()))
+    }
+
+    async fn fetch_jwks(&self) -> Result<JwkSet, OAuthError> {
+        let jwks_uri = self
+            .config
+            .jwks_uri
+            .as_ref()
+            .ok_or(OAuthError::UnknownKey)?;
+
+        let response = self
+            .http
+            .get(jwks_uri)
+            .send()
+            .await
+            .map_err(OAuthError::Http)?;
+        if !response.status().is_success() {
+            return Err(provider_error(response).await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|error| OAuthError::InvalidResponse(error.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+async fn provider_error(response: reqwest::Response) -> OAuthError {
+    let status = response.status();
+    match response.json::<ProviderErrorResponse>().await {
+        Ok(error) => OAuthError::Provider {
+            error: error.error,
+            description: error.error_description,
+        },
+        Err(_) => OAuthError::InvalidResponse(format!("unexpected status code {status}")),
+    }
+}
+
+fn subject_claim(claims: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+    match claims.get("sub").or_else(|| claims.get("id"))? {
+        serde_json::Value::String(subject) if !subject.is_empty() => Some(subject.clone()),
+        serde_json::Value::Number(subject) => Some(subject.to_string()),
+        _ => None,
+    }
+}
+
+fn http_client() -> Result<reqwest::Client, OAuthConfigError> {
+    static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+    if let Some(client) = HTTP_CLIENT.get() {
+        return Ok(client.clone());
+    }
+
+    let client_error =
+        |error: &dyn std::error::Error| OAuthConfigError::HttpClient(error.to_string());
+    let tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|error| client_error(&error))?;
+    let tls_config =
+        rustls_platform_verifier::BuilderVerifierExt::with_platform_verifier(tls_config)
+            .map_err(|error| client_error(&error))?
+            .with_no_client_auth();
+    let client = reqwest::Client::builder()
+        .tls_backend_preconfigured(tls_config)
+        // the responses of the token and userinfo endpoints must never be redirects
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|error| client_error(&error))?;
+
+    Ok(HTTP_CLIENT.get_or_init(|| client).clone())
+}
+
+fn id_token_algorithm(algorithm: JwtAlgorithm) -> Algorithm {
+    match algorithm {
+        JwtAlgorithm::Hs256 => Algorithm::HS256,
+        JwtAlgorithm::Rs256 => Algorithm::RS256,
+        JwtAlgorithm::EdDsa => Algorithm::EdDSA,
+    }
+}
+
+/// Computes the `S256` PKCE code challenge for the code verifier, as defined
+/// in [RFC 7636, section 4.2](https://www.rfc-editor.org/rfc/rfc7636#section-4.2).
+pub(crate) fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SecretKey;
+
+    fn test_client(config: OAuthProviderConfig) -> OAuthClient {
+        OAuthClient::new("example", config).unwrap()
+    }
+
+    fn test_config() -> OAuthProviderConfig {
+        OAuthProviderConfig::builder()
+            .client_id("client")
+            .client_secret(SecretKey::from("secret"))
+            .authorization_endpoint("https://example.com/authorize?prompt=login")
+            .token_endpoint("https://example.com/token")
+            .issuer("https://example.com")
+            .id_token_algorithms(vec![JwtAlgorithm::Hs256])
+            .build()
+    }
+
+    fn id_token(claims: &serde_json::Value, secret: &[u8]) -> String {
+        id_token_with_algorithm(claims, secret, Algorithm::HS256)
+    }
+
+    fn id_token_with_algorithm(
+        claims: &serde_json::Value,
+        secret: &[u8],
+        algorithm: Algorithm,
+    ) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(algorithm),
+            claims,
+            &jsonwebtoken::EncodingKey::from_secret(secret),
+        )
+        .unwrap()
+    }
+
+    fn valid_claims() -> serde_json::Value {
+        serde_json::json!({
+            "iss": "https://example.com",
+            "sub": "123",
+            "aud": "client",
+            "exp": chrono::Utc::now().timestamp() + 60,
+            "nonce": "nonce",
+        })
+    }
+
+    #[test]
+    fn code_challenge_rfc_7636() {
+        // RFC 7636, Appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "unsupported operation: can't call foreign function")]
+    fn authorization_request() {
+        let client = test_client(test_config());
+
+        let request = client
+            .authorization_request("https://my.site/callback/")
+            .unwrap();
+        let url = url::Url::parse(&request.url).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["prompt"], "login");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], "client");
+        assert_eq!(params["redirect_uri"], "https://my.site/callback/");
+        assert_eq!(params["scope"], "openid email profile");
+        assert_eq!(params["state"], request.state);
+        assert_eq!(params["nonce"], request.nonce);
+        assert_eq!(
+            params["code_challenge"],
+            code_challenge(&request.code_verifier)
+        );
+        assert_eq!(params["code_challenge_method"], "S256");
+        assert_eq!(request.code_verifier.len(), 64);
+
+        let other = client
+            .authorization_request("https://my.site/callback/")
+            .unwrap();
+        assert_ne!(request.state, other.state);
+        assert!(request.verify_state(&request.state));
+        assert!(!request.verify_state(&other.state));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore = "unsupported operation: can't call foreign function")]
+    async fn validate_id_token() {
+        let client = test_client(test_config());
+
+        let token = id_token(&valid_claims(), b"secret");
+        let claims = client.validate_id_token(&token, "nonce").await.unwrap();
+        assert_eq!(claims["sub"], "123");
+
+        assert!(matches!(
+            client.validate_id_token(&token, "other").await,
+            Err(OAuthError::InvalidNonce)
+        ));
+
+        let token = id_token(&valid_claims(), b"wrong secret");
+        assert!(matches!(
+            client.validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(_))
+        ));
+
+        let mut claims = valid_claims();
+        claims["aud"] = "other-client".into();
+        let token = id_token(&claims, b"secret");
+        assert!(matches!(
+            client.validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(_))
+        ));
+
+        let mut claims = valid_claims();
+        claims["iss"] = "https://evil.example.com".into();
+        let token = id_token(&claims, b"secret");
+        assert!(matches!(
+            client.validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(_))
+        ));
+
+        let mut claims = valid_claims();
+        claims["exp"] = (chrono::Utc::now().timestamp() - 3600).into();
+        let token = id_token(&claims, b"secret");
+        assert!(matches!(
+            client.validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(_))
+        ));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore = "unsupported operation: can't call foreign function")]
+    async fn validate_id_token_algorithm_pinned() {
+        let token = id_token_with_algorithm(&valid_claims(), b"secret", Algorithm::HS512);
+        assert!(matches!(
+            test_client(test_config()).validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(error))
+                if *error.kind() == jsonwebtoken::errors::ErrorKind::InvalidAlgorithm
+        ));
+
+        let mut config = test_config();
+        config.id_token_algorithms = vec![JwtAlgorithm::Rs256];
+        let token = id_token(&valid_claims(), b"secret");
+        assert!(matches!(
+            test_client(config).validate_id_token(&token, "nonce").await,
+            Err(OAuthError::InvalidIdToken(error))
+                if *error.kind() == jsonwebtoken::errors::ErrorKind::InvalidAlgorithm
+        ));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore = "unsupported operation: can't call foreign function")]
+    async fn validate_id_token_issuer_required() {
+        let token = id_token(&valid_claims(), b"secret");
+
+        let mut config = test_config();
+        config.issuer = None;
+        assert!(matches!(
+            test_client(config).validate_id_token(&token, "nonce").await,
+            Err(OAuthError::IssuerNotConfigured)
+        ));
+
+        let mut claims = valid_claims();
+        claims.as_object_mut().unwrap().remove("iss");
+        let token = id_token(&claims, b"secret");
+        assert!(matches!(
+            test_client(test_config())
+                .validate_id_token(&token, "nonce")
+                .await,
+            Err(OAuthError::InvalidIdToken(_))
+        ));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore = "unsupported operation: can't call foreign function")]
+    async fn validate_id_token_without_key() {
+        let mut config = test_config();
+        config.client_secret = None;
+        let client = test_client(config);
+
+        let token = id_token(&valid_claims(), b"secret");
+        assert!(matches!(
+            client.validate_id_token(&token, "nonce").await,
+            Err(OAuthError::UnknownKey)
+        ));
+    }
+
+    #[test]
+    fn user_info_from_claims() {
+        let claims = serde_json::json!({
+            "id": 42,
+            "login": "octocat",
+            "email": "octocat@example.com",
+            "email_verified": true,
+        });
+
+        let user_info =
+            OAuthUserInfo::from_claims("github", claims.as_object().unwrap().clone()).unwrap();
+
+        assert_eq!(user_info.provider, "github");
+        assert_eq!(user_info.subject, "42");
+        assert_eq!(user_info.preferred_username.as_deref(), Some("octocat"));
+        assert_eq!(user_info.email.as_deref(), Some("octocat@example.com"));
+        assert!(user_info.email_verified);
+
+        let claims = serde_json::json!({"sub": ""});
+        assert!(matches!(
+            OAuthUserInfo::from_claims("example", claims.as_object().unwrap().clone()),
+            Err(OAuthError::InvalidSubject)
+        ));
+    }
+}
@@ -0,0 +1,335 @@
+//! Password reset tokens and views.
+//!
+//! This module provides the [`PasswordResetTokenGenerator`], which generates
+//! signed, expiring tokens that can be sent to users (typically in an email)
+//! to let them set a new password. The tokens are bound to the current
+//! password of the user, so they automatically become invalid once they are
+//! used to change it.
+//!
+//! With the `db` and `email` features enabled, this module also provides
+//! [`PasswordResetApp`], which contains ready-made views and forms
+//! implementing the entire password reset flow for
+//! [`DatabaseUser`](crate::auth::db::DatabaseUser)s, as well as a view for
+//! changing the password of a logged-in user.
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::auth::{User, UserId};
+use crate::config::{ProjectConfig, SecretKey};
+
+const PASSWORD_RESET_TOKEN_CONTEXT: &str = "cot.rs password reset token v1";
+
+/// The default time after which the password reset tokens expire.
+pub const DEFAULT_PASSWORD_RESET_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Generator and validator of password reset tokens.
+///
+/// The tokens are created using a keyed hash of the user ID, the user's
+/// [session auth hash](User::session_auth_hash) (which is derived from the
+/// user's password hash) and the time the token was generated at. This means
+/// that the tokens:
+///
+/// * cannot be forged without knowing the project's [`SecretKey`],
+/// * expire after the [timeout](Self::timeout) passes,
+/// * become invalid once the user's password is changed, which makes them
+///   effectively single-use.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::User;
+/// use cot::auth::password_reset::PasswordResetTokenGenerator;
+/// use cot::config::SecretKey;
+///
+/// fn reset_token(user: &dyn User) -> Option<String> {
+///     let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+///     let token = generator.make_token(user)?;
+///     assert!(generator.check_token(user, &token));
+///     Some(token)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswordResetTokenGenerator {
+    secret_key: SecretKey,
+    fallback_secret_keys: Vec<SecretKey>,
+    timeout: Duration,
+}
+
+impl PasswordResetTokenGenerator {
+    /// Creates a new token generator that signs the tokens with the given
+    /// secret key.
+    ///
+    /// The tokens generated expire after [`DEFAULT_PASSWORD_RESET_TIMEOUT`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+    /// ```
+    #[must_use]
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            secret_key,
+            fallback_secret_keys: Vec::new(),
+            timeout: DEFAULT_PASSWORD_RESET_TIMEOUT,
+        }
+    }
+
+    /// Creates a new token generator that uses the secret key and the
+    /// fallback secret keys from the project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let generator = PasswordResetTokenGenerator::from_config(&ProjectConfig::default());
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self::new(config.secret_key.clone())
+            .fallback_secret_keys(config.fallback_secret_keys.clone())
+    }
+
+    /// Sets the secret keys that are accepted when checking the tokens, in
+    /// addition to the main secret key.
+    ///
+    /// This is useful when rotating the secret key, so that the tokens that
+    /// have already been sent to users stay valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"new secret"))
+    ///     .fallback_secret_keys(vec![SecretKey::new(b"old secret")]);
+    /// ```
+    #[must_use]
+    pub fn fallback_secret_keys(mut self, fallback_secret_keys: Vec<SecretKey>) -> Self {
+        self.fallback_secret_keys = fallback_secret_keys;
+        self
+    }
+
+    /// Sets the time after which the tokens expire.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"))
+    ///     .timeout(Duration::from_secs(15 * 60));
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Generates a password reset token for the given user.
+    ///
+    /// Returns [`None`] if the user doesn't have an ID or a
+    /// [session auth hash](User::session_auth_hash) (for instance, if it's an
+    /// anonymous user).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::auth::{AnonymousUser, User};
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+    /// assert!(generator.make_token(&AnonymousUser).is_none());
+    /// ```
+    #[must_use]
+    pub fn make_token(&self, user: &dyn User) -> Option<String> {
+        self.make_token_at(user, Utc::now().timestamp())
+    }
+
+    /// Checks whether the given token is a valid password reset token for the
+    /// given user.
+    ///
+    /// The token is valid if it was generated for this user with the current
+    /// secret key (or one of the fallback secret keys), the user's password
+    /// hasn't changed since, and the token hasn't expired yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetTokenGenerator;
+    /// use cot::auth::{AnonymousUser, User};
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+    /// assert!(!generator.check_token(&AnonymousUser, "invalid"));
+    /// ```
+    #[must_use]
+    pub fn check_token(&self, user: &dyn User, token: &str) -> bool {
+        self.check_token_at(user, token, Utc::now().timestamp())
+    }
+
+    fn make_token_at(&self, user: &dyn User, timestamp: i64) -> Option<String> {
+        let hash = Self::token_hash(&self.secret_key, user, timestamp)?;
+
+        Some(format!("{timestamp:x}-{}", hash.to_hex()))
+    }
+
+    fn check_token_at(&self, user: &dyn User, token: &str, now: i64) -> bool {
+        let Some((timestamp, hash)) = token.split_once('-') else {
+            return false;
+        };
+        let Ok(timestamp) = i64::from_str_radix(timestamp, 16) else {
+            return false;
+        };
+        let Ok(hash) = blake3::Hash::from_hex(hash) else {
+            return false;
+        };
+
+        let timeout = i64::try_from(self.timeout.as_secs()).unwrap_or(i64::MAX);
+        if timestamp > now || now - timestamp > timeout {
+            return false;
+        }
+
+        // `blake3::Hash` implements constant-time equality
+        std::iter::once(&self.secret_key)
+            .chain(&self.fallback_secret_keys)
+            .any(|secret_key| Self::token_hash(secret_key, user, timestamp) == Some(hash))
+    }
+
+    fn token_hash(secret_key: &SecretKey, user: &dyn User, timestamp: i64) -> Option<blake3::Hash> {
+        let user_id = match user.id()? {
+            UserId::Int(id) => format!("int:{id}"),
+            UserId::String(id) => format!("string:{id}"),
+        };
+        let session_auth_hash = user.session_auth_hash(secret_key)?;
+
+        let key = blake3::derive_key(PASSWORD_RESET_TOKEN_CONTEXT, secret_key.as_bytes());
+        let mut hasher = blake3::Hasher::new_keyed(&key);
+        hasher.update(&(user_id.len() as u64).to_le_bytes());
+        hasher.update(user_id.as_bytes());
+        hasher.update(&(session_auth_hash.as_bytes().len() as u64).to_le_bytes());
+        hasher.update(session_auth_hash.as_bytes());
+        hasher.update(&timestamp.to_le_bytes());
+
+        Some(hasher.finalize())
+    }
+}
+
+#[cfg(all(feature = "db", feature = "email"))]
+mod app;
+#[cfg(all(feature = "db", feature = "email"))]
+pub use app::{PasswordChangeForm, PasswordResetApp, PasswordResetForm, SetPasswordForm};
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::always;
+
+    use super::*;
+    use crate::auth::{AnonymousUser, MockUser, SessionAuthHash};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn mock_user(id: i64, password_hash: &'static [u8]) -> MockUser {
+        let mut user = MockUser::new();
+        user.expect_id().return_const(Some(UserId::Int(id)));
+        user.expect_session_auth_hash()
+            .with(always())
+            .returning(move |_| Some(SessionAuthHash::new(password_hash)));
+        user
+    }
+
+    #[test]
+    fn token_valid() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+        let user = mock_user(1, b"hash");
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(generator.check_token_at(&user, &token, NOW));
+        assert!(generator.check_token_at(&user, &token, NOW + 60));
+    }
+
+    #[test]
+    fn token_expired() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"))
+            .timeout(Duration::from_secs(60));
+        let user = mock_user(1, b"hash");
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(generator.check_token_at(&user, &token, NOW + 60));
+        assert!(!generator.check_token_at(&user, &token, NOW + 61));
+        assert!(!generator.check_token_at(&user, &token, NOW - 1));
+    }
+
+    #[test]
+    fn token_invalid_after_password_change() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+        let user = mock_user(1, b"hash");
+        let user_with_new_password = mock_user(1, b"new hash");
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(!generator.check_token_at(&user_with_new_password, &token, NOW));
+    }
+
+    #[test]
+    fn token_invalid_for_other_user() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+        let user = mock_user(1, b"hash");
+        let other_user = mock_user(2, b"hash");
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(!generator.check_token_at(&other_user, &token, NOW));
+    }
+
+    #[test]
+    fn token_tampered() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+        let user = mock_user(1, b"hash");
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+        let (_, hash) = token.split_once('-').unwrap();
+
+        // changing the timestamp to extend the validity of the token
+        let tampered = format!("{:x}-{hash}", NOW + 1000);
+        assert!(!generator.check_token_at(&user, &tampered, NOW + 1000));
+        assert!(!generator.check_token_at(&user, "", NOW));
+        assert!(!generator.check_token_at(&user, "zzz-abc", NOW));
+        assert!(!generator.check_token_at(&user, &format!("{NOW:x}-abc"), NOW));
+    }
+
+    #[test]
+    fn token_fallback_secret_key() {
+        let old_generator = PasswordResetTokenGenerator::new(SecretKey::new(b"old"));
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"new"))
+            .fallback_secret_keys(vec![SecretKey::new(b"old")]);
+        let other_generator = PasswordResetTokenGenerator::new(SecretKey::new(b"other"));
+        let user = mock_user(1, b"hash");
+
+        let token = old_generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(generator.check_token_at(&user, &token, NOW));
+        assert!(!other_generator.check_token_at(&user, &token, NOW));
+    }
+
+    #[test]
+    fn token_anonymous_user() {
+        let generator = PasswordResetTokenGenerator::new(SecretKey::new(b"secret"));
+
+        assert!(generator.make_token(&AnonymousUser).is_none());
+    }
+}
@@ -0,0 +1,880 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::auth::db::{DatabaseUser, DatabaseUserCredentials};
+use crate::auth::password_reset::{DEFAULT_PASSWORD_RESET_TIMEOUT, PasswordResetTokenGenerator};
+use crate::auth::password_validation::PasswordValidators;
+use crate::auth::{Auth, AuthError, AuthenticationRequired};
+use crate::common_types::{Email, Password};
+use crate::csrf::CsrfToken;
+use crate::db::{Database, Model};
+use crate::email::EmailMessage;
+use crate::error::MethodNotAllowed;
+use crate::form::{
+    Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, FormResult,
+};
+use crate::html::Html;
+use crate::request::extractors::{FromRequestHead, Path};
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Response};
+use crate::router::{Route, Router, Urls};
+use crate::{App, Error, Method, Template, reverse, reverse_redirect};
+
+/// A form for requesting a password reset link.
+///
+/// Used by the [`PasswordResetApp`].
+#[derive(Debug, Form)]
+pub struct PasswordResetForm {
+    /// The email address of the user who wants to reset their password.
+    pub email: Email,
+}
+
+/// A form for setting a new password.
+///
+/// Used by the [`PasswordResetApp`].
+#[derive(Debug, Form)]
+pub struct SetPasswordForm {
+    /// The new password.
+    pub password: Password,
+    /// The new password, repeated to avoid typos.
+    pub password_confirm: Password,
+}
+
+/// A form for changing the password of the logged-in user.
+///
+/// Used by the [`PasswordResetApp`].
+#[derive(Debug, Form)]
+pub struct PasswordChangeForm {
+    /// The current password, required to make sure it's the user who is
+    /// changing it.
+    pub old_password: Password,
+    /// The new password.
+    pub password: Password,
+    /// The new password, repeated to avoid typos.
+    pub password_confirm: Password,
+}
+
+/// An app that implements the password reset flow for
+/// [`DatabaseUser`]s.
+///
+/// The app provides the following views:
+///
+/// * `password_reset` (`/`) – a form where the user enters their email address.
+///   If there are users with that email address, a password reset link is sent
+///   to them using [`Email`](crate::email::Email). To avoid disclosing which
+///   email addresses are registered, the user is always redirected to the next
+///   view, regardless of whether any email was sent.
+/// * `password_reset_done` (`/done/`) – a page saying that the email has been
+///   sent.
+/// * `password_reset_confirm` (`/{user_id}/{token}/`) – the page the link in
+///   the email points to, containing a form to set a new password. The token is
+///   generated and checked with the [`PasswordResetTokenGenerator`], and the
+///   new password is checked with the [password
+///   validators](crate::config::ProjectConfig::password_validators) configured
+///   for the project.
+/// * `password_reset_complete` (`/complete/`) – a page saying that the password
+///   has been changed.
+/// * `password_change` (`/change/`) – a form where the logged-in user changes
+///   their password by entering the current one and a new one, which is checked
+///   with the same password validators. The failed attempts to enter the
+///   current password are
+///   [throttled](crate::config::ProjectConfig::login_throttle) just like the
+///   logins. The user stays logged in in the current session, but their other
+///   sessions are logged out. Anonymous users get a `401 Unauthorized`
+///   response.
+/// * `password_change_done` (`/change/done/`) – a page saying that the password
+///   has been changed.
+///
+/// The forms are protected against CSRF, so the
+/// [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) needs to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_reset::PasswordResetApp;
+/// use cot::common_types::Email;
+/// use cot::project::RegisterAppsContext;
+/// use cot::{AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register_with_views(
+///             PasswordResetApp::new(
+///                 "https://example.com",
+///                 Email::try_from("no-reply@example.com").unwrap(),
+///             ),
+///             "/password-reset",
+///         );
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PasswordResetApp {
+    site_url: String,
+    from_email: Email,
+    subject: String,
+    timeout: Duration,
+}
+
+impl PasswordResetApp {
+    /// Creates a new password reset app.
+    ///
+    /// `site_url` is the URL of the website (such as `https://example.com`)
+    /// that is used to create the absolute link sent in the emails. It is not
+    /// derived from the request, since the `Host` header can be forged by an
+    /// attacker to make the link point to a website they control.
+    ///
+    /// `from_email` is the address the emails are sent from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = PasswordResetApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(site_url: T, from_email: Email) -> Self {
+        Self {
+            site_url: site_url.into().trim_end_matches('/').to_owned(),
+            from_email,
+            subject: "Password reset".to_owned(),
+            timeout: DEFAULT_PASSWORD_RESET_TIMEOUT,
+        }
+    }
+
+    /// Sets the subject of the password reset emails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_reset::PasswordResetApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = PasswordResetApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// )
+    /// .subject("Reset your password");
+    /// ```
+    #[must_use]
+    pub fn subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Sets the time after which the password reset links expire.
+    ///
+    /// The default is [`DEFAULT_PASSWORD_RESET_TIMEOUT`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::auth::password_reset::PasswordResetApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = PasswordResetApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// )
+    /// .timeout(Duration::from_secs(15 * 60));
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn token_generator(&self, request: &Request) -> PasswordResetTokenGenerator {
+        PasswordResetTokenGenerator::from_config(request.project_config()).timeout(self.timeout)
+    }
+
+    fn email_body(&self, user: &DatabaseUser, link: &str) -> String {
+        format!(
+            "Hello {username},\n\
+            \n\
+            You're receiving this email because a password reset was requested for your \
+            account at {site_url}. To choose a new password, open the following link:\n\
+            \n\
+            {link}\n\
+            \n\
+            If you didn't request a password reset, you can safely ignore this email.\n",
+            username = user.username(),
+            site_url = self.site_url,
+        )
+    }
+}
+
+impl App for PasswordResetApp {
+    fn name(&self) -> &'static str {
+        "cot_password_reset"
+    }
+
+    fn router(&self) -> Router {
+        let app = Arc::new(self.clone());
+        let confirm_app = Arc::clone(&app);
+
+        Router::with_urls([
+            Route::with_handler_and_name(
+                "/",
+                move |base_context: BaseContext,
+                      database: Database,
+                      email: crate::email::Email,
+                      request: Request| {
+                    password_reset(Arc::clone(&app), base_context, database, email, request)
+                },
+                "password_reset",
+            ),
+            Route::with_handler_and_name("/done/", password_reset_done, "password_reset_done"),
+            Route::with_handler_and_name(
+                "/{user_id}/{token}/",
+                move |base_context: BaseContext,
+                      database: Database,
+                      path: Path<(i64, String)>,
+                      request: Request| {
+                    password_reset_confirm(
+                        Arc::clone(&confirm_app),
+                        base_context,
+                        database,
+                        path,
+                        request,
+                    )
+                },
+                "password_reset_confirm",
+            ),
+            Route::with_handler_and_name(
+                "/complete/",
+                password_reset_complete,
+                "password_reset_complete",
+            ),
+            Route::with_handler_and_name("/change/", password_change, "password_change"),
+            Route::with_handler_and_name(
+                "/change/done/",
+                password_change_done,
+                "password_change_done",
+            ),
+        ])
+    }
+}
+
+#[derive(Debug, FromRequestHead)]
+struct BaseContext {
+    urls: Urls,
+    csrf_token: CsrfToken,
+}
+
+async fn password_reset(
+    app: Arc<PasswordResetApp>,
+    base_context: BaseContext,
+    database: Database,
+    email: crate::email::Email,
+    mut request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_reset.html")]
+    struct PasswordResetTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <PasswordResetForm as Form>::Context,
+    }
+
+    let form_context = if request.method() == Method::GET {
+        PasswordResetForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match PasswordResetForm::from_request(&mut request).await? {
+            FormResult::Ok(form) => {
+                let generator = app.token_generator(&request);
+                for user in DatabaseUser::get_by_email(&database, &form.email).await? {
+                    if !user.is_active() {
+                        continue;
+                    }
+                    let Some(token) = generator.make_token(&user) else {
+                        continue;
+                    };
+
+                    let path = reverse!(
+                        base_context.urls,
+                        "password_reset_confirm",
+                        user_id = user.id(),
+                        token = token
+                    )?;
+                    let link = format!("{}{path}", app.site_url);
+                    let message = EmailMessage::builder()
+                        .from(app.from_email.clone())
+                        .to(vec![form.email.clone()])
+                        .subject(app.subject.clone())
+                        .body(app.email_body(&user, &link))
+                        .build()?;
+                    email.send(message).await?;
+                }
+
+                return Ok(reverse_redirect!(base_context.urls, "password_reset_done")?);
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    };
+
+    let template = PasswordResetTemplate {
+        ctx: &base_context,
+        form: form_context,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+async fn password_reset_done() -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_reset_done.html")]
+    struct PasswordResetDoneTemplate;
+
+    Ok(Html::new(PasswordResetDoneTemplate.render()?))
+}
+
+async fn password_reset_confirm(
+    app: Arc<PasswordResetApp>,
+    base_context: BaseContext,
+    database: Database,
+    Path((user_id, token)): Path<(i64, String)>,
+    mut request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_reset_confirm.html")]
+    struct PasswordResetConfirmTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <SetPasswordForm as Form>::Context,
+    }
+
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_reset_invalid.html")]
+    struct PasswordResetInvalidTemplate<'a> {
+        ctx: &'a BaseContext,
+    }
+
+    let generator = app.token_generator(&request);
+    let user = DatabaseUser::get_by_id(&database, user_id)
+        .await?
+        .filter(|user| user.is_active() && generator.check_token(user, &token));
+    let Some(mut user) = user else {
+        let template = PasswordResetInvalidTemplate { ctx: &base_context };
+        return Html::new(template.render()?).into_response();
+    };
+
+    let form_context = if request.method() == Method::GET {
+        SetPasswordForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match SetPasswordForm::from_request(&mut request).await? {
+            FormResult::Ok(form) if form.password.as_str() == form.password_confirm.as_str() => {
+                let validators = PasswordValidators::from_config(request.context().config());
+                match validators.validate_form_field(&form.password, Some(user.username())) {
+                    Ok(()) => {
+                        user.set_password(&form.password);
+                        user.save(&database).await?;
+
+                        return Ok(reverse_redirect!(
+                            base_context.urls,
+                            "password_reset_complete"
+                        )?);
+                    }
+                    Err(error) => {
+                        let mut context = SetPasswordForm::build_context(&mut request).await?;
+                        context.add_error(FormErrorTarget::Field("password"), error);
+                        context
+                    }
+                }
+            }
+            FormResult::Ok(_) => {
+                let mut context = SetPasswordForm::build_context(&mut request).await?;
+                context.add_error(
+                    FormErrorTarget::Field("password_confirm"),
+                    FormFieldValidationError::from_static("The passwords do not match"),
+                );
+                context
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    };
+
+    let template = PasswordResetConfirmTemplate {
+        ctx: &base_context,
+        form: form_context,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+async fn password_reset_complete() -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_reset_complete.html")]
+    struct PasswordResetCompleteTemplate;
+
+    Ok(Html::new(PasswordResetCompleteTemplate.render()?))
+}
+
+async fn password_change(
+    base_context: BaseContext,
+    auth: Auth,
+    database: Database,
+    mut request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_change.html")]
+    struct PasswordChangeTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <PasswordChangeForm as Form>::Context,
+    }
+
+    let user_id = auth.user().id().and_then(|id| id.as_int());
+    let user = match user_id {
+        Some(user_id) => DatabaseUser::get_by_id(&database, user_id).await?,
+        None => None,
+    };
+    let Some(mut user) = user else {
+        return Err(AuthenticationRequired::new().into());
+    };
+
+    let form_context = if request.method() == Method::GET {
+        PasswordChangeForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match PasswordChangeForm::from_request(&mut request).await? {
+            FormResult::Ok(form) => {
+                let credentials =
+                    DatabaseUserCredentials::new(user.username().to_owned(), form.old_password);
+                let validators = PasswordValidators::from_config(request.context().config());
+                // the current password is checked through `Auth` so that the
+                // attempts to guess it are throttled just like the logins
+                let old_password_error = match auth.authenticate(&credentials).await {
+                    Ok(Some(_)) => None,
+                    Ok(None) => Some(FormFieldValidationError::from_static(
+                        "The current password is incorrect",
+                    )),
+                    Err(AuthError::TooManyAttempts { .. }) => {
+                        Some(FormFieldValidationError::from_static(
+                            "Too many failed attempts. Please try again later.",
+                        ))
+                    }
+                    Err(error) => return Err(error.into()),
+                };
+                let error = if let Some(error) = old_password_error {
+                    Some(("old_password", error))
+                } else if form.password.as_str() != form.password_confirm.as_str() {
+                    Some((
+                        "password_confirm",
+                        FormFieldValidationError::from_static("The passwords do not match"),
+                    ))
+                } else {
+                    validators
+                        .validate_form_field(&form.password, Some(user.username()))
+                        .err()
+                        .map(|error| ("password", error))
+                };
+
+                match error {
+                    None => {
+                        user.set_password(&form.password);
+                        user.save(&database).await?;
+                        auth.update_session_auth_hash(Box::new(user)).await?;
+
+                        return Ok(reverse_redirect!(
+                            base_context.urls,
+                            "password_change_done"
+                        )?);
+                    }
+                    Some((field, error)) => {
+                        let mut context = PasswordChangeForm::build_context(&mut request).await?;
+                        context.add_error(FormErrorTarget::Field(field), error);
+                        context
+                    }
+                }
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    };
+
+    let template = PasswordChangeTemplate {
+        ctx: &base_context,
+        form: form_context,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+async fn password_change_done() -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/password_change_done.html")]
+    struct PasswordChangeDoneTemplate;
+
+    Ok(Html::new(PasswordChangeDoneTemplate.render()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use cot_core::StatusCode;
+
+    use super::*;
+    use crate::auth::db::DatabaseUserBackend;
+    use crate::auth::password_validation::PasswordValidatorConfig;
+    use crate::config::{ProjectConfig, SecretKey};
+    use crate::email::transport::{Transport, TransportResult};
+    use crate::session::Session;
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    #[derive(Debug, Clone, Default)]
+    struct TestTransport {
+        messages: Arc<Mutex<Vec<EmailMessage>>>,
+    }
+
+    impl Transport for TestTransport {
+        async fn send(&self, messages: &[EmailMessage]) -> TransportResult<()> {
+            self.messages.lock().unwrap().extend_from_slice(messages);
+            Ok(())
+        }
+    }
+
+    fn test_app() -> PasswordResetApp {
+        PasswordResetApp::new(
+            "https://example.com/",
+            Email::try_from("no-reply@example.com").unwrap(),
+        )
+    }
+
+    async fn build_request(builder: &mut TestRequestBuilder, db: &TestDatabase) -> Request {
+        builder
+            .router(test_app().router())
+            .with_db_auth(db.database())
+            .await;
+        let mut request = builder.build();
+        let csrf_token = CsrfToken::from_session(Session::from_request(&request))
+            .await
+            .unwrap();
+        request.extensions_mut().insert(csrf_token);
+        request
+    }
+
+    async fn create_user(db: &TestDatabase, email: &str) -> DatabaseUser {
+        let mut user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from(email).unwrap()));
+        user.save(&db.database()).await.unwrap();
+        user
+    }
+
+    async fn response_body(response: Response) -> String {
+        String::from_utf8(response.into_body().into_bytes().await.unwrap().to_vec()).unwrap()
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_sends_email() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        create_user(&db, "user@example.com").await;
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&[("email", "user@example.com")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(transport.messages.lock().unwrap().len(), 1);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_confirm_validates_password() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = create_user(&db, "user@example.com").await;
+        let token = PasswordResetTokenGenerator::from_config(&ProjectConfig::default())
+            .make_token(&user)
+            .unwrap();
+        let url = format!("/{}/{token}/", user.id());
+        let config = ProjectConfig::builder()
+            .password_validators(PasswordValidatorConfig::recommended())
+            .build();
+
+        let request = build_request(
+            TestRequestBuilder::post(&url)
+                .config(config)
+                .form_data(&[("password", "qwerty123"), ("password_confirm", "qwerty123")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response_body(response)
+                .await
+                .contains("This password is too common.")
+        );
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_owned(), Password::new("password123"));
+        let user = DatabaseUser::authenticate(&db.database(), &credentials)
+            .await
+            .unwrap();
+        assert!(user.is_some());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_unknown_email() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        create_user(&db, "user@example.com").await;
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&[("email", "other@example.com")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        // the response is the same, so that the registered addresses are not disclosed
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(transport.messages.lock().unwrap().is_empty());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_reset_confirm() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = create_user(&db, "user@example.com").await;
+        let token = PasswordResetTokenGenerator::from_config(&ProjectConfig::default())
+            .make_token(&user)
+            .unwrap();
+        let url = format!("/{}/{token}/", user.id());
+
+        let request = build_request(&mut TestRequestBuilder::get(&url), &db).await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response_body(response).await.contains("Enter new password"));
+
+        let request = build_request(
+            TestRequestBuilder::post(&url)
+                .form_data(&[("password", "new_password"), ("password_confirm", "typo")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert!(
+            response_body(response)
+                .await
+                .contains("The passwords do not match")
+        );
+
+        let request = build_request(
+            TestRequestBuilder::post(&url).form_data(&[
+                ("password", "new_password"),
+                ("password_confirm", "new_password"),
+            ]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_owned(), Password::new("new_password"));
+        let user = DatabaseUser::authenticate(&db.database(), &credentials)
+            .await
+            .unwrap();
+        assert!(user.is_some());
+
+        // the token is single-use
+        let request = build_request(&mut TestRequestBuilder::get(&url), &db).await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert!(
+            response_body(response)
+                .await
+                .contains("Password reset unsuccessful")
+        );
+    }
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_change_requires_login() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+
+        let request = build_request(&mut TestRequestBuilder::get("/change/"), &db).await;
+        let error = test_app().router().handle(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_change() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = create_user(&db, "user@example.com").await;
+
+        let request = build_request(
+            TestRequestBuilder::post("/change/").form_data(&[
+                ("old_password", "wrong_password"),
+                ("password", "new_password"),
+                ("password_confirm", "new_password"),
+            ]),
+            &db,
+        )
+        .await;
+        request
+            .extensions()
+            .get::<Auth>()
+            .unwrap()
+            .login(Box::new(user.clone()))
+            .await
+            .unwrap();
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response_body(response)
+                .await
+                .contains("The current password is incorrect")
+        );
+
+        let request = build_request(
+            TestRequestBuilder::post("/change/").form_data(&[
+                ("old_password", "password123"),
+                ("password", "new_password"),
+                ("password_confirm", "new_password"),
+            ]),
+            &db,
+        )
+        .await;
+        request
+            .extensions()
+            .get::<Auth>()
+            .unwrap()
+            .login(Box::new(user))
+            .await
+            .unwrap();
+        let session = Session::from_request(&request).clone();
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        // the user stays logged in in the current session
+        let auth = Auth::new(
+            session,
+            Arc::new(DatabaseUserBackend::new(db.database())),
+            SecretKey::from("000000"),
+            &[],
+        )
+        .await
+        .unwrap();
+        assert!(auth.user().is_authenticated());
+
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_owned(), Password::new("new_password"));
+        let user = DatabaseUser::authenticate(&db.database(), &credentials)
+            .await
+            .unwrap();
+        assert!(user.is_some());
+    }
+
+    #[cot::test]
+    #[cfg(feature = "cache")]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn password_change_throttled() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = create_user(&db, "user@example.com").await;
+        let cache = crate::test::TestCache::new_memory();
+        let config = ProjectConfig::builder()
+            .login_throttle(
+                crate::config::LoginThrottleConfig::builder()
+                    .enabled(true)
+                    .max_attempts(2)
+                    .build(),
+            )
+            .build();
+
+        let change_password = async |old_password: &str| {
+            let mut request = build_request(
+                TestRequestBuilder::post("/change/")
+                    .config(config.clone())
+                    .cache(cache.cache())
+                    .form_data(&[
+                        ("old_password", old_password),
+                        ("password", "new_password"),
+                        ("password_confirm", "new_password"),
+                    ]),
+                &db,
+            )
+            .await;
+            // unlike the one set up by the request builder, this `Auth` object
+            // uses the throttle configured for the project
+            let auth = Auth::from_request(&mut request).await.unwrap();
+            auth.login(Box::new(user.clone())).await.unwrap();
+            request.extensions_mut().insert(auth);
+            test_app().router().handle(request).await.unwrap()
+        };
+
+        for _ in 0..2 {
+            let response = change_password("wrong_password").await;
+            assert!(
+                response_body(response)
+                    .await
+                    .contains("The current password is incorrect")
+            );
+        }
+
+        // the correct password is rejected as well once the limit is reached
+        let response = change_password("password123").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response_body(response)
+                .await
+                .contains("Too many failed attempts")
+        );
+        let credentials =
+            DatabaseUserCredentials::new("testuser".to_owned(), Password::new("password123"));
+        assert!(
+            DatabaseUser::authenticate(&db.database(), &credentials)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,923 @@
+//! Password validation.
+//!
+//! This module provides the [`PasswordValidator`] trait, which can be used to
+//! reject passwords that are too weak, along with a few built-in validators:
+//!
+//! * [`MinimumLengthValidator`] – rejects passwords that are too short,
+//! * [`UsernameSimilarityValidator`] – rejects passwords that are too similar
+//!   to the username,
+//! * [`NumericPasswordValidator`] – rejects passwords consisting of digits
+//!   only,
+//! * [`CommonPasswordValidator`] – rejects passwords from a list of commonly
+//!   used passwords. The built-in list is short, see the validator's docs for
+//!   how to use a more comprehensive one.
+//!
+//! The validators used by the project are configured with
+//! [`ProjectConfig::password_validators`](crate::config::ProjectConfig::password_validators),
+//! and can be obtained with [`PasswordValidators::from_config`]. They are
+//! applied by the
+//! [`PasswordResetApp`](crate::auth::password_reset::PasswordResetApp)
+//! when setting a new password, and can be used when creating users with
+//! [`DatabaseUser::create_user_validated`](crate::auth::db::DatabaseUser::create_user_validated),
+//! or in any form with [`PasswordValidators::validate_form_field`].
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, LazyLock};
+
+use derive_more::with_trait::Debug;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::common_types::Password;
+use crate::config::ProjectConfig;
+use crate::form::FormFieldValidationError;
+
+/// The default minimum length of a password used by
+/// [`MinimumLengthValidator`].
+pub const DEFAULT_MINIMUM_PASSWORD_LENGTH: usize = 8;
+
+/// The default maximum similarity between a password and a username used by
+/// [`UsernameSimilarityValidator`].
+pub const DEFAULT_MAX_SIMILARITY: f64 = 0.7;
+
+/// Only this many characters of the password and the username are compared
+/// by [`UsernameSimilarityValidator`], so that very long passwords can't be
+/// used to make the server spend a lot of time on the comparison.
+const MAX_SIMILARITY_COMPARED_LENGTH: usize = 128;
+
+static COMMON_PASSWORDS: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    include_str!("password_validation/common_passwords.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+});
+
+/// An error returned when a password doesn't pass the validation.
+///
+/// The error contains the messages returned by all the validators the
+/// password has failed.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::PasswordValidationError;
+///
+/// let error = PasswordValidationError::new("This password is too common.");
+/// assert_eq!(error.messages(), ["This password is too common."]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct PasswordValidationError {
+    messages: Vec<Cow<'static, str>>,
+}
+
+impl PasswordValidationError {
+    /// Creates a new password validation error with the given message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidationError;
+    ///
+    /// let error = PasswordValidationError::new("This password is too short.");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<Cow<'static, str>>>(message: T) -> Self {
+        Self {
+            messages: vec![message.into()],
+        }
+    }
+
+    /// Returns the messages describing why the password is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidationError;
+    ///
+    /// let error = PasswordValidationError::new("This password is too short.");
+    /// assert_eq!(error.messages(), ["This password is too short."]);
+    /// ```
+    #[must_use]
+    pub fn messages(&self) -> &[Cow<'static, str>] {
+        &self.messages
+    }
+}
+
+impl Display for PasswordValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.messages.join(" "))
+    }
+}
+
+impl From<PasswordValidationError> for FormFieldValidationError {
+    fn from(error: PasswordValidationError) -> Self {
+        FormFieldValidationError::from_string(error.to_string())
+    }
+}
+
+/// A validator that checks whether a password is strong enough.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{PasswordValidationError, PasswordValidator};
+/// use cot::common_types::Password;
+///
+/// struct NoSpacesValidator;
+///
+/// impl PasswordValidator for NoSpacesValidator {
+///     fn validate(
+///         &self,
+///         password: &Password,
+///         _username: Option<&str>,
+///     ) -> Result<(), PasswordValidationError> {
+///         if password.as_str().contains(' ') {
+///             return Err(PasswordValidationError::new(
+///                 "This password contains spaces.",
+///             ));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// assert!(
+///     NoSpacesValidator
+///         .validate(&Password::new("my password"), None)
+///         .is_err()
+/// );
+/// ```
+pub trait PasswordValidator: Send + Sync {
+    /// Validates the password.
+    ///
+    /// The username of the user the password is set for is passed, if known,
+    /// so that the validator can check the password against it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the problem if the password is invalid.
+    fn validate(
+        &self,
+        password: &Password,
+        username: Option<&str>,
+    ) -> Result<(), PasswordValidationError>;
+}
+
+/// A validator that rejects the passwords shorter than the given number of
+/// characters.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{MinimumLengthValidator, PasswordValidator};
+/// use cot::common_types::Password;
+///
+/// let validator = MinimumLengthValidator::new(10);
+/// assert!(validator.validate(&Password::new("short"), None).is_err());
+/// assert!(
+///     validator
+///         .validate(&Password::new("long enough"), None)
+///         .is_ok()
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MinimumLengthValidator {
+    min_length: usize,
+}
+
+impl MinimumLengthValidator {
+    /// Creates a new validator that rejects the passwords shorter than
+    /// `min_length` characters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::MinimumLengthValidator;
+    ///
+    /// let validator = MinimumLengthValidator::new(12);
+    /// ```
+    #[must_use]
+    pub const fn new(min_length: usize) -> Self {
+        Self { min_length }
+    }
+}
+
+impl Default for MinimumLengthValidator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MINIMUM_PASSWORD_LENGTH)
+    }
+}
+
+impl PasswordValidator for MinimumLengthValidator {
+    fn validate(
+        &self,
+        password: &Password,
+        _username: Option<&str>,
+    ) -> Result<(), PasswordValidationError> {
+        if password.as_str().chars().count() < self.min_length {
+            return Err(PasswordValidationError::new(format!(
+                "This password is too short. It must contain at least {} characters.",
+                self.min_length
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A validator that rejects the passwords that are too similar to the
+/// username.
+///
+/// The password is compared (case-insensitively) to the whole username, as
+/// well as to its parts separated by non-alphanumeric characters (so that,
+/// for instance, `john.smith@example.com` is checked against `john`, `smith`,
+/// `example` and `com` as well). The similarity is a number between 0 and 1,
+/// where 1 means that the strings are identical.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{PasswordValidator, UsernameSimilarityValidator};
+/// use cot::common_types::Password;
+///
+/// let validator = UsernameSimilarityValidator::new();
+/// assert!(
+///     validator
+///         .validate(&Password::new("johnsmith1"), Some("johnsmith"))
+///         .is_err()
+/// );
+/// assert!(
+///     validator
+///         .validate(&Password::new("correct horse"), Some("johnsmith"))
+///         .is_ok()
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UsernameSimilarityValidator {
+    max_similarity: f64,
+}
+
+impl UsernameSimilarityValidator {
+    /// Creates a new validator with the [default maximum
+    /// similarity](DEFAULT_MAX_SIMILARITY).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::UsernameSimilarityValidator;
+    ///
+    /// let validator = UsernameSimilarityValidator::new();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_similarity: DEFAULT_MAX_SIMILARITY,
+        }
+    }
+
+    /// Sets the maximum similarity between the password and the username. The
+    /// passwords with the similarity equal or greater than this value are
+    /// rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::UsernameSimilarityValidator;
+    ///
+    /// let validator = UsernameSimilarityValidator::new().max_similarity(0.5);
+    /// ```
+    #[must_use]
+    pub const fn max_similarity(mut self, max_similarity: f64) -> Self {
+        self.max_similarity = max_similarity;
+        self
+    }
+}
+
+impl Default for UsernameSimilarityValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PasswordValidator for UsernameSimilarityValidator {
+    fn validate(
+        &self,
+        password: &Password,
+        username: Option<&str>,
+    ) -> Result<(), PasswordValidationError> {
+        let Some(username) = username else {
+            return Ok(());
+        };
+        let password = password.as_str().to_lowercase();
+        let username = username.to_lowercase();
+
+        let too_similar = std::iter::once(username.as_str())
+            .chain(username.split(|c: char| !c.is_alphanumeric()))
+            .filter(|part| !part.is_empty())
+            .any(|part| similarity(&password, part) >= self.max_similarity);
+        if too_similar {
+            return Err(PasswordValidationError::new(
+                "This password is too similar to the username.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the similarity of two strings, calculated as the doubled length of
+/// their longest common subsequence divided by the total length of both
+/// strings.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().take(MAX_SIMILARITY_COMPARED_LENGTH).collect();
+    let b: Vec<char> = b.chars().take(MAX_SIMILARITY_COMPARED_LENGTH).collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let mut previous = vec![0_usize; b.len() + 1];
+    let mut current = vec![0_usize; b.len() + 1];
+    for a_char in &a {
+        for (j, b_char) in b.iter().enumerate() {
+            current[j + 1] = if a_char == b_char {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    #[expect(clippy::cast_precision_loss)] // the lengths are at most 128
+    let similarity = (2 * previous[b.len()]) as f64 / (a.len() + b.len()) as f64;
+    similarity
+}
+
+/// A validator that rejects the passwords consisting of digits only.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{NumericPasswordValidator, PasswordValidator};
+/// use cot::common_types::Password;
+///
+/// let validator = NumericPasswordValidator;
+/// assert!(
+///     validator
+///         .validate(&Password::new("20240101"), None)
+///         .is_err()
+/// );
+/// assert!(
+///     validator
+///         .validate(&Password::new("2024-01-01"), None)
+///         .is_ok()
+/// );
+/// ```
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NumericPasswordValidator;
+
+impl PasswordValidator for NumericPasswordValidator {
+    fn validate(
+        &self,
+        password: &Password,
+        _username: Option<&str>,
+    ) -> Result<(), PasswordValidationError> {
+        let password = password.as_str();
+        if !password.is_empty() && password.chars().all(|c| c.is_ascii_digit()) {
+            return Err(PasswordValidationError::new(
+                "This password is entirely numeric.",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A validator that rejects the commonly used passwords.
+///
+/// By default, a built-in list of a couple hundred of the most common
+/// passwords is used. The comparison is case-insensitive.
+///
+/// Note that the built-in list only catches the most obvious passwords and is
+/// not meant to be exhaustive: it is kept short so that it doesn't make the
+/// binary noticeably larger. Projects that need a stronger protection should
+/// load a comprehensive list (such as one of the lists of leaked passwords
+/// published by security researchers, which contain tens of thousands of
+/// entries) and pass it to [`with_passwords`](Self::with_passwords).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{CommonPasswordValidator, PasswordValidator};
+/// use cot::common_types::Password;
+///
+/// let validator = CommonPasswordValidator::new();
+/// assert!(
+///     validator
+///         .validate(&Password::new("Password1"), None)
+///         .is_err()
+/// );
+/// assert!(
+///     validator
+///         .validate(&Password::new("correct horse battery staple"), None)
+///         .is_ok()
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommonPasswordValidator {
+    #[debug("..")]
+    passwords: Option<Arc<HashSet<String>>>,
+}
+
+impl CommonPasswordValidator {
+    /// Creates a new validator that uses the built-in list of common
+    /// passwords.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::CommonPasswordValidator;
+    ///
+    /// let validator = CommonPasswordValidator::new();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { passwords: None }
+    }
+
+    /// Creates a new validator that uses the given list of common passwords
+    /// instead of the built-in one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::{CommonPasswordValidator, PasswordValidator};
+    /// use cot::common_types::Password;
+    ///
+    /// let validator = CommonPasswordValidator::with_passwords(["cot", "rust"]);
+    /// assert!(validator.validate(&Password::new("Rust"), None).is_err());
+    /// ```
+    #[must_use]
+    pub fn with_passwords<I, T>(passwords: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let passwords = passwords
+            .into_iter()
+            .map(|password| password.as_ref().trim().to_lowercase())
+            .collect();
+        Self {
+            passwords: Some(Arc::new(passwords)),
+        }
+    }
+
+    fn passwords(&self) -> &HashSet<String> {
+        self.passwords.as_deref().unwrap_or(&COMMON_PASSWORDS)
+    }
+}
+
+impl Default for CommonPasswordValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PasswordValidator for CommonPasswordValidator {
+    fn validate(
+        &self,
+        password: &Password,
+        _username: Option<&str>,
+    ) -> Result<(), PasswordValidationError> {
+        let password = password.as_str().trim().to_lowercase();
+        if self.passwords().contains(&password) {
+            return Err(PasswordValidationError::new("This password is too common."));
+        }
+
+        Ok(())
+    }
+}
+
+/// The configuration of a single built-in password validator.
+///
+/// It is used as part of the
+/// [`ProjectConfig::password_validators`](crate::config::ProjectConfig::password_validators)
+/// list.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::PasswordValidatorConfig;
+///
+/// let config = PasswordValidatorConfig::MinimumLength { min_length: 12 };
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [[password_validators]]
+/// type = "minimum_length"
+/// min_length = 12
+///
+/// [[password_validators]]
+/// type = "username_similarity"
+/// max_similarity = 0.7
+///
+/// [[password_validators]]
+/// type = "numeric"
+///
+/// [[password_validators]]
+/// type = "common"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PasswordValidatorConfig {
+    /// [`MinimumLengthValidator`].
+    MinimumLength {
+        /// The minimum number of characters in a password. The default is
+        /// [`DEFAULT_MINIMUM_PASSWORD_LENGTH`].
+        #[serde(default = "default_min_length")]
+        min_length: usize,
+    },
+    /// [`UsernameSimilarityValidator`].
+    UsernameSimilarity {
+        /// The maximum similarity between the password and the username. The
+        /// default is [`DEFAULT_MAX_SIMILARITY`].
+        #[serde(default = "default_max_similarity")]
+        max_similarity: f64,
+    },
+    /// [`NumericPasswordValidator`].
+    Numeric,
+    /// [`CommonPasswordValidator`] with the built-in list of common
+    /// passwords, which is short; see the validator's docs for how to use a
+    /// more comprehensive list.
+    Common,
+}
+
+fn default_min_length() -> usize {
+    DEFAULT_MINIMUM_PASSWORD_LENGTH
+}
+
+fn default_max_similarity() -> f64 {
+    DEFAULT_MAX_SIMILARITY
+}
+
+impl PasswordValidatorConfig {
+    /// Returns the list of validators recommended for most projects: all the
+    /// built-in validators with their default settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidatorConfig;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::builder()
+    ///     .password_validators(PasswordValidatorConfig::recommended())
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn recommended() -> Vec<Self> {
+        vec![
+            Self::MinimumLength {
+                min_length: DEFAULT_MINIMUM_PASSWORD_LENGTH,
+            },
+            Self::UsernameSimilarity {
+                max_similarity: DEFAULT_MAX_SIMILARITY,
+            },
+            Self::Numeric,
+            Self::Common,
+        ]
+    }
+
+    fn to_validator(&self) -> Arc<dyn PasswordValidator> {
+        match *self {
+            Self::MinimumLength { min_length } => Arc::new(MinimumLengthValidator::new(min_length)),
+            Self::UsernameSimilarity { max_similarity } => {
+                Arc::new(UsernameSimilarityValidator::new().max_similarity(max_similarity))
+            }
+            Self::Numeric => Arc::new(NumericPasswordValidator),
+            Self::Common => Arc::new(CommonPasswordValidator::new()),
+        }
+    }
+}
+
+/// A list of password validators.
+///
+/// A password is valid if it passes all the validators in the list; an empty
+/// list accepts any password.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::password_validation::{
+///     MinimumLengthValidator, NumericPasswordValidator, PasswordValidators,
+/// };
+/// use cot::common_types::Password;
+///
+/// let validators = PasswordValidators::new()
+///     .validator(MinimumLengthValidator::new(8))
+///     .validator(NumericPasswordValidator);
+///
+/// let error = validators
+///     .validate(&Password::new("1234"), None)
+///     .unwrap_err();
+/// assert_eq!(error.messages().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PasswordValidators {
+    #[debug("..")]
+    validators: Vec<Arc<dyn PasswordValidator>>,
+}
+
+impl PasswordValidators {
+    /// Creates an empty list of password validators, which accepts any
+    /// password.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidators;
+    ///
+    /// let validators = PasswordValidators::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the list of password validators configured in the project
+    /// config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::{PasswordValidatorConfig, PasswordValidators};
+    /// use cot::common_types::Password;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::builder()
+    ///     .password_validators(PasswordValidatorConfig::recommended())
+    ///     .build();
+    /// let validators = PasswordValidators::from_config(&config);
+    /// assert!(validators.validate(&Password::new("qwerty"), None).is_err());
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self {
+            validators: config
+                .password_validators
+                .iter()
+                .map(PasswordValidatorConfig::to_validator)
+                .collect(),
+        }
+    }
+
+    /// Adds a validator to the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::{CommonPasswordValidator, PasswordValidators};
+    ///
+    /// let validators = PasswordValidators::new().validator(CommonPasswordValidator::new());
+    /// ```
+    #[must_use]
+    pub fn validator<V: PasswordValidator + 'static>(mut self, validator: V) -> Self {
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Returns whether the list contains no validators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidators;
+    ///
+    /// assert!(PasswordValidators::new().is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Validates the password with all the validators in the list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error containing the messages from all the validators the
+    /// password has failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::{PasswordValidators, UsernameSimilarityValidator};
+    /// use cot::common_types::Password;
+    ///
+    /// let validators = PasswordValidators::new().validator(UsernameSimilarityValidator::new());
+    /// assert!(
+    ///     validators
+    ///         .validate(&Password::new("admin1"), Some("admin"))
+    ///         .is_err()
+    /// );
+    /// ```
+    pub fn validate(
+        &self,
+        password: &Password,
+        username: Option<&str>,
+    ) -> Result<(), PasswordValidationError> {
+        let messages: Vec<_> = self
+            .validators
+            .iter()
+            .filter_map(|validator| validator.validate(password, username).err())
+            .flat_map(|error| error.messages)
+            .collect();
+
+        if messages.is_empty() {
+            Ok(())
+        } else {
+            Err(PasswordValidationError { messages })
+        }
+    }
+
+    /// Validates the password entered in a form field.
+    ///
+    /// This is the same as [`validate`](Self::validate), but returns a
+    /// [`FormFieldValidationError`], so that it can be added to a form context
+    /// with [`FormContext::add_error`](crate::form::FormContext::add_error).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error containing the messages from all the validators the
+    /// password has failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidators;
+    /// use cot::common_types::Password;
+    /// use cot::form::{Form, FormContext, FormErrorTarget, FormResult};
+    /// use cot::request::{Request, RequestExt};
+    ///
+    /// #[derive(Form)]
+    /// struct SignupForm {
+    ///     username: String,
+    ///     password: Password,
+    /// }
+    ///
+    /// async fn signup(mut request: Request) -> cot::Result<()> {
+    ///     let validators = PasswordValidators::from_config(request.context().config());
+    ///
+    ///     if let FormResult::Ok(form) = SignupForm::from_request(&mut request).await? {
+    ///         if let Err(error) = validators.validate_form_field(&form.password, Some(&form.username))
+    ///         {
+    ///             let mut context = SignupForm::build_context(&mut request).await?;
+    ///             context.add_error(FormErrorTarget::Field("password"), error);
+    ///             // render the form with the errors...
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn validate_form_field(
+        &self,
+        password: &Password,
+        username: Option<&str>,
+    ) -> Result<(), FormFieldValidationError> {
+        self.validate(password, username).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate<V: PasswordValidator>(validator: &V, password: &str, username: &str) -> bool {
+        validator
+            .validate(&Password::new(password), Some(username))
+            .is_ok()
+    }
+
+    #[test]
+    fn minimum_length() {
+        let validator = MinimumLengthValidator::default();
+
+        assert!(!validate(&validator, "1234567", "user"));
+        assert!(validate(&validator, "12345678", "user"));
+        // characters, not bytes, are counted
+        assert!(!validate(&validator, "zażółć", "user"));
+    }
+
+    #[test]
+    fn username_similarity() {
+        let validator = UsernameSimilarityValidator::new();
+
+        assert!(!validate(&validator, "johnsmith", "johnsmith"));
+        assert!(!validate(&validator, "JohnSmith1", "johnsmith"));
+        assert!(!validate(&validator, "smith123", "john.smith@example.com"));
+        assert!(validate(&validator, "correct horse battery", "johnsmith"));
+        assert!(
+            validator
+                .validate(&Password::new("johnsmith"), None)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn username_similarity_max_similarity() {
+        let strict = UsernameSimilarityValidator::new().max_similarity(0.3);
+        let lenient = UsernameSimilarityValidator::new().max_similarity(1.0);
+
+        assert!(!validate(&strict, "john1234", "johnsmith"));
+        assert!(validate(&lenient, "john1234", "johnsmith"));
+        assert!(!validate(&lenient, "johnsmith", "johnsmith"));
+    }
+
+    #[test]
+    fn similarity_ratio() {
+        assert!((similarity("abcd", "abcd") - 1.0).abs() < f64::EPSILON);
+        assert!((similarity("abcd", "wxyz")).abs() < f64::EPSILON);
+        assert!((similarity("abcd", "abxy") - 0.5).abs() < f64::EPSILON);
+        assert!((similarity("", "") - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn numeric() {
+        let validator = NumericPasswordValidator;
+
+        assert!(!validate(&validator, "0123456789", "user"));
+        assert!(validate(&validator, "0123456789a", "user"));
+        assert!(validate(&validator, "", "user"));
+    }
+
+    #[test]
+    fn common() {
+        let validator = CommonPasswordValidator::new();
+
+        assert!(!validate(&validator, "password", "user"));
+        assert!(!validate(&validator, "QWERTY", "user"));
+        assert!(validate(&validator, "7ZbG4eyq2Tgv", "user"));
+
+        let validator = CommonPasswordValidator::with_passwords(["Cot"]);
+        assert!(!validate(&validator, "cot", "user"));
+        assert!(validate(&validator, "password", "user"));
+    }
+
+    #[test]
+    fn validators_from_config() {
+        let config = ProjectConfig::builder()
+            .password_validators(PasswordValidatorConfig::recommended())
+            .build();
+        let validators = PasswordValidators::from_config(&config);
+
+        let error = validators
+            .validate(&Password::new("1234"), Some("user"))
+            .unwrap_err();
+        assert_eq!(
+            error.messages(),
+            [
+                "This password is too short. It must contain at least 8 characters.",
+                "This password is entirely numeric.",
+                "This password is too common.",
+            ]
+        );
+        assert!(
+            validators
+                .validate(&Password::new("correct horse battery staple"), Some("user"))
+                .is_ok()
+        );
+        assert!(
+            PasswordValidators::from_config(&ProjectConfig::default())
+                .validate(&Password::new("1"), Some("user"))
+                .is_err()
+        );
+        assert!(
+            PasswordValidators::from_config(
+                &ProjectConfig::builder().password_validators(vec![]).build()
+            )
+            .validate(&Password::new("1"), Some("user"))
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_form_field() {
+        let validators = PasswordValidators::new()
+            .validator(MinimumLengthValidator::new(4))
+            .validator(NumericPasswordValidator);
+
+        let error = validators
+            .validate_form_field(&Password::new("123"), None)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "This password is too short. It must contain at least 4 characters. \
+             This password is entirely numeric."
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! User registration and email verification.
+//!
+//! This module provides the [`EmailVerificationTokenGenerator`], which
+//! generates signed, expiring tokens that are sent to users to verify that
+//! they own the email address they have registered with. The tokens are bound
+//! to the current email address of the user and its verification status, so
+//! they become invalid once they are used or the email address is changed.
+//!
+//! With the `email` feature enabled, this module also provides [`SignupApp`],
+//! which contains ready-made views and forms for registering new
+//! [`DatabaseUser`]s and verifying their email addresses. The users who
+//! haven't verified their email address can be prevented from logging in with
+//! [`EmailVerificationConfig::required_for_login`](crate::config::EmailVerificationConfig::required_for_login).
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::auth::db::DatabaseUser;
+use crate::config::{ProjectConfig, SecretKey};
+
+const EMAIL_VERIFICATION_TOKEN_CONTEXT: &str = "cot.rs email verification token v1";
+
+/// The default time after which the email verification tokens expire.
+pub const DEFAULT_EMAIL_VERIFICATION_TIMEOUT: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Generator and validator of email verification tokens.
+///
+/// The tokens are created using a keyed hash of the user ID, the user's email
+/// address, whether it has already been verified, and the time the token was
+/// generated at. This means that the tokens:
+///
+/// * cannot be forged without knowing the project's [`SecretKey`],
+/// * expire after the [timeout](Self::timeout) passes,
+/// * become invalid once the email address is verified or changed, which makes
+///   them effectively single-use.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::db::DatabaseUser;
+/// use cot::auth::signup::EmailVerificationTokenGenerator;
+/// use cot::config::SecretKey;
+///
+/// fn verification_token(user: &DatabaseUser) -> Option<String> {
+///     let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"secret"));
+///     let token = generator.make_token(user)?;
+///     assert!(generator.check_token(user, &token));
+///     Some(token)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EmailVerificationTokenGenerator {
+    secret_key: SecretKey,
+    fallback_secret_keys: Vec<SecretKey>,
+    timeout: Duration,
+}
+
+impl EmailVerificationTokenGenerator {
+    /// Creates a new token generator that signs the tokens with the given
+    /// secret key.
+    ///
+    /// The tokens generated expire after
+    /// [`DEFAULT_EMAIL_VERIFICATION_TIMEOUT`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::EmailVerificationTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"secret"));
+    /// ```
+    #[must_use]
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            secret_key,
+            fallback_secret_keys: Vec::new(),
+            timeout: DEFAULT_EMAIL_VERIFICATION_TIMEOUT,
+        }
+    }
+
+    /// Creates a new token generator that uses the secret key, the fallback
+    /// secret keys, and the [email verification
+    /// timeout](crate::config::EmailVerificationConfig::timeout) from the
+    /// project config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::EmailVerificationTokenGenerator;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let generator = EmailVerificationTokenGenerator::from_config(&ProjectConfig::default());
+    /// ```
+    #[must_use]
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self::new(config.secret_key.clone())
+            .fallback_secret_keys(config.fallback_secret_keys.clone())
+            .timeout(config.email_verification.timeout)
+    }
+
+    /// Sets the secret keys that are accepted when checking the tokens, in
+    /// addition to the main secret key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::EmailVerificationTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"new secret"))
+    ///     .fallback_secret_keys(vec![SecretKey::new(b"old secret")]);
+    /// ```
+    #[must_use]
+    pub fn fallback_secret_keys(mut self, fallback_secret_keys: Vec<SecretKey>) -> Self {
+        self.fallback_secret_keys = fallback_secret_keys;
+        self
+    }
+
+    /// Sets the time after which the tokens expire.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::auth::signup::EmailVerificationTokenGenerator;
+    /// use cot::config::SecretKey;
+    ///
+    /// let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"secret"))
+    ///     .timeout(Duration::from_secs(60 * 60));
+    /// ```
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Generates an email verification token for the given user.
+    ///
+    /// Returns [`None`] if the user doesn't have an email address.
+    #[must_use]
+    pub fn make_token(&self, user: &DatabaseUser) -> Option<String> {
+        self.make_token_at(user, Utc::now().timestamp())
+    }
+
+    /// Checks whether the given token is a valid email verification token for
+    /// the given user.
+    ///
+    /// The token is valid if it was generated for this user and their current
+    /// email address with the current secret key (or one of the fallback
+    /// secret keys), the email address hasn't been verified since, and the
+    /// token hasn't expired yet.
+    #[must_use]
+    pub fn check_token(&self, user: &DatabaseUser, token: &str) -> bool {
+        self.check_token_at(user, token, Utc::now().timestamp())
+    }
+
+    fn make_token_at(&self, user: &DatabaseUser, timestamp: i64) -> Option<String> {
+        let hash = Self::token_hash(&self.secret_key, user, timestamp)?;
+
+        Some(format!("{timestamp:x}-{}", hash.to_hex()))
+    }
+
+    fn check_token_at(&self, user: &DatabaseUser, token: &str, now: i64) -> bool {
+        let Some((timestamp, hash)) = token.split_once('-') else {
+            return false;
+        };
+        let Ok(timestamp) = i64::from_str_radix(timestamp, 16) else {
+            return false;
+        };
+        let Ok(hash) = blake3::Hash::from_hex(hash) else {
+            return false;
+        };
+
+        let timeout = i64::try_from(self.timeout.as_secs()).unwrap_or(i64::MAX);
+        if timestamp > now || now - timestamp > timeout {
+            return false;
+        }
+
+        // `blake3::Hash` implements constant-time equality
+        std::iter::once(&self.secret_key)
+            .chain(&self.fallback_secret_keys)
+            .any(|secret_key| Self::token_hash(secret_key, user, timestamp) == Some(hash))
+    }
+
+    fn token_hash(
+        secret_key: &SecretKey,
+        user: &DatabaseUser,
+        timestamp: i64,
+    ) -> Option<blake3::Hash> {
+        let email = user.email()?.as_str();
+
+        let key = blake3::derive_key(EMAIL_VERIFICATION_TOKEN_CONTEXT, secret_key.as_bytes());
+        let mut hasher = blake3::Hasher::new_keyed(&key);
+        hasher.update(&user.id().to_le_bytes());
+        hasher.update(&(email.len() as u64).to_le_bytes());
+        hasher.update(email.as_bytes());
+        hasher.update(&[u8::from(user.is_email_verified())]);
+        hasher.update(&timestamp.to_le_bytes());
+
+        Some(hasher.finalize())
+    }
+}
+
+#[cfg(feature = "email")]
+mod app;
+#[cfg(feature = "email")]
+pub use app::{ResendVerificationEmailForm, SignupApp, SignupForm};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common_types::Email;
+    use crate::test::TestDatabase;
+
+    const NOW: i64 = 1_700_000_000;
+
+    async fn create_user(db: &TestDatabase) -> DatabaseUser {
+        let mut user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+        user
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn token_valid_until_verified() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = create_user(&db).await;
+        let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"secret"))
+            .timeout(Duration::from_secs(60));
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+
+        assert!(generator.check_token_at(&user, &token, NOW + 60));
+        assert!(!generator.check_token_at(&user, &token, NOW + 61));
+        assert!(!generator.check_token_at(&user, &token, NOW - 1));
+
+        user.set_email_verified(true);
+        assert!(!generator.check_token_at(&user, &token, NOW));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn token_invalid_after_email_change() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = create_user(&db).await;
+        let generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"secret"));
+        let other_generator = EmailVerificationTokenGenerator::new(SecretKey::new(b"other"));
+
+        let token = generator.make_token_at(&user, NOW).unwrap();
+        assert!(!other_generator.check_token_at(&user, &token, NOW));
+        assert!(!generator.check_token_at(&user, "zzz-abc", NOW));
+
+        user.set_email(Some(Email::try_from("other@example.com").unwrap()));
+        assert!(!generator.check_token_at(&user, &token, NOW));
+
+        user.set_email(None);
+        assert!(generator.make_token_at(&user, NOW).is_none());
+    }
+}
@@ -0,0 +1,901 @@
+use std::sync::Arc;
+
+use crate::auth::db::{DatabaseUser, MAX_USERNAME_LENGTH};
+use crate::auth::password_validation::PasswordValidators;
+use crate::auth::signup::EmailVerificationTokenGenerator;
+use crate::auth::throttle::LoginThrottle;
+use crate::common_types::{Email, Password};
+use crate::csrf::CsrfToken;
+use crate::db::{Database, LimitedString, Model, query};
+use crate::email::EmailMessage;
+use crate::error::MethodNotAllowed;
+use crate::form::{
+    Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError, FormResult,
+};
+use crate::html::Html;
+use crate::request::extractors::{FromRequestHead, Path};
+use crate::request::{Request, RequestExt};
+use crate::response::{IntoResponse, Response};
+use crate::router::{Route, Router, Urls};
+use crate::{App, Error, Method, Template, reverse, reverse_redirect};
+
+/// A form for registering a new user.
+///
+/// Used by the [`SignupApp`].
+#[derive(Debug, Form)]
+pub struct SignupForm {
+    /// The username of the new user.
+    pub username: LimitedString<MAX_USERNAME_LENGTH>,
+    /// The email address of the new user. A verification link is sent to it.
+    pub email: Email,
+    /// The password of the new user.
+    pub password: Password,
+    /// The password, repeated to avoid typos.
+    pub password_confirm: Password,
+}
+
+/// A form for requesting another email with the verification link.
+///
+/// Used by the [`SignupApp`].
+#[derive(Debug, Form)]
+pub struct ResendVerificationEmailForm {
+    /// The email address the user signed up with.
+    pub email: Email,
+}
+
+/// An app that implements the registration of new [`DatabaseUser`]s and the
+/// verification of their email addresses.
+///
+/// The app provides the following views:
+///
+/// * `signup` (`/`) – a form where the user chooses their username, email
+///   address and password. The password is checked with the [password
+///   validators](crate::config::ProjectConfig::password_validators) configured
+///   for the project, and the username has to be unused. Once the user is
+///   created, an email containing a verification link is sent to them using
+///   [`Email`](crate::email::Email). If the email can't be sent, the user is
+///   removed, so that they can sign up again. To avoid disclosing which email
+///   addresses are registered, signing up with an address that is already used
+///   doesn't show an error; instead, an email saying that there already is an
+///   account is sent to the address, and the user is redirected to the next
+///   view as usual.
+/// * `signup_done` (`/done/`) – a page asking the user to check their inbox.
+/// * `verify_email` (`/verify/{user_id}/{token}/`) – the page the link in the
+///   email points to, which marks the user's email address as
+///   [verified](DatabaseUser::is_email_verified). The token is generated and
+///   checked with the [`EmailVerificationTokenGenerator`].
+/// * `resend_verification_email` (`/resend/`) – a form where the user enters
+///   their email address to get another verification link, for instance when
+///   the previous one has expired. The link is sent to the active users with
+///   that address which haven't verified it yet. To avoid disclosing which
+///   email addresses are registered, the user is always redirected to the next
+///   view, regardless of whether any email was sent.
+///
+/// The number of emails sent to a single address, or requested from a single
+/// IP address, is limited with the [`LoginThrottle`] settings configured in
+/// [`ProjectConfig::login_throttle`](crate::config::ProjectConfig::login_throttle),
+/// even if the login throttling itself is disabled. Once the limit is reached,
+/// no more emails are sent until the lockout expires, but the user is still
+/// redirected to the next view.
+/// * `resend_verification_email_done` (`/resend/done/`) – a page saying that
+///   the email has been sent.
+///
+/// To prevent the users from logging in before they verify their email
+/// address, enable
+/// [`EmailVerificationConfig::required_for_login`](crate::config::EmailVerificationConfig::required_for_login).
+///
+/// The forms are protected against CSRF, so the
+/// [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) needs to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::signup::SignupApp;
+/// use cot::common_types::Email;
+/// use cot::project::RegisterAppsContext;
+/// use cot::{AppBuilder, Project};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+///         apps.register_with_views(
+///             SignupApp::new(
+///                 "https://example.com",
+///                 Email::try_from("no-reply@example.com").unwrap(),
+///             ),
+///             "/signup",
+///         );
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SignupApp {
+    site_url: String,
+    from_email: Email,
+    subject: String,
+    existing_account_subject: String,
+}
+
+impl SignupApp {
+    /// Creates a new signup app.
+    ///
+    /// `site_url` is the URL of the website (such as `https://example.com`)
+    /// that is used to create the absolute link sent in the emails. It is not
+    /// derived from the request, since the `Host` header can be forged by an
+    /// attacker to make the link point to a website they control.
+    ///
+    /// `from_email` is the address the emails are sent from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::SignupApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = SignupApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(site_url: T, from_email: Email) -> Self {
+        Self {
+            site_url: site_url.into().trim_end_matches('/').to_owned(),
+            from_email,
+            subject: "Verify your email address".to_owned(),
+            existing_account_subject: "You already have an account".to_owned(),
+        }
+    }
+
+    /// Sets the subject of the verification emails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::SignupApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = SignupApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// )
+    /// .subject("Welcome! Please confirm your email");
+    /// ```
+    #[must_use]
+    pub fn subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Sets the subject of the emails sent when someone signs up with an email
+    /// address that is already used by another account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::signup::SignupApp;
+    /// use cot::common_types::Email;
+    ///
+    /// let app = SignupApp::new(
+    ///     "https://example.com",
+    ///     Email::try_from("no-reply@example.com").unwrap(),
+    /// )
+    /// .existing_account_subject("Did you try to sign up again?");
+    /// ```
+    #[must_use]
+    pub fn existing_account_subject<T: Into<String>>(mut self, subject: T) -> Self {
+        self.existing_account_subject = subject.into();
+        self
+    }
+
+    fn existing_account_email_body(&self) -> String {
+        format!(
+            "Hello,\n\
+            \n\
+            Someone tried to sign up at {site_url} with this email address, but there \
+            already is an account using it. If it was you, you can log in to your existing \
+            account, or reset its password if you have forgotten it.\n\
+            \n\
+            If you didn't try to sign up, you can safely ignore this email.\n",
+            site_url = self.site_url,
+        )
+    }
+
+    /// Sends the email saying that there already is an account using the
+    /// given address, unless too many emails have been sent to it recently.
+    async fn send_existing_account_email(
+        &self,
+        email: &crate::email::Email,
+        request: &Request,
+        to: &Email,
+    ) -> crate::Result<()> {
+        if !Self::allow_email(request, to).await? {
+            return Ok(());
+        }
+
+        let message = EmailMessage::builder()
+            .from(self.from_email.clone())
+            .to(vec![to.clone()])
+            .subject(self.existing_account_subject.clone())
+            .body(self.existing_account_email_body())
+            .build()?;
+        email.send(message).await?;
+
+        Ok(())
+    }
+
+    /// Returns whether another email can be sent to the given address, and
+    /// records it if so.
+    async fn allow_email(request: &Request, to: &Email) -> crate::Result<bool> {
+        let throttle = LoginThrottle::emails(request);
+        let address = to.as_str().to_lowercase();
+        let ip = LoginThrottle::client_ip(request);
+        match throttle.check(Some(&address), ip).await {
+            Ok(()) => {}
+            Err(crate::auth::AuthError::TooManyAttempts { .. }) => return Ok(false),
+            Err(error) => return Err(error.into()),
+        }
+        throttle.record_failure(Some(&address), ip).await?;
+
+        Ok(true)
+    }
+
+    fn email_body(&self, user: &DatabaseUser, link: &str) -> String {
+        format!(
+            "Hello {username},\n\
+            \n\
+            Thank you for signing up at {site_url}. To verify your email address, open \
+            the following link:\n\
+            \n\
+            {link}\n\
+            \n\
+            If you didn't create an account, you can safely ignore this email.\n",
+            username = user.username(),
+            site_url = self.site_url,
+        )
+    }
+
+    async fn send_verification_email(
+        &self,
+        email: &crate::email::Email,
+        urls: &Urls,
+        request: &Request,
+        user: &DatabaseUser,
+    ) -> crate::Result<()> {
+        let generator = EmailVerificationTokenGenerator::from_config(request.project_config());
+        let (Some(to), Some(token)) = (user.email(), generator.make_token(user)) else {
+            return Ok(());
+        };
+
+        let path = reverse!(urls, "verify_email", user_id = user.id(), token = token)?;
+        let link = format!("{}{path}", self.site_url);
+        let message = EmailMessage::builder()
+            .from(self.from_email.clone())
+            .to(vec![to.clone()])
+            .subject(self.subject.clone())
+            .body(self.email_body(user, &link))
+            .build()?;
+        email.send(message).await?;
+
+        Ok(())
+    }
+}
+
+impl App for SignupApp {
+    fn name(&self) -> &'static str {
+        "cot_signup"
+    }
+
+    fn router(&self) -> Router {
+        let app = Arc::new(self.clone());
+        let resend_app = Arc::clone(&app);
+
+        Router::with_urls([
+            Route::with_handler_and_name(
+                "/",
+                move |base_context: BaseContext,
+                      database: Database,
+                      email: crate::email::Email,
+                      request: Request| {
+                    signup(Arc::clone(&app), base_context, database, email, request)
+                },
+                "signup",
+            ),
+            Route::with_handler_and_name("/done/", signup_done, "signup_done"),
+            Route::with_handler_and_name(
+                "/verify/{user_id}/{token}/",
+                verify_email,
+                "verify_email",
+            ),
+            Route::with_handler_and_name(
+                "/resend/",
+                move |base_context: BaseContext,
+                      database: Database,
+                      email: crate::email::Email,
+                      request: Request| {
+                    resend_verification_email(
+                        Arc::clone(&resend_app),
+                        base_context,
+                        database,
+                        email,
+                        request,
+                    )
+                },
+                "resend_verification_email",
+            ),
+            Route::with_handler_and_name(
+                "/resend/done/",
+                resend_verification_email_done,
+                "resend_verification_email_done",
+            ),
+        ])
+    }
+}
+
+#[derive(Debug, FromRequestHead)]
+struct BaseContext {
+    urls: Urls,
+    csrf_token: CsrfToken,
+}
+
+async fn signup(
+    app: Arc<SignupApp>,
+    base_context: BaseContext,
+    database: Database,
+    email: crate::email::Email,
+    mut request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/signup.html")]
+    struct SignupTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <SignupForm as Form>::Context,
+    }
+
+    let form_context = if request.method() == Method::GET {
+        SignupForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match SignupForm::from_request(&mut request).await? {
+            FormResult::Ok(form) => {
+                let validators = PasswordValidators::from_config(request.context().config());
+                let (target, error) = if form.password.as_str() != form.password_confirm.as_str() {
+                    (
+                        FormErrorTarget::Field("password_confirm"),
+                        FormFieldValidationError::from_static("The passwords do not match"),
+                    )
+                } else if let Err(error) =
+                    validators.validate_form_field(&form.password, Some(form.username.as_str()))
+                {
+                    (FormErrorTarget::Field("password"), error)
+                } else if DatabaseUser::get_by_username(&database, form.username.as_str())
+                    .await?
+                    .is_some()
+                {
+                    (
+                        FormErrorTarget::Field("username"),
+                        FormFieldValidationError::from_static(
+                            "A user with this username already exists",
+                        ),
+                    )
+                } else if !DatabaseUser::get_by_email(&database, &form.email)
+                    .await?
+                    .is_empty()
+                {
+                    app.send_existing_account_email(&email, &request, &form.email)
+                        .await?;
+
+                    return Ok(reverse_redirect!(base_context.urls, "signup_done")?);
+                } else {
+                    let mut user = DatabaseUser::create_user_validated(
+                        &database,
+                        form.username.as_str(),
+                        form.password,
+                        &validators,
+                    )
+                    .await?;
+                    user.set_email(Some(form.email));
+                    user.save(&database).await?;
+
+                    // The user needs the token to be created first, but if the email can't be
+                    // sent, they would be left with an account they can't verify, and a
+                    // username they can't sign up with again
+                    if let Err(error) = app
+                        .send_verification_email(&email, &base_context.urls, &request, &user)
+                        .await
+                    {
+                        query!(DatabaseUser, $id == user.id())
+                            .delete(&database)
+                            .await?;
+                        return Err(error);
+                    }
+
+                    return Ok(reverse_redirect!(base_context.urls, "signup_done")?);
+                };
+
+                let mut context = SignupForm::build_context(&mut request).await?;
+                context.add_error(target, error);
+                context
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    };
+
+    let template = SignupTemplate {
+        ctx: &base_context,
+        form: form_context,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+async fn signup_done() -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/signup_done.html")]
+    struct SignupDoneTemplate;
+
+    Ok(Html::new(SignupDoneTemplate.render()?))
+}
+
+async fn verify_email(
+    database: Database,
+    Path((user_id, token)): Path<(i64, String)>,
+    request: Request,
+) -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/verify_email.html")]
+    struct VerifyEmailTemplate {
+        verified: bool,
+    }
+
+    let generator = EmailVerificationTokenGenerator::from_config(request.project_config());
+    let user = DatabaseUser::get_by_id(&database, user_id)
+        .await?
+        .filter(|user| generator.check_token(user, &token));
+    let verified = if let Some(mut user) = user {
+        user.set_email_verified(true);
+        user.save(&database).await?;
+        true
+    } else {
+        false
+    };
+
+    Ok(Html::new(VerifyEmailTemplate { verified }.render()?))
+}
+
+async fn resend_verification_email(
+    app: Arc<SignupApp>,
+    base_context: BaseContext,
+    database: Database,
+    email: crate::email::Email,
+    mut request: Request,
+) -> crate::Result<Response> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/resend_verification_email.html")]
+    struct ResendVerificationEmailTemplate<'a> {
+        ctx: &'a BaseContext,
+        form: <ResendVerificationEmailForm as Form>::Context,
+    }
+
+    let form_context = if request.method() == Method::GET {
+        ResendVerificationEmailForm::build_context(&mut request).await?
+    } else if request.method() == Method::POST {
+        match ResendVerificationEmailForm::from_request(&mut request).await? {
+            FormResult::Ok(form) => {
+                let users: Vec<_> = DatabaseUser::get_by_email(&database, &form.email)
+                    .await?
+                    .into_iter()
+                    .filter(|user| user.is_active() && !user.is_email_verified())
+                    .collect();
+                if !users.is_empty() && SignupApp::allow_email(&request, &form.email).await? {
+                    for user in users {
+                        app.send_verification_email(&email, &base_context.urls, &request, &user)
+                            .await?;
+                    }
+                }
+
+                return Ok(reverse_redirect!(
+                    base_context.urls,
+                    "resend_verification_email_done"
+                )?);
+            }
+            FormResult::ValidationError(context) => context,
+        }
+    } else {
+        return Err(Error::from(MethodNotAllowed::new(request.method().clone())));
+    };
+
+    let template = ResendVerificationEmailTemplate {
+        ctx: &base_context,
+        form: form_context,
+    };
+    Html::new(template.render()?).into_response()
+}
+
+async fn resend_verification_email_done() -> crate::Result<Html> {
+    #[derive(Debug, Template)]
+    #[template(path = "auth/resend_verification_email_done.html")]
+    struct ResendVerificationEmailDoneTemplate;
+
+    Ok(Html::new(ResendVerificationEmailDoneTemplate.render()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use cot_core::StatusCode;
+
+    use super::*;
+    use crate::auth::db::{DatabaseUserBackend, DatabaseUserCredentials};
+    use crate::auth::{AuthBackend, AuthError};
+    use crate::config::ProjectConfig;
+    use crate::email::transport::{Transport, TransportError, TransportResult};
+    use crate::session::Session;
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    #[derive(Debug, Clone, Default)]
+    struct TestTransport {
+        messages: Arc<Mutex<Vec<EmailMessage>>>,
+    }
+
+    impl Transport for TestTransport {
+        async fn send(&self, messages: &[EmailMessage]) -> TransportResult<()> {
+            self.messages.lock().unwrap().extend_from_slice(messages);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct FailingTransport;
+
+    impl Transport for FailingTransport {
+        async fn send(&self, _messages: &[EmailMessage]) -> TransportResult<()> {
+            Err(TransportError::Backend(Box::new(std::io::Error::other(
+                "connection refused",
+            ))))
+        }
+    }
+
+    fn test_app() -> SignupApp {
+        SignupApp::new(
+            "https://example.com/",
+            Email::try_from("no-reply@example.com").unwrap(),
+        )
+    }
+
+    async fn build_request(builder: &mut TestRequestBuilder, db: &TestDatabase) -> Request {
+        builder
+            .router(test_app().router())
+            .with_db_auth(db.database())
+            .await;
+        let mut request = builder.build();
+        let csrf_token = CsrfToken::from_session(Session::from_request(&request))
+            .await
+            .unwrap();
+        request.extensions_mut().insert(csrf_token);
+        request
+    }
+
+    async fn response_body(response: Response) -> String {
+        String::from_utf8(response.into_body().into_bytes().await.unwrap().to_vec()).unwrap()
+    }
+
+    fn signup_form_data(username: &str) -> [(&str, &str); 4] {
+        [
+            ("username", username),
+            ("email", "user@example.com"),
+            ("password", "correct horse battery"),
+            ("password_confirm", "correct horse battery"),
+        ]
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn signup_and_verify_email() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&signup_form_data("newuser")),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+
+        let user = DatabaseUser::get_by_username(&db.database(), "newuser")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.email().unwrap().as_str(), "user@example.com");
+        assert!(!user.is_email_verified());
+
+        assert_eq!(transport.messages.lock().unwrap().len(), 1);
+
+        let token = EmailVerificationTokenGenerator::from_config(&ProjectConfig::default())
+            .make_token(&user)
+            .unwrap();
+        let path = format!("/verify/{}/{token}/", user.id());
+        let request = build_request(&mut TestRequestBuilder::get(&path), &db).await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert!(
+            response_body(response)
+                .await
+                .contains("Email address verified")
+        );
+        let user = DatabaseUser::get_by_username(&db.database(), "newuser")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.is_email_verified());
+
+        // the link is single-use
+        let request = build_request(&mut TestRequestBuilder::get(&path), &db).await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert!(
+            response_body(response)
+                .await
+                .contains("Email verification unsuccessful")
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn signup_username_taken() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        DatabaseUser::create_user(&db.database(), "newuser", "password123")
+            .await
+            .unwrap();
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&signup_form_data("newuser")),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response_body(response)
+                .await
+                .contains("A user with this username already exists")
+        );
+        assert!(transport.messages.lock().unwrap().is_empty());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn signup_email_taken() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "otheruser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+        user.save(&db.database()).await.unwrap();
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&signup_form_data("newuser")),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        // the response is the same as for a successful signup
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        let user = DatabaseUser::get_by_username(&db.database(), "newuser")
+            .await
+            .unwrap();
+        assert!(user.is_none());
+        let messages = transport.messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(format!("{:?}", messages[0]).contains("You already have an account"));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn signup_email_failure_removes_user() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+
+        let request = build_request(
+            TestRequestBuilder::post("/")
+                .email(crate::email::Email::new(FailingTransport))
+                .form_data(&signup_form_data("newuser")),
+            &db,
+        )
+        .await;
+        let result = test_app().router().handle(request).await;
+
+        assert!(result.is_err());
+        let user = DatabaseUser::get_by_username(&db.database(), "newuser")
+            .await
+            .unwrap();
+        assert!(user.is_none());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn resend_verification_email() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "newuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+        user.save(&db.database()).await.unwrap();
+        let transport = TestTransport::default();
+
+        let request = build_request(
+            TestRequestBuilder::post("/resend/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&[("email", "user@example.com")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(transport.messages.lock().unwrap().len(), 1);
+
+        // no email is sent once the address is verified
+        user.set_email_verified(true);
+        user.save(&db.database()).await.unwrap();
+        let request = build_request(
+            TestRequestBuilder::post("/resend/")
+                .email(crate::email::Email::new(transport.clone()))
+                .form_data(&[("email", "user@example.com")]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(transport.messages.lock().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "cache")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn resend_verification_email_throttled() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "newuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+        user.save(&db.database()).await.unwrap();
+        let transport = TestTransport::default();
+        let cache = crate::test::TestCache::new_memory().cache();
+        let max_attempts = ProjectConfig::default().login_throttle.max_attempts;
+
+        for _ in 0..=max_attempts {
+            let request = build_request(
+                TestRequestBuilder::post("/resend/")
+                    .email(crate::email::Email::new(transport.clone()))
+                    .cache(cache.clone())
+                    .form_data(&[("email", "user@example.com")]),
+                &db,
+            )
+            .await;
+            let response = test_app().router().handle(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        }
+
+        assert_eq!(
+            transport.messages.lock().unwrap().len(),
+            max_attempts as usize
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn signup_passwords_do_not_match() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+
+        let request = build_request(
+            TestRequestBuilder::post("/").form_data(&[
+                ("username", "newuser"),
+                ("email", "user@example.com"),
+                ("password", "correct horse battery"),
+                ("password_confirm", "typo"),
+            ]),
+            &db,
+        )
+        .await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        assert!(
+            response_body(response)
+                .await
+                .contains("The passwords do not match")
+        );
+        let user = DatabaseUser::get_by_username(&db.database(), "newuser")
+            .await
+            .unwrap();
+        assert!(user.is_none());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn verify_email_invalid_token() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "newuser", "password123")
+            .await
+            .unwrap();
+        user.set_email(Some(Email::try_from("user@example.com").unwrap()));
+        user.save(&db.database()).await.unwrap();
+        let token = EmailVerificationTokenGenerator::from_config(&ProjectConfig::default())
+            .make_token(&user)
+            .unwrap();
+
+        let url = format!("/verify/{}/{token}/", user.id() + 1);
+        let request = build_request(&mut TestRequestBuilder::get(&url), &db).await;
+        let response = test_app().router().handle(request).await.unwrap();
+
+        assert!(
+            response_body(response)
+                .await
+                .contains("Email verification unsuccessful")
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn login_requires_verified_email() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let mut user = DatabaseUser::create_user(&db.database(), "newuser", "password123")
+            .await
+            .unwrap();
+        let backend = DatabaseUserBackend::new(db.database()).require_verified_email(true);
+        let credentials =
+            DatabaseUserCredentials::new("newuser".to_owned(), Password::new("password123"));
+
+        let result = backend.authenticate(&credentials).await;
+        assert!(matches!(result, Err(AuthError::EmailNotVerified)));
+
+        user.set_email_verified(true);
+        user.save(&db.database()).await.unwrap();
+        let result = backend.authenticate(&credentials).await.unwrap();
+        assert!(result.is_some());
+    }
+}
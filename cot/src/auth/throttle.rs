@@ -0,0 +1,791 @@
+//! Throttling of failed login attempts.
+//!
+//! This module provides the [`LoginThrottle`], which keeps track of failed
+//! login attempts per username and per client IP address, and temporarily
+//! locks out the ones that have failed too many times. The lockout time
+//! doubles with each subsequent failure, up to the configured maximum.
+//!
+//! The throttling is configured with
+//! [`ProjectConfig::login_throttle`](crate::config::ProjectConfig::login_throttle)
+//! and, when enabled, is applied automatically by
+//! [`Auth::authenticate`](crate::auth::Auth::authenticate), as well as to the
+//! verification codes of the two-factor authentication in the admin panel
+//! (see [`LoginThrottle::second_factor`]). The same mechanism limits the
+//! emails sent by the `SignupApp` to a single address, regardless of whether
+//! the login throttling is enabled. The failed attempts are stored in
+//! the project's [`Cache`](crate::cache::Cache) if the `cache` feature is
+//! enabled, or in the process memory otherwise.
+//!
+//! # Concurrency
+//!
+//! The failed attempts are updated atomically within a single process, so the
+//! concurrent attempts made against one server are all counted. The cache
+//! doesn't provide an atomic increment, though, so if the cache is shared by
+//! multiple processes, the failures recorded by them at the same time may
+//! overwrite each other. In that case, at least one failure is counted for
+//! every batch of concurrent attempts, so the number of attempts an attacker
+//! can make before being locked out is at most the configured maximum
+//! multiplied by the number of processes.
+//!
+//! # Reverse proxies
+//!
+//! The client IP address is the address of the peer connected to the server.
+//! If the project runs behind a reverse proxy, all the requests come from the
+//! proxy, so the addresses of the proxies have to be listed in
+//! [`LoginThrottleConfig::trusted_proxies`]; the client IP address is then
+//! read from the `X-Forwarded-For` header set by the proxies. This applies to
+//! all the places the throttling is used in, including the
+//! [`BasicAuthMiddleware`](crate::middleware::BasicAuthMiddleware).
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+#[cfg(not(feature = "cache"))]
+use std::sync::LazyLock;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::HeaderValue;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{AuthBackend, AuthError, Result, User};
+#[cfg(feature = "cache")]
+use crate::cache::Cache;
+use crate::config::LoginThrottleConfig;
+#[cfg(feature = "cache")]
+use crate::config::Timeout;
+use crate::request::{Request, RequestExt};
+
+const LOGIN_THROTTLE_KEY_PREFIX: &str = "cot_login_throttle";
+const SECOND_FACTOR_THROTTLE_KEY_PREFIX: &str = "cot_second_factor_throttle";
+#[cfg(all(feature = "db", feature = "email"))]
+const EMAIL_THROTTLE_KEY_PREFIX: &str = "cot_email_throttle";
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// The lock held while the failed attempts stored in a cache are updated, so
+/// that the concurrent updates made by this process don't overwrite each other.
+#[cfg(feature = "cache")]
+static CACHE_UPDATE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The store shared by all the [`LoginThrottle`]s that don't use a cache.
+#[cfg(not(feature = "cache"))]
+static MEMORY_STORE: LazyLock<Arc<Mutex<HashMap<String, FailedAttempts>>>> =
+    LazyLock::new(Arc::default);
+
+/// Tracker of the failed login attempts.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// use cot::auth::AuthError;
+/// use cot::auth::throttle::LoginThrottle;
+/// use cot::config::LoginThrottleConfig;
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let throttle = LoginThrottle::in_memory(
+///     LoginThrottleConfig::builder()
+///         .enabled(true)
+///         .max_attempts(2)
+///         .build(),
+/// );
+/// let ip = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+///
+/// throttle.record_failure(Some("admin"), ip).await?;
+/// throttle.check(Some("admin"), ip).await?;
+/// throttle.record_failure(Some("admin"), ip).await?;
+/// assert!(matches!(
+///     throttle.check(Some("admin"), ip).await,
+///     Err(AuthError::TooManyAttempts { .. })
+/// ));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoginThrottle {
+    config: LoginThrottleConfig,
+    store: ThrottleStore,
+    key_prefix: &'static str,
+}
+
+#[derive(Debug, Clone)]
+enum ThrottleStore {
+    #[cfg(feature = "cache")]
+    Cache(Cache),
+    Memory(Arc<Mutex<HashMap<String, FailedAttempts>>>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FailedAttempts {
+    count: u32,
+    first_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginThrottle {
+    /// Creates a new login throttle that stores the failed attempts in the
+    /// given cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::auth::throttle::LoginThrottle;
+    /// use cot::cache::Cache;
+    /// use cot::cache::store::memory::Memory;
+    /// use cot::config::{LoginThrottleConfig, Timeout};
+    ///
+    /// let cache = Cache::new(
+    ///     Memory::new(),
+    ///     None,
+    ///     Timeout::After(Duration::from_secs(300)),
+    /// );
+    /// let throttle = LoginThrottle::new(LoginThrottleConfig::default(), cache);
+    /// ```
+    #[cfg(feature = "cache")]
+    #[must_use]
+    pub fn new(config: LoginThrottleConfig, cache: Cache) -> Self {
+        Self {
+            config,
+            store: ThrottleStore::Cache(cache),
+            key_prefix: LOGIN_THROTTLE_KEY_PREFIX,
+        }
+    }
+
+    /// Creates a new login throttle that stores the failed attempts in the
+    /// process memory.
+    ///
+    /// Each throttle created with this method has its own, separate store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::throttle::LoginThrottle;
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let throttle = LoginThrottle::in_memory(LoginThrottleConfig::default());
+    /// ```
+    #[must_use]
+    pub fn in_memory(config: LoginThrottleConfig) -> Self {
+        Self {
+            config,
+            store: ThrottleStore::Memory(Arc::default()),
+            key_prefix: LOGIN_THROTTLE_KEY_PREFIX,
+        }
+    }
+
+    /// Creates the login throttle used for the requests handled by the
+    /// project: backed by the project's cache if the `cache` feature is
+    /// enabled, or by a process-wide memory store otherwise.
+    pub(crate) fn from_context(
+        config: LoginThrottleConfig,
+        #[cfg(feature = "cache")] cache: &Cache,
+    ) -> Self {
+        #[cfg(feature = "cache")]
+        let store = ThrottleStore::Cache(cache.clone());
+        #[cfg(not(feature = "cache"))]
+        let store = ThrottleStore::Memory(Arc::clone(&MEMORY_STORE));
+
+        Self {
+            config,
+            store,
+            key_prefix: LOGIN_THROTTLE_KEY_PREFIX,
+        }
+    }
+
+    /// Returns a throttle for the verification of the second authentication
+    /// factor, sharing the store and the configuration with this one.
+    ///
+    /// The failed attempts are tracked separately from the password ones, so
+    /// that logging in with the password again doesn't reset the attempts to
+    /// guess the verification code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::throttle::LoginThrottle;
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let throttle = LoginThrottle::in_memory(LoginThrottleConfig::default()).second_factor();
+    /// ```
+    #[must_use]
+    pub fn second_factor(self) -> Self {
+        Self {
+            key_prefix: SECOND_FACTOR_THROTTLE_KEY_PREFIX,
+            ..self
+        }
+    }
+
+    /// Creates the login throttle configured for the project the request is
+    /// handled by, or returns [`None`] if the login throttling is disabled.
+    pub(crate) fn from_request(request: &Request) -> Option<Self> {
+        let config = request.project_config();
+        if !config.login_throttle.enabled {
+            return None;
+        }
+
+        Some(Self::from_context(
+            config.login_throttle.clone(),
+            #[cfg(feature = "cache")]
+            request.context().cache(),
+        ))
+    }
+
+    /// Creates the throttle limiting the emails sent to the same address by
+    /// the [`SignupApp`](crate::auth::signup::SignupApp), sharing the store
+    /// and the configuration with the login throttle of the project.
+    ///
+    /// Each email is recorded as an attempt, so after the configured maximum
+    /// number of attempts, no more emails are sent to the address (or from
+    /// the client IP address) until the lockout expires. Unlike the login
+    /// throttling, this is applied even if
+    /// [`LoginThrottleConfig::enabled`] is `false`, since sending emails to
+    /// arbitrary addresses shouldn't be unlimited.
+    #[cfg(all(feature = "db", feature = "email"))]
+    pub(crate) fn emails(request: &Request) -> Self {
+        Self {
+            key_prefix: EMAIL_THROTTLE_KEY_PREFIX,
+            ..Self::from_context(
+                request.project_config().login_throttle.clone(),
+                #[cfg(feature = "cache")]
+                request.context().cache(),
+            )
+        }
+    }
+
+    /// Returns the IP address of the client that sent the request, if known.
+    ///
+    /// This is the address of the peer connected to the server, unless it's
+    /// one of the [trusted
+    /// proxies](crate::config::LoginThrottleConfig::trusted_proxies), in which
+    /// case it's the rightmost address in the `X-Forwarded-For` header that is
+    /// not a trusted proxy.
+    pub(crate) fn client_ip(request: &Request) -> Option<IpAddr> {
+        let peer_ip = request
+            .extensions()
+            .get::<axum::extract::ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0.ip())?;
+        let trusted_proxies = &request.project_config().login_throttle.trusted_proxies;
+
+        Some(forwarded_client_ip(
+            peer_ip,
+            request.headers().get_all(X_FORWARDED_FOR),
+            trusted_proxies,
+        ))
+    }
+
+    /// Returns the configuration of this throttle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::throttle::LoginThrottle;
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let throttle = LoginThrottle::in_memory(LoginThrottleConfig::default());
+    /// assert_eq!(throttle.config().max_attempts, 5);
+    /// ```
+    #[must_use]
+    pub fn config(&self) -> &LoginThrottleConfig {
+        &self.config
+    }
+
+    /// Checks whether a login attempt for the given username from the given
+    /// IP address is currently allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::TooManyAttempts`] if either the username or the IP
+    /// address is locked out.
+    ///
+    /// Returns [`AuthError::UserBackend`] if the store cannot be accessed.
+    pub async fn check(&self, username: Option<&str>, ip: Option<IpAddr>) -> Result<()> {
+        let now = Utc::now();
+        let mut retry_after = Duration::ZERO;
+        for (key, _) in self.keys(username, ip) {
+            if let Some(locked_until) = self.get(&key).await?.and_then(|a| a.locked_until)
+                && let Ok(remaining) = (locked_until - now).to_std()
+            {
+                retry_after = retry_after.max(remaining);
+            }
+        }
+
+        if retry_after.is_zero() {
+            Ok(())
+        } else {
+            Err(AuthError::TooManyAttempts { retry_after })
+        }
+    }
+
+    /// Records a failed login attempt for the given username from the given
+    /// IP address, locking them out if they have failed too many times.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::UserBackend`] if the store cannot be accessed.
+    pub async fn record_failure(&self, username: Option<&str>, ip: Option<IpAddr>) -> Result<()> {
+        let now = Utc::now();
+        for (key, max_attempts) in self.keys(username, ip) {
+            self.update(&key, |previous| {
+                let mut attempts = match previous {
+                    Some(attempts) if attempts.is_active(now, self.config.window) => attempts,
+                    _ => FailedAttempts {
+                        count: 0,
+                        first_failure: now,
+                        locked_until: None,
+                    },
+                };
+                attempts.count = attempts.count.saturating_add(1);
+
+                let mut expiry = self.config.window;
+                if attempts.count >= max_attempts {
+                    let lockout = self.lockout(attempts.count - max_attempts);
+                    attempts.locked_until = Some(
+                        now + chrono::Duration::from_std(lockout).unwrap_or(chrono::TimeDelta::MAX),
+                    );
+                    expiry = expiry.max(lockout);
+                }
+
+                (attempts, expiry)
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Clears the failed login attempts recorded for the given username.
+    ///
+    /// This is called after a successful login. The failed attempts recorded
+    /// for the IP address are intentionally kept, so that an attacker can't
+    /// reset them by logging in to their own account.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AuthError::UserBackend`] if the store cannot be accessed.
+    #[cfg_attr(not(feature = "cache"), expect(clippy::unused_async))]
+    pub async fn reset(&self, username: &str) -> Result<()> {
+        let key = self.username_key(username);
+        match &self.store {
+            #[cfg(feature = "cache")]
+            ThrottleStore::Cache(cache) => {
+                cache.remove(key).await.map_err(AuthError::backend_error)?;
+            }
+            ThrottleStore::Memory(store) => {
+                Self::lock(store).remove(&key);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Authenticates the user with the given backend, checking and updating
+    /// the failed attempts recorded for the username and the IP address.
+    pub(crate) async fn authenticate(
+        &self,
+        backend: &dyn AuthBackend,
+        credentials: &(dyn Any + Send + Sync),
+        ip: Option<IpAddr>,
+    ) -> Result<Option<Box<dyn User + Send + Sync>>> {
+        let username = backend.login_identifier(credentials);
+        self.check(username.as_deref(), ip).await?;
+
+        let user = backend.authenticate(credentials).await?;
+        match (&user, &username) {
+            (Some(_), Some(username)) => self.reset(username).await?,
+            (Some(_), None) => {}
+            (None, _) => self.record_failure(username.as_deref(), ip).await?,
+        }
+
+        Ok(user)
+    }
+
+    /// Returns the lockout time after the given number of failures past the
+    /// maximum number of attempts: the configured lockout time, doubled with
+    /// each failure, but never longer than the configured maximum.
+    fn lockout(&self, excess_failures: u32) -> Duration {
+        let multiplier = 2_u32.checked_pow(excess_failures).unwrap_or(u32::MAX);
+        self.config
+            .lockout
+            .saturating_mul(multiplier)
+            .min(self.config.max_lockout)
+    }
+
+    #[cfg_attr(not(feature = "cache"), expect(clippy::unused_async))]
+    async fn get(&self, key: &str) -> Result<Option<FailedAttempts>> {
+        match &self.store {
+            #[cfg(feature = "cache")]
+            ThrottleStore::Cache(cache) => cache.get(key).await.map_err(AuthError::backend_error),
+            ThrottleStore::Memory(store) => Ok(Self::lock(store).get(key).copied()),
+        }
+    }
+
+    /// Replaces the failed attempts stored under the given key with the ones
+    /// returned by `f`, along with the time after which they expire.
+    ///
+    /// The read and the write are done atomically with respect to the other
+    /// updates made by this process, so that concurrent failures are never
+    /// lost. See the [module documentation](self) for the guarantees when the
+    /// cache is shared by multiple processes.
+    #[cfg_attr(not(feature = "cache"), expect(clippy::unused_async))]
+    async fn update(
+        &self,
+        key: &str,
+        f: impl FnOnce(Option<FailedAttempts>) -> (FailedAttempts, Duration),
+    ) -> Result<()> {
+        match &self.store {
+            #[cfg(feature = "cache")]
+            ThrottleStore::Cache(cache) => {
+                let _guard = CACHE_UPDATE_LOCK.lock().await;
+                let previous = cache.get(key).await.map_err(AuthError::backend_error)?;
+                let (attempts, expiry) = f(previous);
+                cache
+                    .insert_expiring(key, attempts, Timeout::After(expiry))
+                    .await
+                    .map_err(AuthError::backend_error)
+            }
+            ThrottleStore::Memory(store) => {
+                let mut store = Self::lock(store);
+                let (attempts, _expiry) = f(store.get(key).copied());
+                // the expired entries are not removed by anything else, so
+                // let's clean them up here to keep the memory usage bounded
+                let now = Utc::now();
+                store.retain(|_, attempts| attempts.is_active(now, self.config.window));
+                store.insert(key.to_owned(), attempts);
+                Ok(())
+            }
+        }
+    }
+
+    fn lock(
+        store: &Mutex<HashMap<String, FailedAttempts>>,
+    ) -> std::sync::MutexGuard<'_, HashMap<String, FailedAttempts>> {
+        // the map is always left in a consistent state, so it's safe to
+        // ignore the poisoning
+        store
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the store keys for the given username and IP address, along
+    /// with the maximum number of failed attempts allowed for each of them.
+    fn keys(&self, username: Option<&str>, ip: Option<IpAddr>) -> Vec<(String, u32)> {
+        username
+            .map(|username| (self.username_key(username), self.config.max_attempts))
+            .into_iter()
+            .chain(ip.map(|ip| {
+                (
+                    format!("{}:ip:{ip}", self.key_prefix),
+                    self.config.max_attempts_per_ip,
+                )
+            }))
+            .collect()
+    }
+
+    fn username_key(&self, username: &str) -> String {
+        format!("{}:user:{username}", self.key_prefix)
+    }
+}
+
+/// Returns the address of the client that connected to the trusted proxies,
+/// going through the `X-Forwarded-For` header from right to left, starting at
+/// the peer address.
+fn forwarded_client_ip<'a>(
+    peer_ip: IpAddr,
+    forwarded_for: impl IntoIterator<Item = &'a HeaderValue, IntoIter: DoubleEndedIterator>,
+    trusted_proxies: &[IpAddr],
+) -> IpAddr {
+    let forwarded_ips = forwarded_for
+        .into_iter()
+        .rev()
+        .flat_map(|value| value.to_str().unwrap_or_default().rsplit(','))
+        .map(|ip| ip.trim().parse::<IpAddr>());
+
+    let mut client_ip = peer_ip;
+    for forwarded_ip in forwarded_ips {
+        if !trusted_proxies.contains(&client_ip) {
+            break;
+        }
+        // an invalid entry might have been added by anyone, so it's not
+        // possible to go any further
+        let Ok(forwarded_ip) = forwarded_ip else {
+            break;
+        };
+        client_ip = forwarded_ip;
+    }
+
+    client_ip
+}
+
+impl FailedAttempts {
+    /// Returns whether the failed attempts should still be taken into
+    /// account: either they are within the configured window, or they have
+    /// caused a lockout that hasn't expired yet.
+    fn is_active(&self, now: DateTime<Utc>, window: Duration) -> bool {
+        let window = chrono::Duration::from_std(window).unwrap_or(chrono::TimeDelta::MAX);
+        now - self.first_failure <= window || self.locked_until.is_some_and(|until| until > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn throttle() -> LoginThrottle {
+        LoginThrottle::in_memory(
+            LoginThrottleConfig::builder()
+                .enabled(true)
+                .max_attempts(3)
+                .max_attempts_per_ip(5)
+                .lockout(Duration::from_secs(60))
+                .max_lockout(Duration::from_secs(300))
+                .build(),
+        )
+    }
+
+    fn assert_locked_for(result: Result<()>, max: Duration) {
+        match result {
+            Err(AuthError::TooManyAttempts { retry_after }) => {
+                assert!(retry_after <= max);
+                assert!(retry_after + Duration::from_secs(5) > max);
+            }
+            other => panic!("expected the login to be throttled, got {other:?}"),
+        }
+    }
+
+    #[cot::test]
+    async fn login_throttle_locks_out_username() {
+        let throttle = throttle();
+
+        for _ in 0..2 {
+            throttle.record_failure(Some("user"), None).await.unwrap();
+            throttle.check(Some("user"), None).await.unwrap();
+        }
+        throttle.record_failure(Some("user"), None).await.unwrap();
+
+        assert_locked_for(
+            throttle.check(Some("user"), None).await,
+            Duration::from_secs(60),
+        );
+        throttle.check(Some("other"), None).await.unwrap();
+    }
+
+    #[cot::test]
+    async fn login_throttle_exponential_lockout() {
+        let throttle = throttle();
+
+        for _ in 0..4 {
+            throttle.record_failure(Some("user"), None).await.unwrap();
+        }
+        assert_locked_for(
+            throttle.check(Some("user"), None).await,
+            Duration::from_secs(120),
+        );
+
+        for _ in 0..10 {
+            throttle.record_failure(Some("user"), None).await.unwrap();
+        }
+        assert_locked_for(
+            throttle.check(Some("user"), None).await,
+            Duration::from_secs(300),
+        );
+    }
+
+    #[cot::test]
+    async fn login_throttle_locks_out_ip() {
+        let throttle = throttle();
+        let ip = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+
+        for i in 0..5 {
+            throttle
+                .record_failure(Some(&format!("user{i}")), ip)
+                .await
+                .unwrap();
+        }
+
+        assert_locked_for(
+            throttle.check(Some("user5"), ip).await,
+            Duration::from_secs(60),
+        );
+        throttle.check(Some("user5"), None).await.unwrap();
+    }
+
+    #[cot::test]
+    async fn login_throttle_reset() {
+        let throttle = throttle();
+        let ip = Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+
+        for _ in 0..3 {
+            throttle.record_failure(Some("user"), ip).await.unwrap();
+        }
+        throttle.reset("user").await.unwrap();
+
+        throttle.check(Some("user"), None).await.unwrap();
+        throttle.record_failure(Some("user"), None).await.unwrap();
+        throttle.check(Some("user"), None).await.unwrap();
+    }
+
+    async fn record_failures_concurrently(throttle: &LoginThrottle, count: usize) {
+        let failures = (0..count)
+            .map(|_| throttle.record_failure(Some("user"), Some(IpAddr::from([192, 0, 2, 1]))));
+        for result in futures_util::future::join_all(failures).await {
+            result.unwrap();
+        }
+    }
+
+    #[cot::test]
+    async fn login_throttle_concurrent_failures() {
+        let throttle = throttle();
+
+        record_failures_concurrently(&throttle, 20).await;
+
+        let attempts = throttle
+            .get(&throttle.username_key("user"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(attempts.count, 20);
+    }
+
+    #[test]
+    fn forwarded_client_ip() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let inner_proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let client = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let client_ip = |peer_ip, forwarded_for: &[&'static str]| {
+            let headers: Vec<_> = forwarded_for
+                .iter()
+                .map(|value| HeaderValue::from_static(value))
+                .collect();
+            super::forwarded_client_ip(peer_ip, &headers, &[proxy, inner_proxy])
+        };
+
+        // the header is ignored if the peer is not a trusted proxy
+        assert_eq!(client_ip(client, &["203.0.113.1"]), client);
+        assert_eq!(client_ip(proxy, &[]), proxy);
+        assert_eq!(client_ip(proxy, &["192.0.2.1"]), client);
+        // the addresses added by the client itself are skipped
+        assert_eq!(client_ip(proxy, &["203.0.113.1, 192.0.2.1"]), client);
+        assert_eq!(client_ip(proxy, &["203.0.113.1", "192.0.2.1"]), client);
+        assert_eq!(client_ip(proxy, &["192.0.2.1, 10.0.0.2"]), client);
+        assert_eq!(client_ip(proxy, &["invalid, 10.0.0.2"]), inner_proxy);
+    }
+
+    #[cot::test]
+    async fn login_throttle_second_factor() {
+        let throttle = throttle();
+        let second_factor = throttle.clone().second_factor();
+
+        for _ in 0..3 {
+            second_factor
+                .record_failure(Some("user"), None)
+                .await
+                .unwrap();
+        }
+        // logging in with the password doesn't reset the second factor attempts
+        throttle.reset("user").await.unwrap();
+
+        throttle.check(Some("user"), None).await.unwrap();
+        assert_locked_for(
+            second_factor.check(Some("user"), None).await,
+            Duration::from_secs(60),
+        );
+    }
+
+    #[cfg(feature = "cache")]
+    #[cot::test]
+    async fn login_throttle_cache() {
+        use crate::cache::store::memory::Memory;
+
+        let cache = Cache::new(
+            Memory::new(),
+            None,
+            Timeout::After(Duration::from_secs(300)),
+        );
+        let throttle = LoginThrottle::new(throttle().config().clone(), cache.clone());
+
+        for _ in 0..3 {
+            throttle.record_failure(Some("user"), None).await.unwrap();
+        }
+
+        assert!(
+            cache
+                .contains_key(throttle.username_key("user"))
+                .await
+                .unwrap()
+        );
+        assert_locked_for(
+            throttle.check(Some("user"), None).await,
+            Duration::from_secs(60),
+        );
+    }
+
+    /// A memory cache store that yields to the other tasks before each read
+    /// and write, so that concurrent read-modify-write cycles interleave.
+    #[cfg(feature = "cache")]
+    #[derive(Debug, Clone)]
+    struct YieldingMemory(crate::cache::store::memory::Memory);
+
+    #[cfg(feature = "cache")]
+    impl crate::cache::store::CacheStore for YieldingMemory {
+        async fn get(
+            &self,
+            key: &str,
+        ) -> crate::cache::store::CacheStoreResult<Option<serde_json::Value>> {
+            tokio::task::yield_now().await;
+            self.0.get(key).await
+        }
+
+        async fn insert(
+            &self,
+            key: String,
+            value: serde_json::Value,
+            expiry: Timeout,
+        ) -> crate::cache::store::CacheStoreResult<()> {
+            tokio::task::yield_now().await;
+            self.0.insert(key, value, expiry).await
+        }
+
+        async fn remove(&self, key: &str) -> crate::cache::store::CacheStoreResult<()> {
+            self.0.remove(key).await
+        }
+
+        async fn clear(&self) -> crate::cache::store::CacheStoreResult<()> {
+            self.0.clear().await
+        }
+
+        async fn approx_size(&self) -> crate::cache::store::CacheStoreResult<usize> {
+            self.0.approx_size().await
+        }
+
+        async fn contains_key(&self, key: &str) -> crate::cache::store::CacheStoreResult<bool> {
+            self.0.contains_key(key).await
+        }
+    }
+
+    #[cfg(feature = "cache")]
+    #[cot::test]
+    async fn login_throttle_cache_concurrent_failures() {
+        let cache = Cache::new(
+            YieldingMemory(crate::cache::store::memory::Memory::new()),
+            None,
+            Timeout::After(Duration::from_secs(300)),
+        );
+        let throttle = LoginThrottle::new(throttle().config().clone(), cache);
+
+        record_failures_concurrently(&throttle, 20).await;
+
+        let attempts = throttle
+            .get(&throttle.username_key("user"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(attempts.count, 20);
+    }
+}
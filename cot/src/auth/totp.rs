@@ -0,0 +1,968 @@
+//! Two-factor authentication with time-based one-time passwords (TOTP).
+//!
+//! This module provides [`TotpDevice`], a model storing the TOTP secret of a
+//! [`DatabaseUser`], compatible with authenticator apps such as Google
+//! Authenticator, Authy or 1Password, and [`TotpRecoveryCode`], a model
+//! storing single-use recovery codes that can be used instead of a one-time
+//! code when the user loses access to their device.
+//!
+//! Two-factor authentication is opt-in. Once a user has a confirmed device,
+//! [`DatabaseUserBackend`](crate::auth::db::DatabaseUserBackend) requires them
+//! to verify a second factor when logging in: [`Auth::login`] only marks the
+//! session as "password verified, second factor pending", and the login has
+//! to be finished with [`Auth::complete_second_factor`] once the one-time
+//! code is verified.
+//!
+//! # Examples
+//!
+//! ```
+//! use cot::auth::Auth;
+//! use cot::auth::db::DatabaseUser;
+//! use cot::auth::totp::TotpDevice;
+//! use cot::db::Database;
+//! use cot::html::Html;
+//!
+//! async fn verify_code(auth: Auth, db: Database, code: String) -> cot::Result<Html> {
+//!     let Some(user) = auth.pending_second_factor_user().await? else {
+//!         return Ok(Html::new("There is no login pending"));
+//!     };
+//!     let user_id = user.id().and_then(|id| id.as_int()).unwrap_or_default();
+//!     let Some(user) = DatabaseUser::get_by_id(&db, user_id).await? else {
+//!         return Ok(Html::new("The user does not exist"));
+//!     };
+//!
+//!     let Some(mut device) = TotpDevice::get_for_user(&db, &user).await? else {
+//!         return Ok(Html::new("Two-factor authentication is not enabled"));
+//!     };
+//!     if device.verify(&db, &code).await? {
+//!         auth.complete_second_factor().await?;
+//!         Ok(Html::new("Logged in"))
+//!     } else {
+//!         Ok(Html::new("Invalid code"))
+//!     }
+//! }
+//! ```
+//!
+//! [`Auth::login`]: crate::auth::Auth::login
+//! [`Auth::complete_second_factor`]: crate::auth::Auth::complete_second_factor
+
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, FixedOffset, Utc};
+// Importing `Auto` from `cot` instead of `crate` so that the migration generator
+// can figure out it's an autogenerated field
+use cot::db::Auto;
+use rand::Rng;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use crate::auth::db::DatabaseUser;
+use crate::auth::{AuthError, Result};
+use crate::db::query::{Expr, Query};
+use crate::db::{Database, DatabaseBackend, ForeignKey, LimitedString, Model, model, query};
+
+pub(crate) const TOTP_SECRET_LENGTH: u32 = 32;
+pub(crate) const RECOVERY_CODE_HASH_LENGTH: u32 = 64;
+
+/// The number of 30-second time steps before and after the current one for
+/// which the one-time codes are accepted by [`TotpDevice::verify`].
+pub const DEFAULT_TOTP_DRIFT: u8 = 1;
+
+/// The number of recovery codes that are usually generated for a user.
+pub const DEFAULT_RECOVERY_CODE_COUNT: usize = 10;
+
+const TOTP_DIGITS: usize = 6;
+const TOTP_STEP: u64 = 30;
+/// 160 bits, as recommended by [RFC 4226](https://www.rfc-editor.org/rfc/rfc4226#section-4).
+const TOTP_SECRET_BYTES: usize = 20;
+
+/// An error that occurs when enrolling a user in the two-factor
+/// authentication.
+#[derive(Debug, Clone, Error)]
+#[non_exhaustive]
+pub enum TotpError {
+    /// The user already has a confirmed TOTP device.
+    #[error("two-factor authentication is already enabled for the user")]
+    AlreadyEnabled,
+    /// The secret stored in the database is not a valid base32 string.
+    #[error("the TOTP secret is invalid")]
+    InvalidSecret,
+}
+
+/// A TOTP device (i.e. an authenticator app) of a [`DatabaseUser`].
+///
+/// A device is created with [`TotpDevice::enroll`] and only becomes active
+/// once the user proves they have set up their authenticator app correctly by
+/// entering a valid code, which is checked by [`TotpDevice::confirm`]. Each
+/// user has at most one device.
+#[derive(Debug, Clone)]
+#[model]
+pub struct TotpDevice {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    secret: LimitedString<TOTP_SECRET_LENGTH>,
+    confirmed: bool,
+    last_used_step: i64,
+    created_at: DateTime<FixedOffset>,
+}
+
+impl TotpDevice {
+    /// Creates a new, unconfirmed TOTP device with a random secret for the
+    /// user and saves it to the database.
+    ///
+    /// Any previous unconfirmed device of the user is removed. The secret
+    /// (or the [provisioning URI](Self::provisioning_uri), usually shown as a
+    /// QR code) should then be shown to the user, and the device confirmed
+    /// with [`confirm`](Self::confirm).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the user already has a confirmed device, or if
+    /// there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn enroll(db: &Database, user: &DatabaseUser) -> cot::Result<String> {
+    ///     let device = TotpDevice::enroll(db, user).await?;
+    ///     Ok(device.provisioning_uri("My Project", user.username()))
+    /// }
+    /// ```
+    pub async fn enroll<DB: DatabaseBackend>(db: &DB, user: &DatabaseUser) -> Result<Self> {
+        let user_fk = ForeignKey::from(user);
+        let user_filter = user_fk.clone();
+        if query!(TotpDevice, $user == user_filter && $confirmed == true)
+            .exists(db)
+            .await
+            .map_err(AuthError::backend_error)?
+        {
+            return Err(AuthError::backend_error(TotpError::AlreadyEnabled));
+        }
+        let user_filter = user_fk.clone();
+        query!(TotpDevice, $user == user_filter)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        let mut device = Self {
+            id: Auto::auto(),
+            user: user_fk,
+            secret: random_secret(),
+            confirmed: false,
+            last_used_step: 0,
+            created_at: Utc::now().fixed_offset(),
+        };
+        device.insert(db).await.map_err(AuthError::backend_error)?;
+
+        Ok(device)
+    }
+
+    /// Returns the TOTP device of the user, whether it's confirmed or not.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn has_device(db: &Database, user: &DatabaseUser) -> cot::Result<bool> {
+    ///     Ok(TotpDevice::get_for_user(db, user).await?.is_some())
+    /// }
+    /// ```
+    pub async fn get_for_user<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+    ) -> Result<Option<Self>> {
+        let user = ForeignKey::from(user);
+        let device = query!(TotpDevice, $user == user)
+            .get(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(device)
+    }
+
+    /// Returns whether the user with given ID has a confirmed TOTP device,
+    /// i.e. whether the two-factor authentication is enabled for them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn two_factor_enabled(db: &Database, user: &DatabaseUser) -> cot::Result<bool> {
+    ///     Ok(TotpDevice::is_enabled_for(db, user.id()).await?)
+    /// }
+    /// ```
+    pub async fn is_enabled_for<DB: DatabaseBackend>(db: &DB, user_id: i64) -> Result<bool> {
+        let user = ForeignKey::<DatabaseUser>::PrimaryKey(Auto::fixed(user_id));
+        query!(TotpDevice, $user == user && $confirmed == true)
+            .exists(db)
+            .await
+            .map_err(AuthError::backend_error)
+    }
+
+    /// Disables the two-factor authentication for the user by removing their
+    /// TOTP device and all their recovery codes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn disable(db: &Database, user: &DatabaseUser) -> cot::Result<()> {
+    ///     TotpDevice::disable_for_user(db, user).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn disable_for_user<DB: DatabaseBackend>(db: &DB, user: &DatabaseUser) -> Result<()> {
+        let user = ForeignKey::from(user);
+        let device_user = user.clone();
+        query!(TotpDevice, $user == device_user)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+        query!(TotpRecoveryCode, $user == user)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(())
+    }
+
+    /// Confirms the device if the one-time code is valid.
+    ///
+    /// Returns `false` if the code is invalid, in which case the device stays
+    /// unconfirmed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn confirm(db: &Database, mut device: TotpDevice, code: &str) -> cot::Result<bool> {
+    ///     Ok(device.confirm(db, code).await?)
+    /// }
+    /// ```
+    pub async fn confirm<DB: DatabaseBackend>(&mut self, db: &DB, code: &str) -> Result<bool> {
+        if !self.check_code(code, DEFAULT_TOTP_DRIFT, Utc::now().timestamp())? {
+            return Ok(false);
+        }
+
+        self.confirmed = true;
+        self.save(db).await.map_err(AuthError::backend_error)?;
+        Ok(true)
+    }
+
+    /// Verifies the one-time code, accepting the codes from
+    /// [`DEFAULT_TOTP_DRIFT`] time steps before and after the current one.
+    ///
+    /// Every code can only be used once; the codes from the same or earlier
+    /// time steps as the last code verified are rejected. The time step of
+    /// the code is stored with a conditional update, so even if the same code
+    /// is sent in several concurrent requests, only one of them succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn verify(db: &Database, mut device: TotpDevice, code: &str) -> cot::Result<bool> {
+    ///     Ok(device.verify(db, code).await?)
+    /// }
+    /// ```
+    pub async fn verify(&mut self, db: &Database, code: &str) -> Result<bool> {
+        self.verify_with_drift(db, code, DEFAULT_TOTP_DRIFT).await
+    }
+
+    /// Verifies the one-time code, accepting the codes from `drift` time steps
+    /// before and after the current one.
+    ///
+    /// See [`verify`](Self::verify) for more details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::totp::TotpDevice;
+    /// use cot::db::Database;
+    ///
+    /// async fn verify(db: &Database, mut device: TotpDevice, code: &str) -> cot::Result<bool> {
+    ///     Ok(device.verify_with_drift(db, code, 2).await?)
+    /// }
+    /// ```
+    pub async fn verify_with_drift(
+        &mut self,
+        db: &Database,
+        code: &str,
+        drift: u8,
+    ) -> Result<bool> {
+        if !self.confirmed {
+            return Ok(false);
+        }
+        let Some(step) = self.matching_step(code, drift, Utc::now().timestamp())? else {
+            return Ok(false);
+        };
+
+        // The step is only updated if no code from the same or a later step has
+        // been used in the meantime, so that a code cannot be reused by
+        // concurrent requests
+        let statement = sea_query::Query::update()
+            .table(<Self as Model>::TABLE_NAME)
+            .value(
+                <Self as Model>::Fields::last_used_step.identifier(),
+                Expr::value(step).as_sea_query_expr(),
+            )
+            .and_where(
+                Expr::and(
+                    Expr::and(
+                        Expr::eq(
+                            Expr::field(<Self as Model>::Fields::id.identifier()),
+                            Expr::value(self.id()),
+                        ),
+                        Expr::eq(
+                            Expr::field(<Self as Model>::Fields::confirmed.identifier()),
+                            Expr::value(true),
+                        ),
+                    ),
+                    Expr::lt(
+                        Expr::field(<Self as Model>::Fields::last_used_step.identifier()),
+                        Expr::value(step),
+                    ),
+                )
+                .as_sea_query_expr(),
+            )
+            .to_owned();
+        let result = db
+            .execute_statement(&statement)
+            .await
+            .map_err(AuthError::backend_error)?;
+        if result.rows_affected().0 == 0 {
+            return Ok(false);
+        }
+
+        self.last_used_step = step;
+        Ok(true)
+    }
+
+    /// Checks the code against the codes generated for the time steps around
+    /// `timestamp`, and remembers the time step of the matching code.
+    fn check_code(&mut self, code: &str, drift: u8, timestamp: i64) -> Result<bool> {
+        let Some(step) = self.matching_step(code, drift, timestamp)? else {
+            return Ok(false);
+        };
+
+        self.last_used_step = step;
+        Ok(true)
+    }
+
+    /// Returns the time step around `timestamp` the code has been generated
+    /// for, if it's later than the last time step used.
+    fn matching_step(&self, code: &str, drift: u8, timestamp: i64) -> Result<Option<i64>> {
+        let code = code.trim();
+        if code.len() != TOTP_DIGITS || !code.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let totp = self.totp()?;
+        #[expect(clippy::cast_possible_wrap)] // the step is a small constant
+        let current_step = timestamp.div_euclid(TOTP_STEP as i64);
+        let drift = i64::from(drift);
+        for step in (current_step - drift)..=(current_step + drift) {
+            if step <= self.last_used_step {
+                continue;
+            }
+
+            #[expect(clippy::cast_sign_loss)] // `step` is greater than `last_used_step >= 0`
+            let expected = totp.generate(step as u64 * TOTP_STEP);
+            if bool::from(expected.as_bytes().ct_eq(code.as_bytes())) {
+                return Ok(Some(step));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn totp(&self) -> Result<TOTP> {
+        let secret = Secret::Encoded(self.secret.to_string())
+            .to_bytes()
+            .map_err(|_| AuthError::backend_error(TotpError::InvalidSecret))?;
+
+        Ok(TOTP::new_unchecked(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            0,
+            TOTP_STEP,
+            secret,
+        ))
+    }
+
+    /// Returns the ID of the device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the device has not been saved to the database.
+    #[must_use]
+    pub fn id(&self) -> i64 {
+        match self.id {
+            Auto::Fixed(id) => id,
+            Auto::Auto => unreachable!("TotpDevice constructed with an unknown ID"),
+        }
+    }
+
+    /// Returns the ID of the user that owns the device.
+    #[must_use]
+    pub fn user_id(&self) -> i64 {
+        match self.user.primary_key() {
+            Auto::Fixed(id) => *id,
+            Auto::Auto => unreachable!("TotpDevice constructed with an unknown user ID"),
+        }
+    }
+
+    /// Returns the base32-encoded secret, which can be typed into the
+    /// authenticator apps.
+    #[must_use]
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+
+    /// Returns whether the device has been confirmed.
+    #[must_use]
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed
+    }
+
+    /// Returns the time the device was created at.
+    #[must_use]
+    pub fn created_at(&self) -> DateTime<FixedOffset> {
+        self.created_at
+    }
+
+    /// Returns the `otpauth://` URI used to add the device to the
+    /// authenticator apps, usually shown to the user as a QR code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::totp::TotpDevice;
+    ///
+    /// fn uri(device: &TotpDevice) -> String {
+    ///     device.provisioning_uri("My Project", "alice")
+    /// }
+    /// ```
+    #[must_use]
+    pub fn provisioning_uri(&self, issuer: &str, account_name: &str) -> String {
+        let mut url = otpauth_base_url();
+        url.set_path(&format!("{issuer}:{account_name}"));
+        url.query_pairs_mut()
+            .append_pair("secret", &self.secret)
+            .append_pair("issuer", issuer)
+            .append_pair("algorithm", "SHA1")
+            .append_pair("digits", &TOTP_DIGITS.to_string())
+            .append_pair("period", &TOTP_STEP.to_string());
+
+        url.into()
+    }
+}
+
+impl Display for TotpDevice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TOTP device #{}", self.id)
+    }
+}
+
+/// A single-use recovery code of a [`DatabaseUser`].
+///
+/// Recovery codes can be used instead of a one-time code when the user loses
+/// access to their TOTP device. Only a hash of every code is stored in the
+/// database, so the codes have to be shown to the user right after they are
+/// generated.
+#[derive(Debug, Clone)]
+#[model]
+pub struct TotpRecoveryCode {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    user: ForeignKey<DatabaseUser>,
+    code_hash: LimitedString<RECOVERY_CODE_HASH_LENGTH>,
+}
+
+impl TotpRecoveryCode {
+    /// Generates `count` new recovery codes for the user, replacing all the
+    /// previous ones.
+    ///
+    /// Returns the codes themselves, which should be shown to the user. The
+    /// codes cannot be retrieved again later, since only their hashes are
+    /// stored in the database.
+    ///
+    /// The new codes are saved before the previous ones are removed, so if
+    /// this fails halfway, the user is left with both sets of codes rather
+    /// than none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::{DEFAULT_RECOVERY_CODE_COUNT, TotpRecoveryCode};
+    /// use cot::db::Database;
+    ///
+    /// async fn codes(db: &Database, user: &DatabaseUser) -> cot::Result<Vec<String>> {
+    ///     Ok(TotpRecoveryCode::generate(db, user, DEFAULT_RECOVERY_CODE_COUNT).await?)
+    /// }
+    /// ```
+    pub async fn generate<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+        count: usize,
+    ) -> Result<Vec<String>> {
+        let user = ForeignKey::from(user);
+        let code_user = user.clone();
+        let previous_ids = query!(TotpRecoveryCode, $user == code_user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?
+            .into_iter()
+            .map(|recovery_code| Expr::value(recovery_code.id))
+            .collect::<Vec<_>>();
+
+        let mut codes = Vec::with_capacity(count);
+        let mut recovery_codes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let token = crate::utils::random::random_token::<10>();
+            let code = format!(
+                "{}-{}-{}-{}",
+                &token[0..5],
+                &token[5..10],
+                &token[10..15],
+                &token[15..20]
+            );
+
+            recovery_codes.push(Self {
+                id: Auto::auto(),
+                user: user.clone(),
+                code_hash: hash_recovery_code(&code),
+            });
+            codes.push(code);
+        }
+        db.bulk_insert(&mut recovery_codes)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        if !previous_ids.is_empty() {
+            Query::<TotpRecoveryCode>::new()
+                .filter(Expr::is_in(
+                    Expr::field(<Self as Model>::Fields::id.identifier()),
+                    previous_ids,
+                ))
+                .delete(db)
+                .await
+                .map_err(AuthError::backend_error)?;
+        }
+
+        Ok(codes)
+    }
+
+    /// Verifies the recovery code of the user and, if it's valid, removes it
+    /// so that it cannot be used again.
+    ///
+    /// The dashes and the letter case in the code are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpRecoveryCode;
+    /// use cot::db::Database;
+    ///
+    /// async fn use_code(db: &Database, user: &DatabaseUser, code: &str) -> cot::Result<bool> {
+    ///     Ok(TotpRecoveryCode::verify(db, user, code).await?)
+    /// }
+    /// ```
+    pub async fn verify<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+        code: &str,
+    ) -> Result<bool> {
+        let user = ForeignKey::from(user);
+        let code_hash = hash_recovery_code(code);
+        let result = query!(TotpRecoveryCode, $user == user && $code_hash == code_hash)
+            .delete(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(result.rows_affected().0 > 0)
+    }
+
+    /// Returns the number of unused recovery codes of the user.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there was an error querying the database.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::auth::db::DatabaseUser;
+    /// use cot::auth::totp::TotpRecoveryCode;
+    /// use cot::db::Database;
+    ///
+    /// async fn codes_left(db: &Database, user: &DatabaseUser) -> cot::Result<usize> {
+    ///     Ok(TotpRecoveryCode::count_for_user(db, user).await?)
+    /// }
+    /// ```
+    pub async fn count_for_user<DB: DatabaseBackend>(
+        db: &DB,
+        user: &DatabaseUser,
+    ) -> Result<usize> {
+        let user = ForeignKey::from(user);
+        let codes = query!(TotpRecoveryCode, $user == user)
+            .all(db)
+            .await
+            .map_err(AuthError::backend_error)?;
+
+        Ok(codes.len())
+    }
+}
+
+impl Display for TotpRecoveryCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "recovery code #{}", self.id)
+    }
+}
+
+fn random_secret() -> LimitedString<TOTP_SECRET_LENGTH> {
+    let mut secret = [0u8; TOTP_SECRET_BYTES];
+    rand::rng().fill_bytes(&mut secret);
+    let secret = Secret::Raw(secret.to_vec()).to_encoded().to_string();
+    LimitedString::new(secret).expect("TOTP secret should fit in the column")
+}
+
+fn otpauth_base_url() -> url::Url {
+    url::Url::parse("otpauth://totp/").expect("the base URI should be valid")
+}
+
+fn hash_recovery_code(code: &str) -> LimitedString<RECOVERY_CODE_HASH_LENGTH> {
+    let code: String = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    // The codes are 80-bit random strings, so a fast hash function is enough to
+    // make them impossible to recover from the hashes.
+    let hash = blake3::hash(code.as_bytes()).to_hex();
+    LimitedString::new(hash.as_str()).expect("recovery code hash should fit in the column")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthBackend;
+    use crate::auth::db::DatabaseUserBackend;
+    use crate::test::TestDatabase;
+
+    fn device(secret: &str, last_used_step: i64) -> TotpDevice {
+        TotpDevice {
+            id: Auto::fixed(1),
+            user: ForeignKey::PrimaryKey(Auto::fixed(1)),
+            secret: LimitedString::new(secret).unwrap(),
+            confirmed: true,
+            last_used_step,
+            created_at: Utc::now().fixed_offset(),
+        }
+    }
+
+    // The secret from the RFC 6238 test vectors ("12345678901234567890")
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn totp_rfc_6238_test_vectors() {
+        for (timestamp, code) in [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+        ] {
+            assert!(
+                device(RFC_SECRET, 0)
+                    .check_code(code, 0, timestamp)
+                    .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn totp_drift() {
+        // 287082 is the code for the step 1 (timestamps 30-59)
+        assert!(device(RFC_SECRET, 0).check_code("287082", 1, 60).unwrap());
+        assert!(device(RFC_SECRET, 0).check_code("287082", 1, 0).unwrap());
+        assert!(!device(RFC_SECRET, 0).check_code("287082", 0, 60).unwrap());
+        assert!(!device(RFC_SECRET, 0).check_code("287082", 1, 90).unwrap());
+    }
+
+    #[test]
+    fn totp_replay() {
+        let mut device = device(RFC_SECRET, 0);
+
+        assert!(device.check_code("287082", 1, 59).unwrap());
+        assert_eq!(device.last_used_step, 1);
+        assert!(!device.check_code("287082", 1, 59).unwrap());
+    }
+
+    #[test]
+    fn totp_invalid_codes() {
+        let mut device = device(RFC_SECRET, 0);
+
+        assert!(!device.check_code("", 1, 59).unwrap());
+        assert!(!device.check_code("28708", 1, 59).unwrap());
+        assert!(!device.check_code("2870822", 1, 59).unwrap());
+        assert!(!device.check_code("28708a", 1, 59).unwrap());
+        assert!(device.check_code(" 287082 ", 1, 59).unwrap());
+    }
+
+    #[test]
+    fn totp_provisioning_uri() {
+        let device = device(RFC_SECRET, 0);
+
+        assert_eq!(
+            device.provisioning_uri("My Project", "alice"),
+            "otpauth://totp/My%20Project:alice?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ\
+             &issuer=My+Project&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+
+    #[test]
+    fn hash_recovery_code_normalizes() {
+        assert_eq!(
+            hash_recovery_code("abcde-12345-fghij-67890"),
+            hash_recovery_code("ABCDE12345FGHIJ67890")
+        );
+        assert_ne!(
+            hash_recovery_code("abcde-12345-fghij-67890"),
+            hash_recovery_code("abcde-12345-fghij-67891")
+        );
+    }
+
+    async fn test_db() -> (TestDatabase, DatabaseUser) {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        (db, user)
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn totp_device_enroll_and_confirm() {
+        let (db, user) = test_db().await;
+
+        let mut device = TotpDevice::enroll(&db.database(), &user).await.unwrap();
+        assert_eq!(device.secret().len(), 32);
+        assert!(!device.is_confirmed());
+        assert!(
+            !TotpDevice::is_enabled_for(&db.database(), user.id())
+                .await
+                .unwrap()
+        );
+
+        let code = device.totp().unwrap().generate_current().unwrap();
+        assert!(!device.verify(&db.database(), &code).await.unwrap());
+        assert!(device.confirm(&db.database(), &code).await.unwrap());
+        assert!(
+            TotpDevice::is_enabled_for(&db.database(), user.id())
+                .await
+                .unwrap()
+        );
+        assert!(TotpDevice::enroll(&db.database(), &user).await.is_err());
+
+        // The code used to confirm the device cannot be used again
+        let mut device = TotpDevice::get_for_user(&db.database(), &user)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!device.verify(&db.database(), &code).await.unwrap());
+
+        TotpDevice::disable_for_user(&db.database(), &user)
+            .await
+            .unwrap();
+        assert!(
+            !TotpDevice::is_enabled_for(&db.database(), user.id())
+                .await
+                .unwrap()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn totp_device_concurrent_replay() {
+        let (db, user) = test_db().await;
+
+        let mut device = TotpDevice::enroll(&db.database(), &user).await.unwrap();
+        let code = device.totp().unwrap().generate_current().unwrap();
+        assert!(device.confirm(&db.database(), &code).await.unwrap());
+
+        // Two requests that loaded the device before either of them used the code
+        let mut first = TotpDevice::get_for_user(&db.database(), &user)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut second = TotpDevice::get_for_user(&db.database(), &user)
+            .await
+            .unwrap()
+            .unwrap();
+        #[expect(clippy::cast_sign_loss)]
+        let next_code = device
+            .totp()
+            .unwrap()
+            .generate(Utc::now().timestamp() as u64 + TOTP_STEP);
+
+        assert!(
+            first
+                .verify_with_drift(&db.database(), &next_code, 1)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !second
+                .verify_with_drift(&db.database(), &next_code, 1)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn totp_requires_second_factor() {
+        let (db, mut user) = test_db().await;
+        let backend = DatabaseUserBackend::new(db.database());
+        let staff_backend =
+            DatabaseUserBackend::new(db.database()).require_second_factor_for_staff(true);
+
+        assert!(!backend.requires_second_factor(&user).await.unwrap());
+        assert!(!staff_backend.requires_second_factor(&user).await.unwrap());
+        user.set_staff(true);
+        assert!(!backend.requires_second_factor(&user).await.unwrap());
+        assert!(staff_backend.requires_second_factor(&user).await.unwrap());
+
+        let mut device = TotpDevice::enroll(&db.database(), &user).await.unwrap();
+        assert!(!backend.requires_second_factor(&user).await.unwrap());
+        let code = device.totp().unwrap().generate_current().unwrap();
+        device.confirm(&db.database(), &code).await.unwrap();
+        assert!(backend.requires_second_factor(&user).await.unwrap());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn totp_recovery_codes() {
+        let (db, user) = test_db().await;
+
+        let codes = TotpRecoveryCode::generate(&db.database(), &user, 3)
+            .await
+            .unwrap();
+        assert_eq!(codes.len(), 3);
+        assert_eq!(codes[0].len(), 23);
+        assert_eq!(
+            TotpRecoveryCode::count_for_user(&db.database(), &user)
+                .await
+                .unwrap(),
+            3
+        );
+
+        assert!(
+            TotpRecoveryCode::verify(&db.database(), &user, &codes[0].to_uppercase())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !TotpRecoveryCode::verify(&db.database(), &user, &codes[0])
+                .await
+                .unwrap()
+        );
+        assert!(
+            !TotpRecoveryCode::verify(&db.database(), &user, "invalid")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            TotpRecoveryCode::count_for_user(&db.database(), &user)
+                .await
+                .unwrap(),
+            2
+        );
+
+        let new_codes = TotpRecoveryCode::generate(&db.database(), &user, 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            TotpRecoveryCode::count_for_user(&db.database(), &user)
+                .await
+                .unwrap(),
+            3
+        );
+        assert!(
+            !TotpRecoveryCode::verify(&db.database(), &user, &codes[1])
+                .await
+                .unwrap()
+        );
+        assert!(
+            TotpRecoveryCode::verify(&db.database(), &user, &new_codes[1])
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -14,6 +14,8 @@ use crate::{Bootstrapper, Error, Result};
 const CONFIG_PARAM: &str = "config";
 const COLLECT_STATIC_SUBCOMMAND: &str = "collect-static";
 const CHECK_SUBCOMMAND: &str = "check";
+#[cfg(feature = "db")]
+pub(crate) const MIGRATE_SUBCOMMAND: &str = "migrate";
 const LISTEN_PARAM: &str = "listen";
 const COLLECT_STATIC_DIR_PARAM: &str = "dir";
 
@@ -175,9 +177,65 @@ impl Cli {
         self.tasks.insert(Some(name), Box::new(task));
     }
 
+    /// Registers the built-in tasks that are only available with specific
+    /// configuration, such as applying the migrations when the database is
+    /// configured, or managing the users when the database authentication
+    /// backend is used.
+    ///
+    /// The tasks whose names are already taken by the project's own tasks are
+    /// skipped.
+    #[cfg_attr(not(feature = "db"), expect(unused_variables))]
+    pub(crate) fn register_config_tasks(&mut self, config: &ProjectConfig) {
+        #[cfg(feature = "db")]
+        if config.database.url.is_some() && !self.has_task(MIGRATE_SUBCOMMAND) {
+            self.add_task(Migrate);
+        }
+
+        #[cfg(feature = "db")]
+        if matches!(
+            config.auth_backend,
+            crate::config::AuthBackendConfig::Database
+        ) {
+            use crate::auth::db::cli::{
+                CHANGE_PASSWORD_SUBCOMMAND, CREATE_SUPERUSER_SUBCOMMAND, ChangePassword,
+                CreateSuperuser,
+            };
+
+            if !self.has_task(CREATE_SUPERUSER_SUBCOMMAND) {
+                self.add_task(CreateSuperuser);
+            }
+            if !self.has_task(CHANGE_PASSWORD_SUBCOMMAND) {
+                self.add_task(ChangePassword);
+            }
+        }
+    }
+
+    #[cfg(feature = "db")]
+    fn has_task(&self, name: &str) -> bool {
+        self.tasks.contains_key(&Some(name.to_owned()))
+    }
+
     #[must_use]
     pub(crate) fn common_options(&mut self) -> CommonOptions {
-        let matches = self.command.get_matches_mut();
+        // The config has to be read before the tasks that depend on it are
+        // registered, so the command line is parsed leniently here: unknown
+        // subcommands are accepted and help/version flags are left to be
+        // handled by `execute`.
+        let lenient_matches = self
+            .command
+            .clone()
+            .disable_help_flag(true)
+            .disable_version_flag(true)
+            .disable_help_subcommand(true)
+            .allow_external_subcommands(true)
+            .ignore_errors(true)
+            .try_get_matches();
+
+        let matches = match lenient_matches {
+            Ok(matches) if matches.get_one::<String>(CONFIG_PARAM).is_some() => matches,
+            // let clap report the error
+            _ => self.command.get_matches_mut(),
+        };
         CommonOptions::new(matches)
     }
 
@@ -389,6 +447,31 @@ impl CliTask for Check {
     }
 }
 
+/// Applies the pending database migrations without starting the server.
+#[cfg(feature = "db")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Migrate;
+
+#[cfg(feature = "db")]
+#[async_trait(?Send)]
+impl CliTask for Migrate {
+    fn subcommand(&self) -> Command {
+        Command::new(MIGRATE_SUBCOMMAND).about("Applies the pending database migrations")
+    }
+
+    async fn execute(
+        &mut self,
+        _matches: &ArgMatches,
+        bootstrapper: Bootstrapper<WithConfig>,
+    ) -> Result<()> {
+        let bootstrapper = bootstrapper.boot().await?;
+        let context = bootstrapper.context();
+        crate::project::migrate_database(context.database(), context.apps()).await?;
+        println!("Database migrations applied successfully");
+        Ok(())
+    }
+}
+
 /// A macro to generate a [`CliMetadata`] struct from the Cargo manifest.
 #[macro_export]
 macro_rules! metadata {
@@ -404,6 +487,7 @@ macro_rules! metadata {
 
 pub use metadata;
 
+use crate::config::ProjectConfig;
 use crate::project::{StartServerError, WithConfig};
 use crate::static_files::StaticFiles;
 
@@ -476,6 +560,29 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "db")]
+    fn cli_register_config_tasks() {
+        use crate::config::{AuthBackendConfig, DatabaseConfig};
+
+        let mut cli = Cli::new();
+        cli.register_config_tasks(&ProjectConfig::default());
+        assert!(!cli.tasks.contains_key(&Some("create-superuser".to_owned())));
+        assert!(!cli.tasks.contains_key(&Some("migrate".to_owned())));
+
+        let config = ProjectConfig::builder()
+            .auth_backend(AuthBackendConfig::Database)
+            .database(DatabaseConfig::builder().url("sqlite::memory:").build())
+            .build();
+        cli.register_config_tasks(&config);
+        assert!(cli.tasks.contains_key(&Some("migrate".to_owned())));
+        assert!(cli.tasks.contains_key(&Some("create-superuser".to_owned())));
+        assert!(cli.tasks.contains_key(&Some("change-password".to_owned())));
+
+        // registering again doesn't clash with the existing tasks
+        cli.register_config_tasks(&config);
+    }
+
     #[test]
     fn run_server_subcommand() {
         let matches = RunServer
@@ -15,10 +15,13 @@
 // not implementing Copy for them
 #![allow(missing_copy_implementations)]
 
+use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::path::PathBuf;
 use std::time::Duration;
 
 use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
 use cot_core::error::impl_into_cot_error;
 use derive_builder::Builder;
 use derive_more::with_trait::{Debug, From};
@@ -26,6 +29,7 @@ use serde::{Deserialize, Serialize};
 use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+use crate::auth::password_validation::PasswordValidatorConfig;
 #[cfg(feature = "email")]
 use crate::email::transport::smtp::Mechanism;
 use crate::utils::chrono::DateTimeWithOffsetAdapter;
@@ -150,6 +154,28 @@ pub struct ProjectConfig {
     /// # Ok::<(), cot::Error>(())
     /// ```
     pub auth_backend: AuthBackendConfig,
+    /// Configuration related to the HTTP Basic authentication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [basic_auth]
+    /// realm = "Webhooks"
+    ///
+    /// [basic_auth.users]
+    /// deploy = "$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$2jz2kDJZ0fLyeMZcH5Jtr7SAxyMU8UmUhSw2T3aGkTc"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(config.basic_auth.realm, "Webhooks");
+    /// assert!(config.basic_auth.users.contains_key("deploy"));
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub basic_auth: BasicAuthConfig,
     /// Configuration related to the database.
     ///
     /// # Examples
@@ -271,6 +297,166 @@ pub struct ProjectConfig {
     /// ```
     #[cfg(feature = "email")]
     pub email: EmailConfig,
+    /// Configuration related to the JSON Web Token authentication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{JwtAlgorithm, ProjectConfig};
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [jwt]
+    /// algorithm = "HS256"
+    /// issuer = "https://example.com"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(config.jwt.algorithm, JwtAlgorithm::Hs256);
+    /// assert_eq!(config.jwt.issuer.as_deref(), Some("https://example.com"));
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[cfg(feature = "jwt")]
+    pub jwt: JwtConfig,
+    /// Configuration related to the two-factor authentication.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [two_factor]
+    /// required_for_staff = true
+    /// issuer = "My Project"
+    /// "#,
+    /// )?;
+    ///
+    /// assert!(config.two_factor.required_for_staff);
+    /// assert_eq!(config.two_factor.issuer, "My Project");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[cfg(feature = "db")]
+    pub two_factor: TwoFactorConfig,
+    /// Configuration related to the verification of the users' email
+    /// addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [email_verification]
+    /// required_for_login = true
+    /// timeout = "1day"
+    /// "#,
+    /// )?;
+    ///
+    /// assert!(config.email_verification.required_for_login);
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[cfg(feature = "db")]
+    pub email_verification: EmailVerificationConfig,
+    /// Configuration related to the throttling of failed login attempts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [login_throttle]
+    /// enabled = true
+    /// max_attempts = 3
+    /// "#,
+    /// )?;
+    ///
+    /// assert!(config.login_throttle.enabled);
+    /// assert_eq!(config.login_throttle.max_attempts, 3);
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub login_throttle: LoginThrottleConfig,
+    /// The validators used to check whether the passwords set by the users
+    /// are strong enough. The default is a single
+    /// [`MinimumLength`](PasswordValidatorConfig::MinimumLength) validator
+    /// requiring
+    /// [`DEFAULT_MINIMUM_PASSWORD_LENGTH`](crate::auth::password_validation::DEFAULT_MINIMUM_PASSWORD_LENGTH)
+    /// characters; an explicitly empty list (`password_validators = []`)
+    /// accepts any password.
+    ///
+    /// See the [`password_validation`](crate::auth::password_validation)
+    /// module for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::password_validation::PasswordValidatorConfig;
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [[password_validators]]
+    /// type = "minimum_length"
+    /// min_length = 12
+    ///
+    /// [[password_validators]]
+    /// type = "common"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     config.password_validators,
+    ///     vec![
+    ///         PasswordValidatorConfig::MinimumLength { min_length: 12 },
+    ///         PasswordValidatorConfig::Common,
+    ///     ]
+    /// );
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub password_validators: Vec<PasswordValidatorConfig>,
+    /// Configuration related to the OAuth 2.0 / OpenID Connect login.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [oauth.providers.example]
+    /// client_id = "my-client-id"
+    /// authorization_endpoint = "https://example.com/authorize"
+    /// token_endpoint = "https://example.com/token"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(config.oauth.providers["example"].client_id, "my-client-id");
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[cfg(feature = "oauth")]
+    pub oauth: OAuthConfig,
+    /// Configuration related to the admin panel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [admin]
+    /// time_zone = "Europe/Warsaw"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(config.admin.time_zone, chrono_tz::Europe::Warsaw);
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    pub admin: AdminConfig,
     /// All the config that was not recognized.
     ///
     /// This is useful for parsing project-specific config that is not part of
@@ -311,6 +497,12 @@ const fn default_debug() -> bool {
     cfg!(debug_assertions)
 }
 
+fn default_password_validators() -> Vec<PasswordValidatorConfig> {
+    vec![PasswordValidatorConfig::MinimumLength {
+        min_length: crate::auth::password_validation::DEFAULT_MINIMUM_PASSWORD_LENGTH,
+    }]
+}
+
 impl Default for ProjectConfig {
     fn default() -> Self {
         ProjectConfig::builder().build()
@@ -407,6 +599,7 @@ impl ProjectConfigBuilder {
             secret_key: self.secret_key.clone().unwrap_or_default(),
             fallback_secret_keys: self.fallback_secret_keys.clone().unwrap_or_default(),
             auth_backend: self.auth_backend.unwrap_or_default(),
+            basic_auth: self.basic_auth.clone().unwrap_or_default(),
             #[cfg(feature = "db")]
             database: self.database.clone().unwrap_or_default(),
             #[cfg(feature = "cache")]
@@ -415,6 +608,20 @@ impl ProjectConfigBuilder {
             middlewares: self.middlewares.clone().unwrap_or_default(),
             #[cfg(feature = "email")]
             email: self.email.clone().unwrap_or_default(),
+            #[cfg(feature = "jwt")]
+            jwt: self.jwt.clone().unwrap_or_default(),
+            #[cfg(feature = "db")]
+            two_factor: self.two_factor.clone().unwrap_or_default(),
+            #[cfg(feature = "db")]
+            email_verification: self.email_verification.clone().unwrap_or_default(),
+            login_throttle: self.login_throttle.clone().unwrap_or_default(),
+            password_validators: self
+                .password_validators
+                .clone()
+                .unwrap_or_else(default_password_validators),
+            #[cfg(feature = "oauth")]
+            oauth: self.oauth.clone().unwrap_or_default(),
+            admin: self.admin.clone().unwrap_or_default(),
             extra: toml::Table::default(),
         }
     }
@@ -446,6 +653,160 @@ pub enum AuthBackendConfig {
     /// to be used as the authentication backend.
     #[cfg(feature = "db")]
     Database,
+    /// HTTP Basic authentication backend.
+    ///
+    /// This enables [`BasicAuthBackend`](cot::auth::basic::BasicAuthBackend)
+    /// to be used as the authentication backend. It accepts the users
+    /// listed in [`ProjectConfig::basic_auth`] and, if the database is
+    /// configured, the [`DatabaseUser`](cot::auth::db::DatabaseUser)s.
+    Basic,
+}
+
+/// The configuration for the HTTP Basic authentication.
+///
+/// It is used as part of the [`ProjectConfig`] struct by the
+/// [`BasicAuthMiddleware`](crate::middleware::BasicAuthMiddleware) and the
+/// [`BasicAuthBackend`](crate::auth::basic::BasicAuthBackend).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::PasswordHash;
+/// use cot::common_types::Password;
+/// use cot::config::BasicAuthConfig;
+///
+/// let config = BasicAuthConfig::builder()
+///     .realm("Webhooks")
+///     .user(
+///         "deploy",
+///         PasswordHash::from_password(&Password::new("password123")),
+///     )
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [basic_auth]
+/// realm = "Webhooks"
+///
+/// [basic_auth.users]
+/// deploy = "$argon2id$v=19$m=19456,t=2,p=1$c2FsdHNhbHQ$2jz2kDJZ0fLyeMZcH5Jtr7SAxyMU8UmUhSw2T3aGkTc"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct BasicAuthConfig {
+    /// The realm sent to the clients in the `WWW-Authenticate` header. The
+    /// browsers typically show it in the login prompt. The default is
+    /// `"Restricted"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::BasicAuthConfig;
+    ///
+    /// let config = BasicAuthConfig::builder().realm("Webhooks").build();
+    /// assert_eq!(config.realm, "Webhooks");
+    /// ```
+    #[builder(setter(into))]
+    pub realm: String,
+
+    /// The static credentials accepted by the
+    /// [`BasicAuthBackend`](crate::auth::basic::BasicAuthBackend), as a map
+    /// from usernames to the [hashes](crate::auth::PasswordHash) of their
+    /// passwords. The default is an empty map.
+    ///
+    /// Only the password hashes are stored in the config, so that the
+    /// passwords themselves are not leaked if the config file is. The hashes
+    /// can be generated with
+    /// [`PasswordHash::from_password`](crate::auth::PasswordHash::from_password).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use cot::config::BasicAuthConfig;
+    ///
+    /// let config = BasicAuthConfig::builder().users(BTreeMap::new()).build();
+    /// assert!(config.users.is_empty());
+    /// ```
+    pub users: BTreeMap<String, String>,
+}
+
+impl BasicAuthConfig {
+    /// Create a new [`BasicAuthConfigBuilder`] to build a
+    /// [`BasicAuthConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::BasicAuthConfig;
+    ///
+    /// let config = BasicAuthConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> BasicAuthConfigBuilder {
+        BasicAuthConfigBuilder::default()
+    }
+}
+
+impl BasicAuthConfigBuilder {
+    /// Adds a user with the given username and password hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::PasswordHash;
+    /// use cot::common_types::Password;
+    /// use cot::config::BasicAuthConfig;
+    ///
+    /// let config = BasicAuthConfig::builder()
+    ///     .user(
+    ///         "deploy",
+    ///         PasswordHash::from_password(&Password::new("password123")),
+    ///     )
+    ///     .build();
+    /// assert!(config.users.contains_key("deploy"));
+    /// ```
+    pub fn user<S: Into<String>>(
+        &mut self,
+        username: S,
+        password_hash: crate::auth::PasswordHash,
+    ) -> &mut Self {
+        self.users
+            .get_or_insert_with(BTreeMap::new)
+            .insert(username.into(), password_hash.into_string());
+        self
+    }
+
+    /// Builds the HTTP Basic authentication configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::BasicAuthConfig;
+    ///
+    /// let config = BasicAuthConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> BasicAuthConfig {
+        BasicAuthConfig {
+            realm: self
+                .realm
+                .clone()
+                .unwrap_or_else(|| crate::auth::basic::DEFAULT_BASIC_AUTH_REALM.to_owned()),
+            users: self.users.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for BasicAuthConfig {
+    fn default() -> Self {
+        BasicAuthConfig::builder().build()
+    }
 }
 
 /// The configuration for the database.
@@ -478,6 +839,54 @@ pub struct DatabaseConfig {
     /// ```
     #[builder(setter(into, strip_option), default)]
     pub url: Option<DatabaseUrl>,
+    /// The level at which the executed database queries are logged.
+    ///
+    /// Each query executed by the ORM emits a [`tracing`] span at this level
+    /// containing the SQL, the number of bind parameters, the duration of the
+    /// query, and the number of rows affected or returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{DatabaseConfig, QueryLogLevel};
+    ///
+    /// let config = DatabaseConfig::builder()
+    ///     .url("sqlite::memory:")
+    ///     .query_log_level(QueryLogLevel::Info)
+    ///     .build();
+    /// assert_eq!(config.query_log_level, QueryLogLevel::Info);
+    /// ```
+    #[builder(default)]
+    pub query_log_level: QueryLogLevel,
+    /// The duration after which a query is considered slow.
+    ///
+    /// Queries that take longer than this are additionally logged at the
+    /// `WARN` level. When set to `None`, slow queries are not reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::ProjectConfig;
+    ///
+    /// let config = ProjectConfig::from_toml(
+    ///     r#"
+    /// [database]
+    /// url = "sqlite::memory:"
+    /// slow_query_threshold = "500ms"
+    /// "#,
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     config.database.slow_query_threshold,
+    ///     Some(Duration::from_millis(500))
+    /// );
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[serde(with = "crate::serializers::humantime")]
+    #[builder(setter(strip_option), default)]
+    pub slow_query_threshold: Option<Duration>,
 }
 
 #[cfg(feature = "db")]
@@ -499,6 +908,8 @@ impl DatabaseConfigBuilder {
     pub fn build(&self) -> DatabaseConfig {
         DatabaseConfig {
             url: self.url.clone().expect("Database URL is required"),
+            query_log_level: self.query_log_level.unwrap_or_default(),
+            slow_query_threshold: self.slow_query_threshold.unwrap_or_default(),
         }
     }
 }
@@ -520,6 +931,49 @@ impl DatabaseConfig {
     }
 }
 
+/// The level at which database queries are logged.
+///
+/// This is used as part of the [`DatabaseConfig`] struct.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::QueryLogLevel;
+///
+/// let level = QueryLogLevel::default();
+/// assert_eq!(level, QueryLogLevel::Debug);
+/// ```
+#[cfg(feature = "db")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum QueryLogLevel {
+    /// Log queries at the `TRACE` level.
+    Trace,
+    /// Log queries at the `DEBUG` level.
+    #[default]
+    Debug,
+    /// Log queries at the `INFO` level.
+    Info,
+    /// Log queries at the `WARN` level.
+    Warn,
+    /// Log queries at the `ERROR` level.
+    Error,
+}
+
+#[cfg(feature = "db")]
+impl From<QueryLogLevel> for tracing::Level {
+    fn from(value: QueryLogLevel) -> Self {
+        match value {
+            QueryLogLevel::Trace => Self::TRACE,
+            QueryLogLevel::Debug => Self::DEBUG,
+            QueryLogLevel::Info => Self::INFO,
+            QueryLogLevel::Warn => Self::WARN,
+            QueryLogLevel::Error => Self::ERROR,
+        }
+    }
+}
+
 /// Expiration policy for cached values.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -2107,25 +2561,1289 @@ impl Default for EmailConfig {
     }
 }
 
-/// A secret key.
+/// The algorithm used to sign and verify JSON Web Tokens.
 ///
-/// This is a wrapper over a byte array, which is used to store a cryptographic
-/// key. This is useful for [`ProjectConfig::secret_key`] and
-/// [`ProjectConfig::fallback_secret_keys`], which are used to sign cookies and
-/// other sensitive data.
+/// This is used as part of the [`JwtConfig`] struct.
 ///
-/// # Security
+/// # Examples
 ///
-/// The implementation of the [`PartialEq`] trait for this type is constant-time
-/// to prevent timing attacks.
+/// ```
+/// use cot::config::JwtAlgorithm;
 ///
-/// The implementation of the [`Debug`] trait for this type hides the secret key
-/// to prevent it from being leaked in logs or other debug output.
+/// let algorithm = JwtAlgorithm::Rs256;
+/// ```
+#[cfg(feature = "jwt")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum JwtAlgorithm {
+    /// HMAC using SHA-256.
+    ///
+    /// The tokens are signed and verified with the same shared secret.
+    #[default]
+    #[serde(rename = "HS256")]
+    Hs256,
+    /// RSASSA-PKCS1-v1_5 using SHA-256.
+    ///
+    /// The tokens are signed with an RSA private key and verified with the
+    /// corresponding public key.
+    #[serde(rename = "RS256")]
+    Rs256,
+    /// Edwards-curve Digital Signature Algorithm (Ed25519).
+    ///
+    /// The tokens are signed with an Ed25519 private key and verified with the
+    /// corresponding public key.
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+}
+
+/// The configuration for the JSON Web Token authentication.
+///
+/// This is used by [`JwtCodec`](crate::auth::jwt::JwtCodec) to issue and
+/// validate the tokens.
 ///
 /// # Examples
 ///
 /// ```
-/// use cot::config::SecretKey;
+/// use std::time::Duration;
+///
+/// use cot::config::{JwtAlgorithm, JwtConfig};
+///
+/// let config = JwtConfig::builder()
+///     .algorithm(JwtAlgorithm::EdDsa)
+///     .private_key_file("keys/jwt.pem")
+///     .jwks_file("keys/jwks.json")
+///     .audience("my-spa")
+///     .leeway(Duration::from_secs(30))
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [jwt]
+/// algorithm = "RS256" # or "HS256", "EdDSA"
+/// private_key_file = "keys/jwt.pem"
+/// public_key_file = "keys/jwt.pub.pem" # or jwks_file = "keys/jwks.json"
+/// issuer = "https://example.com"
+/// audience = "my-spa"
+/// leeway = "1m"
+/// token_lifetime = "15m"
+/// ```
+#[cfg(feature = "jwt")]
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct JwtConfig {
+    /// The algorithm used to sign and verify the tokens. The default is
+    /// [`JwtAlgorithm::Hs256`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{JwtAlgorithm, JwtConfig};
+    ///
+    /// let config = JwtConfig::builder().algorithm(JwtAlgorithm::Rs256).build();
+    /// assert_eq!(config.algorithm, JwtAlgorithm::Rs256);
+    /// ```
+    pub algorithm: JwtAlgorithm,
+
+    /// The shared secret used with [`JwtAlgorithm::Hs256`].
+    ///
+    /// If not set, a key derived from [`ProjectConfig::secret_key`] is used
+    /// instead. Set this if the tokens have to be verified by other services,
+    /// which then need to know the secret.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{JwtConfig, SecretKey};
+    ///
+    /// let config = JwtConfig::builder()
+    ///     .secret(SecretKey::new(b"secret"))
+    ///     .build();
+    /// assert_eq!(config.secret, Some(SecretKey::new(b"secret")));
+    /// ```
+    #[builder(setter(strip_option), default)]
+    pub secret: Option<SecretKey>,
+
+    /// The path to the PEM-encoded private key used to sign the tokens with
+    /// [`JwtAlgorithm::Rs256`] or [`JwtAlgorithm::EdDsa`].
+    ///
+    /// This is only needed if the project issues the tokens itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder()
+    ///     .private_key_file("keys/jwt.pem")
+    ///     .build();
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    pub private_key_file: Option<PathBuf>,
+
+    /// The path to the PEM-encoded public key used to verify the tokens with
+    /// [`JwtAlgorithm::Rs256`] or [`JwtAlgorithm::EdDsa`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder()
+    ///     .public_key_file("keys/jwt.pub.pem")
+    ///     .build();
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    pub public_key_file: Option<PathBuf>,
+
+    /// The path to a local JSON Web Key Set file containing the keys used to
+    /// verify the tokens.
+    ///
+    /// The key is chosen using the `kid` header of the token. This takes
+    /// precedence over [`public_key_file`](Self::public_key_file).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().jwks_file("keys/jwks.json").build();
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    pub jwks_file: Option<PathBuf>,
+
+    /// The expected issuer (`iss` claim) of the tokens.
+    ///
+    /// If set, it is also added to the tokens issued by the project, and the
+    /// tokens with a different issuer are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().issuer("https://example.com").build();
+    /// assert_eq!(config.issuer.as_deref(), Some("https://example.com"));
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    pub issuer: Option<String>,
+
+    /// The expected audience (`aud` claim) of the tokens.
+    ///
+    /// If set, it is also added to the tokens issued by the project, and the
+    /// tokens for a different audience are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().audience("my-spa").build();
+    /// assert_eq!(config.audience.as_deref(), Some("my-spa"));
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    pub audience: Option<String>,
+
+    /// The allowed clock skew when validating the time-based claims (`exp`
+    /// and `nbf`). The default is 60 seconds.
+    ///
+    /// # TOML
+    ///
+    /// This field is serialized as a "human-readable" duration, like `30s`,
+    /// `2m`, etc. Please refer to the [`humantime::parse_duration`]
+    /// documentation for the supported formats for this field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().leeway(Duration::from_secs(30)).build();
+    /// assert_eq!(config.leeway, Duration::from_secs(30));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub leeway: Duration,
+
+    /// The time after which the tokens issued by the project expire. The
+    /// default is 1 hour.
+    ///
+    /// # TOML
+    ///
+    /// This field is serialized as a "human-readable" duration, like `15m`,
+    /// `1h`, etc. Please refer to the [`humantime::parse_duration`]
+    /// documentation for the supported formats for this field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder()
+    ///     .token_lifetime(Duration::from_secs(15 * 60))
+    ///     .build();
+    /// assert_eq!(config.token_lifetime, Duration::from_secs(15 * 60));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub token_lifetime: Duration,
+}
+
+#[cfg(feature = "jwt")]
+impl JwtConfig {
+    /// Create a new [`JwtConfigBuilder`] to build a [`JwtConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> JwtConfigBuilder {
+        JwtConfigBuilder::default()
+    }
+}
+
+#[cfg(feature = "jwt")]
+impl JwtConfigBuilder {
+    /// Builds the JWT configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::JwtConfig;
+    ///
+    /// let config = JwtConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> JwtConfig {
+        JwtConfig {
+            algorithm: self.algorithm.unwrap_or_default(),
+            secret: self.secret.clone().unwrap_or_default(),
+            private_key_file: self.private_key_file.clone().unwrap_or_default(),
+            public_key_file: self.public_key_file.clone().unwrap_or_default(),
+            jwks_file: self.jwks_file.clone().unwrap_or_default(),
+            issuer: self.issuer.clone().unwrap_or_default(),
+            audience: self.audience.clone().unwrap_or_default(),
+            leeway: self.leeway.unwrap_or(Duration::from_secs(60)),
+            token_lifetime: self.token_lifetime.unwrap_or(Duration::from_secs(60 * 60)),
+        }
+    }
+}
+
+#[cfg(feature = "jwt")]
+impl Default for JwtConfig {
+    fn default() -> Self {
+        JwtConfig::builder().build()
+    }
+}
+
+/// The configuration for the two-factor authentication.
+///
+/// This is used by
+/// [`DatabaseUserBackend`](crate::auth::db::DatabaseUserBackend)
+/// to decide which users have to verify a one-time code when logging in, and
+/// by the admin panel when enrolling the users in the
+/// [TOTP two-factor authentication](crate::auth::totp).
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::TwoFactorConfig;
+///
+/// let config = TwoFactorConfig::builder()
+///     .required_for_staff(true)
+///     .issuer("My Project")
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [two_factor]
+/// required_for_staff = true
+/// issuer = "My Project"
+/// drift = 1
+/// ```
+#[cfg(feature = "db")]
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct TwoFactorConfig {
+    /// Whether the staff users have to use two-factor authentication. The
+    /// default is `false`.
+    ///
+    /// If enabled, staff users who haven't set up two-factor authentication
+    /// yet are asked to do so when logging in to the admin panel. Users who
+    /// have set it up always have to verify a one-time code, regardless of
+    /// this setting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TwoFactorConfig;
+    ///
+    /// let config = TwoFactorConfig::builder().required_for_staff(true).build();
+    /// assert!(config.required_for_staff);
+    /// ```
+    pub required_for_staff: bool,
+
+    /// The name of the service shown in the authenticator apps. The default
+    /// is `Cot`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TwoFactorConfig;
+    ///
+    /// let config = TwoFactorConfig::builder().issuer("My Project").build();
+    /// assert_eq!(config.issuer, "My Project");
+    /// ```
+    #[builder(setter(into))]
+    pub issuer: String,
+
+    /// The number of 30-second time steps before and after the current one
+    /// for which the one-time codes are still accepted, to allow for clock
+    /// drift between the server and the user's device. The default is 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TwoFactorConfig;
+    ///
+    /// let config = TwoFactorConfig::builder().drift(2).build();
+    /// assert_eq!(config.drift, 2);
+    /// ```
+    pub drift: u8,
+}
+
+#[cfg(feature = "db")]
+impl TwoFactorConfig {
+    /// Create a new [`TwoFactorConfigBuilder`] to build a
+    /// [`TwoFactorConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TwoFactorConfig;
+    ///
+    /// let config = TwoFactorConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> TwoFactorConfigBuilder {
+        TwoFactorConfigBuilder::default()
+    }
+}
+
+#[cfg(feature = "db")]
+impl TwoFactorConfigBuilder {
+    /// Builds the two-factor authentication configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::TwoFactorConfig;
+    ///
+    /// let config = TwoFactorConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> TwoFactorConfig {
+        TwoFactorConfig {
+            required_for_staff: self.required_for_staff.unwrap_or_default(),
+            issuer: self.issuer.clone().unwrap_or_else(|| "Cot".to_owned()),
+            drift: self.drift.unwrap_or(crate::auth::totp::DEFAULT_TOTP_DRIFT),
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        TwoFactorConfig::builder().build()
+    }
+}
+
+/// The configuration for the verification of the users' email addresses.
+///
+/// The email addresses are verified by opening a link sent to them by the
+/// [`SignupApp`](crate::auth::signup::SignupApp).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::config::EmailVerificationConfig;
+///
+/// let config = EmailVerificationConfig::builder()
+///     .required_for_login(true)
+///     .timeout(Duration::from_secs(24 * 60 * 60))
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [email_verification]
+/// required_for_login = true
+/// timeout = "1day"
+/// ```
+#[cfg(feature = "db")]
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct EmailVerificationConfig {
+    /// Whether the users have to verify their email address before they can
+    /// log in. The default is `false`.
+    ///
+    /// If enabled, the
+    /// [`DatabaseUserBackend`](crate::auth::db::DatabaseUserBackend) rejects
+    /// the users whose email address is [not
+    /// verified](crate::auth::db::DatabaseUser::is_email_verified) with
+    /// [`AuthError::EmailNotVerified`](crate::auth::AuthError::EmailNotVerified),
+    /// even if they provide the correct password. Note that this also applies
+    /// to the users created in code, so these have to be marked as verified
+    /// with
+    /// [`DatabaseUser::set_email_verified`](crate::auth::db::DatabaseUser::set_email_verified).
+    /// The users that existed before the email verification flag was added
+    /// to the database are marked as verified by the migration adding it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::EmailVerificationConfig;
+    ///
+    /// let config = EmailVerificationConfig::builder()
+    ///     .required_for_login(true)
+    ///     .build();
+    /// assert!(config.required_for_login);
+    /// ```
+    pub required_for_login: bool,
+
+    /// The time after which the email verification links expire. The default
+    /// is 3 days.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::EmailVerificationConfig;
+    ///
+    /// let config = EmailVerificationConfig::builder()
+    ///     .timeout(Duration::from_secs(60 * 60))
+    ///     .build();
+    /// assert_eq!(config.timeout, Duration::from_secs(60 * 60));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub timeout: Duration,
+}
+
+#[cfg(feature = "db")]
+impl EmailVerificationConfig {
+    /// Create a new [`EmailVerificationConfigBuilder`] to build a
+    /// [`EmailVerificationConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::EmailVerificationConfig;
+    ///
+    /// let config = EmailVerificationConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> EmailVerificationConfigBuilder {
+        EmailVerificationConfigBuilder::default()
+    }
+}
+
+#[cfg(feature = "db")]
+impl EmailVerificationConfigBuilder {
+    /// Builds the email verification configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::EmailVerificationConfig;
+    ///
+    /// let config = EmailVerificationConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> EmailVerificationConfig {
+        EmailVerificationConfig {
+            required_for_login: self.required_for_login.unwrap_or_default(),
+            timeout: self
+                .timeout
+                .unwrap_or(crate::auth::signup::DEFAULT_EMAIL_VERIFICATION_TIMEOUT),
+        }
+    }
+}
+
+#[cfg(feature = "db")]
+impl Default for EmailVerificationConfig {
+    fn default() -> Self {
+        EmailVerificationConfig::builder().build()
+    }
+}
+
+/// The configuration for the throttling of failed login attempts.
+///
+/// When enabled, [`Auth::authenticate`](crate::auth::Auth::authenticate)
+/// keeps track of the failed login attempts per username and per client IP
+/// address, and once there are too many of them within the configured
+/// [window](Self::window), rejects any further attempts with
+/// [`AuthError::TooManyAttempts`](crate::auth::AuthError::TooManyAttempts)
+/// for the [lockout time](Self::lockout). The lockout time doubles with each
+/// subsequent failure, up to the [maximum lockout time](Self::max_lockout).
+/// See the [`throttle`](crate::auth::throttle) module for more details.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use cot::config::LoginThrottleConfig;
+///
+/// let config = LoginThrottleConfig::builder()
+///     .enabled(true)
+///     .max_attempts(3)
+///     .lockout(Duration::from_secs(5 * 60))
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [login_throttle]
+/// enabled = true
+/// max_attempts = 5
+/// max_attempts_per_ip = 20
+/// window = "15m"
+/// lockout = "1m"
+/// max_lockout = "1h"
+/// trusted_proxies = ["10.0.0.1"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct LoginThrottleConfig {
+    /// Whether the failed login attempts are throttled. The default is
+    /// `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder().enabled(true).build();
+    /// assert!(config.enabled);
+    /// ```
+    pub enabled: bool,
+
+    /// The number of failed login attempts for a single username after which
+    /// it is locked out. The default is 5.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder().max_attempts(3).build();
+    /// assert_eq!(config.max_attempts, 3);
+    /// ```
+    pub max_attempts: u32,
+
+    /// The number of failed login attempts from a single IP address (for any
+    /// usernames) after which it is locked out. The default is 20.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder()
+    ///     .max_attempts_per_ip(50)
+    ///     .build();
+    /// assert_eq!(config.max_attempts_per_ip, 50);
+    /// ```
+    pub max_attempts_per_ip: u32,
+
+    /// The time within which the failed login attempts are counted. The
+    /// default is 15 minutes.
+    ///
+    /// # TOML
+    ///
+    /// This field is serialized as a "human-readable" duration, like `30s`,
+    /// `2m`, etc. Please refer to the [`humantime::parse_duration`]
+    /// documentation for the supported formats for this field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder()
+    ///     .window(Duration::from_secs(60 * 60))
+    ///     .build();
+    /// assert_eq!(config.window, Duration::from_secs(60 * 60));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub window: Duration,
+
+    /// The time for which the username or IP address is locked out after
+    /// reaching the maximum number of failed attempts. The default is 1
+    /// minute.
+    ///
+    /// # TOML
+    ///
+    /// This field is serialized as a "human-readable" duration, like `30s`,
+    /// `2m`, etc. Please refer to the [`humantime::parse_duration`]
+    /// documentation for the supported formats for this field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder()
+    ///     .lockout(Duration::from_secs(5 * 60))
+    ///     .build();
+    /// assert_eq!(config.lockout, Duration::from_secs(5 * 60));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub lockout: Duration,
+
+    /// The maximum lockout time, no matter how many failed attempts there
+    /// were. The default is 1 hour.
+    ///
+    /// # TOML
+    ///
+    /// This field is serialized as a "human-readable" duration, like `30s`,
+    /// `2m`, etc. Please refer to the [`humantime::parse_duration`]
+    /// documentation for the supported formats for this field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder()
+    ///     .max_lockout(Duration::from_secs(24 * 60 * 60))
+    ///     .build();
+    /// assert_eq!(config.max_lockout, Duration::from_secs(24 * 60 * 60));
+    /// ```
+    #[serde(with = "crate::serializers::humantime_required")]
+    pub max_lockout: Duration,
+
+    /// The IP addresses of the reverse proxies whose `X-Forwarded-For`
+    /// headers are trusted. The default is empty.
+    ///
+    /// The failed attempts are tracked per the IP address of the peer
+    /// connected to the server. When the application runs behind a reverse
+    /// proxy, this is the address of the proxy, so all the clients would
+    /// share the same limit. If the peer is one of the trusted proxies, the
+    /// client address is taken from the `X-Forwarded-For` header instead: it's
+    /// the rightmost address in the header that is not a trusted proxy.
+    ///
+    /// Only list the proxies you control and that set the header themselves,
+    /// since the clients can put anything in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::net::{IpAddr, Ipv4Addr};
+    ///
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder()
+    ///     .trusted_proxies(vec![IpAddr::V4(Ipv4Addr::LOCALHOST)])
+    ///     .build();
+    /// assert_eq!(
+    ///     config.trusted_proxies,
+    ///     vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]
+    /// );
+    /// ```
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl LoginThrottleConfig {
+    /// Create a new [`LoginThrottleConfigBuilder`] to build a
+    /// [`LoginThrottleConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> LoginThrottleConfigBuilder {
+        LoginThrottleConfigBuilder::default()
+    }
+}
+
+impl LoginThrottleConfigBuilder {
+    /// Builds the login throttling configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::LoginThrottleConfig;
+    ///
+    /// let config = LoginThrottleConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> LoginThrottleConfig {
+        LoginThrottleConfig {
+            enabled: self.enabled.unwrap_or_default(),
+            max_attempts: self.max_attempts.unwrap_or(5),
+            max_attempts_per_ip: self.max_attempts_per_ip.unwrap_or(20),
+            window: self.window.unwrap_or(Duration::from_secs(15 * 60)),
+            lockout: self.lockout.unwrap_or(Duration::from_secs(60)),
+            max_lockout: self.max_lockout.unwrap_or(Duration::from_secs(60 * 60)),
+            trusted_proxies: self.trusted_proxies.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        LoginThrottleConfig::builder().build()
+    }
+}
+
+/// The configuration for the OAuth 2.0 / OpenID Connect login.
+///
+/// This is used by the [`OAuthApp`](crate::auth::oauth::OAuthApp) to log in
+/// the users with external identity providers. Each provider is identified by
+/// a name, which is used in the URLs of the login and callback views.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::{OAuthConfig, OAuthProviderConfig};
+///
+/// let config = OAuthConfig::builder()
+///     .provider(
+///         "google",
+///         OAuthProviderConfig::builder()
+///             .client_id("my-client-id")
+///             .authorization_endpoint("https://accounts.google.com/o/oauth2/v2/auth")
+///             .token_endpoint("https://oauth2.googleapis.com/token")
+///             .build(),
+///     )
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [oauth.providers.google]
+/// client_id = "my-client-id"
+/// client_secret = "my-client-secret"
+/// authorization_endpoint = "https://accounts.google.com/o/oauth2/v2/auth"
+/// token_endpoint = "https://oauth2.googleapis.com/token"
+/// userinfo_endpoint = "https://openidconnect.googleapis.com/v1/userinfo"
+/// jwks_uri = "https://www.googleapis.com/oauth2/v3/certs"
+/// issuer = "https://accounts.google.com"
+/// id_token_algorithms = ["RS256"]
+/// scopes = ["openid", "email", "profile"]
+/// ```
+#[cfg(feature = "oauth")]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct OAuthConfig {
+    /// The identity providers the users can log in with, keyed by their
+    /// names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use cot::config::OAuthConfig;
+    ///
+    /// let config = OAuthConfig::builder().providers(BTreeMap::new()).build();
+    /// assert!(config.providers.is_empty());
+    /// ```
+    pub providers: BTreeMap<String, OAuthProviderConfig>,
+}
+
+#[cfg(feature = "oauth")]
+impl OAuthConfig {
+    /// Create a new [`OAuthConfigBuilder`] to build an [`OAuthConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthConfig;
+    ///
+    /// let config = OAuthConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> OAuthConfigBuilder {
+        OAuthConfigBuilder::default()
+    }
+}
+
+#[cfg(feature = "oauth")]
+impl OAuthConfigBuilder {
+    /// Adds an identity provider with the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{OAuthConfig, OAuthProviderConfig};
+    ///
+    /// let config = OAuthConfig::builder()
+    ///     .provider(
+    ///         "example",
+    ///         OAuthProviderConfig::builder()
+    ///             .client_id("client")
+    ///             .authorization_endpoint("https://example.com/authorize")
+    ///             .token_endpoint("https://example.com/token")
+    ///             .build(),
+    ///     )
+    ///     .build();
+    /// assert!(config.providers.contains_key("example"));
+    /// ```
+    pub fn provider<S: Into<String>>(
+        &mut self,
+        name: S,
+        provider: OAuthProviderConfig,
+    ) -> &mut Self {
+        self.providers
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.into(), provider);
+        self
+    }
+
+    /// Builds the OAuth configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthConfig;
+    ///
+    /// let config = OAuthConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> OAuthConfig {
+        OAuthConfig {
+            providers: self.providers.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// The configuration of a single OAuth 2.0 / OpenID Connect identity
+/// provider.
+///
+/// This is used as part of the [`OAuthConfig`] struct.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::{OAuthProviderConfig, SecretKey};
+///
+/// let config = OAuthProviderConfig::builder()
+///     .client_id("my-client-id")
+///     .client_secret(SecretKey::from("my-client-secret"))
+///     .authorization_endpoint("https://example.com/authorize")
+///     .token_endpoint("https://example.com/token")
+///     .userinfo_endpoint("https://example.com/userinfo")
+///     .build();
+/// ```
+#[cfg(feature = "oauth")]
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[non_exhaustive]
+pub struct OAuthProviderConfig {
+    /// The client ID issued by the provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// assert_eq!(config.client_id, "my-client-id");
+    /// ```
+    #[builder(setter(into))]
+    pub client_id: String,
+
+    /// The client secret issued by the provider. Public clients, which only
+    /// rely on PKCE, don't have one.
+    ///
+    /// The client secret is also used to verify the ID tokens signed with
+    /// [`JwtAlgorithm::Hs256`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{OAuthProviderConfig, SecretKey};
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .client_secret(SecretKey::from("my-client-secret"))
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// assert_eq!(
+    ///     config.client_secret,
+    ///     Some(SecretKey::from("my-client-secret"))
+    /// );
+    /// ```
+    #[builder(setter(strip_option), default)]
+    #[serde(default)]
+    pub client_secret: Option<SecretKey>,
+
+    /// The URL the users are redirected to in order to authorize the
+    /// project.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// assert_eq!(
+    ///     config.authorization_endpoint,
+    ///     "https://example.com/authorize"
+    /// );
+    /// ```
+    #[builder(setter(into))]
+    pub authorization_endpoint: String,
+
+    /// The URL used to exchange the authorization code for the tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// assert_eq!(config.token_endpoint, "https://example.com/token");
+    /// ```
+    #[builder(setter(into))]
+    pub token_endpoint: String,
+
+    /// The URL used to fetch the information about the user. If not set, the
+    /// user information is read from the ID token only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .userinfo_endpoint("https://example.com/userinfo")
+    ///     .build();
+    /// assert_eq!(
+    ///     config.userinfo_endpoint.as_deref(),
+    ///     Some("https://example.com/userinfo")
+    /// );
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+
+    /// The URL of the JSON Web Key Set used to verify the ID tokens signed
+    /// with asymmetric algorithms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .jwks_uri("https://example.com/jwks")
+    ///     .build();
+    /// assert_eq!(config.jwks_uri.as_deref(), Some("https://example.com/jwks"));
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+
+    /// The expected `iss` claim of the ID tokens. It has to be set if the
+    /// provider returns ID tokens; otherwise, the ID tokens are rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .issuer("https://example.com")
+    ///     .build();
+    /// assert_eq!(config.issuer.as_deref(), Some("https://example.com"));
+    /// ```
+    #[builder(setter(into, strip_option), default)]
+    #[serde(default)]
+    pub issuer: Option<String>,
+
+    /// The algorithms the ID tokens are allowed to be signed with. The ID
+    /// tokens signed with any other algorithm are rejected. The default is
+    /// [`JwtAlgorithm::Rs256`], which is what the OpenID Connect providers
+    /// use unless configured otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{JwtAlgorithm, OAuthProviderConfig};
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .id_token_algorithms(vec![JwtAlgorithm::EdDsa])
+    ///     .build();
+    /// assert_eq!(config.id_token_algorithms, vec![JwtAlgorithm::EdDsa]);
+    /// ```
+    #[serde(default = "default_oauth_id_token_algorithms")]
+    pub id_token_algorithms: Vec<JwtAlgorithm>,
+
+    /// The scopes requested from the provider. The default is `openid`,
+    /// `email` and `profile`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .scopes(vec!["openid".to_owned()])
+    ///     .build();
+    /// assert_eq!(config.scopes, vec!["openid"]);
+    /// ```
+    #[serde(default = "default_oauth_scopes")]
+    pub scopes: Vec<String>,
+}
+
+#[cfg(feature = "oauth")]
+fn default_oauth_id_token_algorithms() -> Vec<JwtAlgorithm> {
+    vec![JwtAlgorithm::Rs256]
+}
+
+#[cfg(feature = "oauth")]
+fn default_oauth_scopes() -> Vec<String> {
+    vec![
+        "openid".to_owned(),
+        "email".to_owned(),
+        "profile".to_owned(),
+    ]
+}
+
+#[cfg(feature = "oauth")]
+impl OAuthProviderConfig {
+    /// Create a new [`OAuthProviderConfigBuilder`] to build an
+    /// [`OAuthProviderConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> OAuthProviderConfigBuilder {
+        OAuthProviderConfigBuilder::default()
+    }
+}
+
+#[cfg(feature = "oauth")]
+impl OAuthProviderConfigBuilder {
+    /// Builds the identity provider configuration.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the client ID, the authorization endpoint or the
+    /// token endpoint is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::OAuthProviderConfig;
+    ///
+    /// let config = OAuthProviderConfig::builder()
+    ///     .client_id("my-client-id")
+    ///     .authorization_endpoint("https://example.com/authorize")
+    ///     .token_endpoint("https://example.com/token")
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> OAuthProviderConfig {
+        OAuthProviderConfig {
+            client_id: self.client_id.clone().expect("Client ID is required"),
+            client_secret: self.client_secret.clone().unwrap_or_default(),
+            authorization_endpoint: self
+                .authorization_endpoint
+                .clone()
+                .expect("Authorization endpoint is required"),
+            token_endpoint: self
+                .token_endpoint
+                .clone()
+                .expect("Token endpoint is required"),
+            userinfo_endpoint: self.userinfo_endpoint.clone().unwrap_or_default(),
+            jwks_uri: self.jwks_uri.clone().unwrap_or_default(),
+            issuer: self.issuer.clone().unwrap_or_default(),
+            id_token_algorithms: self
+                .id_token_algorithms
+                .clone()
+                .unwrap_or_else(default_oauth_id_token_algorithms),
+            scopes: self.scopes.clone().unwrap_or_else(default_oauth_scopes),
+        }
+    }
+}
+
+/// The configuration for the [admin panel](crate::admin::AdminApp).
+///
+/// This is used as part of the [`ProjectConfig`] struct.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::AdminConfig;
+///
+/// let config = AdminConfig::builder()
+///     .time_zone(chrono_tz::Europe::Warsaw)
+///     .build();
+/// ```
+///
+/// # TOML Configuration
+///
+/// ```toml
+/// [admin]
+/// time_zone = "Europe/Warsaw"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Builder, Serialize, Deserialize)]
+#[builder(build_fn(skip, error = std::convert::Infallible))]
+#[serde(default)]
+#[non_exhaustive]
+pub struct AdminConfig {
+    /// The time zone the dates and times are displayed in. The default is
+    /// UTC.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::AdminConfig;
+    ///
+    /// let config = AdminConfig::builder()
+    ///     .time_zone(chrono_tz::America::New_York)
+    ///     .build();
+    /// assert_eq!(config.time_zone, chrono_tz::America::New_York);
+    /// ```
+    #[serde(with = "crate::serializers::time_zone")]
+    pub time_zone: Tz,
+}
+
+impl AdminConfig {
+    /// Create a new [`AdminConfigBuilder`] to build an [`AdminConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::AdminConfig;
+    ///
+    /// let config = AdminConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> AdminConfigBuilder {
+        AdminConfigBuilder::default()
+    }
+}
+
+impl AdminConfigBuilder {
+    /// Builds the admin panel configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::AdminConfig;
+    ///
+    /// let config = AdminConfig::builder().build();
+    /// ```
+    #[must_use]
+    pub fn build(&self) -> AdminConfig {
+        AdminConfig {
+            time_zone: self.time_zone.unwrap_or(Tz::UTC),
+        }
+    }
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        AdminConfig::builder().build()
+    }
+}
+
+/// A secret key.
+///
+/// This is a wrapper over a byte array, which is used to store a cryptographic
+/// key. This is useful for [`ProjectConfig::secret_key`] and
+/// [`ProjectConfig::fallback_secret_keys`], which are used to sign cookies and
+/// other sensitive data.
+///
+/// # Security
+///
+/// The implementation of the [`PartialEq`] trait for this type is constant-time
+/// to prevent timing attacks.
+///
+/// The implementation of the [`Debug`] trait for this type hides the secret key
+/// to prevent it from being leaked in logs or other debug output.
+///
+/// # Examples
+///
+/// ```
+/// use cot::config::SecretKey;
 ///
 /// let key = SecretKey::new(&[1, 2, 3]);
 /// assert_eq!(key.as_bytes(), &[1, 2, 3]);
@@ -2764,6 +4482,25 @@ mod tests {
             StaticFilesPathRewriteMode::QueryParam
         );
     }
+
+    #[test]
+    fn password_validators_default() {
+        let config = ProjectConfig::from_toml("").unwrap();
+        assert_eq!(
+            config.password_validators,
+            vec![PasswordValidatorConfig::MinimumLength {
+                min_length: crate::auth::password_validation::DEFAULT_MINIMUM_PASSWORD_LENGTH,
+            }]
+        );
+        assert_eq!(
+            ProjectConfig::default().password_validators,
+            config.password_validators
+        );
+
+        let config = ProjectConfig::from_toml("password_validators = []").unwrap();
+        assert!(config.password_validators.is_empty());
+    }
+
     #[test]
     #[cfg(feature = "redis")]
     fn cache_type_from_str_redis() {
@@ -3152,6 +4889,208 @@ mod tests {
         assert_eq!(u1.as_str(), s);
     }
 
+    #[test]
+    #[cfg(feature = "jwt")]
+    fn jwt_config_from_toml() {
+        let toml_content = r#"
+            [jwt]
+            algorithm = "EdDSA"
+            private_key_file = "keys/jwt.pem"
+            jwks_file = "keys/jwks.json"
+            issuer = "https://example.com"
+            audience = "spa"
+            leeway = "30s"
+            token_lifetime = "15m"
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert_eq!(config.jwt.algorithm, JwtAlgorithm::EdDsa);
+        assert_eq!(config.jwt.secret, None);
+        assert_eq!(
+            config.jwt.private_key_file,
+            Some(PathBuf::from("keys/jwt.pem"))
+        );
+        assert_eq!(config.jwt.public_key_file, None);
+        assert_eq!(config.jwt.jwks_file, Some(PathBuf::from("keys/jwks.json")));
+        assert_eq!(config.jwt.issuer.as_deref(), Some("https://example.com"));
+        assert_eq!(config.jwt.audience.as_deref(), Some("spa"));
+        assert_eq!(config.jwt.leeway, Duration::from_secs(30));
+        assert_eq!(config.jwt.token_lifetime, Duration::from_secs(15 * 60));
+    }
+
+    #[test]
+    #[cfg(feature = "jwt")]
+    fn jwt_config_defaults() {
+        let config = ProjectConfig::from_toml("").unwrap();
+
+        assert_eq!(config.jwt, JwtConfig::default());
+        assert_eq!(config.jwt.algorithm, JwtAlgorithm::Hs256);
+        assert_eq!(config.jwt.leeway, Duration::from_secs(60));
+        assert_eq!(config.jwt.token_lifetime, Duration::from_secs(3600));
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    fn two_factor_config_from_toml() {
+        let toml_content = r#"
+            [two_factor]
+            required_for_staff = true
+            issuer = "Example"
+            drift = 2
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert!(config.two_factor.required_for_staff);
+        assert_eq!(config.two_factor.issuer, "Example");
+        assert_eq!(config.two_factor.drift, 2);
+        assert_eq!(
+            ProjectConfig::default().two_factor,
+            TwoFactorConfig::default()
+        );
+        assert!(!TwoFactorConfig::default().required_for_staff);
+        assert_eq!(TwoFactorConfig::default().issuer, "Cot");
+        assert_eq!(TwoFactorConfig::default().drift, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "db")]
+    fn email_verification_config_from_toml() {
+        let toml_content = r#"
+            [email_verification]
+            required_for_login = true
+            timeout = "1h"
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert!(config.email_verification.required_for_login);
+        assert_eq!(
+            config.email_verification.timeout,
+            Duration::from_secs(60 * 60)
+        );
+        assert!(!EmailVerificationConfig::default().required_for_login);
+        assert_eq!(
+            EmailVerificationConfig::default().timeout,
+            Duration::from_secs(3 * 24 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn basic_auth_config_from_toml() {
+        let toml_content = r#"
+            [auth_backend]
+            type = "basic"
+
+            [basic_auth]
+            realm = "Webhooks"
+
+            [basic_auth.users]
+            deploy = "hash"
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert_eq!(config.auth_backend, AuthBackendConfig::Basic);
+        assert_eq!(config.basic_auth.realm, "Webhooks");
+        assert_eq!(config.basic_auth.users["deploy"], "hash");
+        assert_eq!(BasicAuthConfig::default().realm, "Restricted");
+        assert!(BasicAuthConfig::default().users.is_empty());
+    }
+
+    #[test]
+    fn admin_config_from_toml() {
+        let toml_content = r#"
+            [admin]
+            time_zone = "America/New_York"
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert_eq!(config.admin.time_zone, Tz::America__New_York);
+        assert_eq!(AdminConfig::default().time_zone, Tz::UTC);
+        assert!(ProjectConfig::from_toml("[admin]\ntime_zone = \"Mars/Olympus\"").is_err());
+    }
+
+    #[test]
+    fn login_throttle_config_from_toml() {
+        let toml_content = r#"
+            [login_throttle]
+            enabled = true
+            max_attempts = 3
+            max_attempts_per_ip = 10
+            window = "1h"
+            lockout = "5m"
+            max_lockout = "1day"
+            trusted_proxies = ["10.0.0.1", "::1"]
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert!(config.login_throttle.enabled);
+        assert_eq!(config.login_throttle.max_attempts, 3);
+        assert_eq!(config.login_throttle.max_attempts_per_ip, 10);
+        assert_eq!(config.login_throttle.window, Duration::from_secs(60 * 60));
+        assert_eq!(config.login_throttle.lockout, Duration::from_secs(5 * 60));
+        assert_eq!(
+            config.login_throttle.max_lockout,
+            Duration::from_secs(24 * 60 * 60)
+        );
+        assert_eq!(
+            config.login_throttle.trusted_proxies,
+            [
+                IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1)),
+                IpAddr::V6(std::net::Ipv6Addr::LOCALHOST)
+            ]
+        );
+        assert!(!ProjectConfig::default().login_throttle.enabled);
+    }
+
+    #[test]
+    #[cfg(feature = "oauth")]
+    fn oauth_config_from_toml() {
+        let toml_content = r#"
+            [oauth.providers.example]
+            client_id = "client"
+            client_secret = "secret"
+            authorization_endpoint = "https://example.com/authorize"
+            token_endpoint = "https://example.com/token"
+            userinfo_endpoint = "https://example.com/userinfo"
+            issuer = "https://example.com"
+            id_token_algorithms = ["HS256", "EdDSA"]
+
+            [oauth.providers.other]
+            client_id = "other-client"
+            authorization_endpoint = "https://other.example.com/authorize"
+            token_endpoint = "https://other.example.com/token"
+            scopes = ["openid"]
+        "#;
+
+        let config = ProjectConfig::from_toml(toml_content).unwrap();
+
+        assert_eq!(config.oauth.providers.len(), 2);
+        let example = &config.oauth.providers["example"];
+        assert_eq!(example.client_id, "client");
+        assert_eq!(example.client_secret, Some(SecretKey::from("secret")));
+        assert_eq!(
+            example.userinfo_endpoint.as_deref(),
+            Some("https://example.com/userinfo")
+        );
+        assert_eq!(example.issuer.as_deref(), Some("https://example.com"));
+        assert_eq!(example.jwks_uri, None);
+        assert_eq!(
+            example.id_token_algorithms,
+            vec![JwtAlgorithm::Hs256, JwtAlgorithm::EdDsa]
+        );
+        assert_eq!(example.scopes, vec!["openid", "email", "profile"]);
+        let other = &config.oauth.providers["other"];
+        assert_eq!(other.client_secret, None);
+        assert_eq!(other.id_token_algorithms, vec![JwtAlgorithm::Rs256]);
+        assert_eq!(other.scopes, vec!["openid"]);
+        assert_eq!(ProjectConfig::default().oauth, OAuthConfig::default());
+    }
+
     #[test]
     fn config_extra_can_be_accessed() {
         #[derive(Deserialize)]
@@ -0,0 +1,219 @@
+//! Cross-site request forgery (CSRF) protection.
+//!
+//! This module provides the [`CsrfToken`] type, which is a secret token bound
+//! to the user's session. The token must be sent along with every request that
+//! uses an unsafe HTTP method (such as `POST`), either as a form field named
+//! [`CSRF_FIELD_NAME`] or in the [`CSRF_HEADER_NAME`] header. The requests
+//! are validated by the [`CsrfMiddleware`](crate::middleware::CsrfMiddleware),
+//! which rejects the ones that do not contain a valid token with a
+//! `403 Forbidden` error.
+//!
+//! # Examples
+//!
+//! [`CsrfToken`] can be extracted in a request handler and passed to a
+//! template. Rendering the token outputs a hidden form field:
+//!
+//! ```
+//! use cot::Template;
+//! use cot::csrf::CsrfToken;
+//! use cot::html::Html;
+//!
+//! #[derive(Debug, Template)]
+//! #[template(
+//!     source = r#"<form method="post">{{ csrf_token }}<button>Send</button></form>"#,
+//!     ext = "html"
+//! )]
+//! struct IndexTemplate {
+//!     csrf_token: CsrfToken,
+//! }
+//!
+//! async fn index(csrf_token: CsrfToken) -> cot::Result<Html> {
+//!     let template = IndexTemplate { csrf_token };
+//!     Ok(Html::new(template.render()?))
+//! }
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+use askama::filters::HtmlSafe;
+use cot_core::error::impl_into_cot_error;
+use cot_core::html::HtmlTag;
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+use crate::session::Session;
+
+/// The name of the form field that contains the CSRF token.
+pub const CSRF_FIELD_NAME: &str = "csrf_token";
+
+/// The name of the HTTP header that can contain the CSRF token.
+///
+/// This is useful for requests sent by JavaScript code, where adding a form
+/// field is not convenient.
+pub const CSRF_HEADER_NAME: &str = "x-csrf-token";
+
+const CSRF_SESSION_KEY: &str = "__cot_csrf_token";
+
+/// An error returned when a request does not pass the CSRF validation.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CsrfError {
+    /// The request does not contain a CSRF token.
+    #[error(
+        "CSRF verification failed: the request does not contain a CSRF token (expected a \
+        `{CSRF_FIELD_NAME}` form field or a `{CSRF_HEADER_NAME}` header)"
+    )]
+    Missing,
+    /// The CSRF token sent in the request does not match the one stored in
+    /// the session.
+    #[error("CSRF verification failed: the CSRF token is invalid or has expired")]
+    Invalid,
+}
+impl_into_cot_error!(CsrfError, FORBIDDEN);
+
+/// An error returned when [`CsrfToken`] is extracted in a request handler,
+/// but the [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) is not
+/// enabled for the route or the project.
+///
+/// This is a configuration error, so it results in a
+/// `500 Internal Server Error` response.
+#[derive(Debug, Error)]
+#[error(
+    "the CSRF token is not available: the `CsrfMiddleware` is not enabled for the route/project"
+)]
+#[non_exhaustive]
+pub struct CsrfMiddlewareNotEnabled;
+impl_into_cot_error!(CsrfMiddlewareNotEnabled);
+
+/// A CSRF token of the current session.
+///
+/// This is added to the request by the
+/// [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) and can be extracted
+/// in request handlers. When rendered in a template, the token outputs a
+/// hidden `<input>` element that should be put inside every `<form>` that
+/// sends a `POST` request.
+///
+/// # Examples
+///
+/// ```
+/// use cot::csrf::CsrfToken;
+/// use cot::html::Html;
+///
+/// async fn index(csrf_token: CsrfToken) -> Html {
+///     Html::new(format!(
+///         r#"<form method="post">{csrf_token}<button>Send</button></form>"#
+///     ))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    /// Returns the CSRF token stored in the session, generating a new one if
+    /// there is none yet.
+    pub(crate) async fn from_session(session: &Session) -> crate::Result<Self> {
+        if let Some(token) = session.get::<String>(CSRF_SESSION_KEY).await? {
+            return Ok(Self(token));
+        }
+
+        let token = crate::utils::random::random_token::<32>();
+        session.insert(CSRF_SESSION_KEY, &token).await?;
+        Ok(Self(token))
+    }
+
+    /// Removes the CSRF token from the session, so that a new one is
+    /// generated for the next request.
+    ///
+    /// This is done when the user logs in, so that a token obtained before
+    /// the privilege level changed cannot be used afterwards.
+    pub(crate) async fn rotate(session: &Session) -> Result<(), tower_sessions::session::Error> {
+        session.remove_value(CSRF_SESSION_KEY).await?;
+        Ok(())
+    }
+
+    /// Returns the value of the token.
+    ///
+    /// This can be used to send the token in the [`CSRF_HEADER_NAME`] header,
+    /// for instance from JavaScript code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::csrf::CsrfToken;
+    /// use cot::html::Html;
+    ///
+    /// async fn index(csrf_token: CsrfToken) -> Html {
+    ///     Html::new(format!(
+    ///         r#"<meta name="csrf-token" content="{}">"#,
+    ///         csrf_token.as_str()
+    ///     ))
+    /// }
+    /// ```
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Checks in constant time whether the given value matches this token.
+    #[must_use]
+    pub(crate) fn verify(&self, value: &str) -> bool {
+        self.0.as_bytes().ct_eq(value.as_bytes()).into()
+    }
+}
+
+impl Display for CsrfToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tag = HtmlTag::input("hidden");
+        tag.attr("name", CSRF_FIELD_NAME);
+        tag.attr("value", &self.0);
+
+        write!(f, "{}", tag.render())
+    }
+}
+
+impl HtmlSafe for CsrfToken {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    #[test]
+    fn csrf_token_render() {
+        let token = CsrfToken("abc123".to_owned());
+
+        assert_eq!(
+            token.to_string(),
+            r#"<input type="hidden" name="csrf_token" value="abc123"/>"#
+        );
+    }
+
+    #[test]
+    fn csrf_token_verify() {
+        let token = CsrfToken("abc123".to_owned());
+
+        assert!(token.verify("abc123"));
+        assert!(!token.verify("abc124"));
+        assert!(!token.verify(""));
+    }
+
+    #[cot::test]
+    async fn csrf_token_from_session_is_stable() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let session = Session::from_request(&request);
+
+        let token = CsrfToken::from_session(session).await.unwrap();
+        assert_eq!(token.as_str().len(), 64);
+        assert_eq!(CsrfToken::from_session(session).await.unwrap(), token);
+    }
+
+    #[cot::test]
+    async fn csrf_token_rotate() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let session = Session::from_request(&request);
+
+        let token = CsrfToken::from_session(session).await.unwrap();
+        CsrfToken::rotate(session).await.unwrap();
+        assert_ne!(CsrfToken::from_session(session).await.unwrap(), token);
+    }
+}
@@ -12,6 +12,7 @@ pub mod impl_postgres;
 pub mod impl_sqlite;
 pub mod migrations;
 pub mod query;
+pub(crate) mod query_log;
 mod relations;
 mod sea_query_db;
 
@@ -36,6 +37,7 @@ use sqlx::{Type, TypeInfo};
 use thiserror::Error;
 use tracing::{Instrument, Level, span, trace};
 
+use crate::config::DatabaseConfig;
 #[cfg(feature = "mysql")]
 use crate::db::impl_mysql::{DatabaseMySql, MySqlRow, MySqlValueRef};
 #[cfg(feature = "postgres")]
@@ -43,6 +45,7 @@ use crate::db::impl_postgres::{DatabasePostgres, PostgresRow, PostgresValueRef};
 #[cfg(feature = "sqlite")]
 use crate::db::impl_sqlite::{DatabaseSqlite, SqliteRow, SqliteValueRef};
 use crate::db::migrations::ColumnTypeMapper;
+use crate::db::query_log::QueryLogger;
 
 const ERROR_PREFIX: &str = "database error:";
 /// An error that can occur when interacting with the database.
@@ -82,6 +85,23 @@ pub enum DatabaseError {
     /// was not found.
     #[error("{ERROR_PREFIX} error retrieving a Foreign Key from the database: record not found")]
     ForeignKeyNotFound,
+    /// The object could not be updated because it has been modified in the
+    /// database since it was fetched (i.e. its version column does not match).
+    #[error(
+        "{ERROR_PREFIX} record with primary key `{primary_key}` has been modified or removed \
+        since it was fetched"
+    )]
+    StaleObject {
+        /// The primary key of the record that could not be updated.
+        primary_key: DbValue,
+    },
+    /// Soft-deleted rows were to be restored, but the model is not
+    /// soft-deletable (see [`Model::SOFT_DELETE_COLUMN`]).
+    #[error("{ERROR_PREFIX} model `{table_name}` is not soft-deletable")]
+    NotSoftDeletable {
+        /// The name of the table of the model.
+        table_name: String,
+    },
     /// Error when a unique constraint is violated in the database.
     #[error("{ERROR_PREFIX} unique constraint violation")]
     UniqueViolation,
@@ -186,6 +206,26 @@ pub trait Model: Sized + Send + 'static {
     /// The columns of the model.
     const COLUMNS: &'static [Column];
 
+    /// The name of the column storing the time at which a row has been
+    /// soft-deleted, or [`None`] if the model is not soft-deletable.
+    ///
+    /// This is set to `Some` for the models annotated with
+    /// `#[model(soft_delete)]`. For such models, [`Query::delete`] marks the
+    /// rows as deleted instead of removing them, and queries exclude the
+    /// deleted rows unless [`Query::with_deleted`] or [`Query::only_deleted`]
+    /// is used.
+    const SOFT_DELETE_COLUMN: Option<Identifier> = None;
+
+    /// The name of the version column used for optimistic locking, or
+    /// [`None`] if the model does not use optimistic locking.
+    ///
+    /// This is set to `Some` for the models that have a field annotated with
+    /// `#[model(version)]`. For such models, [`Model::update`] and
+    /// [`Model::save`] only update the row if its version in the database
+    /// matches the version of the instance, and return
+    /// [`DatabaseError::StaleObject`] otherwise.
+    const VERSION_COLUMN: Option<Identifier> = None;
+
     /// Creates a model instance from a database row.
     ///
     /// # Errors
@@ -215,6 +255,13 @@ pub trait Model: Sized + Send + 'static {
     /// Gets the values of the model for the given columns.
     fn get_values(&self, columns: &[usize]) -> Vec<&dyn ToDbFieldValue>;
 
+    /// Used by the ORM to increment the version field of the model after it
+    /// has been updated in the database.
+    ///
+    /// This does nothing if the model does not have a version field (see
+    /// [`Self::VERSION_COLUMN`]).
+    fn increment_version(&mut self) {}
+
     /// Returns a query for all objects of this model.
     #[must_use]
     fn objects() -> Query<Self> {
@@ -274,6 +321,10 @@ pub trait Model: Sized + Send + 'static {
     ///
     /// This method can return an error if the model with the given primary key
     /// could not be found in the database.
+    ///
+    /// If the model has a version field (see [`Self::VERSION_COLUMN`]), this
+    /// method returns [`DatabaseError::StaleObject`] if the row has been
+    /// modified since the instance was fetched.
     async fn update<DB: DatabaseBackend>(&mut self, db: &DB) -> Result<()> {
         db.update(self).await?;
         Ok(())
@@ -379,7 +430,7 @@ impl Identifier {
 
     /// Returns the inner string of the identifier.
     #[must_use]
-    pub fn as_str(&self) -> &str {
+    pub const fn as_str(&self) -> &'static str {
         self.0
     }
 }
@@ -831,11 +882,60 @@ impl Database {
     /// }
     /// ```
     pub async fn new<T: Into<String>>(url: T) -> Result<Self> {
-        let url = url.into();
+        Self::new_with_logger(url.into(), QueryLogger::default()).await
+    }
+
+    /// Creates a new database connection using the given configuration.
+    ///
+    /// Apart from the database URL, this also applies the query logging
+    /// settings ([`DatabaseConfig::query_log_level`] and
+    /// [`DatabaseConfig::slow_query_threshold`]).
+    ///
+    /// # Errors
+    ///
+    /// This method can return an error if the connection to the database could
+    /// not be established.
+    ///
+    /// This method can return an error if the database URL is invalid.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the database URL is not set in the config or
+    /// is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use cot::config::DatabaseConfig;
+    /// use cot::db::Database;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> cot::Result<()> {
+    /// let config = DatabaseConfig::builder()
+    ///     .url("sqlite::memory:")
+    ///     .slow_query_threshold(Duration::from_millis(200))
+    ///     .build();
+    /// let db = Database::from_config(&config).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_config(config: &DatabaseConfig) -> Result<Self> {
+        let url = config
+            .url
+            .as_ref()
+            .expect("Database URL is required")
+            .as_str()
+            .to_owned();
 
+        Self::new_with_logger(url, QueryLogger::from_config(config)).await
+    }
+
+    async fn new_with_logger(url: String, query_logger: QueryLogger) -> Result<Self> {
         #[cfg(feature = "sqlite")]
         if url.starts_with("sqlite:") {
-            let inner = DatabaseSqlite::new(&url).await?;
+            let inner = DatabaseSqlite::new(&url, query_logger).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::Sqlite(inner)),
             });
@@ -843,7 +943,7 @@ impl Database {
 
         #[cfg(feature = "postgres")]
         if url.starts_with("postgresql:") {
-            let inner = DatabasePostgres::new(&url).await?;
+            let inner = DatabasePostgres::new(&url, query_logger).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::Postgres(inner)),
             });
@@ -851,7 +951,7 @@ impl Database {
 
         #[cfg(feature = "mysql")]
         if url.starts_with("mysql:") {
-            let inner = DatabaseMySql::new(&url).await?;
+            let inner = DatabaseMySql::new(&url, query_logger).await?;
             return Ok(Self {
                 inner: Arc::new(DatabaseImpl::MySql(inner)),
             });
@@ -916,6 +1016,11 @@ impl Database {
     /// This method can return an error if the row could not be inserted into
     /// the database, for instance because the migrations haven't been
     /// applied, or there was a problem with the database connection.
+    ///
+    /// If the model has a version field (see [`Model::VERSION_COLUMN`]) and
+    /// the row already exists, it is updated the same way as in
+    /// [`Database::update`], so [`DatabaseError::StaleObject`] is returned
+    /// if its version doesn't match the version of `data`.
     pub async fn insert_or_update<T: Model>(&self, data: &mut T) -> Result<()> {
         let span = span!(
             Level::TRACE,
@@ -923,9 +1028,48 @@ impl Database {
             table = %T::TABLE_NAME
         );
 
-        Self::insert_or_update_impl(self, data, true)
-            .instrument(span)
-            .await
+        if T::VERSION_COLUMN.is_some() {
+            Self::insert_or_update_versioned(self, data)
+                .instrument(span)
+                .await
+        } else {
+            Self::insert_or_update_impl(self, data, true)
+                .instrument(span)
+                .await
+        }
+    }
+
+    async fn insert_or_update_versioned<T: Model>(&self, data: &mut T) -> Result<()> {
+        let DbFieldValue::Value(primary_key) = data.primary_key().to_db_field_value() else {
+            return self.insert_or_update_impl(data, false).await;
+        };
+
+        // Inserting first and falling back to the versioned update on a unique
+        // violation leaves no window in which a concurrently inserted row could
+        // be missed or overwritten without its version being checked.
+        match self.insert_or_update_impl(data, false).await {
+            Err(DatabaseError::UniqueViolation) => match self.update_impl(data).await {
+                Err(DatabaseError::StaleObject { .. }) => {
+                    // The violation may come from a unique column other than
+                    // the primary key, in which case there is no row to update.
+                    let query = sea_query::Query::select()
+                        .from(T::TABLE_NAME)
+                        .column(T::PRIMARY_KEY_NAME)
+                        .and_where(
+                            sea_query::Expr::col(T::PRIMARY_KEY_NAME).eq(primary_key.clone()),
+                        )
+                        .limit(1)
+                        .to_owned();
+                    if self.fetch_option(&query).await?.is_some() {
+                        Err(DatabaseError::StaleObject { primary_key })
+                    } else {
+                        Err(DatabaseError::UniqueViolation)
+                    }
+                }
+                result => result,
+            },
+            result => result,
+        }
     }
 
     async fn insert_or_update_impl<T: Model>(&self, data: &mut T, update: bool) -> Result<()> {
@@ -1023,6 +1167,11 @@ impl Database {
     ///
     /// This method can return an error if the row with the given primary key
     /// could not be found in the database.
+    ///
+    /// If the model has a version field (see [`Model::VERSION_COLUMN`]), the
+    /// row is only updated if its version matches the version of `data`, and
+    /// the version is incremented. [`DatabaseError::StaleObject`] is returned
+    /// otherwise.
     pub async fn update<T: Model>(&self, data: &mut T) -> Result<()> {
         let span = span!(
             Level::TRACE,
@@ -1049,12 +1198,18 @@ impl Database {
             .map(ToDbFieldValue::to_db_field_value);
 
         let mut statement_values = Vec::new();
+        let mut current_version = None;
         std::iter::zip(column_identifiers, values).for_each(|(identifier, value)| match value {
             DbFieldValue::Auto => {
                 panic!("Auto values are not supported in update queries");
             }
             DbFieldValue::Value(value) => {
-                statement_values.push((identifier, SimpleExpr::Value(value)));
+                if Some(identifier) == T::VERSION_COLUMN {
+                    statement_values.push((identifier, sea_query::Expr::col(identifier).add(1)));
+                    current_version = Some(value);
+                } else {
+                    statement_values.push((identifier, SimpleExpr::Value(value)));
+                }
             }
         });
 
@@ -1062,16 +1217,25 @@ impl Database {
             .primary_key()
             .to_db_field_value()
             .expect_value("primary key cannot be auto when updating");
-        let update_statement = sea_query::Query::update()
+        let mut update_statement = sea_query::Query::update()
             .table(T::TABLE_NAME)
             .values(statement_values)
             .and_where(sea_query::Expr::col(T::PRIMARY_KEY_NAME).eq(primary_key.clone()))
             .to_owned();
+        if let (Some(version_column), Some(current_version)) = (T::VERSION_COLUMN, current_version)
+        {
+            update_statement.and_where(sea_query::Expr::col(version_column).eq(current_version));
+        }
 
         let result = self.execute_statement(&update_statement).await?;
         if result.rows_affected == RowsNum(0) {
-            return Err(DatabaseError::RecordNotFound { primary_key });
+            return if T::VERSION_COLUMN.is_some() {
+                Err(DatabaseError::StaleObject { primary_key })
+            } else {
+                Err(DatabaseError::RecordNotFound { primary_key })
+            };
         }
+        data.increment_version();
 
         trace!("Updated row");
 
@@ -1331,6 +1495,7 @@ impl Database {
         let mut select = sea_query::Query::select();
         select.columns(columns_to_get).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_order_to_statement(&mut select);
         query.add_limit_to_statement(&mut select);
         query.add_offset_to_statement(&mut select);
 
@@ -1357,6 +1522,7 @@ impl Database {
         let mut select = sea_query::Query::select();
         select.columns(columns_to_get).from(T::TABLE_NAME);
         query.add_filter_to_statement(&mut select);
+        query.add_order_to_statement(&mut select);
         select.limit(1);
 
         let row = self.fetch_option(&select).await?;
@@ -1392,6 +1558,9 @@ impl Database {
 
     /// Deletes all rows that match the given query.
     ///
+    /// If the model is soft-deletable (see [`Model::SOFT_DELETE_COLUMN`]),
+    /// the rows are not removed, but marked as deleted instead.
+    ///
     /// # Errors
     ///
     /// This method can return an error if the query is invalid.
@@ -1402,6 +1571,17 @@ impl Database {
     ///
     /// Can return an error if the database connection is lost.
     pub async fn delete<T: Model>(&self, query: &Query<T>) -> Result<StatementResult> {
+        if let Some(soft_delete_column) = T::SOFT_DELETE_COLUMN {
+            let mut update = sea_query::Query::update();
+            update.table(T::TABLE_NAME).value(
+                soft_delete_column,
+                chrono::Utc::now().fixed_offset().to_db_value(),
+            );
+            query.add_filter_to_statement(&mut update);
+
+            return self.execute_statement(&update).await;
+        }
+
         let mut delete = sea_query::Query::delete();
         delete.from_table(T::TABLE_NAME);
         query.add_filter_to_statement(&mut delete);
@@ -1540,7 +1720,7 @@ impl Database {
         Ok(result)
     }
 
-    async fn execute_statement<T>(&self, statement: &T) -> Result<StatementResult>
+    pub(crate) async fn execute_statement<T>(&self, statement: &T) -> Result<StatementResult>
     where
         T: SqlxBinder + Send + Sync,
     {
@@ -1556,7 +1736,7 @@ impl Database {
         Ok(result)
     }
 
-    async fn execute_schema<T: SchemaStatementBuilder>(
+    pub(crate) async fn execute_schema<T: SchemaStatementBuilder>(
         &self,
         statement: T,
     ) -> Result<StatementResult> {
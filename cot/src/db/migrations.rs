@@ -215,6 +215,29 @@ impl MigrationEngine {
         Ok(())
     }
 
+    /// Returns whether all the migrations have been applied to the database.
+    ///
+    /// Like [`MigrationEngine::run`], this creates the `cot__migrations` table
+    /// if it does not exist, but it doesn't apply any of the migrations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is an error while interacting with the
+    /// database.
+    pub(crate) async fn is_up_to_date(&self, database: &Database) -> Result<bool> {
+        CREATE_APPLIED_MIGRATIONS_MIGRATION
+            .forwards(database)
+            .await?;
+
+        for migration in &self.migrations {
+            if !Self::is_migration_applied(database, migration).await? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
     async fn is_migration_applied(
         database: &Database,
         migration: &MigrationWrapper,
@@ -2077,6 +2100,20 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cot_macros::dbtest]
+    async fn test_migration_engine_is_up_to_date(test_db: &mut TestDatabase) {
+        #[expect(trivial_casts)] // cast to the correct trait object type
+        let engine = MigrationEngine::new([
+            &TestMigration as &SyncDynMigration,
+            &DummyMigration as &SyncDynMigration,
+        ])
+        .unwrap();
+
+        assert!(!engine.is_up_to_date(&test_db.database()).await.unwrap());
+        engine.run(&test_db.database()).await.unwrap();
+        assert!(engine.is_up_to_date(&test_db.database()).await.unwrap());
+    }
+
     #[test]
     fn test_operation_create_model() {
         const OPERATION_CREATE_MODEL_FIELDS: &[Field; 2] = &[
@@ -34,9 +34,54 @@ pub struct Query<T> {
     filter: Option<Expr>,
     limit: Option<u64>,
     offset: Option<u64>,
+    order_by: Vec<(Expr, Order)>,
+    deleted_rows: DeletedRows,
     phantom_data: PhantomData<fn() -> T>,
 }
 
+/// The direction in which the rows of a [`Query`] are sorted.
+///
+/// # Example
+///
+/// ```
+/// use cot::db::model;
+/// use cot::db::query::{Expr, Order, Query};
+///
+/// #[model]
+/// struct User {
+///     #[model(primary_key)]
+///     id: i32,
+///     name: String,
+/// }
+///
+/// let query = Query::<User>::new().order_by(Expr::field("name"), Order::Desc);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Order {
+    /// Ascending order (smallest values first).
+    Asc,
+    /// Descending order (largest values first).
+    Desc,
+}
+
+impl Order {
+    fn as_sea_query_order(self) -> sea_query::Order {
+        match self {
+            Self::Asc => sea_query::Order::Asc,
+            Self::Desc => sea_query::Order::Desc,
+        }
+    }
+}
+
+/// Which rows of a soft-deletable model should be matched by a [`Query`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+enum DeletedRows {
+    #[default]
+    Exclude,
+    Include,
+    Only,
+}
+
 // manual implementation to avoid `T: Debug` in the trait bounds
 impl<T> Debug for Query<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,6 +89,8 @@ impl<T> Debug for Query<T> {
             .field("filter", &self.filter)
             .field("limit", &self.limit)
             .field("offset", &self.offset)
+            .field("order_by", &self.order_by)
+            .field("deleted_rows", &self.deleted_rows)
             .field("phantom_data", &self.phantom_data)
             .finish()
     }
@@ -56,6 +103,8 @@ impl<T> Clone for Query<T> {
             filter: self.filter.clone(),
             limit: self.limit,
             offset: self.offset,
+            order_by: self.order_by.clone(),
+            deleted_rows: self.deleted_rows,
             phantom_data: PhantomData,
         }
     }
@@ -64,7 +113,7 @@ impl<T> Clone for Query<T> {
 // manual implementation to avoid `T: PartialEq` in the trait bounds
 impl<T> PartialEq for Query<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.filter == other.filter
+        self.filter == other.filter && self.deleted_rows == other.deleted_rows
     }
 }
 
@@ -99,6 +148,8 @@ impl<T: Model> Query<T> {
             filter: None,
             limit: None,
             offset: None,
+            order_by: Vec::new(),
+            deleted_rows: DeletedRows::Exclude,
             phantom_data: PhantomData,
         }
     }
@@ -172,6 +223,85 @@ impl<T: Model> Query<T> {
         self
     }
 
+    /// Add an expression to sort the query results by.
+    ///
+    /// Calling this multiple times sorts the results by each of the
+    /// expressions in turn, so that the later ones are only used to order the
+    /// rows for which all the previous ones are equal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::{Expr, Order, Query};
+    ///
+    /// #[model]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    ///     age: i32,
+    /// }
+    ///
+    /// let query = Query::<User>::new()
+    ///     .order_by(Expr::field("age"), Order::Desc)
+    ///     .order_by(Expr::field("name"), Order::Asc);
+    /// ```
+    pub fn order_by(&mut self, expr: Expr, order: Order) -> &mut Self {
+        self.order_by.push((expr, order));
+        self
+    }
+
+    /// Include the soft-deleted rows in the query results.
+    ///
+    /// By default, queries on models annotated with `#[model(soft_delete)]`
+    /// exclude the rows that have been deleted. This has no effect on models
+    /// that are not soft-deletable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model(soft_delete)]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// let query = Query::<User>::new().with_deleted();
+    /// ```
+    pub fn with_deleted(&mut self) -> &mut Self {
+        self.deleted_rows = DeletedRows::Include;
+        self
+    }
+
+    /// Only match the soft-deleted rows.
+    ///
+    /// This has no effect on models that are not soft-deletable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::Query;
+    ///
+    /// #[model(soft_delete)]
+    /// struct User {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// }
+    ///
+    /// let query = Query::<User>::new().only_deleted();
+    /// ```
+    pub fn only_deleted(&mut self) -> &mut Self {
+        self.deleted_rows = DeletedRows::Only;
+        self
+    }
+
     /// Execute the query and return all results.
     ///
     /// # Errors
@@ -222,6 +352,10 @@ impl<T: Model> Query<T> {
 
     /// Delete all rows that match the query.
     ///
+    /// For soft-deletable models, the rows are marked as deleted instead of
+    /// being removed from the database. They can be brought back using
+    /// [`Self::restore`].
+    ///
     /// # Errors
     ///
     /// Returns an error if the query fails.
@@ -229,6 +363,37 @@ impl<T: Model> Query<T> {
         db.delete(self).await
     }
 
+    /// Restore the soft-deleted rows that match the query.
+    ///
+    /// The rows are matched regardless of [`Self::with_deleted`] and
+    /// [`Self::only_deleted`]; only the rows that are currently deleted are
+    /// affected.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatabaseError::NotSoftDeletable`](db::DatabaseError::NotSoftDeletable)
+    /// if the model is not soft-deletable.
+    ///
+    /// Returns an error if the query fails.
+    pub async fn restore(&self, db: &Database) -> db::Result<StatementResult> {
+        let soft_delete_column =
+            T::SOFT_DELETE_COLUMN.ok_or_else(|| db::DatabaseError::NotSoftDeletable {
+                table_name: T::TABLE_NAME.as_str().to_owned(),
+            })?;
+
+        let mut update = sea_query::Query::update();
+        update.table(T::TABLE_NAME).value(
+            soft_delete_column,
+            sea_query::Value::ChronoDateTimeWithTimeZone(None),
+        );
+        if let Some(filter) = &self.filter {
+            update.and_where(filter.as_sea_query_expr());
+        }
+        update.and_where(sea_query::Expr::col(soft_delete_column).is_not_null());
+
+        db.execute_statement(&update).await
+    }
+
     pub(super) fn add_filter_to_statement<S: sea_query::ConditionalStatement>(
         &self,
         statement: &mut S,
@@ -236,6 +401,18 @@ impl<T: Model> Query<T> {
         if let Some(filter) = &self.filter {
             statement.and_where(filter.as_sea_query_expr());
         }
+        if let Some(soft_delete_column) = T::SOFT_DELETE_COLUMN {
+            let column = sea_query::Expr::col(soft_delete_column);
+            match self.deleted_rows {
+                DeletedRows::Exclude => {
+                    statement.and_where(column.is_null());
+                }
+                DeletedRows::Include => {}
+                DeletedRows::Only => {
+                    statement.and_where(column.is_not_null());
+                }
+            }
+        }
     }
 
     pub(super) fn add_limit_to_statement(&self, statement: &mut sea_query::SelectStatement) {
@@ -249,6 +426,12 @@ impl<T: Model> Query<T> {
             statement.offset(offset);
         }
     }
+
+    pub(super) fn add_order_to_statement(&self, statement: &mut sea_query::SelectStatement) {
+        for (expr, order) in &self.order_by {
+            statement.order_by_expr(expr.as_sea_query_expr(), order.as_sea_query_order());
+        }
+    }
 }
 
 /// An expression that can be used to filter, update, or delete rows.
@@ -595,6 +778,47 @@ pub enum Expr {
     /// );
     /// ```
     Div(Box<Expr>, Box<Expr>),
+    /// A case-insensitive check whether a text expression contains the given
+    /// substring.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// };
+    ///
+    /// let expr = Expr::icontains(Expr::field("name"), "john");
+    ///
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    IContains(Box<Expr>, String),
+    /// An `IN` expression, checking whether the value of an expression is one
+    /// of the given values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)]);
+    ///
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    In(Box<Expr>, Vec<Expr>),
 }
 
 impl Expr {
@@ -984,6 +1208,57 @@ impl Expr {
         Self::Div(Box::new(lhs), Box::new(rhs))
     }
 
+    /// Create a new expression checking whether `expr` contains `value`,
+    /// ignoring the case of both.
+    ///
+    /// The `%` and `_` characters in `value` are matched literally.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    ///     name: String,
+    /// };
+    ///
+    /// let expr = Expr::icontains(Expr::field("name"), "john");
+    ///
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    #[must_use]
+    pub fn icontains<V: Into<String>>(expr: Self, value: V) -> Self {
+        Self::IContains(Box::new(expr), value.into())
+    }
+
+    /// Create a new expression checking whether the value of `expr` is one of
+    /// `values`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cot::db::model;
+    /// use cot::db::query::{Expr, Query};
+    ///
+    /// #[model]
+    /// struct MyModel {
+    ///     #[model(primary_key)]
+    ///     id: i32,
+    /// };
+    ///
+    /// let expr = Expr::is_in(Expr::field("id"), [1, 2, 3].map(Expr::value));
+    ///
+    /// let query = <Query<MyModel>>::new().filter(expr);
+    /// ```
+    #[must_use]
+    pub fn is_in<I: IntoIterator<Item = Self>>(expr: Self, values: I) -> Self {
+        Self::In(Box::new(expr), values.into_iter().collect())
+    }
+
     /// Returns the expression as a [`sea_query::SimpleExpr`].
     ///
     /// # Example
@@ -1020,8 +1295,32 @@ impl Expr {
             Self::Sub(lhs, rhs) => lhs.as_sea_query_expr().sub(rhs.as_sea_query_expr()),
             Self::Mul(lhs, rhs) => lhs.as_sea_query_expr().mul(rhs.as_sea_query_expr()),
             Self::Div(lhs, rhs) => lhs.as_sea_query_expr().div(rhs.as_sea_query_expr()),
+            Self::IContains(expr, value) => {
+                sea_query::SimpleExpr::from(sea_query::Func::lower(expr.as_sea_query_expr())).like(
+                    sea_query::LikeExpr::new(format!("%{}%", escape_like(value)))
+                        .escape(LIKE_ESCAPE_CHAR),
+                )
+            }
+            Self::In(expr, values) => expr
+                .as_sea_query_expr()
+                .is_in(values.iter().map(Self::as_sea_query_expr)),
+        }
+    }
+}
+
+const LIKE_ESCAPE_CHAR: char = '!';
+
+/// Escapes the wildcard characters in a `LIKE` pattern and converts it to
+/// lowercase.
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.to_lowercase().chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE_CHAR) {
+            escaped.push(LIKE_ESCAPE_CHAR);
         }
+        escaped.push(c);
     }
+    escaped
 }
 
 /// A reference to a field in a database table.
@@ -1052,6 +1351,12 @@ impl<T> FieldRef<T> {
     pub fn as_expr(&self) -> Expr {
         Expr::Field(self.identifier)
     }
+
+    /// Returns the identifier of the referenced field.
+    #[must_use]
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
 }
 
 /// A trait for types that can be compared in database expressions.
@@ -1478,6 +1783,25 @@ mod tests {
         assert_eq!(query.offset.unwrap(), 10);
     }
 
+    #[test]
+    fn query_order_by() {
+        let mut query: Query<MockModel> = Query::new();
+        query
+            .order_by(Expr::field("name"), Order::Desc)
+            .order_by(Expr::field("id"), Order::Asc);
+
+        let mut select = sea_query::Query::select();
+        select
+            .column(sea_query::Asterisk)
+            .from(MockModel::TABLE_NAME);
+        query.add_order_to_statement(&mut select);
+
+        assert_eq!(
+            select.to_string(sea_query::SqliteQueryBuilder),
+            r#"SELECT * FROM "cot__mock_model" ORDER BY "name" DESC, "id" ASC"#
+        );
+    }
+
     #[cot::test]
     async fn query_all() {
         let mut db = MockDatabaseBackend::new();
@@ -1571,4 +1895,51 @@ mod tests {
     test_expr_constructor!(expr_sub, Sub, sub);
     test_expr_constructor!(expr_mul, Mul, mul);
     test_expr_constructor!(expr_div, Div, div);
+
+    #[test]
+    fn expr_icontains() {
+        let expr = Expr::icontains(Expr::field("name"), "John");
+        if let Expr::IContains(lhs, value) = &expr {
+            assert!(matches!(**lhs, Expr::Field(_)));
+            assert_eq!(value, "John");
+        } else {
+            panic!("Expected Expr::IContains");
+        }
+
+        let mut select = sea_query::Query::select();
+        select
+            .column(sea_query::Asterisk)
+            .from(MockModel::TABLE_NAME)
+            .and_where(expr.as_sea_query_expr());
+        assert_eq!(
+            select.to_string(sea_query::SqliteQueryBuilder),
+            r#"SELECT * FROM "cot__mock_model" WHERE LOWER("name") LIKE '%john%' ESCAPE '!'"#
+        );
+    }
+
+    #[test]
+    fn expr_is_in() {
+        let expr = Expr::is_in(Expr::field("id"), [Expr::value(1), Expr::value(2)]);
+        if let Expr::In(lhs, values) = &expr {
+            assert!(matches!(**lhs, Expr::Field(_)));
+            assert_eq!(values.len(), 2);
+        } else {
+            panic!("Expected Expr::In");
+        }
+
+        let mut select = sea_query::Query::select();
+        select
+            .column(sea_query::Asterisk)
+            .from(MockModel::TABLE_NAME)
+            .and_where(expr.as_sea_query_expr());
+        assert_eq!(
+            select.to_string(sea_query::SqliteQueryBuilder),
+            r#"SELECT * FROM "cot__mock_model" WHERE "id" IN (1, 2)"#
+        );
+    }
+
+    #[test]
+    fn escape_like_wildcards() {
+        assert_eq!(escape_like("50%_Off!"), "50!%!_off!!");
+    }
 }
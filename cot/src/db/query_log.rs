@@ -0,0 +1,261 @@
+//! Logging and collection of the queries executed by the database backends.
+//!
+//! Every statement executed through one of the `SeaQuery`-based backends is
+//! wrapped in a [`tracing`] span at the level configured in
+//! [`DatabaseConfig::query_log_level`](crate::config::DatabaseConfig::query_log_level).
+//! Additionally, when a [`QueryRecorder`] is active for the current task (which
+//! is the case for requests handled in debug mode), the queries are collected
+//! so that they can be displayed on the error page.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{Instrument, warn};
+
+use crate::config::{DatabaseConfig, QueryLogLevel};
+
+/// Dispatches a `tracing` macro call to the level given as a [`QueryLogLevel`].
+///
+/// This is needed because `tracing` macros only accept levels known at compile
+/// time.
+macro_rules! with_level {
+    ($level:expr, $macro:ident!($($args:tt)*)) => {
+        match $level {
+            QueryLogLevel::Trace => tracing::$macro!(tracing::Level::TRACE, $($args)*),
+            QueryLogLevel::Debug => tracing::$macro!(tracing::Level::DEBUG, $($args)*),
+            QueryLogLevel::Info => tracing::$macro!(tracing::Level::INFO, $($args)*),
+            QueryLogLevel::Warn => tracing::$macro!(tracing::Level::WARN, $($args)*),
+            QueryLogLevel::Error => tracing::$macro!(tracing::Level::ERROR, $($args)*),
+        }
+    };
+}
+
+/// Logs the queries executed by a database backend.
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct QueryLogger {
+    level: QueryLogLevel,
+    slow_query_threshold: Option<Duration>,
+}
+
+impl QueryLogger {
+    #[must_use]
+    pub(crate) fn new(level: QueryLogLevel, slow_query_threshold: Option<Duration>) -> Self {
+        Self {
+            level,
+            slow_query_threshold,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn from_config(config: &DatabaseConfig) -> Self {
+        Self::new(config.query_log_level, config.slow_query_threshold)
+    }
+
+    /// Runs the given query future, logging the SQL, the number of bind
+    /// parameters, the duration, and the number of rows affected.
+    ///
+    /// The number of rows is computed from the successful result using the
+    /// `rows` function.
+    pub(crate) async fn log<T, F>(
+        &self,
+        sql: &str,
+        param_count: usize,
+        query: F,
+        rows: impl FnOnce(&T) -> u64,
+    ) -> crate::db::Result<T>
+    where
+        F: Future<Output = crate::db::Result<T>>,
+    {
+        let span = with_level!(
+            self.level,
+            span!(
+                "query",
+                sql = %sql,
+                params = param_count,
+                duration = tracing::field::Empty,
+                rows_affected = tracing::field::Empty,
+            )
+        );
+
+        let start = Instant::now();
+        let result = query.instrument(span.clone()).await;
+        let duration = start.elapsed();
+        let rows = result.as_ref().ok().map(rows);
+
+        span.record("duration", tracing::field::debug(duration));
+        if let Some(rows) = rows {
+            span.record("rows_affected", rows);
+        }
+
+        {
+            let _enter = span.enter();
+            match &result {
+                Ok(_) => with_level!(self.level, event!("Query executed")),
+                Err(error) => with_level!(self.level, event!(%error, "Query failed")),
+            }
+            if let Some(threshold) = self.slow_query_threshold
+                && duration >= threshold
+            {
+                warn!(?duration, ?threshold, sql = %sql, "Slow query");
+            }
+        }
+
+        QueryRecorder::record(ExecutedQuery {
+            sql: sql.to_owned(),
+            param_count,
+            duration,
+            rows_affected: rows,
+        });
+
+        result
+    }
+}
+
+/// A query that has been executed while a [`QueryRecorder`] was active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExecutedQuery {
+    pub(crate) sql: String,
+    pub(crate) param_count: usize,
+    pub(crate) duration: Duration,
+    /// The number of rows affected or returned; `None` if the query failed.
+    pub(crate) rows_affected: Option<u64>,
+}
+
+tokio::task_local! {
+    static QUERY_RECORDER: QueryRecorder;
+}
+
+/// Collects the queries executed within a future.
+///
+/// This is used to list the queries executed during a request on the error
+/// page in debug mode.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct QueryRecorder {
+    enabled: bool,
+    queries: Arc<Mutex<Vec<ExecutedQuery>>>,
+}
+
+impl QueryRecorder {
+    #[must_use]
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            queries: Arc::default(),
+        }
+    }
+
+    /// Runs the future, recording all the queries it executes (if the
+    /// recorder is enabled).
+    pub(crate) async fn scope<F: Future>(&self, future: F) -> F::Output {
+        if self.enabled {
+            QUERY_RECORDER.scope(self.clone(), future).await
+        } else {
+            future.await
+        }
+    }
+
+    /// Returns the queries recorded so far.
+    #[must_use]
+    pub(crate) fn queries(&self) -> Vec<ExecutedQuery> {
+        self.queries
+            .lock()
+            .expect("query recorder mutex poisoned")
+            .clone()
+    }
+
+    fn record(query: ExecutedQuery) {
+        let _ = QUERY_RECORDER.try_with(|recorder| {
+            recorder
+                .queries
+                .lock()
+                .expect("query recorder mutex poisoned")
+                .push(query);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    #[cot::test]
+    #[traced_test]
+    async fn log_query() {
+        let logger = QueryLogger::new(QueryLogLevel::Info, None);
+
+        let result = logger
+            .log("SELECT 1", 2, async { Ok(vec![1, 2, 3]) }, |rows| {
+                rows.len() as u64
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![1, 2, 3]);
+        assert!(logs_contain("Query executed"));
+        assert!(logs_contain("SELECT 1"));
+        assert!(logs_contain("params=2"));
+        assert!(logs_contain("rows_affected=3"));
+        assert!(!logs_contain("Slow query"));
+    }
+
+    #[cot::test]
+    #[traced_test]
+    async fn log_slow_query() {
+        let logger = QueryLogger::new(QueryLogLevel::Debug, Some(Duration::ZERO));
+
+        logger
+            .log("SELECT 1", 0, async { Ok(()) }, |()| 0)
+            .await
+            .unwrap();
+
+        assert!(logs_contain("WARN"));
+        assert!(logs_contain("Slow query"));
+    }
+
+    #[cot::test]
+    async fn recorder_collects_queries() {
+        let logger = QueryLogger::default();
+        let recorder = QueryRecorder::new(true);
+
+        recorder
+            .scope(async {
+                logger
+                    .log("SELECT 1", 1, async { Ok(()) }, |()| 1)
+                    .await
+                    .unwrap();
+                logger
+                    .log(
+                        "SELECT 2",
+                        0,
+                        async { Err::<(), _>(crate::db::DatabaseError::ForeignKeyNotFound) },
+                        |()| 1,
+                    )
+                    .await
+                    .unwrap_err();
+            })
+            .await;
+
+        let queries = recorder.queries();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].sql, "SELECT 1");
+        assert_eq!(queries[0].param_count, 1);
+        assert_eq!(queries[0].rows_affected, Some(1));
+        assert_eq!(queries[1].sql, "SELECT 2");
+        assert_eq!(queries[1].rows_affected, None);
+    }
+
+    #[cot::test]
+    async fn disabled_recorder_collects_nothing() {
+        let logger = QueryLogger::default();
+        let recorder = QueryRecorder::new(false);
+
+        recorder
+            .scope(logger.log("SELECT 1", 0, async { Ok(()) }, |()| 0))
+            .await
+            .unwrap();
+
+        assert!(recorder.queries().is_empty());
+    }
+}
@@ -11,13 +11,20 @@ macro_rules! impl_sea_query_db_backend {
         #[derive(Debug)]
         pub(super) struct $db_name {
             db_connection: $pool_ty,
+            query_logger: crate::db::query_log::QueryLogger,
         }
 
         impl $db_name {
-            pub(super) async fn new(url: &str) -> crate::db::Result<Self> {
+            pub(super) async fn new(
+                url: &str,
+                query_logger: crate::db::query_log::QueryLogger,
+            ) -> crate::db::Result<Self> {
                 let db_connection = <$pool_ty>::connect(url).await?;
 
-                let db = Self { db_connection };
+                let db = Self {
+                    db_connection,
+                    query_logger,
+                };
                 db.init().await?;
                 Ok(db)
             }
@@ -32,11 +39,22 @@ macro_rules! impl_sea_query_db_backend {
                 statement: &T,
             ) -> crate::db::Result<Option<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
-
-                let row = Self::sqlx_query_with(&sql, values)
-                    .fetch_optional(&self.db_connection)
-                    .await
-                    .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))?;
+                let param_count = values.0.0.len();
+
+                let row = self
+                    .query_logger
+                    .log(
+                        &sql,
+                        param_count,
+                        async {
+                            Self::sqlx_query_with(&sql, values)
+                                .fetch_optional(&self.db_connection)
+                                .await
+                                .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))
+                        },
+                        |row| u64::from(row.is_some()),
+                    )
+                    .await?;
                 Ok(row.map($row_name::new))
             }
 
@@ -45,9 +63,20 @@ macro_rules! impl_sea_query_db_backend {
                 statement: &T,
             ) -> crate::db::Result<Vec<$row_name>> {
                 let (sql, values) = Self::build_sql(statement);
-
-                let result = Self::sqlx_query_with(&sql, values)
-                    .fetch_all(&self.db_connection)
+                let param_count = values.0.0.len();
+
+                let result = self
+                    .query_logger
+                    .log(
+                        &sql,
+                        param_count,
+                        async {
+                            Ok(Self::sqlx_query_with(&sql, values)
+                                .fetch_all(&self.db_connection)
+                                .await?)
+                        },
+                        |rows| rows.len() as u64,
+                    )
                     .await?
                     .into_iter()
                     .map($row_name::new)
@@ -61,8 +90,10 @@ macro_rules! impl_sea_query_db_backend {
             ) -> crate::db::Result<crate::db::StatementResult> {
                 let (sql, mut values) = Self::build_sql(statement);
                 Self::prepare_values(&mut values);
+                let param_count = values.0.0.len();
 
-                self.execute_sqlx(Self::sqlx_query_with(&sql, values)).await
+                self.execute_sqlx(&sql, param_count, Self::sqlx_query_with(&sql, values))
+                    .await
             }
 
             pub(super) async fn execute_schema<T: sea_query::SchemaStatementBuilder>(
@@ -72,7 +103,7 @@ macro_rules! impl_sea_query_db_backend {
                 let sql = statement.build($query_builder);
                 tracing::debug!("Schema modification: {}", sql);
 
-                self.execute_sqlx(sqlx::query(&sql)).await
+                self.execute_sqlx(&sql, 0, sqlx::query(&sql)).await
             }
 
             pub(super) async fn raw_with(
@@ -80,27 +111,38 @@ macro_rules! impl_sea_query_db_backend {
                 sql: &str,
                 values: sea_query_binder::SqlxValues,
             ) -> crate::db::Result<crate::db::StatementResult> {
-                self.execute_sqlx(Self::sqlx_query_with(sql, values)).await
+                let param_count = values.0.0.len();
+
+                self.execute_sqlx(sql, param_count, Self::sqlx_query_with(sql, values))
+                    .await
             }
 
             async fn execute_sqlx<'a, A>(
                 &self,
+                sql: &str,
+                param_count: usize,
                 sqlx_statement: sqlx::query::Query<'a, $sqlx_db_ty, A>,
             ) -> crate::db::Result<crate::db::StatementResult>
             where
                 A: 'a + sqlx::IntoArguments<'a, $sqlx_db_ty>,
             {
-                let result = sqlx_statement
-                    .execute(&self.db_connection)
+                self.query_logger
+                    .log(
+                        sql,
+                        param_count,
+                        async {
+                            let result = sqlx_statement
+                                .execute(&self.db_connection)
+                                .await
+                                .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))?;
+                            Ok(crate::db::StatementResult {
+                                rows_affected: crate::db::RowsNum(result.rows_affected()),
+                                last_inserted_row_id: Self::last_inserted_row_id_for(&result),
+                            })
+                        },
+                        |result| result.rows_affected.0,
+                    )
                     .await
-                    .map_err(|err| crate::db::sea_query_db::map_sqlx_error(err))?;
-                let result = crate::db::StatementResult {
-                    rows_affected: crate::db::RowsNum(result.rows_affected()),
-                    last_inserted_row_id: Self::last_inserted_row_id_for(&result),
-                };
-
-                tracing::debug!("Rows affected: {}", result.rows_affected.0);
-                Ok(result)
             }
 
             fn build_sql<T>(statement: &T) -> (String, sea_query_binder::SqlxValues)
@@ -117,7 +159,7 @@ macro_rules! impl_sea_query_db_backend {
                 mut values: sea_query_binder::SqlxValues,
             ) -> sqlx::query::Query<'_, $sqlx_db_ty, sea_query_binder::SqlxValues> {
                 Self::prepare_values(&mut values);
-                tracing::debug!("Query: `{}` (values: {:?})", sql, values);
+                tracing::trace!("Query: `{}` (values: {:?})", sql, values);
 
                 sqlx::query_with(sql, values)
             }
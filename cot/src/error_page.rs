@@ -6,6 +6,8 @@ use cot_core::error::backtrace::{__cot_create_backtrace, Backtrace};
 use tracing::{Level, error, warn};
 
 use crate::config::ProjectConfig;
+#[cfg(feature = "db")]
+use crate::db::query_log::ExecutedQuery;
 use crate::error::NotFound;
 use crate::router::Router;
 use crate::{Error, Result, StatusCode, Template};
@@ -15,6 +17,8 @@ pub(super) struct Diagnostics {
     project_config: ProjectConfig,
     router: Arc<Router>,
     request_head: Option<crate::request::RequestHead>,
+    #[cfg(feature = "db")]
+    executed_queries: Vec<ExecutedQuery>,
 }
 
 impl Diagnostics {
@@ -28,8 +32,15 @@ impl Diagnostics {
             project_config,
             router,
             request_head,
+            #[cfg(feature = "db")]
+            executed_queries: Vec::new(),
         }
     }
+
+    #[cfg(feature = "db")]
+    pub(super) fn set_executed_queries(&mut self, executed_queries: Vec<ExecutedQuery>) {
+        self.executed_queries = executed_queries;
+    }
 }
 
 #[derive(Debug, Template)]
@@ -43,6 +54,7 @@ struct ErrorPageTemplate {
     error_data: Vec<ErrorData>,
     route_data: Vec<RouteData>,
     request_data: Option<RequestData>,
+    query_data: Vec<QueryData>,
     project_config: String,
 }
 
@@ -60,6 +72,7 @@ struct ErrorPageTemplateBuilder {
     error_data: Vec<ErrorData>,
     route_data: Vec<RouteData>,
     request_data: Option<RequestData>,
+    query_data: Vec<QueryData>,
     project_config: String,
 }
 
@@ -128,6 +141,14 @@ impl ErrorPageTemplateBuilder {
             .request_head
             .as_ref()
             .map(Self::build_request_data);
+        #[cfg(feature = "db")]
+        {
+            self.query_data = diagnostics
+                .executed_queries
+                .iter()
+                .map(Self::build_query_data)
+                .collect();
+        }
         self
     }
 
@@ -196,6 +217,17 @@ impl ErrorPageTemplateBuilder {
         }
     }
 
+    #[cfg(feature = "db")]
+    #[must_use]
+    fn build_query_data(query: &ExecutedQuery) -> QueryData {
+        QueryData {
+            sql: query.sql.clone(),
+            param_count: query.param_count,
+            duration: format!("{:?}", query.duration),
+            rows_affected: query.rows_affected.map(|rows| rows.to_string()),
+        }
+    }
+
     #[must_use]
     fn get_panic_string(panic_payload: &Box<dyn Any + Send>) -> Option<String> {
         if let Some(&panic_string) = panic_payload.downcast_ref::<&str>() {
@@ -215,6 +247,7 @@ impl ErrorPageTemplateBuilder {
             error_data: self.error_data.clone(),
             route_data: self.route_data.clone(),
             request_data: self.request_data.clone(),
+            query_data: self.query_data.clone(),
             project_config: self.project_config.clone(),
         }
         .render()?)
@@ -252,6 +285,14 @@ struct RequestData {
     headers: Vec<(String, String)>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryData {
+    sql: String,
+    param_count: usize,
+    duration: String,
+    rows_affected: Option<String>,
+}
+
 #[must_use]
 pub(super) fn handle_not_found(
     error: &Error,
@@ -611,6 +652,43 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "db")]
+    #[test]
+    fn error_page_lists_executed_queries() {
+        let mut diagnostics = create_diagnostics();
+        diagnostics.set_executed_queries(vec![
+            ExecutedQuery {
+                sql: "SELECT \"id\" FROM \"test_model\"".to_owned(),
+                param_count: 0,
+                duration: std::time::Duration::from_millis(3),
+                rows_affected: Some(7),
+            },
+            ExecutedQuery {
+                sql: "DELETE FROM \"test_model\"".to_owned(),
+                param_count: 1,
+                duration: std::time::Duration::from_millis(1),
+                rows_affected: None,
+            },
+        ]);
+
+        let html = build_error_response(&Error::internal("error occurred"), &diagnostics).unwrap();
+
+        assert!(html.contains("Database queries"));
+        assert!(html.contains("SELECT &#34;id&#34; FROM &#34;test_model&#34;"));
+        assert!(html.contains("3ms"));
+        assert!(html.contains("DELETE FROM"));
+        assert!(html.contains("<em>failed</em>"));
+    }
+
+    #[test]
+    fn error_page_without_queries() {
+        let diagnostics = create_diagnostics();
+
+        let html = build_error_response(&Error::internal("error occurred"), &diagnostics).unwrap();
+
+        assert!(!html.contains("Database queries"));
+    }
+
     #[test]
     fn test_build_cot_failure_page() {
         let response = build_cot_failure_page();
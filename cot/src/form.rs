@@ -23,6 +23,7 @@
 mod field_value;
 /// Built-in form fields that can be used in a form.
 pub mod fields;
+mod formset;
 
 use std::borrow::Cow;
 use std::fmt::Display;
@@ -60,6 +61,7 @@ use cot_core::headers::{MULTIPART_FORM_CONTENT_TYPE, URLENCODED_FORM_CONTENT_TYP
 pub use cot_macros::Form;
 use derive_more::with_trait::Debug;
 pub use field_value::{FormFieldValue, FormFieldValueError};
+pub use formset::{FormSet, FormSetChange, FormSetForm, FormSetResult, MAX_FORMSET_FORMS};
 use http_body_util::BodyExt;
 use thiserror::Error;
 
@@ -301,28 +303,87 @@ pub trait Form: Sized {
     /// from the request.
     async fn from_request(request: &mut Request) -> Result<FormResult<Self>, FormError>;
 
+    /// Converts the values of the form fields in the context into the form
+    /// struct.
+    ///
+    /// Returns the context with the validation errors added if any of the
+    /// values is invalid.
+    fn from_context(context: Self::Context) -> FormResult<Self>;
+
     /// Creates the context for the form from `self`.
     ///
     /// This is useful for pre-populating forms with objects created in the code
     /// or obtained externally, such as from a database.
     async fn to_context(&self) -> Self::Context;
 
+    /// Creates the context for the form from `self`, with the IDs of the form
+    /// fields prefixed with `prefix`.
+    ///
+    /// See [`FormContext::with_prefix`] for details.
+    async fn to_context_with_prefix(&self, prefix: &str) -> Self::Context;
+
     /// Builds the context for the form from a request.
     ///
     /// Note that this doesn't try to convert the values from the form fields
     /// into the final types, so this context object may not include all the
-    /// errors. The conversion is done in the [`Self::from_request`] method.
+    /// errors. The conversion is done in the [`Self::from_context`] method.
     ///
     /// # Errors
     ///
     /// This method should return an error if the form data could not be read
     /// from the request.
     async fn build_context(request: &mut Request) -> Result<Self::Context, FormError> {
+        Self::build_context_ignoring(request, &[]).await
+    }
+
+    /// Builds the context for the form from a request, ignoring the values
+    /// submitted for the fields with given IDs.
+    ///
+    /// The ignored fields are left empty, as if they were not submitted at
+    /// all. This is useful for the fields that must not be changed by the
+    /// user, such as the read-only fields in the admin panel.
+    ///
+    /// # Errors
+    ///
+    /// This method should return an error if the form data could not be read
+    /// from the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormField};
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// #[derive(Form)]
+    /// struct Profile {
+    ///     name: String,
+    ///     is_admin: bool,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), cot::form::FormError> {
+    /// let mut request = TestRequestBuilder::post("/")
+    ///     .form_data(&[("name", "Alice"), ("is_admin", "true")])
+    ///     .build();
+    ///
+    /// let context = Profile::build_context_ignoring(&mut request, &["is_admin"]).await?;
+    /// assert_eq!(context.name.value(), Some("Alice"));
+    /// assert_eq!(context.is_admin.value(), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn build_context_ignoring(
+        request: &mut Request,
+        ignored_fields: &[&str],
+    ) -> Result<Self::Context, FormError> {
         let mut context = Self::Context::new();
 
         let mut form_data = form_data(request).await?;
 
         while let Some((field_id, value)) = form_data.next_value().await? {
+            if ignored_fields.contains(&field_id.as_str()) {
+                continue;
+            }
             if let Err(err) = context.set_value(&field_id, value).await {
                 context.add_error(FormErrorTarget::Field(&field_id), err);
             }
@@ -472,7 +533,22 @@ impl<'a> FormData<'a> {
 #[async_trait]
 pub trait FormContext: Debug {
     /// Creates a new form context without any initial form data.
+    #[must_use]
     fn new() -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_prefix("")
+    }
+
+    /// Creates a new form context without any initial form data, with the
+    /// IDs of the form fields prefixed with `prefix`.
+    ///
+    /// This allows rendering several forms in the same HTML form without the
+    /// names of their fields clashing, such as in a [`FormSet`]. The context
+    /// accepts the field IDs both with and without the prefix in
+    /// [`Self::set_value`] and [`Self::errors_for`].
+    fn with_prefix(prefix: &str) -> Self
     where
         Self: Sized;
 
@@ -607,7 +607,7 @@ where
     T: Model,
     <T as Model>::PrimaryKey: AsFormField,
 {
-    type Type = <<T as Model>::PrimaryKey as AsFormField>::Type;
+    type Type = ForeignKeyField<T>;
 
     fn new_field(
         options: FormFieldOptions,
@@ -620,7 +620,7 @@ where
     where
         Self: Sized,
     {
-        let value = <T as Model>::PrimaryKey::clean_value(field);
+        let value = <T as Model>::PrimaryKey::clean_value(&field.primary_key_field);
         match value {
             Ok(value) => Ok(ForeignKey::PrimaryKey(value)),
             Err(error) => Err(error),
@@ -635,6 +635,101 @@ where
     }
 }
 
+/// A form field for a [`ForeignKey`].
+///
+/// The field is rendered as the form field of the primary key of the
+/// referenced model, followed by a hidden search input marked with the
+/// `fk-autocomplete` class. The search input holds the name of the
+/// referenced model's table in its `data-model` attribute and the ID of the
+/// primary key field in its `data-target` attribute, so that a script can turn
+/// it into an autocomplete widget (the [admin panel](crate::admin) does this
+/// for the models it manages). Without such a script, the primary key can
+/// still be entered directly.
+#[cfg(feature = "db")]
+pub struct ForeignKeyField<T>
+where
+    T: Model,
+    <T as Model>::PrimaryKey: AsFormField,
+{
+    primary_key_field: <<T as Model>::PrimaryKey as AsFormField>::Type,
+    phantom_data: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "db")]
+impl<T> Debug for ForeignKeyField<T>
+where
+    T: Model,
+    <T as Model>::PrimaryKey: AsFormField,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForeignKeyField")
+            .field("model", &T::TABLE_NAME)
+            .field("options", self.options())
+            .field("value", &self.value())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T> FormField for ForeignKeyField<T>
+where
+    T: Model,
+    <T as Model>::PrimaryKey: AsFormField,
+{
+    type CustomOptions =
+        <<<T as Model>::PrimaryKey as AsFormField>::Type as FormField>::CustomOptions;
+
+    fn with_options(options: FormFieldOptions, custom_options: Self::CustomOptions) -> Self {
+        Self {
+            primary_key_field: <T as Model>::PrimaryKey::new_field(options, custom_options),
+            phantom_data: std::marker::PhantomData,
+        }
+    }
+
+    fn options(&self) -> &FormFieldOptions {
+        self.primary_key_field.options()
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.primary_key_field.value()
+    }
+
+    fn set_value(
+        &mut self,
+        field: crate::form::FormFieldValue<'_>,
+    ) -> impl Future<Output = Result<(), crate::form::FormFieldValueError>> + Send {
+        self.primary_key_field.set_value(field)
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T> Display for ForeignKeyField<T>
+where
+    T: Model,
+    <T as Model>::PrimaryKey: AsFormField,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut tag = HtmlTag::input("search");
+        tag.attr("id", format!("{}__autocomplete", self.id()));
+        tag.attr("class", "fk-autocomplete");
+        tag.attr("data-model", T::TABLE_NAME.as_str());
+        tag.attr("data-target", self.id());
+        tag.attr("autocomplete", "off");
+        tag.attr("placeholder", "Search…");
+        tag.bool_attr("hidden");
+
+        write!(f, "{}{}", self.primary_key_field, tag.render())
+    }
+}
+
+#[cfg(feature = "db")]
+impl<T> HtmlSafe for ForeignKeyField<T>
+where
+    T: Model,
+    <T as Model>::PrimaryKey: AsFormField,
+{
+}
+
 pub(crate) fn check_required<T: FormField>(field: &T) -> Result<&str, FormFieldValidationError> {
     if let Some(value) = field.value() {
         if value.is_empty() {
@@ -99,10 +99,17 @@ const BROWSER_DATETIME_WITHOUT_SEC_FMT: &str = "%Y-%m-%dT%H:%M";
 const BROWSER_DATE_FMT: &str = "%Y-%m-%d";
 const BROWSER_TIME_FMT: &str = "%H:%M:%S";
 const BROWSER_TIME_WITHOUT_SEC_FMT: &str = "%H:%M";
+/// The format of the values returned by `to_field_value` (i.e. the `Display`
+/// format of the chrono types), accepted as well so that the values can be
+/// read back, for instance when importing the objects exported from the admin
+/// panel.
+const DISPLAY_DATETIME_FMT: &str = "%Y-%m-%d %H:%M:%S%.f";
+const DISPLAY_DATETIME_WITH_OFFSET_FMT: &str = "%Y-%m-%d %H:%M:%S%.f %:z";
 
 fn parse_datetime_with_fallback(value: &str) -> Result<NaiveDateTime, ParseError> {
     NaiveDateTime::parse_from_str(value, BROWSER_DATETIME_FMT)
         .or_else(|_| NaiveDateTime::parse_from_str(value, BROWSER_DATETIME_WITHOUT_SEC_FMT))
+        .or_else(|_| NaiveDateTime::parse_from_str(value, DISPLAY_DATETIME_FMT))
 }
 
 fn parse_time_with_fallback(value: &str) -> Result<NaiveTime, ParseError> {
@@ -399,30 +406,38 @@ impl AsFormField for DateTime<FixedOffset> {
         Self: Sized,
     {
         let value = check_required(field)?;
-        // Browsers only support naive datetime.
-        let naive = parse_datetime_with_fallback(value)?;
-        // default to UTC if offset(timezone) is not provided.
-        let tz = field.custom_options.timezone.unwrap_or(Tz::UTC);
-
-        let date_time = match tz.from_local_datetime(&naive) {
-            LocalResult::Single(dt) => dt,
-            LocalResult::Ambiguous(dt1, dt2) => {
-                if let Some(prefer_latest) = field.custom_options.prefer_latest {
-                    if prefer_latest { dt2 } else { dt1 }
-                } else {
-                    return Err(FormFieldValidationError::ambiguous_datetime(naive));
+        let opts = &field.custom_options;
+
+        // values with an offset (as returned by `to_field_value`) are used as is
+        let date_time = if let Ok(date_time) =
+            DateTime::parse_from_str(value, DISPLAY_DATETIME_WITH_OFFSET_FMT)
+        {
+            date_time
+        } else {
+            // Browsers only support naive datetime.
+            let naive = parse_datetime_with_fallback(value)?;
+            // default to UTC if offset(timezone) is not provided.
+            let tz = opts.timezone.unwrap_or(Tz::UTC);
+
+            let date_time = match tz.from_local_datetime(&naive) {
+                LocalResult::Single(dt) => dt,
+                LocalResult::Ambiguous(dt1, dt2) => {
+                    if let Some(prefer_latest) = opts.prefer_latest {
+                        if prefer_latest { dt2 } else { dt1 }
+                    } else {
+                        return Err(FormFieldValidationError::ambiguous_datetime(naive));
+                    }
                 }
-            }
-            LocalResult::None => {
-                return Err(FormFieldValidationError::non_existent_local_datetime(
-                    naive, tz,
-                ));
-            }
-        };
+                LocalResult::None => {
+                    return Err(FormFieldValidationError::non_existent_local_datetime(
+                        naive, tz,
+                    ));
+                }
+            };
 
-        let opts = &field.custom_options;
-        // transform the timezone into a fixed offset.
-        let date_time = date_time.with_timezone(&date_time.offset().fix());
+            // transform the timezone into a fixed offset.
+            date_time.with_timezone(&date_time.offset().fix())
+        };
 
         if let Some(min) = &opts.min
             && date_time < *min
@@ -1167,7 +1182,11 @@ mod tests {
             },
         );
 
-        for &dt in &["2025-05-27T12:34", "2025-05-27T12:34:00"] {
+        for &dt in &[
+            "2025-05-27T12:34",
+            "2025-05-27T12:34:00",
+            "2025-05-27 12:34:00",
+        ] {
             field.set_value(FormFieldValue::new_text(dt)).await.unwrap();
             let dt = NaiveDateTime::clean_value(&field).unwrap();
             assert_eq!(dt.to_string(), "2025-05-27 12:34:00");
@@ -1486,6 +1505,31 @@ mod tests {
         ));
     }
 
+    #[cot::test]
+    async fn datetime_with_tz_clean_field_value() {
+        let date_time = DateTime::parse_from_rfc3339("2025-05-27T12:34:56.5+02:00").unwrap();
+        let mut field = DateTimeWithTimezoneField::with_options(
+            FormFieldOptions {
+                id: "dt".into(),
+                name: "dt".into(),
+                required: true,
+            },
+            DateTimeWithTimezoneFieldOptions {
+                timezone: Some(Tz::America__New_York),
+                ..DateTimeWithTimezoneFieldOptions::default()
+            },
+        );
+        field
+            .set_value(FormFieldValue::new_text(date_time.to_field_value()))
+            .await
+            .unwrap();
+
+        // the offset from the value is used instead of the field's timezone
+        let dt = DateTime::<FixedOffset>::clean_value(&field).unwrap();
+        assert_eq!(dt, date_time);
+        assert_eq!(dt.offset(), date_time.offset());
+    }
+
     #[cot::test]
     async fn datetime_with_tz_clean_invalid_format() {
         let mut field = DateTimeWithTimezoneField::with_options(
@@ -0,0 +1,495 @@
+use derive_more::with_trait::Debug;
+
+use crate::form::{
+    Form, FormContext, FormError, FormErrorTarget, FormFieldValue, FormResult, form_data,
+};
+use crate::request::Request;
+
+/// The maximum number of forms a [`FormSet`] accepts from a request.
+///
+/// This limits the amount of memory a single request can make the server
+/// allocate.
+pub const MAX_FORMSET_FORMS: usize = 1000;
+
+const COUNT_FIELD: &str = "count";
+const OBJECT_ID_FIELD: &str = "object-id";
+const REMOVE_FIELD: &str = "remove-form";
+
+/// A set of forms of the same type submitted together, such as the forms
+/// editing several related objects at once.
+///
+/// Each form in the set has its own prefix, `{prefix}-{index}-`, which is
+/// prepended to the IDs of its fields (see [`FormContext::with_prefix`]), so
+/// that all the forms can be rendered in a single HTML form. Apart from the
+/// form fields, each form can hold the ID of the object it edits and can be
+/// marked as removed; these are submitted in the fields named by
+/// [`FormSetForm::object_id_field_name`] and
+/// [`FormSetForm::remove_field_name`]. The number of the forms is submitted
+/// in the field named by [`FormSet::count_field_name`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::form::{Form, FormSet, FormSetChange, FormSetResult};
+/// use cot::test::TestRequestBuilder;
+///
+/// #[derive(Form)]
+/// struct Comment {
+///     text: String,
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> cot::Result<()> {
+/// let mut request = TestRequestBuilder::post("/")
+///     .form_data(&[
+///         ("comments-count", "2"),
+///         ("comments-0-object-id", "1"),
+///         ("comments-0-text", "Hello"),
+///         ("comments-1-text", "World"),
+///     ])
+///     .build();
+///
+/// let FormSetResult::Ok(changes) = FormSet::<Comment>::from_request(&mut request, "comments").await?
+/// else {
+///     panic!("the form set should be valid");
+/// };
+/// assert!(matches!(&changes[0], FormSetChange::Change { object_id, .. } if object_id == "1"));
+/// assert!(matches!(&changes[1], FormSetChange::Add(comment) if comment.text == "World"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FormSet<F: Form> {
+    prefix: String,
+    forms: Vec<FormSetForm<F>>,
+}
+
+impl<F: Form> FormSet<F> {
+    /// Creates an empty form set with the given prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct Comment {
+    ///     text: String,
+    /// }
+    ///
+    /// let formset = FormSet::<Comment>::new("comments");
+    /// assert_eq!(formset.prefix(), "comments");
+    /// assert!(formset.forms().is_empty());
+    /// ```
+    #[must_use]
+    pub fn new<P: Into<String>>(prefix: P) -> Self {
+        Self {
+            prefix: prefix.into(),
+            forms: Vec::new(),
+        }
+    }
+
+    /// Returns the prefix of the form set.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the name of the field holding the number of the forms in the
+    /// set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct Comment {
+    ///     text: String,
+    /// }
+    ///
+    /// let formset = FormSet::<Comment>::new("comments");
+    /// assert_eq!(formset.count_field_name(), "comments-count");
+    /// ```
+    #[must_use]
+    pub fn count_field_name(&self) -> String {
+        format!("{}-{COUNT_FIELD}", self.prefix)
+    }
+
+    /// Returns the forms in the set.
+    #[must_use]
+    pub fn forms(&self) -> &[FormSetForm<F>] {
+        &self.forms
+    }
+
+    /// Returns the forms in the set as mutable references.
+    #[must_use]
+    pub fn forms_mut(&mut self) -> &mut [FormSetForm<F>] {
+        &mut self.forms
+    }
+
+    /// Consumes the form set and returns its forms.
+    #[must_use]
+    pub fn into_forms(self) -> Vec<FormSetForm<F>> {
+        self.forms
+    }
+
+    /// Adds an empty form, not tied to any object, to the set.
+    pub fn add_form(&mut self) -> &mut FormSetForm<F> {
+        let index = self.forms.len();
+        let prefix = self.next_form_prefix();
+        self.forms.push(FormSetForm {
+            context: F::Context::with_prefix(&prefix),
+            prefix,
+            object_id: None,
+            removed: false,
+        });
+        &mut self.forms[index]
+    }
+
+    /// Adds a form pre-filled with the values of the object with the given ID
+    /// to the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::form::{Form, FormSet};
+    ///
+    /// #[derive(Form)]
+    /// struct Comment {
+    ///     text: String,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut formset = FormSet::<Comment>::new("comments");
+    /// let form = formset
+    ///     .add_object(
+    ///         "1",
+    ///         &Comment {
+    ///             text: "Hello".to_owned(),
+    ///         },
+    ///     )
+    ///     .await;
+    /// assert_eq!(form.object_id(), Some("1"));
+    /// assert_eq!(form.object_id_field_name(), "comments-0-object-id");
+    /// # }
+    /// ```
+    pub async fn add_object<I: Into<String> + Send>(
+        &mut self,
+        object_id: I,
+        object: &F,
+    ) -> &mut FormSetForm<F>
+    where
+        F: Sync,
+    {
+        let index = self.forms.len();
+        let object_id = object_id.into();
+        let prefix = self.next_form_prefix();
+        self.forms.push(FormSetForm {
+            context: object.to_context_with_prefix(&prefix).await,
+            prefix,
+            object_id: Some(object_id),
+            removed: false,
+        });
+        &mut self.forms[index]
+    }
+
+    fn next_form_prefix(&self) -> String {
+        form_prefix(&self.prefix, self.forms.len())
+    }
+
+    /// Builds the form set from a request, without converting the values of
+    /// the form fields.
+    ///
+    /// The fields of the request that don't belong to the form set are
+    /// ignored, so the same request data can also contain other forms. At
+    /// most [`MAX_FORMSET_FORMS`] forms are accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form data could not be read from the request.
+    pub async fn build_from_request<P: Into<String>>(
+        request: &mut Request,
+        prefix: P,
+    ) -> Result<Self, FormError> {
+        let mut formset = Self::new(prefix);
+        let mut count = 0;
+
+        let mut form_data = form_data(request).await?;
+        while let Some((field_id, value)) = form_data.next_value().await? {
+            let Some(name) = field_id
+                .strip_prefix(formset.prefix.as_str())
+                .and_then(|name| name.strip_prefix('-'))
+            else {
+                continue;
+            };
+            if name == COUNT_FIELD {
+                count = value
+                    .into_text()
+                    .await?
+                    .parse::<usize>()
+                    .unwrap_or_default()
+                    .min(MAX_FORMSET_FORMS);
+                continue;
+            }
+            let Some((index, name)) = name.split_once('-') else {
+                continue;
+            };
+            let Some(index) = index
+                .parse::<usize>()
+                .ok()
+                .filter(|&index| index < MAX_FORMSET_FORMS)
+            else {
+                continue;
+            };
+
+            while formset.forms.len() <= index {
+                formset.add_form();
+            }
+            formset.forms[index]
+                .set_value(&field_id, name, value)
+                .await?;
+        }
+
+        formset.forms.truncate(count);
+        while formset.forms.len() < count {
+            formset.add_form();
+        }
+
+        Ok(formset)
+    }
+
+    /// Builds the form set from a request and converts the values of the
+    /// forms.
+    ///
+    /// This is a shorthand for [`Self::build_from_request`] followed by
+    /// [`Self::into_result`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the form data could not be read from the request.
+    pub async fn from_request<P: Into<String>>(
+        request: &mut Request,
+        prefix: P,
+    ) -> Result<FormSetResult<F>, FormError> {
+        Ok(Self::build_from_request(request, prefix)
+            .await?
+            .into_result()
+            .await)
+    }
+
+    /// Converts the values of the forms in the set into the changes to be
+    /// made to the objects.
+    ///
+    /// The forms marked as removed are not validated. The forms not tied to
+    /// any object whose fields have all been left empty (such as the spare
+    /// forms for adding new objects) are skipped.
+    ///
+    /// If any of the forms is invalid, the form set with the validation errors
+    /// is returned instead, so that it can be shown to the user again.
+    pub async fn into_result(self) -> FormSetResult<F> {
+        let prefix = self.prefix;
+        let mut has_errors = false;
+        let mut results = Vec::with_capacity(self.forms.len());
+
+        for form in self.forms {
+            let result = if form.removed {
+                FormSetFormResult::Removed(form.object_id)
+            } else if form.object_id.is_none() && form.is_empty() {
+                FormSetFormResult::Empty(form.context)
+            } else {
+                match F::from_context(form.context) {
+                    FormResult::Ok(value) => FormSetFormResult::Ok(form.object_id, value),
+                    FormResult::ValidationError(context) => {
+                        has_errors = true;
+                        FormSetFormResult::Invalid(form.object_id, context)
+                    }
+                }
+            };
+            results.push(result);
+        }
+
+        if !has_errors {
+            let changes = results
+                .into_iter()
+                .filter_map(|result| match result {
+                    FormSetFormResult::Ok(Some(object_id), form) => {
+                        Some(FormSetChange::Change { object_id, form })
+                    }
+                    FormSetFormResult::Ok(None, form) => Some(FormSetChange::Add(form)),
+                    FormSetFormResult::Removed(object_id) => {
+                        object_id.map(|object_id| FormSetChange::Remove { object_id })
+                    }
+                    FormSetFormResult::Empty(_) | FormSetFormResult::Invalid(..) => None,
+                })
+                .collect();
+            return FormSetResult::Ok(changes);
+        }
+
+        let mut formset = Self::new(prefix);
+        for result in results {
+            let form_prefix = formset.next_form_prefix();
+            let (object_id, removed, context) = match result {
+                FormSetFormResult::Ok(object_id, form) => (
+                    object_id,
+                    false,
+                    form.to_context_with_prefix(&form_prefix).await,
+                ),
+                FormSetFormResult::Invalid(object_id, context) => (object_id, false, context),
+                FormSetFormResult::Empty(context) => (None, false, context),
+                FormSetFormResult::Removed(object_id) => {
+                    (object_id, true, F::Context::with_prefix(&form_prefix))
+                }
+            };
+            formset.forms.push(FormSetForm {
+                prefix: form_prefix,
+                object_id,
+                removed,
+                context,
+            });
+        }
+        FormSetResult::ValidationError(formset)
+    }
+
+    /// Returns whether any of the forms in the set has validation errors.
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.forms.iter().any(|form| form.context.has_errors())
+    }
+}
+
+fn form_prefix(prefix: &str, index: usize) -> String {
+    format!("{prefix}-{index}-")
+}
+
+enum FormSetFormResult<F: Form> {
+    Ok(Option<String>, F),
+    Invalid(Option<String>, F::Context),
+    Empty(F::Context),
+    Removed(Option<String>),
+}
+
+/// A single form in a [`FormSet`].
+#[derive(Debug)]
+pub struct FormSetForm<F: Form> {
+    prefix: String,
+    object_id: Option<String>,
+    removed: bool,
+    context: F::Context,
+}
+
+impl<F: Form> FormSetForm<F> {
+    /// Returns the prefix of the IDs of the fields in this form, such as
+    /// `comments-0-`.
+    #[must_use]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns the ID of the object edited by this form, or [`None`] if the
+    /// form adds a new object.
+    #[must_use]
+    pub fn object_id(&self) -> Option<&str> {
+        self.object_id.as_deref()
+    }
+
+    /// Returns the name of the field holding the ID of the object edited by
+    /// this form.
+    #[must_use]
+    pub fn object_id_field_name(&self) -> String {
+        format!("{}{OBJECT_ID_FIELD}", self.prefix)
+    }
+
+    /// Returns whether the form has been marked as removed.
+    #[must_use]
+    pub fn is_removed(&self) -> bool {
+        self.removed
+    }
+
+    /// Returns the name of the field marking this form as removed.
+    #[must_use]
+    pub fn remove_field_name(&self) -> String {
+        format!("{}{REMOVE_FIELD}", self.prefix)
+    }
+
+    /// Returns the context of this form.
+    #[must_use]
+    pub fn context(&self) -> &F::Context {
+        &self.context
+    }
+
+    /// Returns the context of this form as a mutable reference.
+    #[must_use]
+    pub fn context_mut(&mut self) -> &mut F::Context {
+        &mut self.context
+    }
+
+    /// Consumes the form and returns its context.
+    #[must_use]
+    pub fn into_context(self) -> F::Context {
+        self.context
+    }
+
+    /// Returns whether all the fields of this form are empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.context
+            .fields()
+            .all(|field| field.dyn_value().is_none_or(str::is_empty))
+    }
+
+    async fn set_value(
+        &mut self,
+        field_id: &str,
+        name: &str,
+        value: FormFieldValue<'_>,
+    ) -> Result<(), FormError> {
+        match name {
+            OBJECT_ID_FIELD => {
+                self.object_id = Some(value.into_text().await?).filter(|id| !id.is_empty());
+            }
+            REMOVE_FIELD => {
+                self.removed = !value.into_text().await?.is_empty();
+            }
+            _ => {
+                if let Err(error) = self.context.set_value(field_id, value).await {
+                    self.context
+                        .add_error(FormErrorTarget::Field(field_id), error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The result of converting the values of a [`FormSet`].
+#[must_use]
+#[derive(Debug)]
+pub enum FormSetResult<F: Form> {
+    /// All the forms are valid; contains the changes to be made to the
+    /// objects, in the order of the forms.
+    Ok(Vec<FormSetChange<F>>),
+    /// Some of the forms are invalid; contains the form set with the
+    /// validation errors.
+    ValidationError(FormSet<F>),
+}
+
+/// A change to be made to an object, as submitted in a [`FormSet`].
+#[derive(Debug)]
+pub enum FormSetChange<F> {
+    /// A new object should be added.
+    Add(F),
+    /// The object with the given ID should be changed.
+    Change {
+        /// The ID of the object.
+        object_id: String,
+        /// The new values of the object.
+        form: F,
+    },
+    /// The object with the given ID should be removed.
+    Remove {
+        /// The ID of the object.
+        object_id: String,
+    },
+}
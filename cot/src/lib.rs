@@ -66,6 +66,7 @@ pub mod auth;
 pub mod cli;
 pub mod common_types;
 pub mod config;
+pub mod csrf;
 #[cfg(feature = "email")]
 pub mod email;
 mod error_page;
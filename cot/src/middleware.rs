@@ -30,9 +30,18 @@ use crate::session::store::memory::MemoryStore;
 #[cfg(feature = "redis")]
 use crate::session::store::redis::RedisStore;
 
+#[cfg(feature = "db")]
+mod api_token;
+mod basic_auth;
+mod csrf;
+#[cfg(feature = "jwt")]
+mod jwt;
 #[cfg(feature = "live-reload")]
 mod live_reload;
 
+#[cfg(feature = "db")]
+pub use api_token::{ApiTokenMiddleware, ApiTokenService};
+pub use basic_auth::{BasicAuthMiddleware, BasicAuthService};
 /// Middleware that converts any error type to [`Error`].
 ///
 /// This is useful for converting a response from a middleware that is
@@ -96,6 +105,9 @@ pub use cot_core::middleware::IntoCotErrorLayer;
 pub use cot_core::middleware::IntoCotResponseLayer;
 #[doc(inline)]
 pub use cot_core::middleware::{IntoCotError, IntoCotResponse};
+pub use csrf::{CsrfMiddleware, CsrfService};
+#[cfg(feature = "jwt")]
+pub use jwt::{JwtMiddleware, JwtService};
 #[cfg(feature = "live-reload")]
 pub use live_reload::LiveReloadMiddleware;
 
@@ -539,8 +551,12 @@ where
         let mut inner = std::mem::replace(&mut self.inner, clone);
 
         Box::pin(async move {
-            let auth = crate::auth::Auth::from_request(&mut req).await?;
-            req.extensions_mut().insert(auth);
+            // The user might have already been authenticated without a session (e.g. by
+            // the `ApiTokenMiddleware`), in which case the session is not checked at all.
+            if req.extensions().get::<crate::auth::Auth>().is_none() {
+                let auth = crate::auth::Auth::from_request(&mut req).await?;
+                req.extensions_mut().insert(auth);
+            }
 
             inner.call(req).await
         })
@@ -0,0 +1,333 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::header::AUTHORIZATION;
+use tower::Service;
+
+use crate::Error;
+use crate::auth::Auth;
+use crate::auth::api_token::{ApiToken, ApiTokenError};
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// A middleware that authenticates the requests using API tokens.
+///
+/// If a request contains the `Authorization: Bearer <token>` header, the
+/// token is looked up in the database, and its owner becomes the current user
+/// returned by the [`Auth`] extractor for the duration of that request only.
+/// No session is used (or created) in that case, so this is the recommended
+/// way to authenticate JSON API clients. The [`ApiToken`] itself is also made
+/// available to the request handlers, for instance to check its
+/// [scopes](ApiToken::require_scope).
+///
+/// Requests with a token that doesn't exist or has expired are rejected with a
+/// `401 Unauthorized` error, as are the requests with a token of a user who
+/// has to verify a second authentication factor (see
+/// [`AuthBackend::requires_second_factor`](crate::auth::AuthBackend::requires_second_factor)). Requests without the header are passed through
+/// unchanged, so that they can still be authenticated with a session.
+///
+/// This middleware should be applied *after* the
+/// [`AuthMiddleware`](crate::middleware::AuthMiddleware) (i.e. it should be
+/// added before the auth middleware in
+/// [`Project::middlewares`](crate::project::Project::middlewares)). It requires
+/// the database to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::middleware::{ApiTokenMiddleware, AuthMiddleware, SessionMiddleware};
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(ApiTokenMiddleware::new())
+///             .middleware(AuthMiddleware::new())
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ApiTokenMiddleware;
+
+impl ApiTokenMiddleware {
+    /// Create a new [`ApiTokenMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::ApiTokenMiddleware;
+    ///
+    /// let middleware = ApiTokenMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl<S> tower::Layer<S> for ApiTokenMiddleware {
+    type Service = ApiTokenService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiTokenService::new(inner)
+    }
+}
+
+/// Service that authenticates the requests using API tokens.
+///
+/// Used by [`ApiTokenMiddleware`].
+#[derive(Debug, Clone)]
+pub struct ApiTokenService<S>(S);
+
+impl<S> ApiTokenService<S> {
+    fn new(inner: S) -> Self {
+        Self(inner)
+    }
+}
+
+impl<S> Service<Request> for ApiTokenService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // See `AuthService::call` for why the inner service is cloned here.
+        let clone = self.0.clone();
+        let mut inner = std::mem::replace(&mut self.0, clone);
+
+        Box::pin(async move {
+            if let Some(token) = bearer_token(&req)? {
+                let database = req.context().database();
+                let (api_token, user) = ApiToken::authenticate(database, &token)
+                    .await?
+                    .ok_or(ApiTokenError::Invalid)?;
+                let backend = req.context().auth_backend();
+                if backend.requires_second_factor(&user).await? {
+                    return Err(ApiTokenError::SecondFactorRequired.into());
+                }
+
+                let auth = Auth::stateless(
+                    Arc::clone(backend),
+                    Box::new(user),
+                    req.project_config().secret_key.clone(),
+                );
+                req.extensions_mut().insert(auth);
+                req.extensions_mut().insert(api_token);
+            }
+
+            inner.call(req).await
+        })
+    }
+}
+
+/// Returns the bearer token from the `Authorization` header, if any.
+fn bearer_token(req: &Request) -> crate::Result<Option<String>> {
+    let Some(header) = req.headers().get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let value = header.to_str().map_err(|_| ApiTokenError::Invalid)?;
+    // Other authorization schemes are left to be handled by other middlewares
+    let Some(token) = value.strip_prefix(BEARER_PREFIX) else {
+        return Ok(None);
+    };
+
+    let token = token.trim();
+    // API tokens never contain dots, unlike JSON Web Tokens, which are left to
+    // be handled by the `JwtMiddleware`
+    if token.contains('.') {
+        return Ok(None);
+    }
+    if token.is_empty() {
+        return Err(ApiTokenError::Missing.into());
+    }
+    Ok(Some(token.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::auth::db::{DatabaseUser, DatabaseUserBackend};
+    use crate::test::{TestDatabase, TestRequestBuilder};
+
+    async fn test_db() -> (TestDatabase, DatabaseUser) {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "testuser", "password123")
+            .await
+            .unwrap();
+        (db, user)
+    }
+
+    async fn current_user(
+        db: &TestDatabase,
+        authorization: Option<&str>,
+    ) -> crate::Result<Option<String>> {
+        let mut request = TestRequestBuilder::get("/")
+            .with_db_auth(db.database())
+            .await
+            .build();
+        if let Some(authorization) = authorization {
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        }
+
+        let service =
+            ApiTokenMiddleware::new().layer(tower::service_fn(|req: Request| async move {
+                let username = req
+                    .extensions()
+                    .get::<Auth>()
+                    .and_then(|auth| auth.user().username().map(|name| name.to_string()));
+                assert_eq!(
+                    username.is_some(),
+                    req.extensions().get::<ApiToken>().is_some()
+                );
+                Ok::<_, Error>(Response::new(Body::fixed(username.unwrap_or_default())))
+            }));
+
+        let response = service.oneshot(request).await?;
+        let body = response.into_body().into_bytes().await?;
+        let username = String::from_utf8(body.to_vec()).unwrap();
+        Ok(Some(username).filter(|username| !username.is_empty()))
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn api_token_authenticates_user() {
+        let (db, user) = test_db().await;
+        let (_, token) = ApiToken::create(&db.database(), &user, "test", ["read"], None)
+            .await
+            .unwrap();
+
+        let username = current_user(&db, Some(&format!("Bearer {token}")))
+            .await
+            .unwrap();
+
+        assert_eq!(username.as_deref(), Some("testuser"));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn api_token_no_bearer_header() {
+        let (db, _) = test_db().await;
+
+        assert_eq!(current_user(&db, None).await.unwrap(), None);
+        assert_eq!(
+            current_user(&db, Some("Basic dXNlcjpwYXNz")).await.unwrap(),
+            None
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn api_token_invalid() {
+        let (db, _) = test_db().await;
+
+        assert!(current_user(&db, Some("Bearer invalid")).await.is_err());
+        assert!(current_user(&db, Some("Bearer ")).await.is_err());
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn api_token_expired_and_revoked() {
+        let (db, user) = test_db().await;
+        let expires_at = chrono::Utc::now().fixed_offset() - chrono::Duration::minutes(1);
+        let (_, expired) =
+            ApiToken::create(&db.database(), &user, "expired", ["read"], Some(expires_at))
+                .await
+                .unwrap();
+        let (api_token, revoked) =
+            ApiToken::create(&db.database(), &user, "revoked", ["read"], None)
+                .await
+                .unwrap();
+
+        let tokens = ApiToken::list_for_user(&db.database(), &user)
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 2);
+        api_token.revoke(&db.database()).await.unwrap();
+        let tokens = ApiToken::list_for_user(&db.database(), &user)
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+
+        for token in [expired, revoked] {
+            assert!(
+                current_user(&db, Some(&format!("Bearer {token}")))
+                    .await
+                    .is_err()
+            );
+        }
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn api_token_second_factor_required() {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_superuser(&db.database(), "admin", "password123")
+            .await
+            .unwrap();
+        let (_, token) = ApiToken::create(&db.database(), &user, "test", ["read"], None)
+            .await
+            .unwrap();
+
+        let mut request = TestRequestBuilder::get("/")
+            .auth_backend(
+                DatabaseUserBackend::new(db.database()).require_second_factor_for_staff(true),
+            )
+            .database(db.database())
+            .build();
+        request.headers_mut().insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        let service = ApiTokenMiddleware::new().layer(tower::service_fn(|_: Request| async {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        }));
+        let error = service.oneshot(request).await.unwrap_err();
+        assert_eq!(error.status_code(), crate::StatusCode::UNAUTHORIZED);
+        assert!(matches!(
+            error.inner().downcast_ref::<ApiTokenError>(),
+            Some(ApiTokenError::SecondFactorRequired)
+        ));
+    }
+}
@@ -0,0 +1,479 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::HeaderValue;
+use http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use tower::Service;
+
+use crate::auth::basic::{BasicAuthCredentials, BasicAuthError, DEFAULT_BASIC_AUTH_REALM};
+use crate::auth::throttle::LoginThrottle;
+use crate::auth::{Auth, User};
+use crate::error::handler::RequestOuterError;
+use crate::project::MiddlewareContext;
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+use crate::{Error, StatusCode};
+
+/// A middleware that authenticates the requests using HTTP Basic
+/// authentication.
+///
+/// If a request contains the `Authorization: Basic <credentials>` header, the
+/// credentials are checked by the project's
+/// [`AuthBackend`](crate::auth::AuthBackend), and the user becomes the current
+/// user returned by the [`Auth`] extractor for the duration of that request
+/// only. No session is used (or created) in that case. The
+/// [`BasicAuthBackend`](crate::auth::basic::BasicAuthBackend) (enabled with
+/// [`AuthBackendConfig::Basic`](crate::config::AuthBackendConfig::Basic))
+/// accepts the users listed in the config as well as the database users,
+/// and the [`DatabaseUserBackend`](crate::auth::db::DatabaseUserBackend) only
+/// the latter. Failed attempts are subject to the [login
+/// throttling](crate::auth::throttle), if it's enabled.
+///
+/// Requests with invalid credentials are rejected with a
+/// `401 Unauthorized` error, and so are the requests of the users who have to
+/// verify a second authentication factor (see
+/// [`AuthBackend::requires_second_factor`](crate::auth::AuthBackend::requires_second_factor)),
+/// since it can't be verified with HTTP Basic authentication. Requests without
+/// the header are passed through unchanged, so that they can still be
+/// authenticated in other ways (such as with a session), unless the
+/// credentials are [required](Self::required). Every `401 Unauthorized`
+/// response gets a `WWW-Authenticate` header, so that browsers and other
+/// clients know they should send the credentials.
+///
+/// This middleware should be applied *after* the
+/// [`AuthMiddleware`](crate::middleware::AuthMiddleware) (i.e. it should be
+/// added before the auth middleware in
+/// [`Project::middlewares`](crate::project::Project::middlewares)).
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::middleware::{AuthMiddleware, BasicAuthMiddleware, SessionMiddleware};
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(BasicAuthMiddleware::from_context(context))
+///             .middleware(AuthMiddleware::new())
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BasicAuthMiddleware {
+    challenge: HeaderValue,
+    required: bool,
+}
+
+impl BasicAuthMiddleware {
+    /// Create a new [`BasicAuthMiddleware`] that uses the
+    /// [default realm](DEFAULT_BASIC_AUTH_REALM).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BasicAuthMiddleware;
+    ///
+    /// let middleware = BasicAuthMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            challenge: challenge(DEFAULT_BASIC_AUTH_REALM),
+            required: false,
+        }
+    }
+
+    /// Create a new [`BasicAuthMiddleware`] that uses the realm from
+    /// [`ProjectConfig::basic_auth`](crate::config::ProjectConfig::basic_auth).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BasicAuthMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    ///
+    /// fn middlewares(handler: RootHandlerBuilder, context: &MiddlewareContext) -> RootHandler {
+    ///     handler
+    ///         .middleware(BasicAuthMiddleware::from_context(context))
+    ///         .build()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        Self::new().realm(&context.config().basic_auth.realm)
+    }
+
+    /// Sets the realm sent in the `WWW-Authenticate` header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BasicAuthMiddleware;
+    ///
+    /// let middleware = BasicAuthMiddleware::new().realm("Webhooks");
+    /// ```
+    #[must_use]
+    pub fn realm(mut self, realm: &str) -> Self {
+        self.challenge = challenge(realm);
+        self
+    }
+
+    /// Sets whether the requests that are not authenticated in any other way
+    /// are rejected with a `401 Unauthorized` error.
+    ///
+    /// This is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::BasicAuthMiddleware;
+    ///
+    /// let middleware = BasicAuthMiddleware::new().required(true);
+    /// ```
+    #[must_use]
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+}
+
+impl Default for BasicAuthMiddleware {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> tower::Layer<S> for BasicAuthMiddleware {
+    type Service = BasicAuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BasicAuthService::new(inner, self.challenge.clone(), self.required)
+    }
+}
+
+/// Service that authenticates the requests using HTTP Basic authentication.
+///
+/// Used by [`BasicAuthMiddleware`].
+#[derive(Debug, Clone)]
+pub struct BasicAuthService<S> {
+    inner: S,
+    challenge: HeaderValue,
+    required: bool,
+}
+
+impl<S> BasicAuthService<S> {
+    fn new(inner: S, challenge: HeaderValue, required: bool) -> Self {
+        Self {
+            inner,
+            challenge,
+            required,
+        }
+    }
+}
+
+impl<S> Service<Request> for BasicAuthService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // See `AuthService::call` for why the inner service is cloned here.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let challenge = self.challenge.clone();
+        let required = self.required;
+
+        Box::pin(async move {
+            // The error handler only needs the challenge to be added to its response
+            if req.extensions().get::<RequestOuterError>().is_none() {
+                if let Some(credentials) = basic_credentials(&req)? {
+                    let user = authenticate(&req, &credentials)
+                        .await?
+                        .ok_or(BasicAuthError::Invalid)?;
+
+                    let auth = Auth::stateless(
+                        Arc::clone(req.context().auth_backend()),
+                        user,
+                        req.project_config().secret_key.clone(),
+                    );
+                    req.extensions_mut().insert(auth);
+                } else if required
+                    && !req
+                        .extensions()
+                        .get::<Auth>()
+                        .is_some_and(|auth| auth.user().is_authenticated())
+                {
+                    return Err(BasicAuthError::Missing.into());
+                }
+            }
+
+            let mut response = inner.call(req).await?;
+            if response.status() == StatusCode::UNAUTHORIZED {
+                response
+                    .headers_mut()
+                    .entry(WWW_AUTHENTICATE)
+                    .or_insert(challenge);
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// Returns the HTTP Basic credentials from the `Authorization` header, if any.
+fn basic_credentials(req: &Request) -> crate::Result<Option<BasicAuthCredentials>> {
+    let Some(header) = req.headers().get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let value = header.to_str().map_err(|_| BasicAuthError::Invalid)?;
+    // Other authorization schemes are left to be handled by other middlewares
+    Ok(BasicAuthCredentials::from_authorization_header(value)?)
+}
+
+async fn authenticate(
+    req: &Request,
+    credentials: &BasicAuthCredentials,
+) -> crate::Result<Option<Box<dyn User + Send + Sync>>> {
+    let backend = req.context().auth_backend();
+    let user = match LoginThrottle::from_request(req) {
+        Some(throttle) => {
+            throttle
+                .authenticate(&**backend, credentials, LoginThrottle::client_ip(req))
+                .await?
+        }
+        None => backend.authenticate(credentials).await?,
+    };
+
+    let Some(user) = user.filter(|user| user.is_active()) else {
+        return Ok(None);
+    };
+    if backend.requires_second_factor(&*user).await? {
+        return Err(BasicAuthError::SecondFactorRequired.into());
+    }
+
+    Ok(Some(user))
+}
+
+fn challenge(realm: &str) -> HeaderValue {
+    let realm = realm.replace('\\', "\\\\").replace('"', "\\\"");
+    HeaderValue::from_str(&format!("Basic realm=\"{realm}\", charset=\"UTF-8\""))
+        .unwrap_or_else(|_| HeaderValue::from_static("Basic charset=\"UTF-8\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::Body;
+    use crate::auth::PasswordHash;
+    use crate::auth::basic::BasicAuthBackend;
+    use crate::common_types::Password;
+    use crate::middleware::AuthMiddleware;
+    use crate::test::TestRequestBuilder;
+
+    const VALID: &str = "Basic ZGVwbG95OnBhc3N3b3JkMTIz"; // deploy:password123
+    const INVALID: &str = "Basic ZGVwbG95OmludmFsaWQ="; // deploy:invalid
+
+    async fn call(
+        middleware: BasicAuthMiddleware,
+        authorization: Option<&str>,
+    ) -> crate::Result<(Option<String>, Response)> {
+        let backend = BasicAuthBackend::new().user(
+            "deploy",
+            PasswordHash::from_password(&Password::new("password123")),
+        );
+        let mut request = TestRequestBuilder::get("/")
+            .auth_backend(backend)
+            .with_session()
+            .build();
+        if let Some(authorization) = authorization {
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let service = middleware.layer(tower::service_fn(move |req: Request| {
+            let tx = tx.clone();
+            async move {
+                let username = req
+                    .extensions()
+                    .get::<Auth>()
+                    .and_then(|auth| auth.user().username().map(|name| name.to_string()));
+                tx.send(username).unwrap();
+                let mut response = Response::new(Body::empty());
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                Ok::<_, Error>(response)
+            }
+        }));
+
+        let response = service.oneshot(request).await?;
+        Ok((rx.recv().unwrap(), response))
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn valid_credentials() {
+        let (username, _) = call(BasicAuthMiddleware::new(), Some(VALID)).await.unwrap();
+
+        assert_eq!(username.as_deref(), Some("deploy"));
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn invalid_credentials() {
+        let error = call(BasicAuthMiddleware::new(), Some(INVALID))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+
+        let error = call(BasicAuthMiddleware::new(), Some("Basic !!!"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn no_credentials() {
+        let (username, _) = call(BasicAuthMiddleware::new(), None).await.unwrap();
+        assert_eq!(username, None);
+
+        let (username, _) = call(BasicAuthMiddleware::new(), Some("Bearer token"))
+            .await
+            .unwrap();
+        assert_eq!(username, None);
+
+        let error = call(BasicAuthMiddleware::new().required(true), None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn database_user() {
+        let mut db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        crate::auth::db::DatabaseUser::create_user(&db.database(), "deploy", "password123")
+            .await
+            .unwrap();
+
+        let mut request = TestRequestBuilder::get("/")
+            .with_db_auth(db.database())
+            .await
+            .build();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static(VALID));
+        let service =
+            BasicAuthMiddleware::new().layer(tower::service_fn(|req: Request| async move {
+                let auth = req.extensions().get::<Auth>().unwrap();
+                assert_eq!(auth.user().username().as_deref(), Some("deploy"));
+                Ok::<_, Error>(Response::new(Body::empty()))
+            }));
+        service.oneshot(request).await.unwrap();
+
+        db.cleanup().await.unwrap();
+    }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn second_factor_required() {
+        let mut db = crate::test::TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        crate::auth::db::DatabaseUser::create_superuser(&db.database(), "deploy", "password123")
+            .await
+            .unwrap();
+
+        let mut request = TestRequestBuilder::get("/")
+            .auth_backend(
+                crate::auth::db::DatabaseUserBackend::new(db.database())
+                    .require_second_factor_for_staff(true),
+            )
+            .database(db.database())
+            .with_session()
+            .build();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static(VALID));
+        let service = BasicAuthMiddleware::new().layer(tower::service_fn(|_: Request| async {
+            Ok::<_, Error>(Response::new(Body::empty()))
+        }));
+        let error = service.oneshot(request).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+
+        db.cleanup().await.unwrap();
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn required_with_auth_middleware() {
+        let service = AuthMiddleware::new().layer(BasicAuthMiddleware::new().required(true).layer(
+            tower::service_fn(|req: Request| async move {
+                let auth = req.extensions().get::<Auth>().unwrap();
+                assert!(auth.user().is_authenticated());
+                Ok::<_, Error>(Response::new(Body::empty()))
+            }),
+        ));
+        let build_request = || {
+            TestRequestBuilder::get("/")
+                .auth_backend(BasicAuthBackend::new().user(
+                    "deploy",
+                    PasswordHash::from_password(&Password::new("password123")),
+                ))
+                .with_session()
+                .build()
+        };
+
+        // the anonymous user set by the auth middleware is not enough
+        let error = service.clone().oneshot(build_request()).await.unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+
+        let mut request = build_request();
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_static(VALID));
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    #[cfg_attr(miri, ignore)]
+    async fn challenge_on_unauthorized() {
+        let (_, response) = call(BasicAuthMiddleware::new().realm("Web\"hooks"), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Basic realm=\"Web\\\"hooks\", charset=\"UTF-8\""
+        );
+    }
+}
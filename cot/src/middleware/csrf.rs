@@ -0,0 +1,464 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use cot_core::headers::{MULTIPART_FORM_CONTENT_TYPE, URLENCODED_FORM_CONTENT_TYPE};
+use futures_core::future::BoxFuture;
+use tower::Service;
+
+use crate::csrf::{CSRF_FIELD_NAME, CSRF_HEADER_NAME, CsrfError, CsrfToken};
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+use crate::session::Session;
+use crate::{Body, Error};
+
+/// A middleware that protects the project against cross-site request forgery
+/// (CSRF) attacks.
+///
+/// The middleware adds a [`CsrfToken`] to every request, which can be
+/// extracted in request handlers and rendered in templates. Requests with an
+/// unsafe HTTP method (that is, anything other than `GET`, `HEAD`, `OPTIONS`,
+/// and `TRACE`) are rejected with a `403 Forbidden` error unless they contain
+/// the token, either as a form field named
+/// [`CSRF_FIELD_NAME`](crate::csrf::CSRF_FIELD_NAME), or in the
+/// [`CSRF_HEADER_NAME`](crate::csrf::CSRF_HEADER_NAME) header.
+///
+/// The token is stored in the session, so this middleware requires the
+/// [`SessionMiddleware`](crate::middleware::SessionMiddleware) to be enabled
+/// and to be applied *after* it (i.e. the CSRF middleware should be added
+/// before the session middleware in
+/// [`Project::middlewares`](crate::project::Project::middlewares)).
+///
+/// When the token is sent in a form field, the body of the request has to be
+/// read into memory to find it. To avoid running out of memory, bodies larger
+/// than 2 MiB are rejected with a `400 Bad Request` error; this can be changed
+/// with [`CsrfMiddleware::max_body_size`]. Requests sending
+/// the token in the header are not subject to this limit.
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::middleware::{CsrfMiddleware, SessionMiddleware};
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(CsrfMiddleware::new().exempt("/api/"))
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsrfMiddleware {
+    exempt_paths: Arc<Vec<String>>,
+    max_body_size: usize,
+}
+
+/// The default maximum size, in bytes, of a request body read to find the CSRF
+/// token in it.
+const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+impl Default for CsrfMiddleware {
+    fn default() -> Self {
+        Self {
+            exempt_paths: Arc::default(),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+}
+
+impl CsrfMiddleware {
+    /// Create a new [`CsrfMiddleware`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CsrfMiddleware;
+    ///
+    /// let middleware = CsrfMiddleware::new();
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exempt all the requests whose path starts with the given prefix from
+    /// the CSRF validation.
+    ///
+    /// The prefix is matched against whole path segments, so `/api` (or
+    /// `/api/`) exempts `/api` and `/api/items`, but not `/apiary`.
+    ///
+    /// This is useful for API endpoints that are authenticated in some other
+    /// way than with the session cookie (for instance, with a bearer token),
+    /// and hence are not vulnerable to CSRF attacks. Note that the
+    /// [`CsrfToken`] is still added to the exempted requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CsrfMiddleware;
+    ///
+    /// let middleware = CsrfMiddleware::new().exempt("/api/").exempt("/webhooks/");
+    /// ```
+    #[must_use]
+    pub fn exempt<T: Into<String>>(mut self, path_prefix: T) -> Self {
+        Arc::make_mut(&mut self.exempt_paths).push(path_prefix.into());
+        self
+    }
+
+    /// Set the maximum size, in bytes, of a request body read to find the
+    /// CSRF token in a form field.
+    ///
+    /// Larger requests are rejected with a `400 Bad Request` error. The
+    /// default is 2 MiB; you might want to increase it if your forms upload
+    /// large files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::CsrfMiddleware;
+    ///
+    /// let middleware = CsrfMiddleware::new().max_body_size(16 * 1024 * 1024);
+    /// ```
+    #[must_use]
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+}
+
+impl<S> tower::Layer<S> for CsrfMiddleware {
+    type Service = CsrfService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CsrfService::new(inner, Arc::clone(&self.exempt_paths), self.max_body_size)
+    }
+}
+
+/// Service that validates the CSRF token and adds [`CsrfToken`] to the
+/// request.
+///
+/// Used by [`CsrfMiddleware`].
+#[derive(Debug, Clone)]
+pub struct CsrfService<S> {
+    inner: S,
+    exempt_paths: Arc<Vec<String>>,
+    max_body_size: usize,
+}
+
+impl<S> CsrfService<S> {
+    fn new(inner: S, exempt_paths: Arc<Vec<String>>, max_body_size: usize) -> Self {
+        Self {
+            inner,
+            exempt_paths,
+            max_body_size,
+        }
+    }
+}
+
+impl<S> Service<Request> for CsrfService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // See `AuthService::call` for why the inner service is cloned here.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let exempt_paths = Arc::clone(&self.exempt_paths);
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let token = CsrfToken::from_session(Session::from_request(&req)).await?;
+
+            let is_exempt = exempt_paths
+                .iter()
+                .any(|prefix| is_path_under(req.uri().path(), prefix));
+            if !is_safe_method(req.method()) && !is_exempt {
+                req = verify_request(req, &token, max_body_size).await?;
+            }
+
+            req.extensions_mut().insert(token);
+            inner.call(req).await
+        })
+    }
+}
+
+/// Checks whether the path is equal to the given prefix or is under it,
+/// comparing whole path segments.
+fn is_path_under(path: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+fn is_safe_method(method: &http::Method) -> bool {
+    matches!(
+        *method,
+        http::Method::GET | http::Method::HEAD | http::Method::OPTIONS | http::Method::TRACE
+    )
+}
+
+/// Checks whether the request contains a valid CSRF token.
+///
+/// Returns the request back, as the body might have been read and replaced
+/// in the process.
+async fn verify_request(
+    req: Request,
+    token: &CsrfToken,
+    max_body_size: usize,
+) -> crate::Result<Request> {
+    if let Some(header) = req.headers().get(CSRF_HEADER_NAME) {
+        let value = header.to_str().map_err(|_| CsrfError::Invalid)?;
+        return if token.verify(value) {
+            Ok(req)
+        } else {
+            Err(CsrfError::Invalid.into())
+        };
+    }
+
+    let content_type = req
+        .content_type()
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let is_urlencoded = content_type.starts_with(URLENCODED_FORM_CONTENT_TYPE);
+    let is_multipart = content_type.starts_with(MULTIPART_FORM_CONTENT_TYPE);
+    if !is_urlencoded && !is_multipart {
+        return Err(CsrfError::Missing.into());
+    }
+
+    // The body has to be buffered, so that it can still be read by the request
+    // handler after the token is extracted from it.
+    let (head, body) = req.into_parts();
+    let bytes = body.into_bytes_limited(max_body_size).await?;
+    let value = if is_urlencoded {
+        urlencoded_token(&bytes)
+    } else {
+        multipart_token(&content_type, bytes.clone()).await
+    };
+
+    match value {
+        Some(value) if token.verify(&value) => Ok(Request::from_parts(head, Body::fixed(bytes))),
+        Some(_) => Err(CsrfError::Invalid.into()),
+        None => Err(CsrfError::Missing.into()),
+    }
+}
+
+fn urlencoded_token(bytes: &[u8]) -> Option<String> {
+    form_urlencoded::parse(bytes)
+        .find(|(key, _)| key == CSRF_FIELD_NAME)
+        .map(|(_, value)| value.into_owned())
+}
+
+async fn multipart_token(content_type: &str, bytes: Bytes) -> Option<String> {
+    let boundary = multer::parse_boundary(content_type).ok()?;
+    let stream = futures_util::stream::once(async move { Ok::<_, std::io::Error>(bytes) });
+    let mut multipart = multer::Multipart::new(stream, boundary);
+
+    while let Some(field) = multipart.next_field().await.ok()? {
+        if field.name() == Some(CSRF_FIELD_NAME) {
+            return field.text().await.ok();
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use cot_core::StatusCode;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::test::TestRequestBuilder;
+
+    fn test_service()
+    -> impl Service<Request, Response = Response, Error = Error, Future: Send> + Clone + Send + 'static
+    {
+        CsrfMiddleware::new()
+            .exempt("/api/")
+            .layer(tower::service_fn(|req: Request| async move {
+                assert!(req.extensions().get::<CsrfToken>().is_some());
+                let body = req.into_body().into_bytes().await?;
+                Ok::<_, Error>(Response::new(Body::fixed(body)))
+            }))
+    }
+
+    async fn session_token(req: &Request) -> CsrfToken {
+        CsrfToken::from_session(Session::from_request(req))
+            .await
+            .unwrap()
+    }
+
+    #[cot::test]
+    async fn safe_method_passes() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+
+        let response = test_service().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn post_without_token_is_rejected() {
+        let request = TestRequestBuilder::post("/")
+            .with_session()
+            .form_data(&[("name", "value")])
+            .build();
+
+        let error = test_service().oneshot(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+        assert!(matches!(
+            error.inner().downcast_ref::<CsrfError>(),
+            Some(CsrfError::Missing)
+        ));
+    }
+
+    #[cot::test]
+    async fn post_with_invalid_token_is_rejected() {
+        let request = TestRequestBuilder::post("/")
+            .with_session()
+            .form_data(&[(CSRF_FIELD_NAME, "invalid")])
+            .build();
+
+        let error = test_service().oneshot(request).await.unwrap_err();
+
+        assert!(matches!(
+            error.inner().downcast_ref::<CsrfError>(),
+            Some(CsrfError::Invalid)
+        ));
+    }
+
+    #[cot::test]
+    async fn post_with_form_token_passes() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token(&request).await;
+        let request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .form_data(&[("name", "value"), (CSRF_FIELD_NAME, token.as_str())])
+            .build();
+
+        let response = test_service().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        // the body is still available to the handler
+        let body = response.into_body().into_bytes().await.unwrap();
+        assert!(body.starts_with(b"name=value&csrf_token="));
+    }
+
+    #[cot::test]
+    async fn post_with_too_large_body_is_rejected() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token(&request).await;
+        let request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .form_data(&[
+                ("name", "a".repeat(64).as_str()),
+                (CSRF_FIELD_NAME, token.as_str()),
+            ])
+            .build();
+        let service = CsrfMiddleware::new()
+            .max_body_size(32)
+            .layer(tower::service_fn(|_: Request| async {
+                Ok::<_, Error>(Response::new(Body::empty()))
+            }));
+
+        let error = service.oneshot(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[cot::test]
+    async fn post_with_multipart_token_passes() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token(&request).await;
+        let mut request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .build();
+        let body = format!(
+            "--boundary\r\n\
+            Content-Disposition: form-data; name=\"{CSRF_FIELD_NAME}\"\r\n\
+            \r\n\
+            {}\r\n\
+            --boundary--\r\n",
+            token.as_str()
+        );
+        *request.body_mut() = Body::fixed(body);
+        request.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("multipart/form-data; boundary=boundary"),
+        );
+
+        let response = test_service().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn post_with_header_token_passes() {
+        let request = TestRequestBuilder::get("/").with_session().build();
+        let token = session_token(&request).await;
+        let mut request = TestRequestBuilder::post("/")
+            .with_session_from(&request)
+            .build();
+        request.headers_mut().insert(
+            CSRF_HEADER_NAME,
+            http::HeaderValue::from_str(token.as_str()).unwrap(),
+        );
+
+        let response = test_service().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn exempt_path_passes() {
+        let request = TestRequestBuilder::post("/api/items")
+            .with_session()
+            .build();
+
+        let response = test_service().oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[cot::test]
+    async fn exempt_path_matches_whole_segments() {
+        let request = TestRequestBuilder::post("/apiary").with_session().build();
+
+        let error = test_service().oneshot(request).await.unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn path_under() {
+        assert!(is_path_under("/api", "/api/"));
+        assert!(is_path_under("/api/", "/api/"));
+        assert!(is_path_under("/api/items", "/api"));
+        assert!(is_path_under("/anything", "/"));
+        assert!(!is_path_under("/apiary", "/api"));
+        assert!(!is_path_under("/apiary", "/api/"));
+        assert!(!is_path_under("/", "/api"));
+    }
+}
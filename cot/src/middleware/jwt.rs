@@ -0,0 +1,419 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::future::BoxFuture;
+use http::header::AUTHORIZATION;
+use tower::Service;
+
+use crate::Error;
+use crate::auth::Auth;
+use crate::auth::jwt::{JwtAuth, JwtCodec, JwtError, JwtUser};
+use crate::project::MiddlewareContext;
+use crate::request::{Request, RequestExt};
+use crate::response::Response;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// A middleware that authenticates the requests using JSON Web Tokens.
+///
+/// If a request contains the `Authorization: Bearer <token>` header with a
+/// JSON Web Token, the token is validated with the [`JwtCodec`], and the user
+/// it has been issued for is looked up by its
+/// [subject](crate::auth::jwt::JwtClaims::user_id)
+/// with the [`AuthBackend`](crate::auth::AuthBackend). That user, wrapped in a
+/// [`JwtUser`], becomes the current user returned by the [`Auth`] extractor
+/// for the duration of that request only. No session is used in that case.
+/// The claims are also made available through the [`JwtAuth`] extractor.
+///
+/// Requests with an invalid or expired token, with a token issued for a user
+/// that doesn't exist or is inactive, or with a token issued for a user who
+/// has to verify a second authentication factor (see
+/// [`AuthBackend::requires_second_factor`](crate::auth::AuthBackend::requires_second_factor))
+/// are rejected with a `401 Unauthorized` error. Requests without a JSON Web
+/// Token are passed through unchanged, so that they can still be
+/// authenticated in other ways (for instance, with a session or with the
+/// [`ApiTokenMiddleware`](crate::middleware::ApiTokenMiddleware)).
+///
+/// This middleware should be applied *after* the
+/// [`AuthMiddleware`](crate::middleware::AuthMiddleware) (i.e. it should be
+/// added before the auth middleware in
+/// [`Project::middlewares`](crate::project::Project::middlewares)).
+///
+/// # Examples
+///
+/// ```
+/// use cot::Project;
+/// use cot::middleware::{AuthMiddleware, JwtMiddleware, SessionMiddleware};
+/// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+///
+/// struct MyProject;
+/// impl Project for MyProject {
+///     fn middlewares(
+///         &self,
+///         handler: RootHandlerBuilder,
+///         context: &MiddlewareContext,
+///     ) -> RootHandler {
+///         handler
+///             .middleware(JwtMiddleware::from_context(context))
+///             .middleware(AuthMiddleware::new())
+///             .middleware(SessionMiddleware::from_context(context))
+///             .build()
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwtMiddleware {
+    codec: Arc<JwtCodec>,
+}
+
+impl JwtMiddleware {
+    /// Create a new [`JwtMiddleware`] that validates the tokens with the
+    /// given codec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::jwt::JwtCodec;
+    /// use cot::config::ProjectConfig;
+    /// use cot::middleware::JwtMiddleware;
+    ///
+    /// let codec = JwtCodec::from_config(&ProjectConfig::default())?;
+    /// let middleware = JwtMiddleware::new(codec);
+    /// # Ok::<(), cot::Error>(())
+    /// ```
+    #[must_use]
+    pub fn new(codec: JwtCodec) -> Self {
+        Self {
+            codec: Arc::new(codec),
+        }
+    }
+
+    /// Create a new [`JwtMiddleware`] that validates the tokens according to
+    /// [`ProjectConfig::jwt`](crate::config::ProjectConfig::jwt).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys configured cannot be loaded. Use
+    /// [`JwtCodec::from_config`] together with [`JwtMiddleware::new`] to
+    /// handle this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::middleware::JwtMiddleware;
+    /// use cot::project::{MiddlewareContext, RootHandler, RootHandlerBuilder};
+    ///
+    /// fn middlewares(handler: RootHandlerBuilder, context: &MiddlewareContext) -> RootHandler {
+    ///     handler
+    ///         .middleware(JwtMiddleware::from_context(context))
+    ///         .build()
+    /// }
+    /// ```
+    #[must_use]
+    pub fn from_context(context: &MiddlewareContext) -> Self {
+        let codec = JwtCodec::from_config(context.config())
+            .expect("failed to load the JSON Web Token keys");
+        Self::new(codec)
+    }
+}
+
+impl<S> tower::Layer<S> for JwtMiddleware {
+    type Service = JwtService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        JwtService::new(inner, Arc::clone(&self.codec))
+    }
+}
+
+/// Service that authenticates the requests using JSON Web Tokens.
+///
+/// Used by [`JwtMiddleware`].
+#[derive(Debug, Clone)]
+pub struct JwtService<S> {
+    inner: S,
+    codec: Arc<JwtCodec>,
+}
+
+impl<S> JwtService<S> {
+    fn new(inner: S, codec: Arc<JwtCodec>) -> Self {
+        Self { inner, codec }
+    }
+}
+
+impl<S> Service<Request> for JwtService<S>
+where
+    S: Service<Request, Response = Response, Error = Error> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request) -> Self::Future {
+        // See `AuthService::call` for why the inner service is cloned here.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let codec = Arc::clone(&self.codec);
+
+        Box::pin(async move {
+            if let Some(token) = json_web_token(&req) {
+                let claims = codec.validate(token)?;
+                let backend = req.context().auth_backend();
+                let user = backend
+                    .get_by_id(claims.user_id())
+                    .await?
+                    .filter(|user| user.is_active())
+                    .ok_or(JwtError::UnknownUser)?;
+                if backend.requires_second_factor(&*user).await? {
+                    return Err(JwtError::SecondFactorRequired.into());
+                }
+                let user = JwtUser::new(claims, user);
+
+                let auth = Auth::stateless(
+                    Arc::clone(backend),
+                    Box::new(user.clone()),
+                    req.project_config().secret_key.clone(),
+                );
+                req.extensions_mut().insert(auth);
+                req.extensions_mut().insert(JwtAuth::new(user));
+            }
+
+            inner.call(req).await
+        })
+    }
+}
+
+/// Returns the JSON Web Token from the `Authorization` header, if any.
+///
+/// Bearer tokens that are not JSON Web Tokens (i.e. don't consist of three
+/// dot-separated parts) are ignored, so that they can be handled by other
+/// middlewares.
+fn json_web_token(req: &Request) -> Option<&str> {
+    let value = req.headers().get(AUTHORIZATION)?.to_str().ok()?;
+    let token = value.strip_prefix(BEARER_PREFIX)?.trim();
+
+    (token.split('.').count() == 3).then_some(token)
+}
+
+#[cfg(all(test, feature = "db"))]
+mod tests {
+    use http::HeaderValue;
+    use tower::{Layer, ServiceExt};
+
+    use super::*;
+    use crate::auth::User;
+    use crate::auth::db::{DatabaseUser, DatabaseUserBackend};
+    use crate::auth::jwt::JwtClaims;
+    use crate::config::{JwtConfig, SecretKey};
+    use crate::test::{TestDatabase, TestRequestBuilder};
+    use crate::{Body, StatusCode};
+
+    fn codec() -> JwtCodec {
+        JwtCodec::new(&JwtConfig::default(), &SecretKey::new(b"secret")).unwrap()
+    }
+
+    async fn test_db() -> (TestDatabase, DatabaseUser) {
+        let mut db = TestDatabase::new_sqlite().await.unwrap();
+        db.with_auth().run_migrations().await;
+        let user = DatabaseUser::create_user(&db.database(), "alice", "password123")
+            .await
+            .unwrap();
+        (db, user)
+    }
+
+    fn token_for(user: &DatabaseUser) -> String {
+        let mut claims = JwtClaims::new(user.id().to_string());
+        claims.username = Some(user.username().to_owned());
+        codec().issue(claims).unwrap()
+    }
+
+    async fn current_user(
+        backend: DatabaseUserBackend,
+        authorization: Option<&str>,
+    ) -> crate::Result<Option<(String, bool)>> {
+        let mut request = TestRequestBuilder::get("/")
+            .auth_backend(backend)
+            .with_session()
+            .build();
+        if let Some(authorization) = authorization {
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, HeaderValue::from_str(authorization).unwrap());
+        }
+
+        let service =
+            JwtMiddleware::new(codec()).layer(tower::service_fn(|req: Request| async move {
+                let jwt_auth = req.extensions().get::<JwtAuth>().cloned();
+                let user = req.extensions().get::<Auth>().map(Auth::user);
+                let username = user
+                    .as_ref()
+                    .and_then(|user| user.username().map(|name| name.to_string()));
+                assert_eq!(
+                    jwt_auth.and_then(|jwt_auth| jwt_auth.user().username().map(Into::into)),
+                    username
+                );
+                let is_superuser = user.is_some_and(|user| user.is_superuser());
+                Ok::<_, Error>(Response::new(Body::fixed(format!(
+                    "{}:{is_superuser}",
+                    username.unwrap_or_default()
+                ))))
+            }));
+
+        let response = service.oneshot(request).await?;
+        let body = response.into_body().into_bytes().await?;
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let (username, is_superuser) = body.split_once(':').unwrap();
+        Ok(Some((username.to_owned(), is_superuser == "true"))
+            .filter(|(username, _)| !username.is_empty()))
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_authenticates_user() {
+        let (db, user) = test_db().await;
+        let token = token_for(&user);
+
+        let current = current_user(
+            DatabaseUserBackend::new(db.database()),
+            Some(&format!("Bearer {token}")),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(current, Some(("alice".to_owned(), false)));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_forged_superuser_claim() {
+        let (db, user) = test_db().await;
+        let mut claims = JwtClaims::new(user.id().to_string());
+        claims.username = Some("admin".to_owned());
+        claims.is_superuser = true;
+        claims.is_staff = true;
+        let token = codec().issue(claims).unwrap();
+
+        let current = current_user(
+            DatabaseUserBackend::new(db.database()),
+            Some(&format!("Bearer {token}")),
+        )
+        .await
+        .unwrap();
+
+        // the user and their flags are taken from the backend, not the claims
+        assert_eq!(current, Some(("alice".to_owned(), false)));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_unknown_user() {
+        let (db, _) = test_db().await;
+        let token = codec().issue(JwtClaims::new("42")).unwrap();
+
+        let error = current_user(
+            DatabaseUserBackend::new(db.database()),
+            Some(&format!("Bearer {token}")),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+        assert!(matches!(
+            error.inner().downcast_ref::<JwtError>(),
+            Some(JwtError::UnknownUser)
+        ));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_second_factor_required() {
+        let (db, _) = test_db().await;
+        let admin = DatabaseUser::create_superuser(&db.database(), "admin", "password123")
+            .await
+            .unwrap();
+        let token = token_for(&admin);
+
+        let error = current_user(
+            DatabaseUserBackend::new(db.database()).require_second_factor_for_staff(true),
+            Some(&format!("Bearer {token}")),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(error.status_code(), StatusCode::UNAUTHORIZED);
+        assert!(matches!(
+            error.inner().downcast_ref::<JwtError>(),
+            Some(JwtError::SecondFactorRequired)
+        ));
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_invalid_token() {
+        let (db, user) = test_db().await;
+        let other_codec = JwtCodec::new(&JwtConfig::default(), &SecretKey::new(b"other")).unwrap();
+        let token = other_codec
+            .issue(JwtClaims::new(user.id().to_string()))
+            .unwrap();
+
+        assert!(
+            current_user(
+                DatabaseUserBackend::new(db.database()),
+                Some(&format!("Bearer {token}"))
+            )
+            .await
+            .is_err()
+        );
+        assert!(
+            current_user(
+                DatabaseUserBackend::new(db.database()),
+                Some("Bearer a.b.c")
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn jwt_no_token() {
+        let (db, _) = test_db().await;
+        let backend = || DatabaseUserBackend::new(db.database());
+
+        assert_eq!(current_user(backend(), None).await.unwrap(), None);
+        assert_eq!(
+            current_user(backend(), Some("Bearer 0123abcd"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            current_user(backend(), Some("Basic dXNlcjpwYXNz"))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
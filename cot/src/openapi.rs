@@ -841,6 +841,7 @@ impl ApiOperationPart for Urls {}
 impl ApiOperationPart for Method {}
 impl ApiOperationPart for Session {}
 impl ApiOperationPart for Auth {}
+impl ApiOperationPart for crate::auth::guard::RequireUser {}
 #[cfg(feature = "db")]
 impl ApiOperationPart for crate::db::Database {}
 
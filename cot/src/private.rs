@@ -10,6 +10,8 @@
 pub use aide::openapi::{Operation, RequestBody, Response as OpenApiResponse, StatusCode};
 pub use async_trait::async_trait;
 pub use bytes::Bytes;
+// used in the CLI
+pub use chrono;
 pub use cot_macros::ModelHelper;
 pub use tokio;
 
@@ -21,6 +21,7 @@
 //! }
 //! ```
 use std::future::poll_fn;
+use std::net::SocketAddr;
 use std::panic::AssertUnwindSafe;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -39,6 +40,7 @@ use tower::{Layer, Service};
 use tracing::{error, info, trace};
 
 use crate::admin::AdminModelManager;
+use crate::auth::basic::BasicAuthBackend;
 #[cfg(feature = "db")]
 use crate::auth::db::DatabaseUserBackend;
 use crate::auth::{AuthBackend, NoAuthBackend};
@@ -54,6 +56,8 @@ use crate::config::{AuthBackendConfig, ProjectConfig};
 use crate::db::Database;
 #[cfg(feature = "db")]
 use crate::db::migrations::{MigrationEngine, SyncDynMigration};
+#[cfg(feature = "db")]
+use crate::db::query_log::QueryRecorder;
 #[cfg(feature = "email")]
 use crate::email::Email;
 use crate::error::UncaughtPanic;
@@ -346,16 +350,32 @@ pub trait Project {
         match &context.config().auth_backend {
             AuthBackendConfig::None => Arc::new(NoAuthBackend) as Arc<dyn AuthBackend>,
             #[cfg(feature = "db")]
-            AuthBackendConfig::Database => Arc::new(DatabaseUserBackend::new(
-                context
-                    .try_database()
-                    .expect(
-                        "Database missing when constructing database auth backend. \
-                        Make sure the database config is set up correctly or disable \
-                        authentication in the config.",
-                    )
-                    .clone(),
-            )) as Arc<dyn AuthBackend>,
+            AuthBackendConfig::Database => Arc::new(
+                DatabaseUserBackend::new(
+                    context
+                        .try_database()
+                        .expect(
+                            "Database missing when constructing database auth backend. \
+                            Make sure the database config is set up correctly or disable \
+                            authentication in the config.",
+                        )
+                        .clone(),
+                )
+                .require_second_factor_for_staff(context.config().two_factor.required_for_staff)
+                .require_verified_email(context.config().email_verification.required_for_login),
+            ) as Arc<dyn AuthBackend>,
+            AuthBackendConfig::Basic => {
+                let backend = BasicAuthBackend::from_config(&context.config().basic_auth).expect(
+                    "The HTTP Basic authentication config should have been validated when \
+                    booting the project",
+                );
+                #[cfg(feature = "db")]
+                let backend = match context.try_database() {
+                    Some(database) => backend.database(database.clone()),
+                    None => backend,
+                };
+                Arc::new(backend) as Arc<dyn AuthBackend>
+            }
         }
     }
 
@@ -805,10 +825,15 @@ async fn default_error_handler(error: RequestOuterError) -> crate::Result<impl I
     #[template(path = "default_error.html")]
     struct ErrorTemplate {
         error: RequestOuterError,
+        is_csrf_error: bool,
     }
 
     let status_code = error.status_code();
-    let error_template = ErrorTemplate { error };
+    let is_csrf_error = error.inner().is::<crate::csrf::CsrfError>();
+    let error_template = ErrorTemplate {
+        error,
+        is_csrf_error,
+    };
     let rendered = error_template.render()?;
 
     Ok(Html::new(rendered).with_status(status_code))
@@ -941,6 +966,7 @@ impl Bootstrapper<Uninitialized> {
 
         let common_options = cli.common_options();
         let self_with_context = self.with_config_name(common_options.config())?;
+        cli.register_config_tasks(self_with_context.context().config());
 
         cli.execute(self_with_context).await
     }
@@ -1050,6 +1076,11 @@ struct LoadConfig {
 }
 impl_into_cot_error!(LoadConfig);
 
+#[derive(Debug, Error)]
+#[error("invalid password hash in the HTTP Basic authentication config: {0}")]
+struct InvalidBasicAuthConfig(crate::auth::AuthError);
+impl_into_cot_error!(InvalidBasicAuthConfig);
+
 impl Bootstrapper<WithConfig> {
     /// Builds the initialized Cot project instance.
     ///
@@ -1224,8 +1255,8 @@ impl Bootstrapper<WithApps> {
     #[cfg(feature = "db")]
     async fn init_database(config: &DatabaseConfig) -> cot::Result<Option<Database>> {
         match &config.url {
-            Some(url) => {
-                let database = Database::new(url.as_str()).await?;
+            Some(_) => {
+                let database = Database::from_config(config).await?;
                 Ok(Some(database))
             }
             None => Ok(None),
@@ -1349,7 +1380,9 @@ impl Bootstrapper<WithCache> {
     /// # Errors
     ///
     /// This method may return an error if it cannot initialize any of the
-    /// project's components, such as the database.
+    /// project's components, such as the database, or if the password hashes
+    /// in the [HTTP Basic authentication
+    /// config](crate::config::ProjectConfig::basic_auth) are invalid.
     ///
     /// # Examples
     ///
@@ -1375,6 +1408,11 @@ impl Bootstrapper<WithCache> {
         reason = "for consistency with other Bootstrapper::boot methods"
     )]
     pub async fn boot(self) -> cot::Result<Bootstrapper<Initialized>> {
+        if matches!(self.context.config.auth_backend, AuthBackendConfig::Basic) {
+            BasicAuthBackend::from_config(&self.context.config.basic_auth)
+                .map_err(InvalidBasicAuthConfig)?;
+        }
+
         let router_service = RouterService::new(Arc::clone(&self.context.router));
         let handler_builder = RootHandlerBuilder {
             handler: router_service,
@@ -2105,12 +2143,7 @@ pub async fn run_at_with_shutdown(
 
     #[cfg(feature = "db")]
     if let Some(database) = &context.database {
-        let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
-        for app in &context.apps {
-            migrations.extend(app.migrations());
-        }
-        let migration_engine = MigrationEngine::new(migrations)?;
-        migration_engine.run(database).await?;
+        migrate_database(database, &context.apps).await?;
     }
 
     let mut apps = std::mem::take(&mut context.apps);
@@ -2137,9 +2170,14 @@ pub async fn run_at_with_shutdown(
 
         let (request_head, request) = request_parts_for_diagnostics(request);
 
-        let catch_unwind_response = AssertUnwindSafe(pass_to_axum(request, &mut handler))
-            .catch_unwind()
-            .await;
+        // queries are only collected to be displayed on the debug error page
+        #[cfg(feature = "db")]
+        let query_recorder = QueryRecorder::new(is_debug);
+        let handler_future = pass_to_axum(request, &mut handler);
+        #[cfg(feature = "db")]
+        let handler_future = query_recorder.scope(handler_future);
+
+        let catch_unwind_response = AssertUnwindSafe(handler_future).catch_unwind().await;
 
         let response: Result<axum::response::Response, ErrorResponse> = match catch_unwind_response
         {
@@ -2152,11 +2190,14 @@ pub async fn run_at_with_shutdown(
             Ok(response) => response,
             Err(error_response) => {
                 if is_debug && accepts_html(request_head.as_ref()) {
-                    let diagnostics = Diagnostics::new(
+                    #[cfg_attr(not(feature = "db"), expect(unused_mut))]
+                    let mut diagnostics = Diagnostics::new(
                         context.config().clone(),
                         Arc::clone(&context.router),
                         request_head,
                     );
+                    #[cfg(feature = "db")]
+                    diagnostics.set_executed_queries(query_recorder.queries());
 
                     build_cot_error_page(error_response, &diagnostics)
                 } else {
@@ -2184,10 +2225,13 @@ pub async fn run_at_with_shutdown(
         };
         std::panic::set_hook(Box::new(new_hook));
     }
-    axum::serve(listener, handler.into_make_service())
-        .with_graceful_shutdown(shutdown_signal)
-        .await
-        .map_err(StartServerError)?;
+    axum::serve(
+        listener,
+        handler.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal)
+    .await
+    .map_err(StartServerError)?;
     if register_panic_hook {
         let _ = std::panic::take_hook();
     }
@@ -2210,15 +2254,20 @@ impl From<StartServerError> for Error {
 }
 
 fn accepts_html(head: Option<&RequestHead>) -> bool {
-    head.and_then(|p| p.headers.get(http::header::ACCEPT))
-        .is_some_and(|accept| {
-            let value = accept.to_str().unwrap_or_default();
-            let accept = AcceptHeaderParser::parse(value);
-            // we check if the "Accept" header contains "text/html" explicitly
-            // we ignore wildcards, such as "*/*", because they are
-            // sent by tools like curl as well
-            accept.contains_explicit(&mime::TEXT_HTML)
-        })
+    head.is_some_and(|head| headers_accept_html(&head.headers))
+}
+
+/// Returns whether the `Accept` header explicitly contains `text/html`, which
+/// means that the request has most likely been sent by a web browser.
+pub(crate) fn headers_accept_html(headers: &http::HeaderMap) -> bool {
+    headers.get(http::header::ACCEPT).is_some_and(|accept| {
+        let value = accept.to_str().unwrap_or_default();
+        let accept = AcceptHeaderParser::parse(value);
+        // we check if the "Accept" header contains "text/html" explicitly
+        // we ignore wildcards, such as "*/*", because they are
+        // sent by tools like curl as well
+        accept.contains_explicit(&mime::TEXT_HTML)
+    })
 }
 
 enum ErrorResponse {
@@ -2312,6 +2361,57 @@ pub async fn run_cli(project: impl Project + Send + 'static) -> cot::Result<()>
     Bootstrapper::new(project).run_cli().await
 }
 
+/// Applies the migrations of all the apps to the database and registers the
+/// permissions of the models that are shown in the admin panel.
+#[cfg(feature = "db")]
+pub(crate) async fn migrate_database(
+    database: &Database,
+    apps: &[Box<dyn App>],
+) -> cot::Result<()> {
+    let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
+    for app in apps {
+        migrations.extend(app.migrations());
+    }
+    let migration_engine = MigrationEngine::new(migrations)?;
+    migration_engine.run(database).await?;
+
+    crate::auth::db::register_admin_permissions(database, apps).await?;
+
+    Ok(())
+}
+
+/// Returns an error if any of the migrations of the apps haven't been applied
+/// to the database yet.
+///
+/// This is used by the CLI tasks that access the database, which shouldn't
+/// change its schema as a side effect.
+#[cfg(feature = "db")]
+pub(crate) async fn ensure_database_migrated(
+    database: &Database,
+    apps: &[Box<dyn App>],
+) -> cot::Result<()> {
+    let mut migrations: Vec<Box<SyncDynMigration>> = Vec::new();
+    for app in apps {
+        migrations.extend(app.migrations());
+    }
+    let migration_engine = MigrationEngine::new(migrations)?;
+    if migration_engine.is_up_to_date(database).await? {
+        Ok(())
+    } else {
+        Err(PendingMigrations.into())
+    }
+}
+
+#[cfg(feature = "db")]
+#[derive(Debug, Error)]
+#[error(
+    "the database has pending migrations; run the `{}` command first to apply them",
+    cli::MIGRATE_SUBCOMMAND
+)]
+struct PendingMigrations;
+#[cfg(feature = "db")]
+impl_into_cot_error!(PendingMigrations);
+
 fn request_parts_for_diagnostics(request: Request) -> (Option<RequestHead>, Request) {
     if request.project_config().debug {
         let (head, body) = request.into_parts();
@@ -2614,6 +2714,33 @@ mod tests {
         assert_eq!(bootstrapper.context().router.routes().len(), 1);
     }
 
+    #[cot::test]
+    async fn bootstrapper_invalid_basic_auth_config() {
+        struct TestProject;
+        impl Project for TestProject {}
+
+        let config = ProjectConfig::from_toml(
+            r#"
+            auth_backend = { type = "basic" }
+
+            [basic_auth.users]
+            deploy = "not a password hash"
+            "#,
+        )
+        .unwrap();
+        let result = Bootstrapper::new(TestProject)
+            .with_config(config)
+            .boot()
+            .await;
+
+        let error = result.err().unwrap();
+        assert!(
+            error
+                .to_string()
+                .contains("invalid password hash in the HTTP Basic authentication config")
+        );
+    }
+
     #[cot::test]
     async fn build_custom_error_page_poll_ready_failure() {
         #[derive(Clone)]
@@ -2739,4 +2866,23 @@ mod tests {
         let request_error = head.extensions.get::<RequestOuterError>().unwrap();
         assert_eq!(request_error.to_string(), "test error");
     }
+
+    #[cfg(feature = "db")]
+    #[cot::test]
+    #[cfg_attr(
+        miri,
+        ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2` on OS `linux`"
+    )]
+    async fn ensure_database_migrated_pending() {
+        let database = Database::new("sqlite::memory:").await.unwrap();
+        let apps: Vec<Box<dyn App>> = vec![Box::new(crate::auth::db::DatabaseUserApp::new())];
+
+        let error = ensure_database_migrated(&database, &apps)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("pending migrations"));
+
+        migrate_database(&database, &apps).await.unwrap();
+        ensure_database_migrated(&database, &apps).await.unwrap();
+    }
 }
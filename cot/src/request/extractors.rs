@@ -73,6 +73,12 @@ pub use cot_core::request::extractors::{Path, UrlQuery};
 
 use crate::Body;
 use crate::auth::Auth;
+#[cfg(feature = "db")]
+use crate::auth::api_token::{ApiToken, ApiTokenError};
+use crate::auth::guard::RequireUser;
+#[cfg(feature = "jwt")]
+use crate::auth::jwt::{JwtAuth, JwtError};
+use crate::csrf::{CsrfMiddlewareNotEnabled, CsrfToken};
 use crate::form::{Form, FormResult};
 use crate::request::{Request, RequestExt, RequestHead};
 use crate::router::Urls;
@@ -288,6 +294,80 @@ impl FromRequestHead for Auth {
     }
 }
 
+/// Extracts the currently logged-in user.
+///
+/// # Errors
+///
+/// Returns a `401 Unauthorized` error if the user is anonymous or inactive.
+impl FromRequestHead for RequireUser {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let auth = Auth::from_request_head(head).await?;
+
+        Ok(RequireUser::from_auth(&auth)?)
+    }
+}
+
+/// Extracts the [`CsrfToken`] of the current session.
+///
+/// # Errors
+///
+/// Returns a `500 Internal Server Error` error
+/// ([`CsrfMiddlewareNotEnabled`]) if the
+/// [`CsrfMiddleware`](crate::middleware::CsrfMiddleware) is not enabled for
+/// the route or the project.
+impl FromRequestHead for CsrfToken {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let token = head
+            .extensions
+            .get::<CsrfToken>()
+            .cloned()
+            .ok_or(CsrfMiddlewareNotEnabled)?;
+
+        Ok(token)
+    }
+}
+
+/// Extracts the [`ApiToken`] the request has been authenticated with by the
+/// [`ApiTokenMiddleware`](crate::middleware::ApiTokenMiddleware).
+///
+/// # Errors
+///
+/// Returns a `401 Unauthorized` error if the request has not been
+/// authenticated with an API token.
+#[cfg(feature = "db")]
+impl FromRequestHead for ApiToken {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let token = head
+            .extensions
+            .get::<ApiToken>()
+            .cloned()
+            .ok_or(ApiTokenError::Missing)?;
+
+        Ok(token)
+    }
+}
+
+/// Extracts the claims of the JSON Web Token the request has been
+/// authenticated with by the
+/// [`JwtMiddleware`](crate::middleware::JwtMiddleware).
+///
+/// # Errors
+///
+/// Returns a `401 Unauthorized` error if the request has not been
+/// authenticated with a JSON Web Token.
+#[cfg(feature = "jwt")]
+impl FromRequestHead for JwtAuth {
+    async fn from_request_head(head: &RequestHead) -> cot::Result<Self> {
+        let jwt_auth = head
+            .extensions
+            .get::<JwtAuth>()
+            .cloned()
+            .ok_or(JwtError::Missing)?;
+
+        Ok(jwt_auth)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use cot_core::Method;
@@ -326,6 +406,20 @@ mod tests {
 
         assert_eq!(method, Method::GET);
     }
+
+    #[cot::test]
+    async fn csrf_token_extraction_without_middleware() {
+        let mut request = TestRequestBuilder::get("/").build();
+
+        let error = request.extract_from_head::<CsrfToken>().await.unwrap_err();
+
+        assert_eq!(
+            error.status_code(),
+            cot_core::StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert!(error.inner().is::<CsrfMiddlewareNotEnabled>());
+    }
+
     #[cot::test]
     async fn request_form() {
         #[derive(Debug, PartialEq, Eq, Form)]
@@ -122,6 +122,27 @@ impl Router {
         self.app_name = Some(app_name);
     }
 
+    /// Wraps all the handlers of this router, including the ones in the nested
+    /// routers, with the given wrapper.
+    pub(crate) fn wrap_handlers<W: HandlerWrapper>(mut self, wrapper: &W) -> Self {
+        for route in &mut self.urls {
+            route.view = match &route.view {
+                RouteInner::Handler(handler) => {
+                    RouteInner::Handler(wrapper.wrap(Arc::clone(handler)))
+                }
+                RouteInner::Router(router) => {
+                    RouteInner::Router(router.clone().wrap_handlers(wrapper))
+                }
+                #[cfg(feature = "openapi")]
+                RouteInner::ApiHandler(handler) => {
+                    RouteInner::ApiHandler(wrapper.wrap_api(Arc::clone(handler)))
+                }
+            };
+        }
+
+        self
+    }
+
     async fn route(&self, mut request: Request, request_path: &str) -> Result<Response> {
         debug!("Routing request to {}", request_path);
 
@@ -772,6 +793,21 @@ impl Route {
     }
 }
 
+/// A wrapper that can be applied to all the handlers of a router with
+/// [`Router::wrap_handlers`].
+pub(crate) trait HandlerWrapper {
+    fn wrap(
+        &self,
+        handler: Arc<dyn BoxRequestHandler + Send + Sync>,
+    ) -> Arc<dyn BoxRequestHandler + Send + Sync>;
+
+    #[cfg(feature = "openapi")]
+    fn wrap_api(
+        &self,
+        handler: Arc<dyn crate::openapi::BoxApiEndpointRequestHandler + Send + Sync>,
+    ) -> Arc<dyn crate::openapi::BoxApiEndpointRequestHandler + Send + Sync>;
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub(crate) enum RouteKind {
     Handler,
@@ -33,6 +33,27 @@ pub(crate) mod humantime {
     }
 }
 
+pub(crate) mod humantime_required {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&humantime::format_duration(*duration).to_string())
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        humantime::parse_duration(&value).map_err(serde::de::Error::custom)
+    }
+}
+
 pub(crate) mod session_expiry_time {
     use chrono::DateTime;
     use serde::{Deserialize, Deserializer, Serializer};
@@ -117,6 +138,27 @@ pub(crate) mod cache_timeout {
     }
 }
 
+pub(crate) mod time_zone {
+    use chrono_tz::Tz;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[expect(clippy::trivially_copy_pass_by_ref, reason = "&Tz needed for serde")]
+    pub(crate) fn serialize<S>(time_zone: &Tz, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(time_zone.name())
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Tz, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -1,6 +1,7 @@
 //! Test utilities for Cot projects.
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::future::poll_fn;
 use std::marker::PhantomData;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
@@ -273,6 +274,28 @@ impl AuthBackend for AuthBackendWrapper {
     ) -> cot::auth::Result<Option<Box<dyn User + Send + Sync>>> {
         self.inner.get_by_id(id).await
     }
+
+    async fn requires_second_factor(
+        &self,
+        user: &(dyn User + Send + Sync),
+    ) -> cot::auth::Result<bool> {
+        self.inner.requires_second_factor(user).await
+    }
+
+    async fn get_permissions(
+        &self,
+        user: &(dyn User + Send + Sync),
+    ) -> cot::auth::Result<HashSet<String>> {
+        self.inner.get_permissions(user).await
+    }
+
+    fn login_identifier(&self, credentials: &(dyn Any + Send + Sync)) -> Option<String> {
+        self.inner.login_identifier(credentials)
+    }
+
+    async fn user_logged_in(&self, user: &(dyn User + Send + Sync)) -> cot::auth::Result<()> {
+        self.inner.user_logged_in(user).await
+    }
 }
 
 impl Default for TestRequestBuilder {
@@ -601,6 +624,50 @@ impl TestRequestBuilder {
         self
     }
 
+    /// Use a specific email sender in the test request.
+    ///
+    /// By default, the emails are printed to the console using the
+    /// [`Console`] transport.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::email::Email;
+    /// use cot::email::transport::console::Console;
+    /// use cot::test::TestRequestBuilder;
+    ///
+    /// let request = TestRequestBuilder::get("/")
+    ///     .email(Email::new(Console::new()))
+    ///     .build();
+    /// ```
+    #[cfg(feature = "email")]
+    pub fn email(&mut self, email: Email) -> &mut Self {
+        self.email = Some(email);
+        self
+    }
+
+    /// Use a specific cache in the test request.
+    ///
+    /// By default, a new in-memory cache is created for every request, so
+    /// this is useful when the state stored in the cache (such as the failed
+    /// login attempts) has to be shared between several requests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{TestCache, TestRequestBuilder};
+    ///
+    /// let test_cache = TestCache::new_memory();
+    /// let request = TestRequestBuilder::get("/")
+    ///     .cache(test_cache.cache())
+    ///     .build();
+    /// ```
+    #[cfg(feature = "cache")]
+    pub fn cache(&mut self, cache: Cache) -> &mut Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Use database authentication in the test request.
     ///
     /// Note that this calls [`Self::auth_backend`], [`Self::with_session`],
@@ -1580,6 +1647,557 @@ impl<T: Project + Send + 'static> TestServer<T> {
     }
 }
 
+/// A user of the [`MockOAuthProvider`].
+///
+/// # Examples
+///
+/// ```
+/// use cot::test::MockOAuthUser;
+///
+/// let user = MockOAuthUser::new("user-1")
+///     .email("alice@example.com")
+///     .preferred_username("alice");
+/// ```
+#[cfg(feature = "oauth")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockOAuthUser {
+    subject: String,
+    email: Option<String>,
+    email_verified: bool,
+    name: Option<String>,
+    preferred_username: Option<String>,
+}
+
+#[cfg(feature = "oauth")]
+impl MockOAuthUser {
+    /// Creates a new user with given subject (the unique identifier of the
+    /// user at the provider).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockOAuthUser;
+    ///
+    /// let user = MockOAuthUser::new("user-1");
+    /// ```
+    #[must_use]
+    pub fn new<T: Into<String>>(subject: T) -> Self {
+        Self {
+            subject: subject.into(),
+            email: None,
+            email_verified: true,
+            name: None,
+            preferred_username: None,
+        }
+    }
+
+    /// Sets the email address of the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockOAuthUser;
+    ///
+    /// let user = MockOAuthUser::new("user-1").email("alice@example.com");
+    /// ```
+    #[must_use]
+    pub fn email<T: Into<String>>(mut self, email: T) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Sets whether the email address of the user is verified. The default
+    /// is `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockOAuthUser;
+    ///
+    /// let user = MockOAuthUser::new("user-1")
+    ///     .email("alice@example.com")
+    ///     .email_verified(false);
+    /// ```
+    #[must_use]
+    pub fn email_verified(mut self, email_verified: bool) -> Self {
+        self.email_verified = email_verified;
+        self
+    }
+
+    /// Sets the full name of the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockOAuthUser;
+    ///
+    /// let user = MockOAuthUser::new("user-1").name("Alice Smith");
+    /// ```
+    #[must_use]
+    pub fn name<T: Into<String>>(mut self, name: T) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the preferred username of the user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::MockOAuthUser;
+    ///
+    /// let user = MockOAuthUser::new("user-1").preferred_username("alice");
+    /// ```
+    #[must_use]
+    pub fn preferred_username<T: Into<String>>(mut self, preferred_username: T) -> Self {
+        self.preferred_username = Some(preferred_username.into());
+        self
+    }
+
+    fn claims(&self) -> serde_json::Map<String, serde_json::Value> {
+        let mut claims = serde_json::Map::new();
+        claims.insert("sub".to_owned(), self.subject.clone().into());
+        if let Some(email) = &self.email {
+            claims.insert("email".to_owned(), email.clone().into());
+            claims.insert("email_verified".to_owned(), self.email_verified.into());
+        }
+        if let Some(name) = &self.name {
+            claims.insert("name".to_owned(), name.clone().into());
+        }
+        if let Some(preferred_username) = &self.preferred_username {
+            claims.insert(
+                "preferred_username".to_owned(),
+                preferred_username.clone().into(),
+            );
+        }
+        claims
+    }
+}
+
+/// A local OAuth 2.0 / OpenID Connect identity provider for testing the
+/// [`OAuthApp`](crate::auth::oauth::OAuthApp) and the
+/// [`OAuthClient`](crate::auth::oauth::OAuthClient).
+///
+/// The provider runs an HTTP server in a background task and implements the
+/// authorization code flow with PKCE for a single client and a single
+/// [`MockOAuthUser`], who automatically authorizes every request. It provides
+/// the following endpoints:
+///
+/// * `/authorize` – redirects back to the client with an authorization code,
+/// * `/token` – exchanges the code for tokens, checking the client credentials,
+///   the redirect URI and the PKCE code verifier. The ID token is signed with
+///   the `HS256` algorithm using the client secret,
+/// * `/userinfo` – returns the claims of the user.
+///
+/// Use [`provider_config`](Self::provider_config) to get the configuration
+/// that should be put in [`OAuthConfig`](crate::config::OAuthConfig).
+///
+/// # Examples
+///
+/// ```
+/// use cot::auth::oauth::OAuthClient;
+/// use cot::test::{MockOAuthProvider, MockOAuthUser};
+///
+/// #[cot::test]
+/// async fn test_oauth() -> cot::Result<()> {
+///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+///     let client = OAuthClient::new("mock", provider.provider_config())?;
+///
+///     let request = client.authorization_request("http://localhost/callback/")?;
+///     // the user authorizes the client
+///     let callback_url = provider.authorize(&request.url);
+///     assert!(callback_url.starts_with("http://localhost/callback/?code="));
+///
+///     provider.close().await;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "oauth")]
+#[must_use = "MockOAuthProvider must be used to close the server"]
+#[derive(Debug)]
+pub struct MockOAuthProvider {
+    address: SocketAddr,
+    state: Arc<MockOAuthState>,
+    channel_send: oneshot::Sender<()>,
+    server_handle: tokio::task::JoinHandle<()>,
+}
+
+#[cfg(feature = "oauth")]
+#[derive(Debug)]
+struct MockOAuthState {
+    issuer: String,
+    user: MockOAuthUser,
+    codes: std::sync::Mutex<std::collections::HashMap<String, MockAuthorizationCode>>,
+    access_tokens: std::sync::Mutex<HashSet<String>>,
+}
+
+#[cfg(feature = "oauth")]
+#[derive(Debug)]
+struct MockAuthorizationCode {
+    redirect_uri: String,
+    code_challenge: String,
+    nonce: Option<String>,
+}
+
+#[cfg(feature = "oauth")]
+impl MockOAuthProvider {
+    /// The client ID the provider accepts.
+    pub const CLIENT_ID: &'static str = "cot-test-client";
+    /// The client secret the provider accepts.
+    pub const CLIENT_SECRET: &'static str = "cot-test-client-secret";
+
+    /// Starts the provider in a background task.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if it fails to bind to a port.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{MockOAuthProvider, MockOAuthUser};
+    ///
+    /// #[cot::test]
+    /// async fn test_oauth() {
+    ///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+    ///     // ...
+    ///     provider.close().await;
+    /// }
+    /// ```
+    pub async fn start(user: MockOAuthUser) -> Self {
+        use axum::handler::HandlerWithoutStateExt;
+
+        let tcp_listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .expect("Failed to bind to a port");
+        let address = tcp_listener
+            .local_addr()
+            .expect("Failed to get the listening address");
+        let state = Arc::new(MockOAuthState {
+            issuer: format!("http://{address}"),
+            user,
+            codes: std::sync::Mutex::default(),
+            access_tokens: std::sync::Mutex::default(),
+        });
+
+        let (send, recv) = oneshot::channel::<()>();
+        let handler_state = Arc::clone(&state);
+        let handler = move |request: axum::extract::Request| {
+            let state = Arc::clone(&handler_state);
+            async move { state.handle(request).await }
+        };
+        let server_handle = tokio::spawn(async move {
+            axum::serve(tcp_listener, handler.into_make_service())
+                .with_graceful_shutdown(async move {
+                    recv.await.expect("Failed to receive a shutdown signal");
+                })
+                .await
+                .expect("Failed to run the mock OAuth provider");
+        });
+
+        Self {
+            address,
+            state,
+            channel_send: send,
+            server_handle,
+        }
+    }
+
+    /// Returns the URL of the provider, which is also the issuer of the ID
+    /// tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{MockOAuthProvider, MockOAuthUser};
+    ///
+    /// #[cot::test]
+    /// async fn test_oauth() {
+    ///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+    ///     assert!(provider.url().starts_with("http://127.0.0.1:"));
+    ///     provider.close().await;
+    /// }
+    /// ```
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Returns the configuration of the provider, to be used in
+    /// [`OAuthConfig`](crate::config::OAuthConfig).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::config::{OAuthConfig, ProjectConfig};
+    /// use cot::test::{MockOAuthProvider, MockOAuthUser};
+    ///
+    /// #[cot::test]
+    /// async fn test_oauth() {
+    ///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+    ///     let config = ProjectConfig::builder()
+    ///         .oauth(
+    ///             OAuthConfig::builder()
+    ///                 .provider("mock", provider.provider_config())
+    ///                 .build(),
+    ///         )
+    ///         .build();
+    ///     // ...
+    ///     provider.close().await;
+    /// }
+    /// ```
+    #[must_use]
+    pub fn provider_config(&self) -> crate::config::OAuthProviderConfig {
+        let url = self.url();
+        crate::config::OAuthProviderConfig::builder()
+            .client_id(Self::CLIENT_ID)
+            .client_secret(crate::config::SecretKey::from(Self::CLIENT_SECRET))
+            .authorization_endpoint(format!("{url}/authorize"))
+            .token_endpoint(format!("{url}/token"))
+            .userinfo_endpoint(format!("{url}/userinfo"))
+            .issuer(url)
+            .id_token_algorithms(vec![crate::config::JwtAlgorithm::Hs256])
+            .build()
+    }
+
+    /// Simulates the user authorizing the client: takes the URL the user is
+    /// redirected to (as created by
+    /// [`OAuthClient::authorization_request`](crate::auth::oauth::OAuthClient::authorization_request))
+    /// and returns the URL the provider redirects the user back to, with the
+    /// authorization code (or an error) in the query string.
+    ///
+    /// This is equivalent to sending a request to the `/authorize` endpoint
+    /// and reading the `Location` header of the response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the URL is not a valid authorization URL of this provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::auth::oauth::OAuthClient;
+    /// use cot::test::{MockOAuthProvider, MockOAuthUser};
+    ///
+    /// #[cot::test]
+    /// async fn test_oauth() -> cot::Result<()> {
+    ///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+    ///     let client = OAuthClient::new("mock", provider.provider_config())?;
+    ///
+    ///     let request = client.authorization_request("http://localhost/callback/")?;
+    ///     let callback_url = provider.authorize(&request.url);
+    ///
+    ///     provider.close().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn authorize(&self, authorization_url: &str) -> String {
+        let url = url::Url::parse(authorization_url).expect("Invalid authorization URL");
+        assert_eq!(
+            format!("{}{}", url.origin().ascii_serialization(), url.path()),
+            format!("{}/authorize", self.url()),
+            "The URL does not point to the mock provider's authorization endpoint"
+        );
+
+        self.state
+            .authorize(url.query().unwrap_or_default())
+            .expect("Invalid authorization request")
+    }
+
+    /// Stops the provider.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if an error occurs while sending the shutdown
+    /// signal or if the server task panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cot::test::{MockOAuthProvider, MockOAuthUser};
+    ///
+    /// #[cot::test]
+    /// async fn test_oauth() {
+    ///     let provider = MockOAuthProvider::start(MockOAuthUser::new("user-1")).await;
+    ///     provider.close().await;
+    /// }
+    /// ```
+    pub async fn close(self) {
+        self.channel_send
+            .send(())
+            .expect("Failed to send a shutdown signal");
+        self.server_handle
+            .await
+            .expect("Failed to join the mock OAuth provider task");
+    }
+}
+
+#[cfg(feature = "oauth")]
+impl MockOAuthState {
+    async fn handle(&self, request: axum::extract::Request) -> axum::response::Response {
+        use http_body_util::BodyExt;
+
+        let (parts, body) = request.into_parts();
+        let result = match (&parts.method, parts.uri.path()) {
+            (&http::Method::GET, "/authorize") => self
+                .authorize(parts.uri.query().unwrap_or_default())
+                .map(|location| {
+                    http::Response::builder()
+                        .status(http::StatusCode::FOUND)
+                        .header(http::header::LOCATION, location)
+                        .body(axum::body::Body::empty())
+                        .expect("Failed to build the response")
+                }),
+            (&http::Method::POST, "/token") => {
+                let body = body
+                    .collect()
+                    .await
+                    .map(http_body_util::Collected::to_bytes)
+                    .unwrap_or_default();
+                self.token(&body).map(|tokens| mock_json_response(&tokens))
+            }
+            (&http::Method::GET, "/userinfo") => {
+                let access_token = parts
+                    .headers
+                    .get(http::header::AUTHORIZATION)
+                    .and_then(|header| header.to_str().ok())
+                    .and_then(|header| header.strip_prefix("Bearer "))
+                    .unwrap_or_default();
+                if self
+                    .access_tokens
+                    .lock()
+                    .expect("Failed to lock the access tokens")
+                    .contains(access_token)
+                {
+                    Ok(mock_json_response(&self.user.claims().into()))
+                } else {
+                    Err((http::StatusCode::UNAUTHORIZED, "invalid_token"))
+                }
+            }
+            _ => Err((http::StatusCode::NOT_FOUND, "not_found")),
+        };
+
+        result.unwrap_or_else(|(status, error)| {
+            let mut response = mock_json_response(&serde_json::json!({ "error": error }));
+            *response.status_mut() = status;
+            response
+        })
+    }
+
+    fn authorize(
+        &self,
+        query: &str,
+    ) -> std::result::Result<String, (http::StatusCode, &'static str)> {
+        let params: std::collections::HashMap<String, String> =
+            form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect();
+        let param = |name: &str| params.get(name).map(String::as_str);
+
+        let (Some(redirect_uri), Some(code_challenge)) =
+            (param("redirect_uri"), param("code_challenge"))
+        else {
+            return Err((http::StatusCode::BAD_REQUEST, "invalid_request"));
+        };
+        if param("client_id") != Some(MockOAuthProvider::CLIENT_ID) {
+            return Err((http::StatusCode::BAD_REQUEST, "unauthorized_client"));
+        }
+        if param("response_type") != Some("code") || param("code_challenge_method") != Some("S256")
+        {
+            return Err((http::StatusCode::BAD_REQUEST, "invalid_request"));
+        }
+
+        let code = hex::encode(rand::random::<[u8; 16]>());
+        self.codes.lock().expect("Failed to lock the codes").insert(
+            code.clone(),
+            MockAuthorizationCode {
+                redirect_uri: redirect_uri.to_owned(),
+                code_challenge: code_challenge.to_owned(),
+                nonce: param("nonce").map(ToOwned::to_owned),
+            },
+        );
+
+        let mut location = url::Url::parse(redirect_uri)
+            .map_err(|_| (http::StatusCode::BAD_REQUEST, "invalid_request"))?;
+        location.query_pairs_mut().append_pair("code", &code);
+        if let Some(state) = param("state") {
+            location.query_pairs_mut().append_pair("state", state);
+        }
+        Ok(location.into())
+    }
+
+    fn token(
+        &self,
+        body: &[u8],
+    ) -> std::result::Result<serde_json::Value, (http::StatusCode, &'static str)> {
+        let params: std::collections::HashMap<String, String> =
+            form_urlencoded::parse(body).into_owned().collect();
+        let param = |name: &str| params.get(name).map(String::as_str);
+
+        if param("grant_type") != Some("authorization_code") {
+            return Err((http::StatusCode::BAD_REQUEST, "unsupported_grant_type"));
+        }
+        if param("client_id") != Some(MockOAuthProvider::CLIENT_ID)
+            || param("client_secret") != Some(MockOAuthProvider::CLIENT_SECRET)
+        {
+            return Err((http::StatusCode::UNAUTHORIZED, "invalid_client"));
+        }
+        // the codes are single-use
+        let code = param("code")
+            .and_then(|code| {
+                self.codes
+                    .lock()
+                    .expect("Failed to lock the codes")
+                    .remove(code)
+            })
+            .ok_or((http::StatusCode::BAD_REQUEST, "invalid_grant"))?;
+        let code_verifier = param("code_verifier").unwrap_or_default();
+        if param("redirect_uri") != Some(code.redirect_uri.as_str())
+            || crate::auth::oauth::code_challenge(code_verifier) != code.code_challenge
+        {
+            return Err((http::StatusCode::BAD_REQUEST, "invalid_grant"));
+        }
+
+        let access_token = hex::encode(rand::random::<[u8; 16]>());
+        self.access_tokens
+            .lock()
+            .expect("Failed to lock the access tokens")
+            .insert(access_token.clone());
+
+        let now = chrono::Utc::now().timestamp();
+        let mut claims = self.user.claims();
+        claims.insert("iss".to_owned(), self.issuer.clone().into());
+        claims.insert("aud".to_owned(), MockOAuthProvider::CLIENT_ID.into());
+        claims.insert("iat".to_owned(), now.into());
+        claims.insert("exp".to_owned(), (now + 300).into());
+        if let Some(nonce) = code.nonce {
+            claims.insert("nonce".to_owned(), nonce.into());
+        }
+        let id_token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(MockOAuthProvider::CLIENT_SECRET.as_bytes()),
+        )
+        .expect("Failed to encode the ID token");
+
+        Ok(serde_json::json!({
+            "access_token": access_token,
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "id_token": id_token,
+        }))
+    }
+}
+
+#[cfg(feature = "oauth")]
+fn mock_json_response(value: &serde_json::Value) -> axum::response::Response {
+    http::Response::builder()
+        .header(http::header::CONTENT_TYPE, "application/json")
+        .body(axum::body::Body::from(value.to_string()))
+        .expect("Failed to build the response")
+}
+
 /// A guard for running tests serially.
 ///
 /// This is mostly useful for tests that need to modify some global state (e.g.
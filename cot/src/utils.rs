@@ -2,3 +2,4 @@ pub(crate) mod accept_header_parser;
 pub(crate) mod chrono;
 #[cfg(feature = "db")]
 pub(crate) mod graph;
+pub(crate) mod random;
@@ -0,0 +1,29 @@
+use rand::Rng;
+
+/// Generates a random, URL-safe token suitable for security-sensitive use
+/// (e.g. CSRF tokens or password reset tokens).
+///
+/// The token is the hex representation of `N` random bytes generated by a
+/// cryptographically secure random number generator.
+#[must_use]
+pub(crate) fn random_token<const N: usize>() -> String {
+    let mut bytes = [0u8; N];
+    rand::rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_token_length() {
+        assert_eq!(random_token::<32>().len(), 64);
+        assert_eq!(random_token::<16>().len(), 32);
+    }
+
+    #[test]
+    fn random_token_unique() {
+        assert_ne!(random_token::<32>(), random_token::<32>());
+    }
+}
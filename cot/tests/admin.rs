@@ -1,16 +1,25 @@
 use std::error::Error;
+use std::fmt::Display;
 
 use async_trait::async_trait;
-use cot::admin::AdminApp;
+use chrono::{DateTime, FixedOffset};
+use cot::admin::log::{LogEntry, LogEntryAction};
+use cot::admin::{AdminApp, AdminModel, AdminModelManager, DefaultAdminModelManager, SaveResult};
 use cot::auth::db::{DatabaseUser, DatabaseUserApp};
 use cot::cli::CliMetadata;
+use cot::common_types::Password;
 use cot::config::{
     AuthBackendConfig, DatabaseConfig, MiddlewareConfig, ProjectConfig, SessionMiddlewareConfig,
 };
-use cot::middleware::{AuthMiddleware, SessionMiddleware};
+use cot::db::migrations::{
+    Field, Migration, MigrationDependency, MigrationEngine, Operation, SyncDynMigration,
+};
+use cot::db::{Auto, DatabaseField, Identifier, Model, model};
+use cot::form::{Form, FormErrorTarget, FormFieldValidationError};
+use cot::middleware::{AuthMiddleware, CsrfMiddleware, SessionMiddleware};
 use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler};
 use cot::static_files::StaticFilesMiddleware;
-use cot::test::{TestServer, TestServerBuilder};
+use cot::test::{TestDatabase, TestRequestBuilder, TestServer, TestServerBuilder};
 use cot::{App, AppBuilder, Project, ProjectContext};
 use fantoccini::{Client, ClientBuilder, Locator};
 
@@ -26,7 +35,8 @@ impl App for HelloApp {
     }
 
     async fn init(&self, context: &mut ProjectContext) -> cot::Result<()> {
-        DatabaseUser::create_user(context.database(), DEFAULT_USERNAME, DEFAULT_PASSWORD).await?;
+        DatabaseUser::create_superuser(context.database(), DEFAULT_USERNAME, DEFAULT_PASSWORD)
+            .await?;
         Ok(())
     }
 }
@@ -65,6 +75,7 @@ impl Project for AdminProject {
         handler
             .middleware(StaticFilesMiddleware::from_context(context))
             .middleware(AuthMiddleware::new())
+            .middleware(CsrfMiddleware::new())
             .middleware(SessionMiddleware::from_context(context))
             .build()
     }
@@ -136,6 +147,713 @@ async fn admin_e2e_change_password() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[cot_macros::dbtest]
+async fn admin_log_entries(test_db: &mut TestDatabase) {
+    test_db
+        .with_auth()
+        .add_migrations(cot::admin::log::migrations::MIGRATIONS.to_vec())
+        .run_migrations()
+        .await;
+    let user = DatabaseUser::create_user(&**test_db, "admin", &Password::new("password123"))
+        .await
+        .unwrap();
+
+    LogEntry::record(
+        &**test_db,
+        &user,
+        "article",
+        "1",
+        "First article",
+        LogEntryAction::Create,
+        ["title", "body"],
+    )
+    .await
+    .unwrap();
+    LogEntry::record(
+        &**test_db,
+        &user,
+        "article",
+        "1",
+        "First article (edited)",
+        LogEntryAction::Change,
+        ["title"],
+    )
+    .await
+    .unwrap();
+    LogEntry::record(
+        &**test_db,
+        &user,
+        "article",
+        "2",
+        "Second article",
+        LogEntryAction::Delete,
+        Vec::<String>::new(),
+    )
+    .await
+    .unwrap();
+    LogEntry::record(
+        &**test_db,
+        &user,
+        "comment",
+        "1",
+        "A comment",
+        LogEntryAction::Create,
+        ["text"],
+    )
+    .await
+    .unwrap();
+
+    let entries = LogEntry::for_object(&**test_db, "article", "1")
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].action(), LogEntryAction::Change);
+    assert_eq!(entries[0].object_repr(), "First article (edited)");
+    assert_eq!(entries[0].changed_fields().collect::<Vec<_>>(), ["title"]);
+    assert_eq!(entries[0].username(), Some("admin"));
+    assert_eq!(entries[0].user_id(), Some(user.id().to_string().as_str()));
+    assert_eq!(entries[1].action(), LogEntryAction::Create);
+    assert_eq!(
+        entries[1].changed_fields().collect::<Vec<_>>(),
+        ["title", "body"]
+    );
+
+    let recent = LogEntry::recent(&**test_db, &["article"], 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        recent
+            .iter()
+            .map(|entry| (entry.object_id(), entry.action()))
+            .collect::<Vec<_>>(),
+        [
+            ("2", LogEntryAction::Delete),
+            ("1", LogEntryAction::Change),
+            ("1", LogEntryAction::Create),
+        ]
+    );
+    let recent = LogEntry::recent(&**test_db, &["article", "comment"], 2)
+        .await
+        .unwrap();
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[0].model_name(), "comment");
+    assert!(
+        LogEntry::recent(&**test_db, &[], 10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[derive(Debug, Form, AdminModel)]
+#[admin(readonly = [created_at], exclude = [notes])]
+#[model]
+struct Article {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    title: String,
+    created_at: DateTime<FixedOffset>,
+    notes: Option<String>,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+struct CreateArticle;
+
+impl Migration for CreateArticle {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0001_initial";
+    const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+        .table_name(<Article as Model>::TABLE_NAME)
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("created_at"),
+                <DateTime<FixedOffset> as DatabaseField>::TYPE,
+            ),
+            Field::new(
+                Identifier::new("notes"),
+                <Option<String> as DatabaseField>::TYPE,
+            )
+            .set_null(<Option<String> as DatabaseField>::NULLABLE),
+        ])
+        .build()];
+}
+
+#[cot_macros::dbtest]
+async fn admin_save_non_editable_fields(test_db: &mut TestDatabase) {
+    test_db
+        .add_migrations([CreateArticle])
+        .run_migrations()
+        .await;
+    let manager = DefaultAdminModelManager::<Article>::new();
+    // the seconds and the offset are not preserved by the form field, so the value
+    // would change if it was passed through the form
+    let created_at = DateTime::parse_from_rfc3339("2024-01-01T12:34:56+02:00").unwrap();
+    let mut article = Article {
+        id: Auto::auto(),
+        title: "Title".to_owned(),
+        created_at,
+        notes: Some("Secret".to_owned()),
+    };
+    article.insert(&**test_db).await.unwrap();
+    let article_id = article.id.to_string();
+
+    let mut request = TestRequestBuilder::post("/")
+        .database(test_db.database())
+        .form_data(&[
+            ("title", "New title"),
+            ("created_at", "2000-01-01T00:00"),
+            ("notes", "Overwritten"),
+        ])
+        .build();
+    let result = manager
+        .save_from_request(&mut request, Some(&article_id), &["created_at", "notes"])
+        .await
+        .unwrap();
+    assert!(matches!(result, SaveResult::Saved(_)));
+
+    let article = Article::objects().all(&**test_db).await.unwrap().remove(0);
+    assert_eq!(article.title, "New title");
+    assert_eq!(article.created_at, created_at);
+    assert_eq!(article.notes.as_deref(), Some("Secret"));
+
+    // the non-editable fields are left empty when creating a new object
+    let mut request = TestRequestBuilder::post("/")
+        .database(test_db.database())
+        .form_data(&[("title", "Another"), ("created_at", "2000-01-01T00:00")])
+        .build();
+    let result = manager
+        .save_from_request(&mut request, None, &["created_at"])
+        .await
+        .unwrap();
+    let SaveResult::Invalid(context) = result else {
+        panic!("Expected a validation error");
+    };
+    assert_eq!(
+        context.errors_for(FormErrorTarget::Field("created_at")),
+        &[FormFieldValidationError::Required]
+    );
+}
+
+#[cot_macros::dbtest]
+async fn admin_remove_by_ids(test_db: &mut TestDatabase) {
+    test_db
+        .add_migrations([CreateArticle])
+        .run_migrations()
+        .await;
+    let manager = DefaultAdminModelManager::<Article>::new();
+    let mut ids = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let mut article = Article {
+            id: Auto::auto(),
+            title: title.to_owned(),
+            created_at: DateTime::parse_from_rfc3339("2024-01-01T12:00:00+00:00").unwrap(),
+            notes: None,
+        };
+        article.insert(&**test_db).await.unwrap();
+        ids.push(article.id.to_string());
+    }
+
+    let mut request = TestRequestBuilder::post("/")
+        .database(test_db.database())
+        .build();
+    // nothing is removed if any of the IDs is invalid
+    let error = manager
+        .remove_by_ids(&mut request, &[ids[0].clone(), "invalid".to_owned()])
+        .await
+        .unwrap_err();
+    assert_eq!(error.status_code(), cot::StatusCode::NOT_FOUND);
+    assert_eq!(Article::objects().count(test_db).await.unwrap(), 3);
+
+    manager
+        .remove_by_ids(&mut request, &[ids[0].clone(), ids[2].clone()])
+        .await
+        .unwrap();
+    let articles = Article::objects().all(&**test_db).await.unwrap();
+    assert_eq!(
+        articles
+            .iter()
+            .map(|article| article.title.as_str())
+            .collect::<Vec<_>>(),
+        ["Second"]
+    );
+}
+
+#[derive(Debug, Form, AdminModel)]
+#[model]
+struct Note {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    text: String,
+    #[model(version)]
+    version: i64,
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+struct CreateNote;
+
+impl Migration for CreateNote {
+    const APP_NAME: &'static str = "cot";
+    const MIGRATION_NAME: &'static str = "m_0001_initial";
+    const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+        .table_name(<Note as Model>::TABLE_NAME)
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("text"), <String as DatabaseField>::TYPE),
+            Field::new(Identifier::new("version"), <i64 as DatabaseField>::TYPE),
+        ])
+        .build()];
+}
+
+#[cot_macros::dbtest]
+async fn admin_save_stale_object(test_db: &mut TestDatabase) {
+    test_db.add_migrations([CreateNote]).run_migrations().await;
+    let manager = DefaultAdminModelManager::<Note>::new();
+    assert_eq!(manager.version_field(), Some("version"));
+
+    let mut note = Note {
+        id: Auto::auto(),
+        text: "Text".to_owned(),
+        version: 0,
+    };
+    note.insert(&**test_db).await.unwrap();
+    let note_id = note.id.to_string();
+
+    let mut request = TestRequestBuilder::post("/")
+        .database(test_db.database())
+        .form_data(&[("text", "First"), ("version", "0")])
+        .build();
+    let result = manager
+        .save_from_request(&mut request, Some(&note_id), &[])
+        .await
+        .unwrap();
+    assert!(matches!(result, SaveResult::Saved(_)));
+
+    // the form was opened before the first change was saved
+    let mut request = TestRequestBuilder::post("/")
+        .database(test_db.database())
+        .form_data(&[("text", "Second"), ("version", "0")])
+        .build();
+    let result = manager
+        .save_from_request(&mut request, Some(&note_id), &[])
+        .await
+        .unwrap();
+    let SaveResult::Invalid(context) = result else {
+        panic!("Expected a validation error");
+    };
+    assert_eq!(context.errors_for(FormErrorTarget::Form).len(), 1);
+
+    let note = Note::objects().all(&**test_db).await.unwrap().remove(0);
+    assert_eq!(note.text, "First");
+    assert_eq!(note.version, 1);
+}
+
+#[derive(Debug, Form, AdminModel)]
+#[model(soft_delete)]
+struct Post {
+    #[model(primary_key)]
+    id: Auto<i64>,
+    title: String,
+}
+
+impl Display for Post {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.title)
+    }
+}
+
+struct CreatePost;
+
+impl Migration for CreatePost {
+    const APP_NAME: &'static str = "posts";
+    const MIGRATION_NAME: &'static str = "m_0001_initial";
+    const DEPENDENCIES: &'static [MigrationDependency] = &[];
+    const OPERATIONS: &'static [Operation] = &[Operation::create_model()
+        .table_name(<Post as Model>::TABLE_NAME)
+        .fields(&[
+            Field::new(Identifier::new("id"), <Auto<i64> as DatabaseField>::TYPE)
+                .primary_key()
+                .auto(),
+            Field::new(Identifier::new("title"), <String as DatabaseField>::TYPE),
+            Field::new(
+                Identifier::new("deleted_at"),
+                <Option<DateTime<FixedOffset>> as DatabaseField>::TYPE,
+            )
+            .set_null(true),
+        ])
+        .build()];
+}
+
+struct PostApp;
+
+impl App for PostApp {
+    fn name(&self) -> &'static str {
+        "posts"
+    }
+
+    fn admin_model_managers(&self) -> Vec<Box<dyn AdminModelManager>> {
+        vec![Box::new(DefaultAdminModelManager::<Post>::new())]
+    }
+
+    fn migrations(&self) -> Vec<Box<SyncDynMigration>> {
+        cot::db::migrations::wrap_migrations(&[&CreatePost])
+    }
+}
+
+struct PostProject {
+    database_url: String,
+}
+
+impl Project for PostProject {
+    fn config(&self, _config_name: &str) -> cot::Result<ProjectConfig> {
+        Ok(ProjectConfig::builder()
+            .debug(true)
+            .database(
+                DatabaseConfig::builder()
+                    .url(self.database_url.clone())
+                    .build(),
+            )
+            .auth_backend(AuthBackendConfig::Database)
+            .middlewares(
+                MiddlewareConfig::builder()
+                    .session(SessionMiddlewareConfig::builder().secure(false).build())
+                    .build(),
+            )
+            .build())
+    }
+
+    fn register_apps(&self, apps: &mut AppBuilder, _context: &RegisterAppsContext) {
+        apps.register(DatabaseUserApp::new());
+        apps.register_with_views(AdminApp::new(), "/admin");
+        apps.register(PostApp);
+    }
+
+    fn middlewares(
+        &self,
+        handler: cot::project::RootHandlerBuilder,
+        context: &MiddlewareContext,
+    ) -> RootHandler {
+        handler
+            .middleware(StaticFilesMiddleware::from_context(context))
+            .middleware(AuthMiddleware::new())
+            // the forms are sent without the CSRF tokens
+            .middleware(CsrfMiddleware::new().exempt("/admin/"))
+            .middleware(SessionMiddleware::from_context(context))
+            .build()
+    }
+}
+
+/// Sends a form to the admin panel using the session cookie, updating it if
+/// the response sets a new one.
+async fn post_form(
+    client: &mut cot::test::Client,
+    cookie: &mut Option<String>,
+    url: &str,
+    form_data: &[(&str, &str)],
+) -> cot::response::Response {
+    let response = send_form(client, cookie, url, form_data).await;
+    assert_eq!(response.status(), cot::StatusCode::SEE_OTHER, "POST {url}");
+    response
+}
+
+async fn send_form(
+    client: &mut cot::test::Client,
+    cookie: &mut Option<String>,
+    url: &str,
+    form_data: &[(&str, &str)],
+) -> cot::response::Response {
+    let mut request = TestRequestBuilder::post(url).form_data(form_data).build();
+    if let Some(cookie) = cookie {
+        request
+            .headers_mut()
+            .insert(http::header::COOKIE, cookie.parse().unwrap());
+    }
+
+    let response = client.request(request).await.unwrap();
+    if let Some(set_cookie) = response.headers().get(http::header::SET_COOKIE) {
+        let set_cookie = set_cookie.to_str().unwrap();
+        *cookie = Some(set_cookie.split(';').next().unwrap().to_owned());
+    }
+    response
+}
+
+#[cot::test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+)]
+async fn admin_views_record_log_entries() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let database_url = format!(
+        "sqlite://{}?mode=rwc",
+        temp_dir.path().join("db.sqlite3").display()
+    );
+    // the test client doesn't run the migrations, nor initializes the apps
+    let db = cot::db::Database::new(database_url.clone()).await.unwrap();
+    let migrations = [
+        DatabaseUserApp::new().migrations(),
+        AdminApp::new().migrations(),
+        PostApp.migrations(),
+    ];
+    MigrationEngine::new(migrations.into_iter().flatten())
+        .unwrap()
+        .run(&db)
+        .await
+        .unwrap();
+    DatabaseUser::create_superuser(&db, DEFAULT_USERNAME, DEFAULT_PASSWORD)
+        .await
+        .unwrap();
+    for title in ["First", "Second", "Third"] {
+        Post {
+            id: Auto::auto(),
+            title: title.to_owned(),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+
+    let mut client = cot::test::Client::new(PostProject {
+        database_url: database_url.clone(),
+    })
+    .await;
+
+    let mut cookie = None;
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/login/",
+        &[
+            ("username", DEFAULT_USERNAME),
+            ("password", DEFAULT_PASSWORD),
+        ],
+    )
+    .await;
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/post/1/edit/",
+        &[("title", "First (edited)")],
+    )
+    .await;
+    post_form(&mut client, &mut cookie, "/admin/post/1/remove/", &[]).await;
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/post/actions/",
+        &[
+            ("action", "delete_selected"),
+            ("ids", "2"),
+            ("ids", "3"),
+            ("confirm", "true"),
+        ],
+    )
+    .await;
+    post_form(&mut client, &mut cookie, "/admin/post/1/restore/", &[]).await;
+
+    let entries = LogEntry::recent(&db, &["post"], 10).await.unwrap();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.object_id(), entry.object_repr(), entry.action()))
+            .collect::<Vec<_>>(),
+        [
+            ("1", "First (edited)", LogEntryAction::Restore),
+            ("3", "Third", LogEntryAction::Delete),
+            ("2", "Second", LogEntryAction::Delete),
+            ("1", "First (edited)", LogEntryAction::Delete),
+            ("1", "First (edited)", LogEntryAction::Change),
+        ]
+    );
+    assert_eq!(entries[4].changed_fields().collect::<Vec<_>>(), ["title"]);
+    assert!(
+        entries
+            .iter()
+            .all(|entry| entry.username() == Some(DEFAULT_USERNAME))
+    );
+    db.close().await.unwrap();
+}
+
+#[cot::test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+)]
+async fn admin_model_action_selected_ids() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let database_url = format!(
+        "sqlite://{}?mode=rwc",
+        temp_dir.path().join("db.sqlite3").display()
+    );
+    let db = cot::db::Database::new(database_url.clone()).await.unwrap();
+    let migrations = [
+        DatabaseUserApp::new().migrations(),
+        AdminApp::new().migrations(),
+        PostApp.migrations(),
+    ];
+    MigrationEngine::new(migrations.into_iter().flatten())
+        .unwrap()
+        .run(&db)
+        .await
+        .unwrap();
+    DatabaseUser::create_superuser(&db, DEFAULT_USERNAME, DEFAULT_PASSWORD)
+        .await
+        .unwrap();
+    Post {
+        id: Auto::auto(),
+        title: "First".to_owned(),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    let mut client = cot::test::Client::new(PostProject {
+        database_url: database_url.clone(),
+    })
+    .await;
+
+    let mut cookie = None;
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/login/",
+        &[
+            ("username", DEFAULT_USERNAME),
+            ("password", DEFAULT_PASSWORD),
+        ],
+    )
+    .await;
+
+    // nothing is removed if any of the objects doesn't exist
+    let response = send_form(
+        &mut client,
+        &mut cookie,
+        "/admin/post/actions/",
+        &[
+            ("action", "delete_selected"),
+            ("ids", "1"),
+            ("ids", "2"),
+            ("confirm", "true"),
+        ],
+    )
+    .await;
+    assert_eq!(response.status(), cot::StatusCode::NOT_FOUND);
+    assert!(
+        Post::get_by_primary_key(&db, Auto::fixed(1))
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let ids: Vec<_> = (1..=1001).map(|id| id.to_string()).collect();
+    let form_data: Vec<_> = std::iter::once(("action", "delete_selected"))
+        .chain(ids.iter().map(|id| ("ids", id.as_str())))
+        .collect();
+    let response = send_form(&mut client, &mut cookie, "/admin/post/actions/", &form_data).await;
+    assert_eq!(response.status(), cot::StatusCode::BAD_REQUEST);
+
+    // the repeated IDs are only handled once
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/post/actions/",
+        &[
+            ("action", "delete_selected"),
+            ("ids", "1"),
+            ("ids", "1"),
+            ("confirm", "true"),
+        ],
+    )
+    .await;
+    assert!(
+        Post::get_by_primary_key(&db, Auto::fixed(1))
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let entries = LogEntry::recent(&db, &["post"], 10).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    db.close().await.unwrap();
+}
+
+#[cot::test]
+#[cfg_attr(
+    miri,
+    ignore = "unsupported operation: can't call foreign function `sqlite3_open_v2`"
+)]
+async fn admin_existing_users_keep_access_after_migration() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let database_url = format!(
+        "sqlite://{}?mode=rwc",
+        temp_dir.path().join("db.sqlite3").display()
+    );
+    let db = cot::db::Database::new(database_url.clone()).await.unwrap();
+    // create a user before the superuser and staff flags were introduced
+    MigrationEngine::new(DatabaseUserApp::new().migrations().into_iter().take(1))
+        .unwrap()
+        .run(&db)
+        .await
+        .unwrap();
+    let password_hash = cot::auth::PasswordHash::from_password(&Password::new(DEFAULT_PASSWORD));
+    db.raw_with(
+        "INSERT INTO cot__database_user (username, password) VALUES (?, ?)",
+        &[&DEFAULT_USERNAME, &password_hash],
+    )
+    .await
+    .unwrap();
+    let migrations = [
+        DatabaseUserApp::new().migrations(),
+        AdminApp::new().migrations(),
+        PostApp.migrations(),
+    ];
+    MigrationEngine::new(migrations.into_iter().flatten())
+        .unwrap()
+        .run(&db)
+        .await
+        .unwrap();
+
+    let mut client = cot::test::Client::new(PostProject {
+        database_url: database_url.clone(),
+    })
+    .await;
+
+    let mut cookie = None;
+    post_form(
+        &mut client,
+        &mut cookie,
+        "/admin/login/",
+        &[
+            ("username", DEFAULT_USERNAME),
+            ("password", DEFAULT_PASSWORD),
+        ],
+    )
+    .await;
+    let mut request = TestRequestBuilder::get("/admin/").build();
+    request
+        .headers_mut()
+        .insert(http::header::COOKIE, cookie.unwrap().parse().unwrap());
+    let response = client.request(request).await.unwrap();
+    assert_eq!(response.status(), cot::StatusCode::OK);
+    db.close().await.unwrap();
+}
+
 async fn login(server: &TestServer<AdminProject>, driver: &Client) -> Result<(), Box<dyn Error>> {
     login_with(server, driver, DEFAULT_USERNAME, DEFAULT_PASSWORD).await
 }
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 
 use cot::auth::Auth;
-use cot::auth::db::{DatabaseUser, DatabaseUserCredentials};
+use cot::auth::db::{DatabaseUser, DatabaseUserCredentials, Group, Permission};
 use cot::common_types::Password;
 use cot::request::RequestExt;
 use cot::test::{TestDatabase, TestRequestBuilder};
@@ -66,3 +66,82 @@ async fn database_user(test_db: &mut TestDatabase) {
     auth.logout().await.unwrap();
     assert!(!auth.user().is_authenticated());
 }
+
+#[cot_macros::dbtest]
+async fn database_user_permissions(test_db: &mut TestDatabase) {
+    test_db.with_auth().run_migrations().await;
+    let mut request_builder = TestRequestBuilder::get("/");
+    request_builder.with_db_auth(test_db.database()).await;
+
+    let user = DatabaseUser::create_user(
+        &**test_db,
+        "testuser".to_string(),
+        &Password::new("password123"),
+    )
+    .await
+    .unwrap();
+    let publish = Permission::get_or_create(&**test_db, "article.publish", "Can publish articles")
+        .await
+        .unwrap();
+    let review = Permission::get_or_create(&**test_db, "article.review", "Can review articles")
+        .await
+        .unwrap();
+    assert!(user.get_permissions(&**test_db).await.unwrap().is_empty());
+
+    // Direct permission
+    user.add_permission(&**test_db, &publish).await.unwrap();
+    user.add_permission(&**test_db, &publish).await.unwrap();
+    let permissions = user.get_permissions(&**test_db).await.unwrap();
+    assert_eq!(permissions.len(), 1);
+    assert!(permissions.contains("article.publish"));
+
+    // Group permission
+    let group = Group::create(&**test_db, "reviewers").await.unwrap();
+    group.add_permission(&**test_db, &review).await.unwrap();
+    user.add_to_group(&**test_db, &group).await.unwrap();
+    let permissions = user.get_permissions(&**test_db).await.unwrap();
+    assert_eq!(permissions.len(), 2);
+    assert!(permissions.contains("article.review"));
+    assert_eq!(user.get_groups(&**test_db).await.unwrap().len(), 1);
+
+    // Permissions are available on the logged in user once loaded
+    let mut request = request_builder.clone().with_session().build();
+    let auth: Auth = request.extract_from_head().await.unwrap();
+    let authenticated = auth
+        .authenticate(&DatabaseUserCredentials::new(
+            "testuser".to_string(),
+            Password::new("password123"),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!authenticated.has_perm("article.publish"));
+    auth.login(authenticated).await.unwrap();
+    auth.load_permissions().await.unwrap();
+    let authenticated = auth.user();
+    assert!(authenticated.has_perm("article.publish"));
+    assert!(authenticated.has_perm("article.review"));
+    assert!(!authenticated.has_perm("article.delete"));
+    assert!(!authenticated.is_superuser());
+
+    // Revoking permissions
+    user.remove_permission(&**test_db, &publish).await.unwrap();
+    user.remove_from_group(&**test_db, &group).await.unwrap();
+    assert!(user.get_permissions(&**test_db).await.unwrap().is_empty());
+
+    // Superusers have all the permissions
+    DatabaseUser::create_superuser(&**test_db, "admin", &Password::new("admin"))
+        .await
+        .unwrap();
+    let admin = auth
+        .authenticate(&DatabaseUserCredentials::new(
+            "admin".to_string(),
+            Password::new("admin"),
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(admin.is_superuser());
+    assert!(admin.is_staff());
+    assert!(admin.has_perm("article.delete"));
+}
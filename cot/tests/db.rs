@@ -2,7 +2,7 @@
 #![cfg_attr(miri, ignore)]
 
 use cot::db::migrations::{Field, Operation};
-use cot::db::query::ExprEq;
+use cot::db::query::{Expr, ExprEq, Order};
 use cot::db::{
     Auto, Database, DatabaseError, DatabaseField, ForeignKey, ForeignKeyOnDeletePolicy,
     ForeignKeyOnUpdatePolicy, Identifier, LimitedString, Model, model, query,
@@ -160,6 +160,54 @@ async fn model_macro_filtering(test_db: &mut TestDatabase) {
     assert!(objects.is_empty());
 }
 
+#[cot_macros::dbtest]
+async fn model_ordering_and_search(test_db: &mut TestDatabase) {
+    migrate_test_model(&*test_db).await;
+
+    for name in ["Charlie", "alice", "Bob", "100% off"] {
+        let mut model = TestModel {
+            id: Auto::auto(),
+            name: name.to_owned(),
+        };
+        model.save(&**test_db).await.unwrap();
+    }
+
+    let names = |objects: Vec<TestModel>| {
+        objects
+            .into_iter()
+            .map(|object| object.name)
+            .collect::<Vec<_>>()
+    };
+
+    let objects = TestModel::objects()
+        .order_by(<TestModel as Model>::Fields::id.as_expr(), Order::Desc)
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(names(objects), ["100% off", "Bob", "alice", "Charlie"]);
+
+    let objects = TestModel::objects()
+        .filter(Expr::icontains(
+            <TestModel as Model>::Fields::name.as_expr(),
+            "LI",
+        ))
+        .order_by(<TestModel as Model>::Fields::id.as_expr(), Order::Asc)
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(names(objects), ["Charlie", "alice"]);
+
+    let objects = TestModel::objects()
+        .filter(Expr::icontains(
+            <TestModel as Model>::Fields::name.as_expr(),
+            "0%",
+        ))
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(names(objects), ["100% off"]);
+}
+
 async fn migrate_test_model(db: &Database) {
     CREATE_TEST_MODEL.forwards(db).await.unwrap();
 }
@@ -932,3 +980,242 @@ async fn bulk_insert_with_fixed_pk(test_db: &mut TestDatabase) {
         .unwrap();
     assert_eq!(model300.name, "test300");
 }
+
+#[derive(Debug, PartialEq)]
+#[model(soft_delete)]
+struct SoftDeleteModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+}
+
+const CREATE_SOFT_DELETE_MODEL: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__soft_delete_model"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        Field::new(
+            Identifier::new("deleted_at"),
+            <Option<chrono::DateTime<chrono::FixedOffset>> as DatabaseField>::TYPE,
+        )
+        .set_null(true),
+    ])
+    .build();
+
+#[cot_macros::dbtest]
+async fn soft_delete(test_db: &mut TestDatabase) {
+    CREATE_SOFT_DELETE_MODEL.forwards(test_db).await.unwrap();
+
+    for name in ["alice", "bob"] {
+        let mut model = SoftDeleteModel {
+            id: Auto::auto(),
+            name: name.to_owned(),
+        };
+        model.save(&**test_db).await.unwrap();
+    }
+
+    let result = query!(SoftDeleteModel, $name == "alice")
+        .delete(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 1);
+
+    let objects = SoftDeleteModel::objects().all(&**test_db).await.unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].name, "bob");
+    assert_eq!(SoftDeleteModel::objects().count(test_db).await.unwrap(), 1);
+    assert!(
+        !query!(SoftDeleteModel, $name == "alice")
+            .exists(&**test_db)
+            .await
+            .unwrap()
+    );
+
+    let deleted = SoftDeleteModel::objects()
+        .only_deleted()
+        .all(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].name, "alice");
+    assert_eq!(
+        SoftDeleteModel::objects()
+            .with_deleted()
+            .count(test_db)
+            .await
+            .unwrap(),
+        2
+    );
+
+    // deleting an already deleted row is a no-op
+    let result = query!(SoftDeleteModel, $name == "alice")
+        .delete(&**test_db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 0);
+
+    let result = query!(SoftDeleteModel, $name == "alice")
+        .restore(test_db)
+        .await
+        .unwrap();
+    assert_eq!(result.rows_affected().0, 1);
+    assert_eq!(SoftDeleteModel::objects().count(test_db).await.unwrap(), 2);
+    assert_eq!(
+        SoftDeleteModel::objects()
+            .only_deleted()
+            .count(test_db)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[cot_macros::dbtest]
+async fn restore_not_soft_deletable(test_db: &mut TestDatabase) {
+    let result = TestModel::objects().restore(test_db).await;
+
+    assert!(matches!(
+        result,
+        Err(DatabaseError::NotSoftDeletable { table_name }) if table_name == "cot__test_model"
+    ));
+}
+
+#[derive(Debug, PartialEq)]
+#[model]
+struct VersionedModel {
+    #[model(primary_key)]
+    id: Auto<i32>,
+    name: String,
+    #[model(version)]
+    version: i64,
+}
+
+const CREATE_VERSIONED_MODEL: Operation = Operation::create_model()
+    .table_name(Identifier::new("cot__versioned_model"))
+    .fields(&[
+        Field::new(Identifier::new("id"), <Auto<i32> as DatabaseField>::TYPE)
+            .primary_key()
+            .auto(),
+        Field::new(Identifier::new("name"), <String as DatabaseField>::TYPE),
+        Field::new(Identifier::new("version"), <i64 as DatabaseField>::TYPE),
+    ])
+    .build();
+
+#[cot_macros::dbtest]
+async fn optimistic_locking(test_db: &mut TestDatabase) {
+    CREATE_VERSIONED_MODEL.forwards(test_db).await.unwrap();
+
+    let mut model = VersionedModel {
+        id: Auto::auto(),
+        name: "test".to_owned(),
+        version: 0,
+    };
+    model.insert(&**test_db).await.unwrap();
+
+    let mut first = VersionedModel::get_by_primary_key(&**test_db, model.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut second = VersionedModel::get_by_primary_key(&**test_db, model.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    first.name = "first".to_owned();
+    first.update(&**test_db).await.unwrap();
+    assert_eq!(first.version, 1);
+
+    second.name = "second".to_owned();
+    let result = second.update(&**test_db).await;
+    assert!(matches!(result, Err(DatabaseError::StaleObject { .. })));
+    assert_eq!(second.version, 0);
+
+    let stored = VersionedModel::get_by_primary_key(&**test_db, model.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.name, "first");
+    assert_eq!(stored.version, 1);
+}
+
+#[cot_macros::dbtest]
+async fn optimistic_locking_save(test_db: &mut TestDatabase) {
+    CREATE_VERSIONED_MODEL.forwards(test_db).await.unwrap();
+
+    let mut model = VersionedModel {
+        id: Auto::auto(),
+        name: "test".to_owned(),
+        version: 0,
+    };
+    model.save(&**test_db).await.unwrap();
+    assert_eq!(model.version, 0);
+
+    let mut second = VersionedModel::get_by_primary_key(&**test_db, model.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    model.name = "first".to_owned();
+    model.save(&**test_db).await.unwrap();
+    assert_eq!(model.version, 1);
+
+    second.name = "second".to_owned();
+    let result = second.save(&**test_db).await;
+    assert!(matches!(result, Err(DatabaseError::StaleObject { .. })));
+
+    let mut with_id = VersionedModel {
+        id: Auto::fixed(100),
+        name: "new".to_owned(),
+        version: 0,
+    };
+    with_id.save(&**test_db).await.unwrap();
+
+    let stored = VersionedModel::get_by_primary_key(&**test_db, model.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.name, "first");
+    assert_eq!(stored.version, 1);
+    let stored = VersionedModel::get_by_primary_key(&**test_db, Auto::fixed(100))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.name, "new");
+}
+
+#[cot_macros::dbtest]
+async fn optimistic_locking_save_concurrent_insert(test_db: &mut TestDatabase) {
+    CREATE_VERSIONED_MODEL.forwards(test_db).await.unwrap();
+
+    // Two clients both see no row with ID 100 and try to create it
+    let mut first = VersionedModel {
+        id: Auto::fixed(100),
+        name: "first".to_owned(),
+        version: 0,
+    };
+    let mut second = VersionedModel {
+        id: Auto::fixed(100),
+        name: "second".to_owned(),
+        version: 0,
+    };
+
+    first.save(&**test_db).await.unwrap();
+    first.name = "first updated".to_owned();
+    first.save(&**test_db).await.unwrap();
+    assert_eq!(first.version, 1);
+
+    // The second client's insert collides with the row created in the meantime
+    // and must not overwrite it
+    let result = second.save(&**test_db).await;
+    assert!(matches!(result, Err(DatabaseError::StaleObject { .. })));
+    assert_eq!(second.version, 0);
+
+    let stored = VersionedModel::get_by_primary_key(&**test_db, Auto::fixed(100))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.name, "first updated");
+    assert_eq!(stored.version, 1);
+}
@@ -1,8 +1,8 @@
-use cot::db::{Auto, ForeignKey};
+use cot::db::{Auto, ForeignKey, Model};
 use cot::form::fields::{SelectChoice, SelectField};
 use cot::form::{
     AsFormField, Form, FormContext, FormErrorTarget, FormField, FormFieldValidationError,
-    FormResult,
+    FormResult, FormSet, FormSetChange, FormSetResult,
 };
 use cot::test::TestRequestBuilder;
 use cot_macros::model;
@@ -113,6 +113,12 @@ async fn foreign_key_field() {
     let form_rendered = context.to_string();
     assert!(form_rendered.contains("test_field"));
     assert!(form_rendered.contains("type=\"text\""));
+    assert!(form_rendered.contains("class=\"fk-autocomplete\""));
+    assert!(form_rendered.contains(&format!(
+        "data-model=\"{}\"",
+        <TestModel as Model>::TABLE_NAME.as_str()
+    )));
+    assert!(form_rendered.contains("data-target=\"test_field\""));
 
     // test form data
     let mut request = TestRequestBuilder::post("/")
@@ -275,3 +281,121 @@ async fn select_field_context_display() {
     assert!(form_rendered.contains("value=\"medium\""));
     assert!(form_rendered.contains("value=\"high\""));
 }
+
+#[cot::test]
+async fn context_with_prefix() {
+    let form = MyForm {
+        name: "Alice".to_string(),
+        address: None,
+        age: 30,
+    };
+
+    let context = form.to_context_with_prefix("people-0-").await;
+    assert_eq!(context.name.id(), "people-0-name");
+    assert_eq!(context.name.value(), Some("Alice"));
+    assert!(context.to_string().contains("name=\"people-0-age\""));
+
+    let form = MyForm::from_context(context).unwrap();
+    assert_eq!(form.name, "Alice");
+    assert_eq!(form.age, 30);
+}
+
+#[cot::test]
+async fn formset_from_request() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("people-count", "4"),
+            ("people-0-object-id", "1"),
+            ("people-0-name", "Alice"),
+            ("people-0-age", "31"),
+            ("people-1-object-id", "2"),
+            ("people-1-remove-form", "on"),
+            ("people-2-name", "Bob"),
+            ("people-2-age", "25"),
+            ("people-3-name", ""),
+            ("people-3-age", ""),
+            ("other-field", "ignored"),
+        ])
+        .build();
+
+    let formset = FormSet::<MyForm>::build_from_request(&mut request, "people")
+        .await
+        .unwrap();
+    assert_eq!(formset.forms().len(), 4);
+    assert_eq!(formset.forms()[0].object_id(), Some("1"));
+    assert!(formset.forms()[1].is_removed());
+    assert_eq!(formset.forms()[2].object_id(), None);
+    assert!(formset.forms()[3].is_empty());
+
+    let FormSetResult::Ok(changes) = formset.into_result().await else {
+        panic!("Expected the form set to be valid");
+    };
+    assert_eq!(changes.len(), 3);
+    match &changes[0] {
+        FormSetChange::Change { object_id, form } => {
+            assert_eq!(object_id, "1");
+            assert_eq!(form.name, "Alice");
+            assert_eq!(form.age, 31);
+        }
+        change => panic!("Expected a change, got {change:?}"),
+    }
+    assert!(matches!(&changes[1], FormSetChange::Remove { object_id } if object_id == "2"));
+    assert!(matches!(&changes[2], FormSetChange::Add(form) if form.name == "Bob"));
+}
+
+#[cot::test]
+async fn formset_count_limits_forms() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("people-count", "1"),
+            ("people-0-name", "Alice"),
+            ("people-0-age", "30"),
+            ("people-1-name", "Bob"),
+            ("people-1-age", "25"),
+        ])
+        .build();
+
+    let formset = FormSet::<MyForm>::build_from_request(&mut request, "people")
+        .await
+        .unwrap();
+    assert_eq!(formset.forms().len(), 1);
+}
+
+#[cot::test]
+async fn formset_validation_error() {
+    let mut request = TestRequestBuilder::post("/")
+        .form_data(&[
+            ("people-count", "3"),
+            ("people-0-object-id", "1"),
+            ("people-0-name", "Alice"),
+            ("people-0-age", "30"),
+            ("people-1-name", "Bob"),
+            ("people-1-age", "invalid"),
+        ])
+        .build();
+
+    let result = FormSet::<MyForm>::from_request(&mut request, "people")
+        .await
+        .unwrap();
+    let FormSetResult::ValidationError(formset) = result else {
+        panic!("Expected a validation error");
+    };
+    assert!(formset.has_errors());
+    assert_eq!(formset.forms().len(), 3);
+
+    let valid = formset.forms()[0].context();
+    assert_eq!(valid.name.value(), Some("Alice"));
+    assert_eq!(valid.errors_for(FormErrorTarget::Field("age")), &[]);
+    assert_eq!(formset.forms()[0].object_id(), Some("1"));
+
+    let invalid = formset.forms()[1].context();
+    assert_eq!(invalid.name.value(), Some("Bob"));
+    assert_eq!(
+        invalid.errors_for(FormErrorTarget::Field("people-1-age")),
+        &[FormFieldValidationError::InvalidValue(
+            "invalid".to_string()
+        )]
+    );
+
+    assert!(!formset.forms()[2].context().has_errors());
+}
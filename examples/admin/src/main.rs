@@ -15,13 +15,14 @@ use cot::db::migrations::SyncDynMigration;
 use cot::db::{Auto, Database, Model, model};
 use cot::form::Form;
 use cot::html::Html;
-use cot::middleware::{AuthMiddleware, LiveReloadMiddleware, SessionMiddleware};
+use cot::middleware::{AuthMiddleware, CsrfMiddleware, LiveReloadMiddleware, SessionMiddleware};
 use cot::project::{MiddlewareContext, RegisterAppsContext, RootHandler};
 use cot::router::{Route, Router, Urls};
 use cot::static_files::StaticFilesMiddleware;
 use cot::{App, AppBuilder, Project, ProjectContext, Template};
 
 #[derive(Debug, Clone, Form, AdminModel)]
+#[admin(list_display = [id, title], search_fields = [title])]
 #[model]
 struct TodoItem {
     #[model(primary_key)]
@@ -65,7 +66,7 @@ impl App for HelloApp {
         // TODO use transaction
         let user = DatabaseUser::get_by_username(context.database(), "admin").await?;
         if user.is_none() {
-            DatabaseUser::create_user(context.database(), "admin", "admin").await?;
+            DatabaseUser::create_superuser(context.database(), "admin", "admin").await?;
         }
 
         Ok(())
@@ -128,6 +129,7 @@ impl Project for AdminProject {
         handler
             .middleware(StaticFilesMiddleware::from_context(context))
             .middleware(AuthMiddleware::new())
+            .middleware(CsrfMiddleware::new())
             .middleware(SessionMiddleware::from_context(context))
             .middleware(LiveReloadMiddleware::new())
             .build()